@@ -4,6 +4,7 @@ use orders_client::{CreateOrderRequest, OrdersClient};
 use orders_hex::application::order_service::OrderService;
 use orders_hex::inbound::http::{HttpServer, HttpServerConfig};
 use orders_repo::build_repo;
+use orders_types::domain::money::Money;
 use orders_types::domain::order::{OrderItem, OrderStatus};
 use reqwest::StatusCode;
 use tempfile::tempdir;
@@ -33,6 +34,8 @@ async fn main() -> anyhow::Result<()> {
         service,
         HttpServerConfig {
             port: port.to_string(),
+            tls: None,
+            admin_addr: None,
         },
     )
     .await?;
@@ -51,8 +54,17 @@ async fn main() -> anyhow::Result<()> {
             items: vec![OrderItem {
                 name: "Widget".into(),
                 qty: 1,
-                unit_price_cents: 500,
+                unit_price: Money::usd(500),
+                weight_grams: 0,
+                sku: None,
+                description: None,
+                metadata: Default::default(),
+                discount_cents: 0,
             }],
+            discount_code: None,
+            shipping_address: None,
+            billing_address: None,
+            metadata: Default::default(),
         })
         .await?;
     println!("Created order id={}", created.id);
@@ -88,8 +100,17 @@ async fn main() -> anyhow::Result<()> {
                         items: vec![OrderItem {
                             name: "Gadget".into(),
                             qty: 1,
-                            unit_price_cents: 700,
+                            unit_price: Money::usd(700),
+                            weight_grams: 0,
+                            sku: None,
+                            description: None,
+                            metadata: Default::default(),
+                            discount_cents: 0,
                         }],
+                        discount_code: None,
+                        shipping_address: None,
+                        billing_address: None,
+                        metadata: Default::default(),
                     })
                     .await?;
                 client.delete_order(&alt.id).await?;
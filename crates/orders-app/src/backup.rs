@@ -0,0 +1,381 @@
+//! `orders-app backup` and `orders-app restore`: every order of every
+//! tenant, with its status history and fulfillments, plus the outbox, as
+//! newline-delimited JSON.
+//!
+//! A backup is a `header` line, one `order` line per order in id order, one
+//! `outbox` line per outbox record, and a closing `trailer` with the counts
+//! and the SHA-256 of every line before it. `restore` checks the trailer
+//! before writing anything, so a truncated or edited file is refused whole.
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use futures_util::stream::{self, Stream, StreamExt, TryStreamExt};
+use orders_types::domain::fulfillment::Fulfillment;
+use orders_types::domain::history::OrderHistoryEntry;
+use orders_types::domain::order::Order;
+use orders_types::domain::outbox::OutboxRecord;
+use orders_types::ports::order_repository::{OrderRepository, RepoError};
+use orders_types::ports::outbox::OutboxStore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::{BufRead, Write};
+use uuid::Uuid;
+
+/// Orders or outbox records read per repository call.
+const PAGE_SIZE: usize = 500;
+/// Progress is reported after this many orders, and once at the end.
+const PROGRESS_EVERY: u64 = 1_000;
+/// The only format `restore` reads.
+const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Line {
+    Header {
+        version: u32,
+        created_at: DateTime<Utc>,
+    },
+    Order {
+        order: Box<Order>,
+        #[serde(default)]
+        history: Vec<OrderHistoryEntry>,
+        #[serde(default)]
+        fulfillments: Vec<Fulfillment>,
+    },
+    Outbox {
+        record: Box<OutboxRecord>,
+    },
+    Trailer {
+        orders: u64,
+        outbox: u64,
+        sha256: String,
+    },
+}
+
+/// How much a backup or restore has covered so far.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub orders: u64,
+    pub outbox: u64,
+}
+
+/// Every order in the store, fetched [`PAGE_SIZE`] at a time.
+pub fn scan_orders<R: OrderRepository>(
+    repo: &R,
+) -> impl Stream<Item = Result<Order, RepoError>> + '_ {
+    // `None` once the last page was short.
+    stream::try_unfold(Some(None), move |after: Option<Option<Uuid>>| async move {
+        let Some(after) = after else {
+            return Ok(None);
+        };
+        let page = repo.scan(after, PAGE_SIZE).await?;
+        let next = (page.len() == PAGE_SIZE).then(|| page.last().map(|o| o.id));
+        Ok(Some((stream::iter(page.into_iter().map(Ok)), next)))
+    })
+    .try_flatten()
+}
+
+/// Lines written to `out`, hashed as they go.
+struct Writer<W> {
+    out: W,
+    hasher: Sha256,
+}
+
+impl<W: Write> Writer<W> {
+    fn line(&mut self, line: &Line) -> anyhow::Result<()> {
+        let mut json = serde_json::to_string(line)?;
+        json.push('\n');
+        self.hasher.update(json.as_bytes());
+        self.out.write_all(json.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Write everything in `repo`, and in `outbox` when given, to `out`.
+pub async fn backup<R: OrderRepository>(
+    repo: &R,
+    outbox: Option<&dyn OutboxStore>,
+    out: impl Write,
+    mut progress: impl FnMut(Progress),
+) -> anyhow::Result<Progress> {
+    let mut out = Writer {
+        out,
+        hasher: Sha256::new(),
+    };
+    let mut done = Progress::default();
+    out.line(&Line::Header {
+        version: FORMAT_VERSION,
+        created_at: Utc::now(),
+    })?;
+
+    let mut orders = std::pin::pin!(scan_orders(repo));
+    while let Some(order) = orders.next().await {
+        let order = order?;
+        let history = repo.status_history(&order.tenant_id, order.id).await?;
+        let fulfillments = repo.fulfillments(&order.tenant_id, order.id).await?;
+        out.line(&Line::Order {
+            order: Box::new(order),
+            history,
+            fulfillments,
+        })?;
+        done.orders += 1;
+        if done.orders % PROGRESS_EVERY == 0 {
+            progress(done);
+        }
+    }
+
+    if let Some(outbox) = outbox {
+        let mut after = 0;
+        loop {
+            let page = outbox.outbox_after(after, PAGE_SIZE).await?;
+            let Some(last) = page.last() else { break };
+            after = last.seq;
+            for record in page {
+                out.line(&Line::Outbox {
+                    record: Box::new(record),
+                })?;
+                done.outbox += 1;
+            }
+        }
+    }
+
+    let sha256 = hex::encode(out.hasher.finalize_reset());
+    out.line(&Line::Trailer {
+        orders: done.orders,
+        outbox: done.outbox,
+        sha256,
+    })?;
+    out.out.flush()?;
+    progress(done);
+    Ok(done)
+}
+
+/// Check that `input` is a complete backup whose lines match its trailer,
+/// returning what it holds.
+pub fn verify(input: impl BufRead) -> anyhow::Result<Progress> {
+    let mut hasher = Sha256::new();
+    let mut counted = Progress::default();
+    for (n, raw) in input.lines().enumerate() {
+        let raw = raw?;
+        let line: Line = serde_json::from_str(&raw).with_context(|| format!("line {}", n + 1))?;
+        match line {
+            Line::Header { version, .. } if n == 0 => {
+                anyhow::ensure!(
+                    version == FORMAT_VERSION,
+                    "backup format {version} is not supported (expected {FORMAT_VERSION})"
+                );
+            }
+            _ if n == 0 => anyhow::bail!("not a backup: the first line is not a header"),
+            Line::Header { .. } => anyhow::bail!("line {}: a second header", n + 1),
+            Line::Order { .. } => counted.orders += 1,
+            Line::Outbox { .. } => counted.outbox += 1,
+            Line::Trailer {
+                orders,
+                outbox,
+                sha256,
+            } => {
+                let actual = hex::encode(hasher.finalize_reset());
+                anyhow::ensure!(
+                    sha256 == actual,
+                    "checksum mismatch: the trailer says {sha256}, the lines hash to {actual}"
+                );
+                anyhow::ensure!(
+                    (orders, outbox) == (counted.orders, counted.outbox),
+                    "the trailer counts {orders} orders and {outbox} outbox records, the file holds {} and {}",
+                    counted.orders,
+                    counted.outbox
+                );
+                return Ok(counted);
+            }
+        }
+        hasher.update(raw.as_bytes());
+        hasher.update(b"\n");
+    }
+    anyhow::bail!("the backup is truncated: no trailer")
+}
+
+/// Load a backup into an empty store. `open` is called twice: once to
+/// [`verify`] the whole file, then again to write it.
+pub async fn restore<R, I>(
+    repo: &R,
+    outbox: Option<&dyn OutboxStore>,
+    mut open: impl FnMut() -> std::io::Result<I>,
+    mut progress: impl FnMut(Progress),
+) -> anyhow::Result<Progress>
+where
+    R: OrderRepository,
+    I: BufRead,
+{
+    let expected = verify(open()?)?;
+    anyhow::ensure!(
+        expected.outbox == 0 || outbox.is_some(),
+        "the backup holds outbox records, which only the sqlite backend can restore"
+    );
+    anyhow::ensure!(
+        repo.scan(None, 1).await?.is_empty(),
+        "restore needs an empty store, but it already holds orders"
+    );
+
+    let mut done = Progress::default();
+    let mut records = Vec::new();
+    for raw in open()?.lines() {
+        match serde_json::from_str(&raw?)? {
+            Line::Order {
+                order,
+                history,
+                fulfillments,
+            } => {
+                let (tenant, id) = (order.tenant_id.clone(), order.id);
+                let mut unit = repo.begin().await?;
+                unit.create(*order).await?;
+                for entry in history {
+                    unit.record_transition(&tenant, id, entry).await?;
+                }
+                unit.commit().await?;
+                for fulfillment in fulfillments {
+                    repo.record_fulfillment(&tenant, id, fulfillment).await?;
+                }
+                done.orders += 1;
+                if done.orders % PROGRESS_EVERY == 0 {
+                    progress(done);
+                }
+            }
+            Line::Outbox { record } => records.push(*record),
+            Line::Header { .. } => {}
+            // Only what the checksum covers is restored.
+            Line::Trailer { .. } => break,
+        }
+        if records.len() == PAGE_SIZE {
+            flush_outbox(outbox, &mut records, &mut done).await?;
+        }
+    }
+    flush_outbox(outbox, &mut records, &mut done).await?;
+    progress(done);
+    Ok(done)
+}
+
+async fn flush_outbox(
+    outbox: Option<&dyn OutboxStore>,
+    records: &mut Vec<OutboxRecord>,
+    done: &mut Progress,
+) -> anyhow::Result<()> {
+    if let (Some(outbox), false) = (outbox, records.is_empty()) {
+        outbox.restore_outbox(records).await?;
+        done.outbox += records.len() as u64;
+        records.clear();
+    }
+    Ok(())
+}
+
+#[cfg(all(test, feature = "memory"))]
+mod tests {
+    use super::*;
+    use crate::seed::seed_orders;
+    use orders_repo::memory::InMemoryRepo;
+    use orders_types::domain::tenant::TenantId;
+    use orders_types::fixtures::FakeOrders;
+    use std::io::Cursor;
+
+    async fn seeded(count: usize) -> InMemoryRepo {
+        let repo = InMemoryRepo::new();
+        for (seed, tenant) in [
+            (7, TenantId::default()),
+            (8, TenantId::parse("acme").unwrap()),
+        ] {
+            let mut orders = FakeOrders::new(seed).with_tenant(tenant);
+            seed_orders(&repo, count, &mut orders).await.unwrap();
+        }
+        repo
+    }
+
+    async fn dump(repo: &InMemoryRepo) -> Vec<u8> {
+        let mut out = Vec::new();
+        backup(repo, None, &mut out, |_| {}).await.unwrap();
+        out
+    }
+
+    #[tokio::test]
+    async fn restores_what_it_backed_up() {
+        let source = seeded(30).await;
+        let bytes = dump(&source).await;
+        assert_eq!(
+            verify(Cursor::new(&bytes)).unwrap(),
+            Progress {
+                orders: 60,
+                outbox: 0
+            }
+        );
+
+        let target = InMemoryRepo::new();
+        let mut reports = Vec::new();
+        let restored = restore(
+            &target,
+            None,
+            || Ok(Cursor::new(&bytes)),
+            |p| reports.push(p),
+        )
+        .await
+        .unwrap();
+        assert_eq!(restored.orders, 60);
+        assert_eq!(reports.last(), Some(&restored));
+
+        let mut originals = std::pin::pin!(scan_orders(&source));
+        while let Some(order) = originals.next().await {
+            let order = order.unwrap();
+            let copy = target.get(&order.tenant_id, order.id).await.unwrap();
+            assert_eq!(
+                serde_json::to_value(copy).unwrap(),
+                serde_json::to_value(Some(&order)).unwrap()
+            );
+            assert_eq!(
+                target
+                    .status_history(&order.tenant_id, order.id)
+                    .await
+                    .unwrap(),
+                source
+                    .status_history(&order.tenant_id, order.id)
+                    .await
+                    .unwrap()
+            );
+        }
+        // Backing up the copy gives the same orders again.
+        let again = String::from_utf8(dump(&target).await).unwrap();
+        let lines = |s: &str| {
+            s.lines()
+                .skip(1)
+                .take(60)
+                .map(String::from)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(lines(&again), lines(std::str::from_utf8(&bytes).unwrap()));
+    }
+
+    #[tokio::test]
+    async fn refuses_a_tampered_or_truncated_backup() {
+        let bytes = dump(&seeded(3).await).await;
+        let text = String::from_utf8(bytes).unwrap();
+
+        let tampered = text.replacen("\"Pending\"", "\"Shipped\"", 1);
+        let err = verify(Cursor::new(&tampered)).unwrap_err();
+        assert!(err.to_string().contains("checksum mismatch"), "{err}");
+
+        let truncated: String = text.lines().take(4).map(|l| format!("{l}\n")).collect();
+        let err = verify(Cursor::new(&truncated)).unwrap_err();
+        assert!(err.to_string().contains("truncated"), "{err}");
+
+        let target = InMemoryRepo::new();
+        let restored = restore(&target, None, || Ok(Cursor::new(&tampered)), |_| {}).await;
+        assert!(restored.is_err());
+        assert!(target.scan(None, 1).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn refuses_to_restore_over_existing_orders() {
+        let repo = seeded(2).await;
+        let bytes = dump(&repo).await;
+        let err = restore(&repo, None, || Ok(Cursor::new(&bytes)), |_| {})
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("empty store"), "{err}");
+    }
+}
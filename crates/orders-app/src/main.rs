@@ -1,24 +1,518 @@
+mod backup;
+mod seed;
+
+use anyhow::Context;
+use clap::{Parser, Subcommand};
+use orders_hex::application::api_key_service::ApiKeyService;
+#[cfg(feature = "sqlite")]
+use orders_hex::application::dead_letters::DeadLetterService;
+use orders_hex::application::notifications::NotificationQueue;
+#[cfg(feature = "smtp")]
+use orders_hex::application::notifications::RetryPolicy;
 use orders_hex::application::order_service::OrderService;
+use orders_hex::application::priority::PriorityGate;
+use orders_hex::application::scheduler::{Scheduler, StaleOrderExpiry};
+use orders_hex::application::webhook_service::WebhookService;
 use orders_hex::config::Config;
-use orders_hex::inbound::http::{HttpServer, HttpServerConfig};
-use orders_repo::{build_repo, Repo};
+use orders_hex::inbound::http::body_log::BodyLogger;
+use orders_hex::inbound::http::rate_limit::{
+    InMemoryRateLimitStore, KeySource, Quota, RateLimiter,
+};
+use orders_hex::inbound::http::request_metrics::RequestMetrics;
+use orders_hex::inbound::http::slo::SloTracker;
+use orders_hex::inbound::http::{HttpServer, HttpServerConfig, TlsConfig};
+#[cfg(feature = "smtp")]
+use orders_hex::outbound::email::EmailTemplates;
+use orders_hex::outbound::inventory::InMemoryInventory;
+#[cfg(feature = "email-mx")]
+use orders_hex::outbound::mx::MxEmailValidator;
+use orders_hex::outbound::payment::MockPaymentGateway;
+#[cfg(feature = "smtp")]
+use orders_hex::outbound::smtp::SmtpNotifier;
+#[cfg(feature = "stripe")]
+use orders_hex::outbound::stripe::StripePaymentGateway;
+use orders_hex::outbound::validator::HttpOrderValidator;
+use orders_hex::outbound::webhook::ReqwestTransport;
+#[cfg(feature = "sqlite")]
+use orders_repo::sqlite::SqliteRepo;
+use orders_repo::{build_repo_with, Repo, RepoBackend, RepoOptions};
+use orders_types::domain::share::ShareSigner;
+use orders_types::domain::tenant::TenantId;
+use orders_types::fixtures::FakeOrders;
+use orders_types::ports::field_encryption::KeyProvider;
+use orders_types::ports::outbox::OutboxStore;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::seed::seed_orders;
+
+#[derive(Parser, Debug)]
+#[command(about = "Orders API server and maintenance commands")]
+struct Cli {
+    /// What to do; `serve` when omitted.
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Run the HTTP server.
+    Serve,
+    /// Apply pending repository migrations and exit.
+    Migrate {
+        /// Only list pending migrations; exit non-zero if there are any.
+        #[arg(long)]
+        check: bool,
+    },
+    /// Insert generated orders into the configured repository.
+    Seed {
+        /// Number of orders to create.
+        #[arg(long, default_value_t = 20)]
+        count: usize,
+        /// Tenant that owns the seeded orders.
+        #[arg(long, default_value = TenantId::DEFAULT)]
+        tenant: String,
+        /// Generator seed, for reproducible data (random when omitted).
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Spread creation dates over this many days before now.
+        #[arg(long, default_value_t = 90)]
+        days: u32,
+    },
+    /// Encrypt order emails and customer names stored in plaintext or under
+    /// an older key with the current `PII_ENCRYPTION_KEYS` key, then exit.
+    /// Run after adding a key, before retiring the old one.
+    EncryptPii,
+    /// Write every order with its status history and fulfillments, and the
+    /// outbox, to a newline-delimited JSON file.
+    Backup {
+        #[arg(long, value_name = "FILE")]
+        out: PathBuf,
+    },
+    /// Load a backup into an empty store after checking its checksum.
+    Restore {
+        #[arg(long = "in", value_name = "FILE")]
+        input: PathBuf,
+    },
+}
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
     // Load .env for DATABASE_URL / SERVER_PORT when present.
     let _ = dotenvy::dotenv();
     tracing_subscriber::fmt()
         .with_env_filter(std::env::var("RUST_LOG").unwrap_or_else(|_| "debug".to_string()))
         .init();
 
-    let config = Config::from_env()?;
-    let repo: Repo = build_repo(config.database_url.as_deref()).await?;
-    let service = OrderService::new(repo);
+    let mut config = Config::from_env()?;
+    let command = cli.command.unwrap_or(Command::Serve);
+    // A restore puts the outbox back as it was; new events for the
+    // restored orders would publish them all again.
+    if matches!(command, Command::Restore { .. }) {
+        config.outbox = false;
+    }
+    // Every command but `migrate` applies pending migrations on open.
+    let repo = open_repo(&config, matches!(command, Command::Migrate { .. })).await?;
+    match command {
+        Command::Serve => serve(config, repo).await,
+        Command::Migrate { check: true } => {
+            let pending = repo.pending_migrations().await?;
+            for m in &pending {
+                println!("pending {:04} {}", m.version, m.description);
+            }
+            if !pending.is_empty() {
+                anyhow::bail!("{} pending migration(s)", pending.len());
+            }
+            println!("schema is up to date");
+            Ok(())
+        }
+        Command::Migrate { check: false } => {
+            let applied = repo.migrate().await?;
+            for m in &applied {
+                println!("applied {:04} {}", m.version, m.description);
+            }
+            println!("{} migration(s) applied", applied.len());
+            Ok(())
+        }
+        Command::Seed {
+            count,
+            tenant,
+            seed,
+            days,
+        } => {
+            let tenant = TenantId::parse(&tenant).map_err(|e| anyhow::anyhow!(e))?;
+            if repo.backend() == RepoBackend::Memory {
+                tracing::warn!("seeding the memory backend; orders are lost on exit");
+            }
+            let seed = seed.unwrap_or_else(|| {
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_nanos() as u64)
+                    .unwrap_or(1)
+            });
+            let mut orders = FakeOrders::new(seed)
+                .with_tenant(tenant.clone())
+                .with_spread(chrono::Duration::days(days.into()));
+            let created = seed_orders(&repo, count, &mut orders).await?;
+            println!("seeded {created} order(s) for tenant {tenant} (seed {seed})");
+            Ok(())
+        }
+        Command::EncryptPii => encrypt_pii(&config, &repo).await,
+        Command::Backup { out } => {
+            let file = std::fs::File::create(&out)
+                .with_context(|| format!("cannot create {}", out.display()))?;
+            let done = backup::backup(
+                &repo,
+                outbox_store(&repo),
+                std::io::BufWriter::new(file),
+                |p| eprintln!("backed up {} order(s)", p.orders),
+            )
+            .await?;
+            println!(
+                "wrote {} order(s) and {} outbox record(s) to {}",
+                done.orders,
+                done.outbox,
+                out.display()
+            );
+            Ok(())
+        }
+        Command::Restore { input } => {
+            let done = backup::restore(
+                &repo,
+                outbox_store(&repo),
+                || std::fs::File::open(&input).map(std::io::BufReader::new),
+                |p| eprintln!("restored {} order(s)", p.orders),
+            )
+            .await
+            .with_context(|| format!("cannot restore {}", input.display()))?;
+            println!(
+                "restored {} order(s) and {} outbox record(s)",
+                done.orders, done.outbox
+            );
+            Ok(())
+        }
+    }
+}
+
+/// The outbox backups read and restores write; sqlite only.
+fn outbox_store(repo: &Repo) -> Option<&dyn OutboxStore> {
+    #[cfg(feature = "sqlite")]
+    return repo.sqlite().map(|r| r as &dyn OutboxStore);
+    #[cfg(not(feature = "sqlite"))]
+    {
+        let _ = repo;
+        None
+    }
+}
+
+async fn open_repo(config: &Config, skip_migrations: bool) -> anyhow::Result<Repo> {
+    let mut options = RepoOptions {
+        skip_migrations,
+        cache: config.repo_cache,
+        cache_capacity: config.repo_cache_capacity,
+        cache_ttl: config
+            .repo_cache_ttl_secs
+            .map(std::time::Duration::from_secs),
+        outbox: config.outbox,
+        pii_keys: config
+            .pii_keys()?
+            .map(|keys| Arc::new(keys) as Arc<dyn KeyProvider>),
+        ..Default::default()
+    };
+    if let Some(backend) = &config.repo_backend {
+        options.backend =
+            Some(RepoBackend::parse(backend).map_err(|e| anyhow::anyhow!("REPO_BACKEND: {e}"))?);
+    }
+    if let Some(max) = config.db_max_connections {
+        options.pool.max_connections = max;
+    }
+    if let Some(ms) = config.db_acquire_timeout_ms {
+        options.pool.acquire_timeout = std::time::Duration::from_millis(ms);
+    }
+    if let Some(secs) = config.db_idle_timeout_secs {
+        options.pool.idle_timeout = (secs > 0).then(|| std::time::Duration::from_secs(secs));
+    }
+    if let Some(wal) = config.sqlite_wal {
+        options.pool.wal = wal;
+    }
+    if let Some(ms) = config.sqlite_busy_timeout_ms {
+        options.pool.busy_timeout = std::time::Duration::from_millis(ms);
+    }
+    if let Some(attempts) = config.db_retry_attempts.filter(|&a| a > 1) {
+        let mut policy = orders_repo::retry::RetryPolicy {
+            max_attempts: attempts,
+            ..Default::default()
+        };
+        if let Some(ms) = config.db_retry_base_delay_ms {
+            policy.base_delay = std::time::Duration::from_millis(ms);
+        }
+        if let Some(ms) = config.db_retry_max_delay_ms {
+            policy.max_delay = std::time::Duration::from_millis(ms);
+        }
+        options.retry = Some(policy);
+    }
+    if let Some(rules) = &config.db_faults {
+        let plan = orders_repo::fault::FaultPlan::parse(rules)
+            .map_err(|e| anyhow::anyhow!("DB_FAULTS: {e}"))?;
+        options.faults = Some(plan);
+    }
+    options.slow_query_threshold = Some(std::time::Duration::from_millis(config.db_slow_query_ms));
+    let repo = build_repo_with(config.database_url.as_deref(), options).await?;
+    tracing::info!(backend = repo.backend().as_str(), "repository ready");
+    Ok(repo)
+}
+
+#[cfg(feature = "smtp")]
+fn email_notifications(config: &Config, url: &str) -> anyhow::Result<NotificationQueue> {
+    let templates = match &config.email_template_dir {
+        Some(dir) => EmailTemplates::load_dir(dir)?,
+        None => EmailTemplates::default(),
+    };
+    let notifier = SmtpNotifier::new(url, &config.smtp_from, templates)?;
+    Ok(NotificationQueue::spawn(notifier, RetryPolicy::default()))
+}
+
+#[cfg(not(feature = "smtp"))]
+fn email_notifications(_config: &Config, _url: &str) -> anyhow::Result<NotificationQueue> {
+    anyhow::bail!("SMTP_URL is set but this build lacks the `smtp` feature")
+}
+
+#[cfg(feature = "email-mx")]
+fn with_mx_check(service: OrderService<Repo>) -> anyhow::Result<OrderService<Repo>> {
+    Ok(service.with_email_validator(MxEmailValidator::from_system_conf()?))
+}
+
+#[cfg(not(feature = "email-mx"))]
+fn with_mx_check(_service: OrderService<Repo>) -> anyhow::Result<OrderService<Repo>> {
+    anyhow::bail!("EMAIL_MX_CHECK is set but this build lacks the `email-mx` feature")
+}
+
+/// Where reads are served from with `READ_MODEL_ENABLED`: the database at
+/// `READ_MODEL_DATABASE_URL`, or the write store's own.
+#[cfg(feature = "sqlite")]
+async fn read_model(config: &Config, repo: &Repo) -> anyhow::Result<SqliteRepo> {
+    match &config.read_model_database_url {
+        Some(url) => {
+            let views = SqliteRepo::new(url).await?;
+            Ok(match config.pii_keys()? {
+                Some(keys) => views.with_field_encryption(Arc::new(keys)),
+                None => views,
+            })
+        }
+        None => repo
+            .sqlite()
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("READ_MODEL_ENABLED needs the sqlite backend")),
+    }
+}
+
+/// Reseal personal data in the database and a separate read model's.
+#[cfg(feature = "sqlite")]
+async fn encrypt_pii(config: &Config, repo: &Repo) -> anyhow::Result<()> {
+    if config.pii_encryption_keys.is_none() {
+        anyhow::bail!("encrypt-pii needs PII_ENCRYPTION_KEYS and PII_INDEX_KEY");
+    }
+    let sqlite = repo
+        .sqlite()
+        .ok_or_else(|| anyhow::anyhow!("encrypt-pii needs the sqlite backend"))?;
+    let mut stores = vec![("database", sqlite.clone())];
+    if config.read_model_database_url.is_some() {
+        stores.push(("read model", read_model(config, repo).await?));
+    }
+    for (name, store) in stores {
+        let report = store.reencrypt_pii().await?;
+        println!(
+            "{name}: resealed orders {}, views {}, audit entries {}, outbox records {}, dead letters {}",
+            report.orders,
+            report.order_views,
+            report.audit_entries,
+            report.outbox,
+            report.dead_letters
+        );
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "sqlite"))]
+async fn encrypt_pii(_config: &Config, _repo: &Repo) -> anyhow::Result<()> {
+    anyhow::bail!("encrypt-pii needs the `sqlite` feature")
+}
+
+async fn serve(config: Config, repo: Repo) -> anyhow::Result<()> {
+    #[cfg(all(feature = "memory", feature = "sqlite"))]
+    let cache_metrics = repo.cache_metrics();
+    let latency_metrics = repo.latency_metrics();
+    let api_keys = config.admin_api_key.as_deref().map(|k| {
+        let keys = ApiKeyService::new(repo.clone()).with_bootstrap_key(k);
+        match config.api_key_signing_secret.as_deref() {
+            Some(secret) => keys.with_signing_secret(secret),
+            None => keys,
+        }
+    });
+    let mut service = OrderService::new(repo.clone())
+        .with_discounts(repo.clone())
+        .with_audit(repo.clone())
+        .with_status_mapping(config.legacy_status_map.clone())
+        .with_pricing_rules(config.pricing_policy())
+        .with_limits(config.order_limits());
+    if let Some(secs) = config.order_dedupe_window_secs {
+        service = service.with_dedupe_window(chrono::Duration::seconds(secs as i64));
+    }
+    if config.read_model {
+        #[cfg(feature = "sqlite")]
+        {
+            service = service.with_read_model(read_model(&config, &repo).await?);
+        }
+        #[cfg(not(feature = "sqlite"))]
+        anyhow::bail!("READ_MODEL_ENABLED needs the `sqlite` feature");
+    }
+    if let Some(secret) = &config.share_link_secret {
+        service = service.with_share_signer(
+            ShareSigner::new(secret)
+                .with_max_ttl(chrono::Duration::seconds(config.share_link_max_ttl_secs))
+                .with_clock_skew(chrono::Duration::seconds(config.share_link_clock_skew_secs)),
+        );
+    }
+    if let Some(url) = &config.order_validator_url {
+        service = service.with_validator(
+            HttpOrderValidator::new(url),
+            std::time::Duration::from_millis(config.order_validator_timeout_ms),
+            config.order_validator_policy,
+        );
+    }
+    match config.payment_gateway.as_deref() {
+        None => {}
+        Some("mock") => {
+            tracing::warn!("PAYMENT_GATEWAY=mock: confirmed orders are not really charged");
+            service = service.with_payments(MockPaymentGateway::new());
+        }
+        #[cfg(feature = "stripe")]
+        Some("stripe") => {
+            let key = config
+                .stripe_secret_key
+                .as_deref()
+                .expect("checked by Config::from_env");
+            let mut stripe = StripePaymentGateway::new(key);
+            if let Some(pm) = &config.stripe_payment_method {
+                stripe = stripe.with_payment_method(pm);
+            }
+            service = service.with_payments(stripe);
+        }
+        #[cfg(not(feature = "stripe"))]
+        Some("stripe") => anyhow::bail!("PAYMENT_GATEWAY=stripe needs the `stripe` feature"),
+        Some(other) => anyhow::bail!("PAYMENT_GATEWAY: expected mock or stripe, got `{other}`"),
+    }
+    if let Some(levels) = &config.inventory_stock {
+        let inventory = InMemoryInventory::new();
+        for line in levels {
+            inventory.set_stock(&line.sku, line.qty);
+        }
+        service = service.with_inventory(inventory);
+    }
+    if let Some(url) = &config.smtp_url {
+        service = service.with_notifications(email_notifications(&config, url)?);
+    }
+    if config.email_mx_check {
+        service = with_mx_check(service)?;
+    }
+    if let Some(limits) = config.priority_limits() {
+        service = service.with_priority(PriorityGate::new(limits));
+    }
+    // Surface rows the decoder would reject before serving traffic.
+    let report = service
+        .check_integrity(config.integrity_fix_on_startup, Default::default())
+        .await?;
+    if !report.is_clean() {
+        tracing::warn!(
+            issues = report.issues.len(),
+            "stored orders need attention; see GET /admin/integrity"
+        );
+    }
 
     let server_cfg = HttpServerConfig {
         port: config.server_port.clone(),
+        tls: config
+            .tls_cert_path
+            .clone()
+            .zip(config.tls_key_path.clone())
+            .map(|(cert, key)| TlsConfig::new(cert, key)),
+        admin_addr: config.admin_addr,
     };
 
-    let http = HttpServer::new(service, server_cfg).await?;
+    // The worker parks what its sinks give up on next to the outbox.
+    #[cfg(feature = "sqlite")]
+    let dead_letters = config
+        .outbox
+        .then(|| repo.sqlite().cloned())
+        .flatten()
+        .map(|sqlite| DeadLetterService::new(Arc::new(sqlite)));
+    let mut http = HttpServer::new(service, server_cfg)
+        .await?
+        .with_slo(SloTracker::new(config.slo_targets()))
+        .with_listener(config.listen.clone())
+        .with_config_dump(config.redacted())
+        .with_migrations(repo);
+    let mut scheduler = Scheduler::new();
+    if let Some(secs) = config.stale_order_max_age_secs {
+        scheduler = scheduler.with_job(
+            StaleOrderExpiry::new(http.service.clone(), chrono::Duration::seconds(secs as i64)),
+            std::time::Duration::from_secs(config.stale_order_sweep_interval_secs.max(1)),
+        );
+    }
+    #[cfg(feature = "sqlite")]
+    if let Some(letters) = dead_letters {
+        scheduler = scheduler.with_job(letters.clone(), std::time::Duration::from_secs(30));
+        http = http.with_dead_letters(letters);
+    }
+    if !scheduler.is_empty() {
+        http = http.with_jobs(scheduler.spawn());
+    }
+    if config.request_metrics {
+        http = http.with_request_metrics(RequestMetrics::new());
+    }
+    #[cfg(all(feature = "memory", feature = "sqlite"))]
+    if let Some(metrics) = cache_metrics {
+        http = http.with_metrics(metrics);
+    }
+    if let Some(metrics) = latency_metrics {
+        http = http.with_metrics(metrics);
+    }
+    if let Some(per_second) = config.rate_limit_per_sec {
+        let key = match config.rate_limit_key_header.clone() {
+            Some(header) => KeySource::Header(header),
+            None => KeySource::ClientIp,
+        };
+        let quota = Quota {
+            burst: config.rate_limit_burst,
+            per_second,
+        };
+        http = http.with_rate_limiter(RateLimiter::new(InMemoryRateLimitStore::new(), key, quota));
+    }
+    http = http.with_body_limit(config.max_body_bytes);
+    if let Some(body_log) = config.body_log() {
+        http = http.with_body_logger(BodyLogger::new(body_log));
+    }
+    if let Some(secret) = &config.jwt_secret {
+        http = http.with_tenant_jwt_secret(secret.as_bytes());
+    }
+    if let Some(secret) = &config.webhook_secret {
+        let transport = ReqwestTransport::new(std::time::Duration::from_secs(10))?;
+        http = http.with_webhooks(WebhookService::new(
+            config.webhook_targets.clone(),
+            secret.clone(),
+            transport,
+        ));
+    }
+    if let Some(keys) = api_keys {
+        http = http.with_api_keys(keys);
+    }
+    if let Some(sunset) = config.legacy_routes_sunset {
+        http = http.with_legacy_sunset(sunset);
+    }
+    #[cfg(feature = "graphql")]
+    {
+        http = http.with_graphql(config.dev_mode);
+    }
     http.run().await
 }
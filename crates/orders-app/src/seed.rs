@@ -0,0 +1,63 @@
+//! Generated orders for `orders-app seed`.
+
+use orders_types::fixtures::{FakeOrder, FakeOrders};
+use orders_types::ports::order_repository::OrderRepository;
+
+/// Store `count` orders from `orders`, each with its status history in one
+/// unit of work. They are written straight to the repository, so they keep
+/// their backdated instants. Returns how many were created.
+pub async fn seed_orders<R: OrderRepository>(
+    repo: &R,
+    count: usize,
+    orders: &mut FakeOrders,
+) -> anyhow::Result<usize> {
+    for FakeOrder { order, history } in orders.take(count) {
+        let (tenant, id) = (order.tenant_id.clone(), order.id);
+        let mut unit = repo.begin().await?;
+        unit.create(order).await?;
+        for entry in history {
+            unit.record_transition(&tenant, id, entry).await?;
+        }
+        unit.commit().await?;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+    use orders_types::domain::order::OrderStatus;
+    use orders_types::domain::tenant::TenantId;
+
+    #[tokio::test]
+    async fn seeds_orders_into_the_repo() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = if cfg!(feature = "sqlite") {
+            format!("sqlite://{}", dir.path().join("seed.db").display())
+        } else {
+            "memory://".to_string()
+        };
+        let repo = orders_repo::build_repo(Some(&url)).await.unwrap();
+        let tenant = TenantId::parse("seeded").unwrap();
+        let mut orders = FakeOrders::new(7)
+            .with_tenant(tenant.clone())
+            .with_spread(Duration::days(30));
+
+        let created = seed_orders(&repo, 25, &mut orders).await.unwrap();
+        assert_eq!(created, 25);
+        let stored = repo.list(&tenant).await.unwrap();
+        assert_eq!(stored.len(), 25);
+        assert!(stored
+            .iter()
+            .filter(|o| o.status == OrderStatus::Confirmed)
+            .all(|o| o.pricing.is_some()));
+        assert!(stored
+            .iter()
+            .any(|o| o.created_at < Utc::now() - Duration::days(1)));
+        for order in &stored {
+            let history = repo.status_history(&tenant, order.id).await.unwrap();
+            assert_eq!(history.last().map(|h| &h.to), Some(&order.status));
+        }
+    }
+}
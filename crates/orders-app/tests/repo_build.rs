@@ -1,7 +1,9 @@
 use orders_repo::{build_repo, Repo};
+use orders_types::domain::tenant::TenantId;
 use orders_types::ports::order_repository::OrderRepository;
 use std::env;
 
+#[cfg(feature = "sqlite")]
 #[tokio::test]
 async fn builds_sqlite_repo_from_env() {
     // Use a temp DB path for isolation.
@@ -12,6 +14,6 @@ async fn builds_sqlite_repo_from_env() {
 
     let repo: Repo = build_repo(Some(&url)).await.expect("build repo");
     // basic sanity: list should succeed and be empty
-    let list = repo.list().await.expect("list");
+    let list = repo.list(&TenantId::default()).await.expect("list");
     assert!(list.is_empty());
 }
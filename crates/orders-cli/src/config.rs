@@ -0,0 +1,127 @@
+//! Where to reach the API and as whom: flags first, then the environment,
+//! then a config file of the same `ORDERS_*` variables in `.env` syntax.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use orders_client::OrdersBlockingClient;
+use orders_types::domain::tenant::TenantId;
+
+const DEFAULT_URL: &str = "http://127.0.0.1:3000/";
+
+#[derive(clap::Args, Debug, Default)]
+pub struct Connection {
+    /// Base URL of the Orders API.
+    #[arg(long, env = "ORDERS_URL", global = true)]
+    url: Option<String>,
+    /// API key sent as `X-Api-Key`.
+    #[arg(long, env = "ORDERS_API_KEY", hide_env_values = true, global = true)]
+    api_key: Option<String>,
+    /// Bearer token, used when no API key is set.
+    #[arg(long, env = "ORDERS_TOKEN", hide_env_values = true, global = true)]
+    token: Option<String>,
+    #[arg(long, env = "ORDERS_TENANT", global = true)]
+    tenant: Option<String>,
+    /// Config file; `~/.config/orders-cli/config` when it exists.
+    #[arg(long, env = "ORDERS_CLI_CONFIG", global = true)]
+    config: Option<PathBuf>,
+}
+
+/// A [`Connection`] with the config file folded in.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Settings {
+    pub url: String,
+    pub api_key: Option<String>,
+    pub token: Option<String>,
+    pub tenant: Option<TenantId>,
+}
+
+fn default_config_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+    Some(base.join("orders-cli").join("config"))
+}
+
+fn read_config(path: &Path) -> anyhow::Result<HashMap<String, String>> {
+    dotenvy::from_path_iter(path)
+        .and_then(|vars| vars.collect())
+        .with_context(|| format!("reading config file {}", path.display()))
+}
+
+impl Connection {
+    /// An explicitly named config file must exist; the default one may not.
+    pub fn resolve(self) -> anyhow::Result<Settings> {
+        let file = match &self.config {
+            Some(path) => read_config(path)?,
+            None => match default_config_path().filter(|p| p.is_file()) {
+                Some(path) => read_config(&path)?,
+                None => HashMap::new(),
+            },
+        };
+        let pick = |flag: Option<String>, key: &str| flag.or_else(|| file.get(key).cloned());
+        let tenant = pick(self.tenant, "ORDERS_TENANT")
+            .map(|t| TenantId::parse(&t).map_err(anyhow::Error::msg))
+            .transpose()?;
+        Ok(Settings {
+            url: pick(self.url, "ORDERS_URL").unwrap_or_else(|| DEFAULT_URL.into()),
+            api_key: pick(self.api_key, "ORDERS_API_KEY"),
+            token: pick(self.token, "ORDERS_TOKEN"),
+            tenant,
+        })
+    }
+}
+
+impl Settings {
+    pub fn client(&self) -> anyhow::Result<OrdersBlockingClient> {
+        let mut builder = OrdersBlockingClient::builder(&self.url)?;
+        if let Some(key) = &self.api_key {
+            builder = builder.with_api_key(key)?;
+        } else if let Some(token) = &self.token {
+            builder = builder.with_bearer_token(token)?;
+        }
+        if let Some(tenant) = &self.tenant {
+            builder = builder.with_tenant(tenant)?;
+        }
+        builder.build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_win_over_the_config_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config");
+        std::fs::write(
+            &path,
+            "# staging\nORDERS_URL=https://orders.staging.example/\nORDERS_API_KEY=\"ok_file\"\nORDERS_TENANT=acme\n",
+        )
+        .unwrap();
+        let settings = Connection {
+            api_key: Some("ok_flag".into()),
+            config: Some(path),
+            ..Connection::default()
+        }
+        .resolve()
+        .unwrap();
+        assert_eq!(
+            settings,
+            Settings {
+                url: "https://orders.staging.example/".into(),
+                api_key: Some("ok_flag".into()),
+                token: None,
+                tenant: Some(TenantId::parse("acme").unwrap()),
+            }
+        );
+
+        let missing = Connection {
+            config: Some(dir.path().join("absent")),
+            ..Connection::default()
+        };
+        assert!(missing.resolve().is_err());
+    }
+}
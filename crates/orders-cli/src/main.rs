@@ -0,0 +1,245 @@
+//! orders-cli: create, list and move orders from the terminal.
+//!
+//! ```bash
+//! orders-cli create --customer "Ann Lee" --email ann@example.com --item "Widget:2:4.99"
+//! orders-cli list --status shipped --output json
+//! orders-cli status 3f2c... shipped
+//! ```
+
+mod config;
+mod table;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use orders_client::{CreateOrderRequest, ListOrdersQuery, OrdersBlockingClient};
+use orders_types::domain::money::{Currency, Money};
+use orders_types::domain::order::{OrderItem, OrderStatus};
+
+use crate::config::Connection;
+
+#[derive(Parser, Debug)]
+#[command(about = "Work with orders through the Orders API")]
+struct Cli {
+    #[command(flatten)]
+    connection: Connection,
+    /// How to print results.
+    #[arg(long, value_enum, default_value_t = Output::Table, global = true)]
+    output: Output,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Output {
+    Table,
+    Json,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Place an order.
+    Create {
+        #[arg(long)]
+        customer: String,
+        #[arg(long)]
+        email: String,
+        /// `name:qty:price`, with the price in major units (`4.99`);
+        /// repeat for more items.
+        #[arg(long = "item", required = true)]
+        items: Vec<String>,
+        /// Currency of every item's price.
+        #[arg(long, default_value = "USD", value_parser = Currency::parse)]
+        currency: Currency,
+        #[arg(long)]
+        discount_code: Option<String>,
+    },
+    /// List orders, every page unless `--limit` is given.
+    List {
+        #[arg(long, value_parser = parse_status)]
+        status: Option<OrderStatus>,
+        #[arg(long)]
+        email: Option<String>,
+        /// Country of the shipping address.
+        #[arg(long)]
+        country: Option<String>,
+        /// At most this many orders, oldest first.
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// Move an order to another status.
+    Status {
+        id: String,
+        #[arg(value_parser = parse_status)]
+        status: OrderStatus,
+    },
+}
+
+const STATUSES: [OrderStatus; 5] = [
+    OrderStatus::Pending,
+    OrderStatus::Confirmed,
+    OrderStatus::Shipped,
+    OrderStatus::Cancelled,
+    OrderStatus::Completed,
+];
+
+/// A status by name, ignoring case (`shipped`, `Shipped`).
+fn parse_status(s: &str) -> Result<OrderStatus, String> {
+    STATUSES
+        .into_iter()
+        .find(|status| format!("{status:?}").eq_ignore_ascii_case(s))
+        .ok_or_else(|| {
+            let names: Vec<String> = STATUSES
+                .iter()
+                .map(|s| format!("{s:?}").to_lowercase())
+                .collect();
+            format!("unknown status `{s}`; expected one of {}", names.join(", "))
+        })
+}
+
+/// `name:qty:price`; the name may itself contain colons.
+fn parse_item(spec: &str, currency: Currency) -> anyhow::Result<OrderItem> {
+    let mut parts = spec.rsplitn(3, ':');
+    let (Some(price), Some(qty), Some(name)) = (parts.next(), parts.next(), parts.next()) else {
+        anyhow::bail!("item `{spec}` is not `name:qty:price`");
+    };
+    let qty = qty
+        .parse()
+        .map_err(|_| anyhow::anyhow!("item `{spec}` has an invalid quantity `{qty}`"))?;
+    let unit_price = Money::from_major_str(price, currency).map_err(anyhow::Error::msg)?;
+    Ok(OrderItem {
+        name: name.to_string(),
+        qty,
+        unit_price,
+        weight_grams: 0,
+        sku: None,
+        description: None,
+        metadata: Default::default(),
+        discount_cents: 0,
+    })
+}
+
+fn run(command: Command, output: Output, client: &OrdersBlockingClient) -> anyhow::Result<String> {
+    let json = output == Output::Json;
+    Ok(match command {
+        Command::Create {
+            customer,
+            email,
+            items,
+            currency,
+            discount_code,
+        } => {
+            let items = items
+                .iter()
+                .map(|spec| parse_item(spec, currency))
+                .collect::<anyhow::Result<_>>()?;
+            let created = client.create_order(CreateOrderRequest {
+                customer_name: customer,
+                email,
+                items,
+                discount_code,
+                shipping_address: None,
+                billing_address: None,
+                metadata: Default::default(),
+            })?;
+            if json {
+                serde_json::to_string_pretty(&created)?
+            } else {
+                table::render(
+                    &["ID", "STATUS"],
+                    vec![vec![created.id, format!("{:?}", created.status)]],
+                )
+            }
+        }
+        Command::List {
+            status,
+            email,
+            country,
+            limit,
+        } => {
+            let mut query = ListOrdersQuery::new();
+            if let Some(status) = status {
+                query = query.with_status(status);
+            }
+            if let Some(email) = email {
+                query = query.with_email(email);
+            }
+            if let Some(country) = country {
+                query = query.with_country(country);
+            }
+            let orders = match limit {
+                Some(limit) => client.orders_stream(query).take(limit).collect(),
+                None => client.list_all(query),
+            }?;
+            if json {
+                serde_json::to_string_pretty(&orders)?
+            } else {
+                table::orders(&orders)
+            }
+        }
+        Command::Status { id, status } => {
+            let order = client.update_status(&id, status)?;
+            if json {
+                serde_json::to_string_pretty(&order)?
+            } else {
+                table::orders(std::slice::from_ref(&order))
+            }
+        }
+    })
+}
+
+fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    let client = cli.connection.resolve()?.client()?;
+    print!("{}", run(cli.command, cli.output, &client)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_items_and_statuses() {
+        let item = parse_item("Mug: large:2:4.5", Currency::USD).unwrap();
+        assert_eq!(item.name, "Mug: large");
+        assert_eq!(item.qty, 2);
+        assert_eq!(item.unit_price, Money::usd(450));
+        assert!(parse_item("Mug:2", Currency::USD).is_err());
+        assert!(parse_item("Mug:two:4.50", Currency::USD).is_err());
+        assert!(parse_item("Mug:2:4.505", Currency::USD).is_err());
+
+        assert_eq!(parse_status("shipped"), Ok(OrderStatus::Shipped));
+        assert_eq!(parse_status("CANCELLED"), Ok(OrderStatus::Cancelled));
+        assert!(parse_status("lost").unwrap_err().contains("pending"));
+    }
+
+    #[test]
+    fn command_line_shapes() {
+        let cli = Cli::try_parse_from([
+            "orders-cli",
+            "create",
+            "--customer",
+            "Ann",
+            "--email",
+            "ann@example.com",
+            "--item",
+            "Widget:1:5",
+            "--item",
+            "Gadget:2:1.25",
+            "--output",
+            "json",
+        ])
+        .unwrap();
+        assert_eq!(cli.output, Output::Json);
+        assert!(matches!(cli.command, Command::Create { ref items, .. } if items.len() == 2));
+
+        let cli = Cli::try_parse_from(["orders-cli", "status", "abc", "Shipped"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Status {
+                status: OrderStatus::Shipped,
+                ..
+            }
+        ));
+        assert!(Cli::try_parse_from(["orders-cli", "create", "--customer", "Ann"]).is_err());
+    }
+}
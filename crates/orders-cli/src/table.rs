@@ -0,0 +1,61 @@
+//! Plain-text tables for `--output table`.
+
+use orders_types::domain::order::Order;
+
+/// Left-aligned columns two spaces apart, one line per row.
+pub fn render(headers: &[&str], rows: Vec<Vec<String>>) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let mut out = String::new();
+    let header = headers.iter().map(|h| h.to_string()).collect();
+    for row in std::iter::once(header).chain(rows) {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, &width)| format!("{cell:<width$}"))
+            .collect();
+        out.push_str(cells.join("  ").trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+pub fn orders(orders: &[Order]) -> String {
+    render(
+        &["ID", "STATUS", "CUSTOMER", "EMAIL", "TOTAL", "CREATED"],
+        orders
+            .iter()
+            .map(|o| {
+                vec![
+                    o.id.to_string(),
+                    format!("{:?}", o.status),
+                    o.customer_name.clone(),
+                    o.email.clone(),
+                    o.total.to_string(),
+                    o.created_at.format("%Y-%m-%d %H:%M").to_string(),
+                ]
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pads_columns_to_the_widest_cell() {
+        let table = render(
+            &["ID", "STATUS"],
+            vec![
+                vec!["a".into(), "Pending".into()],
+                vec!["bbbb".into(), "Shipped".into()],
+            ],
+        );
+        assert_eq!(table, "ID    STATUS\na     Pending\nbbbb  Shipped\n");
+    }
+}
@@ -0,0 +1,77 @@
+//! [`OrdersApi`]: the order calls as a trait, so code that talks to the
+//! Orders API can be tested against an in-process fake instead of HTTP.
+
+use async_trait::async_trait;
+use orders_types::domain::filter::OrderFilter;
+use orders_types::domain::history::OrderHistoryEntry;
+use orders_types::domain::order::{Order, OrderStatus};
+use orders_types::domain::order_patch::OrderPatch;
+
+use crate::{CreateOrderRequest, CreateOrderResponse, ListOrdersQuery, OrdersClient};
+
+/// Implemented by [`OrdersClient`] and, with the `in-memory` feature, by
+/// [`InMemoryOrdersApi`](crate::in_memory::InMemoryOrdersApi). Failures the
+/// server would answer with an error status come back as an
+/// [`ApiError`](crate::ApiError) inside the `anyhow::Error` from both.
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait OrdersApi: Send + Sync {
+    async fn create_order(&self, req: CreateOrderRequest) -> anyhow::Result<CreateOrderResponse>;
+
+    /// `id` is the order's id or its order number.
+    async fn get_order(&self, id: &str) -> anyhow::Result<Order>;
+
+    /// Orders matching `filter`, including its `limit` and `offset`.
+    async fn list_orders_with(&self, filter: OrderFilter) -> anyhow::Result<Vec<Order>>;
+
+    async fn update_status(&self, id: &str, status: OrderStatus) -> anyhow::Result<Order>;
+
+    /// Change the order's customer details or addresses.
+    async fn update_order(&self, id: &str, patch: &OrderPatch) -> anyhow::Result<Order>;
+
+    /// The order's status changes, oldest first.
+    async fn order_history(&self, id: &str) -> anyhow::Result<Vec<OrderHistoryEntry>>;
+
+    async fn delete_order(&self, id: &str) -> anyhow::Result<()>;
+
+    async fn list_orders(&self) -> anyhow::Result<Vec<Order>> {
+        self.list_orders_with(OrderFilter::default()).await
+    }
+
+    /// One page of `query`.
+    async fn list_orders_query(&self, query: &ListOrdersQuery) -> anyhow::Result<Vec<Order>> {
+        self.list_orders_with(query.to_filter()).await
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl OrdersApi for OrdersClient {
+    async fn create_order(&self, req: CreateOrderRequest) -> anyhow::Result<CreateOrderResponse> {
+        OrdersClient::create_order(self, req).await
+    }
+
+    async fn get_order(&self, id: &str) -> anyhow::Result<Order> {
+        OrdersClient::get_order(self, id).await
+    }
+
+    async fn list_orders_with(&self, filter: OrderFilter) -> anyhow::Result<Vec<Order>> {
+        OrdersClient::list_orders_with(self, filter).await
+    }
+
+    async fn update_status(&self, id: &str, status: OrderStatus) -> anyhow::Result<Order> {
+        OrdersClient::update_status(self, id, status).await
+    }
+
+    async fn update_order(&self, id: &str, patch: &OrderPatch) -> anyhow::Result<Order> {
+        OrdersClient::update_order(self, id, patch).await
+    }
+
+    async fn order_history(&self, id: &str) -> anyhow::Result<Vec<OrderHistoryEntry>> {
+        OrdersClient::order_history(self, id).await
+    }
+
+    async fn delete_order(&self, id: &str) -> anyhow::Result<()> {
+        OrdersClient::delete_order(self, id).await
+    }
+}
@@ -0,0 +1,451 @@
+//! [`OrdersBlockingClient`]: the calls of [`OrdersClient`](crate::OrdersClient)
+//! on `reqwest::blocking`, for CLI tools and scripts without a tokio runtime.
+//! There is no response cache. Don't use it from inside an async runtime;
+//! `reqwest::blocking` panics there.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use orders_types::domain::correlation::RequestId;
+use orders_types::domain::filter::{OrderFilter, OrderPage};
+use orders_types::domain::history::OrderHistoryEntry;
+use orders_types::domain::order::{Order, OrderStatus};
+use orders_types::domain::order_patch::OrderPatch;
+use orders_types::domain::share::ShareToken;
+use orders_types::domain::tenant::TenantId;
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+use reqwest::{Method, Url};
+use uuid::Uuid;
+
+use crate::circuit::{CircuitBreaker, CircuitBreakerConfig};
+use crate::{
+    api_url, bearer, normalize_base, share_url, signature, ApiError, CreateOrderRequest,
+    CreateOrderResponse, CreateShareLinkRequest, ListOrdersQuery, ShareLink, UpdateStatusRequest,
+};
+
+#[derive(Clone)]
+pub struct OrdersBlockingClientBuilder {
+    base: Url,
+    headers: HeaderMap,
+    timeout: Option<Duration>,
+    client: Option<Client>,
+    auth: Option<Auth>,
+    circuit_breaker: Option<CircuitBreakerConfig>,
+}
+
+#[derive(Clone)]
+enum Auth {
+    Header(HeaderName, HeaderValue),
+    Basic { username: String, password: String },
+    Provider(Arc<dyn Fn() -> anyhow::Result<String> + Send + Sync>),
+    Hmac { key_id: Uuid, signing_key: String },
+}
+
+#[derive(Clone)]
+pub struct OrdersBlockingClient {
+    base: Url,
+    client: Client,
+    tag_requests: bool,
+    auth: Option<Auth>,
+    breaker: Option<Arc<CircuitBreaker>>,
+}
+
+impl OrdersBlockingClient {
+    pub fn new(base_url: &str) -> anyhow::Result<Self> {
+        Self::builder(base_url)?.build()
+    }
+
+    /// Same base URL rules as [`OrdersClient::builder`](crate::OrdersClient::builder).
+    pub fn builder(base_url: &str) -> anyhow::Result<OrdersBlockingClientBuilder> {
+        Ok(OrdersBlockingClientBuilder {
+            base: normalize_base(base_url)?,
+            headers: HeaderMap::new(),
+            timeout: None,
+            client: None,
+            auth: None,
+            circuit_breaker: None,
+        })
+    }
+
+    fn url(&self, segments: &[&str]) -> anyhow::Result<Url> {
+        api_url(&self.base, segments)
+    }
+
+    fn request(&self, method: Method, url: Url) -> anyhow::Result<RequestBuilder> {
+        let mut req = self.client.request(method, url);
+        if self.tag_requests {
+            req = req.header(RequestId::HEADER, RequestId::new().as_str());
+        }
+        Ok(match &self.auth {
+            None => req,
+            Some(Auth::Header(name, value)) => req.header(name, value),
+            Some(Auth::Basic { username, password }) => req.basic_auth(username, Some(password)),
+            Some(Auth::Provider(provider)) => {
+                let token = provider().context("token provider failed")?;
+                req.header(AUTHORIZATION, bearer(&token)?)
+            }
+            Some(Auth::Hmac { .. }) => req,
+        })
+    }
+
+    fn sign(&self, req: RequestBuilder) -> anyhow::Result<RequestBuilder> {
+        let Some(Auth::Hmac {
+            key_id,
+            signing_key,
+        }) = &self.auth
+        else {
+            return Ok(req);
+        };
+        let mut req = req.build()?;
+        let body = req
+            .body()
+            .map_or(Some(&[][..]), reqwest::blocking::Body::as_bytes);
+        let value = signature(*key_id, signing_key, req.method(), req.url(), body)?;
+        req.headers_mut().insert(AUTHORIZATION, value);
+        Ok(RequestBuilder::from_parts(self.client.clone(), req))
+    }
+
+    fn send(&self, req: RequestBuilder) -> anyhow::Result<Response> {
+        let req = self.sign(req)?;
+        let Some(breaker) = &self.breaker else {
+            return Ok(req.send()?);
+        };
+        let permit = breaker.acquire()?;
+        let res = req.send();
+        permit.record(res.as_ref().is_ok_and(|r| !r.status().is_server_error()));
+        Ok(res?)
+    }
+
+    pub fn create_order(&self, req: CreateOrderRequest) -> anyhow::Result<CreateOrderResponse> {
+        let res = self.send(
+            self.request(Method::POST, self.url(&["orders"])?)?
+                .json(&req),
+        )?;
+        Ok(api_result(res)?.json()?)
+    }
+
+    pub fn get_order(&self, id: &str) -> anyhow::Result<Order> {
+        let res = self.send(self.request(Method::GET, self.url(&["orders", id])?)?)?;
+        Ok(api_result(res)?.json()?)
+    }
+
+    pub fn list_orders(&self) -> anyhow::Result<Vec<Order>> {
+        self.list_orders_with(OrderFilter::default())
+    }
+
+    /// List orders matching `filter`, encoded exactly as the server decodes it.
+    pub fn list_orders_with(&self, filter: OrderFilter) -> anyhow::Result<Vec<Order>> {
+        let res = self.send(
+            self.request(Method::GET, self.url(&["orders"])?)?
+                .query(&filter)
+                .query(&filter.metadata_params()),
+        )?;
+        Ok(api_result(res)?.json::<OrderPage>()?.orders)
+    }
+
+    /// One page of `query`.
+    pub fn list_orders_query(&self, query: &ListOrdersQuery) -> anyhow::Result<Vec<Order>> {
+        self.list_orders_with(query.to_filter())
+    }
+
+    /// Every order matching `query`; see
+    /// [`OrdersClient::list_all`](crate::OrdersClient::list_all).
+    pub fn list_all(&self, query: ListOrdersQuery) -> anyhow::Result<Vec<Order>> {
+        let mut all = Vec::new();
+        for page in self.orders_pages(query) {
+            all.extend(page?);
+        }
+        Ok(all)
+    }
+
+    /// The pages [`Self::list_all`] would fetch, each requested only when
+    /// the iterator gets to it. Empty pages are skipped, and iteration ends
+    /// after the first error.
+    pub fn orders_pages(
+        &self,
+        query: ListOrdersQuery,
+    ) -> impl Iterator<Item = anyhow::Result<Vec<Order>>> + '_ {
+        let mut next = Some(query.for_paging());
+        std::iter::from_fn(move || {
+            let mut query = next.take()?;
+            match self.list_orders_query(&query) {
+                Ok(page) if page.is_empty() => None,
+                Ok(page) => {
+                    if page.len() >= query.per_page() {
+                        query.page = Some(query.page() + 1);
+                        next = Some(query);
+                    }
+                    Some(Ok(page))
+                }
+                Err(e) => Some(Err(e)),
+            }
+        })
+    }
+
+    /// [`Self::orders_pages`] one order at a time.
+    pub fn orders_stream(
+        &self,
+        query: ListOrdersQuery,
+    ) -> impl Iterator<Item = anyhow::Result<Order>> + '_ {
+        self.orders_pages(query).flat_map(|page| match page {
+            Ok(orders) => orders.into_iter().map(Ok).collect::<Vec<_>>(),
+            Err(e) => vec![Err(e)],
+        })
+    }
+
+    pub fn update_status(&self, id: &str, status: OrderStatus) -> anyhow::Result<Order> {
+        let res = self.send(
+            self.request(Method::PATCH, self.url(&["orders", id, "status"])?)?
+                .json(&UpdateStatusRequest { status }),
+        )?;
+        Ok(api_result(res)?.json()?)
+    }
+
+    /// Change an order's customer details or addresses; see [`OrderPatch`].
+    pub fn update_order(&self, id: &str, patch: &OrderPatch) -> anyhow::Result<Order> {
+        let res = self.send(
+            self.request(Method::PATCH, self.url(&["orders", id])?)?
+                .header(CONTENT_TYPE, OrderPatch::CONTENT_TYPE)
+                .body(serde_json::to_vec(patch)?),
+        )?;
+        Ok(api_result(res)?.json()?)
+    }
+
+    /// The order's status changes, oldest first.
+    pub fn order_history(&self, id: &str) -> anyhow::Result<Vec<OrderHistoryEntry>> {
+        let res = self.send(self.request(Method::GET, self.url(&["orders", id, "history"])?)?)?;
+        Ok(api_result(res)?.json()?)
+    }
+
+    /// Ask the server to mint a read-only share link; `ttl_secs` defaults to
+    /// the server's choice.
+    pub fn create_share_link(&self, id: &str, ttl_secs: Option<i64>) -> anyhow::Result<ShareLink> {
+        let res = self.send(
+            self.request(Method::POST, self.url(&["orders", id, "share"])?)?
+                .json(&CreateShareLinkRequest { ttl_secs }),
+        )?;
+        Ok(api_result(res)?.json()?)
+    }
+
+    pub fn share_url(&self, id: &str, token: &ShareToken) -> anyhow::Result<Url> {
+        share_url(&self.base, id, token)
+    }
+
+    /// Fetch an order through a share token; no credentials are needed.
+    pub fn get_shared_order(&self, id: &str, token: &ShareToken) -> anyhow::Result<Order> {
+        let res = self.send(self.request(Method::GET, self.share_url(id, token)?)?)?;
+        Ok(api_result(res)?.json()?)
+    }
+
+    pub fn delete_order(&self, id: &str) -> anyhow::Result<()> {
+        let res = self.send(self.request(Method::DELETE, self.url(&["orders", id])?)?)?;
+        api_result(res)?;
+        Ok(())
+    }
+}
+
+fn api_result(res: Response) -> anyhow::Result<Response> {
+    let status = res.status();
+    if status.is_success() {
+        return Ok(res);
+    }
+    let body = res.bytes()?;
+    Err(ApiError::from_body(status, &body).into())
+}
+
+impl OrdersBlockingClientBuilder {
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_header(
+        mut self,
+        key: impl AsRef<str>,
+        value: impl AsRef<str>,
+    ) -> anyhow::Result<Self> {
+        let header_name =
+            HeaderName::from_bytes(key.as_ref().as_bytes()).context("invalid header name")?;
+        let header_value = HeaderValue::from_str(value.as_ref()).context("invalid header value")?;
+        self.headers.insert(header_name, header_value);
+        Ok(self)
+    }
+
+    /// Send `Authorization: Bearer <token>` on every request. Like the other
+    /// auth helpers, replaces any credentials set before.
+    pub fn with_bearer_token(mut self, token: impl AsRef<str>) -> anyhow::Result<Self> {
+        self.auth = Some(Auth::Header(AUTHORIZATION, bearer(token.as_ref())?));
+        Ok(self)
+    }
+
+    /// HTTP Basic credentials on every request.
+    pub fn with_basic_auth(
+        mut self,
+        username: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Self {
+        self.auth = Some(Auth::Basic {
+            username: username.into(),
+            password: password.into(),
+        });
+        self
+    }
+
+    /// Authenticate with a server-minted key, sent as `X-Api-Key`.
+    pub fn with_api_key(mut self, key: impl AsRef<str>) -> anyhow::Result<Self> {
+        let mut value = HeaderValue::from_str(key.as_ref()).context("invalid api key")?;
+        value.set_sensitive(true);
+        self.auth = Some(Auth::Header(HeaderName::from_static("x-api-key"), value));
+        Ok(self)
+    }
+
+    /// Sign every request with API key `key_id` and its `signing_key`; see
+    /// [`OrdersClientBuilder::with_hmac_credentials`](crate::OrdersClientBuilder::with_hmac_credentials).
+    pub fn with_hmac_credentials(
+        mut self,
+        key_id: impl AsRef<str>,
+        signing_key: impl AsRef<str>,
+    ) -> anyhow::Result<Self> {
+        self.auth = Some(Auth::Hmac {
+            key_id: Uuid::parse_str(key_id.as_ref()).context("invalid api key id")?,
+            signing_key: signing_key.as_ref().to_string(),
+        });
+        Ok(self)
+    }
+
+    /// Ask `provider` for a bearer token before every request; it should
+    /// hand back a cached token until that one needs refreshing.
+    pub fn with_token_provider(
+        mut self,
+        provider: impl Fn() -> anyhow::Result<String> + Send + Sync + 'static,
+    ) -> Self {
+        self.auth = Some(Auth::Provider(Arc::new(provider)));
+        self
+    }
+
+    /// Act for `tenant` by sending `X-Tenant-Id` on every request.
+    pub fn with_tenant(self, tenant: &TenantId) -> anyhow::Result<Self> {
+        self.with_header("x-tenant-id", tenant.as_str())
+    }
+
+    /// Fail fast with [`OrdersClientError::CircuitOpen`](crate::OrdersClientError::CircuitOpen)
+    /// once the server keeps failing.
+    pub fn with_circuit_breaker(mut self, config: CircuitBreakerConfig) -> Self {
+        self.circuit_breaker = Some(config);
+        self
+    }
+
+    /// Use `client` as is; timeout and headers on this builder are then
+    /// ignored, but credentials and the circuit breaker still apply.
+    pub fn with_reqwest_client(mut self, client: Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    pub fn build(self) -> anyhow::Result<OrdersBlockingClient> {
+        let breaker = self
+            .circuit_breaker
+            .map(|config| Arc::new(CircuitBreaker::new(config)));
+        if let Some(client) = self.client {
+            return Ok(OrdersBlockingClient {
+                base: self.base,
+                client,
+                tag_requests: true,
+                auth: self.auth,
+                breaker,
+            });
+        }
+
+        let tag_requests = !self.headers.contains_key(RequestId::HEADER);
+        let mut builder = Client::builder();
+        if !self.headers.is_empty() {
+            builder = builder.default_headers(self.headers);
+        }
+        if let Some(t) = self.timeout {
+            builder = builder.timeout(t);
+        }
+        Ok(OrdersBlockingClient {
+            base: self.base,
+            client: builder.build()?,
+            tag_requests,
+            auth: self.auth,
+            breaker,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{page, sample_order};
+    use httpmock::prelude::*;
+    use orders_types::domain::error_code::ErrorCode;
+
+    #[test]
+    fn blocking_calls_mirror_the_async_client() {
+        let server = MockServer::start();
+        let orders: Vec<Order> = (0..3).map(|_| sample_order()).collect();
+        let id = orders[0].id.to_string();
+        let get = server.mock(|when, then| {
+            when.method(GET)
+                .path(format!("/v1/orders/{id}"))
+                .header("authorization", "Bearer s3cret");
+            then.status(200).json_body_obj(&orders[0]);
+        });
+        let missing = server.mock(|when, then| {
+            when.method(GET).path("/v1/orders/missing");
+            then.status(404).json_body(serde_json::json!({
+                "error": "order not found",
+                "code": "ORDER_NOT_FOUND"
+            }));
+        });
+        let first = server.mock(|when, then| {
+            when.method(GET)
+                .path("/v1/orders")
+                .query_param("limit", "2")
+                .query_param("offset", "0");
+            then.status(200).json_body_obj(&page(orders[..2].to_vec()));
+        });
+        let second = server.mock(|when, then| {
+            when.method(GET)
+                .path("/v1/orders")
+                .query_param("limit", "2")
+                .query_param("offset", "2");
+            then.status(200).json_body_obj(&page(orders[2..].to_vec()));
+        });
+
+        let client = OrdersBlockingClient::builder(&server.base_url())
+            .unwrap()
+            .with_bearer_token("s3cret")
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(client.get_order(&id).unwrap().id, orders[0].id);
+        get.assert();
+
+        let err = client.get_order("missing").unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ApiError>().map(|e| e.code),
+            Some(ErrorCode::OrderNotFound)
+        );
+        missing.assert();
+
+        let query = ListOrdersQuery::new().with_per_page(2);
+        let mut pages = client.orders_pages(query.clone());
+        assert_eq!(pages.next().unwrap().unwrap().len(), 2);
+        second.assert_hits(0);
+        assert_eq!(pages.next().unwrap().unwrap().len(), 1);
+        assert!(pages.next().is_none());
+
+        let ids: Vec<_> = client
+            .orders_stream(query)
+            .map(|o| o.map(|o| o.id))
+            .collect::<anyhow::Result<_>>()
+            .unwrap();
+        assert_eq!(ids, orders.iter().map(|o| o.id).collect::<Vec<_>>());
+        first.assert_hits(2);
+        second.assert_hits(2);
+    }
+}
@@ -0,0 +1,203 @@
+//! An optional circuit breaker around client calls. After enough failures
+//! in a row the circuit opens and calls fail at once with
+//! [`OrdersClientError::CircuitOpen`] instead of reaching a server that is
+//! down. Once the reset timeout has passed, a few probe calls go through;
+//! if they succeed the circuit closes again, and if any fails it reopens.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use web_time::Instant;
+
+use crate::OrdersClientError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failures that open the circuit.
+    pub failure_threshold: u32,
+    /// How long the circuit stays open before probing.
+    pub reset_timeout: Duration,
+    /// Calls let through while half-open; all must succeed to close.
+    pub half_open_probes: u32,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            reset_timeout: Duration::from_secs(30),
+            half_open_probes: 1,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum State {
+    Closed { failures: u32 },
+    Open { until: Instant },
+    HalfOpen { in_flight: u32, succeeded: u32 },
+}
+
+/// Only transport errors and `5xx` answers count as failures; a `4xx` is the
+/// caller's problem, not the server's.
+#[derive(Debug)]
+pub(crate) struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    state: Mutex<State>,
+}
+
+/// Leave to make one call. Dropping it without [`Permit::record`] (a
+/// cancelled call) frees its probe slot without judging the server.
+pub(crate) struct Permit<'a> {
+    breaker: &'a CircuitBreaker,
+    recorded: bool,
+}
+
+impl CircuitBreaker {
+    pub(crate) fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(State::Closed { failures: 0 }),
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().expect("circuit breaker poisoned")
+    }
+
+    pub(crate) fn acquire(&self) -> Result<Permit<'_>, OrdersClientError> {
+        let mut state = self.state();
+        match *state {
+            State::Closed { .. } => {}
+            State::Open { until } => {
+                let now = Instant::now();
+                if now < until {
+                    return Err(OrdersClientError::CircuitOpen {
+                        retry_after: until - now,
+                    });
+                }
+                *state = State::HalfOpen {
+                    in_flight: 1,
+                    succeeded: 0,
+                };
+            }
+            State::HalfOpen {
+                ref mut in_flight,
+                succeeded,
+            } => {
+                if *in_flight + succeeded >= self.config.half_open_probes {
+                    return Err(OrdersClientError::CircuitOpen {
+                        retry_after: Duration::ZERO,
+                    });
+                }
+                *in_flight += 1;
+            }
+        }
+        Ok(Permit {
+            breaker: self,
+            recorded: false,
+        })
+    }
+
+    fn trip(&self, state: &mut State) {
+        *state = State::Open {
+            until: Instant::now() + self.config.reset_timeout,
+        };
+    }
+}
+
+impl Permit<'_> {
+    pub(crate) fn record(mut self, ok: bool) {
+        self.recorded = true;
+        let breaker = self.breaker;
+        let mut state = breaker.state();
+        match *state {
+            State::Closed { ref mut failures } => {
+                if ok {
+                    *failures = 0;
+                } else {
+                    *failures += 1;
+                    if *failures >= breaker.config.failure_threshold {
+                        breaker.trip(&mut state);
+                    }
+                }
+            }
+            // A call that started before the circuit opened.
+            State::Open { .. } => {}
+            State::HalfOpen {
+                ref mut in_flight,
+                ref mut succeeded,
+            } => {
+                if !ok {
+                    breaker.trip(&mut state);
+                    return;
+                }
+                *in_flight = in_flight.saturating_sub(1);
+                *succeeded += 1;
+                if *succeeded >= breaker.config.half_open_probes {
+                    *state = State::Closed { failures: 0 };
+                }
+            }
+        }
+    }
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        if self.recorded {
+            return;
+        }
+        if let State::HalfOpen { in_flight, .. } = &mut *self.breaker.state() {
+            *in_flight = in_flight.saturating_sub(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker(threshold: u32, probes: u32) -> CircuitBreaker {
+        CircuitBreaker::new(CircuitBreakerConfig {
+            failure_threshold: threshold,
+            reset_timeout: Duration::from_millis(20),
+            half_open_probes: probes,
+        })
+    }
+
+    #[test]
+    fn opens_after_consecutive_failures_only() {
+        let b = breaker(3, 1);
+        for ok in [false, false, true, false, false] {
+            b.acquire().unwrap().record(ok);
+        }
+        b.acquire().unwrap().record(false);
+        let Err(OrdersClientError::CircuitOpen { retry_after }) = b.acquire() else {
+            panic!("circuit should be open");
+        };
+        assert!(retry_after <= Duration::from_millis(20));
+    }
+
+    #[test]
+    fn half_open_probes_close_or_reopen() {
+        let b = breaker(1, 2);
+        b.acquire().unwrap().record(false);
+        assert!(b.acquire().is_err());
+        std::thread::sleep(Duration::from_millis(25));
+
+        let first = b.acquire().unwrap();
+        let second = b.acquire().unwrap();
+        assert!(b.acquire().is_err(), "only two probes at a time");
+        first.record(true);
+        drop(second);
+        // The cancelled probe's slot is free again.
+        b.acquire().unwrap().record(true);
+        b.acquire().unwrap().record(true);
+        b.acquire().unwrap().record(false);
+        assert!(b.acquire().is_err(), "closed again, then tripped");
+
+        std::thread::sleep(Duration::from_millis(25));
+        b.acquire().unwrap().record(false);
+        assert!(b.acquire().is_err(), "a failed probe reopens at once");
+    }
+}
@@ -0,0 +1,253 @@
+//! [`InMemoryOrdersApi`]: the real [`OrderService`] over an [`InMemoryRepo`],
+//! called directly, for tests of code written against [`OrdersApi`]. It
+//! validates, prices and moves orders through their statuses exactly as the
+//! server does, and its errors carry the status and code the server would
+//! have sent.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use axum::response::IntoResponse;
+use orders_hex::application::order_service::{NewOrder, OrderService};
+use orders_hex::errors::AppError;
+use orders_repo::memory::InMemoryRepo;
+use orders_types::domain::filter::OrderFilter;
+use orders_types::domain::history::OrderHistoryEntry;
+use orders_types::domain::order::{Order, OrderStatus};
+use orders_types::domain::order_patch::OrderPatch;
+use orders_types::domain::tenant::TenantId;
+use uuid::Uuid;
+
+use crate::{ApiError, CreateOrderRequest, CreateOrderResponse, OrdersApi};
+
+#[derive(Clone)]
+pub struct InMemoryOrdersApi {
+    service: Arc<OrderService<InMemoryRepo>>,
+    tenant: TenantId,
+}
+
+impl Default for InMemoryOrdersApi {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InMemoryOrdersApi {
+    /// An empty store with the service's default pricing and no discounts,
+    /// acting for the default tenant.
+    pub fn new() -> Self {
+        Self::from_service(OrderService::new(InMemoryRepo::new()))
+    }
+
+    /// Use a service configured by the test, e.g. with discounts or
+    /// inventory.
+    pub fn from_service(service: OrderService<InMemoryRepo>) -> Self {
+        Self {
+            service: Arc::new(service),
+            tenant: TenantId::default(),
+        }
+    }
+
+    /// Act for `tenant`, like [`OrdersClientBuilder::with_tenant`](crate::OrdersClientBuilder::with_tenant).
+    pub fn with_tenant(mut self, tenant: TenantId) -> Self {
+        self.tenant = tenant;
+        self
+    }
+
+    /// The service behind the API, to seed or inspect state directly.
+    pub fn service(&self) -> &OrderService<InMemoryRepo> {
+        &self.service
+    }
+}
+
+/// `result` with its error turned into the [`ApiError`] an HTTP caller
+/// would decode from the response.
+async fn answer<T>(result: Result<T, AppError>) -> anyhow::Result<T> {
+    let err = match result {
+        Ok(value) => return Ok(value),
+        Err(err) => err,
+    };
+    let res = err.into_response();
+    let status = res.status();
+    let body = axum::body::to_bytes(res.into_body(), usize::MAX).await?;
+    Err(ApiError::from_body(status, &body).into())
+}
+
+async fn parse_id(id: &str) -> anyhow::Result<Uuid> {
+    answer(Uuid::parse_str(id).map_err(|e| AppError::BadRequest(e.to_string()))).await
+}
+
+#[async_trait]
+impl OrdersApi for InMemoryOrdersApi {
+    async fn create_order(&self, req: CreateOrderRequest) -> anyhow::Result<CreateOrderResponse> {
+        let new = NewOrder {
+            customer_name: req.customer_name,
+            email: req.email,
+            items: req.items,
+            discount_code: req.discount_code,
+            shipping_address: req.shipping_address,
+            billing_address: req.billing_address,
+            metadata: req.metadata,
+        };
+        let order = answer(self.service.place_order(&self.tenant, new).await).await?;
+        Ok(CreateOrderResponse {
+            id: order.id.to_string(),
+            order_number: order.order_number,
+            status: order.status,
+        })
+    }
+
+    async fn get_order(&self, id: &str) -> anyhow::Result<Order> {
+        answer(self.service.get_order_by_ref(&self.tenant, id).await).await
+    }
+
+    async fn list_orders_with(&self, filter: OrderFilter) -> anyhow::Result<Vec<Order>> {
+        let page = answer(self.service.list_page(&self.tenant, &filter).await).await?;
+        Ok(page.orders)
+    }
+
+    async fn update_status(&self, id: &str, status: OrderStatus) -> anyhow::Result<Order> {
+        let id = parse_id(id).await?;
+        answer(self.service.update_status(&self.tenant, id, status).await).await
+    }
+
+    async fn update_order(&self, id: &str, patch: &OrderPatch) -> anyhow::Result<Order> {
+        let id = parse_id(id).await?;
+        answer(self.service.patch_order(&self.tenant, id, patch).await).await
+    }
+
+    async fn order_history(&self, id: &str) -> anyhow::Result<Vec<OrderHistoryEntry>> {
+        let id = parse_id(id).await?;
+        answer(self.service.order_history(&self.tenant, id).await).await
+    }
+
+    async fn delete_order(&self, id: &str) -> anyhow::Result<()> {
+        let id = parse_id(id).await?;
+        answer(self.service.delete_order(&self.tenant, id).await).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use orders_hex::inbound::http::{HttpServer, HttpServerConfig};
+    use orders_types::domain::error_code::ErrorCode;
+    use orders_types::domain::money::Money;
+    use orders_types::domain::order::OrderItem;
+
+    use super::*;
+    use crate::OrdersClient;
+
+    fn code(err: &anyhow::Error) -> Option<(u16, ErrorCode)> {
+        err.downcast_ref::<ApiError>()
+            .map(|e| (e.status.as_u16(), e.code))
+    }
+
+    fn request() -> CreateOrderRequest {
+        CreateOrderRequest {
+            customer_name: "Ann".into(),
+            email: "ann@example.com".into(),
+            items: vec![OrderItem {
+                name: "Widget".into(),
+                qty: 2,
+                unit_price: Money::usd(500),
+                weight_grams: 0,
+                sku: None,
+                description: None,
+                metadata: Default::default(),
+                discount_cents: 0,
+            }],
+            discount_code: None,
+            shipping_address: None,
+            billing_address: None,
+            metadata: Default::default(),
+        }
+    }
+
+    /// What downstream code would do through the trait; both
+    /// implementations must agree on every step.
+    async fn scenario(api: &dyn OrdersApi) {
+        let created = api.create_order(request()).await.unwrap();
+        assert_eq!(created.status, OrderStatus::Pending);
+
+        let order = api.get_order(&created.id).await.unwrap();
+        assert_eq!(order.id.to_string(), created.id);
+        assert_eq!(api.list_orders().await.unwrap().len(), 1);
+        let shipped = OrderFilter::default().with_status(OrderStatus::Shipped);
+        assert!(api.list_orders_with(shipped).await.unwrap().is_empty());
+
+        let renamed = OrderPatch::new().with_customer_name("Renamed");
+        let patched = api.update_order(&created.id, &renamed).await.unwrap();
+        assert_eq!(patched.customer_name, "Renamed");
+        let err = api
+            .update_order(&created.id, &OrderPatch::new().with_email("nope"))
+            .await
+            .unwrap_err();
+        assert_eq!(code(&err), Some((422, ErrorCode::ValidationFailed)));
+
+        let updated = api
+            .update_status(&created.id, OrderStatus::Shipped)
+            .await
+            .unwrap();
+        assert_eq!(updated.status, OrderStatus::Shipped);
+        let err = api
+            .update_status(&created.id, OrderStatus::Pending)
+            .await
+            .unwrap_err();
+        assert_eq!(code(&err), Some((409, ErrorCode::InvalidTransition)));
+        assert_eq!(api.order_history(&created.id).await.unwrap().len(), 2);
+        let err = api.update_order(&created.id, &renamed).await.unwrap_err();
+        assert_eq!(code(&err), Some((409, ErrorCode::Conflict)));
+
+        let invalid = api
+            .create_order(CreateOrderRequest {
+                customer_name: String::new(),
+                email: "not-an-email".into(),
+                items: Vec::new(),
+                ..request()
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(code(&invalid), Some((422, ErrorCode::ValidationFailed)));
+
+        api.delete_order(&created.id).await.unwrap();
+        let err = api.get_order(&created.id).await.unwrap_err();
+        assert_eq!(code(&err), Some((404, ErrorCode::OrderNotFound)));
+        let err = api.get_order("not-a-uuid").await.unwrap_err();
+        assert_eq!(code(&err), Some((400, ErrorCode::BadRequest)));
+    }
+
+    #[tokio::test]
+    async fn in_memory_api_behaves_like_the_server() {
+        scenario(&InMemoryOrdersApi::new()).await;
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+        let server = HttpServer::new(
+            OrderService::new(InMemoryRepo::new()),
+            HttpServerConfig {
+                port: port.to_string(),
+                tls: None,
+                admin_addr: None,
+            },
+        )
+        .await
+        .unwrap();
+        tokio::spawn(async move { server.run().await.unwrap() });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        scenario(&OrdersClient::new(&format!("http://127.0.0.1:{port}")).unwrap()).await;
+    }
+
+    #[tokio::test]
+    async fn tenants_are_kept_apart() {
+        let api = InMemoryOrdersApi::new();
+        let other = api.clone().with_tenant(TenantId::parse("other").unwrap());
+        let created = api.create_order(request()).await.unwrap();
+        assert!(api.get_order(&created.id).await.is_ok());
+        let err = other.get_order(&created.id).await.unwrap_err();
+        assert_eq!(code(&err), Some((404, ErrorCode::OrderNotFound)));
+        assert!(other.list_orders().await.unwrap().is_empty());
+    }
+}
@@ -1,10 +1,48 @@
+pub mod api;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod circuit;
+#[cfg(feature = "in-memory")]
+pub mod in_memory;
+pub mod webhook;
+
+pub use api::OrdersApi;
+#[cfg(feature = "blocking")]
+pub use blocking::OrdersBlockingClient;
+pub use circuit::CircuitBreakerConfig;
+#[cfg(feature = "in-memory")]
+pub use in_memory::InMemoryOrdersApi;
+
+use std::collections::{BTreeMap, VecDeque};
+use std::future::Future;
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use anyhow::Context;
+use chrono::{DateTime, Utc};
+use circuit::CircuitBreaker;
+use futures_util::stream::{self, Stream, TryStreamExt};
+use orders_types::domain::address::Address;
+use orders_types::domain::correlation::RequestId;
+use orders_types::domain::error_code::ErrorCode;
+use orders_types::domain::filter::{OrderFilter, OrderPage, SortField, SortOrder};
+use orders_types::domain::history::OrderHistoryEntry;
 use orders_types::domain::order::{Order, OrderItem, OrderStatus};
-use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
-use reqwest::Url;
+use orders_types::domain::order_number::OrderNumber;
+use orders_types::domain::order_patch::OrderPatch;
+use orders_types::domain::request_signing::{RequestSignature, SignedRequest};
+use orders_types::domain::share::ShareToken;
+use orders_types::domain::tenant::TenantId;
+use reqwest::header::{
+    HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, CONTENT_TYPE, ETAG, IF_NONE_MATCH,
+};
+use reqwest::{Method, StatusCode, Url};
 use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
 
 #[derive(Clone)]
 pub struct OrdersClientBuilder {
@@ -12,12 +50,170 @@ pub struct OrdersClientBuilder {
     headers: HeaderMap,
     timeout: Option<Duration>,
     client: Option<reqwest::Client>,
+    cache_capacity: usize,
+    auth: Option<Auth>,
+    circuit_breaker: Option<CircuitBreakerConfig>,
+    #[cfg(unix)]
+    unix_socket: Option<PathBuf>,
 }
 
+/// What [`OrdersClientBuilder::with_token_provider`] calls for each request.
+pub type TokenFuture = Pin<Box<dyn Future<Output = anyhow::Result<String>> + Send>>;
+
+/// Credentials attached to every request. They are applied per request
+/// rather than as default headers so a provider's token can change between
+/// calls.
+#[derive(Clone)]
+enum Auth {
+    Header(HeaderName, HeaderValue),
+    Basic {
+        username: String,
+        password: String,
+    },
+    Provider(Arc<dyn Fn() -> TokenFuture + Send + Sync>),
+    /// Signed just before sending, once the body is known.
+    Hmac {
+        key_id: Uuid,
+        signing_key: String,
+    },
+}
+
+fn bearer(token: &str) -> anyhow::Result<HeaderValue> {
+    let mut value =
+        HeaderValue::from_str(&format!("Bearer {token}")).context("invalid bearer token")?;
+    value.set_sensitive(true);
+    Ok(value)
+}
+
+/// `Authorization` for a request to `url` signed as `key_id`. `body` is
+/// `None` for a streamed body, which can't be signed.
+fn signature(
+    key_id: Uuid,
+    signing_key: &str,
+    method: &Method,
+    url: &Url,
+    body: Option<&[u8]>,
+) -> anyhow::Result<HeaderValue> {
+    let body = body.context("a streamed request body can't be signed")?;
+    let req = SignedRequest {
+        method: method.as_str(),
+        path: url.path(),
+        query: url.query(),
+        body,
+    };
+    let header = RequestSignature::sign(key_id, signing_key, &req, Utc::now()).header_value();
+    let mut value = HeaderValue::from_str(&header).context("invalid request signature")?;
+    value.set_sensitive(true);
+    Ok(value)
+}
+
+/// Version of the server's routes this client speaks.
+const API_VERSION: &str = "v1";
+
 #[derive(Clone)]
 pub struct OrdersClient {
     base: Url,
     client: reqwest::Client,
+    /// Send a fresh `X-Request-Id` with each call; off when the caller set
+    /// one of their own.
+    tag_requests: bool,
+    cache: Option<Arc<ResponseCache>>,
+    auth: Option<Auth>,
+    /// Shared by clones, so they trip together.
+    breaker: Option<Arc<CircuitBreaker>>,
+    /// The browser's `fetch` has no client-wide timeout, so it is set on
+    /// each request.
+    #[cfg(target_arch = "wasm32")]
+    timeout: Option<Duration>,
+    call: CallOptions,
+}
+
+/// Settings for particular calls, layered over the builder's; see
+/// [`OrdersClient::with_call_options`].
+#[derive(Debug, Clone, Default)]
+pub struct CallOptions {
+    timeout: Option<Duration>,
+    headers: HeaderMap,
+    cancel: Option<CancellationToken>,
+}
+
+impl CallOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the builder's timeout, shorter or longer.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sent with the call, replacing a default header or credential of the
+    /// same name.
+    pub fn with_header(mut self, name: &str, value: &str) -> anyhow::Result<Self> {
+        self.headers.insert(
+            HeaderName::from_bytes(name.as_bytes())?,
+            HeaderValue::from_str(value)?,
+        );
+        Ok(self)
+    }
+
+    /// Stop waiting once `token` is cancelled; the call then fails with
+    /// [`OrdersClientError::Cancelled`]. The server may still have acted on
+    /// a request that was already sent.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+}
+
+/// Bodies of recent reads by URL, with the ETag they came with. A cached
+/// read is revalidated with `If-None-Match`, so an unchanged resource costs
+/// an empty `304`.
+struct ResponseCache {
+    capacity: usize,
+    /// Oldest first.
+    entries: Mutex<VecDeque<CachedBody>>,
+}
+
+struct CachedBody {
+    url: String,
+    etag: HeaderValue,
+    body: Vec<u8>,
+}
+
+impl ResponseCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    fn etag(&self, url: &str) -> Option<HeaderValue> {
+        let entries = self.entries.lock().expect("response cache poisoned");
+        entries
+            .iter()
+            .find(|e| e.url == url)
+            .map(|e| e.etag.clone())
+    }
+
+    fn body(&self, url: &str) -> Option<Vec<u8>> {
+        let entries = self.entries.lock().expect("response cache poisoned");
+        entries
+            .iter()
+            .find(|e| e.url == url)
+            .map(|e| e.body.clone())
+    }
+
+    fn put(&self, url: String, etag: HeaderValue, body: Vec<u8>) {
+        let mut entries = self.entries.lock().expect("response cache poisoned");
+        entries.retain(|e| e.url != url);
+        if entries.len() >= self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(CachedBody { url, etag, body });
+    }
 }
 
 impl OrdersClient {
@@ -25,18 +221,159 @@ impl OrdersClient {
         Self::builder(base_url)?.build()
     }
 
+    /// `base_url` may carry a path prefix (`http://gw/orders-api`); with or
+    /// without a trailing slash, every call is made below it.
     pub fn builder(base_url: &str) -> anyhow::Result<OrdersClientBuilder> {
-        let base = Url::parse(base_url).context("invalid base url")?;
         Ok(OrdersClientBuilder {
-            base,
+            base: normalize_base(base_url)?,
             headers: HeaderMap::new(),
             timeout: None,
             client: None,
+            cache_capacity: 0,
+            auth: None,
+            circuit_breaker: None,
+            #[cfg(unix)]
+            unix_socket: None,
         })
     }
 
-    fn url(&self, path: &str) -> anyhow::Result<Url> {
-        self.base.join(path).context("failed to join url")
+    /// A client that makes its calls with `options`; it shares the
+    /// connection pool, cache and circuit breaker with this one.
+    ///
+    /// ```no_run
+    /// # async fn f(client: orders_client::OrdersClient) -> anyhow::Result<()> {
+    /// use std::time::Duration;
+    /// use orders_client::CallOptions;
+    ///
+    /// let quick = client.with_call_options(CallOptions::new().with_timeout(Duration::from_millis(200)));
+    /// let order = quick.get_order("42").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_call_options(&self, options: CallOptions) -> Self {
+        Self {
+            call: options,
+            ..self.clone()
+        }
+    }
+
+    fn url(&self, segments: &[&str]) -> anyhow::Result<Url> {
+        api_url(&self.base, segments)
+    }
+
+    /// A request to `url` with the configured credentials, tagged so the
+    /// server's logs and error body for it can be found by its request id.
+    async fn request(&self, method: Method, url: Url) -> anyhow::Result<reqwest::RequestBuilder> {
+        let mut req = self.client.request(method, url);
+        if self.tag_requests {
+            req = req.header(RequestId::HEADER, RequestId::new().as_str());
+        }
+        let timeout = self.call.timeout;
+        #[cfg(target_arch = "wasm32")]
+        let timeout = timeout.or(self.timeout);
+        if let Some(t) = timeout {
+            req = req.timeout(t);
+        }
+        req = match &self.auth {
+            None => req,
+            Some(Auth::Header(name, value)) => req.header(name, value),
+            Some(Auth::Basic { username, password }) => req.basic_auth(username, Some(password)),
+            Some(Auth::Provider(provider)) => {
+                let token = provider().await.context("token provider failed")?;
+                req.header(AUTHORIZATION, bearer(&token)?)
+            }
+            Some(Auth::Hmac { .. }) => req,
+        };
+        Ok(req.headers(self.call.headers.clone()))
+    }
+
+    /// Sign `req` if this client signs its requests.
+    fn sign(&self, req: reqwest::RequestBuilder) -> anyhow::Result<reqwest::RequestBuilder> {
+        let Some(Auth::Hmac {
+            key_id,
+            signing_key,
+        }) = &self.auth
+        else {
+            return Ok(req);
+        };
+        let mut req = req.build()?;
+        let body = req.body().map_or(Some(&[][..]), reqwest::Body::as_bytes);
+        let value = signature(*key_id, signing_key, req.method(), req.url(), body)?;
+        req.headers_mut().insert(AUTHORIZATION, value);
+        Ok(reqwest::RequestBuilder::from_parts(
+            self.client.clone(),
+            req,
+        ))
+    }
+
+    /// Send `req`, giving up if the call is cancelled before the response
+    /// arrives.
+    async fn send(&self, req: reqwest::RequestBuilder) -> anyhow::Result<reqwest::Response> {
+        let req = self.sign(req)?;
+        match &self.call.cancel {
+            None => self.send_through_breaker(req).await,
+            Some(token) => token
+                .run_until_cancelled(self.send_through_breaker(req))
+                .await
+                .unwrap_or_else(|| Err(OrdersClientError::Cancelled.into())),
+        }
+    }
+
+    /// Send `req` through the circuit breaker, if there is one. A cancelled
+    /// call counts as neither success nor failure.
+    async fn send_through_breaker(
+        &self,
+        req: reqwest::RequestBuilder,
+    ) -> anyhow::Result<reqwest::Response> {
+        let Some(breaker) = &self.breaker else {
+            return Ok(req.send().await?);
+        };
+        let permit = breaker.acquire()?;
+        let res = req.send().await;
+        permit.record(res.as_ref().is_ok_and(|r| !r.status().is_server_error()));
+        Ok(res?)
+    }
+
+    /// Send the GET `req`, answering from the cache when the server says the
+    /// cached body is still current.
+    async fn read(&self, req: reqwest::RequestBuilder) -> anyhow::Result<Vec<u8>> {
+        let Some(cache) = &self.cache else {
+            let res = self.send(req).await?.api_result().await?;
+            return Ok(res.bytes().await?.to_vec());
+        };
+        let mut req = req.build()?;
+        let url = req.url().to_string();
+        if let Some(etag) = cache.etag(&url) {
+            req.headers_mut().insert(IF_NONE_MATCH, etag);
+        }
+        let res = self
+            .send(reqwest::RequestBuilder::from_parts(
+                self.client.clone(),
+                req,
+            ))
+            .await?;
+        if res.status() == StatusCode::NOT_MODIFIED {
+            if let Some(body) = cache.body(&url) {
+                return Ok(body);
+            }
+        }
+        let res = res.api_result().await?;
+        let etag = res.headers().get(ETAG).cloned();
+        let body = res.bytes().await?.to_vec();
+        if let Some(etag) = etag {
+            cache.put(url, etag, body.clone());
+        }
+        Ok(body)
+    }
+
+    /// [`create_order`](Self::create_order) with `options` for this call
+    /// only.
+    pub async fn create_order_with_opts(
+        &self,
+        req: CreateOrderRequest,
+        options: CallOptions,
+    ) -> anyhow::Result<CreateOrderResponse> {
+        self.with_call_options(options).create_order(req).await
     }
 
     pub async fn create_order(
@@ -44,56 +381,224 @@ impl OrdersClient {
         req: CreateOrderRequest,
     ) -> anyhow::Result<CreateOrderResponse> {
         let res = self
-            .client
-            .post(self.url("orders")?)
-            .json(&req)
-            .send()
+            .send(
+                self.request(Method::POST, self.url(&["orders"])?)
+                    .await?
+                    .json(&req),
+            )
             .await?
-            .error_for_status()?;
+            .api_result()
+            .await?;
         Ok(res.json().await?)
     }
 
     pub async fn get_order(&self, id: &str) -> anyhow::Result<Order> {
+        let body = self
+            .read(
+                self.request(Method::GET, self.url(&["orders", id])?)
+                    .await?,
+            )
+            .await?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    pub async fn list_orders(&self) -> anyhow::Result<Vec<Order>> {
+        self.list_orders_with(OrderFilter::default()).await
+    }
+
+    /// List orders matching `filter`, encoded exactly as the server decodes it.
+    pub async fn list_orders_with(&self, filter: OrderFilter) -> anyhow::Result<Vec<Order>> {
+        let body = self
+            .read(
+                self.request(Method::GET, self.url(&["orders"])?)
+                    .await?
+                    .query(&filter)
+                    .query(&filter.metadata_params()),
+            )
+            .await?;
+        Ok(serde_json::from_slice::<OrderPage>(&body)?.orders)
+    }
+
+    /// One page of `query`.
+    pub async fn list_orders_query(&self, query: &ListOrdersQuery) -> anyhow::Result<Vec<Order>> {
+        self.list_orders_with(query.to_filter()).await
+    }
+
+    /// Every order matching `query`, fetched a page at a time from `query`'s
+    /// page onwards until one comes back short. Without a sort the pages go
+    /// oldest first, so orders created meanwhile land at the end instead of
+    /// shifting earlier pages.
+    pub async fn list_all(&self, query: ListOrdersQuery) -> anyhow::Result<Vec<Order>> {
+        self.orders_pages(query).try_concat().await
+    }
+
+    /// The pages [`Self::list_all`] would fetch, each requested only when
+    /// the previous one has been consumed. Empty pages are skipped, and the
+    /// stream ends after the first error.
+    pub fn orders_pages(
+        &self,
+        query: ListOrdersQuery,
+    ) -> impl Stream<Item = anyhow::Result<Vec<Order>>> + '_ {
+        stream::unfold(Some(query.for_paging()), move |next| async move {
+            let mut query = next?;
+            match self.list_orders_query(&query).await {
+                Ok(page) if page.is_empty() => None,
+                Ok(page) => {
+                    let more = page.len() >= query.per_page();
+                    query.page = Some(query.page() + 1);
+                    Some((Ok(page), more.then_some(query)))
+                }
+                Err(e) => Some((Err(e), None)),
+            }
+        })
+    }
+
+    /// [`Self::orders_pages`] one order at a time.
+    pub fn orders_stream(
+        &self,
+        query: ListOrdersQuery,
+    ) -> impl Stream<Item = anyhow::Result<Order>> + '_ {
+        self.orders_pages(query)
+            .map_ok(|page| stream::iter(page.into_iter().map(Ok)))
+            .try_flatten()
+    }
+
+    pub async fn update_status(&self, id: &str, status: OrderStatus) -> anyhow::Result<Order> {
         let res = self
-            .client
-            .get(self.url(&format!("orders/{id}"))?)
-            .send()
+            .send(
+                self.request(Method::PATCH, self.url(&["orders", id, "status"])?)
+                    .await?
+                    .json(&UpdateStatusRequest { status }),
+            )
             .await?
-            .error_for_status()?;
+            .api_result()
+            .await?;
         Ok(res.json().await?)
     }
 
-    pub async fn list_orders(&self) -> anyhow::Result<Vec<Order>> {
+    /// Change an order's customer details or addresses; see [`OrderPatch`].
+    pub async fn update_order(&self, id: &str, patch: &OrderPatch) -> anyhow::Result<Order> {
         let res = self
-            .client
-            .get(self.url("orders")?)
-            .send()
+            .send(
+                self.request(Method::PATCH, self.url(&["orders", id])?)
+                    .await?
+                    .header(CONTENT_TYPE, OrderPatch::CONTENT_TYPE)
+                    .body(serde_json::to_vec(patch)?),
+            )
             .await?
-            .error_for_status()?;
+            .api_result()
+            .await?;
         Ok(res.json().await?)
     }
 
-    pub async fn update_status(&self, id: &str, status: OrderStatus) -> anyhow::Result<Order> {
+    /// The order's status changes, oldest first.
+    pub async fn order_history(&self, id: &str) -> anyhow::Result<Vec<OrderHistoryEntry>> {
         let res = self
-            .client
-            .patch(self.url(&format!("orders/{id}/status"))?)
-            .json(&UpdateStatusRequest { status })
-            .send()
+            .send(
+                self.request(Method::GET, self.url(&["orders", id, "history"])?)
+                    .await?,
+            )
             .await?
-            .error_for_status()?;
+            .api_result()
+            .await?;
         Ok(res.json().await?)
     }
 
-    pub async fn delete_order(&self, id: &str) -> anyhow::Result<()> {
-        self.client
-            .delete(self.url(&format!("orders/{id}"))?)
-            .send()
+    /// Ask the server to mint a read-only share link; `ttl_secs` defaults to
+    /// the server's choice.
+    pub async fn create_share_link(
+        &self,
+        id: &str,
+        ttl_secs: Option<i64>,
+    ) -> anyhow::Result<ShareLink> {
+        let res = self
+            .send(
+                self.request(Method::POST, self.url(&["orders", id, "share"])?)
+                    .await?
+                    .json(&CreateShareLinkRequest { ttl_secs }),
+            )
+            .await?
+            .api_result()
+            .await?;
+        Ok(res.json().await?)
+    }
+
+    /// Absolute URL for a share token, e.g. one minted offline with
+    /// [`orders_types::domain::share::ShareSigner`].
+    pub fn share_url(&self, id: &str, token: &ShareToken) -> anyhow::Result<Url> {
+        share_url(&self.base, id, token)
+    }
+
+    /// Fetch an order through a share token; no credentials are needed.
+    pub async fn get_shared_order(&self, id: &str, token: &ShareToken) -> anyhow::Result<Order> {
+        let res = self
+            .send(
+                self.request(Method::GET, self.share_url(id, token)?)
+                    .await?,
+            )
             .await?
-            .error_for_status()?;
+            .api_result()
+            .await?;
+        Ok(res.json().await?)
+    }
+
+    pub async fn delete_order(&self, id: &str) -> anyhow::Result<()> {
+        self.send(
+            self.request(Method::DELETE, self.url(&["orders", id])?)
+                .await?,
+        )
+        .await?
+        .api_result()
+        .await?;
         Ok(())
     }
 }
 
+/// `segments` appended to the base path and API version, each
+/// percent-encoded so an id can never reach another path (`a/b` is sent as
+/// `a%2Fb`).
+fn api_url(base: &Url, segments: &[&str]) -> anyhow::Result<Url> {
+    if let Some(bad) = segments.iter().find(|s| matches!(**s, "" | "." | "..")) {
+        anyhow::bail!("invalid path segment `{bad}`");
+    }
+    let mut url = base.clone();
+    url.path_segments_mut()
+        .map_err(|_| anyhow::anyhow!("base url cannot carry a path"))?
+        .pop_if_empty()
+        .push(API_VERSION)
+        .extend(segments);
+    Ok(url)
+}
+
+fn share_url(base: &Url, id: &str, token: &ShareToken) -> anyhow::Result<Url> {
+    let mut url = api_url(base, &["orders", id])?;
+    url.query_pairs_mut()
+        .append_pair("exp", &token.exp.to_string())
+        .append_pair("sig", &token.sig);
+    if let Some(tenant) = &token.tenant {
+        url.query_pairs_mut().append_pair("tenant", tenant.as_str());
+    }
+    Ok(url)
+}
+
+/// Parse `base_url` into a base that paths can be appended to: http(s) only,
+/// no query or fragment (they would be silently dropped), and a trailing
+/// slash so `http://host/api` keeps its `/api`.
+fn normalize_base(base_url: &str) -> anyhow::Result<Url> {
+    let mut base = Url::parse(base_url).context("invalid base url")?;
+    if !matches!(base.scheme(), "http" | "https") {
+        anyhow::bail!("base url must be http or https, got `{base_url}`");
+    }
+    if base.query().is_some() || base.fragment().is_some() {
+        anyhow::bail!("base url must not have a query or fragment, got `{base_url}`");
+    }
+    if !base.path().ends_with('/') {
+        let path = format!("{}/", base.path());
+        base.set_path(&path);
+    }
+    Ok(base)
+}
+
 impl OrdersClientBuilder {
     pub fn with_timeout(mut self, timeout: Duration) -> Self {
         self.timeout = Some(timeout);
@@ -112,47 +617,390 @@ impl OrdersClientBuilder {
         Ok(self)
     }
 
+    /// Send `Authorization: Bearer <token>` on every request. Like the other
+    /// auth helpers, replaces any credentials set before.
+    pub fn with_bearer_token(mut self, token: impl AsRef<str>) -> anyhow::Result<Self> {
+        self.auth = Some(Auth::Header(AUTHORIZATION, bearer(token.as_ref())?));
+        Ok(self)
+    }
+
+    /// HTTP Basic credentials on every request.
+    pub fn with_basic_auth(
+        mut self,
+        username: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Self {
+        self.auth = Some(Auth::Basic {
+            username: username.into(),
+            password: password.into(),
+        });
+        self
+    }
+
+    /// Authenticate with a key minted by the server's `/admin/api-keys`,
+    /// sent as `X-Api-Key`.
+    pub fn with_api_key(mut self, key: impl AsRef<str>) -> anyhow::Result<Self> {
+        let mut value = HeaderValue::from_str(key.as_ref()).context("invalid api key")?;
+        value.set_sensitive(true);
+        self.auth = Some(Auth::Header(HeaderName::from_static("x-api-key"), value));
+        Ok(self)
+    }
+
+    /// Sign every request with API key `key_id` and the `signing_key` it
+    /// was minted with, in the `Authorization` header, instead of sending
+    /// the key. The signature covers the method, path, query, body and
+    /// time, so a captured request can't be altered or sent again, and the
+    /// secret never goes over the wire.
+    pub fn with_hmac_credentials(
+        mut self,
+        key_id: impl AsRef<str>,
+        signing_key: impl AsRef<str>,
+    ) -> anyhow::Result<Self> {
+        self.auth = Some(Auth::Hmac {
+            key_id: Uuid::parse_str(key_id.as_ref()).context("invalid api key id")?,
+            signing_key: signing_key.as_ref().to_string(),
+        });
+        Ok(self)
+    }
+
+    /// Ask `provider` for a bearer token before every request, for tokens
+    /// that expire. The provider decides when to refresh; it is called often,
+    /// so it should hand back a cached token while that one is still good. A
+    /// failure fails the request without sending it.
+    pub fn with_token_provider<F, Fut>(mut self, provider: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = anyhow::Result<String>> + Send + 'static,
+    {
+        self.auth = Some(Auth::Provider(Arc::new(move || {
+            Box::pin(provider()) as TokenFuture
+        })));
+        self
+    }
+
+    /// Act for `tenant` by sending `X-Tenant-Id` on every request.
+    pub fn with_tenant(self, tenant: &TenantId) -> anyhow::Result<Self> {
+        self.with_header("x-tenant-id", tenant.as_str())
+    }
+
+    /// Connect through the unix socket at `path` (e.g. a sidecar proxy)
+    /// instead of TCP. The base URL still supplies the scheme, `Host` header
+    /// and path prefix, e.g. `http://orders.local/`.
+    #[cfg(unix)]
+    pub fn with_unix_socket(mut self, path: impl Into<PathBuf>) -> Self {
+        self.unix_socket = Some(path.into());
+        self
+    }
+
+    /// Keep the bodies of up to `capacity` order and list reads and
+    /// revalidate them with their ETags instead of downloading them again.
+    pub fn with_cache(mut self, capacity: usize) -> Self {
+        self.cache_capacity = capacity;
+        self
+    }
+
+    /// Fail fast with [`OrdersClientError::CircuitOpen`] once the server
+    /// keeps failing; see [`CircuitBreakerConfig`].
+    pub fn with_circuit_breaker(mut self, config: CircuitBreakerConfig) -> Self {
+        self.circuit_breaker = Some(config);
+        self
+    }
+
+    /// Use `client` as is; timeout, headers and unix socket settings on this
+    /// builder are then ignored, but credentials and the circuit breaker
+    /// still apply.
     pub fn with_reqwest_client(mut self, client: reqwest::Client) -> Self {
         self.client = Some(client);
         self
     }
 
     pub fn build(self) -> anyhow::Result<OrdersClient> {
+        let cache =
+            (self.cache_capacity > 0).then(|| Arc::new(ResponseCache::new(self.cache_capacity)));
+        let breaker = self
+            .circuit_breaker
+            .map(|config| Arc::new(CircuitBreaker::new(config)));
         if let Some(client) = self.client {
             return Ok(OrdersClient {
                 base: self.base,
                 client,
+                tag_requests: true,
+                cache,
+                auth: self.auth,
+                breaker,
+                #[cfg(target_arch = "wasm32")]
+                timeout: self.timeout,
+                call: CallOptions::default(),
             });
         }
 
+        let tag_requests = !self.headers.contains_key(RequestId::HEADER);
         let mut builder = reqwest::Client::builder();
         if !self.headers.is_empty() {
             builder = builder.default_headers(self.headers);
         }
+        #[cfg(not(target_arch = "wasm32"))]
         if let Some(t) = self.timeout {
             builder = builder.timeout(t);
         }
+        #[cfg(unix)]
+        if let Some(path) = self.unix_socket {
+            builder = builder.unix_socket(path);
+        }
         let client = builder.build()?;
         Ok(OrdersClient {
             base: self.base,
             client,
+            tag_requests,
+            cache,
+            auth: self.auth,
+            breaker,
+            #[cfg(target_arch = "wasm32")]
+            timeout: self.timeout,
+            call: CallOptions::default(),
         })
     }
 }
 
+/// A non-2xx answer, decoded from the server's error body. Every call returns
+/// it inside its `anyhow::Error`, so callers can branch on the code:
+///
+/// ```no_run
+/// # async fn f(client: orders_client::OrdersClient) {
+/// use orders_client::ApiError;
+/// use orders_types::domain::error_code::ErrorCode;
+///
+/// if let Err(e) = client.get_order("42").await {
+///     if e.downcast_ref::<ApiError>().map(|e| e.code) == Some(ErrorCode::OrderNotFound) {
+///         // create it instead
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ApiError {
+    pub status: StatusCode,
+    /// [`ErrorCode::Unknown`] when the body is not a recognised error body.
+    pub code: ErrorCode,
+    pub message: String,
+    /// The server's correlation id for the failed request, for support.
+    pub request_id: Option<String>,
+    pub details: Option<serde_json::Value>,
+}
+
+impl ApiError {
+    fn from_body(status: StatusCode, body: &[u8]) -> Self {
+        #[derive(Deserialize)]
+        struct Body {
+            error: String,
+            code: Option<ErrorCode>,
+            request_id: Option<String>,
+            details: Option<serde_json::Value>,
+        }
+        match serde_json::from_slice::<Body>(body) {
+            Ok(b) => Self {
+                status,
+                code: b.code.unwrap_or(ErrorCode::Unknown),
+                message: b.error,
+                request_id: b.request_id,
+                details: b.details,
+            },
+            Err(_) => Self {
+                status,
+                code: ErrorCode::Unknown,
+                message: String::from_utf8_lossy(body).into_owned(),
+                request_id: None,
+                details: None,
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}: {}", self.status, self.code, self.message)
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+/// A call the client refused to make, returned inside the `anyhow::Error`
+/// like [`ApiError`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum OrdersClientError {
+    /// The circuit breaker is open after repeated failures, so the call was
+    /// not sent; it may be let through after `retry_after`.
+    CircuitOpen { retry_after: Duration },
+    /// The call's [`CallOptions::with_cancellation`] token was cancelled
+    /// before the response arrived.
+    Cancelled,
+}
+
+impl std::fmt::Display for OrdersClientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CircuitOpen { retry_after } => write!(
+                f,
+                "circuit open after repeated failures; retry in {}ms",
+                retry_after.as_millis()
+            ),
+            Self::Cancelled => f.write_str("call cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for OrdersClientError {}
+
+trait ResponseExt: Sized {
+    /// Pass 2xx responses through; turn anything else into an [`ApiError`].
+    async fn api_result(self) -> anyhow::Result<Self>;
+}
+
+impl ResponseExt for reqwest::Response {
+    async fn api_result(self) -> anyhow::Result<Self> {
+        let status = self.status();
+        if status.is_success() {
+            return Ok(self);
+        }
+        let body = self.bytes().await?;
+        Err(ApiError::from_body(status, &body).into())
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct CreateOrderRequest {
     pub customer_name: String,
     pub email: String,
     pub items: Vec<OrderItem>,
+    /// Discount code to take off the order total.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub discount_code: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shipping_address: Option<Address>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub billing_address: Option<Address>,
+    /// Key/value references of the caller's own, e.g. an ERP id.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct CreateOrderResponse {
     pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order_number: Option<OrderNumber>,
     pub status: OrderStatus,
 }
 
+/// A server-minted share link; `path` is relative to the server root.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ShareLink {
+    pub path: String,
+    pub exp: i64,
+}
+
+/// Which orders to list and which page of them, for
+/// [`OrdersClient::list_orders_query`] and [`OrdersClient::list_all`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ListOrdersQuery {
+    filter: OrderFilter,
+    sort: Option<(SortField, SortOrder)>,
+    page: Option<usize>,
+    per_page: Option<usize>,
+}
+
+impl ListOrdersQuery {
+    pub const DEFAULT_PER_PAGE: usize = 100;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_status(mut self, status: OrderStatus) -> Self {
+        self.filter.status = Some(status);
+        self
+    }
+
+    pub fn with_email(mut self, email: impl Into<String>) -> Self {
+        self.filter.email = Some(email.into());
+        self
+    }
+
+    pub fn with_country(mut self, country: impl Into<String>) -> Self {
+        self.filter.country = Some(country.into());
+        self
+    }
+
+    /// Orders created at or after `at`.
+    pub fn with_created_after(mut self, at: DateTime<Utc>) -> Self {
+        self.filter.created_after = Some(at);
+        self
+    }
+
+    /// Orders created strictly before `at`.
+    pub fn with_created_before(mut self, at: DateTime<Utc>) -> Self {
+        self.filter.created_before = Some(at);
+        self
+    }
+
+    /// Orders whose metadata has `key` set to `value`; repeat to require
+    /// several entries.
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.filter = self.filter.with_metadata(key, value);
+        self
+    }
+
+    pub fn with_sort(mut self, field: SortField, order: SortOrder) -> Self {
+        self.sort = Some((field, order));
+        self
+    }
+
+    /// 1-based; the first page when unset.
+    pub fn with_page(mut self, page: usize) -> Self {
+        self.page = Some(page.max(1));
+        self
+    }
+
+    /// At least 1; [`Self::DEFAULT_PER_PAGE`] when unset.
+    pub fn with_per_page(mut self, per_page: usize) -> Self {
+        self.per_page = Some(per_page.max(1));
+        self
+    }
+
+    /// Oldest first unless a sort was chosen, so pages stay put while new
+    /// orders arrive.
+    fn for_paging(mut self) -> Self {
+        self.sort
+            .get_or_insert((SortField::CreatedAt, SortOrder::Asc));
+        self
+    }
+
+    fn page(&self) -> usize {
+        self.page.unwrap_or(1)
+    }
+
+    fn per_page(&self) -> usize {
+        self.per_page.unwrap_or(Self::DEFAULT_PER_PAGE)
+    }
+
+    /// The query as the server's `limit`/`offset` filter.
+    pub fn to_filter(&self) -> OrderFilter {
+        let mut filter = self.filter.clone();
+        if let Some((field, order)) = self.sort {
+            filter = filter.with_sort(field, order);
+        }
+        filter
+            .with_limit(self.per_page())
+            .with_offset((self.page() - 1).saturating_mul(self.per_page()))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct CreateShareLinkRequest {
+    ttl_secs: Option<i64>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 struct UpdateStatusRequest {
     status: OrderStatus,
@@ -161,25 +1009,124 @@ struct UpdateStatusRequest {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use futures_util::StreamExt;
     use httpmock::prelude::*;
+    use orders_types::domain::money::Money;
 
-    fn sample_order() -> Order {
+    pub(crate) fn page(orders: Vec<Order>) -> OrderPage {
+        OrderPage {
+            orders,
+            sort: None,
+            limit: None,
+            offset: 0,
+        }
+    }
+
+    pub(crate) fn sample_order() -> Order {
         Order {
             id: uuid::Uuid::new_v4(),
+            order_number: None,
+            tenant_id: TenantId::default(),
             customer_name: "User".into(),
             email: "user@example.com".into(),
             items: vec![OrderItem {
                 name: "Widget".into(),
                 qty: 1,
-                unit_price_cents: 500,
+                unit_price: Money::usd(500),
+                weight_grams: 0,
+                sku: None,
+                description: None,
+                metadata: Default::default(),
+                discount_cents: 0,
             }],
-            total_cents: 500,
+            total: Money::usd(500),
+            charges: Default::default(),
+            discount: None,
             status: OrderStatus::Pending,
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
+            pricing: None,
+            payment_id: None,
+            captured: None,
+            cancellation: None,
+            shipping_address: None,
+            billing_address: None,
+            metadata: Default::default(),
+            created_by: None,
+            updated_by: None,
         }
     }
 
+    #[test]
+    fn urls_stay_below_the_base_path() {
+        let cases = [
+            (
+                "http://127.0.0.1:8080",
+                "http://127.0.0.1:8080/v1/orders/42",
+            ),
+            (
+                "http://127.0.0.1:8080/",
+                "http://127.0.0.1:8080/v1/orders/42",
+            ),
+            (
+                "https://gw.example/api",
+                "https://gw.example/api/v1/orders/42",
+            ),
+            (
+                "https://gw.example/api/",
+                "https://gw.example/api/v1/orders/42",
+            ),
+            ("http://gw/a/b//", "http://gw/a/b//v1/orders/42"),
+        ];
+        for (base, expected) in cases {
+            let client = OrdersClient::new(base).unwrap();
+            assert_eq!(client.url(&["orders", "42"]).unwrap().as_str(), expected);
+        }
+    }
+
+    #[test]
+    fn ids_are_encoded_as_single_segments() {
+        let client = OrdersClient::new("http://h/api").unwrap();
+        let url = client.url(&["orders", "a/b?c#d e", "status"]).unwrap();
+        assert_eq!(
+            url.as_str(),
+            "http://h/api/v1/orders/a%2Fb%3Fc%23d%20e/status"
+        );
+        assert!(client.url(&["orders", ".."]).is_err());
+        assert!(client.url(&["orders", ""]).is_err());
+    }
+
+    #[test]
+    fn rejects_bases_that_would_lose_parts() {
+        assert!(OrdersClient::new("http://h/api?x=1").is_err());
+        assert!(OrdersClient::new("http://h/#frag").is_err());
+        assert!(OrdersClient::new("mailto:ops@example.com").is_err());
+        assert!(OrdersClient::new("not a url").is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn talks_to_a_unix_socket_sidecar() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("orders.sock");
+        let order = sample_order();
+        let served = order.clone();
+        let app = axum::Router::new().route(
+            "/api/v1/orders/{id}",
+            axum::routing::get(move || async move { axum::Json(served) }),
+        );
+        let listener = tokio::net::UnixListener::bind(&socket).unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let client = OrdersClient::builder("http://orders.local/api")
+            .unwrap()
+            .with_unix_socket(&socket)
+            .build()
+            .unwrap();
+        let fetched = client.get_order(&order.id.to_string()).await.unwrap();
+        assert_eq!(fetched.id, order.id);
+    }
+
     #[tokio::test]
     async fn create_and_get_order() {
         let server = MockServer::start();
@@ -187,20 +1134,25 @@ mod tests {
 
         let create_mock = server.mock(|when, then| {
             when.method(POST)
-                .path("/orders")
+                .path("/v1/orders")
                 .json_body_obj(&CreateOrderRequest {
                     customer_name: order.customer_name.clone(),
                     email: order.email.clone(),
                     items: order.items.clone(),
+                    discount_code: None,
+                    shipping_address: None,
+                    billing_address: None,
+                    metadata: Default::default(),
                 });
             then.status(201).json_body_obj(&CreateOrderResponse {
                 id: order.id.to_string(),
+                order_number: None,
                 status: OrderStatus::Pending,
             });
         });
 
         let get_mock: httpmock::Mock<'_> = server.mock(|when, then| {
-            when.method(GET).path(format!("/orders/{}", order.id));
+            when.method(GET).path(format!("/v1/orders/{}", order.id));
             then.status(200).json_body_obj(&order);
         });
 
@@ -210,6 +1162,10 @@ mod tests {
                 customer_name: order.customer_name.clone(),
                 email: order.email.clone(),
                 items: order.items.clone(),
+                discount_code: None,
+                shipping_address: None,
+                billing_address: None,
+                metadata: Default::default(),
             })
             .await
             .unwrap();
@@ -223,19 +1179,400 @@ mod tests {
         get_mock.assert();
     }
 
+    #[tokio::test]
+    async fn error_bodies_surface_as_typed_api_errors() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/v1/orders/missing");
+            then.status(404).json_body(serde_json::json!({
+                "error": "order missing",
+                "code": "ORDER_NOT_FOUND",
+                "request_id": "req-1",
+            }));
+        });
+        server.mock(|when, then| {
+            when.method(DELETE).path("/v1/orders/gone");
+            then.status(502).body("bad gateway");
+        });
+
+        let client = OrdersClient::new(&server.base_url()).unwrap();
+        let err = client.get_order("missing").await.unwrap_err();
+        let api = err.downcast_ref::<ApiError>().unwrap();
+        assert_eq!(api.status, StatusCode::NOT_FOUND);
+        assert_eq!(api.code, ErrorCode::OrderNotFound);
+        assert_eq!(api.request_id.as_deref(), Some("req-1"));
+
+        let err = client.delete_order("gone").await.unwrap_err();
+        let api = err.downcast_ref::<ApiError>().unwrap();
+        assert_eq!(api.code, ErrorCode::Unknown);
+        assert_eq!(api.message, "bad gateway");
+    }
+
+    #[tokio::test]
+    async fn calls_carry_a_request_id_unless_the_caller_fixed_one() {
+        let server = MockServer::start();
+        let order = sample_order();
+        let tagged = server.mock(|when, then| {
+            when.method(GET)
+                .path(format!("/v1/orders/{}", order.id))
+                .header_exists("x-request-id");
+            then.status(200).json_body_obj(&order);
+        });
+        let fixed = server.mock(|when, then| {
+            when.method(DELETE)
+                .path(format!("/v1/orders/{}", order.id))
+                .header("x-request-id", "trace-1");
+            then.status(204);
+        });
+
+        let client = OrdersClient::new(&server.base_url()).unwrap();
+        client.get_order(&order.id.to_string()).await.unwrap();
+        let client = OrdersClient::builder(&server.base_url())
+            .unwrap()
+            .with_header("X-Request-Id", "trace-1")
+            .unwrap()
+            .build()
+            .unwrap();
+        client.delete_order(&order.id.to_string()).await.unwrap();
+
+        tagged.assert();
+        fixed.assert();
+    }
+
+    #[tokio::test]
+    async fn auth_helpers_send_credentials() {
+        let server = MockServer::start();
+        let order = sample_order();
+        let path = format!("/v1/orders/{}", order.id);
+        let mock = |header: &str, value: &str| {
+            server.mock(|when, then| {
+                when.method(GET).path(path.clone()).header(header, value);
+                then.status(200).json_body_obj(&order);
+            })
+        };
+        let bearer = mock("authorization", "Bearer s3cret");
+        let basic = mock("authorization", "Basic YWRhOnB3");
+        let api_key = mock("x-api-key", "ok_123");
+        let refreshed = mock("authorization", "Bearer token-2");
+
+        let builder = OrdersClient::builder(&server.base_url()).unwrap();
+        let clients = [
+            builder.clone().with_bearer_token("s3cret").unwrap(),
+            builder.clone().with_basic_auth("ada", "pw"),
+            builder.clone().with_api_key("ok_123").unwrap(),
+        ];
+        for client in clients {
+            client
+                .build()
+                .unwrap()
+                .get_order(&order.id.to_string())
+                .await
+                .unwrap();
+        }
+        bearer.assert();
+        basic.assert();
+        api_key.assert();
+
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = calls.clone();
+        let client = builder
+            .with_token_provider(move || {
+                let n = counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+                async move { Ok(format!("token-{n}")) }
+            })
+            .build()
+            .unwrap();
+        // The first token is refused; the provider hands out a new one.
+        assert!(client.get_order(&order.id.to_string()).await.is_err());
+        client.get_order(&order.id.to_string()).await.unwrap();
+        refreshed.assert();
+
+        let failing = OrdersClient::builder(&server.base_url())
+            .unwrap()
+            .with_token_provider(|| async { anyhow::bail!("idp down") })
+            .build()
+            .unwrap();
+        let err = failing.get_order(&order.id.to_string()).await.unwrap_err();
+        assert!(format!("{err:#}").contains("idp down"));
+    }
+
+    #[tokio::test]
+    async fn hmac_credentials_sign_each_request_for_the_server() {
+        use orders_hex::application::api_key_service::ApiKeyService;
+        use orders_hex::application::order_service::OrderService;
+        use orders_hex::inbound::http::{HttpServer, HttpServerConfig};
+        use orders_repo::memory::InMemoryRepo;
+        use orders_types::domain::api_key::Scope;
+
+        let repo = InMemoryRepo::new();
+        let keys = ApiKeyService::new(repo.clone()).with_signing_secret("pepper");
+        let minted = keys
+            .mint(
+                "signer".into(),
+                vec![Scope::Read, Scope::Write],
+                None,
+                Default::default(),
+            )
+            .await
+            .unwrap();
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+        let server = HttpServer::new(
+            OrderService::new(repo),
+            HttpServerConfig {
+                port: port.to_string(),
+                tls: None,
+                admin_addr: None,
+            },
+        )
+        .await
+        .unwrap()
+        .with_api_keys(keys);
+        tokio::spawn(async move { server.run().await.unwrap() });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let base = format!("http://127.0.0.1:{port}");
+        let id = minted.key.id.to_string();
+        let client = OrdersClient::builder(&base)
+            .unwrap()
+            .with_hmac_credentials(&id, minted.signing_key.as_ref().unwrap())
+            .unwrap()
+            .build()
+            .unwrap();
+        let created = client
+            .create_order(CreateOrderRequest {
+                customer_name: "Ann".into(),
+                email: "ann@example.com".into(),
+                items: sample_order().items,
+                discount_code: None,
+                shipping_address: None,
+                billing_address: None,
+                metadata: Default::default(),
+            })
+            .await
+            .unwrap();
+        let listed = client
+            .list_orders_with(OrderFilter {
+                email: Some("ann@example.com".into()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(listed[0].id.to_string(), created.id);
+
+        let forged = OrdersClient::builder(&base)
+            .unwrap()
+            .with_hmac_credentials(&id, &minted.key.key_hash)
+            .unwrap()
+            .build()
+            .unwrap();
+        let err = forged.list_orders().await.unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ApiError>().unwrap().status,
+            StatusCode::UNAUTHORIZED
+        );
+        assert!(OrdersClient::builder(&base)
+            .unwrap()
+            .with_hmac_credentials("not-a-uuid", "x")
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn circuit_breaker_stops_calls_to_a_failing_server() {
+        let server = MockServer::start();
+        let down = server.mock(|when, then| {
+            when.method(GET).path("/v1/orders/down");
+            then.status(503)
+                .json_body(serde_json::json!({"error": "down", "code": "UNAVAILABLE"}));
+        });
+        let missing = server.mock(|when, then| {
+            when.method(GET).path("/v1/orders/missing");
+            then.status(404)
+                .json_body(serde_json::json!({"error": "nope", "code": "ORDER_NOT_FOUND"}));
+        });
+        let client = OrdersClient::builder(&server.base_url())
+            .unwrap()
+            .with_circuit_breaker(CircuitBreakerConfig {
+                failure_threshold: 2,
+                reset_timeout: Duration::from_secs(60),
+                half_open_probes: 1,
+            })
+            .build()
+            .unwrap();
+
+        // Client errors say nothing about the server's health.
+        for _ in 0..3 {
+            assert!(client.get_order("missing").await.is_err());
+        }
+        for _ in 0..2 {
+            let err = client.get_order("down").await.unwrap_err();
+            assert!(err.downcast_ref::<ApiError>().is_some());
+        }
+        let err = client.get_order("missing").await.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<OrdersClientError>(),
+            Some(OrdersClientError::CircuitOpen { .. })
+        ));
+        down.assert_hits(2);
+        missing.assert_hits(3);
+    }
+
+    #[tokio::test]
+    async fn call_options_apply_only_to_their_calls() {
+        let server = MockServer::start();
+        let order = sample_order();
+        let slow = server.mock(|when, then| {
+            when.method(GET)
+                .path(format!("/v1/orders/{}", order.id))
+                .header("x-priority", "low");
+            then.status(200)
+                .delay(Duration::from_millis(500))
+                .json_body_obj(&order);
+        });
+        let fast = server.mock(|when, then| {
+            when.method(GET)
+                .path(format!("/v1/orders/{}", order.id))
+                .matches(|req| {
+                    !req.headers
+                        .iter()
+                        .flatten()
+                        .any(|(name, _)| name.eq_ignore_ascii_case("x-priority"))
+                });
+            then.status(200).json_body_obj(&order);
+        });
+        let client = OrdersClient::builder(&server.base_url())
+            .unwrap()
+            .with_timeout(Duration::from_secs(30))
+            .build()
+            .unwrap();
+        let id = order.id.to_string();
+
+        let low = CallOptions::new().with_header("x-priority", "low").unwrap();
+        let err = client
+            .with_call_options(low.clone().with_timeout(Duration::from_millis(50)))
+            .get_order(&id)
+            .await
+            .unwrap_err();
+        assert!(err
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(|e| e.is_timeout()));
+        assert_eq!(
+            client
+                .with_call_options(low)
+                .get_order(&id)
+                .await
+                .unwrap()
+                .id,
+            order.id
+        );
+        assert_eq!(client.get_order(&id).await.unwrap().id, order.id);
+        slow.assert_hits(2);
+        fast.assert_hits(1);
+    }
+
+    #[tokio::test]
+    async fn cancelled_calls_stop_waiting() {
+        let server = MockServer::start();
+        let create = server.mock(|when, then| {
+            when.method(POST).path("/v1/orders");
+            then.status(201).delay(Duration::from_secs(5));
+        });
+        let order = sample_order();
+        let req = CreateOrderRequest {
+            customer_name: order.customer_name.clone(),
+            email: order.email.clone(),
+            items: order.items.clone(),
+            discount_code: None,
+            shipping_address: None,
+            billing_address: None,
+            metadata: Default::default(),
+        };
+        let client = OrdersClient::new(&server.base_url()).unwrap();
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let err = client
+            .create_order_with_opts(req.clone(), CallOptions::new().with_cancellation(token))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<OrdersClientError>(),
+            Some(&OrdersClientError::Cancelled)
+        );
+        create.assert_hits(0);
+
+        let token = CancellationToken::new();
+        let canceller = token.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            canceller.cancel();
+        });
+        let started = std::time::Instant::now();
+        let err = client
+            .create_order_with_opts(req, CallOptions::new().with_cancellation(token))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<OrdersClientError>(),
+            Some(&OrdersClientError::Cancelled)
+        );
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn cached_reads_revalidate_with_etags() {
+        let server = MockServer::start();
+        let order = sample_order();
+        let path = format!("/v1/orders/{}", order.id);
+        let fresh = server.mock(|when, then| {
+            when.method(GET).path(path.clone()).matches(|req| {
+                !req.headers
+                    .iter()
+                    .flatten()
+                    .any(|(name, _)| name.eq_ignore_ascii_case("if-none-match"))
+            });
+            then.status(200)
+                .header("etag", "W/\"v1\"")
+                .json_body_obj(&order);
+        });
+        let unchanged = server.mock(|when, then| {
+            when.method(GET)
+                .path(path.clone())
+                .header("if-none-match", "W/\"v1\"");
+            then.status(304).header("etag", "W/\"v1\"");
+        });
+
+        let client = OrdersClient::builder(&server.base_url())
+            .unwrap()
+            .with_cache(8)
+            .build()
+            .unwrap();
+        let id = order.id.to_string();
+        assert_eq!(client.get_order(&id).await.unwrap().id, order.id);
+        assert_eq!(client.get_order(&id).await.unwrap().id, order.id);
+        fresh.assert_hits(1);
+        unchanged.assert_hits(1);
+
+        // Without a cache every read is a full one.
+        let uncached = OrdersClient::new(&server.base_url()).unwrap();
+        uncached.get_order(&id).await.unwrap();
+        fresh.assert_hits(2);
+    }
+
     #[tokio::test]
     async fn list_update_delete() {
         let server = MockServer::start();
         let order = sample_order();
 
         let list_mock = server.mock(|when, then| {
-            when.method(GET).path("/orders");
-            then.status(200).json_body_obj(&vec![order.clone()]);
+            when.method(GET).path("/v1/orders");
+            then.status(200).json_body_obj(&page(vec![order.clone()]));
         });
 
         let update_mock = server.mock(|when, then| {
             when.method(httpmock::Method::PATCH)
-                .path(format!("/orders/{}/status", order.id))
+                .path(format!("/v1/orders/{}/status", order.id))
                 .json_body_obj(&UpdateStatusRequest {
                     status: OrderStatus::Shipped,
                 });
@@ -245,7 +1582,7 @@ mod tests {
         });
 
         let delete_mock = server.mock(|when, then| {
-            when.method(DELETE).path(format!("/orders/{}", order.id));
+            when.method(DELETE).path(format!("/v1/orders/{}", order.id));
             then.status(204);
         });
 
@@ -265,4 +1602,209 @@ mod tests {
         update_mock.assert();
         delete_mock.assert();
     }
+
+    #[tokio::test]
+    async fn update_order_sends_a_merge_patch() {
+        let server = MockServer::start();
+        let order = sample_order();
+        let patch = OrderPatch::new()
+            .with_customer_name("Renamed")
+            .with_shipping_address(None);
+
+        let patch_mock = server.mock(|when, then| {
+            when.method(httpmock::Method::PATCH)
+                .path(format!("/v1/orders/{}", order.id))
+                .header("content-type", "application/merge-patch+json")
+                .json_body(serde_json::json!({
+                    "customer_name": "Renamed",
+                    "shipping_address": null
+                }));
+            let mut updated = order.clone();
+            updated.customer_name = "Renamed".into();
+            then.status(200).json_body_obj(&updated);
+        });
+
+        let client = OrdersClient::new(&server.base_url()).unwrap();
+        let updated = client
+            .update_order(&order.id.to_string(), &patch)
+            .await
+            .unwrap();
+        assert_eq!(updated.customer_name, "Renamed");
+        patch_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn share_links_round_trip() {
+        let server = MockServer::start();
+        let order = sample_order();
+        let path = format!("/v1/orders/{}", order.id);
+
+        let share_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path(format!("{path}/share"))
+                .json_body(serde_json::json!({"ttl_secs": 600}));
+            then.status(201).json_body_obj(&ShareLink {
+                path: format!("{path}?exp=1700000600&sig=abc&tenant=acme"),
+                exp: 1_700_000_600,
+            });
+        });
+        let get_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path(path.clone())
+                .query_param("exp", "1700000600")
+                .query_param("sig", "abc")
+                .query_param("tenant", "acme");
+            then.status(200).json_body_obj(&order);
+        });
+
+        let client = OrdersClient::new(&server.base_url()).unwrap();
+        let id = order.id.to_string();
+        let link = client.create_share_link(&id, Some(600)).await.unwrap();
+        assert_eq!(link.exp, 1_700_000_600);
+
+        let token = ShareToken {
+            exp: link.exp,
+            sig: "abc".into(),
+            tenant: Some(TenantId::parse("acme").unwrap()),
+        };
+        assert_eq!(
+            client.share_url(&id, &token).unwrap().as_str(),
+            format!("{}{}", server.base_url(), link.path)
+        );
+        let fetched = client.get_shared_order(&id, &token).await.unwrap();
+        assert_eq!(fetched.id, order.id);
+
+        share_mock.assert();
+        get_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn list_orders_with_encodes_filter_as_query() {
+        let server = MockServer::start();
+        let order = sample_order();
+
+        let list_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/v1/orders")
+                .query_param("status", "Pending")
+                .query_param("limit", "10")
+                .query_param("sort", "created_at")
+                .query_param("order", "desc")
+                .query_param("metadata.erp_id", "E-1");
+            then.status(200).json_body_obj(&page(vec![order.clone()]));
+        });
+
+        let client = OrdersClient::new(&server.base_url()).unwrap();
+        let listed = client
+            .list_orders_with(
+                OrderFilter::default()
+                    .with_status(OrderStatus::Pending)
+                    .with_limit(10)
+                    .with_sort(SortField::CreatedAt, SortOrder::Desc)
+                    .with_metadata("erp_id", "E-1"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 1);
+        list_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn list_all_pages_through_the_query() {
+        let server = MockServer::start();
+        let first: Vec<Order> = (0..2).map(|_| sample_order()).collect();
+        let last = sample_order();
+        let after = chrono::DateTime::parse_from_rfc3339("2024-03-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let page_mock = |offset: &str, orders: Vec<Order>| {
+            server.mock(|when, then| {
+                when.method(GET)
+                    .path("/v1/orders")
+                    .query_param("email", "user@example.com")
+                    .query_param("created_after", "2024-03-01T00:00:00Z")
+                    .query_param("sort", "created_at")
+                    .query_param("order", "asc")
+                    .query_param("limit", "2")
+                    .query_param("offset", offset);
+                then.status(200).json_body_obj(&page(orders));
+            })
+        };
+        let first_mock = page_mock("0", first.clone());
+        let last_mock = page_mock("2", vec![last.clone()]);
+
+        let client = OrdersClient::new(&server.base_url()).unwrap();
+        let query = ListOrdersQuery::new()
+            .with_email("user@example.com")
+            .with_created_after(after)
+            .with_per_page(2);
+        let all = client.list_all(query.clone()).await.unwrap();
+        assert_eq!(
+            all.iter().map(|o| o.id).collect::<Vec<_>>(),
+            [first[0].id, first[1].id, last.id]
+        );
+        first_mock.assert();
+        last_mock.assert();
+
+        let second = client
+            .list_orders_query(
+                &query
+                    .with_sort(SortField::CreatedAt, SortOrder::Asc)
+                    .with_page(2),
+            )
+            .await
+            .unwrap();
+        assert_eq!(second.len(), 1);
+        last_mock.assert_hits(2);
+    }
+
+    #[tokio::test]
+    async fn order_pages_are_fetched_lazily() {
+        let server = MockServer::start();
+        let orders: Vec<Order> = (0..3).map(|_| sample_order()).collect();
+        let page_mock = |offset: &str, orders: &[Order]| {
+            server.mock(|when, then| {
+                when.method(GET)
+                    .path("/v1/orders")
+                    .query_param("limit", "2")
+                    .query_param("offset", offset);
+                then.status(200).json_body_obj(&page(orders.to_vec()));
+            })
+        };
+        let first = page_mock("0", &orders[..2]);
+        let second = page_mock("2", &orders[2..]);
+
+        let client = OrdersClient::new(&server.base_url()).unwrap();
+        let query = ListOrdersQuery::new().with_per_page(2);
+        let mut pages = std::pin::pin!(client.orders_pages(query.clone()));
+        assert_eq!(pages.next().await.unwrap().unwrap().len(), 2);
+        first.assert();
+        second.assert_hits(0);
+        assert_eq!(pages.next().await.unwrap().unwrap().len(), 1);
+        assert!(pages.next().await.is_none());
+        second.assert();
+
+        let ids: Vec<_> = client
+            .orders_stream(query)
+            .map_ok(|o| o.id)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(ids, orders.iter().map(|o| o.id).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn order_stream_ends_after_an_error() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/v1/orders");
+            then.status(500)
+                .json_body(serde_json::json!({"code": "INTERNAL", "message": "boom"}));
+        });
+        let client = OrdersClient::new(&server.base_url()).unwrap();
+        let results: Vec<_> = client.orders_stream(ListOrdersQuery::new()).collect().await;
+        assert_eq!(results.len(), 1);
+        assert!(results[0].is_err());
+    }
 }
@@ -0,0 +1,203 @@
+//! Checking that a webhook delivery really came from the orders API. Every
+//! delivery carries `X-Orders-Signature: t=<unix seconds>,v1=<hex>`, where
+//! the hex is HMAC-SHA256 of `"<t>.<body>"` keyed with `WEBHOOK_SECRET`.
+//! [`verify_signature`] recomputes it over the raw body, compares in
+//! constant time, and rejects timestamps too far from now so a captured
+//! delivery can't be replayed later.
+//!
+//! As axum middleware, buffering the body to check it and handing it on:
+//!
+//! ```no_run
+//! use axum::body::{to_bytes, Body};
+//! use axum::extract::{Request, State};
+//! use axum::http::StatusCode;
+//! use axum::middleware::Next;
+//! use axum::response::Response;
+//! use orders_client::webhook::verify_signature;
+//!
+//! async fn verify_orders_webhook(
+//!     State(secret): State<String>,
+//!     req: Request,
+//!     next: Next,
+//! ) -> Result<Response, StatusCode> {
+//!     let (parts, body) = req.into_parts();
+//!     let body = to_bytes(body, 1 << 20)
+//!         .await
+//!         .map_err(|_| StatusCode::PAYLOAD_TOO_LARGE)?;
+//!     verify_signature(&secret, &parts.headers, &body).map_err(|_| StatusCode::UNAUTHORIZED)?;
+//!     Ok(next.run(Request::from_parts(parts, Body::from(body))).await)
+//! }
+//!
+//! let app: axum::Router = axum::Router::new()
+//!     .route("/hooks/orders", axum::routing::post(|body: String| async move { body }))
+//!     .layer(axum::middleware::from_fn_with_state(
+//!         "whsec".to_string(),
+//!         verify_orders_webhook,
+//!     ));
+//! ```
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use hmac::{Hmac, Mac};
+use reqwest::header::HeaderMap;
+use sha2::Sha256;
+
+pub const SIGNATURE_HEADER: &str = "x-orders-signature";
+
+/// How far a delivery's timestamp may be from now, either way.
+pub const DEFAULT_TOLERANCE: Duration = Duration::from_secs(300);
+
+/// Why a delivery's signature was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureError {
+    /// No `X-Orders-Signature` header.
+    Missing,
+    /// The header isn't `t=<unix seconds>,v1=<hex>`.
+    Malformed,
+    /// Signed `age_secs` away from now, beyond the tolerance.
+    Expired { age_secs: u64 },
+    /// No `v1` matches the body under this secret.
+    Mismatch,
+}
+
+impl std::fmt::Display for SignatureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SignatureError::Missing => write!(f, "missing {SIGNATURE_HEADER} header"),
+            SignatureError::Malformed => write!(f, "malformed {SIGNATURE_HEADER} header"),
+            SignatureError::Expired { age_secs } => {
+                write!(f, "signature timestamp is {age_secs}s from now")
+            }
+            SignatureError::Mismatch => write!(f, "signature does not match the body"),
+        }
+    }
+}
+
+impl std::error::Error for SignatureError {}
+
+/// Check the `X-Orders-Signature` in `headers` against the raw `body`,
+/// allowing [`DEFAULT_TOLERANCE`] of clock difference.
+pub fn verify_signature(
+    secret: &str,
+    headers: &HeaderMap,
+    body: &[u8],
+) -> Result<(), SignatureError> {
+    let header = headers
+        .get(SIGNATURE_HEADER)
+        .ok_or(SignatureError::Missing)?
+        .to_str()
+        .map_err(|_| SignatureError::Malformed)?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    verify_signature_at(secret, header, body, now, DEFAULT_TOLERANCE)
+}
+
+/// [`verify_signature`] for a header value already taken out of the
+/// request, at `now` (unix seconds) and with a chosen `tolerance`. Any one
+/// matching `v1` is enough, so a sender can sign with an old and a new
+/// secret while it is rotated.
+pub fn verify_signature_at(
+    secret: &str,
+    header: &str,
+    body: &[u8],
+    now: u64,
+    tolerance: Duration,
+) -> Result<(), SignatureError> {
+    let mut timestamp = None;
+    let mut signatures = Vec::new();
+    for part in header.split(',') {
+        match part.trim().split_once('=') {
+            Some(("t", t)) => {
+                timestamp = Some(t.parse::<u64>().map_err(|_| SignatureError::Malformed)?)
+            }
+            Some(("v1", sig)) => {
+                signatures.push(hex::decode(sig).map_err(|_| SignatureError::Malformed)?)
+            }
+            _ => {}
+        }
+    }
+    let timestamp = timestamp.ok_or(SignatureError::Malformed)?;
+    if signatures.is_empty() {
+        return Err(SignatureError::Malformed);
+    }
+    let age_secs = now.abs_diff(timestamp);
+    if age_secs > tolerance.as_secs() {
+        return Err(SignatureError::Expired { age_secs });
+    }
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("hmac accepts any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    if signatures
+        .iter()
+        .any(|sig| mac.clone().verify_slice(sig).is_ok())
+    {
+        Ok(())
+    } else {
+        Err(SignatureError::Mismatch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use orders_hex::application::webhook_service::sign;
+
+    const BODY: &str = r#"{"specversion":"1.0","type":"order.created"}"#;
+
+    #[test]
+    fn accepts_what_the_server_signs_and_nothing_else() {
+        let header = sign("whsec", 1_000, BODY);
+        let check = |secret, body: &str, now| {
+            verify_signature_at(secret, &header, body.as_bytes(), now, DEFAULT_TOLERANCE)
+        };
+        assert_eq!(check("whsec", BODY, 1_000), Ok(()));
+        assert_eq!(check("whsec", BODY, 1_300), Ok(()));
+        assert_eq!(check("other", BODY, 1_000), Err(SignatureError::Mismatch));
+        assert_eq!(
+            check("whsec", &BODY.replace("created", "deleted"), 1_000),
+            Err(SignatureError::Mismatch)
+        );
+        assert_eq!(
+            check("whsec", BODY, 1_301),
+            Err(SignatureError::Expired { age_secs: 301 })
+        );
+    }
+
+    #[test]
+    fn any_signature_in_the_header_may_match() {
+        let old = sign("old", 1_000, BODY);
+        let new = sign("new", 1_000, BODY);
+        let v1 = new.split_once("v1=").unwrap().1;
+        let header = format!("{old},v1={v1}");
+        assert_eq!(
+            verify_signature_at("new", &header, BODY.as_bytes(), 1_000, DEFAULT_TOLERANCE),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn reads_the_header_and_rejects_malformed_ones() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(
+            verify_signature("whsec", &headers, BODY.as_bytes()),
+            Err(SignatureError::Missing)
+        );
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        headers.insert(SIGNATURE_HEADER, sign("whsec", now, BODY).parse().unwrap());
+        assert_eq!(verify_signature("whsec", &headers, BODY.as_bytes()), Ok(()));
+        for bad in ["v1=abcd", "t=1000", "t=soon,v1=abcd", "t=1000,v1=zz"] {
+            assert_eq!(
+                verify_signature_at("whsec", bad, BODY.as_bytes(), 1_000, DEFAULT_TOLERANCE),
+                Err(SignatureError::Malformed),
+                "{bad}"
+            );
+        }
+    }
+}
@@ -0,0 +1,23 @@
+//! Who is making the current request, carried as a task-local like the
+//! correlation id so the audit log, status history, events and the orders
+//! themselves can attribute changes without every service method taking the
+//! caller.
+
+use std::future::Future;
+
+use orders_types::domain::actor::Actor;
+
+tokio::task_local! {
+    static CURRENT: Actor;
+}
+
+/// Run `fut` on behalf of `actor`.
+pub async fn scope<F: Future>(actor: Actor, fut: F) -> F::Output {
+    CURRENT.scope(actor, fut).await
+}
+
+/// The actor set by the nearest enclosing [`scope`], or [`Actor::System`]
+/// outside any request.
+pub fn current() -> Actor {
+    CURRENT.try_with(Clone::clone).unwrap_or_default()
+}
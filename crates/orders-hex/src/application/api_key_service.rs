@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{Duration, Utc};
+use orders_types::domain::api_key::{ApiKey, Role, Scope};
+use orders_types::domain::request_signing::{signing_key, RequestSignature, SignedRequest};
+use orders_types::domain::tenant::TenantId;
+use orders_types::ports::api_key_repository::ApiKeyRepository;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::application::auth::AuthContext;
+use crate::errors::{AppError, Resource};
+
+/// A freshly minted key. `secret` is never stored and cannot be recovered.
+/// `signing_key` signs requests instead of sending the secret; it is only
+/// there when request signing is enabled.
+#[derive(Debug, Clone, Serialize)]
+pub struct MintedKey {
+    #[serde(flatten)]
+    pub key: ApiKey,
+    pub secret: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing_key: Option<String>,
+}
+
+pub struct ApiKeyService {
+    repo: Arc<dyn ApiKeyRepository>,
+    bootstrap_hash: Option<String>,
+    /// Derives each key's [signing key](signing_key); signed requests are
+    /// refused without it.
+    signing_secret: Option<String>,
+    /// Nonces of recent signed requests, with the unix second after which
+    /// their timestamp alone rejects them.
+    nonces: Mutex<HashMap<(Uuid, String), i64>>,
+}
+
+/// The stored form of API key `secret`: hex SHA-256 of it.
+pub fn hash_key(secret: &str) -> String {
+    hex::encode(Sha256::digest(secret.as_bytes()))
+}
+
+impl ApiKeyService {
+    /// How far a signed request's timestamp may be from the server's clock.
+    pub const SIGNATURE_TOLERANCE_SECS: i64 = 300;
+
+    pub fn new(repo: impl ApiKeyRepository) -> Self {
+        Self {
+            repo: Arc::new(repo),
+            bootstrap_hash: None,
+            signing_secret: None,
+            nonces: Mutex::default(),
+        }
+    }
+
+    /// Accept `secret` as an admin key without storing it, so the first real
+    /// keys can be minted.
+    pub fn with_bootstrap_key(mut self, secret: &str) -> Self {
+        self.bootstrap_hash = Some(hash_key(secret));
+        self
+    }
+
+    /// Accept signed requests, with signing keys derived under `secret`.
+    pub fn with_signing_secret(mut self, secret: &str) -> Self {
+        self.signing_secret = Some(secret.to_string());
+        self
+    }
+
+    /// A new key acting for `tenant` only.
+    pub async fn mint(
+        &self,
+        name: String,
+        scopes: Vec<Scope>,
+        role: Option<Role>,
+        tenant: TenantId,
+    ) -> Result<MintedKey, AppError> {
+        if name.trim().is_empty() {
+            return Err(AppError::BadRequest("name empty".into()));
+        }
+        if scopes.is_empty() {
+            return Err(AppError::BadRequest("scopes empty".into()));
+        }
+        if let Some(role) = role.filter(|r| *r > Role::from_scopes(&scopes)) {
+            return Err(AppError::BadRequest(format!(
+                "role {} is broader than the key's scopes",
+                role.as_str()
+            )));
+        }
+        let secret = format!("ok_{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        let mut key = ApiKey::new(name, hash_key(&secret), scopes).with_tenant(tenant);
+        key.role = role;
+        let key = self.repo.create_key(key).await.map_err(AppError::from)?;
+        let signing_key = self
+            .signing_secret
+            .as_deref()
+            .map(|server| signing_key(server, &key.key_hash));
+        Ok(MintedKey {
+            key,
+            secret,
+            signing_key,
+        })
+    }
+
+    pub async fn list(&self) -> Result<Vec<ApiKey>, AppError> {
+        self.repo.list_keys().await.map_err(AppError::from)
+    }
+
+    pub async fn revoke(&self, id: Uuid) -> Result<(), AppError> {
+        let revoked = self.repo.revoke_key(id).await.map_err(AppError::from)?;
+        if revoked {
+            Ok(())
+        } else {
+            Err(AppError::NotFound(Resource::ApiKey, id.to_string()))
+        }
+    }
+
+    /// Resolve a presented secret to a caller; `None` if unknown or revoked.
+    pub async fn authenticate(&self, secret: &str) -> Result<Option<AuthContext>, AppError> {
+        let hash = hash_key(secret);
+        if self.bootstrap_hash.as_deref() == Some(hash.as_str()) {
+            return Ok(Some(AuthContext {
+                key_id: None,
+                scopes: vec![Scope::Admin],
+                role: Role::Admin,
+                tenant: None,
+            }));
+        }
+        let key = self
+            .repo
+            .find_key_by_hash(&hash)
+            .await
+            .map_err(AppError::from)?;
+        Ok(key.filter(ApiKey::is_active).map(|k| AuthContext {
+            key_id: Some(k.id),
+            role: k.effective_role(),
+            tenant: Some(k.tenant),
+            scopes: k.scopes,
+        }))
+    }
+
+    /// Resolve an HMAC-signed request to a caller. Any active stored key
+    /// can sign, with the signing key it was minted with; a signature is
+    /// accepted once, within [`Self::SIGNATURE_TOLERANCE_SECS`] of its
+    /// timestamp. Nonces are remembered per instance only.
+    pub async fn authenticate_signed(
+        &self,
+        sig: &RequestSignature,
+        req: &SignedRequest<'_>,
+    ) -> Result<AuthContext, AppError> {
+        let Some(server) = self.signing_secret.as_deref() else {
+            return Err(AppError::Unauthorized(
+                "request signing is not enabled".into(),
+            ));
+        };
+        let key = self
+            .repo
+            .find_key(sig.key_id)
+            .await
+            .map_err(AppError::from)?
+            .filter(ApiKey::is_active)
+            .ok_or_else(|| AppError::Unauthorized("invalid request signature".into()))?;
+        let now = Utc::now();
+        sig.verify(
+            &signing_key(server, &key.key_hash),
+            req,
+            now,
+            Duration::seconds(Self::SIGNATURE_TOLERANCE_SECS),
+        )
+        .map_err(|e| AppError::Unauthorized(e.to_string()))?;
+        if !self.first_use(sig, now.timestamp()) {
+            return Err(AppError::Unauthorized(
+                "request signature already used".into(),
+            ));
+        }
+        Ok(AuthContext {
+            key_id: Some(key.id),
+            role: key.effective_role(),
+            tenant: Some(key.tenant),
+            scopes: key.scopes,
+        })
+    }
+
+    /// Record `sig`'s nonce; `false` if it was already seen.
+    fn first_use(&self, sig: &RequestSignature, now: i64) -> bool {
+        let mut nonces = self.nonces.lock().expect("nonce cache poisoned");
+        nonces.retain(|_, until| *until >= now);
+        nonces
+            .insert(
+                (sig.key_id, sig.nonce.clone()),
+                sig.timestamp + Self::SIGNATURE_TOLERANCE_SECS,
+            )
+            .is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::auth::{Access, OrderAction};
+
+    #[tokio::test]
+    async fn mint_authenticate_revoke() {
+        let svc =
+            ApiKeyService::new(orders_repo::memory::InMemoryRepo::new()).with_bootstrap_key("boot");
+        let boot = svc.authenticate("boot").await.unwrap().unwrap();
+        assert_eq!(boot.scopes, vec![Scope::Admin]);
+
+        let minted = svc
+            .mint("ci".into(), vec![Scope::Read], None, TenantId::default())
+            .await
+            .unwrap();
+        let ctx = svc.authenticate(&minted.secret).await.unwrap().unwrap();
+        assert_eq!(ctx.key_id, Some(minted.key.id));
+        assert_eq!(ctx.role, Role::Viewer);
+        let access = Access::Caller(ctx);
+        assert!(access.authorize(OrderAction::View).is_ok());
+        assert!(matches!(
+            access.authorize(OrderAction::Create),
+            Err(AppError::RoleDenied { .. })
+        ));
+
+        // A role can narrow a key's scopes but not widen them.
+        assert!(matches!(
+            svc.mint("ops".into(), vec![Scope::Read], Some(Role::Admin), TenantId::default())
+                .await,
+            Err(AppError::BadRequest(m)) if m.contains("broader")
+        ));
+        let narrowed = svc
+            .mint(
+                "ops".into(),
+                vec![Scope::Admin],
+                Some(Role::Viewer),
+                TenantId::default(),
+            )
+            .await
+            .unwrap();
+        let ctx = svc.authenticate(&narrowed.secret).await.unwrap().unwrap();
+        assert!(matches!(
+            Access::Caller(ctx).authorize(OrderAction::Create),
+            Err(AppError::RoleDenied { .. })
+        ));
+
+        svc.revoke(minted.key.id).await.unwrap();
+        assert!(svc.authenticate(&minted.secret).await.unwrap().is_none());
+        assert!(svc.authenticate("nope").await.unwrap().is_none());
+        assert!(matches!(
+            svc.revoke(Uuid::new_v4()).await,
+            Err(AppError::NotFound(..))
+        ));
+    }
+
+    #[tokio::test]
+    async fn signed_requests_are_accepted_once() {
+        let repo = orders_repo::memory::InMemoryRepo::new();
+        let svc = ApiKeyService::new(repo.clone()).with_signing_secret("pepper");
+        let minted = svc
+            .mint("ci".into(), vec![Scope::Write], None, TenantId::default())
+            .await
+            .unwrap();
+        let req = SignedRequest {
+            method: "POST",
+            path: "/v1/orders",
+            query: None,
+            body: b"{}",
+        };
+        let key = minted.signing_key.clone().unwrap();
+        // The stored hash alone doesn't sign.
+        let from_hash =
+            RequestSignature::sign(minted.key.id, &minted.key.key_hash, &req, Utc::now());
+        assert!(svc.authenticate_signed(&from_hash, &req).await.is_err());
+        let sig = RequestSignature::sign(minted.key.id, &key, &req, Utc::now());
+        let ctx = svc.authenticate_signed(&sig, &req).await.unwrap();
+        assert_eq!(ctx.key_id, Some(minted.key.id));
+        assert!(matches!(
+            svc.authenticate_signed(&sig, &req).await,
+            Err(AppError::Unauthorized(m)) if m.contains("already used")
+        ));
+
+        let stale = RequestSignature::sign(
+            minted.key.id,
+            &key,
+            &req,
+            Utc::now() - Duration::minutes(10),
+        );
+        assert!(svc.authenticate_signed(&stale, &req).await.is_err());
+        let wrong_key = RequestSignature::sign(minted.key.id, "guess", &req, Utc::now());
+        assert!(svc.authenticate_signed(&wrong_key, &req).await.is_err());
+        svc.revoke(minted.key.id).await.unwrap();
+        let fresh = RequestSignature::sign(minted.key.id, &key, &req, Utc::now());
+        assert!(svc.authenticate_signed(&fresh, &req).await.is_err());
+
+        // Without a signing secret nothing is handed out and nothing verifies.
+        let unsigned = ApiKeyService::new(repo);
+        let minted = unsigned
+            .mint("ci".into(), vec![Scope::Write], None, TenantId::default())
+            .await
+            .unwrap();
+        assert!(minted.signing_key.is_none());
+        let sig = RequestSignature::sign(minted.key.id, &key, &req, Utc::now());
+        assert!(matches!(
+            unsigned.authenticate_signed(&sig, &req).await,
+            Err(AppError::Unauthorized(m)) if m.contains("not enabled")
+        ));
+    }
+}
@@ -0,0 +1,152 @@
+use std::future::Future;
+
+use orders_types::domain::actor::Actor;
+use orders_types::domain::api_key::{scope_allows, Role, Scope};
+use orders_types::domain::tenant::TenantId;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::errors::AppError;
+
+/// Identity of an authenticated caller.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthContext {
+    /// Stored key id; `None` for the bootstrap admin key from config.
+    pub key_id: Option<Uuid>,
+    pub scopes: Vec<Scope>,
+    pub role: Role,
+    /// Tenant the key is bound to; `None` for the bootstrap key, which may
+    /// act for any.
+    pub tenant: Option<TenantId>,
+}
+
+impl AuthContext {
+    /// Who the caller's changes are attributed to: its key, or
+    /// [`Actor::Bootstrap`] for the admin key from config.
+    pub fn actor(&self) -> Actor {
+        match self.key_id {
+            Some(id) => Actor::ApiKey(id),
+            None => Actor::Bootstrap,
+        }
+    }
+}
+
+tokio::task_local! {
+    static ACCESS: Access;
+}
+
+/// What the current request may do, carried as a task-local like the
+/// [actor](super::actor) and set by the API-key check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Access {
+    /// No API keys are configured, or the caller is internal: the CLI, the
+    /// worker or a background task.
+    Unrestricted,
+    /// API keys are on but the request carried none, as on public paths and
+    /// share links; only calls that authorize themselves succeed.
+    Anonymous,
+    Caller(AuthContext),
+}
+
+impl Access {
+    /// Run `fut` with `self` as the current access.
+    pub async fn scope<F: Future>(self, fut: F) -> F::Output {
+        ACCESS.scope(self, fut).await
+    }
+
+    /// The access set by the nearest enclosing [`scope`](Self::scope), or
+    /// [`Access::Unrestricted`] outside any request.
+    pub fn current() -> Access {
+        ACCESS
+            .try_with(Clone::clone)
+            .unwrap_or(Access::Unrestricted)
+    }
+
+    /// Role policy: viewers read, operators create and move orders, admins
+    /// delete, re-price and run the service. A caller also needs the scope
+    /// that goes with the action, so a role wider than its key's scopes
+    /// grants nothing more. Unrestricted access may do anything and
+    /// anonymous access nothing.
+    pub fn authorize(&self, action: OrderAction) -> Result<(), AppError> {
+        let ctx = match self {
+            Access::Unrestricted => return Ok(()),
+            Access::Anonymous => return Err(AppError::Unauthorized("missing api key".into())),
+            Access::Caller(ctx) => ctx,
+        };
+        let required = action.required_role();
+        if ctx.role < required {
+            return Err(AppError::RoleDenied {
+                action,
+                role: ctx.role,
+                required,
+            });
+        }
+        let scope = action.required_scope();
+        if !scope_allows(&ctx.scopes, scope) {
+            return Err(AppError::Forbidden(format!(
+                "{action:?} needs the {} scope",
+                scope.as_str()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Check the [current](Access::current) access may perform `action`.
+pub fn authorize(action: OrderAction) -> Result<(), AppError> {
+    Access::current().authorize(action)
+}
+
+/// Operations guarded by the role policy; see [`Access::authorize`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderAction {
+    View,
+    Create,
+    UpdateStatus,
+    /// Replace or add items on a pending order.
+    EditItems,
+    /// Change the customer details or addresses of an order that hasn't
+    /// shipped.
+    EditDetails,
+    Reprice,
+    /// Mint a signed read-only link to an order.
+    Share,
+    Delete,
+    /// Set a status outside the transition rules.
+    ForceStatus,
+    /// Data maintenance such as the integrity pass.
+    Maintain,
+    /// Run the service itself: API keys, webhooks, the dead-letter queue,
+    /// jobs and SLOs.
+    Administer,
+}
+
+impl OrderAction {
+    /// Least privileged role allowed to perform the action.
+    pub fn required_role(&self) -> Role {
+        match self {
+            OrderAction::View => Role::Viewer,
+            OrderAction::Create
+            | OrderAction::UpdateStatus
+            | OrderAction::EditItems
+            | OrderAction::EditDetails
+            | OrderAction::Share => Role::Operator,
+            OrderAction::Reprice
+            | OrderAction::Delete
+            | OrderAction::ForceStatus
+            | OrderAction::Maintain
+            | OrderAction::Administer => Role::Admin,
+        }
+    }
+
+    /// Scope a key needs for the action, matching its
+    /// [required role](Self::required_role).
+    pub fn required_scope(&self) -> Scope {
+        match self.required_role() {
+            Role::Viewer => Scope::Read,
+            Role::Operator => Scope::Write,
+            Role::Admin => Scope::Admin,
+        }
+    }
+}
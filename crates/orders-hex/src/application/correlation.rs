@@ -0,0 +1,38 @@
+//! The correlation and request ids of the request being handled, carried as
+//! task-locals so services can stamp events, deliveries and error bodies
+//! without every method taking them as parameters.
+
+use std::future::Future;
+
+use orders_types::domain::correlation::{CorrelationId, RequestId};
+
+tokio::task_local! {
+    static CURRENT: CorrelationId;
+    static REQUEST: RequestId;
+}
+
+/// Run `fut` with `id` as the current correlation id.
+pub async fn scope<F: Future>(id: CorrelationId, fut: F) -> F::Output {
+    CURRENT.scope(id, fut).await
+}
+
+/// The id set by the nearest enclosing [`scope`], if any.
+pub fn current() -> Option<CorrelationId> {
+    CURRENT.try_with(Clone::clone).ok()
+}
+
+/// Run `fut` as part of handling request `id`.
+pub async fn scope_request<F: Future>(id: RequestId, fut: F) -> F::Output {
+    REQUEST.scope(id, fut).await
+}
+
+/// The id set by the nearest enclosing [`scope_request`], if any.
+pub fn current_request() -> Option<RequestId> {
+    REQUEST.try_with(Clone::clone).ok()
+}
+
+/// The current id, or a fresh one for work not started by a request (CLI
+/// seeding, background jobs).
+pub fn current_or_new() -> CorrelationId {
+    current().unwrap_or_default()
+}
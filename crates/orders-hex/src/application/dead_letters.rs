@@ -0,0 +1,117 @@
+//! Admin side of the dead letter queue the `orders-worker` relays park
+//! undeliverable events in: listing them, asking for replays, and the
+//! `orders_dlq_depth` gauge.
+
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use orders_types::domain::dead_letter::{DeadLetter, DeadLetterDepth, DeadLetterState};
+use orders_types::ports::dead_letter::DeadLetterStore;
+use orders_types::ports::metrics::MetricsSource;
+
+use crate::application::scheduler::Job;
+use crate::errors::{AppError, Resource};
+
+/// Cheap to clone; clones share the store and the last depth read.
+#[derive(Clone)]
+pub struct DeadLetterService {
+    store: Arc<dyn DeadLetterStore>,
+    depth: Arc<Mutex<Vec<DeadLetterDepth>>>,
+}
+
+impl DeadLetterService {
+    pub const MAX_PAGE: usize = 500;
+
+    pub fn new(store: Arc<dyn DeadLetterStore>) -> Self {
+        Self {
+            store,
+            depth: Arc::default(),
+        }
+    }
+
+    /// Letters in `state` (any when `None`), newest first; `limit` is capped
+    /// at [`Self::MAX_PAGE`].
+    pub async fn list(
+        &self,
+        state: Option<DeadLetterState>,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<DeadLetter>, AppError> {
+        self.store
+            .list_dead_letters(state, limit.min(Self::MAX_PAGE), offset)
+            .await
+            .map_err(AppError::from)
+    }
+
+    /// Ask the relay to deliver letter `id` again on its next poll. Asking
+    /// twice is harmless; a letter already replayed is a conflict.
+    pub async fn replay(&self, id: u64) -> Result<DeadLetter, AppError> {
+        let letter = self
+            .store
+            .request_replay(id)
+            .await
+            .map_err(AppError::from)?
+            .ok_or_else(|| AppError::NotFound(Resource::DeadLetter, id.to_string()))?;
+        if letter.state == DeadLetterState::Replayed {
+            return Err(AppError::Conflict(format!(
+                "dead letter {id} was already replayed"
+            )));
+        }
+        self.refresh_depth().await?;
+        Ok(letter)
+    }
+
+    /// Re-read the queue depth [`MetricsSource::prometheus`] reports.
+    pub async fn refresh_depth(&self) -> Result<(), AppError> {
+        let depth = self
+            .store
+            .dead_letter_depth()
+            .await
+            .map_err(AppError::from)?;
+        *self.depth.lock().expect("dlq depth poisoned") = depth;
+        Ok(())
+    }
+}
+
+impl MetricsSource for DeadLetterService {
+    fn prometheus(&self) -> String {
+        let mut out = String::from(
+            "# HELP orders_dlq_depth Dead-lettered events per relay consumer and state.\n\
+             # TYPE orders_dlq_depth gauge\n",
+        );
+        for d in self.depth.lock().expect("dlq depth poisoned").iter() {
+            let _ = writeln!(
+                out,
+                "orders_dlq_depth{{consumer=\"{}\",state=\"{}\"}} {}",
+                d.consumer.replace('\\', "\\\\").replace('"', "\\\""),
+                d.state.as_str(),
+                d.count
+            );
+        }
+        out
+    }
+}
+
+/// Keeps the depth gauge current between replays.
+#[async_trait]
+impl Job for DeadLetterService {
+    fn name(&self) -> &str {
+        "dead_letter_depth"
+    }
+
+    async fn run(&self) -> anyhow::Result<String> {
+        self.refresh_depth()
+            .await
+            .map_err(|e| anyhow::anyhow!(e.to_string()))?;
+        let dead: u64 = self
+            .depth
+            .lock()
+            .expect("dlq depth poisoned")
+            .iter()
+            .filter(|d| d.state == DeadLetterState::Dead)
+            .map(|d| d.count)
+            .sum();
+        Ok(format!("{dead} dead letter(s)"))
+    }
+}
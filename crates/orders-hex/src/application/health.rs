@@ -0,0 +1,70 @@
+//! Readiness: whether the dependencies a request needs answer right now.
+
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+/// Longest a single dependency check may take before it counts as down.
+pub const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Up,
+    Down,
+}
+
+/// Outcome of pinging one dependency.
+#[derive(Debug, Clone, Serialize)]
+pub struct DependencyCheck {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    /// Whether a failure makes the whole service unready. Optional
+    /// dependencies are reported but only degrade it.
+    pub required: bool,
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl DependencyCheck {
+    /// Run `ping` under `timeout` and record how it went.
+    pub async fn run<F>(name: &'static str, required: bool, timeout: Duration, ping: F) -> Self
+    where
+        F: Future<Output = Result<(), String>>,
+    {
+        let started = Instant::now();
+        let outcome = match tokio::time::timeout(timeout, ping).await {
+            Ok(outcome) => outcome,
+            Err(_) => Err(format!("no answer within {timeout:?}")),
+        };
+        Self {
+            name,
+            status: if outcome.is_ok() {
+                CheckStatus::Up
+            } else {
+                CheckStatus::Down
+            },
+            required,
+            latency_ms: started.elapsed().as_millis() as u64,
+            error: outcome.err(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadinessReport {
+    /// `false` when any required dependency is down.
+    pub ready: bool,
+    pub checks: Vec<DependencyCheck>,
+}
+
+impl ReadinessReport {
+    pub fn new(checks: Vec<DependencyCheck>) -> Self {
+        let ready = checks
+            .iter()
+            .all(|c| !c.required || c.status == CheckStatus::Up);
+        Self { ready, checks }
+    }
+}
@@ -1 +1,11 @@
+pub mod actor;
+pub mod api_key_service;
+pub mod auth;
+pub mod correlation;
+pub mod dead_letters;
+pub mod health;
+pub mod notifications;
 pub mod order_service;
+pub mod priority;
+pub mod scheduler;
+pub mod webhook_service;
@@ -0,0 +1,168 @@
+//! Fire-and-forget delivery of customer notifications. Order changes only
+//! enqueue; a background worker delivers through the [`Notifier`] and
+//! retries failures with exponential backoff, so a slow or flaky mail server
+//! never holds up an API call.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use orders_types::ports::notifier::{Notification, Notifier};
+use tokio::sync::mpsc;
+
+/// How hard to try before giving up on a notification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Deliveries attempted per notification, including the first.
+    pub max_attempts: u32,
+    /// Wait before the first retry; doubled for each one after.
+    pub initial_backoff: Duration,
+    /// Notifications waiting for the worker before new ones are dropped.
+    pub capacity: usize,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_secs(1),
+            capacity: 1024,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct NotificationQueue {
+    tx: mpsc::Sender<Notification>,
+}
+
+impl NotificationQueue {
+    /// Start the delivery worker on the current Tokio runtime.
+    pub fn spawn(notifier: impl Notifier, policy: RetryPolicy) -> Self {
+        let (tx, mut rx) = mpsc::channel::<Notification>(policy.capacity.max(1));
+        let notifier: Arc<dyn Notifier> = Arc::new(notifier);
+        tokio::spawn(async move {
+            while let Some(notification) = rx.recv().await {
+                // Retries back off per notification without stalling the rest.
+                tokio::spawn(deliver(notifier.clone(), notification, policy));
+            }
+        });
+        Self { tx }
+    }
+
+    /// Queue `notification` without waiting; dropped with a warning when the
+    /// queue is full.
+    pub fn enqueue(&self, notification: Notification) {
+        if let Err(e) = self.tx.try_send(notification) {
+            let notification = match e {
+                mpsc::error::TrySendError::Full(n) | mpsc::error::TrySendError::Closed(n) => n,
+            };
+            tracing::warn!(
+                order_id = %notification.order.id,
+                kind = notification.kind.as_str(),
+                "notification queue full; dropping notification"
+            );
+        }
+    }
+}
+
+async fn deliver(notifier: Arc<dyn Notifier>, notification: Notification, policy: RetryPolicy) {
+    let mut backoff = policy.initial_backoff;
+    for attempt in 1..=policy.max_attempts.max(1) {
+        match notifier.notify(&notification).await {
+            Ok(()) => return,
+            Err(e) if attempt < policy.max_attempts => {
+                tracing::debug!(
+                    order_id = %notification.order.id,
+                    kind = notification.kind.as_str(),
+                    attempt,
+                    error = %e,
+                    "notification failed; retrying"
+                );
+                tokio::time::sleep(backoff).await;
+                backoff = backoff.saturating_mul(2);
+            }
+            Err(e) => {
+                tracing::warn!(
+                    order_id = %notification.order.id,
+                    kind = notification.kind.as_str(),
+                    attempts = attempt,
+                    error = %e,
+                    "giving up on notification"
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use orders_types::domain::money::Money;
+    use orders_types::domain::order::{Order, OrderItem};
+    use orders_types::ports::notifier::NotificationKind;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Fails the first `failures` attempts, then succeeds.
+    struct Flaky {
+        failures: u32,
+        attempts: Arc<AtomicU32>,
+    }
+
+    #[async_trait::async_trait]
+    impl Notifier for Flaky {
+        async fn notify(&self, _n: &Notification) -> Result<(), String> {
+            let attempt = self.attempts.fetch_add(1, Ordering::SeqCst) + 1;
+            if attempt <= self.failures {
+                Err("smtp down".into())
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    fn notification() -> Notification {
+        let order = Order::new(
+            "Nia".into(),
+            "nia@example.com".into(),
+            vec![OrderItem {
+                name: "Widget".into(),
+                qty: 1,
+                unit_price: Money::usd(100),
+                weight_grams: 0,
+                sku: None,
+                description: None,
+                metadata: Default::default(),
+                discount_cents: 0,
+            }],
+        )
+        .unwrap();
+        Notification {
+            kind: NotificationKind::Created,
+            order,
+        }
+    }
+
+    async fn attempts_with(failures: u32, max_attempts: u32) -> u32 {
+        let attempts = Arc::new(AtomicU32::new(0));
+        let queue = NotificationQueue::spawn(
+            Flaky {
+                failures,
+                attempts: attempts.clone(),
+            },
+            RetryPolicy {
+                max_attempts,
+                initial_backoff: Duration::from_millis(1),
+                capacity: 4,
+            },
+        );
+        queue.enqueue(notification());
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        attempts.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test]
+    async fn failures_are_retried_until_the_attempt_limit() {
+        assert_eq!(attempts_with(2, 5).await, 3);
+        assert_eq!(attempts_with(10, 3).await, 3);
+    }
+}
@@ -1,147 +1,3323 @@
-use crate::errors::AppError;
-use orders_types::domain::order::{Order, OrderItem, OrderStatus};
-use orders_types::ports::order_repository::OrderRepository;
+use crate::application::actor;
+use crate::application::auth::{self, OrderAction};
+use crate::application::correlation;
+use crate::application::health::{DependencyCheck, ReadinessReport, CHECK_TIMEOUT};
+use crate::application::notifications::NotificationQueue;
+use crate::application::priority::{Admission, CallerClass, ClassStats, PriorityGate};
+use crate::errors::{AppError, Resource};
+use orders_types::domain::address::Address;
+use orders_types::domain::audit::AuditEntry;
+use orders_types::domain::discount::{AppliedDiscount, Discount};
+use orders_types::domain::email;
+use orders_types::domain::events::{EventEnvelope, OrderEvent};
+use orders_types::domain::filter::{OrderFilter, OrderPage};
+use orders_types::domain::fulfillment::{fully_fulfilled, Fulfillment};
+use orders_types::domain::history::OrderHistoryEntry;
+use orders_types::domain::import::{ImportProgress, ImportRecord};
+use orders_types::domain::integrity::{IntegrityIssue, IntegrityReport, StatusMapping};
+use orders_types::domain::money::Money;
+use orders_types::domain::order::{
+    check_metadata, FieldError, Order, OrderItem, OrderLimits, OrderStatus, RepriceError,
+};
+use orders_types::domain::order_number::OrderNumber;
+use orders_types::domain::order_patch::OrderPatch;
+use orders_types::domain::pricing::{PricingDiff, PricingSnapshot};
+use orders_types::domain::share::{ShareSigner, ShareToken};
+use orders_types::domain::stats::{OrderStats, StatsRange};
+use orders_types::domain::tenant::TenantId;
+use orders_types::ports::audit_repository::AuditRepository;
+use orders_types::ports::clock::{Clock, SystemClock};
+use orders_types::ports::discount_repository::DiscountRepository;
+use orders_types::ports::email_validation::EmailValidator;
+use orders_types::ports::id::{IdGenerator, TimeOrderedIds};
+use orders_types::ports::inventory::{InventoryError, InventoryService, StockLine};
+use orders_types::ports::notifier::{Notification, NotificationKind};
+use orders_types::ports::order_read_repository::OrderReadRepository;
+use orders_types::ports::order_repository::{OrderRepository, RepoError};
+use orders_types::ports::payment_gateway::{PaymentError, PaymentGateway};
+use orders_types::ports::pricing::{ItemPriceRules, PricingRules};
+use orders_types::ports::refund::RefundGateway;
+use orders_types::ports::unit_of_work::{ChangeKind, UnitOfWork};
+use orders_types::ports::validation::{FailurePolicy, OrderValidator};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
 use uuid::Uuid;
 
+/// Buffered events per subscriber before it starts lagging.
+const EVENT_CAPACITY: usize = 256;
+
 pub struct OrderService<R: OrderRepository> {
     repo: R,
+    pricing: Arc<dyn PricingRules>,
+    limits: OrderLimits,
+    events: broadcast::Sender<EventEnvelope>,
+    status_mapping: StatusMapping,
+    share_signer: Option<ShareSigner>,
+    validator: Option<ValidatorHook>,
+    email_validator: Option<Arc<dyn EmailValidator>>,
+    priority: Option<PriorityGate>,
+    discounts: Option<Arc<dyn DiscountRepository>>,
+    refunds: Option<Arc<dyn RefundGateway>>,
+    payments: Option<Arc<dyn PaymentGateway>>,
+    inventory: Option<Arc<dyn InventoryService>>,
+    notifications: Option<NotificationQueue>,
+    audit: Option<Arc<dyn AuditRepository>>,
+    read_model: Option<Arc<dyn OrderReadRepository>>,
+    clock: Arc<dyn Clock>,
+    ids: Arc<dyn IdGenerator>,
+    dedupe_window: Option<chrono::Duration>,
+}
+
+/// External pre-check run on every new order before it is stored.
+struct ValidatorHook {
+    validator: Arc<dyn OrderValidator>,
+    timeout: Duration,
+    policy: FailurePolicy,
+}
+
+/// Everything a new order is created from; see [`OrderService::place_order`].
+#[derive(Debug, Clone, Default)]
+pub struct NewOrder {
+    pub customer_name: String,
+    pub email: String,
+    pub items: Vec<OrderItem>,
+    pub discount_code: Option<String>,
+    pub shipping_address: Option<Address>,
+    pub billing_address: Option<Address>,
+    pub metadata: BTreeMap<String, String>,
+}
+
+/// What [`OrderService::place_or_find_order`] did.
+#[derive(Debug, Clone)]
+pub enum Placed {
+    Created(Order),
+    /// An identical order placed within the dedupe window, returned instead
+    /// of a new one.
+    Duplicate(Order),
+}
+
+impl Placed {
+    pub fn into_order(self) -> Order {
+        match self {
+            Placed::Created(order) | Placed::Duplicate(order) => order,
+        }
+    }
+}
+
+/// A recorded shipment and the order after it; the order is `Shipped` once
+/// every item has gone out.
+#[derive(Debug, Clone, Serialize)]
+pub struct FulfillmentOutcome {
+    pub fulfillment: Fulfillment,
+    pub order: Order,
+}
+
+/// Cancellation reason recorded on orders the stale-order sweep expires.
+pub const EXPIRY_REASON: &str = "expired: still pending past the time limit";
+
+/// What one [stale-order sweep](OrderService::expire_stale_orders) did.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ExpiryReport {
+    pub expired: usize,
+    /// Orders that could not be cancelled, e.g. because they were confirmed
+    /// meanwhile; still-pending ones come up again in the next sweep.
+    pub failed: usize,
+}
+
+/// Outcome of an explicit re-price: the updated order plus what changed.
+#[derive(Debug, Clone, Serialize)]
+pub struct RepriceOutcome {
+    pub order: Order,
+    pub before: PricingSnapshot,
+    pub diff: PricingDiff,
+}
+
+/// Everything stored about one customer, for a data subject access request.
+#[derive(Debug, Clone, Serialize)]
+pub struct CustomerExport {
+    pub email: String,
+    pub exported_at: chrono::DateTime<chrono::Utc>,
+    pub orders: Vec<Order>,
+}
+
+/// What [erasing a customer](OrderService::erase_customer) changed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ErasureReport {
+    pub orders: usize,
+    /// Audit entries whose copies of those orders were scrubbed too.
+    pub audit_entries: u64,
 }
 
 impl<R: OrderRepository> OrderService<R> {
     pub fn new(repo: R) -> Self {
-        Self { repo }
+        let (events, _) = broadcast::channel(EVENT_CAPACITY);
+        Self {
+            repo,
+            pricing: Arc::new(ItemPriceRules),
+            limits: OrderLimits::default(),
+            events,
+            status_mapping: StatusMapping::default(),
+            share_signer: None,
+            validator: None,
+            email_validator: None,
+            priority: None,
+            discounts: None,
+            refunds: None,
+            payments: None,
+            inventory: None,
+            notifications: None,
+            audit: None,
+            read_model: None,
+            clock: Arc::new(SystemClock),
+            ids: Arc::new(TimeOrderedIds),
+            dedupe_window: None,
+        }
+    }
+
+    /// Receive an [`OrderEvent`] for every mutation made through this service,
+    /// stamped with the correlation id of the request that caused it.
+    pub fn subscribe(&self) -> broadcast::Receiver<EventEnvelope> {
+        self.events.subscribe()
+    }
+
+    fn publish(&self, event: OrderEvent) {
+        let correlation_id = correlation::current_or_new();
+        tracing::debug!(
+            %correlation_id,
+            order_id = %event.order_id(),
+            tenant = %event.tenant_id(),
+            "publishing order event"
+        );
+        // No receivers is fine: nobody is listening right now.
+        let _ = self.events.send(EventEnvelope {
+            correlation_id,
+            actor: actor::current(),
+            event,
+        });
+    }
+
+    /// Take the time from `clock` instead of the system's, for timestamps,
+    /// discount validity, share link expiry and the stale order sweep.
+    pub fn with_clock(mut self, clock: impl Clock) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Take the ids of new orders and audit entries from `ids` instead of
+    /// time-ordered version 7 ones.
+    pub fn with_id_generator(mut self, ids: impl IdGenerator) -> Self {
+        self.ids = Arc::new(ids);
+        self
+    }
+
+    /// The current time, per the service's clock.
+    pub fn now(&self) -> chrono::DateTime<chrono::Utc> {
+        self.clock.now()
+    }
+
+    /// Use `rules` as the current catalog pricing, including tax and
+    /// shipping on new orders.
+    pub fn with_pricing_rules(mut self, rules: impl PricingRules) -> Self {
+        self.pricing = Arc::new(rules);
+        self
+    }
+
+    /// Reject new orders and item changes over `limits` instead of the
+    /// defaults.
+    pub fn with_limits(mut self, limits: OrderLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Treat a new order with the same email and items as one the tenant
+    /// placed less than `window` ago as that order submitted twice, and
+    /// return it instead of creating another; see [`Placed`].
+    pub fn with_dedupe_window(mut self, window: chrono::Duration) -> Self {
+        self.dedupe_window = Some(window);
+        self
+    }
+
+    /// Translate these legacy stored statuses when the integrity pass fixes
+    /// rows.
+    pub fn with_status_mapping(mut self, mapping: StatusMapping) -> Self {
+        self.status_mapping = mapping;
+        self
+    }
+
+    /// Enable signed share links, minted and verified with `signer`.
+    pub fn with_share_signer(mut self, signer: ShareSigner) -> Self {
+        self.share_signer = Some(signer);
+        self
+    }
+
+    /// Ask `validator` to accept every new order before it is stored. No
+    /// verdict within `timeout` (or a validator error) is handled per
+    /// `policy`.
+    pub fn with_validator(
+        mut self,
+        validator: impl OrderValidator,
+        timeout: Duration,
+        policy: FailurePolicy,
+    ) -> Self {
+        self.validator = Some(ValidatorHook {
+            validator: Arc::new(validator),
+            timeout,
+            policy,
+        });
+        self
+    }
+
+    /// Ask `validator` about the email of every new order, and of orders
+    /// whose email is changed, on top of the syntax check. Imports only get
+    /// the syntax check.
+    pub fn with_email_validator(mut self, validator: impl EmailValidator) -> Self {
+        self.email_validator = Some(Arc::new(validator));
+        self
+    }
+
+    /// Limit concurrent calls through `gate`. Imports and integrity passes
+    /// admit themselves as background work; adapters admit interactive
+    /// callers with [`OrderService::admit`].
+    pub fn with_priority(mut self, gate: PriorityGate) -> Self {
+        self.priority = Some(gate);
+        self
+    }
+
+    /// Wait for a slot for `class`; `None` (immediately) when no gate is
+    /// configured. Hold the admission for the duration of the call.
+    pub async fn admit(&self, class: CallerClass) -> Option<Admission> {
+        match &self.priority {
+            Some(gate) => Some(gate.admit(class).await),
+            None => None,
+        }
+    }
+
+    /// Queue metrics per caller class; empty when no gate is configured.
+    pub fn priority_stats(&self) -> Vec<ClassStats> {
+        self.priority
+            .as_ref()
+            .map(PriorityGate::stats)
+            .unwrap_or_default()
+    }
+
+    /// Accept discount codes stored in `repo` on new orders.
+    pub fn with_discounts(mut self, repo: impl DiscountRepository) -> Self {
+        self.discounts = Some(Arc::new(repo));
+        self
+    }
+
+    /// Refund paid orders through `gateway` when they are cancelled.
+    pub fn with_refunds(mut self, gateway: impl RefundGateway) -> Self {
+        self.refunds = Some(Arc::new(gateway));
+        self
+    }
+
+    /// Charge orders through `gateway` when they are confirmed, and refund
+    /// them through it when they are cancelled afterwards. Replaces any
+    /// gateway set with [`OrderService::with_refunds`].
+    pub fn with_payments(mut self, gateway: impl PaymentGateway) -> Self {
+        let gateway: Arc<dyn PaymentGateway> = Arc::new(gateway);
+        self.refunds = Some(Arc::new(PaymentRefunds(gateway.clone())));
+        self.payments = Some(gateway);
+        self
+    }
+
+    /// Reserve stock in `inventory` for new orders, failing them when it runs
+    /// out; released on cancellation, committed when the order ships.
+    pub fn with_inventory(mut self, inventory: impl InventoryService) -> Self {
+        self.inventory = Some(Arc::new(inventory));
+        self
+    }
+
+    /// Hold the stock `order` needs, replacing what it held before.
+    async fn reserve_stock(&self, order: &Order) -> Result<(), AppError> {
+        let Some(inventory) = &self.inventory else {
+            return Ok(());
+        };
+        inventory
+            .reserve(order.id, &StockLine::for_items(&order.items))
+            .await
+            .map_err(|e| match e {
+                InventoryError::InsufficientStock {
+                    sku,
+                    requested,
+                    available,
+                } => AppError::InsufficientStock {
+                    sku,
+                    requested,
+                    available,
+                },
+                InventoryError::Unavailable(m) => AppError::Unavailable(m),
+            })
+    }
+
+    /// Best effort: a failure leaves stock held, which is logged rather than
+    /// failing a change that has already been stored.
+    async fn release_stock(&self, order_id: Uuid) {
+        if let Some(inventory) = &self.inventory {
+            if let Err(e) = inventory.release(order_id).await {
+                tracing::warn!(%order_id, error = %e, "failed to release reserved stock");
+            }
+        }
+    }
+
+    /// Best effort, like [`OrderService::release_stock`].
+    async fn commit_stock(&self, order_id: Uuid) {
+        if let Some(inventory) = &self.inventory {
+            if let Err(e) = inventory.commit(order_id).await {
+                tracing::warn!(%order_id, error = %e, "failed to commit reserved stock");
+            }
+        }
+    }
+
+    /// Tell customers through `queue` when their order is created, ships or
+    /// is cancelled. Imported orders are not announced.
+    pub fn with_notifications(mut self, queue: NotificationQueue) -> Self {
+        self.notifications = Some(queue);
+        self
+    }
+
+    fn notify(&self, kind: NotificationKind, order: &Order) {
+        if let Some(queue) = &self.notifications {
+            queue.enqueue(Notification {
+                kind,
+                order: order.clone(),
+            });
+        }
+    }
+
+    /// Record every create, change and delete, with who made it and the
+    /// order before and after, in `repo`.
+    pub fn with_audit(mut self, repo: impl AuditRepository) -> Self {
+        self.audit = Some(Arc::new(repo));
+        self
+    }
+
+    /// Serve [`get_order`](Self::get_order), [`list_page`](Self::list_page)
+    /// and [`count_orders`](Self::count_orders) from `reads`, a projected
+    /// read model, instead of the write store. Writes still read the order
+    /// they change from the write store.
+    pub fn with_read_model(mut self, reads: impl OrderReadRepository) -> Self {
+        self.read_model = Some(Arc::new(reads));
+        self
+    }
+
+    /// Best effort: the change is already stored, so a failure to record it
+    /// is logged rather than reported to the caller.
+    async fn audit(&self, mut entry: AuditEntry) {
+        if let Some(audit) = &self.audit {
+            entry.id = self.ids.new_id();
+            entry.at = self.clock.now();
+            let order_id = entry.order_id;
+            if let Err(e) = audit.record_audit(entry).await {
+                tracing::error!(%order_id, error = %e, "failed to record audit entry");
+            }
+        }
+    }
+
+    /// Store new `orders`, each attributed to the current actor and with
+    /// the first entry of its status history, in one unit of work: all or
+    /// nothing where the repository has transactions. Returns them as
+    /// stored, with their order numbers.
+    async fn store_new(&self, orders: &[Order]) -> Result<Vec<Order>, RepoError> {
+        let actor = actor::current();
+        let mut unit = self.repo.begin().await?;
+        let mut stored = Vec::with_capacity(orders.len());
+        for order in orders {
+            let order = unit
+                .create(order.clone().with_creator(actor.clone()))
+                .await?;
+            if let Some(entry) = transition(None, &order, None) {
+                unit.record_transition(&order.tenant_id, order.id, entry)
+                    .await?;
+            }
+            stored.push(order);
+        }
+        unit.commit().await?;
+        Ok(stored)
+    }
+
+    /// Store `order`, the copy of `read` it changes, as last changed by the
+    /// current actor, and append any change of status to its history, in one
+    /// unit of work. The write only lands while the stored order is still
+    /// `read`, and conflicts otherwise. `None` when the order doesn't exist.
+    async fn store_update(
+        &self,
+        read: &Order,
+        order: Order,
+        note: Option<&str>,
+        kind: ChangeKind,
+    ) -> Result<Option<Order>, RepoError> {
+        let mut unit = self.repo.begin().await?;
+        let Some(order) = stage_update(unit.as_mut(), read, order, note, kind).await? else {
+            return Ok(None);
+        };
+        unit.commit().await?;
+        Ok(Some(order))
+    }
+
+    /// Store a shipment together with `order`, the copy of `read` it
+    /// changes, in one unit of work. The order is written even when its
+    /// status stays: the write only lands while the stored order is still
+    /// `read`, so a shipment checked against what had shipped as of `read`
+    /// conflicts with any other recorded since. `None` when the order
+    /// doesn't exist.
+    async fn store_fulfillment(
+        &self,
+        read: &Order,
+        mut order: Order,
+        fulfillment: Fulfillment,
+    ) -> Result<Option<Order>, RepoError> {
+        order.updated_by = Some(actor::current());
+        let mut unit = self.repo.begin().await?;
+        let Some(order) = unit.update_from(order, read, ChangeKind::Updated).await? else {
+            return Ok(None);
+        };
+        unit.record_fulfillment(&order.tenant_id, order.id, fulfillment)
+            .await?;
+        if let Some(entry) = transition(Some(&read.status), &order, Some("all items fulfilled")) {
+            unit.record_transition(&order.tenant_id, order.id, entry)
+                .await?;
+        }
+        unit.commit().await?;
+        Ok(Some(order))
+    }
+
+    fn audit_log(&self) -> Result<&dyn AuditRepository, AppError> {
+        self.audit
+            .as_deref()
+            .ok_or_else(|| AppError::BadRequest("the audit log is not enabled".into()))
+    }
+
+    /// Every status the order has been in, oldest first, starting with its
+    /// creation.
+    pub async fn order_history(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+    ) -> Result<Vec<OrderHistoryEntry>, AppError> {
+        auth::authorize(OrderAction::View)?;
+        if !self.order_exists(tenant, id).await? {
+            return Err(AppError::NotFound(Resource::Order, id.to_string()));
+        }
+        self.repo
+            .status_history(tenant, id)
+            .await
+            .map_err(AppError::from)
+    }
+
+    /// Every shipment of the order, oldest first.
+    pub async fn order_fulfillments(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+    ) -> Result<Vec<Fulfillment>, AppError> {
+        auth::authorize(OrderAction::View)?;
+        if !self.order_exists(tenant, id).await? {
+            return Err(AppError::NotFound(Resource::Order, id.to_string()));
+        }
+        self.repo
+            .fulfillments(tenant, id)
+            .await
+            .map_err(AppError::from)
+    }
+
+    /// Every recorded change to one order, oldest first.
+    pub async fn order_audit(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+    ) -> Result<Vec<AuditEntry>, AppError> {
+        auth::authorize(OrderAction::View)?;
+        let entries = self
+            .audit_log()?
+            .order_audit(tenant, id)
+            .await
+            .map_err(AppError::from)?;
+        // Deleted orders keep their history; only never-seen ids are unknown.
+        if entries.is_empty() && !self.order_exists(tenant, id).await? {
+            return Err(AppError::NotFound(Resource::Order, id.to_string()));
+        }
+        Ok(entries)
+    }
+
+    /// One page of the tenant's audit log, newest first.
+    pub async fn list_audit(
+        &self,
+        tenant: &TenantId,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<AuditEntry>, AppError> {
+        auth::authorize(OrderAction::Maintain)?;
+        self.audit_log()?
+            .list_audit(tenant, limit, offset)
+            .await
+            .map_err(AppError::from)
+    }
+
+    fn discounts(&self) -> Result<&dyn DiscountRepository, AppError> {
+        self.discounts
+            .as_deref()
+            .ok_or_else(|| AppError::BadRequest("discount codes are not enabled".into()))
+    }
+
+    /// Run the configured validator, if any, against a candidate order.
+    async fn prevalidate(&self, order: &Order) -> Result<(), AppError> {
+        let Some(hook) = &self.validator else {
+            return Ok(());
+        };
+        let outcome = match tokio::time::timeout(hook.timeout, hook.validator.validate(order)).await
+        {
+            Ok(outcome) => outcome,
+            Err(_) => Err(format!("no answer within {:?}", hook.timeout)),
+        };
+        match outcome {
+            Ok(verdict) if verdict.accept => Ok(()),
+            Ok(verdict) => {
+                let reason = verdict.reason.unwrap_or_else(|| "no reason given".into());
+                tracing::info!(order_id = %order.id, %reason, "order rejected by validator");
+                Err(AppError::Rejected(reason))
+            }
+            Err(e) => match hook.policy {
+                FailurePolicy::FailOpen => {
+                    tracing::warn!(order_id = %order.id, error = %e, "validator failed; accepting order (fail-open)");
+                    Ok(())
+                }
+                FailurePolicy::FailClosed => {
+                    tracing::warn!(order_id = %order.id, error = %e, "validator failed; refusing order (fail-closed)");
+                    Err(AppError::Unavailable("order validator unavailable".into()))
+                }
+            },
+        }
+    }
+
+    /// Ping the repository and, when configured, the order validator. The
+    /// validator only gates readiness under [`FailurePolicy::FailClosed`];
+    /// fail-open orders are still accepted while it is down.
+    pub async fn readiness(&self) -> ReadinessReport {
+        let mut checks = vec![
+            DependencyCheck::run("repository", true, CHECK_TIMEOUT, async {
+                self.repo.ping().await.map_err(|e| e.to_string())
+            })
+            .await,
+        ];
+        if let Some(hook) = &self.validator {
+            let required = hook.policy == FailurePolicy::FailClosed;
+            checks.push(
+                DependencyCheck::run(
+                    "order_validator",
+                    required,
+                    CHECK_TIMEOUT,
+                    hook.validator.ping(),
+                )
+                .await,
+            );
+        }
+        ReadinessReport::new(checks)
+    }
+
+    pub async fn create_order(
+        &self,
+        tenant: &TenantId,
+        customer_name: String,
+        email: String,
+        items: Vec<OrderItem>,
+    ) -> Result<Order, AppError> {
+        auth::authorize(OrderAction::Create)?;
+        self.create_order_with_discount(tenant, customer_name, email, items, None)
+            .await
+    }
+
+    /// Create an order, taking `discount_code` off its total when given. An
+    /// unknown, expired, used-up or inapplicable code fails validation on
+    /// `discount_code`; a use is only counted once the order is stored.
+    pub async fn create_order_with_discount(
+        &self,
+        tenant: &TenantId,
+        customer_name: String,
+        email: String,
+        items: Vec<OrderItem>,
+        discount_code: Option<&str>,
+    ) -> Result<Order, AppError> {
+        auth::authorize(OrderAction::Create)?;
+        let new = NewOrder {
+            customer_name,
+            email,
+            items,
+            discount_code: discount_code.map(String::from),
+            ..NewOrder::default()
+        };
+        self.place_order(tenant, new).await
+    }
+
+    /// Create an order from `new`, validating its items and addresses
+    /// together; the discount code is handled as in
+    /// [`OrderService::create_order_with_discount`]. Within the dedupe
+    /// window this may be an earlier, identical order; see
+    /// [`OrderService::place_or_find_order`] to tell.
+    pub async fn place_order(&self, tenant: &TenantId, new: NewOrder) -> Result<Order, AppError> {
+        auth::authorize(OrderAction::Create)?;
+        Ok(self.place_or_find_order(tenant, new).await?.into_order())
+    }
+
+    /// [`OrderService::place_order`], saying whether the order is new or a
+    /// duplicate found within the [dedupe window](Self::with_dedupe_window).
+    /// A duplicate costs no stock or discount use. Two submissions racing
+    /// each other can both be created; the check is against stored orders
+    /// only.
+    pub async fn place_or_find_order(
+        &self,
+        tenant: &TenantId,
+        new: NewOrder,
+    ) -> Result<Placed, AppError> {
+        auth::authorize(OrderAction::Create)?;
+        let mut errors = Order::check(&new.customer_name, &new.email, &new.items);
+        errors.extend(
+            self.limits
+                .check(&new.customer_name, &new.email, &new.items),
+        );
+        if let Some(address) = &new.shipping_address {
+            errors.extend(address.check("shipping_address"));
+        }
+        if let Some(address) = &new.billing_address {
+            errors.extend(address.check("billing_address"));
+        }
+        errors.extend(check_metadata(&new.metadata, "metadata"));
+        if !errors.is_empty() {
+            return Err(AppError::Validation(errors));
+        }
+        if let Some(existing) = self.find_duplicate(tenant, &new).await? {
+            tracing::info!(
+                order_id = %existing.id,
+                tenant = %tenant,
+                "returning duplicate order placed within the dedupe window"
+            );
+            return Ok(Placed::Duplicate(existing));
+        }
+        self.check_email(&email::normalize(&new.email)).await?;
+        let mut order = Order::new_priced_at(
+            new.customer_name,
+            new.email,
+            new.items,
+            self.pricing.as_ref(),
+            self.clock.now(),
+        )
+        .map_err(|e| AppError::Validation(vec![e.into()]))?
+        .with_id(self.ids.new_id())
+        .with_tenant(tenant.clone())
+        .with_addresses(new.shipping_address, new.billing_address)
+        .with_metadata(new.metadata);
+        if let Some(code) = new.discount_code.as_deref() {
+            order
+                .apply_discount(self.quote_discount(tenant, code, &order).await?)
+                .map_err(|e| AppError::Validation(vec![e.into()]))?;
+        }
+        if let Some(e) = self.limits.check_total(order.total) {
+            return Err(AppError::Validation(vec![e]));
+        }
+        self.prevalidate(&order).await?;
+        self.reserve_stock(&order).await?;
+        let redeemed = match &order.discount {
+            Some(applied) => match self.redeem_discount(tenant, &applied.code).await {
+                Ok(code) => Some(code),
+                Err(e) => {
+                    self.release_stock(order.id).await;
+                    return Err(e);
+                }
+            },
+            None => None,
+        };
+        let order = match self.store_new(std::slice::from_ref(&order)).await {
+            Ok(mut stored) => stored.remove(0),
+            Err(e) => {
+                if let Some(code) = redeemed {
+                    // Best effort: the order was never stored, so neither was its use.
+                    let _ = self.discounts()?.release_discount(tenant, &code).await;
+                }
+                self.release_stock(order.id).await;
+                return Err(e.into());
+            }
+        };
+        self.audit(AuditEntry::created(actor::current(), order.clone()))
+            .await;
+        self.publish(OrderEvent::Created {
+            order: order.clone(),
+        });
+        self.notify(NotificationKind::Created, &order);
+        Ok(Placed::Created(order))
+    }
+
+    /// Refuse `email` when the [email validator](Self::with_email_validator)
+    /// does.
+    async fn check_email(&self, email: &str) -> Result<(), AppError> {
+        let Some(validator) = &self.email_validator else {
+            return Ok(());
+        };
+        validator
+            .validate(email)
+            .await
+            .map_err(|message| AppError::Validation(vec![FieldError::new("email", message)]))
+    }
+
+    /// The newest order of `tenant`, not cancelled, with the same
+    /// [fingerprint](Order::fingerprint) as `new` and created within the
+    /// dedupe window; never one without a window.
+    async fn find_duplicate(
+        &self,
+        tenant: &TenantId,
+        new: &NewOrder,
+    ) -> Result<Option<Order>, AppError> {
+        let Some(window) = self.dedupe_window else {
+            return Ok(None);
+        };
+        let email = email::normalize(&new.email);
+        let recent = OrderFilter::default()
+            .with_email(email.as_str())
+            .with_created_after(self.clock.now() - window);
+        let fingerprint = Order::fingerprint(&email, &new.items);
+        let candidates = self
+            .repo
+            .list_filtered(tenant, &recent)
+            .await
+            .map_err(AppError::from)?;
+        Ok(candidates
+            .into_iter()
+            .filter(|o| o.status != OrderStatus::Cancelled)
+            .filter(|o| Order::fingerprint(&o.email, &o.items) == fingerprint)
+            .max_by_key(|o| o.created_at))
+    }
+
+    /// How much `code` takes off `order`, without counting a use.
+    async fn quote_discount(
+        &self,
+        tenant: &TenantId,
+        code: &str,
+        order: &Order,
+    ) -> Result<AppliedDiscount, AppError> {
+        let code = Discount::normalize_code(code);
+        let refuse =
+            |message: String| AppError::Validation(vec![FieldError::new("discount_code", message)]);
+        let discount = self
+            .discounts()?
+            .get_discount(tenant, &code)
+            .await
+            .map_err(AppError::from)?
+            .ok_or_else(|| refuse(format!("unknown discount code `{code}`")))?;
+        let amount_cents = discount
+            .amount_off(order.total, self.clock.now())
+            .map_err(|r| refuse(format!("discount code `{code}` {r}")))?;
+        Ok(AppliedDiscount { code, amount_cents })
+    }
+
+    /// Count one use of `code`, returning it; fails validation when another
+    /// order took the last use first.
+    async fn redeem_discount(&self, tenant: &TenantId, code: &str) -> Result<String, AppError> {
+        let redeemed = self
+            .discounts()?
+            .redeem_discount(tenant, code)
+            .await
+            .map_err(AppError::from)?;
+        if !redeemed {
+            return Err(AppError::Validation(vec![FieldError::new(
+                "discount_code",
+                format!("discount code `{code}` has no uses left"),
+            )]));
+        }
+        Ok(code.to_string())
+    }
+
+    /// Store a new discount code for `tenant`.
+    pub async fn create_discount(
+        &self,
+        tenant: &TenantId,
+        mut discount: Discount,
+    ) -> Result<Discount, AppError> {
+        auth::authorize(OrderAction::Maintain)?;
+        discount.code = Discount::normalize_code(&discount.code);
+        discount.tenant_id = tenant.clone();
+        discount.uses = 0;
+        let errors = discount.check();
+        if !errors.is_empty() {
+            return Err(AppError::Validation(errors));
+        }
+        let created = self
+            .discounts()?
+            .create_discount(discount.clone())
+            .await
+            .map_err(AppError::from)?;
+        if !created {
+            return Err(AppError::Validation(vec![FieldError::new(
+                "code",
+                format!("discount code `{}` already exists", discount.code),
+            )]));
+        }
+        Ok(discount)
+    }
+
+    pub async fn list_discounts(&self, tenant: &TenantId) -> Result<Vec<Discount>, AppError> {
+        auth::authorize(OrderAction::Maintain)?;
+        self.discounts()?
+            .list_discounts(tenant)
+            .await
+            .map_err(AppError::from)
+    }
+
+    pub async fn delete_discount(&self, tenant: &TenantId, code: &str) -> Result<(), AppError> {
+        auth::authorize(OrderAction::Maintain)?;
+        let code = Discount::normalize_code(code);
+        let deleted = self
+            .discounts()?
+            .delete_discount(tenant, &code)
+            .await
+            .map_err(AppError::from)?;
+        if deleted {
+            Ok(())
+        } else {
+            Err(AppError::NotFound(Resource::Discount, code))
+        }
+    }
+
+    /// Check and store one batch of imported records with a single repository
+    /// call, tallying outcomes into `progress`. Invalid or rejected records
+    /// count as failures; a storage error aborts the import.
+    pub async fn import_batch(
+        &self,
+        tenant: &TenantId,
+        batch: Vec<(u64, ImportRecord)>,
+        progress: &mut ImportProgress,
+    ) -> Result<(), AppError> {
+        auth::authorize(OrderAction::Create)?;
+        let _admission = self.admit(CallerClass::Background).await;
+        let mut orders = Vec::with_capacity(batch.len());
+        for (line, record) in batch {
+            let mut errors = Order::check(&record.customer_name, &record.email, &record.items);
+            errors.extend(
+                self.limits
+                    .check(&record.customer_name, &record.email, &record.items),
+            );
+            if let Some(e) = errors.first() {
+                progress.record_failure(line, format!("{}: {}", e.field, e.message));
+                continue;
+            }
+            let order = match Order::new_priced_at(
+                record.customer_name,
+                record.email,
+                record.items,
+                self.pricing.as_ref(),
+                self.clock.now(),
+            ) {
+                Ok(order) => order.with_id(self.ids.new_id()).with_tenant(tenant.clone()),
+                Err(e) => {
+                    progress.record_failure(line, e.to_string());
+                    continue;
+                }
+            };
+            if let Some(e) = self.limits.check_total(order.total) {
+                progress.record_failure(line, format!("{}: {}", e.field, e.message));
+                continue;
+            }
+            if let Err(e) = self.prevalidate(&order).await {
+                progress.record_failure(line, e.to_string());
+                continue;
+            }
+            if let Err(e) = self.reserve_stock(&order).await {
+                progress.record_failure(line, e.to_string());
+                continue;
+            }
+            orders.push(order);
+        }
+        let orders = match self.store_new(&orders).await {
+            Ok(stored) => stored,
+            Err(e) => {
+                for order in &orders {
+                    self.release_stock(order.id).await;
+                }
+                return Err(e.into());
+            }
+        };
+        progress.imported += orders.len() as u64;
+        for order in orders {
+            self.audit(AuditEntry::created(actor::current(), order.clone()))
+                .await;
+            self.publish(OrderEvent::Created { order });
+        }
+        Ok(())
+    }
+
+    pub async fn get_order(&self, tenant: &TenantId, id: Uuid) -> Result<Order, AppError> {
+        auth::authorize(OrderAction::View)?;
+        self.find_order(tenant, id).await
+    }
+
+    /// [`get_order`](Self::get_order) without the role check, for callers
+    /// that authorized the read some other way.
+    async fn find_order(&self, tenant: &TenantId, id: Uuid) -> Result<Order, AppError> {
+        let found = match &self.read_model {
+            Some(reads) => reads.get_view(tenant, id).await,
+            None => self.repo.get(tenant, id).await,
+        };
+        match found.map_err(AppError::from)? {
+            Some(o) => Ok(o),
+            None => Err(AppError::NotFound(Resource::Order, id.to_string())),
+        }
+    }
+
+    /// The order `reference` names: its id, or its order number such as
+    /// `ORD-2026-000123`. Numbers are looked up in the write store.
+    pub async fn get_order_by_ref(
+        &self,
+        tenant: &TenantId,
+        reference: &str,
+    ) -> Result<Order, AppError> {
+        auth::authorize(OrderAction::View)?;
+        if let Ok(id) = Uuid::parse_str(reference) {
+            return self.get_order(tenant, id).await;
+        }
+        let number = OrderNumber::parse(reference).map_err(|_| {
+            AppError::BadRequest(format!(
+                "`{reference}` is neither an order id nor an order number"
+            ))
+        })?;
+        match self
+            .repo
+            .get_by_number(tenant, &number)
+            .await
+            .map_err(AppError::from)?
+        {
+            Some(o) => Ok(o),
+            None => Err(AppError::NotFound(Resource::Order, number.to_string())),
+        }
+    }
+
+    /// The current order from the write store, for changes to it.
+    async fn load_order(&self, tenant: &TenantId, id: Uuid) -> Result<Order, AppError> {
+        match self.repo.get(tenant, id).await.map_err(AppError::from)? {
+            Some(o) => Ok(o),
+            None => Err(AppError::NotFound(Resource::Order, id.to_string())),
+        }
+    }
+
+    fn share_signer(&self) -> Result<&ShareSigner, AppError> {
+        self.share_signer
+            .as_ref()
+            .ok_or_else(|| AppError::BadRequest("share links are not enabled".into()))
+    }
+
+    /// Mint a read-only link token for an existing order, valid for `ttl`
+    /// or, when unset, a day capped at the signer's maximum.
+    pub async fn share_order(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        ttl: Option<chrono::Duration>,
+    ) -> Result<ShareToken, AppError> {
+        auth::authorize(OrderAction::Share)?;
+        let signer = self.share_signer()?;
+        let ttl = ttl.unwrap_or_else(|| chrono::Duration::days(1).min(signer.max_ttl()));
+        if !self.order_exists(tenant, id).await? {
+            return Err(AppError::NotFound(Resource::Order, id.to_string()));
+        }
+        signer
+            .sign(tenant, id, ttl, self.clock.now())
+            .map_err(|e| AppError::BadRequest(e.to_string()))
+    }
+
+    /// Fetch an order through a share link; the token alone authorizes it.
+    pub async fn get_shared_order(&self, id: Uuid, token: &ShareToken) -> Result<Order, AppError> {
+        self.share_signer()?
+            .verify(id, token, self.clock.now())
+            .map_err(|e| AppError::Forbidden(e.to_string()))?;
+        self.find_order(&token.tenant(), id).await
+    }
+
+    /// Whether `id` exists for `tenant`, without loading the order.
+    pub async fn order_exists(&self, tenant: &TenantId, id: Uuid) -> Result<bool, AppError> {
+        auth::authorize(OrderAction::View)?;
+        self.repo.exists(tenant, id).await.map_err(AppError::from)
+    }
+
+    /// Number of orders matching `filter`; `limit` and `offset` are ignored.
+    pub async fn count_orders(
+        &self,
+        tenant: &TenantId,
+        filter: &OrderFilter,
+    ) -> Result<usize, AppError> {
+        auth::authorize(OrderAction::View)?;
+        filter.check_range().map_err(AppError::BadRequest)?;
+        let count = match &self.read_model {
+            Some(reads) => reads.count_views(tenant, filter).await,
+            None => self.repo.count(tenant, filter).await,
+        };
+        count.map_err(AppError::from)
+    }
+
+    /// Counts by status, revenue and orders per day for orders created in
+    /// `range`.
+    pub async fn order_stats(
+        &self,
+        tenant: &TenantId,
+        range: &StatsRange,
+    ) -> Result<OrderStats, AppError> {
+        auth::authorize(OrderAction::View)?;
+        range.check().map_err(AppError::BadRequest)?;
+        self.repo
+            .aggregate(tenant, range)
+            .await
+            .map_err(AppError::from)
+    }
+
+    pub async fn list_orders(&self, tenant: &TenantId) -> Result<Vec<Order>, AppError> {
+        auth::authorize(OrderAction::View)?;
+        self.repo.list(tenant).await.map_err(AppError::from)
+    }
+
+    pub async fn list_orders_with(
+        &self,
+        tenant: &TenantId,
+        filter: &OrderFilter,
+    ) -> Result<Vec<Order>, AppError> {
+        auth::authorize(OrderAction::View)?;
+        Ok(self.list_page(tenant, filter).await?.orders)
+    }
+
+    /// Orders matching `filter`, with the sort and paging they were listed
+    /// with.
+    pub async fn list_page(
+        &self,
+        tenant: &TenantId,
+        filter: &OrderFilter,
+    ) -> Result<OrderPage, AppError> {
+        auth::authorize(OrderAction::View)?;
+        let sort = filter.sorting().map_err(AppError::BadRequest)?;
+        filter.check_range().map_err(AppError::BadRequest)?;
+        let orders = match &self.read_model {
+            Some(reads) => reads.list_views(tenant, filter).await,
+            None => self.repo.list_filtered(tenant, filter).await,
+        }
+        .map_err(AppError::from)?;
+        Ok(OrderPage {
+            orders,
+            sort,
+            limit: filter.limit,
+            offset: filter.offset.unwrap_or(0),
+        })
+    }
+
+    pub async fn update_status(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        status: OrderStatus,
+    ) -> Result<Order, AppError> {
+        auth::authorize(OrderAction::UpdateStatus)?;
+        self.update_status_with_note(tenant, id, status, None).await
+    }
+
+    /// Move an order to `status`, keeping `note` with the change in its
+    /// status history. A note on a cancellation is also its reason.
+    pub async fn update_status_with_note(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        status: OrderStatus,
+        note: Option<&str>,
+    ) -> Result<Order, AppError> {
+        auth::authorize(OrderAction::UpdateStatus)?;
+        let note = note.map(str::trim).filter(|n| !n.is_empty());
+        let current = self.load_order(tenant, id).await?;
+        if !current.status.can_transition_to(&status) {
+            return Err(AppError::InvalidTransition {
+                from: current.status,
+                to: status,
+            });
+        }
+        if status == OrderStatus::Confirmed {
+            return self.confirm(current, note).await;
+        }
+        if status == OrderStatus::Cancelled && current.status != status {
+            return self
+                .cancel(current, note.unwrap_or("status set to Cancelled"))
+                .await;
+        }
+        let mut order = current.clone();
+        order.update_status_at(status, self.clock.now());
+        match self
+            .store_update(&current, order, note, ChangeKind::Updated)
+            .await
+            .map_err(AppError::from)?
+        {
+            Some(o) => Ok(self.status_changed(current, o).await),
+            None => Err(AppError::NotFound(Resource::Order, id.to_string())),
+        }
+    }
+
+    /// What follows a stored move from `before` to `after` along the
+    /// transition rules: stock, the shipping notice, the audit entry and the
+    /// event.
+    async fn status_changed(&self, before: Order, after: Order) -> Order {
+        if matches!(after.status, OrderStatus::Shipped | OrderStatus::Completed) {
+            self.commit_stock(after.id).await;
+        }
+        if after.status == OrderStatus::Shipped && before.status != OrderStatus::Shipped {
+            self.notify(NotificationKind::Shipped, &after);
+        }
+        self.audit(AuditEntry::changed(actor::current(), before, after.clone()))
+            .await;
+        self.publish(OrderEvent::Updated {
+            order: after.clone(),
+        });
+        after
+    }
+
+    /// Admin: set an order's status regardless of the transition rules, to
+    /// clean up bad data. `reason` goes into the status history and the
+    /// audit log. Nothing else follows from the move: no stock is committed
+    /// or released, no payment is refunded and no customer is notified.
+    pub async fn force_status(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        status: OrderStatus,
+        reason: &str,
+    ) -> Result<Order, AppError> {
+        auth::authorize(OrderAction::ForceStatus)?;
+        let reason = reason.trim();
+        if reason.is_empty() {
+            return Err(AppError::Validation(vec![FieldError::new(
+                "reason",
+                "must not be empty",
+            )]));
+        }
+        let current = self.load_order(tenant, id).await?;
+        if current.status == status {
+            return Err(AppError::Conflict(format!(
+                "order {id} is already {status:?}"
+            )));
+        }
+        let mut order = current.clone();
+        order.update_status_at(status, self.clock.now());
+        let Some(o) = self
+            .store_update(
+                &current,
+                order,
+                Some(reason),
+                ChangeKind::StatusOverridden {
+                    from: current.status.clone(),
+                    reason: reason.to_string(),
+                },
+            )
+            .await
+            .map_err(AppError::from)?
+        else {
+            return Err(AppError::NotFound(Resource::Order, id.to_string()));
+        };
+        tracing::info!(order_id = %id, from = ?current.status, to = ?o.status, %reason, "order status overridden");
+        let from = current.status.clone();
+        self.audit(AuditEntry::overridden(
+            actor::current(),
+            current,
+            o.clone(),
+            reason,
+        ))
+        .await;
+        self.publish(OrderEvent::StatusOverridden {
+            order: o.clone(),
+            from,
+            reason: reason.to_string(),
+        });
+        Ok(o)
+    }
+
+    /// Apply `patch` to the customer details and addresses of an order that
+    /// hasn't shipped. A patch that changes nothing stores nothing.
+    pub async fn patch_order(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        patch: &OrderPatch,
+    ) -> Result<Order, AppError> {
+        auth::authorize(OrderAction::EditDetails)?;
+        let before = self.load_order(tenant, id).await?;
+        if !before.cancellable() {
+            return Err(AppError::Conflict(format!(
+                "only orders that haven't shipped can be edited; order {} is {:?}",
+                before.id, before.status
+            )));
+        }
+        let Some(order) = patch
+            .apply_at(&before, self.clock.now())
+            .map_err(AppError::Validation)?
+        else {
+            return Ok(before);
+        };
+        let errors = self
+            .limits
+            .check(&order.customer_name, &order.email, &order.items);
+        if !errors.is_empty() {
+            return Err(AppError::Validation(errors));
+        }
+        if order.email != before.email {
+            self.check_email(&order.email).await?;
+        }
+        self.prevalidate(&order).await?;
+        self.save(before, order, None).await
+    }
+
+    /// Replace every item of a pending order, re-pricing it.
+    pub async fn replace_items(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        items: Vec<OrderItem>,
+    ) -> Result<Order, AppError> {
+        auth::authorize(OrderAction::EditItems)?;
+        let order = self.load_order(tenant, id).await?;
+        let errors = Order::check(&order.customer_name, &order.email, &items);
+        if !errors.is_empty() {
+            return Err(AppError::Validation(errors));
+        }
+        self.save_items(order, items).await
+    }
+
+    /// Add items to the end of a pending order, re-pricing it. They must be
+    /// in the order's currency.
+    pub async fn append_items(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        items: Vec<OrderItem>,
+    ) -> Result<Order, AppError> {
+        auth::authorize(OrderAction::EditItems)?;
+        let order = self.load_order(tenant, id).await?;
+        let mut errors = Order::check(&order.customer_name, &order.email, &items);
+        let currency = order.total.currency();
+        for (i, it) in items.iter().enumerate() {
+            if it.unit_price.currency() != currency {
+                errors.push(FieldError::new(
+                    format!("items[{i}].currency"),
+                    format!("must match the order currency {currency}"),
+                ));
+            }
+        }
+        if !errors.is_empty() {
+            return Err(AppError::Validation(errors));
+        }
+        let combined = order.items.iter().cloned().chain(items).collect();
+        self.save_items(order, combined).await
+    }
+
+    /// Re-price `order` with `items` and store it, failing with a conflict
+    /// when it is no longer pending or someone else changed it since it was
+    /// loaded.
+    async fn save_items(&self, mut order: Order, items: Vec<OrderItem>) -> Result<Order, AppError> {
+        if !order.items_editable() {
+            return Err(AppError::Conflict(format!(
+                "only pending orders can be edited; order {} is {:?}",
+                order.id, order.status
+            )));
+        }
+        if let Some(e) = self.limits.check_items(&items) {
+            return Err(AppError::Validation(vec![e]));
+        }
+        let read_at = order.updated_at;
+        let before = order.clone();
+        order.updated_by = Some(actor::current());
+        order.replace_items_at(items, self.pricing.as_ref(), self.clock.now())?;
+        if let Some(e) = self.limits.check_total(order.total) {
+            return Err(AppError::Validation(vec![e]));
+        }
+        self.prevalidate(&order).await?;
+        self.reserve_stock(&order).await?;
+        let result = match self.repo.update_items(&order, read_at).await {
+            Ok(Some(o)) => {
+                self.audit(AuditEntry::changed(actor::current(), before, o.clone()))
+                    .await;
+                self.publish(OrderEvent::Updated { order: o.clone() });
+                return Ok(o);
+            }
+            Ok(None) => Err(AppError::NotFound(Resource::Order, order.id.to_string())),
+            Err(e) => Err(e.into()),
+        };
+        // Put back the hold of whatever is stored now, if it still holds any.
+        match self.repo.get(&order.tenant_id, order.id).await {
+            Ok(Some(stored)) if stored.cancellable() => {
+                if let Err(e) = self.reserve_stock(&stored).await {
+                    tracing::warn!(order_id = %order.id, error = %e, "failed to restore reserved stock");
+                }
+            }
+            _ => self.release_stock(order.id).await,
+        }
+        result
+    }
+
+    /// Cancel an order that hasn't shipped, recording `reason`. Paid orders
+    /// are refunded first; if the refund fails the order stays as it was.
+    pub async fn cancel_order(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        reason: &str,
+    ) -> Result<Order, AppError> {
+        auth::authorize(OrderAction::UpdateStatus)?;
+        if reason.trim().is_empty() {
+            return Err(AppError::Validation(vec![FieldError::new(
+                "reason",
+                "must not be empty",
+            )]));
+        }
+        let order = self.load_order(tenant, id).await?;
+        self.cancel(order, reason).await
+    }
+
+    /// Record a shipment of some of an order's items. Only pending and
+    /// confirmed orders take one; the order moves to `Shipped` with the shipment
+    /// that sends its last item.
+    pub async fn fulfill_order(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        fulfillment: Fulfillment,
+    ) -> Result<FulfillmentOutcome, AppError> {
+        auth::authorize(OrderAction::UpdateStatus)?;
+        let order = self.load_order(tenant, id).await?;
+        if !matches!(order.status, OrderStatus::Pending | OrderStatus::Confirmed) {
+            return Err(AppError::Conflict(format!(
+                "only pending or confirmed orders can be fulfilled; order {} is {:?}",
+                order.id, order.status
+            )));
+        }
+        let mut shipped = self
+            .repo
+            .fulfillments(tenant, id)
+            .await
+            .map_err(AppError::from)?;
+        let errors = fulfillment.check(&order, &shipped);
+        if !errors.is_empty() {
+            return Err(AppError::Validation(errors));
+        }
+        shipped.push(fulfillment.clone());
+        let mut next = order.clone();
+        next.updated_at = self.clock.now();
+        if fully_fulfilled(&order, &shipped) {
+            next.update_status_at(OrderStatus::Shipped, self.clock.now());
+        }
+        let Some(next) = self
+            .store_fulfillment(&order, next, fulfillment.clone())
+            .await
+            .map_err(AppError::from)?
+        else {
+            return Err(AppError::NotFound(Resource::Order, id.to_string()));
+        };
+        let order = if next.status == order.status {
+            next
+        } else {
+            self.status_changed(order, next).await
+        };
+        Ok(FulfillmentOutcome { fulfillment, order })
+    }
+
+    /// Cancel `order`, refunding it if it was paid. The cancellation is
+    /// written first and only committed once the refund went through, so a
+    /// refund is never paid out for a cancellation that lost to another
+    /// write, and a failed refund leaves the order as it was.
+    async fn cancel(&self, mut order: Order, reason: &str) -> Result<Order, AppError> {
+        if !order.cancellable() {
+            return Err(AppError::InvalidTransition {
+                from: order.status,
+                to: OrderStatus::Cancelled,
+            });
+        }
+        let before = order.clone();
+        order.cancel_at(reason, self.clock.now())?;
+        let mut unit = self.repo.begin().await.map_err(AppError::from)?;
+        let Some(cancelled) = stage_update(
+            unit.as_mut(),
+            &before,
+            order,
+            Some(reason.trim()),
+            ChangeKind::Updated,
+        )
+        .await
+        .map_err(AppError::from)?
+        else {
+            return Err(AppError::NotFound(Resource::Order, before.id.to_string()));
+        };
+        let refunded = match (before.is_paid(), &self.refunds) {
+            (true, Some(refunds)) => {
+                if let Err(e) = refunds.refund(&before, reason).await {
+                    tracing::warn!(order_id = %before.id, error = %e, "refund failed; order not cancelled");
+                    if let Err(e) = unit.rollback().await {
+                        tracing::warn!(order_id = %before.id, error = %e, "could not roll back the cancellation");
+                    }
+                    return Err(AppError::Unavailable(format!("refund failed: {e}")));
+                }
+                true
+            }
+            _ => false,
+        };
+        if let Err(e) = unit.commit().await {
+            if refunded {
+                tracing::error!(order_id = %before.id, error = %e, "order refunded but its cancellation was not stored");
+            }
+            return Err(e.into());
+        }
+        self.audit(AuditEntry::changed(
+            actor::current(),
+            before,
+            cancelled.clone(),
+        ))
+        .await;
+        self.publish(OrderEvent::Updated {
+            order: cancelled.clone(),
+        });
+        self.release_stock(cancelled.id).await;
+        self.notify(NotificationKind::Cancelled, &cancelled);
+        Ok(cancelled)
+    }
+
+    /// Confirm an order, freezing its pricing against the current rules.
+    /// The payment is captured first; when the confirmation can't be stored
+    /// afterwards, because another write got there first or the store
+    /// failed, the capture is refunded.
+    async fn confirm(&self, mut order: Order, note: Option<&str>) -> Result<Order, AppError> {
+        let before = order.clone();
+        let snapshot =
+            PricingSnapshot::compute_at(&order.items, self.pricing.as_ref(), self.clock.now())
+                .map_err(|e| AppError::Validation(vec![e.into()]))?;
+        order.freeze_pricing(snapshot);
+        let mut charged = None;
+        if let Some(payments) = &self.payments {
+            if order.total.amount_minor() > 0 {
+                let payment_id = charge(payments.as_ref(), &order).await?;
+                order.payment_id = Some(payment_id.clone());
+                order.captured = Some(order.total);
+                charged = Some((payments, payment_id));
+            }
+        }
+        order.update_status_at(OrderStatus::Confirmed, self.clock.now());
+        let total = order.total;
+        let result = self.save(before.clone(), order, note).await;
+        if let (Err(e), Some((payments, payment_id))) = (&result, charged) {
+            self.refund_unstored(payments.as_ref(), &before, &payment_id, total, e)
+                .await;
+        }
+        result
+    }
+
+    /// Give back a capture made for a confirmation of `read` that failed to
+    /// store with `error`. Providers that key authorizations by order hand
+    /// concurrent confirmations the same payment; it stays captured when the
+    /// confirmation that won holds it.
+    async fn refund_unstored(
+        &self,
+        payments: &dyn PaymentGateway,
+        read: &Order,
+        payment_id: &str,
+        amount: Money,
+        error: &AppError,
+    ) {
+        if let Ok(Some(stored)) = self.repo.get(&read.tenant_id, read.id).await {
+            if stored.payment_id.as_deref() == Some(payment_id) {
+                return;
+            }
+        }
+        match payments.refund(payment_id, amount).await {
+            Ok(()) => {
+                tracing::warn!(order_id = %read.id, %payment_id, %error, "confirmation not stored; payment refunded")
+            }
+            Err(e) => {
+                tracing::error!(order_id = %read.id, %payment_id, %error, refund_error = %e, "confirmation not stored and the payment could not be refunded")
+            }
+        }
+    }
+
+    /// Recompute a confirmed order's pricing against the current rules and
+    /// record the before/after diff in a `repriced` audit entry. A new total
+    /// over the configured limit is refused. Orders that have moved on, or
+    /// whose payment was captured, keep what they were charged.
+    pub async fn reprice_order(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+    ) -> Result<RepriceOutcome, AppError> {
+        auth::authorize(OrderAction::Reprice)?;
+        let mut order = self.load_order(tenant, id).await?;
+        let original = order.clone();
+        let snapshot =
+            PricingSnapshot::compute_at(&order.items, self.pricing.as_ref(), self.clock.now())
+                .map_err(|e| AppError::Validation(vec![e.into()]))?;
+        let before = order.reprice(snapshot).map_err(|e| match e {
+            RepriceError::NotPriced => AppError::BadRequest(format!("order {id}: {e}")),
+            RepriceError::Status(_) | RepriceError::Captured => {
+                AppError::Conflict(format!("order {id}: {e}"))
+            }
+        })?;
+        if let Some(e) = self.limits.check_total(order.total) {
+            return Err(AppError::Validation(vec![e]));
+        }
+        let diff = before
+            .diff(order.pricing.as_ref().expect("just repriced"))
+            .map_err(|e| AppError::Validation(vec![e.into()]))?;
+        let Some(order) = self
+            .store_update(&original, order, None, ChangeKind::Updated)
+            .await
+            .map_err(AppError::from)?
+        else {
+            return Err(AppError::NotFound(Resource::Order, id.to_string()));
+        };
+        self.audit(AuditEntry::repriced(
+            actor::current(),
+            original,
+            order.clone(),
+            diff.clone(),
+        ))
+        .await;
+        self.publish(OrderEvent::Updated {
+            order: order.clone(),
+        });
+        Ok(RepriceOutcome {
+            order,
+            before,
+            diff,
+        })
+    }
+
+    /// Cancel up to `limit` orders, of any tenant, that are still pending
+    /// `max_age` after they were created. Each goes through the same path as
+    /// [`cancel_order`](Self::cancel_order): the stock is released, the
+    /// customer notified and an `updated` event published.
+    pub async fn expire_stale_orders(
+        &self,
+        max_age: chrono::Duration,
+        limit: usize,
+    ) -> Result<ExpiryReport, AppError> {
+        auth::authorize(OrderAction::Maintain)?;
+        let stale = self
+            .repo
+            .stale_pending(self.clock.now() - max_age, limit)
+            .await
+            .map_err(AppError::from)?;
+        let mut report = ExpiryReport::default();
+        for order in stale {
+            let (id, tenant) = (order.id, order.tenant_id.clone());
+            match self.cancel(order, EXPIRY_REASON).await {
+                Ok(_) => {
+                    tracing::info!(target: "audit", order_id = %id, %tenant, "stale order expired");
+                    report.expired += 1;
+                }
+                Err(e) => {
+                    tracing::warn!(order_id = %id, %tenant, error = %e, "could not expire stale order");
+                    report.failed += 1;
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// Scan stored orders for unknown statuses and undecodable rows, logging
+    /// each finding. With `fix`, statuses covered by the configured mapping
+    /// (plus `extra`, which wins on conflicts) are rewritten.
+    pub async fn check_integrity(
+        &self,
+        fix: bool,
+        extra: StatusMapping,
+    ) -> Result<IntegrityReport, AppError> {
+        auth::authorize(OrderAction::Maintain)?;
+        let _admission = self.admit(CallerClass::Background).await;
+        let mapping = self.status_mapping.clone().merged(extra);
+        let report = self
+            .repo
+            .check_integrity(&mapping, fix)
+            .await
+            .map_err(AppError::from)?;
+        for issue in &report.issues {
+            match issue {
+                IntegrityIssue::UnknownStatus {
+                    order_id,
+                    value,
+                    mapped_to,
+                } => tracing::warn!(
+                    target: "integrity",
+                    %order_id,
+                    value = %value,
+                    mapped_to = ?mapped_to,
+                    "order has unknown status"
+                ),
+                IntegrityIssue::InvalidRow { order_id, reason } => tracing::warn!(
+                    target: "integrity",
+                    %order_id,
+                    %reason,
+                    "order row cannot be decoded"
+                ),
+            }
+        }
+        tracing::info!(
+            target: "integrity",
+            checked = report.checked,
+            fixed = report.fixed,
+            issues = report.issues.len(),
+            "integrity pass finished"
+        );
+        Ok(report)
+    }
+
+    /// Store `order`, which was `before` when it was loaded. `note` goes in
+    /// the status history if the status changed.
+    async fn save(
+        &self,
+        before: Order,
+        order: Order,
+        note: Option<&str>,
+    ) -> Result<Order, AppError> {
+        let id = order.id;
+        match self
+            .store_update(&before, order, note, ChangeKind::Updated)
+            .await
+            .map_err(AppError::from)?
+        {
+            Some(o) => {
+                self.audit(AuditEntry::changed(actor::current(), before, o.clone()))
+                    .await;
+                self.publish(OrderEvent::Updated { order: o.clone() });
+                Ok(o)
+            }
+            None => Err(AppError::NotFound(Resource::Order, id.to_string())),
+        }
+    }
+
+    /// Every order placed with `email` (matched ignoring ASCII case), read
+    /// from the write store. Each order handed out gets an `exported` audit
+    /// entry.
+    pub async fn export_customer(
+        &self,
+        tenant: &TenantId,
+        email: &str,
+    ) -> Result<CustomerExport, AppError> {
+        auth::authorize(OrderAction::Maintain)?;
+        let orders = self.customer_orders(tenant, email).await?;
+        for order in &orders {
+            self.audit(AuditEntry::exported(actor::current(), order))
+                .await;
+        }
+        tracing::info!(
+            target: "audit",
+            %tenant,
+            orders = orders.len(),
+            "customer data exported"
+        );
+        Ok(CustomerExport {
+            email: email.trim().to_string(),
+            exported_at: self.clock.now(),
+            orders,
+        })
+    }
+
+    /// Replace the name, email and street addresses on every order placed
+    /// with `email`, and on the audit log's copies of them, with
+    /// placeholders. Totals, items and the region and country tax was worked
+    /// out for stay, so the orders still add up for accounting.
+    pub async fn erase_customer(
+        &self,
+        tenant: &TenantId,
+        email: &str,
+    ) -> Result<ErasureReport, AppError> {
+        auth::authorize(OrderAction::Maintain)?;
+        let orders = self.customer_orders(tenant, email).await?;
+        let mut report = ErasureReport::default();
+        for mut order in orders {
+            let id = order.id;
+            order.anonymize();
+            order.updated_at = self.clock.now();
+            order.updated_by = Some(actor::current());
+            let Some(o) = self.repo.update(order).await.map_err(AppError::from)? else {
+                // Deleted since it was listed.
+                continue;
+            };
+            if let Some(audit) = &self.audit {
+                report.audit_entries += audit
+                    .anonymize_audit(tenant, id)
+                    .await
+                    .map_err(AppError::from)?;
+            }
+            self.audit(AuditEntry::anonymized(actor::current(), o.clone()))
+                .await;
+            self.publish(OrderEvent::Updated { order: o });
+            report.orders += 1;
+        }
+        tracing::info!(
+            target: "audit",
+            %tenant,
+            orders = report.orders,
+            audit_entries = report.audit_entries,
+            "customer data erased"
+        );
+        Ok(report)
+    }
+
+    async fn customer_orders(
+        &self,
+        tenant: &TenantId,
+        email: &str,
+    ) -> Result<Vec<Order>, AppError> {
+        let email = email.trim();
+        if email.is_empty() {
+            return Err(AppError::BadRequest("email must not be empty".into()));
+        }
+        self.repo
+            .list_filtered(tenant, &OrderFilter::default().with_email(email))
+            .await
+            .map_err(AppError::from)
+    }
+
+    pub async fn delete_order(&self, tenant: &TenantId, id: Uuid) -> Result<(), AppError> {
+        auth::authorize(OrderAction::Delete)?;
+        // Only loaded when there is an audit log to keep it in.
+        let before = match &self.audit {
+            Some(_) => self.repo.get(tenant, id).await.map_err(AppError::from)?,
+            None => None,
+        };
+        let deleted = self.repo.delete(tenant, id).await.map_err(AppError::from)?;
+        if deleted {
+            self.release_stock(id).await;
+            if let Some(order) = before {
+                self.audit(AuditEntry::deleted(actor::current(), order))
+                    .await;
+            }
+            self.publish(OrderEvent::Deleted {
+                id,
+                tenant_id: tenant.clone(),
+            });
+            Ok(())
+        } else {
+            Err(AppError::NotFound(Resource::Order, id.to_string()))
+        }
+    }
+}
+
+/// Authorize and capture the order's total, returning the payment id.
+async fn charge(payments: &dyn PaymentGateway, order: &Order) -> Result<String, AppError> {
+    let payment_error = |e: PaymentError| {
+        tracing::warn!(order_id = %order.id, error = %e, "payment failed; order not confirmed");
+        match e {
+            PaymentError::Declined(_) => AppError::Rejected(e.to_string()),
+            PaymentError::Unavailable(_) => AppError::Unavailable(e.to_string()),
+        }
+    };
+    let payment_id = payments
+        .authorize(order, order.total)
+        .await
+        .map_err(payment_error)?;
+    if let Err(e) = payments.capture(&payment_id, order.total).await {
+        if let Err(void) = payments.void(&payment_id).await {
+            tracing::warn!(order_id = %order.id, %payment_id, error = %void, "could not void the authorization");
+        }
+        return Err(payment_error(e));
+    }
+    Ok(payment_id)
+}
+
+/// Write `order`, the copy of `read` it changes, as last changed by the
+/// current actor, through `unit`, together with the status history entry
+/// for any change of status. `None` when the order doesn't exist.
+async fn stage_update(
+    unit: &mut dyn UnitOfWork,
+    read: &Order,
+    mut order: Order,
+    note: Option<&str>,
+    kind: ChangeKind,
+) -> Result<Option<Order>, RepoError> {
+    order.updated_by = Some(actor::current());
+    let Some(order) = unit.update_from(order, read, kind).await? else {
+        return Ok(None);
+    };
+    if let Some(entry) = transition(Some(&read.status), &order, note) {
+        unit.record_transition(&order.tenant_id, order.id, entry)
+            .await?;
+    }
+    Ok(Some(order))
+}
+
+/// Refunds cancelled orders through the gateway that charged them.
+struct PaymentRefunds(Arc<dyn PaymentGateway>);
+
+#[async_trait::async_trait]
+impl RefundGateway for PaymentRefunds {
+    async fn refund(&self, order: &Order, _reason: &str) -> Result<(), String> {
+        match &order.payment_id {
+            // Orders charged before the captured amount was stored fall back
+            // to their total.
+            Some(id) => self
+                .0
+                .refund(id, order.captured.unwrap_or(order.total))
+                .await
+                .map_err(|e| e.to_string()),
+            // Nothing was charged through the gateway.
+            None => Ok(()),
+        }
+    }
+}
+
+/// The status history entry for `order`'s move from `from` to its current
+/// status; `None` when the status didn't change.
+fn transition(
+    from: Option<&OrderStatus>,
+    order: &Order,
+    note: Option<&str>,
+) -> Option<OrderHistoryEntry> {
+    (from != Some(&order.status)).then(|| {
+        OrderHistoryEntry::new(
+            from.cloned(),
+            order.status.clone(),
+            order.updated_at,
+            actor::current(),
+            note.map(str::to_string),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use orders_types::domain::audit::AuditAction;
+    use orders_types::domain::error_code::ErrorCode;
+    use orders_types::domain::order::OrderItem;
+    use orders_types::ports::validation::Verdict;
+
+    fn tenant() -> TenantId {
+        TenantId::default()
+    }
+
+    #[tokio::test]
+    async fn create_and_get_order_in_memory() {
+        let repo = orders_repo::memory::InMemoryRepo::new();
+        let svc = OrderService::new(repo.clone());
+        let items = vec![OrderItem {
+            name: "Widget".into(),
+            qty: 2,
+            unit_price: Money::usd(500),
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
+        }];
+        let res = svc
+            .create_order(&tenant(), "Alice".into(), "a@b.com".into(), items.clone())
+            .await;
+        assert!(res.is_ok());
+        let order = res.unwrap();
+        let got = svc.get_order(&tenant(), order.id).await.unwrap();
+        assert_eq!(got.customer_name, "Alice");
+        assert_eq!(got.total.amount_minor(), 1000);
+    }
+
+    /// A read model that is only as current as what the test copies into it.
+    #[derive(Clone, Default)]
+    struct Views(orders_repo::memory::InMemoryRepo);
+
+    #[async_trait::async_trait]
+    impl OrderReadRepository for Views {
+        async fn get_view(&self, tenant: &TenantId, id: Uuid) -> Result<Option<Order>, RepoError> {
+            self.0.get(tenant, id).await
+        }
+
+        async fn list_views(
+            &self,
+            tenant: &TenantId,
+            filter: &OrderFilter,
+        ) -> Result<Vec<Order>, RepoError> {
+            self.0.list_filtered(tenant, filter).await
+        }
+
+        async fn count_views(
+            &self,
+            tenant: &TenantId,
+            filter: &OrderFilter,
+        ) -> Result<usize, RepoError> {
+            self.0.count(tenant, filter).await
+        }
     }
 
-    pub async fn create_order(
-        &self,
-        customer_name: String,
-        email: String,
-        items: Vec<OrderItem>,
-    ) -> Result<Order, AppError> {
-        let order = Order::new(customer_name, email, items)
-            .map_err(|e| AppError::BadRequest(e.to_string()))?;
-        self.repo
-            .create(order.clone())
+    #[tokio::test]
+    async fn reads_come_from_the_read_model_and_writes_from_the_store() {
+        let views = Views::default();
+        let svc = OrderService::new(orders_repo::memory::InMemoryRepo::new())
+            .with_read_model(views.clone());
+        let items = vec![OrderItem {
+            name: "Widget".into(),
+            qty: 1,
+            unit_price: Money::usd(500),
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
+        }];
+        let order = svc
+            .create_order(&tenant(), "Alice".into(), "a@b.com".into(), items)
+            .await
+            .unwrap();
+        // Not projected yet.
+        assert!(matches!(
+            svc.get_order(&tenant(), order.id).await,
+            Err(AppError::NotFound(..))
+        ));
+        let all = OrderFilter::default();
+        assert_eq!(svc.count_orders(&tenant(), &all).await.unwrap(), 0);
+        // Writes don't depend on the projection.
+        let shipped = svc
+            .update_status(&tenant(), order.id, OrderStatus::Shipped)
+            .await
+            .unwrap();
+
+        views.0.create(order.clone()).await.unwrap();
+        let got = svc.get_order(&tenant(), order.id).await.unwrap();
+        assert_eq!(got.status, OrderStatus::Pending);
+        views.0.update(shipped).await.unwrap();
+        let page = svc.list_page(&tenant(), &all).await.unwrap();
+        assert_eq!(page.orders[0].status, OrderStatus::Shipped);
+        assert_eq!(svc.count_orders(&tenant(), &all).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn update_status_and_delete() {
+        let repo = orders_repo::memory::InMemoryRepo::new();
+        let svc = OrderService::new(repo.clone());
+        let items = vec![OrderItem {
+            name: "Widget".into(),
+            qty: 1,
+            unit_price: Money::usd(250),
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
+        }];
+        let order = svc
+            .create_order(&tenant(), "Bob".into(), "bob@example.com".into(), items)
+            .await
+            .unwrap();
+
+        let updated = svc
+            .update_status(&tenant(), order.id, OrderStatus::Shipped)
+            .await
+            .unwrap();
+        assert_eq!(updated.status, OrderStatus::Shipped);
+
+        svc.delete_order(&tenant(), order.id).await.unwrap();
+        let missing = svc.get_order(&tenant(), order.id).await;
+        assert!(matches!(missing, Err(AppError::NotFound(..))));
+    }
+
+    #[tokio::test]
+    async fn final_statuses_reject_further_transitions() {
+        let svc = OrderService::new(orders_repo::memory::InMemoryRepo::new());
+        let items = vec![OrderItem {
+            name: "Widget".into(),
+            qty: 1,
+            unit_price: Money::usd(250),
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
+        }];
+        let order = svc
+            .create_order(&tenant(), "Cy".into(), "cy@example.com".into(), items)
+            .await
+            .unwrap();
+        svc.update_status(&tenant(), order.id, OrderStatus::Cancelled)
+            .await
+            .unwrap();
+
+        let err = svc
+            .update_status(&tenant(), order.id, OrderStatus::Confirmed)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            AppError::InvalidTransition {
+                from: OrderStatus::Cancelled,
+                to: OrderStatus::Confirmed,
+            }
+        ));
+        assert_eq!(err.code(), ErrorCode::InvalidTransition);
+    }
+
+    struct FlatTax;
+
+    impl orders_types::ports::pricing::PricingRules for FlatTax {
+        fn quote(&self, item: &OrderItem) -> orders_types::ports::pricing::LineQuote {
+            orders_types::ports::pricing::LineQuote {
+                unit_price_cents: item.unit_price.amount_minor(),
+                discount_cents: 0,
+                tax_rate_bps: 1_000,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn confirmation_freezes_pricing_and_reprice_diffs() {
+        let repo = orders_repo::memory::InMemoryRepo::new();
+        let svc = OrderService::new(repo.clone());
+        let items = vec![OrderItem {
+            name: "Widget".into(),
+            qty: 2,
+            unit_price: Money::usd(500),
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
+        }];
+        let order = svc
+            .create_order(&tenant(), "Fay".into(), "fay@example.com".into(), items)
+            .await
+            .unwrap();
+        assert!(matches!(
+            svc.reprice_order(&tenant(), order.id).await,
+            Err(AppError::BadRequest(_))
+        ));
+
+        let confirmed = svc
+            .update_status(&tenant(), order.id, OrderStatus::Confirmed)
+            .await
+            .unwrap();
+        assert_eq!(confirmed.pricing.as_ref().unwrap().total_cents(), 1000);
+
+        // A new total over the limit is refused and nothing is stored.
+        let capped = OrderService::new(repo.clone())
+            .with_pricing_rules(FlatTax)
+            .with_limits(OrderLimits {
+                max_total_cents: Some(1050),
+                ..Default::default()
+            });
+        assert!(matches!(
+            capped.reprice_order(&tenant(), order.id).await,
+            Err(AppError::Validation(_))
+        ));
+        let stored = repo.get(&tenant(), order.id).await.unwrap().unwrap();
+        assert_eq!(stored.total.amount_minor(), 1000);
+
+        let svc = OrderService::new(repo.clone())
+            .with_pricing_rules(FlatTax)
+            .with_audit(repo.clone());
+        let outcome = svc.reprice_order(&tenant(), order.id).await.unwrap();
+        assert_eq!(outcome.before.total_cents(), 1000);
+        assert_eq!(outcome.order.total.amount_minor(), 1100);
+        assert_eq!(outcome.diff.delta_cents, 100);
+        let entries = repo.order_audit(&tenant(), order.id).await.unwrap();
+        let last = entries.last().unwrap();
+        assert_eq!(last.action, AuditAction::Repriced);
+        assert_eq!(last.pricing_diff.as_ref(), Some(&outcome.diff));
+
+        // Once shipped the order keeps what it was priced at.
+        let shipped = svc
+            .update_status(&tenant(), order.id, OrderStatus::Shipped)
+            .await
+            .unwrap();
+        assert_eq!(shipped.total.amount_minor(), 1100);
+        assert!(matches!(
+            svc.reprice_order(&tenant(), order.id).await,
+            Err(AppError::Conflict(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn stale_pending_orders_expire_with_an_event() {
+        let repo = orders_repo::memory::InMemoryRepo::new();
+        let svc = OrderService::new(repo.clone());
+        let items = vec![OrderItem {
+            name: "Widget".into(),
+            qty: 1,
+            unit_price: Money::usd(100),
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
+        }];
+        let mut ids = Vec::new();
+        for _ in 0..3 {
+            let order = svc
+                .create_order(
+                    &tenant(),
+                    "Gus".into(),
+                    "gus@example.com".into(),
+                    items.clone(),
+                )
+                .await
+                .unwrap();
+            ids.push(order.id);
+        }
+        for id in &ids[..2] {
+            repo.map.get_mut(id).unwrap().created_at -= chrono::Duration::hours(2);
+        }
+        svc.update_status(&tenant(), ids[1], OrderStatus::Confirmed)
+            .await
+            .unwrap();
+        let mut rx = svc.subscribe();
+
+        let report = svc
+            .expire_stale_orders(chrono::Duration::hours(1), 10)
             .await
-            .map_err(|e| AppError::Internal(anyhow::anyhow!(e.to_string())))?;
-        Ok(order)
+            .unwrap();
+        assert_eq!(
+            report,
+            ExpiryReport {
+                expired: 1,
+                failed: 0
+            }
+        );
+        let expired = svc.get_order(&tenant(), ids[0]).await.unwrap();
+        assert_eq!(expired.status, OrderStatus::Cancelled);
+        assert_eq!(expired.cancellation.unwrap().reason, EXPIRY_REASON);
+        let event = rx.recv().await.unwrap().event;
+        assert_eq!(event.order_id(), ids[0]);
+        assert_eq!(event.status(), Some(&OrderStatus::Cancelled));
+        // Confirmed and recent orders are left alone.
+        for id in &ids[1..] {
+            let order = svc.get_order(&tenant(), *id).await.unwrap();
+            assert_ne!(order.status, OrderStatus::Cancelled);
+        }
     }
 
-    pub async fn get_order(&self, id: Uuid) -> Result<Order, AppError> {
-        match self
-            .repo
-            .get(id)
+    #[tokio::test]
+    async fn identical_orders_within_the_dedupe_window_are_returned_not_created() {
+        use orders_types::ports::clock::TestClock;
+
+        let clock = TestClock::new(chrono::Utc::now());
+        let svc = OrderService::new(orders_repo::memory::InMemoryRepo::new())
+            .with_clock(clock.clone())
+            .with_dedupe_window(chrono::Duration::seconds(30));
+        let item = |qty| OrderItem {
+            name: "Widget".into(),
+            qty,
+            unit_price: Money::usd(100),
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
+        };
+        let new = |email: &str, qty| NewOrder {
+            customer_name: "Hal".into(),
+            email: email.into(),
+            items: vec![item(qty)],
+            ..NewOrder::default()
+        };
+        let tenant = tenant();
+
+        let Placed::Created(first) = svc
+            .place_or_find_order(&tenant, new("hal@example.com", 1))
             .await
-            .map_err(|e| AppError::Internal(anyhow::anyhow!(e.to_string())))?
-        {
-            Some(o) => Ok(o),
-            None => Err(AppError::NotFound(format!("order {}", id))),
+            .unwrap()
+        else {
+            panic!("the first order is new");
+        };
+        clock.advance(chrono::Duration::seconds(10));
+        for email in ["HAL@example.com", " hal@Example.com ", "\thal@example.com"] {
+            match svc
+                .place_or_find_order(&tenant, new(email, 1))
+                .await
+                .unwrap()
+            {
+                Placed::Duplicate(order) => assert_eq!(order.id, first.id),
+                other => panic!("expected the first order back for {email:?}, got {other:?}"),
+            }
         }
-    }
+        assert!(matches!(
+            svc.place_or_find_order(&tenant, new("hal@example.com", 2))
+                .await
+                .unwrap(),
+            Placed::Created(_)
+        ));
 
-    pub async fn list_orders(&self) -> Result<Vec<Order>, AppError> {
-        self.repo
-            .list()
+        clock.advance(chrono::Duration::seconds(25));
+        assert!(matches!(
+            svc.place_or_find_order(&tenant, new("hal@example.com", 1))
+                .await
+                .unwrap(),
+            Placed::Created(_)
+        ));
+
+        let cancelled = svc
+            .place_order(&tenant, new("ivy@example.com", 1))
+            .await
+            .unwrap();
+        svc.cancel_order(&tenant, cancelled.id, "changed my mind")
             .await
-            .map_err(|e| AppError::Internal(anyhow::anyhow!(e.to_string())))
+            .unwrap();
+        assert!(matches!(
+            svc.place_or_find_order(&tenant, new("ivy@example.com", 1))
+                .await
+                .unwrap(),
+            Placed::Created(_)
+        ));
     }
 
-    pub async fn update_status(&self, id: Uuid, status: OrderStatus) -> Result<Order, AppError> {
-        match self
-            .repo
-            .update_status(id, status)
+    #[tokio::test]
+    async fn the_clock_drives_timestamps_and_expiry() {
+        use orders_types::ports::clock::TestClock;
+
+        let start = chrono::DateTime::parse_from_rfc3339("2026-01-01T09:00:00Z")
+            .unwrap()
+            .to_utc();
+        let clock = TestClock::new(start);
+        let svc =
+            OrderService::new(orders_repo::memory::InMemoryRepo::new()).with_clock(clock.clone());
+        let items = vec![OrderItem {
+            name: "Widget".into(),
+            qty: 1,
+            unit_price: Money::usd(100),
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
+        }];
+        let tenant = tenant();
+        let create = || {
+            svc.create_order(
+                &tenant,
+                "Gus".into(),
+                "gus@example.com".into(),
+                items.clone(),
+            )
+        };
+        let confirmed = create().await.unwrap();
+        assert_eq!((confirmed.created_at, confirmed.updated_at), (start, start));
+
+        clock.advance(chrono::Duration::minutes(5));
+        let confirmed = svc
+            .update_status(&tenant, confirmed.id, OrderStatus::Confirmed)
             .await
-            .map_err(|e| AppError::Internal(anyhow::anyhow!(e.to_string())))?
-        {
-            Some(o) => Ok(o),
-            None => Err(AppError::NotFound(format!("order {}", id))),
-        }
-    }
+            .unwrap();
+        assert_eq!(confirmed.created_at, start);
+        assert_eq!(confirmed.updated_at, clock.now());
+        assert_eq!(confirmed.pricing.unwrap().priced_at(), clock.now());
+        clock.advance(chrono::Duration::minutes(5));
+        let shipped = svc
+            .update_status(&tenant, confirmed.id, OrderStatus::Shipped)
+            .await
+            .unwrap();
+        assert_eq!(shipped.updated_at, clock.now());
+        let history = svc.order_history(&tenant, shipped.id).await.unwrap();
+        let times: Vec<_> = history.iter().map(|h| h.at).collect();
+        assert_eq!(
+            times,
+            [start, start + chrono::Duration::minutes(5), clock.now()]
+        );
 
-    pub async fn delete_order(&self, id: Uuid) -> Result<(), AppError> {
-        let deleted = self
-            .repo
-            .delete(id)
+        let pending = create().await.unwrap();
+        clock.advance(chrono::Duration::hours(2));
+        let report = svc
+            .expire_stale_orders(chrono::Duration::hours(1), 10)
             .await
-            .map_err(|e| AppError::Internal(anyhow::anyhow!(e.to_string())))?;
-        if deleted {
-            Ok(())
-        } else {
-            Err(AppError::NotFound(format!("order {}", id)))
-        }
+            .unwrap();
+        assert_eq!(report.expired, 1);
+        let expired = svc.get_order(&tenant, pending.id).await.unwrap();
+        assert_eq!(expired.cancellation.unwrap().cancelled_at, clock.now());
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use orders_types::domain::order::OrderItem;
+    #[tokio::test]
+    async fn new_orders_take_ids_from_the_generator() {
+        use orders_types::ports::id::SequentialIds;
+
+        let svc = OrderService::new(orders_repo::memory::InMemoryRepo::new())
+            .with_id_generator(SequentialIds::new());
+        let items = vec![OrderItem {
+            name: "Widget".into(),
+            qty: 1,
+            unit_price: Money::usd(100),
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
+        }];
+        let mut ids = Vec::new();
+        for _ in 0..2 {
+            let order = svc
+                .create_order(
+                    &tenant(),
+                    "Gus".into(),
+                    "gus@example.com".into(),
+                    items.clone(),
+                )
+                .await
+                .unwrap();
+            ids.push(order.id.to_string());
+        }
+        assert_eq!(
+            ids,
+            [
+                "00000000-0000-0000-0000-000000000001",
+                "00000000-0000-0000-0000-000000000002"
+            ]
+        );
+        let stored = svc.get_order(&tenant(), Uuid::from_u128(2)).await.unwrap();
+        assert_eq!(stored.id.to_string(), ids[1]);
+    }
 
     #[tokio::test]
-    async fn create_and_get_order_in_memory() {
+    async fn mutations_publish_events() {
         let repo = orders_repo::memory::InMemoryRepo::new();
-        let svc = OrderService::new(repo.clone());
+        let svc = OrderService::new(repo);
+        let mut rx = svc.subscribe();
+        let order = svc
+            .create_order(
+                &tenant(),
+                "Gus".into(),
+                "gus@example.com".into(),
+                vec![OrderItem {
+                    name: "Widget".into(),
+                    qty: 1,
+                    unit_price: Money::usd(100),
+                    weight_grams: 0,
+                    sku: None,
+                    description: None,
+                    metadata: Default::default(),
+                    discount_cents: 0,
+                }],
+            )
+            .await
+            .unwrap();
+        svc.update_status(&tenant(), order.id, OrderStatus::Shipped)
+            .await
+            .unwrap();
+        svc.delete_order(&tenant(), order.id).await.unwrap();
+
+        assert!(matches!(
+            rx.recv().await.unwrap().event,
+            OrderEvent::Created { .. }
+        ));
+        let updated = rx.recv().await.unwrap().event;
+        assert_eq!(updated.status(), Some(&OrderStatus::Shipped));
+        assert!(
+            matches!(rx.recv().await.unwrap().event, OrderEvent::Deleted { id, .. } if id == order.id)
+        );
+    }
+
+    #[tokio::test]
+    async fn events_carry_the_scoped_correlation_id() {
+        use orders_types::domain::correlation::CorrelationId;
+
+        let svc = OrderService::new(orders_repo::memory::InMemoryRepo::new());
+        let mut rx = svc.subscribe();
+        let id = CorrelationId::parse("req-42").unwrap();
         let items = vec![OrderItem {
             name: "Widget".into(),
-            qty: 2,
-            unit_price_cents: 500,
+            qty: 1,
+            unit_price: Money::usd(100),
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
         }];
-        let res = svc
-            .create_order("Alice".into(), "a@b.com".into(), items.clone())
-            .await;
-        assert!(res.is_ok());
-        let order = res.unwrap();
-        let got = svc.get_order(order.id).await.unwrap();
-        assert_eq!(got.customer_name, "Alice");
-        assert_eq!(got.total_cents, 1000);
+        correlation::scope(
+            id.clone(),
+            svc.create_order(
+                &tenant(),
+                "Hal".into(),
+                "hal@example.com".into(),
+                items.clone(),
+            ),
+        )
+        .await
+        .unwrap();
+        assert_eq!(rx.recv().await.unwrap().correlation_id, id);
+
+        // Outside a request each mutation gets its own id.
+        svc.create_order(&tenant(), "Ida".into(), "ida@example.com".into(), items)
+            .await
+            .unwrap();
+        assert_ne!(rx.recv().await.unwrap().correlation_id, id);
     }
 
     #[tokio::test]
-    async fn update_status_and_delete() {
-        let repo = orders_repo::memory::InMemoryRepo::new();
-        let svc = OrderService::new(repo.clone());
+    async fn changes_are_attributed_to_the_scoped_actor() {
+        use orders_types::domain::actor::Actor;
+
+        let svc = OrderService::new(orders_repo::memory::InMemoryRepo::new());
+        let mut rx = svc.subscribe();
+        let key = Actor::ApiKey(Uuid::new_v4());
         let items = vec![OrderItem {
             name: "Widget".into(),
             qty: 1,
-            unit_price_cents: 250,
+            unit_price: Money::usd(100),
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
         }];
+        let order = actor::scope(
+            key.clone(),
+            svc.create_order(&tenant(), "Hal".into(), "hal@example.com".into(), items),
+        )
+        .await
+        .unwrap();
+        assert_eq!(order.created_by, Some(key.clone()));
+        assert_eq!(order.updated_by, Some(key.clone()));
+        assert_eq!(rx.recv().await.unwrap().actor, key);
+
+        let user = Actor::User("u-42".into());
+        let order = actor::scope(
+            user.clone(),
+            svc.update_status(&tenant(), order.id, OrderStatus::Confirmed),
+        )
+        .await
+        .unwrap();
+        assert_eq!(order.created_by, Some(key));
+        assert_eq!(order.updated_by, Some(user.clone()));
+        assert_eq!(rx.recv().await.unwrap().actor, user);
+        let history = svc.order_history(&tenant(), order.id).await.unwrap();
+        assert_eq!(history[1].actor, "user:u-42");
+
+        // Outside a request the system made the change.
         let order = svc
-            .create_order("Bob".into(), "bob@example.com".into(), items)
+            .update_status(&tenant(), order.id, OrderStatus::Shipped)
             .await
             .unwrap();
+        assert_eq!(order.updated_by, Some(Actor::System));
+        assert_eq!(rx.recv().await.unwrap().actor, Actor::System);
+    }
 
-        let updated = svc
-            .update_status(order.id, OrderStatus::Shipped)
+    #[tokio::test]
+    async fn tenants_cannot_see_each_others_orders() {
+        let svc = OrderService::new(orders_repo::memory::InMemoryRepo::new());
+        let acme = TenantId::parse("acme").unwrap();
+        let globex = TenantId::parse("globex").unwrap();
+        let order = svc
+            .create_order(
+                &acme,
+                "Ann".into(),
+                "ann@acme.test".into(),
+                vec![OrderItem {
+                    name: "Widget".into(),
+                    qty: 1,
+                    unit_price: Money::usd(100),
+                    weight_grams: 0,
+                    sku: None,
+                    description: None,
+                    metadata: Default::default(),
+                    discount_cents: 0,
+                }],
+            )
+            .await
+            .unwrap();
+        assert_eq!(order.tenant_id, acme);
+
+        assert!(matches!(
+            svc.get_order(&globex, order.id).await,
+            Err(AppError::NotFound(..))
+        ));
+        assert!(svc.list_orders(&globex).await.unwrap().is_empty());
+        assert!(matches!(
+            svc.update_status(&globex, order.id, OrderStatus::Cancelled)
+                .await,
+            Err(AppError::NotFound(..))
+        ));
+        assert!(matches!(
+            svc.delete_order(&globex, order.id).await,
+            Err(AppError::NotFound(..))
+        ));
+        let still = svc.get_order(&acme, order.id).await.unwrap();
+        assert_eq!(still.status, OrderStatus::Pending);
+        assert_eq!(svc.list_orders(&acme).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn exists_and_count_are_tenant_scoped() {
+        let svc = OrderService::new(orders_repo::memory::InMemoryRepo::new());
+        let acme = TenantId::parse("acme").unwrap();
+        let globex = TenantId::parse("globex").unwrap();
+        let item = || OrderItem {
+            name: "Widget".into(),
+            qty: 1,
+            unit_price: Money::usd(100),
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
+        };
+        let order = svc
+            .create_order(&acme, "Ann".into(), "ann@acme.test".into(), vec![item()])
+            .await
+            .unwrap();
+        svc.create_order(&acme, "Bo".into(), "bo@acme.test".into(), vec![item()])
+            .await
+            .unwrap();
+        svc.update_status(&acme, order.id, OrderStatus::Shipped)
+            .await
+            .unwrap();
+
+        assert!(svc.order_exists(&acme, order.id).await.unwrap());
+        assert!(!svc.order_exists(&globex, order.id).await.unwrap());
+        assert!(!svc.order_exists(&acme, Uuid::new_v4()).await.unwrap());
+
+        let all = OrderFilter::default().with_limit(1);
+        assert_eq!(svc.count_orders(&acme, &all).await.unwrap(), 2);
+        let shipped = OrderFilter::default().with_status(OrderStatus::Shipped);
+        assert_eq!(svc.count_orders(&acme, &shipped).await.unwrap(), 1);
+        let by_email = OrderFilter::default().with_email("BO@acme.test");
+        assert_eq!(svc.count_orders(&acme, &by_email).await.unwrap(), 1);
+        assert_eq!(svc.count_orders(&globex, &all).await.unwrap(), 0);
+    }
+
+    #[test]
+    fn access_applies_role_policy() {
+        use crate::application::auth::{Access, AuthContext};
+        use orders_types::domain::api_key::{Role, Scope};
+
+        let caller = |role| {
+            Access::Caller(AuthContext {
+                key_id: None,
+                scopes: vec![Scope::Admin],
+                role,
+                tenant: None,
+            })
+        };
+        let viewer = caller(Role::Viewer);
+        let operator = caller(Role::Operator);
+        let admin = caller(Role::Admin);
+
+        assert!(viewer.authorize(OrderAction::View).is_ok());
+        assert!(matches!(
+            viewer.authorize(OrderAction::Create),
+            Err(AppError::RoleDenied { .. })
+        ));
+        assert!(operator.authorize(OrderAction::UpdateStatus).is_ok());
+        assert!(operator.authorize(OrderAction::Delete).is_err());
+        assert!(operator.authorize(OrderAction::Administer).is_err());
+        assert!(admin.authorize(OrderAction::Delete).is_ok());
+        assert!(admin.authorize(OrderAction::Administer).is_ok());
+        // The role alone isn't enough: the scope has to allow the action too.
+        let admin_reading = Access::Caller(AuthContext {
+            key_id: None,
+            scopes: vec![Scope::Read],
+            role: Role::Admin,
+            tenant: None,
+        });
+        assert!(admin_reading.authorize(OrderAction::View).is_ok());
+        assert!(matches!(
+            admin_reading.authorize(OrderAction::Delete),
+            Err(AppError::Forbidden(_))
+        ));
+        assert!(Access::Unrestricted.authorize(OrderAction::Delete).is_ok());
+        assert!(matches!(
+            Access::Anonymous.authorize(OrderAction::View),
+            Err(AppError::Unauthorized(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn service_methods_check_the_callers_role() {
+        use crate::application::auth::{Access, AuthContext};
+        use orders_types::domain::api_key::{Role, Scope};
+
+        let caller = |role| {
+            Access::Caller(AuthContext {
+                key_id: None,
+                scopes: vec![Scope::Admin],
+                role,
+                tenant: None,
+            })
+        };
+        let items = || {
+            vec![OrderItem {
+                name: "Widget".into(),
+                qty: 1,
+                unit_price: Money::usd(100),
+                weight_grams: 0,
+                sku: None,
+                description: None,
+                metadata: Default::default(),
+                discount_cents: 0,
+            }]
+        };
+        let svc = OrderService::new(orders_repo::memory::InMemoryRepo::new());
+        // Outside any request, as from the CLI or a worker, nothing is checked.
+        let order = svc
+            .create_order(&tenant(), "Ava".into(), "ava@example.com".into(), items())
             .await
             .unwrap();
-        assert_eq!(updated.status, OrderStatus::Shipped);
 
-        svc.delete_order(order.id).await.unwrap();
-        let missing = svc.get_order(order.id).await;
-        assert!(matches!(missing, Err(AppError::NotFound(_))));
+        caller(Role::Viewer)
+            .scope(async {
+                assert!(svc.get_order(&tenant(), order.id).await.is_ok());
+                assert!(matches!(
+                    svc.update_status(&tenant(), order.id, OrderStatus::Confirmed)
+                        .await,
+                    Err(AppError::RoleDenied { .. })
+                ));
+                assert!(matches!(
+                    svc.create_order(&tenant(), "Bo".into(), "bo@example.com".into(), items())
+                        .await,
+                    Err(AppError::RoleDenied { .. })
+                ));
+            })
+            .await;
+        caller(Role::Operator)
+            .scope(async {
+                assert!(matches!(
+                    svc.delete_order(&tenant(), order.id).await,
+                    Err(AppError::RoleDenied { .. })
+                ));
+                assert!(matches!(
+                    svc.check_integrity(false, StatusMapping::default()).await,
+                    Err(AppError::RoleDenied { .. })
+                ));
+            })
+            .await;
+        Access::Anonymous
+            .scope(async {
+                assert!(matches!(
+                    svc.order_stats(&tenant(), &StatsRange::default()).await,
+                    Err(AppError::Unauthorized(_))
+                ));
+                assert!(matches!(
+                    svc.order_history(&tenant(), order.id).await,
+                    Err(AppError::Unauthorized(_))
+                ));
+            })
+            .await;
+        assert!(svc.get_order(&tenant(), order.id).await.is_ok());
     }
 
     #[tokio::test]
     async fn validation_errors_propagate() {
         let repo = orders_repo::memory::InMemoryRepo::new();
         let svc = OrderService::new(repo.clone());
-        let res = svc.create_order("".into(), "invalid".into(), vec![]).await;
-        assert!(matches!(res, Err(AppError::BadRequest(_))));
+        let res = svc
+            .create_order(&tenant(), "".into(), "invalid".into(), vec![])
+            .await;
+        assert!(matches!(res, Err(AppError::Validation(e)) if e.len() == 3));
     }
 
     #[tokio::test]
     async fn not_found_paths() {
         let repo = orders_repo::memory::InMemoryRepo::new();
         let svc = OrderService::new(repo.clone());
-        let missing = svc.get_order(uuid::Uuid::new_v4()).await;
-        assert!(matches!(missing, Err(AppError::NotFound(_))));
+        let missing = svc.get_order(&tenant(), uuid::Uuid::new_v4()).await;
+        assert!(matches!(missing, Err(AppError::NotFound(..))));
 
         let updated = svc
-            .update_status(uuid::Uuid::new_v4(), OrderStatus::Shipped)
+            .update_status(&tenant(), uuid::Uuid::new_v4(), OrderStatus::Shipped)
+            .await;
+        assert!(matches!(updated, Err(AppError::NotFound(..))));
+
+        let deleted = svc.delete_order(&tenant(), uuid::Uuid::new_v4()).await;
+        assert!(matches!(deleted, Err(AppError::NotFound(..))));
+    }
+
+    /// Refuses every address at `example.invalid`.
+    struct NoInvalidDomains;
+
+    #[async_trait::async_trait]
+    impl EmailValidator for NoInvalidDomains {
+        async fn validate(&self, email: &str) -> Result<(), String> {
+            match email.ends_with("@example.invalid") {
+                true => Err("`example.invalid` does not accept email".into()),
+                false => Ok(()),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn emails_are_normalized_then_checked_by_the_email_validator() {
+        let svc = OrderService::new(orders_repo::memory::InMemoryRepo::new())
+            .with_email_validator(NoInvalidDomains);
+        let items = vec![OrderItem {
+            name: "Widget".into(),
+            qty: 1,
+            unit_price: Money::usd(100),
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
+        }];
+        let order = svc
+            .create_order(
+                &tenant(),
+                "Kim".into(),
+                " Kim@Example.com ".into(),
+                items.clone(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(order.email, "kim@example.com");
+
+        let refused = svc
+            .create_order(&tenant(), "Kim".into(), "kim@EXAMPLE.invalid".into(), items)
+            .await;
+        assert!(matches!(
+            refused,
+            Err(AppError::Validation(e)) if e == [FieldError::new("email", "`example.invalid` does not accept email")]
+        ));
+
+        let moved = svc
+            .patch_order(
+                &tenant(),
+                order.id,
+                &OrderPatch::new().with_email("kim@example.invalid"),
+            )
+            .await;
+        assert!(matches!(moved, Err(AppError::Validation(_))));
+        let renamed = svc
+            .patch_order(
+                &tenant(),
+                order.id,
+                &OrderPatch::new().with_customer_name("Kim Lee"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(renamed.email, "kim@example.com");
+    }
+
+    struct StubValidator(Result<Verdict, String>, Duration);
+
+    #[async_trait::async_trait]
+    impl OrderValidator for StubValidator {
+        async fn validate(&self, _order: &Order) -> Result<Verdict, String> {
+            tokio::time::sleep(self.1).await;
+            self.0.clone()
+        }
+    }
+
+    async fn create_with(
+        validator: StubValidator,
+        policy: FailurePolicy,
+    ) -> Result<Order, AppError> {
+        let svc = OrderService::new(orders_repo::memory::InMemoryRepo::new()).with_validator(
+            validator,
+            Duration::from_millis(50),
+            policy,
+        );
+        let created = svc
+            .create_order(
+                &tenant(),
+                "Val".into(),
+                "val@example.com".into(),
+                vec![OrderItem {
+                    name: "Widget".into(),
+                    qty: 1,
+                    unit_price: Money::usd(100),
+                    weight_grams: 0,
+                    sku: None,
+                    description: None,
+                    metadata: Default::default(),
+                    discount_cents: 0,
+                }],
+            )
             .await;
-        assert!(matches!(updated, Err(AppError::NotFound(_))));
+        assert_eq!(
+            svc.count_orders(&tenant(), &OrderFilter::default())
+                .await
+                .unwrap(),
+            created.is_ok() as usize
+        );
+        created
+    }
+
+    #[tokio::test]
+    async fn validator_verdicts_and_failure_policies() {
+        let reject = Verdict {
+            accept: false,
+            reason: Some("embargoed".into()),
+        };
+        let accept = Verdict {
+            accept: true,
+            reason: None,
+        };
+        let quick = Duration::ZERO;
+        let slow = Duration::from_secs(5);
+
+        assert!(create_with(
+            StubValidator(Ok(accept.clone()), quick),
+            FailurePolicy::FailClosed
+        )
+        .await
+        .is_ok());
+        assert!(matches!(
+            create_with(StubValidator(Ok(reject), quick), FailurePolicy::FailOpen).await,
+            Err(AppError::Rejected(r)) if r == "embargoed"
+        ));
+        assert!(matches!(
+            create_with(
+                StubValidator(Err("boom".into()), quick),
+                FailurePolicy::FailClosed
+            )
+            .await,
+            Err(AppError::Unavailable(_))
+        ));
+        assert!(create_with(
+            StubValidator(Err("boom".into()), quick),
+            FailurePolicy::FailOpen
+        )
+        .await
+        .is_ok());
+        // A verdict that arrives after the timeout counts as no verdict.
+        assert!(matches!(
+            create_with(
+                StubValidator(Ok(accept.clone()), slow),
+                FailurePolicy::FailClosed
+            )
+            .await,
+            Err(AppError::Unavailable(_))
+        ));
+        assert!(
+            create_with(StubValidator(Ok(accept), slow), FailurePolicy::FailOpen)
+                .await
+                .is_ok()
+        );
+    }
+
+    /// Records refunded order ids; fails every refund when `fail` is set.
+    #[derive(Clone, Default)]
+    struct RecordingRefunds {
+        refunded: Arc<std::sync::Mutex<Vec<Uuid>>>,
+        fail: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl RefundGateway for RecordingRefunds {
+        async fn refund(&self, order: &Order, _reason: &str) -> Result<(), String> {
+            if self.fail {
+                return Err("card network down".into());
+            }
+            self.refunded.lock().unwrap().push(order.id);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn cancelling_refunds_paid_orders_only() {
+        let refunds = RecordingRefunds::default();
+        let svc = OrderService::new(orders_repo::memory::InMemoryRepo::new())
+            .with_refunds(refunds.clone());
+        let default_tenant = tenant();
+        let new_order = || {
+            svc.create_order(
+                &default_tenant,
+                "Ida".into(),
+                "ida@example.com".into(),
+                vec![OrderItem {
+                    name: "Widget".into(),
+                    qty: 1,
+                    unit_price: Money::usd(100),
+                    weight_grams: 0,
+                    sku: None,
+                    description: None,
+                    metadata: Default::default(),
+                    discount_cents: 0,
+                }],
+            )
+        };
+
+        let unpaid = new_order().await.unwrap();
+        let cancelled = svc
+            .cancel_order(&tenant(), unpaid.id, "duplicate")
+            .await
+            .unwrap();
+        assert_eq!(cancelled.status, OrderStatus::Cancelled);
+        assert_eq!(cancelled.cancellation.unwrap().reason, "duplicate");
+        assert!(refunds.refunded.lock().unwrap().is_empty());
+
+        let paid = new_order().await.unwrap();
+        svc.update_status(&tenant(), paid.id, OrderStatus::Confirmed)
+            .await
+            .unwrap();
+        svc.cancel_order(&tenant(), paid.id, "out of stock")
+            .await
+            .unwrap();
+        assert_eq!(*refunds.refunded.lock().unwrap(), vec![paid.id]);
+
+        let shipped = new_order().await.unwrap();
+        svc.update_status(&tenant(), shipped.id, OrderStatus::Shipped)
+            .await
+            .unwrap();
+        assert!(matches!(
+            svc.cancel_order(&tenant(), shipped.id, "late").await,
+            Err(AppError::InvalidTransition { .. })
+        ));
+        assert!(matches!(
+            svc.cancel_order(&tenant(), shipped.id, " ").await,
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn failed_refunds_leave_the_order_uncancelled() {
+        let svc = OrderService::new(orders_repo::memory::InMemoryRepo::new()).with_refunds(
+            RecordingRefunds {
+                fail: true,
+                ..Default::default()
+            },
+        );
+        let order = svc
+            .create_order(
+                &tenant(),
+                "Jo".into(),
+                "jo@example.com".into(),
+                vec![OrderItem {
+                    name: "Widget".into(),
+                    qty: 1,
+                    unit_price: Money::usd(100),
+                    weight_grams: 0,
+                    sku: None,
+                    description: None,
+                    metadata: Default::default(),
+                    discount_cents: 0,
+                }],
+            )
+            .await
+            .unwrap();
+        svc.update_status(&tenant(), order.id, OrderStatus::Confirmed)
+            .await
+            .unwrap();
+
+        // Status updates to Cancelled go through the same refund path.
+        assert!(matches!(
+            svc.update_status(&tenant(), order.id, OrderStatus::Cancelled)
+                .await,
+            Err(AppError::Unavailable(_))
+        ));
+        let stored = svc.get_order(&tenant(), order.id).await.unwrap();
+        assert_eq!(stored.status, OrderStatus::Confirmed);
+        assert!(stored.cancellation.is_none());
+    }
+
+    #[tokio::test]
+    async fn confirming_charges_and_cancelling_refunds_through_payments() {
+        use crate::outbound::payment::MockPaymentGateway;
+
+        let payments = MockPaymentGateway::new().declining_above(10_000);
+        let repo = orders_repo::memory::InMemoryRepo::new();
+        let svc = OrderService::new(repo.clone()).with_payments(payments.clone());
+        let order_of = |cents| {
+            vec![OrderItem {
+                name: "Widget".into(),
+                qty: 1,
+                unit_price: Money::usd(cents),
+                weight_grams: 0,
+                sku: None,
+                description: None,
+                metadata: Default::default(),
+                discount_cents: 0,
+            }]
+        };
+
+        let order = svc
+            .create_order(
+                &tenant(),
+                "Mo".into(),
+                "mo@example.com".into(),
+                order_of(2_500),
+            )
+            .await
+            .unwrap();
+        assert!(order.payment_id.is_none());
+        let confirmed = svc
+            .update_status(&tenant(), order.id, OrderStatus::Confirmed)
+            .await
+            .unwrap();
+        let payment_id = confirmed.payment_id.clone().unwrap();
+        assert_eq!(payments.payment(&payment_id).unwrap().captured, 2_500);
+        assert_eq!(confirmed.captured, Some(Money::usd(2_500)));
+        assert!(matches!(
+            svc.reprice_order(&tenant(), order.id).await,
+            Err(AppError::Conflict(_))
+        ));
+
+        // The refund is what was captured, whatever the total says now.
+        let mut inflated = confirmed.clone();
+        inflated.total = Money::usd(9_999);
+        repo.update(inflated).await.unwrap();
+        svc.cancel_order(&tenant(), order.id, "changed mind")
+            .await
+            .unwrap();
+        assert_eq!(payments.payment(&payment_id).unwrap().refunded, 2_500);
+
+        let pricey = svc
+            .create_order(
+                &tenant(),
+                "Mo".into(),
+                "mo@example.com".into(),
+                order_of(20_000),
+            )
+            .await
+            .unwrap();
+        assert!(matches!(
+            svc.update_status(&tenant(), pricey.id, OrderStatus::Confirmed)
+                .await,
+            Err(AppError::Rejected(_))
+        ));
+        let stored = svc.get_order(&tenant(), pricey.id).await.unwrap();
+        assert_eq!(stored.status, OrderStatus::Pending);
+        assert!(stored.payment_id.is_none());
+    }
+
+    fn widget_order(cents: i64) -> Vec<OrderItem> {
+        vec![OrderItem {
+            name: "Widget".into(),
+            qty: 1,
+            unit_price: Money::usd(cents),
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
+        }]
+    }
+
+    #[tokio::test]
+    async fn payments_are_given_back_when_the_order_cannot_be_stored() {
+        use crate::outbound::payment::MockPaymentGateway;
+        use orders_repo::fault::{Fault, FaultInjectingRepo, FaultPlan};
+
+        let payments = MockPaymentGateway::new();
+        let repo = orders_repo::memory::InMemoryRepo::new();
+        let svc = OrderService::new(repo.clone()).with_payments(payments.clone());
+        let broken = OrderService::new(FaultInjectingRepo::new(
+            repo.clone(),
+            FaultPlan::new().with_op("begin", Fault::failing(1.0)),
+        ))
+        .with_payments(payments.clone());
+
+        // The capture is refunded when the confirmation isn't stored.
+        let order = svc
+            .create_order(
+                &tenant(),
+                "Lu".into(),
+                "lu@example.com".into(),
+                widget_order(1_500),
+            )
+            .await
+            .unwrap();
+        assert!(broken
+            .update_status(&tenant(), order.id, OrderStatus::Confirmed)
+            .await
+            .is_err());
+        let [payment] = payments.payments().try_into().unwrap();
+        assert_eq!((payment.captured, payment.refunded), (1_500, 1_500));
+        let stored = repo.get(&tenant(), order.id).await.unwrap().unwrap();
+        assert_eq!(stored.status, OrderStatus::Pending);
+        assert!(stored.payment_id.is_none());
+
+        // A cancellation that can't be stored refunds nothing.
+        let confirmed = svc
+            .update_status(&tenant(), order.id, OrderStatus::Confirmed)
+            .await
+            .unwrap();
+        let payment_id = confirmed.payment_id.unwrap();
+        assert!(broken
+            .cancel_order(&tenant(), order.id, "changed mind")
+            .await
+            .is_err());
+        assert_eq!(payments.payment(&payment_id).unwrap().refunded, 0);
+        let stored = repo.get(&tenant(), order.id).await.unwrap().unwrap();
+        assert_eq!(stored.status, OrderStatus::Confirmed);
+    }
+
+    #[tokio::test]
+    async fn failed_captures_void_the_authorization() {
+        use crate::outbound::payment::MockPaymentGateway;
+
+        let payments = MockPaymentGateway::new().failing_captures();
+        let svc = OrderService::new(orders_repo::memory::InMemoryRepo::new())
+            .with_payments(payments.clone());
+        let order = svc
+            .create_order(
+                &tenant(),
+                "Vi".into(),
+                "vi@example.com".into(),
+                widget_order(800),
+            )
+            .await
+            .unwrap();
+        assert!(matches!(
+            svc.update_status(&tenant(), order.id, OrderStatus::Confirmed)
+                .await,
+            Err(AppError::Unavailable(_))
+        ));
+        let [payment] = payments.payments().try_into().unwrap();
+        assert!(payment.voided);
+        assert_eq!(payment.captured, 0);
+        let stored = svc.get_order(&tenant(), order.id).await.unwrap();
+        assert_eq!(stored.status, OrderStatus::Pending);
+    }
+
+    #[tokio::test]
+    async fn concurrent_confirmations_charge_once() {
+        use crate::outbound::payment::MockPaymentGateway;
+        use orders_repo::fault::{Fault, FaultInjectingRepo, FaultPlan};
+
+        let payments = MockPaymentGateway::new();
+        let repo = orders_repo::memory::InMemoryRepo::new();
+        // Both confirmations charge before either is stored.
+        let slow_writes = FaultPlan::new().with_op(
+            "begin",
+            Fault::failing(0.0).with_latency(Duration::from_millis(20)),
+        );
+        let svc = OrderService::new(FaultInjectingRepo::new(repo.clone(), slow_writes))
+            .with_payments(payments.clone());
+        let order = svc
+            .create_order(
+                &tenant(),
+                "Cy".into(),
+                "cy@example.com".into(),
+                widget_order(2_000),
+            )
+            .await
+            .unwrap();
+        let acme = tenant();
+        let confirm = || svc.update_status(&acme, order.id, OrderStatus::Confirmed);
+
+        let (first, second) = tokio::join!(confirm(), confirm());
+        let (confirmed, conflicted): (Vec<_>, Vec<_>) =
+            [first, second].into_iter().partition(Result::is_ok);
+        assert_eq!(confirmed.len(), 1, "{conflicted:?}");
+        assert!(
+            matches!(conflicted[0], Err(AppError::Conflict(_))),
+            "{conflicted:?}"
+        );
+        let kept: i64 = payments
+            .payments()
+            .iter()
+            .map(|p| p.captured - p.refunded)
+            .sum();
+        assert_eq!(kept, 2_000);
+        let stored = repo.get(&tenant(), order.id).await.unwrap().unwrap();
+        let payment = payments.payment(&stored.payment_id.unwrap()).unwrap();
+        assert_eq!((payment.captured, payment.refunded), (2_000, 0));
+    }
+
+    #[tokio::test]
+    async fn lifecycle_changes_notify_the_customer() {
+        use crate::application::notifications::RetryPolicy;
+        use orders_types::ports::notifier::Notifier;
+
+        #[derive(Clone, Default)]
+        struct Recorder(Arc<std::sync::Mutex<Vec<NotificationKind>>>);
+
+        #[async_trait::async_trait]
+        impl Notifier for Recorder {
+            async fn notify(&self, n: &Notification) -> Result<(), String> {
+                self.0.lock().unwrap().push(n.kind);
+                Ok(())
+            }
+        }
+
+        let recorder = Recorder::default();
+        let svc = OrderService::new(orders_repo::memory::InMemoryRepo::new()).with_notifications(
+            NotificationQueue::spawn(recorder.clone(), RetryPolicy::default()),
+        );
+        let default_tenant = tenant();
+        let create = || {
+            svc.create_order(
+                &default_tenant,
+                "Oz".into(),
+                "oz@example.com".into(),
+                vec![OrderItem {
+                    name: "Widget".into(),
+                    qty: 1,
+                    unit_price: Money::usd(100),
+                    weight_grams: 0,
+                    sku: None,
+                    description: None,
+                    metadata: Default::default(),
+                    discount_cents: 0,
+                }],
+            )
+        };
+        let shipped = create().await.unwrap();
+        for _ in 0..2 {
+            svc.update_status(&tenant(), shipped.id, OrderStatus::Shipped)
+                .await
+                .unwrap();
+        }
+        let cancelled = create().await.unwrap();
+        svc.cancel_order(&tenant(), cancelled.id, "duplicate")
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        use NotificationKind::*;
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            vec![Created, Shipped, Created, Cancelled]
+        );
+    }
+
+    #[tokio::test]
+    async fn concurrent_fulfillments_cannot_ship_more_than_was_ordered() {
+        use orders_repo::fault::{Fault, FaultInjectingRepo, FaultPlan};
+        use orders_types::domain::fulfillment::FulfilledItem;
+
+        let repo = orders_repo::memory::InMemoryRepo::new();
+        // Both shipments read what already went out before either is stored.
+        let slow_writes = FaultPlan::new().with_op(
+            "begin",
+            Fault::failing(0.0).with_latency(Duration::from_millis(20)),
+        );
+        let svc = OrderService::new(FaultInjectingRepo::new(repo.clone(), slow_writes));
+        let order = svc
+            .create_order(
+                &tenant(),
+                "Flo".into(),
+                "flo@example.com".into(),
+                vec![OrderItem {
+                    name: "Widget".into(),
+                    qty: 2,
+                    unit_price: Money::usd(500),
+                    weight_grams: 0,
+                    sku: None,
+                    description: None,
+                    metadata: Default::default(),
+                    discount_cents: 0,
+                }],
+            )
+            .await
+            .unwrap();
+        let acme = tenant();
+        let ship = || {
+            let items = vec![FulfilledItem {
+                position: 0,
+                qty: 2,
+            }];
+            svc.fulfill_order(
+                &acme,
+                order.id,
+                Fulfillment::new(items, "UPS", "1Z1", chrono::Utc::now()),
+            )
+        };
 
-        let deleted = svc.delete_order(uuid::Uuid::new_v4()).await;
-        assert!(matches!(deleted, Err(AppError::NotFound(_))));
+        let (first, second) = tokio::join!(ship(), ship());
+        let (shipped, conflicted): (Vec<_>, Vec<_>) =
+            [first, second].into_iter().partition(Result::is_ok);
+        assert_eq!(shipped.len(), 1, "{conflicted:?}");
+        assert!(
+            matches!(conflicted[0], Err(AppError::Conflict(_))),
+            "{conflicted:?}"
+        );
+        let shipped = repo.fulfillments(&tenant(), order.id).await.unwrap();
+        assert_eq!(shipped.len(), 1);
+        let stored = repo.get(&tenant(), order.id).await.unwrap().unwrap();
+        assert_eq!(stored.status, OrderStatus::Shipped);
+        assert_eq!(
+            repo.status_history(&tenant(), order.id)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
     }
 }
@@ -0,0 +1,230 @@
+//! Admission control for work reaching the order service from several
+//! adapters. Interactive callers (the HTTP API) and background callers
+//! (imports, maintenance passes, consumers, scheduled jobs) share a pool of
+//! slots, but background work is capped below the pool size so a bulk run
+//! can never take the slots interactive requests need.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use serde::Serialize;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CallerClass {
+    /// A person or client waiting on the answer.
+    Interactive,
+    /// Bulk or scheduled work nobody is waiting on right now.
+    Background,
+}
+
+impl CallerClass {
+    pub const ALL: [CallerClass; 2] = [CallerClass::Interactive, CallerClass::Background];
+
+    fn index(self) -> usize {
+        match self {
+            CallerClass::Interactive => 0,
+            CallerClass::Background => 1,
+        }
+    }
+}
+
+/// How many calls may run at once in total and how many of those may be
+/// background work.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PriorityLimits {
+    pub capacity: usize,
+    pub background: usize,
+}
+
+impl PriorityLimits {
+    /// `capacity` slots with a quarter (at least one) open to background work.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            background: (capacity / 4).max(1),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Counters {
+    queued: AtomicU64,
+    in_flight: AtomicU64,
+    admitted: AtomicU64,
+    wait_ms_total: AtomicU64,
+    wait_ms_max: AtomicU64,
+}
+
+/// Queue metrics for one caller class since start-up.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ClassStats {
+    pub class: CallerClass,
+    /// Slots this class may hold at once.
+    pub limit: u64,
+    /// Calls waiting for a slot right now.
+    pub queued: u64,
+    /// Calls holding a slot right now.
+    pub in_flight: u64,
+    pub admitted: u64,
+    pub wait_ms_total: u64,
+    pub wait_ms_max: u64,
+}
+
+#[derive(Clone)]
+pub struct PriorityGate {
+    shared: Arc<Semaphore>,
+    background: Arc<Semaphore>,
+    limits: PriorityLimits,
+    counters: Arc<[Counters; 2]>,
+}
+
+/// A held slot; released on drop.
+pub struct Admission {
+    _permits: (OwnedSemaphorePermit, Option<OwnedSemaphorePermit>),
+    counters: Arc<[Counters; 2]>,
+    class: CallerClass,
+}
+
+impl Drop for Admission {
+    fn drop(&mut self) {
+        self.counters[self.class.index()]
+            .in_flight
+            .fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl PriorityGate {
+    /// Background work is held to fewer slots than the pool has (unless the
+    /// pool has only one), keeping the rest for interactive callers.
+    pub fn new(limits: PriorityLimits) -> Self {
+        let capacity = limits.capacity.max(1);
+        let background = limits
+            .background
+            .clamp(1, capacity.saturating_sub(1).max(1));
+        let limits = PriorityLimits {
+            capacity,
+            background,
+        };
+        Self {
+            shared: Arc::new(Semaphore::new(capacity)),
+            background: Arc::new(Semaphore::new(background)),
+            limits,
+            counters: Arc::new(Default::default()),
+        }
+    }
+
+    pub fn limits(&self) -> PriorityLimits {
+        self.limits
+    }
+
+    /// Wait for a slot for `class`. Background callers first queue among
+    /// themselves, so at most `limits().background` of them ever compete
+    /// with interactive callers for the shared pool.
+    pub async fn admit(&self, class: CallerClass) -> Admission {
+        let counters = &self.counters[class.index()];
+        counters.queued.fetch_add(1, Ordering::Relaxed);
+        let started = Instant::now();
+        let reserved = match class {
+            CallerClass::Interactive => None,
+            CallerClass::Background => Some(
+                self.background
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .expect("priority semaphores are never closed"),
+            ),
+        };
+        let shared = self
+            .shared
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("priority semaphores are never closed");
+        let waited = started.elapsed().as_millis() as u64;
+        counters.queued.fetch_sub(1, Ordering::Relaxed);
+        counters.in_flight.fetch_add(1, Ordering::Relaxed);
+        counters.admitted.fetch_add(1, Ordering::Relaxed);
+        counters.wait_ms_total.fetch_add(waited, Ordering::Relaxed);
+        counters.wait_ms_max.fetch_max(waited, Ordering::Relaxed);
+        Admission {
+            _permits: (shared, reserved),
+            counters: self.counters.clone(),
+            class,
+        }
+    }
+
+    pub fn stats(&self) -> Vec<ClassStats> {
+        CallerClass::ALL
+            .into_iter()
+            .map(|class| {
+                let c = &self.counters[class.index()];
+                ClassStats {
+                    class,
+                    limit: match class {
+                        CallerClass::Interactive => self.limits.capacity,
+                        CallerClass::Background => self.limits.background,
+                    } as u64,
+                    queued: c.queued.load(Ordering::Relaxed),
+                    in_flight: c.in_flight.load(Ordering::Relaxed),
+                    admitted: c.admitted.load(Ordering::Relaxed),
+                    wait_ms_total: c.wait_ms_total.load(Ordering::Relaxed),
+                    wait_ms_max: c.wait_ms_max.load(Ordering::Relaxed),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn background_work_cannot_take_the_reserved_slots() {
+        let gate = PriorityGate::new(PriorityLimits {
+            capacity: 3,
+            background: 2,
+        });
+        let _a = gate.admit(CallerClass::Background).await;
+        let _b = gate.admit(CallerClass::Background).await;
+
+        let third = tokio::spawn({
+            let gate = gate.clone();
+            async move { gate.admit(CallerClass::Background).await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!third.is_finished());
+
+        // The slot background work may not use is still free.
+        let interactive = tokio::time::timeout(
+            Duration::from_millis(50),
+            gate.admit(CallerClass::Interactive),
+        )
+        .await
+        .expect("interactive caller admitted");
+
+        let stats = gate.stats();
+        assert_eq!(stats[0].in_flight, 1);
+        assert_eq!((stats[1].in_flight, stats[1].queued), (2, 1));
+
+        drop(interactive);
+        drop(_a);
+        third.await.unwrap();
+        assert_eq!(gate.stats()[1].admitted, 3);
+    }
+
+    #[test]
+    fn background_limit_stays_below_capacity() {
+        let gate = PriorityGate::new(PriorityLimits {
+            capacity: 4,
+            background: 10,
+        });
+        assert_eq!(gate.limits().background, 3);
+        assert_eq!(PriorityLimits::new(16).background, 4);
+        assert_eq!(PriorityLimits::new(2).background, 1);
+    }
+}
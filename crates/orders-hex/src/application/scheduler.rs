@@ -0,0 +1,225 @@
+//! Recurring background jobs. Each job runs on its own fixed interval,
+//! starting right away; a run that overruns delays the next one instead of
+//! overlapping it. What every job last did is kept on a [`JobBoard`] for
+//! `GET /admin/jobs`.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use orders_types::ports::order_repository::OrderRepository;
+use serde::Serialize;
+use tokio::time::MissedTickBehavior;
+
+use crate::application::order_service::OrderService;
+
+/// Work the [`Scheduler`] runs periodically.
+#[async_trait]
+pub trait Job: Send + Sync + 'static {
+    fn name(&self) -> &str;
+    /// One run. The `Ok` value is a short summary shown as the last result.
+    async fn run(&self) -> anyhow::Result<String>;
+}
+
+/// A job's schedule and its most recent run.
+#[derive(Debug, Clone, Serialize)]
+pub struct JobStatus {
+    pub name: String,
+    pub interval_secs: u64,
+    pub running: bool,
+    pub runs: u64,
+    pub failures: u64,
+    pub last_started_at: Option<DateTime<Utc>>,
+    pub last_duration_ms: Option<u64>,
+    /// Whether the last run succeeded; `None` before the first one ends.
+    pub last_ok: Option<bool>,
+    /// The last run's summary, or its error when it failed.
+    pub last_result: Option<String>,
+    pub next_run_at: Option<DateTime<Utc>>,
+}
+
+/// Status of every spawned job, in the order they were added.
+#[derive(Clone, Default)]
+pub struct JobBoard {
+    jobs: Arc<Vec<Arc<Mutex<JobStatus>>>>,
+}
+
+impl JobBoard {
+    pub fn statuses(&self) -> Vec<JobStatus> {
+        self.jobs
+            .iter()
+            .map(|s| s.lock().expect("job status poisoned").clone())
+            .collect()
+    }
+}
+
+#[derive(Default)]
+pub struct Scheduler {
+    jobs: Vec<(Arc<dyn Job>, Duration)>,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run `job` every `every`.
+    pub fn with_job(mut self, job: impl Job, every: Duration) -> Self {
+        self.jobs.push((Arc::new(job), every));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    /// Start every job on the current Tokio runtime. They run until the
+    /// process exits.
+    pub fn spawn(self) -> JobBoard {
+        let mut board = Vec::new();
+        for (job, every) in self.jobs {
+            let status = Arc::new(Mutex::new(JobStatus {
+                name: job.name().to_string(),
+                interval_secs: every.as_secs(),
+                running: false,
+                runs: 0,
+                failures: 0,
+                last_started_at: None,
+                last_duration_ms: None,
+                last_ok: None,
+                last_result: None,
+                next_run_at: Some(Utc::now()),
+            }));
+            board.push(status.clone());
+            tokio::spawn(async move {
+                let mut ticks = tokio::time::interval(every);
+                ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                loop {
+                    ticks.tick().await;
+                    run_once(job.as_ref(), &status, every).await;
+                }
+            });
+        }
+        JobBoard {
+            jobs: Arc::new(board),
+        }
+    }
+}
+
+async fn run_once(job: &dyn Job, status: &Mutex<JobStatus>, every: Duration) {
+    {
+        let mut s = status.lock().expect("job status poisoned");
+        s.running = true;
+        s.last_started_at = Some(Utc::now());
+        s.next_run_at = None;
+    }
+    let started = Instant::now();
+    let result = job.run().await;
+    let elapsed = started.elapsed();
+    match &result {
+        Ok(summary) => tracing::info!(job = job.name(), %summary, "job finished"),
+        Err(e) => tracing::error!(job = job.name(), error = %e, "job failed"),
+    }
+    let mut s = status.lock().expect("job status poisoned");
+    s.running = false;
+    s.runs += 1;
+    s.last_duration_ms = Some(elapsed.as_millis() as u64);
+    s.last_ok = Some(result.is_ok());
+    s.last_result = Some(match result {
+        Ok(summary) => summary,
+        Err(e) => {
+            s.failures += 1;
+            e.to_string()
+        }
+    });
+    s.next_run_at = chrono::Duration::from_std(every.saturating_sub(elapsed))
+        .ok()
+        .map(|wait| Utc::now() + wait);
+}
+
+/// Cancels orders left pending too long; see
+/// [`OrderService::expire_stale_orders`].
+pub struct StaleOrderExpiry<R: OrderRepository> {
+    service: Arc<OrderService<R>>,
+    max_age: chrono::Duration,
+    batch: usize,
+}
+
+impl<R: OrderRepository> StaleOrderExpiry<R> {
+    /// Orders per run; the rest wait for the next one.
+    pub const DEFAULT_BATCH: usize = 500;
+
+    pub fn new(service: Arc<OrderService<R>>, max_age: chrono::Duration) -> Self {
+        Self {
+            service,
+            max_age,
+            batch: Self::DEFAULT_BATCH,
+        }
+    }
+
+    pub fn with_batch(mut self, batch: usize) -> Self {
+        self.batch = batch;
+        self
+    }
+}
+
+#[async_trait]
+impl<R: OrderRepository> Job for StaleOrderExpiry<R> {
+    fn name(&self) -> &str {
+        "stale_order_expiry"
+    }
+
+    async fn run(&self) -> anyhow::Result<String> {
+        let report = self
+            .service
+            .expire_stale_orders(self.max_age, self.batch)
+            .await
+            .map_err(|e| anyhow::anyhow!(e.to_string()))?;
+        Ok(format!(
+            "expired {} order(s), {} failed",
+            report.expired, report.failed
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    struct Flaky(AtomicU32);
+
+    #[async_trait]
+    impl Job for Flaky {
+        fn name(&self) -> &str {
+            "flaky"
+        }
+
+        async fn run(&self) -> anyhow::Result<String> {
+            match self.0.fetch_add(1, Ordering::SeqCst) {
+                0 => anyhow::bail!("first run fails"),
+                n => Ok(format!("run {n}")),
+            }
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn jobs_rerun_on_their_interval_and_record_the_outcome() {
+        let board = Scheduler::new()
+            .with_job(Flaky(AtomicU32::new(0)), Duration::from_secs(60))
+            .spawn();
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        let status = &board.statuses()[0];
+        assert_eq!((status.name.as_str(), status.runs), ("flaky", 1));
+        assert_eq!(status.last_ok, Some(false));
+        assert_eq!(status.last_result.as_deref(), Some("first run fails"));
+        assert!(status.next_run_at.is_some());
+
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        let status = &board.statuses()[0];
+        assert_eq!((status.runs, status.failures), (2, 1));
+        assert_eq!(status.last_ok, Some(true));
+        assert_eq!(status.last_result.as_deref(), Some("run 1"));
+    }
+}
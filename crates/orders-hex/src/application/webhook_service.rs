@@ -0,0 +1,199 @@
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use orders_types::domain::cloudevent::{CloudEvent, DEFAULT_SOURCE};
+use orders_types::domain::correlation::CorrelationId;
+use orders_types::domain::events::OrderEvent;
+use orders_types::domain::money::Money;
+use orders_types::domain::order::{Order, OrderItem};
+use orders_types::domain::webhook::WebhookTarget;
+use orders_types::ports::webhook::WebhookTransport;
+use serde::Serialize;
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::application::correlation;
+use crate::errors::{AppError, Resource};
+
+pub const SIGNATURE_HEADER: &str = "x-orders-signature";
+pub const EVENT_ID_HEADER: &str = "x-orders-event-id";
+pub const WEBHOOK_ID_HEADER: &str = "x-orders-webhook-id";
+
+/// Longest slice of a receiver's response body echoed back to the caller.
+const BODY_EXCERPT_CHARS: usize = 512;
+
+/// Outcome of a test delivery. `status` is `None` when the receiver could
+/// not be reached; `error` then says why.
+#[derive(Debug, Clone, Serialize)]
+pub struct TestDelivery {
+    pub target_id: String,
+    pub url: String,
+    pub event_id: Uuid,
+    pub status: Option<u16>,
+    pub latency_ms: u64,
+    pub body_excerpt: Option<String>,
+    pub error: Option<String>,
+}
+
+/// `t=<unix seconds>,v1=<hex HMAC-SHA256 of "<t>.<body>">`.
+pub fn sign(secret: &str, timestamp: u64, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("hmac accepts any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    format!(
+        "t={timestamp},v1={}",
+        hex::encode(mac.finalize().into_bytes())
+    )
+}
+
+pub struct WebhookService {
+    targets: Vec<WebhookTarget>,
+    secret: String,
+    transport: Arc<dyn WebhookTransport>,
+}
+
+impl WebhookService {
+    /// Deliveries to `targets` are signed with `secret`.
+    pub fn new(
+        targets: Vec<WebhookTarget>,
+        secret: impl Into<String>,
+        transport: impl WebhookTransport,
+    ) -> Self {
+        Self {
+            targets,
+            secret: secret.into(),
+            transport: Arc::new(transport),
+        }
+    }
+
+    pub fn targets(&self) -> &[WebhookTarget] {
+        &self.targets
+    }
+
+    /// Send a signed, synthetic `created` event to target `id` and report
+    /// how the receiver answered. No order is created.
+    pub async fn send_test(&self, id: &str) -> Result<TestDelivery, AppError> {
+        let target = self
+            .targets
+            .iter()
+            .find(|t| t.id == id)
+            .ok_or_else(|| AppError::NotFound(Resource::Webhook, id.to_string()))?;
+        let order = Order::new(
+            "Webhook Test".into(),
+            "webhook-test@example.invalid".into(),
+            vec![OrderItem {
+                name: "Test item".into(),
+                qty: 1,
+                unit_price: Money::usd(100),
+                weight_grams: 0,
+                sku: None,
+                description: None,
+                metadata: Default::default(),
+                discount_cents: 0,
+            }],
+        )
+        .map_err(|e| AppError::Internal(e.into()))?;
+        let event = OrderEvent::Created { order };
+        let event_id = Uuid::new_v4();
+        let correlation_id = correlation::current_or_new();
+        // Synthetic deliveries are flagged so receivers can ignore them.
+        let envelope = CloudEvent::order(event_id.to_string(), DEFAULT_SOURCE, Utc::now(), event)
+            .with_correlation_id(&correlation_id)
+            .with_test(true);
+        let body = serde_json::to_string(&envelope).map_err(|e| AppError::Internal(e.into()))?;
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let headers = [
+            (SIGNATURE_HEADER, sign(&self.secret, timestamp, &body)),
+            (EVENT_ID_HEADER, event_id.to_string()),
+            (WEBHOOK_ID_HEADER, target.id.clone()),
+            (CorrelationId::HEADER, correlation_id.to_string()),
+        ];
+
+        let started = Instant::now();
+        let result = self.transport.post(&target.url, &headers, body).await;
+        let latency_ms = started.elapsed().as_millis() as u64;
+        tracing::info!(
+            target: "audit",
+            webhook = %target.id,
+            %event_id,
+            %correlation_id,
+            ok = result.is_ok(),
+            latency_ms,
+            "webhook test delivery"
+        );
+        let (status, body_excerpt, error) = match result {
+            Ok(res) => (
+                Some(res.status),
+                Some(res.body.chars().take(BODY_EXCERPT_CHARS).collect()),
+                None,
+            ),
+            Err(e) => (None, None, Some(e)),
+        };
+        Ok(TestDelivery {
+            target_id: target.id.clone(),
+            url: target.url.clone(),
+            event_id,
+            status,
+            latency_ms,
+            body_excerpt,
+            error,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use orders_types::ports::webhook::WebhookResponse;
+
+    struct Echo;
+
+    #[async_trait::async_trait]
+    impl WebhookTransport for Echo {
+        async fn post(
+            &self,
+            _url: &str,
+            _headers: &[(&'static str, String)],
+            body: String,
+        ) -> Result<WebhookResponse, String> {
+            Ok(WebhookResponse {
+                status: 200,
+                body: body.repeat(10),
+            })
+        }
+    }
+
+    #[test]
+    fn signature_covers_timestamp_and_body() {
+        let sig = sign("s3cret", 1_700_000_000, "{}");
+        assert!(sig.starts_with("t=1700000000,v1="));
+        assert_ne!(sig, sign("s3cret", 1_700_000_001, "{}"));
+        assert_ne!(sig, sign("other", 1_700_000_000, "{}"));
+    }
+
+    #[tokio::test]
+    async fn unknown_target_is_not_found_and_excerpt_is_capped() {
+        let svc = WebhookService::new(
+            WebhookTarget::parse_list("crm=http://crm.test/hook").unwrap(),
+            "s3cret",
+            Echo,
+        );
+        assert!(matches!(
+            svc.send_test("nope").await,
+            Err(AppError::NotFound(..))
+        ));
+        let delivery = svc.send_test("crm").await.unwrap();
+        assert_eq!(delivery.status, Some(200));
+        assert_eq!(
+            delivery.body_excerpt.unwrap().chars().count(),
+            BODY_EXCERPT_CHARS
+        );
+    }
+}
@@ -3,33 +3,295 @@ use axum::response::{IntoResponse, Response};
 use serde::Serialize;
 use thiserror::Error;
 
+use crate::application::auth::OrderAction;
+use crate::application::correlation;
+use orders_types::domain::api_key::Role;
+use orders_types::domain::error_code::ErrorCode;
+use orders_types::domain::order::{FieldError, OrderChangeError, OrderStatus};
+use orders_types::ports::order_repository::RepoError;
+
 #[derive(Error, Debug)]
 pub enum AppError {
     #[error("Bad request: {0}")]
     BadRequest(String),
 
-    #[error("Order not found: {0}")]
-    NotFound(String),
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    #[error("Role {role:?} may not {action:?}")]
+    RoleDenied {
+        action: OrderAction,
+        role: Role,
+        required: Role,
+    },
+
+    #[error("{0} not found: {1}")]
+    NotFound(Resource, String),
+
+    /// The request body is malformed or fails field checks; one entry per
+    /// offending field.
+    #[error("Validation failed")]
+    Validation(Vec<FieldError>),
+
+    /// The request body is over the configured size limit.
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
+
+    /// The order's current status does not allow moving to the requested one.
+    #[error("Cannot move order from {from:?} to {to:?}")]
+    InvalidTransition { from: OrderStatus, to: OrderStatus },
+
+    /// The order changed since it was read, or its status no longer allows
+    /// the edit; the client should reload it before trying again.
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    /// Not enough of `sku` is in stock for the order.
+    #[error("Insufficient stock for {sku}")]
+    InsufficientStock {
+        sku: String,
+        requested: u32,
+        available: u32,
+    },
+
+    /// Well-formed but refused by a business rule.
+    #[error("Rejected: {0}")]
+    Rejected(String),
+
+    /// A dependency needed to answer is down; retrying later may succeed.
+    #[error("Unavailable: {0}")]
+    Unavailable(String),
 
     #[error("Internal error")]
     Internal(#[from] anyhow::Error),
 }
 
+/// What a [`AppError::NotFound`] was looking for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resource {
+    Order,
+    Webhook,
+    ApiKey,
+    Discount,
+    DeadLetter,
+    /// A stored row the repository reported missing, with no more specific
+    /// resource to name.
+    Record,
+}
+
+impl std::fmt::Display for Resource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Resource::Order => "order",
+            Resource::Webhook => "webhook",
+            Resource::ApiKey => "api key",
+            Resource::Discount => "discount",
+            Resource::DeadLetter => "dead letter",
+            Resource::Record => "record",
+        })
+    }
+}
+
+impl AppError {
+    /// The stable code sent to clients alongside the message.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            AppError::BadRequest(_) => ErrorCode::BadRequest,
+            AppError::Unauthorized(_) => ErrorCode::Unauthorized,
+            AppError::Forbidden(_) => ErrorCode::Forbidden,
+            AppError::RoleDenied { .. } => ErrorCode::RoleDenied,
+            AppError::NotFound(Resource::Order, _) => ErrorCode::OrderNotFound,
+            AppError::NotFound(Resource::Webhook, _) => ErrorCode::WebhookNotFound,
+            AppError::NotFound(Resource::ApiKey, _) => ErrorCode::ApiKeyNotFound,
+            AppError::NotFound(Resource::Discount, _) => ErrorCode::DiscountNotFound,
+            AppError::NotFound(Resource::DeadLetter, _) => ErrorCode::DeadLetterNotFound,
+            AppError::NotFound(Resource::Record, _) => ErrorCode::NotFound,
+            AppError::Validation(_) => ErrorCode::ValidationFailed,
+            AppError::PayloadTooLarge(_) => ErrorCode::PayloadTooLarge,
+            AppError::InvalidTransition { .. } => ErrorCode::InvalidTransition,
+            AppError::Conflict(_) => ErrorCode::Conflict,
+            AppError::InsufficientStock { .. } => ErrorCode::InsufficientStock,
+            AppError::Rejected(_) => ErrorCode::Rejected,
+            AppError::Unavailable(_) => ErrorCode::Unavailable,
+            AppError::Internal(_) => ErrorCode::Internal,
+        }
+    }
+}
+
+/// Repository failures by what the client can do about them: a conflict or
+/// a missing row is theirs to resolve, a transient failure is worth retrying
+/// later, and anything else is the server's fault. The repository error
+/// stays the [`Internal`](AppError::Internal) error's source.
+impl From<RepoError> for AppError {
+    fn from(e: RepoError) -> Self {
+        match e {
+            RepoError::Conflict(m) => AppError::Conflict(m),
+            RepoError::NotFound(m) => AppError::NotFound(Resource::Record, m),
+            RepoError::Transient(_) => {
+                tracing::warn!(error = %e, "transient repository failure");
+                AppError::Unavailable("storage is busy; try again shortly".into())
+            }
+            RepoError::Serialization(_) | RepoError::Backend(_) => {
+                AppError::Internal(anyhow::Error::new(e))
+            }
+        }
+    }
+}
+
+impl From<OrderChangeError> for AppError {
+    fn from(e: OrderChangeError) -> Self {
+        match e {
+            OrderChangeError::ItemsLocked(_) => AppError::Conflict(e.to_string()),
+            OrderChangeError::NotCancellable(from) => AppError::InvalidTransition {
+                from,
+                to: OrderStatus::Cancelled,
+            },
+            OrderChangeError::Invalid(e) => AppError::Validation(vec![e.into()]),
+        }
+    }
+}
+
+/// `{"error": <message>, "code": <ErrorCode>, "request_id": <request id>,
+/// "details": {...}}`; `request_id` and `details` are omitted when absent.
 #[derive(Serialize)]
-struct ErrorBody {
-    error: String,
+pub struct ErrorBody {
+    pub error: String,
+    pub code: ErrorCode,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+impl ErrorBody {
+    /// A body for `code`, stamped with the current request id (the
+    /// correlation id outside of a request).
+    pub fn new(code: ErrorCode, error: impl Into<String>) -> Self {
+        let request_id = correlation::current_request()
+            .map(|id| id.as_str().to_string())
+            .or_else(|| correlation::current().map(|id| id.as_str().to_string()));
+        Self {
+            error: error.into(),
+            code,
+            request_id,
+            details: None,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self)
+            .unwrap_or_else(|_| "{\"error\":\"internal serialization\"}".into())
+    }
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        let (code, msg) = match &self {
+        let mut details = None;
+        let (status, msg) = match &self {
             AppError::BadRequest(m) => (StatusCode::BAD_REQUEST, m.clone()),
-            AppError::NotFound(m) => (StatusCode::NOT_FOUND, m.clone()),
+            AppError::Unauthorized(m) => (StatusCode::UNAUTHORIZED, m.clone()),
+            AppError::Forbidden(m) => (StatusCode::FORBIDDEN, m.clone()),
+            AppError::RoleDenied {
+                action,
+                role,
+                required,
+            } => {
+                details = Some(serde_json::json!({
+                    "action": action,
+                    "role": role,
+                    "required_role": required,
+                }));
+                (StatusCode::FORBIDDEN, "forbidden".into())
+            }
+            AppError::NotFound(resource, id) => (StatusCode::NOT_FOUND, format!("{resource} {id}")),
+            AppError::Validation(errors) => {
+                details = Some(serde_json::json!({ "errors": errors }));
+                (StatusCode::UNPROCESSABLE_ENTITY, "validation failed".into())
+            }
+            AppError::PayloadTooLarge(m) => (StatusCode::PAYLOAD_TOO_LARGE, m.clone()),
+            AppError::InvalidTransition { from, to } => {
+                details = Some(serde_json::json!({ "from": from, "to": to }));
+                (StatusCode::CONFLICT, self.to_string())
+            }
+            AppError::Conflict(m) => (StatusCode::CONFLICT, m.clone()),
+            AppError::InsufficientStock {
+                sku,
+                requested,
+                available,
+            } => {
+                details = Some(serde_json::json!({
+                    "sku": sku,
+                    "requested": requested,
+                    "available": available,
+                }));
+                (StatusCode::CONFLICT, self.to_string())
+            }
+            AppError::Rejected(m) => (StatusCode::UNPROCESSABLE_ENTITY, m.clone()),
+            AppError::Unavailable(m) => (StatusCode::SERVICE_UNAVAILABLE, m.clone()),
             AppError::Internal(_) => (StatusCode::INTERNAL_SERVER_ERROR, "internal error".into()),
         };
 
-        let body = serde_json::to_string(&ErrorBody { error: msg })
-            .unwrap_or_else(|_| "{\"error\":\"internal serialization\"}".into());
-        (code, [("content-type", "application/json")], body).into_response()
+        let body = ErrorBody {
+            details,
+            ..ErrorBody::new(self.code(), msg)
+        };
+        (
+            status,
+            [("content-type", "application/json")],
+            body.to_json(),
+        )
+            .into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+
+    #[test]
+    fn repository_failures_get_distinct_statuses() {
+        let cases = [
+            (
+                RepoError::Conflict("UNIQUE constraint failed: orders.id".into()),
+                StatusCode::CONFLICT,
+            ),
+            (RepoError::NotFound("no rows".into()), StatusCode::NOT_FOUND),
+            (
+                RepoError::transient("database is locked"),
+                StatusCode::SERVICE_UNAVAILABLE,
+            ),
+            (
+                RepoError::serialization("unknown status `lost`"),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+            (
+                RepoError::backend("disk I/O error"),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+        ];
+        for (err, status) in cases {
+            let shown = err.to_string();
+            assert_eq!(
+                AppError::from(err).into_response().status(),
+                status,
+                "{shown}"
+            );
+        }
+    }
+
+    #[test]
+    fn internal_errors_keep_the_repository_error_as_their_source() {
+        let io = std::io::Error::other("disk I/O error");
+        let AppError::Internal(e) = AppError::from(RepoError::backend(io)) else {
+            panic!("not internal");
+        };
+        let repo = e.downcast_ref::<RepoError>().unwrap();
+        assert!(matches!(repo, RepoError::Backend(_)));
+        let io = repo.source().unwrap().downcast_ref::<std::io::Error>();
+        assert_eq!(io.unwrap().to_string(), "disk I/O error");
     }
 }
@@ -0,0 +1,481 @@
+//! GraphQL adapter over the same [`OrderService`] as the REST routes.
+//!
+//! Mounted at `POST /graphql` by [`HttpServer::with_graphql`]; requests pass
+//! through the same key check, tenant resolution and actor attribution as
+//! the REST routes, and every resolver authorizes like its REST twin.
+//!
+//! [`HttpServer::with_graphql`]: crate::inbound::http::HttpServer::with_graphql
+
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use async_graphql::http::GraphiQLSource;
+use async_graphql::{
+    Context, EmptySubscription, Enum, ErrorExtensions, InputObject, Object, Schema, SimpleObject,
+    ID,
+};
+use axum::response::Html;
+use axum::routing::post;
+use axum::{Extension, Json, Router};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::application::auth::{self, OrderAction};
+use crate::application::order_service::{NewOrder, OrderService};
+use crate::errors::AppError;
+use crate::inbound::http::json::JsonBody;
+use crate::inbound::http::tenant::Tenant;
+use orders_types::domain::address::Address;
+use orders_types::domain::filter::OrderFilter;
+use orders_types::domain::money::{Currency, Money};
+use orders_types::domain::order::{Order, OrderItem};
+use orders_types::domain::tenant::TenantId;
+use orders_types::ports::order_repository::OrderRepository;
+
+pub type OrdersSchema<R> = Schema<QueryRoot<R>, MutationRoot<R>, EmptySubscription>;
+
+/// The schema, with `service` behind every resolver.
+pub fn build_schema<R>(service: Arc<OrderService<R>>) -> OrdersSchema<R>
+where
+    R: OrderRepository + Send + Sync + 'static,
+{
+    Schema::build(
+        QueryRoot(PhantomData),
+        MutationRoot(PhantomData),
+        EmptySubscription,
+    )
+    .data(service)
+    .finish()
+}
+
+/// `POST /graphql`, plus the GraphiQL IDE at `GET /graphql` when `graphiql`
+/// is set.
+pub fn graphql_router<R>(
+    service: Arc<OrderService<R>>,
+    graphiql: bool,
+) -> Router<Arc<OrderService<R>>>
+where
+    R: OrderRepository + Send + Sync + 'static,
+{
+    let schema = build_schema(service);
+    let route = if graphiql {
+        post(execute::<R>).get(ide)
+    } else {
+        post(execute::<R>)
+    };
+    Router::new()
+        .route("/graphql", route)
+        .layer(Extension(schema))
+}
+
+/// Which tenant is asking; attached to each request.
+struct Scope {
+    tenant: TenantId,
+}
+
+async fn execute<R>(
+    Extension(schema): Extension<OrdersSchema<R>>,
+    Tenant(tenant): Tenant,
+    JsonBody(request): JsonBody<async_graphql::Request>,
+) -> Json<async_graphql::Response>
+where
+    R: OrderRepository + Send + Sync + 'static,
+{
+    let request = request.data(Scope { tenant });
+    Json(schema.execute(request).await)
+}
+
+async fn ide() -> Html<String> {
+    Html(GraphiQLSource::build().endpoint("/graphql").finish())
+}
+
+/// Scope of the request and the service, after checking the caller may
+/// perform `action`.
+fn authorized<'a, R>(
+    ctx: &'a Context<'_>,
+    action: OrderAction,
+) -> async_graphql::Result<(&'a Arc<OrderService<R>>, &'a TenantId)>
+where
+    R: OrderRepository + Send + Sync + 'static,
+{
+    let service = ctx.data::<Arc<OrderService<R>>>()?;
+    let scope = ctx.data::<Scope>()?;
+    auth::authorize(action).map_err(gql_error)?;
+    Ok((service, &scope.tenant))
+}
+
+/// The service error as a GraphQL error whose `code` extension is the same
+/// stable code the REST routes send.
+fn gql_error(err: AppError) -> async_graphql::Error {
+    let message = match &err {
+        AppError::Internal(_) => "internal error".to_string(),
+        other => other.to_string(),
+    };
+    let code = err.code();
+    let fields = match &err {
+        AppError::Validation(errors) => serde_json::to_value(errors)
+            .ok()
+            .and_then(|v| async_graphql::Value::from_json(v).ok()),
+        _ => None,
+    };
+    async_graphql::Error::new(message).extend_with(|_, ext| {
+        ext.set("code", code.as_str());
+        if let Some(fields) = fields.clone() {
+            ext.set("errors", fields);
+        }
+    })
+}
+
+fn parse_id(id: &ID) -> async_graphql::Result<Uuid> {
+    Uuid::parse_str(id).map_err(|e| gql_error(AppError::BadRequest(e.to_string())))
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+#[graphql(
+    name = "OrderStatus",
+    remote = "orders_types::domain::order::OrderStatus"
+)]
+pub enum GqlOrderStatus {
+    Pending,
+    Confirmed,
+    Shipped,
+    Cancelled,
+    Completed,
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+#[graphql(name = "SortField", remote = "orders_types::domain::filter::SortField")]
+pub enum GqlSortField {
+    CreatedAt,
+    UpdatedAt,
+    TotalCents,
+    Status,
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+#[graphql(name = "SortOrder", remote = "orders_types::domain::filter::SortOrder")]
+pub enum GqlSortOrder {
+    Asc,
+    Desc,
+}
+
+pub struct GqlOrder(Order);
+
+#[Object(name = "Order")]
+impl GqlOrder {
+    async fn id(&self) -> ID {
+        ID(self.0.id.to_string())
+    }
+
+    /// Human-readable number, e.g. `ORD-2026-000123`.
+    async fn order_number(&self) -> Option<String> {
+        self.0.order_number.map(|n| n.to_string())
+    }
+
+    async fn customer_name(&self) -> &str {
+        &self.0.customer_name
+    }
+
+    async fn email(&self) -> &str {
+        &self.0.email
+    }
+
+    async fn items(&self) -> Vec<GqlOrderItem> {
+        self.0.items.iter().map(GqlOrderItem::from).collect()
+    }
+
+    /// In the minor unit of `currency`.
+    async fn total_cents(&self) -> i64 {
+        self.0.total.amount_minor()
+    }
+
+    async fn currency(&self) -> String {
+        self.0.total.currency().to_string()
+    }
+
+    async fn subtotal_cents(&self) -> i64 {
+        self.0.charges.subtotal_cents
+    }
+
+    async fn discount_cents(&self) -> i64 {
+        self.0.charges.discount_cents
+    }
+
+    async fn tax_cents(&self) -> i64 {
+        self.0.charges.tax_cents
+    }
+
+    async fn shipping_cents(&self) -> i64 {
+        self.0.charges.shipping_cents
+    }
+
+    async fn status(&self) -> GqlOrderStatus {
+        self.0.status.clone().into()
+    }
+
+    async fn created_at(&self) -> DateTime<Utc> {
+        self.0.created_at
+    }
+
+    async fn updated_at(&self) -> DateTime<Utc> {
+        self.0.updated_at
+    }
+
+    /// Set once the order is cancelled.
+    async fn cancel_reason(&self) -> Option<&str> {
+        self.0.cancellation.as_ref().map(|c| c.reason.as_str())
+    }
+
+    async fn shipping_address(&self) -> Option<GqlAddress> {
+        self.0.shipping_address.clone().map(Into::into)
+    }
+
+    async fn billing_address(&self) -> Option<GqlAddress> {
+        self.0.billing_address.clone().map(Into::into)
+    }
+}
+
+#[derive(SimpleObject, InputObject)]
+#[graphql(name = "Address", input_name = "AddressInput")]
+pub struct GqlAddress {
+    line1: String,
+    line2: Option<String>,
+    city: String,
+    region: Option<String>,
+    postal_code: String,
+    /// ISO 3166-1 alpha-2 code.
+    country: String,
+}
+
+impl From<Address> for GqlAddress {
+    fn from(a: Address) -> Self {
+        Self {
+            line1: a.line1,
+            line2: a.line2,
+            city: a.city,
+            region: a.region,
+            postal_code: a.postal_code,
+            country: a.country,
+        }
+    }
+}
+
+impl From<GqlAddress> for Address {
+    fn from(a: GqlAddress) -> Self {
+        Self {
+            line1: a.line1,
+            line2: a.line2,
+            city: a.city,
+            region: a.region,
+            postal_code: a.postal_code,
+            country: a.country,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(name = "OrderItem")]
+pub struct GqlOrderItem {
+    name: String,
+    qty: u32,
+    unit_price_cents: i64,
+    weight_grams: u32,
+    sku: Option<String>,
+    description: Option<String>,
+    discount_cents: i64,
+}
+
+impl From<&OrderItem> for GqlOrderItem {
+    fn from(item: &OrderItem) -> Self {
+        Self {
+            name: item.name.clone(),
+            qty: item.qty,
+            unit_price_cents: item.unit_price.amount_minor(),
+            weight_grams: item.weight_grams,
+            sku: item.sku.clone(),
+            description: item.description.clone(),
+            discount_cents: item.discount_cents,
+        }
+    }
+}
+
+/// One page of `orders`, with the paging it was cut with.
+#[derive(SimpleObject)]
+#[graphql(name = "OrderPage")]
+pub struct GqlOrderPage {
+    orders: Vec<GqlOrder>,
+    limit: Option<u64>,
+    offset: u64,
+}
+
+#[derive(InputObject, Default)]
+#[graphql(name = "OrderFilter")]
+pub struct GqlOrderFilter {
+    status: Option<GqlOrderStatus>,
+    email: Option<String>,
+    /// Country of the shipping address.
+    country: Option<String>,
+    /// Created at or after this instant.
+    created_after: Option<DateTime<Utc>>,
+    /// Created strictly before this instant.
+    created_before: Option<DateTime<Utc>>,
+    sort: Option<GqlSortField>,
+    /// Direction for `sort`; ascending when absent.
+    order: Option<GqlSortOrder>,
+}
+
+#[derive(InputObject, Default)]
+#[graphql(name = "Page")]
+pub struct GqlPage {
+    limit: Option<u64>,
+    offset: Option<u64>,
+}
+
+#[derive(InputObject)]
+#[graphql(name = "OrderItemInput")]
+pub struct GqlOrderItemInput {
+    name: String,
+    qty: u32,
+    unit_price_cents: i64,
+    /// ISO 4217 code; `USD` when absent.
+    currency: Option<String>,
+    #[graphql(default)]
+    weight_grams: u32,
+    sku: Option<String>,
+    description: Option<String>,
+    #[graphql(default)]
+    discount_cents: i64,
+}
+
+#[derive(InputObject)]
+#[graphql(name = "CreateOrderInput")]
+pub struct GqlCreateOrderInput {
+    customer_name: String,
+    email: String,
+    items: Vec<GqlOrderItemInput>,
+    discount_code: Option<String>,
+    shipping_address: Option<GqlAddress>,
+    billing_address: Option<GqlAddress>,
+}
+
+pub struct QueryRoot<R>(PhantomData<R>);
+
+#[Object]
+impl<R> QueryRoot<R>
+where
+    R: OrderRepository + Send + Sync + 'static,
+{
+    async fn order(&self, ctx: &Context<'_>, id: ID) -> async_graphql::Result<GqlOrder> {
+        let (service, tenant) = authorized::<R>(ctx, OrderAction::View)?;
+        let order = service
+            .get_order(tenant, parse_id(&id)?)
+            .await
+            .map_err(gql_error)?;
+        Ok(GqlOrder(order))
+    }
+
+    async fn orders(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] filter: GqlOrderFilter,
+        #[graphql(default)] page: GqlPage,
+    ) -> async_graphql::Result<GqlOrderPage> {
+        let (service, tenant) = authorized::<R>(ctx, OrderAction::View)?;
+        let filter = OrderFilter {
+            status: filter.status.map(Into::into),
+            email: filter.email,
+            country: filter.country,
+            created_after: filter.created_after,
+            created_before: filter.created_before,
+            limit: page.limit.map(|n| n as usize),
+            offset: page.offset.map(|n| n as usize),
+            sort: filter.sort.map(Into::into),
+            order: filter.order.map(Into::into),
+            metadata: Default::default(),
+        };
+        let page = service
+            .list_page(tenant, &filter)
+            .await
+            .map_err(gql_error)?;
+        Ok(GqlOrderPage {
+            orders: page.orders.into_iter().map(GqlOrder).collect(),
+            limit: page.limit.map(|n| n as u64),
+            offset: page.offset as u64,
+        })
+    }
+}
+
+pub struct MutationRoot<R>(PhantomData<R>);
+
+#[Object]
+impl<R> MutationRoot<R>
+where
+    R: OrderRepository + Send + Sync + 'static,
+{
+    async fn create_order(
+        &self,
+        ctx: &Context<'_>,
+        input: GqlCreateOrderInput,
+    ) -> async_graphql::Result<GqlOrder> {
+        let (service, tenant) = authorized::<R>(ctx, OrderAction::Create)?;
+        let items = input
+            .items
+            .into_iter()
+            .map(|item| {
+                let currency = match item.currency.as_deref() {
+                    Some(code) => {
+                        Currency::parse(code).map_err(|e| gql_error(AppError::BadRequest(e)))?
+                    }
+                    None => Currency::USD,
+                };
+                Ok(OrderItem {
+                    name: item.name,
+                    qty: item.qty,
+                    unit_price: Money::new(item.unit_price_cents, currency),
+                    weight_grams: item.weight_grams,
+                    sku: item.sku,
+                    description: item.description,
+                    metadata: Default::default(),
+                    discount_cents: item.discount_cents,
+                })
+            })
+            .collect::<async_graphql::Result<Vec<_>>>()?;
+        let new = NewOrder {
+            customer_name: input.customer_name,
+            email: input.email,
+            items,
+            discount_code: input.discount_code,
+            shipping_address: input.shipping_address.map(Into::into),
+            billing_address: input.billing_address.map(Into::into),
+            metadata: Default::default(),
+        };
+        let order = service.place_order(tenant, new).await.map_err(gql_error)?;
+        Ok(GqlOrder(order))
+    }
+
+    /// Move the order to `status`; `note` is kept in its status history.
+    async fn update_status(
+        &self,
+        ctx: &Context<'_>,
+        id: ID,
+        status: GqlOrderStatus,
+        note: Option<String>,
+    ) -> async_graphql::Result<GqlOrder> {
+        let (service, tenant) = authorized::<R>(ctx, OrderAction::UpdateStatus)?;
+        let order = service
+            .update_status_with_note(tenant, parse_id(&id)?, status.into(), note.as_deref())
+            .await
+            .map_err(gql_error)?;
+        Ok(GqlOrder(order))
+    }
+
+    /// `true` once the order is gone.
+    async fn delete_order(&self, ctx: &Context<'_>, id: ID) -> async_graphql::Result<bool> {
+        let (service, tenant) = authorized::<R>(ctx, OrderAction::Delete)?;
+        service
+            .delete_order(tenant, parse_id(&id)?)
+            .await
+            .map_err(gql_error)?;
+        Ok(true)
+    }
+}
@@ -0,0 +1,180 @@
+use std::sync::Arc;
+
+use axum::body::{to_bytes, Body};
+use axum::extract::{Path, Request, State};
+use axum::http::header::AUTHORIZATION;
+use axum::http::StatusCode;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get};
+use axum::{Json, Router};
+use orders_types::domain::actor::Actor;
+use orders_types::domain::api_key::{ApiKey, Role, Scope};
+use orders_types::domain::request_signing::{RequestSignature, SignedRequest};
+use orders_types::domain::tenant::TenantId;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use super::json::JsonBody;
+use super::versioning::unversioned;
+use crate::application::actor;
+use crate::application::api_key_service::{ApiKeyService, MintedKey};
+use crate::application::auth::{self, Access, AuthContext, OrderAction};
+use crate::errors::AppError;
+
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Largest body buffered to check a request signature.
+const MAX_SIGNED_BODY_BYTES: usize = 16 * 1024 * 1024;
+
+/// Paths reachable without a key.
+const PUBLIC_PATHS: &[&str] = &["/health", "/healthz", "/readyz"];
+
+/// Reject requests without a valid `X-Api-Key` or request signature (see
+/// [`RequestSignature`]) and attach the caller's [`AuthContext`] to the
+/// request.
+pub async fn require_api_key(
+    State(keys): State<Arc<ApiKeyService>>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    if PUBLIC_PATHS.contains(&req.uri().path()) || is_share_link(&req) {
+        return Access::Anonymous.scope(next.run(req)).await;
+    }
+    let signature = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(RequestSignature::parse);
+    if let Some(signature) = signature {
+        return match signature {
+            Ok(sig) => require_signature(&keys, &sig, req, next).await,
+            Err(e) => AppError::Unauthorized(e.to_string()).into_response(),
+        };
+    }
+    let Some(secret) = req
+        .headers()
+        .get(API_KEY_HEADER)
+        .and_then(|v| v.to_str().ok())
+    else {
+        return AppError::Unauthorized("missing api key".into()).into_response();
+    };
+    match keys.authenticate(secret).await {
+        Ok(Some(ctx)) => {
+            req.extensions_mut().insert(ctx.clone());
+            Access::Caller(ctx).scope(next.run(req)).await
+        }
+        Ok(None) => AppError::Unauthorized("invalid api key".into()).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Buffer the body to check `sig` covers the request, then pass it on.
+async fn require_signature(
+    keys: &ApiKeyService,
+    sig: &RequestSignature,
+    req: Request,
+    next: Next,
+) -> Response {
+    let (parts, body) = req.into_parts();
+    let Ok(body) = to_bytes(body, MAX_SIGNED_BODY_BYTES).await else {
+        return AppError::PayloadTooLarge(format!(
+            "a signed body may be at most {MAX_SIGNED_BODY_BYTES} bytes"
+        ))
+        .into_response();
+    };
+    let signed = SignedRequest {
+        method: parts.method.as_str(),
+        path: parts.uri.path(),
+        query: parts.uri.query(),
+        body: &body,
+    };
+    match keys.authenticate_signed(sig, &signed).await {
+        Ok(ctx) => {
+            let mut req = Request::from_parts(parts, Body::from(body));
+            req.extensions_mut().insert(ctx.clone());
+            Access::Caller(ctx).scope(next.run(req)).await
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Run the rest of the request on behalf of the authenticated caller, or
+/// [`Actor::Anonymous`] when there is none. A bearer token naming a user
+/// narrows this further; see [`resolve_tenant`](super::tenant::resolve_tenant).
+pub async fn attribute(req: Request, next: Next) -> Response {
+    let actor = req
+        .extensions()
+        .get::<AuthContext>()
+        .map(AuthContext::actor)
+        .unwrap_or(Actor::Anonymous);
+    actor::scope(actor, next.run(req)).await
+}
+
+/// `GET /orders/{id}` with both `sig` and `exp`: a signed order link,
+/// which carries its own authorization. The handler verifies the signature;
+/// every other route still wants a key.
+pub(crate) fn is_share_link(req: &Request) -> bool {
+    let is_order = unversioned(req.uri().path())
+        .strip_prefix("/orders/")
+        .is_some_and(|id| Uuid::parse_str(id).is_ok());
+    let has = |name: &str| {
+        req.uri().query().is_some_and(|q| {
+            q.split('&').any(|p| {
+                p.split_once('=')
+                    .is_some_and(|(k, v)| k == name && !v.is_empty())
+            })
+        })
+    };
+    req.method() == axum::http::Method::GET && is_order && has("sig") && has("exp")
+}
+
+#[derive(Deserialize)]
+pub struct MintKeyRequest {
+    pub name: String,
+    pub scopes: Vec<Scope>,
+    #[serde(default)]
+    pub role: Option<Role>,
+    /// Tenant the key may act for; the default one when absent.
+    #[serde(default)]
+    pub tenant: Option<String>,
+}
+
+/// Admin routes for minting, listing and revoking keys.
+pub fn admin_router(keys: Arc<ApiKeyService>) -> Router {
+    Router::new()
+        .route("/admin/api-keys", get(list_keys).post(mint_key))
+        .route("/admin/api-keys/{id}", delete(revoke_key))
+        .with_state(keys)
+}
+
+async fn mint_key(
+    State(keys): State<Arc<ApiKeyService>>,
+    JsonBody(payload): JsonBody<MintKeyRequest>,
+) -> Result<(StatusCode, Json<MintedKey>), AppError> {
+    auth::authorize(OrderAction::Administer)?;
+    let tenant = payload
+        .tenant
+        .map(|t| TenantId::parse(&t).map_err(AppError::BadRequest))
+        .transpose()?
+        .unwrap_or_default();
+    let minted = keys
+        .mint(payload.name, payload.scopes, payload.role, tenant)
+        .await?;
+    Ok((StatusCode::CREATED, Json(minted)))
+}
+
+async fn list_keys(State(keys): State<Arc<ApiKeyService>>) -> Result<Json<Vec<ApiKey>>, AppError> {
+    auth::authorize(OrderAction::Administer)?;
+    Ok(Json(keys.list().await?))
+}
+
+async fn revoke_key(
+    State(keys): State<Arc<ApiKeyService>>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    auth::authorize(OrderAction::Administer)?;
+    let uuid = Uuid::parse_str(&id).map_err(|e| AppError::BadRequest(e.to_string()))?;
+    keys.revoke(uuid).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
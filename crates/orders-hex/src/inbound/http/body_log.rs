@@ -0,0 +1,200 @@
+//! Debug logging of `/orders` request and response bodies, for incident
+//! work in production. Off unless configured; sampled, size-capped and with
+//! personal fields redacted before anything reaches the log.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use axum::body::{to_bytes, Body, Bytes};
+use axum::extract::{Request, State};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use serde_json::Value;
+
+use super::versioning::unversioned;
+use crate::errors::AppError;
+
+const REDACTED: &str = "[REDACTED]";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BodyLogConfig {
+    /// Share of requests logged, from `0.0` (none) to `1.0` (all).
+    pub sample_rate: f64,
+    /// Longest body kept in a log line; longer ones are cut.
+    pub max_bytes: usize,
+    /// JSON fields whose values are replaced at any depth.
+    pub redact: Vec<String>,
+}
+
+impl Default for BodyLogConfig {
+    fn default() -> Self {
+        Self {
+            sample_rate: 1.0,
+            max_bytes: 2048,
+            redact: vec!["email".into(), "customer_name".into()],
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct BodyLogger {
+    config: Arc<BodyLogConfig>,
+    seen: Arc<AtomicU64>,
+}
+
+impl BodyLogger {
+    pub fn new(config: BodyLogConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+            seen: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Spread evenly: with a rate of `0.25` every fourth request is logged.
+    fn sampled(&self) -> bool {
+        let rate = self.config.sample_rate.clamp(0.0, 1.0);
+        let n = self.seen.fetch_add(1, Ordering::Relaxed) as f64;
+        ((n + 1.0) * rate).floor() > (n * rate).floor()
+    }
+
+    /// The body as it may be logged: redacted JSON, cut to `max_bytes`.
+    /// Anything else is only described, since it can't be redacted.
+    fn render(&self, body: &[u8]) -> String {
+        if body.is_empty() {
+            return String::new();
+        }
+        let Ok(mut json) = serde_json::from_slice::<Value>(body) else {
+            return format!("<{} bytes, not JSON>", body.len());
+        };
+        redact(&mut json, &self.config.redact);
+        let mut text = json.to_string();
+        if text.len() > self.config.max_bytes {
+            let mut cut = self.config.max_bytes;
+            while !text.is_char_boundary(cut) {
+                cut -= 1;
+            }
+            text.truncate(cut);
+            text.push_str("...");
+        }
+        text
+    }
+}
+
+fn redact(value: &mut Value, fields: &[String]) {
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map.iter_mut() {
+                if fields.iter().any(|f| f == key) {
+                    *v = Value::String(REDACTED.into());
+                } else {
+                    redact(v, fields);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|v| redact(v, fields)),
+        _ => {}
+    }
+}
+
+/// Whether `path` is an `/orders` API route, in any version. Imports are left out: their
+/// bodies are bulk uploads, not something to read in a log.
+fn logged_path(path: &str) -> bool {
+    let path = unversioned(path);
+    (path == "/orders" || path.starts_with("/orders/")) && path != "/orders/import"
+}
+
+async fn buffer(body: Body) -> Result<Bytes, Response> {
+    to_bytes(body, usize::MAX)
+        .await
+        .map_err(|e| AppError::BadRequest(format!("failed to read body: {e}")).into_response())
+}
+
+pub async fn log_bodies(State(logger): State<BodyLogger>, req: Request, next: Next) -> Response {
+    // Bodies are only buffered when the lines would actually be written.
+    if !logged_path(req.uri().path())
+        || !tracing::enabled!(target: "http_body", tracing::Level::DEBUG)
+        || !logger.sampled()
+    {
+        return next.run(req).await;
+    }
+    let (parts, body) = req.into_parts();
+    let bytes = match buffer(body).await {
+        Ok(b) => b,
+        Err(res) => return res,
+    };
+    tracing::debug!(
+        target: "http_body",
+        method = %parts.method,
+        path = %parts.uri.path(),
+        body = %logger.render(&bytes),
+        "request body"
+    );
+    let res = next
+        .run(Request::from_parts(parts, Body::from(bytes)))
+        .await;
+
+    let (parts, body) = res.into_parts();
+    let bytes = match buffer(body).await {
+        Ok(b) => b,
+        Err(res) => return res,
+    };
+    tracing::debug!(
+        target: "http_body",
+        status = %parts.status,
+        body = %logger.render(&bytes),
+        "response body"
+    );
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_nested_fields_and_caps_size() {
+        let logger = BodyLogger::new(BodyLogConfig {
+            max_bytes: 40,
+            ..BodyLogConfig::default()
+        });
+        let body = serde_json::json!({
+            "orders": [{"email": "ann@example.com", "customer_name": "Ann", "qty": 1}]
+        });
+        let text = logger.render(body.to_string().as_bytes());
+        assert!(!text.contains("ann@example.com"));
+        assert!(!text.contains("Ann"));
+        assert!(text.starts_with(r#"{"orders":[{"customer_name":"[REDACTED]""#));
+        assert!(text.ends_with("..."));
+        assert_eq!(text.len(), 43);
+
+        assert_eq!(logger.render(b"a,b\n1,2"), "<7 bytes, not JSON>");
+        assert_eq!(logger.render(b""), "");
+    }
+
+    #[test]
+    fn samples_evenly() {
+        let logger = BodyLogger::new(BodyLogConfig {
+            sample_rate: 0.25,
+            ..BodyLogConfig::default()
+        });
+        let picked: Vec<bool> = (0..8).map(|_| logger.sampled()).collect();
+        assert_eq!(picked.iter().filter(|&&p| p).count(), 2);
+
+        let none = BodyLogger::new(BodyLogConfig {
+            sample_rate: 0.0,
+            ..BodyLogConfig::default()
+        });
+        assert!(!(0..10).any(|_| none.sampled()));
+    }
+
+    #[test]
+    fn only_order_routes_are_logged() {
+        assert!(logged_path("/orders"));
+        assert!(logged_path("/orders/42/items"));
+        assert!(logged_path("/v1/orders"));
+        assert!(!logged_path("/v1/orders/import"));
+        assert!(!logged_path("/orders/import"));
+        assert!(!logged_path("/ordersx"));
+        assert!(!logged_path("/admin/discounts"));
+    }
+}
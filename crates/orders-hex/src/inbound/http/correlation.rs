@@ -0,0 +1,38 @@
+use axum::extract::Request;
+use axum::http::HeaderValue;
+use axum::middleware::Next;
+use axum::response::Response;
+use orders_types::domain::correlation::{CorrelationId, RequestId};
+
+use crate::application::correlation;
+
+/// Adopt the caller's `X-Correlation-Id` and `X-Request-Id` (minting each
+/// that is missing or malformed), expose them to handlers and the request
+/// span through the request extensions, scope the rest of the request to
+/// them and echo both back.
+pub async fn correlate(mut req: Request, next: Next) -> Response {
+    let id = header(&req, CorrelationId::HEADER)
+        .and_then(|v| CorrelationId::parse(v).ok())
+        .unwrap_or_default();
+    let request_id = header(&req, RequestId::HEADER)
+        .and_then(|v| RequestId::parse(v).ok())
+        .unwrap_or_default();
+    req.extensions_mut().insert(id.clone());
+    req.extensions_mut().insert(request_id.clone());
+    let mut res = correlation::scope(
+        id.clone(),
+        correlation::scope_request(request_id.clone(), next.run(req)),
+    )
+    .await;
+    if let Ok(value) = HeaderValue::from_str(id.as_str()) {
+        res.headers_mut().insert(CorrelationId::HEADER, value);
+    }
+    if let Ok(value) = HeaderValue::from_str(request_id.as_str()) {
+        res.headers_mut().insert(RequestId::HEADER, value);
+    }
+    res
+}
+
+fn header<'a>(req: &'a Request, name: &str) -> Option<&'a str> {
+    req.headers().get(name).and_then(|v| v.to_str().ok())
+}
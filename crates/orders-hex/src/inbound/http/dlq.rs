@@ -0,0 +1,51 @@
+use crate::application::auth::{self, OrderAction};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use orders_types::domain::dead_letter::{DeadLetter, DeadLetterState};
+use serde::Deserialize;
+
+use crate::application::dead_letters::DeadLetterService;
+use crate::errors::AppError;
+
+/// Admin routes for the dead letter queue of the outbox relays.
+pub fn dlq_router(letters: DeadLetterService) -> Router {
+    Router::new()
+        .route("/admin/dlq", get(list_letters))
+        .route("/admin/dlq/{id}/replay", post(replay_letter))
+        .with_state(letters)
+}
+
+#[derive(Deserialize)]
+struct ListQuery {
+    state: Option<String>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+}
+
+async fn list_letters(
+    State(letters): State<DeadLetterService>,
+    Query(q): Query<ListQuery>,
+) -> Result<Json<Vec<DeadLetter>>, AppError> {
+    auth::authorize(OrderAction::Administer)?;
+    let state = q
+        .state
+        .as_deref()
+        .map(DeadLetterState::parse)
+        .transpose()
+        .map_err(AppError::BadRequest)?;
+    let letters = letters
+        .list(state, q.limit.unwrap_or(50), q.offset.unwrap_or(0))
+        .await?;
+    Ok(Json(letters))
+}
+
+/// 202: the relay delivers it on its next poll.
+async fn replay_letter(
+    State(letters): State<DeadLetterService>,
+    Path(id): Path<u64>,
+) -> Result<(StatusCode, Json<DeadLetter>), AppError> {
+    auth::authorize(OrderAction::Administer)?;
+    Ok((StatusCode::ACCEPTED, Json(letters.replay(id).await?)))
+}
@@ -0,0 +1,118 @@
+//! Weak ETags for GET responses, so clients can revalidate with
+//! `If-None-Match` and get an empty `304` when nothing changed.
+
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use orders_types::domain::order::Order;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::errors::AppError;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ETag(String);
+
+impl ETag {
+    /// Changes whenever the order does, since every change bumps
+    /// `updated_at`.
+    pub fn for_order(order: &Order) -> Self {
+        Self(format!(
+            "W/\"{}-{}\"",
+            order.id,
+            order
+                .updated_at
+                .timestamp_nanos_opt()
+                .unwrap_or_else(|| order.updated_at.timestamp_micros())
+        ))
+    }
+
+    /// Digest of a response body, for collections with no single version.
+    pub fn for_bytes(body: &[u8]) -> Self {
+        let digest = Sha256::digest(body);
+        Self(format!("W/\"{}\"", hex::encode(&digest[..16])))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether the request's `If-None-Match` names this tag (or `*`).
+    /// Comparison is weak, so `W/` prefixes are ignored on both sides.
+    pub fn matches(&self, headers: &HeaderMap) -> bool {
+        let ours = opaque(&self.0);
+        headers
+            .get_all(header::IF_NONE_MATCH)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .map(str::trim)
+            .any(|tag| tag == "*" || opaque(tag) == ours)
+    }
+}
+
+fn opaque(tag: &str) -> &str {
+    tag.strip_prefix("W/").unwrap_or(tag)
+}
+
+/// `304` with just the tag when the caller already has it, otherwise `body`
+/// as JSON with the tag attached.
+pub fn json_with_etag<T: Serialize>(headers: &HeaderMap, etag: &ETag, body: &T) -> Response {
+    match serde_json::to_vec(body) {
+        Ok(bytes) => respond(headers, etag, bytes),
+        Err(e) => AppError::Internal(e.into()).into_response(),
+    }
+}
+
+/// Like [`json_with_etag`], tagging the serialized body itself.
+pub fn json_with_body_etag<T: Serialize>(headers: &HeaderMap, body: &T) -> Response {
+    match serde_json::to_vec(body) {
+        Ok(bytes) => respond(headers, &ETag::for_bytes(&bytes), bytes),
+        Err(e) => AppError::Internal(e.into()).into_response(),
+    }
+}
+
+fn respond(headers: &HeaderMap, etag: &ETag, json: Vec<u8>) -> Response {
+    let tag = match HeaderValue::from_str(etag.as_str()) {
+        Ok(tag) => tag,
+        Err(e) => return AppError::Internal(e.into()).into_response(),
+    };
+    if etag.matches(headers) {
+        return (StatusCode::NOT_MODIFIED, [(header::ETAG, tag)]).into_response();
+    }
+    (
+        [
+            (
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/json"),
+            ),
+            (header::ETAG, tag),
+        ],
+        json,
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_if_none_match(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn weak_comparison_over_lists_and_wildcards() {
+        let tag = ETag::for_bytes(b"[]");
+        assert!(tag.as_str().starts_with("W/\""));
+        let strong = tag.as_str().trim_start_matches("W/");
+        assert!(tag.matches(&with_if_none_match(tag.as_str())));
+        assert!(tag.matches(&with_if_none_match(strong)));
+        assert!(tag.matches(&with_if_none_match(&format!("\"x\", {strong}"))));
+        assert!(tag.matches(&with_if_none_match("*")));
+        assert!(!tag.matches(&with_if_none_match("\"x\"")));
+        assert!(!tag.matches(&HeaderMap::new()));
+        assert_ne!(tag, ETag::for_bytes(b"[1]"));
+    }
+}
@@ -0,0 +1,181 @@
+use std::convert::Infallible;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use axum::body::Bytes;
+use axum::extract::{Request, State};
+use axum::http::header::{ACCEPT, CONTENT_TYPE};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use futures_util::{stream, Stream, StreamExt, TryStreamExt};
+use orders_types::domain::import::ImportProgress;
+use orders_types::domain::tenant::TenantId;
+use orders_types::ports::order_repository::OrderRepository;
+use tokio::sync::mpsc;
+
+use super::tenant::Tenant;
+use crate::application::auth::{self, Access, OrderAction};
+use crate::application::correlation;
+use crate::application::order_service::OrderService;
+use crate::errors::AppError;
+use crate::inbound::import::{ImportFormat, RecordParser};
+
+/// Records stored per repository call.
+pub const IMPORT_BATCH_SIZE: usize = 500;
+
+type ByteStream = Pin<Box<dyn Stream<Item = Result<Bytes, String>> + Send>>;
+
+/// `POST /orders/import`: stream NDJSON or CSV orders in, either as the raw
+/// body or as the file part of a `multipart/form-data` upload. Answers with
+/// the final [`ImportProgress`], or with SSE `progress` events after each
+/// batch and a closing `done` (or `error`) event when the caller accepts
+/// `text/event-stream`.
+pub async fn import_orders<R>(
+    State(service): State<Arc<OrderService<R>>>,
+    Tenant(tenant): Tenant,
+    req: Request,
+) -> Result<Response, AppError>
+where
+    R: OrderRepository + Send + Sync + 'static,
+{
+    auth::authorize(OrderAction::Create)?;
+    let wants_events = req
+        .headers()
+        .get(ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains("text/event-stream"));
+    let (format, body) = open_upload(req).await?;
+
+    if !wants_events {
+        let summary = run_import(&service, &tenant, format, body, None).await?;
+        return Ok(Json(summary).into_response());
+    }
+
+    let (tx, rx) = mpsc::channel(8);
+    let id = correlation::current_or_new();
+    // The import outlives the request, so it takes the caller's access along.
+    let access = Access::current();
+    tokio::spawn(correlation::scope(
+        id,
+        access.scope(async move {
+            let event = match run_import(&service, &tenant, format, body, Some(&tx)).await {
+                Ok(summary) => json_event("done", &summary),
+                Err(e) => json_event("error", &serde_json::json!({ "error": e.to_string() })),
+            };
+            let _ = tx.send(event).await;
+        }),
+    ));
+    let events = stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|e| (Ok::<_, Infallible>(e), rx))
+    });
+    Ok(Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response())
+}
+
+/// Pick the format and byte stream out of a raw or multipart upload.
+async fn open_upload(req: Request) -> Result<(ImportFormat, ByteStream), AppError> {
+    let content_type = req
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    let body = req
+        .into_body()
+        .into_data_stream()
+        .map_err(|e| e.to_string());
+
+    let Ok(boundary) = multer::parse_boundary(&content_type) else {
+        let format = ImportFormat::from_mime(&content_type).ok_or_else(|| {
+            AppError::BadRequest("expected an NDJSON, CSV or multipart/form-data body".into())
+        })?;
+        return Ok((format, Box::pin(body)));
+    };
+    let mut multipart = multer::Multipart::new(body, boundary);
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| AppError::BadRequest(e.to_string()))?
+    {
+        let format = field
+            .content_type()
+            .and_then(|m| ImportFormat::from_mime(m.as_ref()))
+            .or_else(|| field.file_name().and_then(ImportFormat::from_filename));
+        if let Some(format) = format {
+            return Ok((format, Box::pin(field.map_err(|e| e.to_string()))));
+        }
+    }
+    Err(AppError::BadRequest(
+        "multipart upload has no .ndjson, .jsonl or .csv file part".into(),
+    ))
+}
+
+/// Parse `body` incrementally and store it batch by batch, sending a
+/// snapshot to `progress` after each batch.
+async fn run_import<R>(
+    service: &OrderService<R>,
+    tenant: &TenantId,
+    format: ImportFormat,
+    mut body: ByteStream,
+    progress: Option<&mpsc::Sender<Event>>,
+) -> Result<ImportProgress, AppError>
+where
+    R: OrderRepository + Send + Sync + 'static,
+{
+    let mut parser = RecordParser::new(format);
+    let mut summary = ImportProgress::default();
+    let mut parsed = Vec::new();
+    let mut batch = Vec::with_capacity(IMPORT_BATCH_SIZE);
+    loop {
+        let chunk = body
+            .next()
+            .await
+            .transpose()
+            .map_err(AppError::BadRequest)?;
+        match &chunk {
+            Some(bytes) => {
+                summary.bytes += bytes.len() as u64;
+                parser.push(bytes, &mut parsed);
+            }
+            None => parser.finish(&mut parsed),
+        }
+        for (line, record) in parsed.drain(..) {
+            summary.records += 1;
+            match record {
+                Ok(record) => batch.push((line, record)),
+                Err(e) => summary.record_failure(line, e),
+            }
+            if batch.len() == IMPORT_BATCH_SIZE {
+                service
+                    .import_batch(tenant, std::mem::take(&mut batch), &mut summary)
+                    .await?;
+                if let Some(tx) = progress {
+                    let _ = tx.send(json_event("progress", &summary)).await;
+                }
+            }
+        }
+        if chunk.is_none() {
+            break;
+        }
+    }
+    if !batch.is_empty() {
+        service.import_batch(tenant, batch, &mut summary).await?;
+    }
+    tracing::info!(
+        tenant = %tenant,
+        records = summary.records,
+        imported = summary.imported,
+        failed = summary.failed,
+        "order import finished"
+    );
+    Ok(summary)
+}
+
+fn json_event(name: &str, data: &impl serde::Serialize) -> Event {
+    Event::default()
+        .event(name)
+        .json_data(data)
+        .unwrap_or_else(|_| Event::default().event("error"))
+}
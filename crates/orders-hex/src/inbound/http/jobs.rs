@@ -0,0 +1,19 @@
+use crate::application::auth::{self, OrderAction};
+use axum::extract::State;
+use axum::routing::get;
+use axum::{Json, Router};
+
+use crate::application::scheduler::{JobBoard, JobStatus};
+use crate::errors::AppError;
+
+/// Admin route reporting the background jobs and their last runs.
+pub fn jobs_router(board: JobBoard) -> Router {
+    Router::new()
+        .route("/admin/jobs", get(list_jobs))
+        .with_state(board)
+}
+
+async fn list_jobs(State(board): State<JobBoard>) -> Result<Json<Vec<JobStatus>>, AppError> {
+    auth::authorize(OrderAction::Administer)?;
+    Ok(Json(board.statuses()))
+}
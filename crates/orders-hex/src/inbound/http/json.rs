@@ -0,0 +1,110 @@
+use axum::body::Bytes;
+use axum::extract::{FromRequest, Request};
+use axum::http::header::CONTENT_TYPE;
+use axum::http::StatusCode;
+use orders_types::domain::order::FieldError;
+use serde::de::DeserializeOwned;
+
+use crate::errors::AppError;
+
+/// `Json<T>` whose rejections use the API's error envelope: a body that does
+/// not parse or does not fit `T` becomes a 422 with the offending field's
+/// path, e.g. `{"field":"items[0].qty","message":"invalid value: ..."}`, and
+/// one over the body limit (see [`HttpServer::with_body_limit`]) a 413.
+///
+/// [`HttpServer::with_body_limit`]: super::HttpServer::with_body_limit
+pub struct JsonBody<T>(pub T);
+
+impl<S, T> FromRequest<S> for JsonBody<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        if !is_json(&req) {
+            return Err(AppError::BadRequest(
+                "expected `Content-Type: application/json`".into(),
+            ));
+        }
+        let bytes = Bytes::from_request(req, state)
+            .await
+            .map_err(|e| match e.status() {
+                StatusCode::PAYLOAD_TOO_LARGE => AppError::PayloadTooLarge(e.body_text()),
+                _ => AppError::BadRequest(e.body_text()),
+            })?;
+        let mut de = serde_json::Deserializer::from_slice(&bytes);
+        let value = serde_path_to_error::deserialize(&mut de).map_err(|e| {
+            let field = e.path().to_string();
+            AppError::Validation(vec![field_error(&field, e.into_inner())])
+        })?;
+        de.end()
+            .map_err(|e| AppError::Validation(vec![field_error(".", e)]))?;
+        Ok(JsonBody(value))
+    }
+}
+
+fn is_json(req: &Request) -> bool {
+    req.headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .map(str::trim)
+        .is_some_and(|mime| {
+            mime.eq_ignore_ascii_case("application/json") || mime.ends_with("+json")
+        })
+}
+
+/// Address a serde error at `path` (`.` is the document root); a missing
+/// field is reported at the field itself rather than at its parent.
+fn field_error(path: &str, err: serde_json::Error) -> FieldError {
+    let parent = if path == "." { "" } else { path };
+    let message = err.to_string();
+    let missing = message
+        .strip_prefix("missing field `")
+        .and_then(|rest| rest.split('`').next());
+    match missing {
+        Some(name) if parent.is_empty() => FieldError::new(name, "is required"),
+        Some(name) => FieldError::new(format!("{parent}.{name}"), "is required"),
+        None => FieldError::new(parent, message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_for(body: &str) -> FieldError {
+        #[derive(serde::Deserialize, Debug)]
+        #[allow(dead_code)]
+        struct Item {
+            name: String,
+            qty: u32,
+        }
+        #[derive(serde::Deserialize, Debug)]
+        #[allow(dead_code)]
+        struct Body {
+            email: String,
+            items: Vec<Item>,
+        }
+        let de = &mut serde_json::Deserializer::from_str(body);
+        let err = serde_path_to_error::deserialize::<_, Body>(de).unwrap_err();
+        let path = err.path().to_string();
+        field_error(&path, err.into_inner())
+    }
+
+    #[test]
+    fn errors_name_the_json_path() {
+        let e = error_for(r#"{"email":"a@b.c","items":[{"name":"A","qty":-1}]}"#);
+        assert_eq!(e.field, "items[0].qty");
+        assert_eq!(
+            error_for(r#"{"items":[]}"#),
+            FieldError::new("email", "is required")
+        );
+        assert_eq!(
+            error_for(r#"{"email":"a@b.c","items":[{"qty":1}]}"#),
+            FieldError::new("items[0].name", "is required")
+        );
+    }
+}
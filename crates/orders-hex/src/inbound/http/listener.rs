@@ -0,0 +1,101 @@
+//! Where the public API accepts connections: TCP by default, a Unix domain
+//! socket for a sidecar or reverse proxy on the same host, or a socket
+//! inherited through systemd socket activation.
+
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Listener {
+    /// All interfaces at [`HttpServerConfig::port`](super::HttpServerConfig::port).
+    #[default]
+    Tcp,
+    /// A socket file at this path; a stale one left by an earlier run is
+    /// replaced.
+    #[cfg(unix)]
+    Unix(PathBuf),
+    /// The first socket systemd passed in `LISTEN_FDS`, TCP or Unix.
+    #[cfg(unix)]
+    Systemd,
+}
+
+impl FromStr for Listener {
+    type Err = String;
+
+    /// `tcp`, `unix:<path>` or `systemd`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "" | "tcp" => Ok(Self::Tcp),
+            #[cfg(unix)]
+            "systemd" => Ok(Self::Systemd),
+            #[cfg(unix)]
+            other if other.starts_with("unix:") => match &other["unix:".len()..] {
+                "" => Err("`unix:` needs a socket path".into()),
+                path => Ok(Self::Unix(path.into())),
+            },
+            other => Err(format!(
+                "unknown listener `{other}`; expected `tcp`, `unix:<path>` or `systemd`"
+            )),
+        }
+    }
+}
+
+/// A socket bound for a non-TCP [`Listener`], ready to serve.
+#[cfg(unix)]
+pub(crate) enum Bound {
+    Tcp(tokio::net::TcpListener),
+    Unix(tokio::net::UnixListener),
+}
+
+#[cfg(unix)]
+pub(crate) fn bind_unix(path: &std::path::Path) -> anyhow::Result<Bound> {
+    use std::os::unix::fs::FileTypeExt;
+
+    if let Ok(meta) = std::fs::symlink_metadata(path) {
+        anyhow::ensure!(
+            meta.file_type().is_socket(),
+            "{} exists and is not a socket",
+            path.display()
+        );
+        std::fs::remove_file(path)?;
+    }
+    Ok(Bound::Unix(tokio::net::UnixListener::bind(path)?))
+}
+
+#[cfg(unix)]
+pub(crate) fn take_systemd() -> anyhow::Result<Bound> {
+    let mut fds = listenfd::ListenFd::from_env();
+    if let Ok(Some(tcp)) = fds.take_tcp_listener(0) {
+        tcp.set_nonblocking(true)?;
+        return Ok(Bound::Tcp(tokio::net::TcpListener::from_std(tcp)?));
+    }
+    match fds.take_unix_listener(0)? {
+        Some(unix) => {
+            unix.set_nonblocking(true)?;
+            Ok(Bound::Unix(tokio::net::UnixListener::from_std(unix)?))
+        }
+        None => anyhow::bail!("systemd passed no listening socket (LISTEN_FDS is unset or 0)"),
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_listener_specs() {
+        assert_eq!("tcp".parse(), Ok(Listener::Tcp));
+        assert_eq!("".parse(), Ok(Listener::Tcp));
+        assert_eq!("systemd".parse(), Ok(Listener::Systemd));
+        assert_eq!(
+            "unix:/run/orders.sock".parse(),
+            Ok(Listener::Unix("/run/orders.sock".into()))
+        );
+        assert!("unix:".parse::<Listener>().is_err());
+        assert!("udp".parse::<Listener>().is_err());
+    }
+}
@@ -1,3 +1,24 @@
+pub mod auth;
+pub mod body_log;
+pub mod correlation;
+pub mod dlq;
+pub mod etag;
+pub mod import;
+pub mod jobs;
+pub mod json;
+pub mod listener;
+pub mod negotiate;
+pub mod ops;
+pub mod rate_limit;
+pub mod request_metrics;
 pub mod server;
+pub mod slo;
+pub mod tenant;
+pub mod tls;
+pub mod versioning;
+pub mod webhooks;
+pub mod ws;
 
+pub use listener::Listener;
 pub use server::{HttpServer, HttpServerConfig};
+pub use tls::TlsConfig;
@@ -0,0 +1,263 @@
+//! Response encodings chosen from `Accept`. Handlers always answer JSON;
+//! this layer re-encodes it as MessagePack on any route, or as CSV rows on
+//! `GET /orders` and `GET /customers/{email}/export`, so no handler needs to
+//! know about formats.
+
+use axum::body::{to_bytes, Body};
+use axum::extract::Request;
+use axum::http::{header, HeaderMap, HeaderValue, Method};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use serde_json::Value;
+
+use super::versioning::unversioned;
+use crate::errors::AppError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    MessagePack,
+    Csv,
+}
+
+impl Format {
+    pub const MSGPACK: &'static str = "application/msgpack";
+    pub const CSV: &'static str = "text/csv; charset=utf-8";
+
+    fn from_media_type(media: &str, csv: bool) -> Option<Self> {
+        match media.to_ascii_lowercase().as_str() {
+            "application/json" | "application/*" | "*/*" => Some(Self::Json),
+            "application/msgpack" | "application/x-msgpack" => Some(Self::MessagePack),
+            "text/csv" if csv => Some(Self::Csv),
+            _ => None,
+        }
+    }
+
+    /// The format the caller prefers, by q-value and then by listing order.
+    /// CSV only counts when `csv` is set; with nothing usable in `Accept`
+    /// the answer is JSON.
+    pub fn negotiate(headers: &HeaderMap, csv: bool) -> Self {
+        let mut best: Option<(Self, f32)> = None;
+        let ranges = headers
+            .get_all(header::ACCEPT)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','));
+        for range in ranges {
+            let mut params = range.split(';').map(str::trim);
+            let Some(format) = params
+                .next()
+                .and_then(|media| Self::from_media_type(media, csv))
+            else {
+                continue;
+            };
+            let q = params
+                .find_map(|p| p.strip_prefix("q="))
+                .and_then(|q| q.parse::<f32>().ok())
+                .unwrap_or(1.0);
+            if q > 0.0 && best.is_none_or(|(_, best_q)| q > best_q) {
+                best = Some((format, q));
+            }
+        }
+        best.map_or(Self::Json, |(format, _)| format)
+    }
+}
+
+/// Column per scalar field, nested objects flattened with dotted names
+/// (`shipping_address.country`) and arrays kept as JSON text.
+fn flatten(prefix: &str, value: &Value, row: &mut Vec<(String, String)>) {
+    let cell = match value {
+        Value::Object(map) => {
+            for (key, v) in map {
+                let name = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                flatten(&name, v, row);
+            }
+            return;
+        }
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    row.push((prefix.to_string(), cell));
+}
+
+fn push_field(out: &mut String, field: &str) {
+    if field.contains([',', '"', '\n', '\r']) {
+        out.push('"');
+        out.push_str(&field.replace('"', "\"\""));
+        out.push('"');
+    } else {
+        out.push_str(field);
+    }
+}
+
+fn push_record<'a>(out: &mut String, fields: impl IntoIterator<Item = &'a str>) {
+    for (i, field) in fields.into_iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        push_field(out, field);
+    }
+    out.push_str("\r\n");
+}
+
+/// One row per order of an order page, with a header row naming every
+/// column any of them has: `id` first, the rest by name. `None` when `page`
+/// isn't an order page.
+pub fn orders_csv(page: &Value) -> Option<String> {
+    let rows: Vec<Vec<(String, String)>> = page
+        .get("orders")?
+        .as_array()?
+        .iter()
+        .map(|order| {
+            let mut row = Vec::new();
+            flatten("", order, &mut row);
+            row
+        })
+        .collect();
+    let mut columns: Vec<&str> = Vec::new();
+    for (name, _) in rows.iter().flatten() {
+        if !columns.contains(&name.as_str()) {
+            columns.push(name);
+        }
+    }
+    columns.sort_by_key(|&name| (name != "id", name));
+    let mut out = String::new();
+    push_record(&mut out, columns.iter().copied());
+    for row in &rows {
+        push_record(
+            &mut out,
+            columns.iter().map(|col| {
+                row.iter()
+                    .find(|(name, _)| name == col)
+                    .map_or("", |(_, cell)| cell.as_str())
+            }),
+        );
+    }
+    Some(out)
+}
+
+fn is_json(res: &Response) -> bool {
+    res.headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"))
+}
+
+/// Routes answering with an `orders` array, which CSV can render.
+fn has_order_rows(path: &str) -> bool {
+    path == "/orders"
+        || path
+            .strip_prefix("/customers/")
+            .and_then(|rest| rest.strip_suffix("/export"))
+            .is_some_and(|email| !email.is_empty() && !email.contains('/'))
+}
+
+pub async fn negotiate(req: Request, next: Next) -> Response {
+    let csv = req.method() == Method::GET && has_order_rows(unversioned(req.uri().path()));
+    let format = Format::negotiate(req.headers(), csv);
+    let mut res = next.run(req).await;
+    res.headers_mut()
+        .append(header::VARY, HeaderValue::from_static("accept"));
+    // Error bodies aren't tabular, so they stay JSON for CSV callers.
+    let wanted = match format {
+        Format::Json => false,
+        Format::MessagePack => true,
+        Format::Csv => res.status().is_success(),
+    };
+    if !wanted || !is_json(&res) {
+        return res;
+    }
+
+    let (mut parts, body) = res.into_parts();
+    let bytes = match to_bytes(body, usize::MAX).await {
+        Ok(b) => b,
+        Err(e) => return AppError::Internal(e.into()).into_response(),
+    };
+    let value: Value = match serde_json::from_slice(&bytes) {
+        Ok(v) => v,
+        Err(e) => return AppError::Internal(e.into()).into_response(),
+    };
+    let (content_type, encoded) = match format {
+        Format::MessagePack => match rmp_serde::to_vec_named(&value) {
+            Ok(encoded) => (Format::MSGPACK, encoded),
+            Err(e) => return AppError::Internal(e.into()).into_response(),
+        },
+        _ => match orders_csv(&value) {
+            Some(text) => (Format::CSV, text.into_bytes()),
+            None => return Response::from_parts(parts, Body::from(bytes)),
+        },
+    };
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts
+        .headers
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+    Response::from_parts(parts, Body::from(encoded))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accept(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn picks_the_preferred_supported_format() {
+        assert_eq!(Format::negotiate(&HeaderMap::new(), true), Format::Json);
+        assert_eq!(
+            Format::negotiate(&accept("application/msgpack"), false),
+            Format::MessagePack
+        );
+        assert_eq!(Format::negotiate(&accept("text/csv"), true), Format::Csv);
+        assert_eq!(Format::negotiate(&accept("text/csv"), false), Format::Json);
+        assert_eq!(
+            Format::negotiate(&accept("text/csv;q=0.5, application/json"), true),
+            Format::Json
+        );
+        assert_eq!(
+            Format::negotiate(&accept("application/json;q=0.2, text/csv"), true),
+            Format::Csv
+        );
+        assert_eq!(
+            Format::negotiate(&accept("application/msgpack;q=0, */*"), false),
+            Format::Json
+        );
+        assert_eq!(Format::negotiate(&accept("image/png"), true), Format::Json);
+    }
+
+    #[test]
+    fn csv_is_offered_on_order_lists_only() {
+        assert!(has_order_rows("/orders"));
+        assert!(has_order_rows("/customers/a@example.com/export"));
+        assert!(!has_order_rows("/customers//export"));
+        assert!(!has_order_rows("/customers/a@example.com/data"));
+        assert!(!has_order_rows("/orders/1"));
+    }
+
+    #[test]
+    fn orders_flatten_into_rows() {
+        let page = serde_json::json!({
+            "orders": [
+                {"id": "a", "customer_name": "Ann, Jr.", "items": [{"qty": 1}],
+                 "shipping_address": {"country": "US"}},
+                {"id": "b", "customer_name": "Bo \"B\"", "items": [], "note": null}
+            ],
+            "sort": null
+        });
+        assert_eq!(
+            orders_csv(&page).unwrap(),
+            "id,customer_name,items,note,shipping_address.country\r\n\
+             a,\"Ann, Jr.\",\"[{\"\"qty\"\":1}]\",,US\r\n\
+             b,\"Bo \"\"B\"\"\",[],,\r\n"
+        );
+        assert_eq!(orders_csv(&serde_json::json!({"id": "a"})), None);
+    }
+}
@@ -0,0 +1,75 @@
+//! Operational endpoints for the internal admin listener: the effective
+//! configuration, runtime diagnostics and schema migration status. None of
+//! them is served on the public API.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use axum::extract::State;
+use axum::routing::get;
+use axum::{Json, Router};
+use orders_types::ports::migrations::{MigrationSource, MigrationStatus};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::errors::AppError;
+
+#[derive(Clone)]
+pub struct Ops {
+    pub started: Instant,
+    /// Shown by `GET /config`; secrets should already be redacted.
+    pub config: Option<Arc<Value>>,
+    pub migrations: Option<Arc<dyn MigrationSource>>,
+}
+
+/// `GET /debug/runtime`: process and async runtime figures, the first
+/// things to look at when the service is slow or stuck.
+#[derive(Debug, Serialize)]
+pub struct RuntimeReport {
+    pub version: &'static str,
+    pub pid: u32,
+    pub uptime_secs: u64,
+    pub workers: usize,
+    pub alive_tasks: usize,
+    /// Tasks waiting in the shared queue for a free worker.
+    pub global_queue_depth: usize,
+}
+
+/// `/config` and `/migrations` are only mounted when there is something to
+/// show.
+pub fn ops_router(ops: Ops) -> Router {
+    let mut router = Router::new().route("/debug/runtime", get(runtime));
+    if ops.config.is_some() {
+        router = router.route("/config", get(config));
+    }
+    if ops.migrations.is_some() {
+        router = router.route("/migrations", get(migrations));
+    }
+    router.with_state(ops)
+}
+
+async fn config(State(ops): State<Ops>) -> Json<Value> {
+    Json(ops.config.as_deref().cloned().unwrap_or_default())
+}
+
+async fn runtime(State(ops): State<Ops>) -> Json<RuntimeReport> {
+    let metrics = tokio::runtime::Handle::current().metrics();
+    Json(RuntimeReport {
+        version: env!("CARGO_PKG_VERSION"),
+        pid: std::process::id(),
+        uptime_secs: ops.started.elapsed().as_secs(),
+        workers: metrics.num_workers(),
+        alive_tasks: metrics.num_alive_tasks(),
+        global_queue_depth: metrics.global_queue_depth(),
+    })
+}
+
+async fn migrations(State(ops): State<Ops>) -> Result<Json<MigrationStatus>, AppError> {
+    let Some(source) = ops.migrations else {
+        return Ok(Json(MigrationStatus {
+            current_version: None,
+            pending: Vec::new(),
+        }));
+    };
+    Ok(Json(source.migration_status().await?))
+}
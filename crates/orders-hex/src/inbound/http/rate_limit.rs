@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use axum::extract::{ConnectInfo, Request, State};
+use axum::http::{HeaderValue, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use orders_types::domain::error_code::ErrorCode;
+
+use crate::errors::ErrorBody;
+
+/// Token-bucket parameters: `burst` tokens, refilled at `per_second`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quota {
+    pub burst: u32,
+    pub per_second: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Decision {
+    Allowed { remaining: u32 },
+    Limited { retry_after: Duration },
+}
+
+/// Backing store for buckets. The in-memory store is per process; implement
+/// this over Redis (or similar) to share limits across instances.
+#[async_trait]
+pub trait RateLimitStore: Send + Sync + 'static {
+    async fn acquire(&self, key: &str, quota: Quota) -> Decision;
+}
+
+struct Bucket {
+    tokens: f64,
+    last: Instant,
+}
+
+#[derive(Default)]
+pub struct InMemoryRateLimitStore {
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl InMemoryRateLimitStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for InMemoryRateLimitStore {
+    async fn acquire(&self, key: &str, quota: Quota) -> Decision {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().expect("rate limit store poisoned");
+        let bucket = buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: quota.burst as f64,
+            last: now,
+        });
+        let elapsed = now.duration_since(bucket.last).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * quota.per_second).min(quota.burst as f64);
+        bucket.last = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Decision::Allowed {
+                remaining: bucket.tokens as u32,
+            }
+        } else {
+            let wait = (1.0 - bucket.tokens) / quota.per_second.max(f64::EPSILON);
+            Decision::Limited {
+                retry_after: Duration::from_secs_f64(wait),
+            }
+        }
+    }
+}
+
+/// What identifies a client for limiting purposes.
+#[derive(Debug, Clone)]
+pub enum KeySource {
+    /// Peer socket address.
+    ClientIp,
+    /// Value of the named header (e.g. `x-api-key`), falling back to the IP.
+    Header(String),
+}
+
+#[derive(Clone)]
+pub struct RateLimiter {
+    store: Arc<dyn RateLimitStore>,
+    key: KeySource,
+    default_quota: Quota,
+    quotas: Arc<HashMap<String, Quota>>,
+}
+
+impl RateLimiter {
+    pub fn new(store: impl RateLimitStore, key: KeySource, default_quota: Quota) -> Self {
+        Self {
+            store: Arc::new(store),
+            key,
+            default_quota,
+            quotas: Arc::new(HashMap::new()),
+        }
+    }
+
+    /// Per-key override of the default quota.
+    pub fn with_quota(mut self, key: impl Into<String>, quota: Quota) -> Self {
+        Arc::make_mut(&mut self.quotas).insert(key.into(), quota);
+        self
+    }
+
+    fn key_for(&self, req: &Request) -> String {
+        let ip = || {
+            req.extensions()
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ci| ci.0.ip().to_string())
+                .unwrap_or_else(|| "unknown".into())
+        };
+        match &self.key {
+            KeySource::ClientIp => ip(),
+            KeySource::Header(name) => req
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+                .unwrap_or_else(ip),
+        }
+    }
+}
+
+pub async fn rate_limit(State(limiter): State<RateLimiter>, req: Request, next: Next) -> Response {
+    let key = limiter.key_for(&req);
+    let quota = limiter
+        .quotas
+        .get(&key)
+        .copied()
+        .unwrap_or(limiter.default_quota);
+    match limiter.store.acquire(&key, quota).await {
+        Decision::Allowed { remaining } => {
+            let mut res = next.run(req).await;
+            res.headers_mut()
+                .insert("x-ratelimit-remaining", HeaderValue::from(remaining));
+            res
+        }
+        Decision::Limited { retry_after } => {
+            let secs = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+            (
+                StatusCode::TOO_MANY_REQUESTS,
+                [
+                    ("retry-after", secs.to_string()),
+                    ("content-type", "application/json".to_string()),
+                ],
+                ErrorBody::new(ErrorCode::RateLimited, "rate limit exceeded").to_json(),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn bucket_drains_then_limits() {
+        let store = InMemoryRateLimitStore::new();
+        let quota = Quota {
+            burst: 2,
+            per_second: 0.5,
+        };
+        assert!(matches!(
+            store.acquire("k", quota).await,
+            Decision::Allowed { remaining: 1 }
+        ));
+        assert!(matches!(
+            store.acquire("k", quota).await,
+            Decision::Allowed { remaining: 0 }
+        ));
+        match store.acquire("k", quota).await {
+            Decision::Limited { retry_after } => assert!(retry_after > Duration::from_secs(1)),
+            other => panic!("expected limit, got {other:?}"),
+        }
+        // Separate keys have separate buckets.
+        assert!(matches!(
+            store.acquire("other", quota).await,
+            Decision::Allowed { .. }
+        ));
+    }
+}
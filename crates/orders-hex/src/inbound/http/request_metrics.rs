@@ -0,0 +1,392 @@
+//! Request counts per tenant and per API key, and order values per tenant,
+//! for per-customer dashboards. Each series remembers the correlation id of
+//! its latest request or order as a `trace_id` exemplar, exported when the
+//! scrape asks for OpenMetrics.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use axum::extract::{MatchedPath, Request, State};
+use axum::middleware::Next;
+use axum::response::Response;
+use orders_types::domain::correlation::CorrelationId;
+use orders_types::domain::events::{EventEnvelope, OrderEvent};
+use orders_types::domain::order::Order;
+use orders_types::domain::tenant::TenantId;
+use orders_types::ports::metrics::MetricsSource;
+
+use super::versioning::unversioned;
+use crate::application::auth::AuthContext;
+
+/// Upper bounds, in minor units, of the order value histogram buckets.
+const ORDER_VALUE_BUCKETS: [i64; 8] = [
+    1_000, 2_500, 5_000, 10_000, 25_000, 50_000, 100_000, 500_000,
+];
+
+/// The request or order a series last saw.
+#[derive(Debug, Clone)]
+struct Exemplar {
+    trace_id: String,
+    value: f64,
+    /// Seconds since the Unix epoch.
+    at: f64,
+}
+
+impl Exemplar {
+    fn new(trace_id: &str, value: f64) -> Self {
+        let at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs_f64())
+            .unwrap_or_default();
+        Self {
+            trace_id: trace_id.to_string(),
+            value,
+            at,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Counter {
+    value: u64,
+    exemplar: Option<Exemplar>,
+}
+
+impl Counter {
+    fn inc(&mut self, trace_id: Option<&str>) {
+        self.value += 1;
+        if let Some(id) = trace_id {
+            self.exemplar = Some(Exemplar::new(id, 1.0));
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Histogram {
+    /// Observations per bucket of [`ORDER_VALUE_BUCKETS`], the last one
+    /// above them all; not cumulative.
+    counts: [u64; ORDER_VALUE_BUCKETS.len() + 1],
+    exemplars: [Option<Exemplar>; ORDER_VALUE_BUCKETS.len() + 1],
+    sum: i64,
+}
+
+impl Histogram {
+    fn observe(&mut self, value: i64, trace_id: &str) {
+        let bucket = ORDER_VALUE_BUCKETS
+            .iter()
+            .position(|&le| value <= le)
+            .unwrap_or(ORDER_VALUE_BUCKETS.len());
+        self.counts[bucket] += 1;
+        self.exemplars[bucket] = Some(Exemplar::new(trace_id, value as f64));
+        self.sum = self.sum.saturating_add(value);
+    }
+}
+
+#[derive(Debug, Default)]
+struct Series {
+    /// By tenant, `METHOD /route` and status class.
+    tenant_requests: BTreeMap<(String, String, &'static str), Counter>,
+    /// By API key and status class.
+    key_requests: BTreeMap<(String, &'static str), Counter>,
+    /// By tenant and currency.
+    order_values: BTreeMap<(String, String), Histogram>,
+}
+
+/// Counters and histograms behind the `orders_tenant_*`, `orders_api_key_*`
+/// and `orders_order_value_*` series. Cheap to clone; clones share counts.
+#[derive(Clone, Default)]
+pub struct RequestMetrics {
+    series: Arc<Mutex<Series>>,
+}
+
+impl RequestMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count one request to `route` for `tenant`, made with `api_key`
+    /// (`key:<id>`, `bootstrap`, or `none` without API keys).
+    pub fn record_request(
+        &self,
+        tenant: &TenantId,
+        route: &str,
+        api_key: &str,
+        status: u16,
+        trace_id: Option<&str>,
+    ) {
+        let class = status_class(status);
+        let mut series = self.series.lock().expect("request metrics poisoned");
+        series
+            .tenant_requests
+            .entry((tenant.as_str().to_string(), route.to_string(), class))
+            .or_default()
+            .inc(trace_id);
+        series
+            .key_requests
+            .entry((api_key.to_string(), class))
+            .or_default()
+            .inc(trace_id);
+    }
+
+    /// Add a new order's total to its tenant's value histogram.
+    pub fn observe_order(&self, order: &Order, trace_id: &str) {
+        let mut series = self.series.lock().expect("request metrics poisoned");
+        series
+            .order_values
+            .entry((
+                order.tenant_id.as_str().to_string(),
+                order.total.currency().to_string(),
+            ))
+            .or_default()
+            .observe(order.total.amount_minor(), trace_id);
+    }
+
+    /// Feed a published event in; only creations carry an order value.
+    pub fn observe(&self, envelope: &EventEnvelope) {
+        if let OrderEvent::Created { order } = &envelope.event {
+            self.observe_order(order, envelope.correlation_id.as_str());
+        }
+    }
+
+    /// Text exposition; exemplars and OpenMetrics counter naming only with
+    /// `openmetrics`, since the Prometheus text format has no exemplars.
+    fn render(&self, openmetrics: bool) -> String {
+        let series = self.series.lock().expect("request metrics poisoned");
+        let mut out = String::new();
+
+        counter_header(
+            &mut out,
+            "orders_tenant_requests",
+            "Requests per tenant, route and status class.",
+            openmetrics,
+        );
+        for ((tenant, route, class), counter) in &series.tenant_requests {
+            let labels = format!(
+                "tenant=\"{}\",route=\"{}\",status=\"{class}\"",
+                escape(tenant),
+                escape(route)
+            );
+            sample(
+                &mut out,
+                "orders_tenant_requests_total",
+                &labels,
+                counter.value,
+                counter.exemplar.as_ref().filter(|_| openmetrics),
+            );
+        }
+
+        counter_header(
+            &mut out,
+            "orders_api_key_requests",
+            "Requests per API key and status class.",
+            openmetrics,
+        );
+        for ((key, class), counter) in &series.key_requests {
+            let labels = format!("api_key=\"{}\",status=\"{class}\"", escape(key));
+            sample(
+                &mut out,
+                "orders_api_key_requests_total",
+                &labels,
+                counter.value,
+                counter.exemplar.as_ref().filter(|_| openmetrics),
+            );
+        }
+
+        let name = "orders_order_value_minor_units";
+        let _ = writeln!(
+            out,
+            "# HELP {name} Totals of new orders per tenant, in minor units of their currency.\n\
+             # TYPE {name} histogram"
+        );
+        for ((tenant, currency), histogram) in &series.order_values {
+            let labels = format!(
+                "tenant=\"{}\",currency=\"{}\"",
+                escape(tenant),
+                escape(currency)
+            );
+            let mut cumulative = 0;
+            let bounds = ORDER_VALUE_BUCKETS
+                .iter()
+                .map(|le| le.to_string())
+                .chain(std::iter::once("+Inf".to_string()));
+            for (i, le) in bounds.enumerate() {
+                cumulative += histogram.counts[i];
+                sample(
+                    &mut out,
+                    &format!("{name}_bucket"),
+                    &format!("{labels},le=\"{le}\""),
+                    cumulative,
+                    histogram.exemplars[i].as_ref().filter(|_| openmetrics),
+                );
+            }
+            let _ = writeln!(out, "{name}_sum{{{labels}}} {}", histogram.sum);
+            let _ = writeln!(out, "{name}_count{{{labels}}} {cumulative}");
+        }
+        out
+    }
+}
+
+impl MetricsSource for RequestMetrics {
+    fn prometheus(&self) -> String {
+        self.render(false)
+    }
+
+    fn openmetrics(&self) -> String {
+        self.render(true)
+    }
+}
+
+/// Count every routed request against its tenant and API key. Runs inside
+/// tenant resolution, so the tenant is known.
+pub async fn track_requests(
+    State(metrics): State<RequestMetrics>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| format!("{} {}", req.method(), unversioned(p.as_str())));
+    let tenant = req.extensions().get::<TenantId>().cloned();
+    let api_key = match req.extensions().get::<AuthContext>() {
+        Some(ctx) => ctx.actor().to_string(),
+        None => "none".to_string(),
+    };
+    let trace_id = req
+        .extensions()
+        .get::<CorrelationId>()
+        .map(|id| id.as_str().to_string());
+    let res = next.run(req).await;
+    if let (Some(route), Some(tenant)) = (route, tenant) {
+        metrics.record_request(
+            &tenant,
+            &route,
+            &api_key,
+            res.status().as_u16(),
+            trace_id.as_deref(),
+        );
+    }
+    res
+}
+
+fn status_class(status: u16) -> &'static str {
+    match status {
+        100..=199 => "1xx",
+        200..=299 => "2xx",
+        300..=399 => "3xx",
+        400..=499 => "4xx",
+        _ => "5xx",
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// OpenMetrics names the counter family without its `_total` suffix; the
+/// Prometheus text format names it after the sample.
+fn counter_header(out: &mut String, family: &str, help: &str, openmetrics: bool) {
+    let name = if openmetrics {
+        family.to_string()
+    } else {
+        format!("{family}_total")
+    };
+    let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} counter");
+}
+
+fn sample(out: &mut String, name: &str, labels: &str, value: u64, exemplar: Option<&Exemplar>) {
+    let _ = write!(out, "{name}{{{labels}}} {value}");
+    if let Some(e) = exemplar {
+        let _ = write!(
+            out,
+            " # {{trace_id=\"{}\"}} {} {:.3}",
+            escape(&e.trace_id),
+            e.value,
+            e.at
+        );
+    }
+    out.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use orders_types::domain::money::Money;
+    use orders_types::domain::order::OrderItem;
+
+    fn order(cents: i64) -> Order {
+        Order::new(
+            "Ann".into(),
+            "ann@example.com".into(),
+            vec![OrderItem {
+                name: "Widget".into(),
+                qty: 1,
+                unit_price: Money::usd(cents),
+                weight_grams: 0,
+                sku: None,
+                description: None,
+                metadata: Default::default(),
+                discount_cents: 0,
+            }],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn exemplars_only_appear_in_openmetrics() {
+        let metrics = RequestMetrics::new();
+        let acme = TenantId::parse("acme").unwrap();
+        metrics.record_request(&acme, "GET /orders", "bootstrap", 200, Some("req-1"));
+        metrics.record_request(&acme, "GET /orders", "bootstrap", 200, Some("req-2"));
+        metrics.record_request(&acme, "GET /orders", "bootstrap", 503, None);
+
+        let text = metrics.prometheus();
+        assert!(text.contains("# TYPE orders_tenant_requests_total counter"));
+        assert!(text.contains(
+            "orders_tenant_requests_total{tenant=\"acme\",route=\"GET /orders\",status=\"2xx\"} 2\n"
+        ));
+        assert!(text
+            .contains("orders_api_key_requests_total{api_key=\"bootstrap\",status=\"5xx\"} 1\n"));
+        assert!(!text.contains("trace_id"));
+
+        let open = metrics.openmetrics();
+        assert!(open.contains("# TYPE orders_tenant_requests counter"));
+        assert!(open.contains(
+            "orders_tenant_requests_total{tenant=\"acme\",route=\"GET /orders\",status=\"2xx\"} 2 # {trace_id=\"req-2\"} 1 "
+        ));
+        assert!(open
+            .contains("orders_api_key_requests_total{api_key=\"bootstrap\",status=\"5xx\"} 1\n"));
+    }
+
+    #[test]
+    fn order_values_fill_cumulative_buckets() {
+        let metrics = RequestMetrics::new();
+        metrics.observe_order(&order(700), "small");
+        metrics.observe_order(&order(4_000), "medium");
+        metrics.observe_order(&order(900_000), "huge");
+
+        let open = metrics.openmetrics();
+        let labels = "tenant=\"default\",currency=\"USD\"";
+        for (le, count) in [("1000", 1), ("5000", 2), ("500000", 2), ("+Inf", 3)] {
+            assert!(
+                open.contains(&format!(
+                    "orders_order_value_minor_units_bucket{{{labels},le=\"{le}\"}} {count}"
+                )),
+                "{le}: {open}"
+            );
+        }
+        assert!(open.contains("le=\"5000\"} 2 # {trace_id=\"medium\"} 4000 "));
+        assert!(open.contains("le=\"+Inf\"} 3 # {trace_id=\"huge\"} 900000 "));
+        assert!(open.contains(&format!(
+            "orders_order_value_minor_units_sum{{{labels}}} 904700"
+        )));
+        assert!(open.contains(&format!(
+            "orders_order_value_minor_units_count{{{labels}}} 3"
+        )));
+    }
+}
@@ -1,22 +1,76 @@
 use axum::{
-    extract::State,
-    routing::{delete, get, patch, post},
+    extract::{rejection::QueryRejection, DefaultBodyLimit, State},
+    routing::{delete, get, patch, post, put},
     serve, Json, Router,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::net::SocketAddr;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tower_http::trace::TraceLayer;
 use uuid::Uuid;
 
-use crate::application::order_service::OrderService;
+use super::auth::{admin_router, attribute, require_api_key};
+use super::body_log::{log_bodies, BodyLogger};
+use super::correlation::correlate;
+use super::dlq::dlq_router;
+use super::etag::{json_with_body_etag, json_with_etag, ETag};
+use super::jobs::jobs_router;
+use super::json::JsonBody;
+use super::listener::Listener;
+#[cfg(unix)]
+use super::listener::{bind_unix, take_systemd, Bound};
+use super::negotiate::negotiate;
+use super::ops::{ops_router, Ops};
+use super::rate_limit::{rate_limit, RateLimiter};
+use super::request_metrics::{track_requests, RequestMetrics};
+use super::slo::{metrics_router, slo_router, track_slo, SloTracker};
+use super::tenant::{resolve_tenant, Tenant, TenantResolver};
+use super::tls::TlsConfig;
+use super::versioning::{LegacyRoutes, V1};
+use super::webhooks::webhook_router;
+use crate::application::api_key_service::ApiKeyService;
+use crate::application::auth::{self, OrderAction};
+use crate::application::dead_letters::DeadLetterService;
+use crate::application::health::ReadinessReport;
+use crate::application::order_service::{
+    CustomerExport, ErasureReport, FulfillmentOutcome, NewOrder, OrderService, Placed,
+    RepriceOutcome,
+};
+use crate::application::priority::{CallerClass, ClassStats};
+use crate::application::scheduler::JobBoard;
+use crate::application::webhook_service::WebhookService;
 use crate::errors::AppError;
+use orders_types::domain::address::Address;
+use orders_types::domain::audit::AuditEntry;
+use orders_types::domain::correlation::{CorrelationId, RequestId};
+use orders_types::domain::discount::{Discount, DiscountKind};
+use orders_types::domain::filter::OrderFilter;
+use orders_types::domain::fulfillment::{FulfilledItem, Fulfillment};
+use orders_types::domain::history::OrderHistoryEntry;
+use orders_types::domain::integrity::{IntegrityReport, StatusMapping};
+use orders_types::domain::money::Money;
 use orders_types::domain::order::{OrderItem, OrderStatus};
+use orders_types::domain::order_number::OrderNumber;
+use orders_types::domain::order_patch::OrderPatch;
+use orders_types::domain::share::ShareToken;
+use orders_types::domain::stats::{OrderStats, StatsRange};
+use orders_types::domain::tenant::TenantId;
+use orders_types::ports::metrics::MetricsSource;
+use orders_types::ports::migrations::MigrationSource;
+use tokio::sync::broadcast;
 
 #[derive(Clone)]
 pub struct HttpServerConfig {
     pub port: String,
+    /// Serve HTTPS instead of plain HTTP.
+    pub tls: Option<TlsConfig>,
+    /// Internal address (e.g. `127.0.0.1:9090`) for probes, metrics and the
+    /// [operational endpoints](super::ops); they are then no longer served
+    /// on `port`. Without it probes and metrics stay public and the rest is
+    /// off.
+    pub admin_addr: Option<SocketAddr>,
 }
 
 #[derive(Clone)]
@@ -26,6 +80,26 @@ where
 {
     pub service: Arc<OrderService<R>>,
     pub config: HttpServerConfig,
+    rate_limiter: Option<RateLimiter>,
+    body_logger: Option<BodyLogger>,
+    api_keys: Option<Arc<ApiKeyService>>,
+    webhooks: Option<Arc<WebhookService>>,
+    jobs: Option<JobBoard>,
+    dead_letters: Option<DeadLetterService>,
+    slo: Option<SloTracker>,
+    request_metrics: Option<RequestMetrics>,
+    metrics: Vec<Arc<dyn MetricsSource>>,
+    tenants: TenantResolver,
+    legacy_sunset: Option<chrono::DateTime<chrono::Utc>>,
+    listener: Listener,
+    started: Instant,
+    config_dump: Option<Arc<serde_json::Value>>,
+    migrations: Option<Arc<dyn MigrationSource>>,
+    /// Largest request body JSON handlers read; axum's 2 MiB when unset.
+    body_limit: Option<usize>,
+    /// `Some(graphiql)` mounts `/graphql`, with the IDE when `graphiql`.
+    #[cfg(feature = "graphql")]
+    graphql: Option<bool>,
 }
 
 #[derive(Deserialize)]
@@ -33,16 +107,124 @@ pub struct CreateOrderRequest {
     pub customer_name: String,
     pub email: String,
     pub items: Vec<OrderItem>,
+    #[serde(default)]
+    pub discount_code: Option<String>,
+    #[serde(default)]
+    pub shipping_address: Option<Address>,
+    #[serde(default)]
+    pub billing_address: Option<Address>,
+    /// Key/value references of the caller's own, e.g. an ERP id.
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+}
+
+/// Items to replace or add to a pending order's items with.
+#[derive(Deserialize)]
+pub struct ItemsRequest {
+    pub items: Vec<OrderItem>,
+}
+
+#[derive(Deserialize)]
+pub struct CancelOrderRequest {
+    pub reason: String,
+}
+
+#[derive(Deserialize)]
+pub struct CreateFulfillmentRequest {
+    pub items: Vec<FulfilledItem>,
+    pub carrier: String,
+    pub tracking_number: String,
+    /// Now when absent.
+    #[serde(default)]
+    pub shipped_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// A new discount code; see [`Discount`] for the rules.
+#[derive(Deserialize)]
+pub struct CreateDiscountRequest {
+    pub code: String,
+    pub kind: DiscountKind,
+    #[serde(default)]
+    pub min_order: Option<Money>,
+    #[serde(default)]
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub max_uses: Option<u32>,
 }
 
 #[derive(Deserialize)]
 pub struct UpdateStatusRequest {
     pub status: OrderStatus,
+    /// Kept with the change in the status history.
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// Body of `POST /admin/orders/{id}/force-status`.
+#[derive(Deserialize)]
+pub struct ForceStatusRequest {
+    pub status: OrderStatus,
+    /// Why the override was needed; kept in the audit log.
+    pub reason: String,
+}
+
+#[derive(Deserialize, Default)]
+pub struct IntegrityFixRequest {
+    /// Extra legacy status translations on top of the configured ones.
+    #[serde(default)]
+    pub mapping: StatusMapping,
+}
+
+/// Paging for `GET /admin/audit`.
+#[derive(Deserialize, Default)]
+pub struct AuditQuery {
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+/// Entries per audit page when the caller doesn't say.
+const DEFAULT_AUDIT_PAGE: usize = 100;
+/// Most entries returned by one audit page.
+const MAX_AUDIT_PAGE: usize = 1000;
+
+/// Query of `GET /orders/{id}`: the share-link parameters, all absent for
+/// normal reads, and `include`.
+#[derive(Deserialize, Default)]
+pub struct OrderQuery {
+    pub exp: Option<i64>,
+    pub sig: Option<String>,
+    pub tenant: Option<String>,
+    /// Comma-separated extras to embed; `history` adds the status history.
+    pub include: Option<String>,
+}
+
+/// An order with the extras asked for through `?include=`.
+#[derive(Serialize)]
+pub struct OrderView {
+    #[serde(flatten)]
+    pub order: orders_types::domain::order::Order,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub history: Option<Vec<OrderHistoryEntry>>,
+}
+
+#[derive(Deserialize, Default)]
+pub struct ShareOrderRequest {
+    /// Link lifetime; defaults to a day, capped by the configured maximum.
+    pub ttl_secs: Option<i64>,
+}
+
+#[derive(Serialize)]
+struct ShareOrderResponse {
+    /// Path and query to append to the public base URL.
+    path: String,
+    exp: i64,
 }
 
 #[derive(Serialize)]
 struct CreateOrderResponse {
     id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    order_number: Option<OrderNumber>,
     status: OrderStatus,
 }
 
@@ -50,6 +232,7 @@ impl From<orders_types::domain::order::Order> for CreateOrderResponse {
     fn from(o: orders_types::domain::order::Order) -> Self {
         Self {
             id: o.id.to_string(),
+            order_number: o.order_number,
             status: o.status,
         }
     }
@@ -63,16 +246,306 @@ where
         Ok(Self {
             service: Arc::new(service),
             config,
+            rate_limiter: None,
+            body_logger: None,
+            jobs: None,
+            dead_letters: None,
+            api_keys: None,
+            webhooks: None,
+            slo: None,
+            request_metrics: None,
+            metrics: Vec::new(),
+            tenants: TenantResolver::default(),
+            legacy_sunset: None,
+            listener: Listener::Tcp,
+            started: Instant::now(),
+            config_dump: None,
+            migrations: None,
+            body_limit: None,
+            #[cfg(feature = "graphql")]
+            graphql: None,
         })
     }
 
+    /// Answer JSON bodies over `bytes` with a 413. Streamed uploads such as
+    /// `/orders/import` are not buffered and so not limited.
+    pub fn with_body_limit(mut self, bytes: usize) -> Self {
+        self.body_limit = Some(bytes);
+        self
+    }
+
+    /// Throttle every route through `limiter`; over-quota callers get a 429.
+    pub fn with_rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Log sampled, redacted `/orders` request and response bodies at debug
+    /// level (target `http_body`).
+    pub fn with_body_logger(mut self, logger: BodyLogger) -> Self {
+        self.body_logger = Some(logger);
+        self
+    }
+
+    /// Require an `X-Api-Key` on every route but the probes and `/metrics`
+    /// and mount the `/admin/api-keys` management routes.
+    pub fn with_api_keys(mut self, keys: ApiKeyService) -> Self {
+        self.api_keys = Some(Arc::new(keys));
+        self
+    }
+
+    /// Mount `POST /admin/webhooks/{id}/test` for the configured targets.
+    pub fn with_webhooks(mut self, hooks: WebhookService) -> Self {
+        self.webhooks = Some(Arc::new(hooks));
+        self
+    }
+
+    /// Mount `GET /admin/jobs`, the status of the scheduled jobs on `board`.
+    pub fn with_jobs(mut self, board: JobBoard) -> Self {
+        self.jobs = Some(board);
+        self
+    }
+
+    /// Mount `GET /admin/dlq` and `POST /admin/dlq/{id}/replay`, and add
+    /// the `orders_dlq_depth` gauge to `GET /metrics`.
+    pub fn with_dead_letters(mut self, letters: DeadLetterService) -> Self {
+        self.metrics.push(Arc::new(letters.clone()));
+        self.dead_letters = Some(letters);
+        self
+    }
+
+    /// Count requests per route against `tracker`'s objectives and mount
+    /// `GET /admin/slo` and `GET /metrics`.
+    pub fn with_slo(mut self, tracker: SloTracker) -> Self {
+        self.slo = Some(tracker);
+        self
+    }
+
+    /// Count API requests per tenant and per API key, and new orders' values
+    /// per tenant, in `metrics`, and add them to `GET /metrics`.
+    pub fn with_request_metrics(mut self, metrics: RequestMetrics) -> Self {
+        self.metrics.push(Arc::new(metrics.clone()));
+        self.request_metrics = Some(metrics);
+        self
+    }
+
+    /// Add `source`'s series to `GET /metrics`, which [`Self::with_slo`]
+    /// mounts.
+    pub fn with_metrics(mut self, source: impl MetricsSource) -> Self {
+        self.metrics.push(Arc::new(source));
+        self
+    }
+
+    /// Take the tenant from the `tenant_id` claim of HS256 bearer tokens
+    /// signed with `secret`, in preference to the `X-Tenant-Id` header.
+    pub fn with_tenant_jwt_secret(mut self, secret: &[u8]) -> Self {
+        self.tenants = self.tenants.with_jwt_secret(secret);
+        self
+    }
+
+    /// Announce `sunset` as the retirement date of the unversioned paths
+    /// that alias the `/v1` routes.
+    pub fn with_legacy_sunset(mut self, sunset: chrono::DateTime<chrono::Utc>) -> Self {
+        self.legacy_sunset = Some(sunset);
+        self
+    }
+
+    /// Accept API connections on `listener` instead of the TCP port.
+    pub fn with_listener(mut self, listener: Listener) -> Self {
+        self.listener = listener;
+        self
+    }
+
+    /// Show `config` at `GET /config` on the admin listener. Secrets should
+    /// be redacted first.
+    pub fn with_config_dump(mut self, config: serde_json::Value) -> Self {
+        self.config_dump = Some(Arc::new(config));
+        self
+    }
+
+    /// Report the schema state at `GET /migrations` on the admin listener.
+    pub fn with_migrations(mut self, source: impl MigrationSource) -> Self {
+        self.migrations = Some(Arc::new(source));
+        self
+    }
+
+    /// Mount the GraphQL API at `POST /graphql`, and the GraphiQL IDE at
+    /// `GET /graphql` when `graphiql` is set (meant for development).
+    #[cfg(feature = "graphql")]
+    pub fn with_graphql(mut self, graphiql: bool) -> Self {
+        self.graphql = Some(graphiql);
+        self
+    }
+
+    /// Everything served under `/v1`, against [`Self::service`] with the v1
+    /// request and response types. Probes and metrics are unversioned.
+    fn v1_router(&self) -> Router<Arc<OrderService<R>>> {
+        let svc = self.service.clone();
+        // API calls someone is waiting on; imports and integrity passes admit
+        // themselves as background work inside the service.
+        let interactive = Router::new()
+            .route("/orders", post(create_order::<R>))
+            .route("/orders", get(list_orders::<R>))
+            .route("/orders/stats", get(order_stats::<R>))
+            .route(
+                "/orders/{id}",
+                get(get_order::<R>)
+                    .head(order_exists::<R>)
+                    .patch(patch_order::<R>),
+            )
+            .route("/orders/{id}/status", patch(update_status::<R>))
+            .route(
+                "/orders/{id}/items",
+                put(replace_items::<R>).post(append_items::<R>),
+            )
+            .route("/orders/{id}", delete(delete_order::<R>))
+            .route("/orders/{id}/cancel", post(cancel_order::<R>))
+            .route("/orders/{id}/reprice", post(reprice_order::<R>))
+            .route("/orders/{id}/share", post(share_order::<R>))
+            .route(
+                "/orders/{id}/fulfillments",
+                get(order_fulfillments::<R>).post(fulfill_order::<R>),
+            )
+            .route("/orders/{id}/history", get(order_history::<R>))
+            .route("/orders/{id}/audit", get(order_audit::<R>));
+        #[cfg(feature = "graphql")]
+        let interactive = match self.graphql {
+            Some(graphiql) => interactive.merge(crate::inbound::graphql::graphql_router(
+                svc.clone(),
+                graphiql,
+            )),
+            None => interactive,
+        };
+        let interactive = interactive.route_layer(axum::middleware::from_fn_with_state(
+            svc,
+            admit_interactive::<R>,
+        ));
+        Router::new()
+            .route("/ws", get(super::ws::ws_handler::<R>))
+            .route("/orders/import", post(super::import::import_orders::<R>))
+            .route(
+                "/admin/integrity",
+                get(integrity_report::<R>).post(integrity_fix::<R>),
+            )
+            .route("/admin/priority", get(priority_stats::<R>))
+            .route("/admin/audit", get(list_audit::<R>))
+            .route("/admin/orders/{id}/force-status", post(force_status::<R>))
+            .route("/customers/{email}/export", get(export_customer::<R>))
+            .route("/customers/{email}/data", delete(erase_customer::<R>))
+            .route(
+                "/admin/discounts",
+                get(list_discounts::<R>).post(create_discount::<R>),
+            )
+            .route("/admin/discounts/{code}", delete(delete_discount::<R>))
+            .merge(interactive)
+    }
+
     pub async fn run(self) -> anyhow::Result<()> {
+        let (listener, config) = (self.listener.clone(), self.config.clone());
+        let (app, admin) = self.into_routers();
+        let _admin = spawn_admin(admin, None).await?;
+
+        #[cfg(unix)]
+        if listener != Listener::Tcp {
+            anyhow::ensure!(
+                config.tls.is_none(),
+                "TLS is only supported on the TCP listener"
+            );
+            let bound = match &listener {
+                Listener::Unix(path) => {
+                    tracing::info!("starting server on unix:{}", path.display());
+                    bind_unix(path)?
+                }
+                _ => {
+                    tracing::info!("starting server on a systemd socket");
+                    take_systemd()?
+                }
+            };
+            match bound {
+                Bound::Tcp(listener) => {
+                    let app = app.into_make_service_with_connect_info::<SocketAddr>();
+                    serve(listener, app).await?
+                }
+                // No peer address, so rate limits there should key on a
+                // header the proxy sets.
+                Bound::Unix(listener) => serve(listener, app).await?,
+            }
+            return Ok(());
+        }
+
+        let addr: SocketAddr = format!("0.0.0.0:{}", config.port).parse()?;
+        let app = app.into_make_service_with_connect_info::<SocketAddr>();
+        if let Some(tls) = config.tls {
+            let rustls = tls.load().await?;
+            tls.reload_on_sighup(rustls.clone());
+            tracing::info!("starting server on {} (tls)", addr);
+            axum_server::bind_rustls(addr, rustls).serve(app).await?;
+            return Ok(());
+        }
+        tracing::info!("starting server on {}", addr);
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        serve(listener, app).await?;
+        Ok(())
+    }
+
+    /// Serve on `listener`, already bound, until `shutdown` resolves, then
+    /// let in-flight requests finish. The port and
+    /// [`with_listener`](Self::with_listener) are ignored; TLS is set up as
+    /// in [`run`](Self::run). With [`HttpServerConfig::admin_addr`] set, the
+    /// admin routes are served on `admin` when given, also already bound,
+    /// and on that address otherwise. Binding first means callers can
+    /// connect as soon as this is spawned.
+    pub async fn serve_on(
+        self,
+        listener: tokio::net::TcpListener,
+        admin: Option<tokio::net::TcpListener>,
+        shutdown: impl std::future::Future<Output = ()> + Send + 'static,
+    ) -> anyhow::Result<()> {
+        let tls = self.config.tls.clone();
+        let (app, admin_router) = self.into_routers();
+        let _admin = spawn_admin(admin_router, admin).await?;
+        let app = app.into_make_service_with_connect_info::<SocketAddr>();
+        tracing::info!("starting server on {}", listener.local_addr()?);
+        if let Some(tls) = tls {
+            let rustls = tls.load().await?;
+            tls.reload_on_sighup(rustls.clone());
+            let handle = axum_server::Handle::new();
+            let stop = handle.clone();
+            tokio::spawn(async move {
+                shutdown.await;
+                stop.graceful_shutdown(None);
+            });
+            axum_server::from_tcp_rustls(listener.into_std()?, rustls)?
+                .handle(handle)
+                .serve(app)
+                .await?;
+            return Ok(());
+        }
+        serve(listener, app)
+            .with_graceful_shutdown(shutdown)
+            .await?;
+        Ok(())
+    }
+
+    /// The public API and, when [`HttpServerConfig::admin_addr`] is set, the
+    /// admin router with its address.
+    fn into_routers(self) -> (Router, Option<(SocketAddr, Router)>) {
         let trace_layer = TraceLayer::new_for_http()
             .make_span_with(|request: &axum::extract::Request<_>| {
                 let uri = request.uri().to_string();
-                let request_id = Uuid::new_v4();
+                let correlation_id = request
+                    .extensions()
+                    .get::<CorrelationId>()
+                    .map(ToString::to_string)
+                    .unwrap_or_default();
+                let request_id = request
+                    .extensions()
+                    .get::<RequestId>()
+                    .map(ToString::to_string)
+                    .unwrap_or_default();
                 tracing::info_span!(
                     "http_request",
+                    %correlation_id,
                     %request_id,
                     method = %request.method(),
                     uri
@@ -99,25 +572,133 @@ where
                 },
             );
 
-        let svc = self.service.clone();
-        let app = Router::new()
+        let legacy = LegacyRoutes {
+            sunset: self.legacy_sunset,
+        };
+        // Probes and metrics; on the admin listener when there is one.
+        let mut ops = Router::new()
             .route("/health", get(health))
-            .route("/orders", post(create_order::<R>))
-            .route("/orders", get(list_orders::<R>))
-            .route("/orders/{id}", get(get_order::<R>))
-            .route("/orders/{id}/status", patch(update_status::<R>))
-            .route("/orders/{id}", delete(delete_order::<R>))
+            .route("/healthz", get(health))
+            .route("/readyz", get(ready::<R>))
+            .with_state(self.service.clone());
+        if let Some(tracker) = &self.slo {
+            ops = ops.merge(metrics_router(tracker.clone(), self.metrics.clone()));
+        }
+        let (ops, admin) = match self.config.admin_addr {
+            Some(addr) => {
+                let admin = ops.merge(ops_router(Ops {
+                    started: self.started,
+                    config: self.config_dump.clone(),
+                    migrations: self.migrations.clone(),
+                }));
+                (Router::new(), Some((addr, admin)))
+            }
+            None => (ops, None),
+        };
+        let mut api = legacy.mount(self.v1_router());
+        if let Some(metrics) = self.request_metrics {
+            // Order values come from the events, which carry the
+            // correlation id of the request that placed the order.
+            let mut events = self.service.subscribe();
+            let values = metrics.clone();
+            tokio::spawn(async move {
+                loop {
+                    match events.recv().await {
+                        Ok(envelope) => values.observe(&envelope),
+                        Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    }
+                }
+            });
+            // Inside tenant resolution, so the tenant is known.
+            api = api.layer(axum::middleware::from_fn_with_state(
+                metrics,
+                track_requests,
+            ));
+        }
+        let mut app = api
+            .with_state(self.service.clone())
+            .layer(axum::middleware::from_fn_with_state(
+                self.tenants,
+                resolve_tenant,
+            ))
+            .merge(ops);
+        if let Some(hooks) = self.webhooks {
+            app = app.merge(legacy.mount(webhook_router(hooks)));
+        }
+        if let Some(board) = self.jobs {
+            app = app.merge(legacy.mount(jobs_router(board)));
+        }
+        if let Some(letters) = self.dead_letters {
+            app = app.merge(legacy.mount(dlq_router(letters)));
+        }
+        if let Some(tracker) = &self.slo {
+            app = app.merge(legacy.mount(slo_router(tracker.clone())));
+        }
+        // Inside the key check, so the caller is known by the time it runs.
+        app = app.layer(axum::middleware::from_fn(attribute));
+        if let Some(keys) = self.api_keys {
+            app = app
+                .merge(legacy.mount(admin_router(keys.clone())))
+                .layer(axum::middleware::from_fn_with_state(keys, require_api_key));
+        }
+        if let Some(bytes) = self.body_limit {
+            app = app.layer(DefaultBodyLimit::max(bytes));
+        }
+        if let Some(tracker) = self.slo {
+            app = app.layer(axum::middleware::from_fn_with_state(tracker, track_slo));
+        }
+        if let Some(limiter) = self.rate_limiter {
+            app = app.layer(axum::middleware::from_fn_with_state(limiter, rate_limit));
+        }
+        if let Some(logger) = self.body_logger {
+            app = app.layer(axum::middleware::from_fn_with_state(logger, log_bodies));
+        }
+        // Outside body logging, which can then always show JSON.
+        app = app.layer(axum::middleware::from_fn(negotiate));
+        // Outermost, so the trace span and everything below see the id.
+        let app = app
             .layer(trace_layer)
-            .with_state(svc);
+            .layer(axum::middleware::from_fn(correlate));
 
-        let addr: SocketAddr = format!("0.0.0.0:{}", self.config.port).parse()?;
-        tracing::info!("starting server on {}", addr);
-        let listener = tokio::net::TcpListener::bind(addr).await?;
-        serve(listener, app.into_make_service()).await?;
-        Ok(())
+        (app, admin)
+    }
+}
+
+/// Serve the admin router, if there is one, in the background until the
+/// returned guard is dropped: on `bound` when given, or else bound to its
+/// address.
+async fn spawn_admin(
+    admin: Option<(SocketAddr, Router)>,
+    bound: Option<tokio::net::TcpListener>,
+) -> anyhow::Result<Option<AbortOnDrop>> {
+    let Some((addr, admin)) = admin else {
+        return Ok(None);
+    };
+    let listener = match bound {
+        Some(listener) => listener,
+        None => tokio::net::TcpListener::bind(addr).await?,
+    };
+    tracing::info!("starting admin listener on {}", listener.local_addr()?);
+    let task = tokio::spawn(async move {
+        if let Err(e) = serve(listener, admin).await {
+            tracing::error!(error = %e, "admin listener stopped");
+        }
+    });
+    Ok(Some(AbortOnDrop(task)))
+}
+
+/// Stops the admin listener along with the server it belongs to.
+struct AbortOnDrop(tokio::task::JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
     }
 }
 
+/// Liveness: the process is up and serving. Deliberately checks nothing else
+/// so a slow database never gets the pod restarted.
 async fn health() -> (axum::http::StatusCode, Json<serde_json::Value>) {
     (
         axum::http::StatusCode::OK,
@@ -125,66 +706,496 @@ async fn health() -> (axum::http::StatusCode, Json<serde_json::Value>) {
     )
 }
 
+/// Hold an interactive slot for the whole request when a priority gate is
+/// configured.
+async fn admit_interactive<R>(
+    State(service): State<Arc<OrderService<R>>>,
+    req: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> axum::response::Response
+where
+    R: orders_types::ports::order_repository::OrderRepository + Send + Sync + 'static,
+{
+    let _admission = service.admit(CallerClass::Interactive).await;
+    next.run(req).await
+}
+
+/// Readiness: `200` when every required dependency answers, `503` otherwise;
+/// the body lists each check either way.
+async fn ready<R>(
+    State(service): State<Arc<OrderService<R>>>,
+) -> (axum::http::StatusCode, Json<ReadinessReport>)
+where
+    R: orders_types::ports::order_repository::OrderRepository + Send + Sync + 'static,
+{
+    let report = service.readiness().await;
+    let status = if report.ready {
+        axum::http::StatusCode::OK
+    } else {
+        axum::http::StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(report))
+}
+
 async fn create_order<R>(
     State(service): State<Arc<OrderService<R>>>,
-    Json(payload): Json<CreateOrderRequest>,
+    Tenant(tenant): Tenant,
+    JsonBody(payload): JsonBody<CreateOrderRequest>,
 ) -> Result<(axum::http::StatusCode, Json<CreateOrderResponse>), AppError>
 where
     R: crate::ports::order_repository::OrderRepository + Send + Sync + 'static,
 {
-    let order = service
-        .create_order(payload.customer_name, payload.email, payload.items)
+    let placed = service
+        .place_or_find_order(
+            &tenant,
+            NewOrder {
+                customer_name: payload.customer_name,
+                email: payload.email,
+                items: payload.items,
+                discount_code: payload.discount_code,
+                shipping_address: payload.shipping_address,
+                billing_address: payload.billing_address,
+                metadata: payload.metadata,
+            },
+        )
         .await?;
+    // A double submit gets the order the first one created.
+    let (status, order) = match placed {
+        Placed::Created(order) => (axum::http::StatusCode::CREATED, order),
+        Placed::Duplicate(order) => (axum::http::StatusCode::OK, order),
+    };
     let body: CreateOrderResponse = order.into();
-    Ok((axum::http::StatusCode::CREATED, Json(body)))
+    Ok((status, Json(body)))
 }
 
 async fn get_order<R>(
     State(service): State<Arc<OrderService<R>>>,
+    Tenant(tenant): Tenant,
     axum::extract::Path(id): axum::extract::Path<String>,
-) -> Result<Json<orders_types::domain::order::Order>, AppError>
+    axum::extract::Query(query): axum::extract::Query<OrderQuery>,
+    headers: axum::http::HeaderMap,
+) -> Result<axum::response::Response, AppError>
+where
+    R: orders_types::ports::order_repository::OrderRepository + Send + Sync + 'static,
+{
+    if let Some(sig) = query.sig {
+        let uuid = Uuid::parse_str(&id).map_err(|e| AppError::BadRequest(e.to_string()))?;
+        // A share link authorizes itself and names its own tenant.
+        let exp = query
+            .exp
+            .ok_or_else(|| AppError::BadRequest("share link missing `exp`".into()))?;
+        let tenant = query
+            .tenant
+            .map(|t| TenantId::parse(&t).map_err(AppError::BadRequest))
+            .transpose()?;
+        let token = ShareToken { exp, sig, tenant };
+        let order = service.get_shared_order(uuid, &token).await?;
+        let etag = ETag::for_order(&order);
+        let view = OrderView {
+            order,
+            history: None,
+        };
+        return Ok(json_with_etag(&headers, &etag, &view));
+    }
+    // Either the order's id or its number.
+    let order = service.get_order_by_ref(&tenant, &id).await?;
+    let wants_history = query
+        .include
+        .as_deref()
+        .is_some_and(|i| i.split(',').any(|part| part.trim() == "history"));
+    let history = if wants_history {
+        Some(service.order_history(&tenant, order.id).await?)
+    } else {
+        None
+    };
+    let etag = ETag::for_order(&order);
+    Ok(json_with_etag(
+        &headers,
+        &etag,
+        &OrderView { order, history },
+    ))
+}
+
+/// The order's status changes, oldest first.
+async fn order_history<R>(
+    State(service): State<Arc<OrderService<R>>>,
+    Tenant(tenant): Tenant,
+    axum::extract::Path(id): axum::extract::Path<String>,
+) -> Result<Json<Vec<OrderHistoryEntry>>, AppError>
 where
     R: orders_types::ports::order_repository::OrderRepository + Send + Sync + 'static,
 {
     let uuid = Uuid::parse_str(&id).map_err(|e| AppError::BadRequest(e.to_string()))?;
-    let order = service.get_order(uuid).await?;
-    Ok(Json(order))
+    Ok(Json(service.order_history(&tenant, uuid).await?))
+}
+
+/// Mint a signed, expiring read-only link to an order.
+async fn share_order<R>(
+    State(service): State<Arc<OrderService<R>>>,
+    Tenant(tenant): Tenant,
+    axum::extract::Path(id): axum::extract::Path<String>,
+    payload: Option<Json<ShareOrderRequest>>,
+) -> Result<(axum::http::StatusCode, Json<ShareOrderResponse>), AppError>
+where
+    R: orders_types::ports::order_repository::OrderRepository + Send + Sync + 'static,
+{
+    let uuid = Uuid::parse_str(&id).map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let Json(payload) = payload.unwrap_or_default();
+    let ttl = payload
+        .ttl_secs
+        .map(|secs| {
+            chrono::Duration::try_seconds(secs)
+                .filter(|ttl| *ttl > chrono::Duration::zero())
+                .ok_or_else(|| AppError::BadRequest("ttl_secs must be positive".into()))
+        })
+        .transpose()?;
+    let token = service.share_order(&tenant, uuid, ttl).await?;
+    let mut path = format!("{V1}/orders/{}?exp={}&sig={}", uuid, token.exp, token.sig);
+    if let Some(tenant) = &token.tenant {
+        path.push_str(&format!("&tenant={tenant}"));
+    }
+    Ok((
+        axum::http::StatusCode::CREATED,
+        Json(ShareOrderResponse {
+            path,
+            exp: token.exp,
+        }),
+    ))
+}
+
+/// `HEAD /orders/{id}`: 200 or 404 with no body, without loading the order.
+async fn order_exists<R>(
+    State(service): State<Arc<OrderService<R>>>,
+    Tenant(tenant): Tenant,
+    axum::extract::Path(id): axum::extract::Path<String>,
+) -> Result<axum::http::StatusCode, AppError>
+where
+    R: orders_types::ports::order_repository::OrderRepository + Send + Sync + 'static,
+{
+    let uuid = Uuid::parse_str(&id).map_err(|e| AppError::BadRequest(e.to_string()))?;
+    if service.order_exists(&tenant, uuid).await? {
+        Ok(axum::http::StatusCode::OK)
+    } else {
+        Ok(axum::http::StatusCode::NOT_FOUND)
+    }
 }
 
 async fn list_orders<R>(
     State(service): State<Arc<OrderService<R>>>,
-) -> Result<Json<Vec<orders_types::domain::order::Order>>, AppError>
+    Tenant(tenant): Tenant,
+    filter: Result<axum::extract::Query<OrderFilter>, QueryRejection>,
+    params: Result<axum::extract::Query<Vec<(String, String)>>, QueryRejection>,
+    headers: axum::http::HeaderMap,
+) -> Result<axum::response::Response, AppError>
 where
     R: orders_types::ports::order_repository::OrderRepository + Send + Sync + 'static,
 {
-    let list = service.list_orders().await?;
-    Ok(Json(list))
+    // Names the allowed values when e.g. `sort` isn't one of them.
+    let axum::extract::Query(filter) = filter.map_err(|e| AppError::BadRequest(e.body_text()))?;
+    // `metadata.<key>` parameters don't fit serde's fixed field names.
+    let axum::extract::Query(params) = params.map_err(|e| AppError::BadRequest(e.body_text()))?;
+    let filter = filter.with_metadata_params(params);
+    let page = service.list_page(&tenant, &filter).await?;
+    Ok(json_with_body_etag(&headers, &page))
+}
+
+/// Counts, revenue and orders per day; `from`/`to` are inclusive UTC dates.
+async fn order_stats<R>(
+    State(service): State<Arc<OrderService<R>>>,
+    Tenant(tenant): Tenant,
+    range: Result<axum::extract::Query<StatsRange>, QueryRejection>,
+) -> Result<Json<OrderStats>, AppError>
+where
+    R: orders_types::ports::order_repository::OrderRepository + Send + Sync + 'static,
+{
+    let axum::extract::Query(range) = range.map_err(|e| AppError::BadRequest(e.body_text()))?;
+    Ok(Json(service.order_stats(&tenant, &range).await?))
 }
 
 async fn update_status<R>(
     State(service): State<Arc<OrderService<R>>>,
+    Tenant(tenant): Tenant,
+    axum::extract::Path(id): axum::extract::Path<String>,
+    JsonBody(payload): JsonBody<UpdateStatusRequest>,
+) -> Result<Json<orders_types::domain::order::Order>, AppError>
+where
+    R: orders_types::ports::order_repository::OrderRepository + Send + Sync + 'static,
+{
+    let uuid = Uuid::parse_str(&id).map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let updated = service
+        .update_status_with_note(&tenant, uuid, payload.status, payload.note.as_deref())
+        .await?;
+    Ok(Json(updated))
+}
+
+async fn cancel_order<R>(
+    State(service): State<Arc<OrderService<R>>>,
+    Tenant(tenant): Tenant,
+    axum::extract::Path(id): axum::extract::Path<String>,
+    JsonBody(payload): JsonBody<CancelOrderRequest>,
+) -> Result<Json<orders_types::domain::order::Order>, AppError>
+where
+    R: orders_types::ports::order_repository::OrderRepository + Send + Sync + 'static,
+{
+    let uuid = Uuid::parse_str(&id).map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let cancelled = service.cancel_order(&tenant, uuid, &payload.reason).await?;
+    Ok(Json(cancelled))
+}
+
+/// Record a shipment; the order is `Shipped` once all of it has gone out.
+async fn fulfill_order<R>(
+    State(service): State<Arc<OrderService<R>>>,
+    Tenant(tenant): Tenant,
+    axum::extract::Path(id): axum::extract::Path<String>,
+    JsonBody(payload): JsonBody<CreateFulfillmentRequest>,
+) -> Result<(axum::http::StatusCode, Json<FulfillmentOutcome>), AppError>
+where
+    R: orders_types::ports::order_repository::OrderRepository + Send + Sync + 'static,
+{
+    let uuid = Uuid::parse_str(&id).map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let fulfillment = Fulfillment::new(
+        payload.items,
+        payload.carrier,
+        payload.tracking_number,
+        payload.shipped_at.unwrap_or_else(chrono::Utc::now),
+    );
+    let outcome = service.fulfill_order(&tenant, uuid, fulfillment).await?;
+    Ok((axum::http::StatusCode::CREATED, Json(outcome)))
+}
+
+/// The order's shipments, oldest first.
+async fn order_fulfillments<R>(
+    State(service): State<Arc<OrderService<R>>>,
+    Tenant(tenant): Tenant,
+    axum::extract::Path(id): axum::extract::Path<String>,
+) -> Result<Json<Vec<Fulfillment>>, AppError>
+where
+    R: orders_types::ports::order_repository::OrderRepository + Send + Sync + 'static,
+{
+    let uuid = Uuid::parse_str(&id).map_err(|e| AppError::BadRequest(e.to_string()))?;
+    Ok(Json(service.order_fulfillments(&tenant, uuid).await?))
+}
+
+/// Change the customer details and addresses of an order that hasn't
+/// shipped, with a JSON Merge Patch.
+async fn patch_order<R>(
+    State(service): State<Arc<OrderService<R>>>,
+    Tenant(tenant): Tenant,
+    axum::extract::Path(id): axum::extract::Path<String>,
+    JsonBody(patch): JsonBody<OrderPatch>,
+) -> Result<Json<orders_types::domain::order::Order>, AppError>
+where
+    R: orders_types::ports::order_repository::OrderRepository + Send + Sync + 'static,
+{
+    let uuid = Uuid::parse_str(&id).map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let updated = service.patch_order(&tenant, uuid, &patch).await?;
+    Ok(Json(updated))
+}
+
+/// Replace all items of a pending order.
+async fn replace_items<R>(
+    State(service): State<Arc<OrderService<R>>>,
+    Tenant(tenant): Tenant,
     axum::extract::Path(id): axum::extract::Path<String>,
-    Json(payload): Json<UpdateStatusRequest>,
+    JsonBody(payload): JsonBody<ItemsRequest>,
 ) -> Result<Json<orders_types::domain::order::Order>, AppError>
 where
     R: orders_types::ports::order_repository::OrderRepository + Send + Sync + 'static,
 {
     let uuid = Uuid::parse_str(&id).map_err(|e| AppError::BadRequest(e.to_string()))?;
-    let updated = service.update_status(uuid, payload.status).await?;
+    let updated = service.replace_items(&tenant, uuid, payload.items).await?;
     Ok(Json(updated))
 }
 
+/// Add items to a pending order.
+async fn append_items<R>(
+    State(service): State<Arc<OrderService<R>>>,
+    Tenant(tenant): Tenant,
+    axum::extract::Path(id): axum::extract::Path<String>,
+    JsonBody(payload): JsonBody<ItemsRequest>,
+) -> Result<Json<orders_types::domain::order::Order>, AppError>
+where
+    R: orders_types::ports::order_repository::OrderRepository + Send + Sync + 'static,
+{
+    let uuid = Uuid::parse_str(&id).map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let updated = service.append_items(&tenant, uuid, payload.items).await?;
+    Ok(Json(updated))
+}
+
+/// Admin: recompute frozen pricing against the current catalog rules.
+async fn reprice_order<R>(
+    State(service): State<Arc<OrderService<R>>>,
+    Tenant(tenant): Tenant,
+    axum::extract::Path(id): axum::extract::Path<String>,
+) -> Result<Json<RepriceOutcome>, AppError>
+where
+    R: orders_types::ports::order_repository::OrderRepository + Send + Sync + 'static,
+{
+    let uuid = Uuid::parse_str(&id).map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let outcome = service.reprice_order(&tenant, uuid).await?;
+    Ok(Json(outcome))
+}
+
 async fn delete_order<R>(
     State(service): State<Arc<OrderService<R>>>,
+    Tenant(tenant): Tenant,
     axum::extract::Path(id): axum::extract::Path<String>,
 ) -> Result<(axum::http::StatusCode, Json<serde_json::Value>), AppError>
 where
     R: orders_types::ports::order_repository::OrderRepository + Send + Sync + 'static,
 {
     let uuid = Uuid::parse_str(&id).map_err(|e| AppError::BadRequest(e.to_string()))?;
-    service.delete_order(uuid).await?;
+    service.delete_order(&tenant, uuid).await?;
     Ok((
         axum::http::StatusCode::NO_CONTENT,
         Json(serde_json::json!({})),
     ))
 }
+
+/// Every recorded change to one order, oldest first.
+async fn order_audit<R>(
+    State(service): State<Arc<OrderService<R>>>,
+    Tenant(tenant): Tenant,
+    axum::extract::Path(id): axum::extract::Path<String>,
+) -> Result<Json<Vec<AuditEntry>>, AppError>
+where
+    R: orders_types::ports::order_repository::OrderRepository + Send + Sync + 'static,
+{
+    let uuid = Uuid::parse_str(&id).map_err(|e| AppError::BadRequest(e.to_string()))?;
+    Ok(Json(service.order_audit(&tenant, uuid).await?))
+}
+
+/// Admin: one page of the tenant's audit log, newest first.
+async fn list_audit<R>(
+    State(service): State<Arc<OrderService<R>>>,
+    Tenant(tenant): Tenant,
+    axum::extract::Query(page): axum::extract::Query<AuditQuery>,
+) -> Result<Json<Vec<AuditEntry>>, AppError>
+where
+    R: orders_types::ports::order_repository::OrderRepository + Send + Sync + 'static,
+{
+    let limit = page.limit.unwrap_or(DEFAULT_AUDIT_PAGE).min(MAX_AUDIT_PAGE);
+    let entries = service
+        .list_audit(&tenant, limit, page.offset.unwrap_or(0))
+        .await?;
+    Ok(Json(entries))
+}
+
+/// Admin: set an order's status past the transition rules, with a reason.
+async fn force_status<R>(
+    State(service): State<Arc<OrderService<R>>>,
+    Tenant(tenant): Tenant,
+    axum::extract::Path(id): axum::extract::Path<String>,
+    JsonBody(payload): JsonBody<ForceStatusRequest>,
+) -> Result<Json<orders_types::domain::order::Order>, AppError>
+where
+    R: orders_types::ports::order_repository::OrderRepository + Send + Sync + 'static,
+{
+    let uuid = Uuid::parse_str(&id).map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let updated = service
+        .force_status(&tenant, uuid, payload.status, &payload.reason)
+        .await?;
+    Ok(Json(updated))
+}
+
+/// Admin: every order placed with an email, for a data subject access
+/// request. `Accept: text/csv` gets one row per order.
+async fn export_customer<R>(
+    State(service): State<Arc<OrderService<R>>>,
+    Tenant(tenant): Tenant,
+    axum::extract::Path(email): axum::extract::Path<String>,
+) -> Result<Json<CustomerExport>, AppError>
+where
+    R: orders_types::ports::order_repository::OrderRepository + Send + Sync + 'static,
+{
+    Ok(Json(service.export_customer(&tenant, &email).await?))
+}
+
+/// Admin: anonymize the personal data on every order placed with an email.
+async fn erase_customer<R>(
+    State(service): State<Arc<OrderService<R>>>,
+    Tenant(tenant): Tenant,
+    axum::extract::Path(email): axum::extract::Path<String>,
+) -> Result<Json<ErasureReport>, AppError>
+where
+    R: orders_types::ports::order_repository::OrderRepository + Send + Sync + 'static,
+{
+    Ok(Json(service.erase_customer(&tenant, &email).await?))
+}
+
+/// Admin: report unknown statuses and undecodable rows without changing them.
+async fn integrity_report<R>(
+    State(service): State<Arc<OrderService<R>>>,
+) -> Result<Json<IntegrityReport>, AppError>
+where
+    R: orders_types::ports::order_repository::OrderRepository + Send + Sync + 'static,
+{
+    let report = service
+        .check_integrity(false, StatusMapping::default())
+        .await?;
+    Ok(Json(report))
+}
+
+/// Admin: queue metrics per caller class; empty without a priority gate.
+async fn priority_stats<R>(
+    State(service): State<Arc<OrderService<R>>>,
+) -> Result<Json<Vec<ClassStats>>, AppError>
+where
+    R: orders_types::ports::order_repository::OrderRepository + Send + Sync + 'static,
+{
+    auth::authorize(OrderAction::Maintain)?;
+    Ok(Json(service.priority_stats()))
+}
+
+/// Admin: mint a discount code for the caller's tenant.
+async fn create_discount<R>(
+    State(service): State<Arc<OrderService<R>>>,
+    Tenant(tenant): Tenant,
+    JsonBody(payload): JsonBody<CreateDiscountRequest>,
+) -> Result<(axum::http::StatusCode, Json<Discount>), AppError>
+where
+    R: orders_types::ports::order_repository::OrderRepository + Send + Sync + 'static,
+{
+    let mut discount = Discount::new(&payload.code, payload.kind);
+    discount.min_order = payload.min_order;
+    discount.expires_at = payload.expires_at;
+    discount.max_uses = payload.max_uses;
+    let created = service.create_discount(&tenant, discount).await?;
+    Ok((axum::http::StatusCode::CREATED, Json(created)))
+}
+
+/// Admin: the tenant's discount codes with their use counts.
+async fn list_discounts<R>(
+    State(service): State<Arc<OrderService<R>>>,
+    Tenant(tenant): Tenant,
+) -> Result<Json<Vec<Discount>>, AppError>
+where
+    R: orders_types::ports::order_repository::OrderRepository + Send + Sync + 'static,
+{
+    Ok(Json(service.list_discounts(&tenant).await?))
+}
+
+async fn delete_discount<R>(
+    State(service): State<Arc<OrderService<R>>>,
+    Tenant(tenant): Tenant,
+    axum::extract::Path(code): axum::extract::Path<String>,
+) -> Result<axum::http::StatusCode, AppError>
+where
+    R: orders_types::ports::order_repository::OrderRepository + Send + Sync + 'static,
+{
+    service.delete_discount(&tenant, &code).await?;
+    Ok(axum::http::StatusCode::NO_CONTENT)
+}
+
+/// Admin: rewrite legacy statuses covered by the mapping table.
+async fn integrity_fix<R>(
+    State(service): State<Arc<OrderService<R>>>,
+    payload: Option<Json<IntegrityFixRequest>>,
+) -> Result<Json<IntegrityReport>, AppError>
+where
+    R: orders_types::ports::order_repository::OrderRepository + Send + Sync + 'static,
+{
+    let Json(payload) = payload.unwrap_or_default();
+    let report = service.check_integrity(true, payload.mapping).await?;
+    Ok(Json(report))
+}
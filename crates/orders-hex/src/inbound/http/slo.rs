@@ -0,0 +1,362 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::extract::{MatchedPath, Request, State};
+use axum::http::header::ACCEPT;
+use axum::http::HeaderMap;
+use axum::middleware::Next;
+use axum::response::Response;
+use axum::routing::get;
+use axum::{Json, Router};
+use orders_types::ports::metrics::MetricsSource;
+use serde::Serialize;
+
+use super::versioning::unversioned;
+use crate::application::auth::{self, OrderAction};
+use crate::errors::AppError;
+
+/// Slices each window is counted in; older slices drop off one at a time.
+const BUCKETS: u64 = 60;
+
+/// Availability objective (e.g. `0.999`) over a rolling window, with
+/// optional per-route overrides keyed by `"METHOD /route/{template}"`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SloTargets {
+    pub objective: f64,
+    pub window: Duration,
+    pub routes: HashMap<String, f64>,
+}
+
+impl Default for SloTargets {
+    fn default() -> Self {
+        Self {
+            objective: 0.999,
+            window: Duration::from_secs(3600),
+            routes: HashMap::new(),
+        }
+    }
+}
+
+impl SloTargets {
+    /// Parse per-route objectives such as `GET /orders/{id}=0.9995,POST /orders=0.99`.
+    pub fn parse_routes(s: &str) -> Result<HashMap<String, f64>, String> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(|pair| {
+                let (route, objective) = pair
+                    .rsplit_once('=')
+                    .ok_or_else(|| format!("expected `METHOD /path=objective`, got `{pair}`"))?;
+                let objective: f64 = objective
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("invalid objective in `{pair}`"))?;
+                if !(0.0..1.0).contains(&objective) {
+                    return Err(format!("objective must be in [0, 1) in `{pair}`"));
+                }
+                Ok((route.trim().to_string(), objective))
+            })
+            .collect()
+    }
+
+    fn objective_for(&self, route: &str) -> f64 {
+        self.routes.get(route).copied().unwrap_or(self.objective)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    slot: u64,
+    requests: u64,
+    errors: u64,
+}
+
+/// Rolling request and server-error counts per route. Only `5xx` responses
+/// spend the error budget; `4xx` are the caller's fault.
+#[derive(Clone)]
+pub struct SloTracker {
+    targets: Arc<SloTargets>,
+    origin: Instant,
+    routes: Arc<Mutex<HashMap<String, VecDeque<Bucket>>>>,
+}
+
+/// Where one route (or the whole service) stands against its objective.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SloStatus {
+    pub route: String,
+    pub objective: f64,
+    pub requests: u64,
+    pub errors: u64,
+    /// Share of successful requests in the window; `1.0` with no traffic.
+    pub availability: f64,
+    /// How fast the budget is being spent: `1.0` uses it up exactly at the
+    /// end of the window, `2.0` in half of it.
+    pub burn_rate: f64,
+    /// Share of the window's error budget left; negative once overspent.
+    pub error_budget_remaining: f64,
+}
+
+impl SloStatus {
+    fn new(route: String, objective: f64, requests: u64, errors: u64) -> Self {
+        let error_ratio = if requests == 0 {
+            0.0
+        } else {
+            errors as f64 / requests as f64
+        };
+        let burn_rate = error_ratio / (1.0 - objective);
+        Self {
+            route,
+            objective,
+            requests,
+            errors,
+            availability: 1.0 - error_ratio,
+            burn_rate,
+            error_budget_remaining: 1.0 - burn_rate,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SloReport {
+    pub window_secs: u64,
+    pub overall: SloStatus,
+    pub routes: Vec<SloStatus>,
+}
+
+impl SloTracker {
+    pub fn new(targets: SloTargets) -> Self {
+        Self {
+            targets: Arc::new(targets),
+            origin: Instant::now(),
+            routes: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    fn slot(&self, now: Instant) -> u64 {
+        let bucket = (self.targets.window.as_millis() as u64 / BUCKETS).max(1);
+        now.duration_since(self.origin).as_millis() as u64 / bucket
+    }
+
+    pub fn record(&self, route: &str, server_error: bool) {
+        self.record_at(route, server_error, Instant::now());
+    }
+
+    fn record_at(&self, route: &str, server_error: bool, now: Instant) {
+        let slot = self.slot(now);
+        let mut routes = self.routes.lock().unwrap();
+        let buckets = routes.entry(route.to_string()).or_default();
+        match buckets.back_mut() {
+            Some(b) if b.slot == slot => {
+                b.requests += 1;
+                b.errors += u64::from(server_error);
+            }
+            _ => buckets.push_back(Bucket {
+                slot,
+                requests: 1,
+                errors: u64::from(server_error),
+            }),
+        }
+        while buckets.front().is_some_and(|b| b.slot + BUCKETS <= slot) {
+            buckets.pop_front();
+        }
+    }
+
+    pub fn report(&self) -> SloReport {
+        self.report_at(Instant::now())
+    }
+
+    fn report_at(&self, now: Instant) -> SloReport {
+        let slot = self.slot(now);
+        let routes = self.routes.lock().unwrap();
+        let mut statuses: Vec<SloStatus> = routes
+            .iter()
+            .map(|(route, buckets)| {
+                let (requests, errors) = buckets
+                    .iter()
+                    .filter(|b| b.slot + BUCKETS > slot)
+                    .fold((0, 0), |(r, e), b| (r + b.requests, e + b.errors));
+                let objective = self.targets.objective_for(route);
+                SloStatus::new(route.clone(), objective, requests, errors)
+            })
+            .collect();
+        statuses.sort_by(|a, b| a.route.cmp(&b.route));
+        let (requests, errors) = statuses
+            .iter()
+            .fold((0, 0), |(r, e), s| (r + s.requests, e + s.errors));
+        SloReport {
+            window_secs: self.targets.window.as_secs(),
+            overall: SloStatus::new("*".into(), self.targets.objective, requests, errors),
+            routes: statuses,
+        }
+    }
+}
+
+impl SloReport {
+    /// Prometheus text exposition of every status as gauges labelled by route.
+    pub fn to_prometheus(&self) -> String {
+        type Field = fn(&SloStatus) -> f64;
+        let gauges: [(&str, &str, Field); 6] = [
+            ("orders_slo_objective", "Availability objective.", |s| {
+                s.objective
+            }),
+            (
+                "orders_slo_window_requests",
+                "Requests in the SLO window.",
+                |s| s.requests as f64,
+            ),
+            (
+                "orders_slo_window_errors",
+                "Server errors in the SLO window.",
+                |s| s.errors as f64,
+            ),
+            (
+                "orders_slo_availability",
+                "Availability over the SLO window.",
+                |s| s.availability,
+            ),
+            ("orders_slo_burn_rate", "Error budget burn rate.", |s| {
+                s.burn_rate
+            }),
+            (
+                "orders_slo_error_budget_remaining",
+                "Share of the error budget left.",
+                |s| s.error_budget_remaining,
+            ),
+        ];
+        let mut out = String::new();
+        for (name, help, field) in gauges {
+            let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} gauge");
+            for s in std::iter::once(&self.overall).chain(&self.routes) {
+                let route = s.route.replace('\\', "\\\\").replace('"', "\\\"");
+                let _ = writeln!(out, "{name}{{route=\"{route}\"}} {}", field(s));
+            }
+        }
+        out
+    }
+}
+
+/// Count every routed request against its `"METHOD /template"` route.
+pub async fn track_slo(State(tracker): State<SloTracker>, req: Request, next: Next) -> Response {
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| format!("{} {}", req.method(), unversioned(p.as_str())));
+    let res = next.run(req).await;
+    if let Some(route) = route {
+        tracker.record(&route, res.status().is_server_error());
+    }
+    res
+}
+
+/// `GET /admin/slo`, for admins.
+pub fn slo_router(tracker: SloTracker) -> Router {
+    Router::new()
+        .route("/admin/slo", get(slo_report))
+        .with_state(tracker)
+}
+
+/// The public `GET /metrics` scrape target, which also carries the series
+/// of every one of `sources`. Scrapes that accept
+/// `application/openmetrics-text` get OpenMetrics, exemplars included.
+pub fn metrics_router(tracker: SloTracker, sources: Vec<Arc<dyn MetricsSource>>) -> Router {
+    Router::new()
+        .route("/metrics", get(metrics))
+        .with_state(Scrape {
+            tracker,
+            sources: Arc::new(sources),
+        })
+}
+
+#[derive(Clone)]
+struct Scrape {
+    tracker: SloTracker,
+    sources: Arc<Vec<Arc<dyn MetricsSource>>>,
+}
+
+async fn slo_report(State(tracker): State<SloTracker>) -> Result<Json<SloReport>, AppError> {
+    auth::authorize(OrderAction::Administer)?;
+    Ok(Json(tracker.report()))
+}
+
+/// Content type of an OpenMetrics scrape, the only format with exemplars.
+const OPENMETRICS: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// Labels name tenants and API keys, so with API keys on the public port
+/// only admins may scrape. The admin listener needs no key.
+async fn metrics(
+    State(scrape): State<Scrape>,
+    headers: HeaderMap,
+) -> Result<([(&'static str, &'static str); 1], String), AppError> {
+    auth::authorize(OrderAction::Administer)?;
+    let openmetrics = headers
+        .get_all(ACCEPT)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .any(|v| v.contains("application/openmetrics-text"));
+    let mut body = scrape.tracker.report().to_prometheus();
+    for source in scrape.sources.iter() {
+        if openmetrics {
+            body.push_str(&source.openmetrics());
+        } else {
+            body.push_str(&source.prometheus());
+        }
+    }
+    if openmetrics {
+        body.push_str("# EOF\n");
+        return Ok(([("content-type", OPENMETRICS)], body));
+    }
+    Ok(([("content-type", "text/plain; version=0.0.4")], body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker(window_secs: u64) -> SloTracker {
+        SloTracker::new(SloTargets {
+            objective: 0.99,
+            window: Duration::from_secs(window_secs),
+            routes: SloTargets::parse_routes("POST /orders=0.9").unwrap(),
+        })
+    }
+
+    #[test]
+    fn burn_rate_and_budget_follow_the_objective() {
+        let t = tracker(60);
+        let now = t.origin;
+        for i in 0..100 {
+            t.record_at("GET /orders", i < 2, now);
+            t.record_at("POST /orders", i < 5, now);
+        }
+        let report = t.report_at(now);
+        let get = &report.routes[0];
+        assert_eq!((get.requests, get.errors), (100, 2));
+        assert!((get.availability - 0.98).abs() < 1e-9);
+        assert!((get.burn_rate - 2.0).abs() < 1e-9);
+        assert!((get.error_budget_remaining + 1.0).abs() < 1e-9);
+
+        let post = &report.routes[1];
+        assert_eq!(post.objective, 0.9);
+        assert!((post.burn_rate - 0.5).abs() < 1e-9);
+        assert_eq!((report.overall.requests, report.overall.errors), (200, 7));
+    }
+
+    #[test]
+    fn old_requests_leave_the_window() {
+        let t = tracker(60);
+        t.record_at("GET /orders", true, t.origin);
+        t.record_at("GET /orders", false, t.origin + Duration::from_secs(30));
+        let later = t.report_at(t.origin + Duration::from_secs(61));
+        assert_eq!((later.routes[0].requests, later.routes[0].errors), (1, 0));
+        assert_eq!(later.routes[0].availability, 1.0);
+    }
+
+    #[test]
+    fn route_objectives_must_be_fractions() {
+        assert!(SloTargets::parse_routes("GET /orders=1.5").is_err());
+        assert!(SloTargets::parse_routes("GET /orders").is_err());
+        assert!(SloTargets::parse_routes("").unwrap().is_empty());
+    }
+}
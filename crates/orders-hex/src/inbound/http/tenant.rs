@@ -0,0 +1,164 @@
+use std::sync::Arc;
+
+use axum::extract::{FromRequestParts, Request, State};
+use axum::http::header::AUTHORIZATION;
+use axum::http::request::Parts;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use orders_types::domain::actor::Actor;
+use orders_types::domain::request_signing::SCHEME;
+use orders_types::domain::tenant::TenantId;
+use serde::Deserialize;
+
+use super::auth::is_share_link;
+use crate::application::actor;
+use crate::application::auth::AuthContext;
+use crate::errors::AppError;
+
+pub const TENANT_HEADER: &str = "x-tenant-id";
+/// JWT claim carrying the tenant.
+pub const TENANT_CLAIM: &str = "tenant_id";
+
+/// Works out which tenant a request acts for, and which user when its bearer
+/// token names one.
+///
+/// With a JWT secret configured every request needs a verified bearer token
+/// with a `tenant_id` claim, and an `X-Tenant-Id` header naming another
+/// tenant is rejected. Without one the header is used, and without that the
+/// default tenant. An API key bound to a tenant acts for it alone: a token
+/// or header naming another is rejected too. A signed request uses
+/// `Authorization` for its signature, so it carries no token and acts for
+/// its key's tenant instead. The token's `sub` claim, if any, is the user
+/// changes are attributed to.
+#[derive(Clone, Default)]
+pub struct TenantResolver {
+    jwt_key: Option<Arc<DecodingKey>>,
+}
+
+#[derive(Deserialize, Default)]
+struct Claims {
+    tenant_id: Option<String>,
+    sub: Option<String>,
+}
+
+impl TenantResolver {
+    /// Trust HS256 bearer tokens signed with `secret`.
+    pub fn with_jwt_secret(mut self, secret: &[u8]) -> Self {
+        self.jwt_key = Some(Arc::new(DecodingKey::from_secret(secret)));
+        self
+    }
+
+    fn claims(&self, req: &Request) -> Result<Claims, AppError> {
+        let Some(key) = &self.jwt_key else {
+            return Ok(Claims::default());
+        };
+        let Some(token) = req
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+        else {
+            return Ok(Claims::default());
+        };
+        decode::<Claims>(token, key, &Validation::new(Algorithm::HS256))
+            .map(|data| data.claims)
+            .map_err(|e| AppError::Unauthorized(format!("invalid bearer token: {e}")))
+    }
+
+    fn resolve(&self, req: &Request) -> Result<(TenantId, Option<Actor>), AppError> {
+        let bound = req
+            .extensions()
+            .get::<AuthContext>()
+            .and_then(|ctx| ctx.tenant.clone());
+        let signed = req
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with(SCHEME));
+        if signed && self.jwt_key.is_some() && bound.is_none() {
+            return Err(AppError::Unauthorized(
+                "a signed request must come from a key bound to a tenant".into(),
+            ));
+        }
+        let claims = self.claims(req)?;
+        let user = claims.sub.filter(|s| !s.is_empty()).map(Actor::User);
+        let claimed = claims
+            .tenant_id
+            .map(|t| TenantId::parse(&t).map_err(AppError::Unauthorized))
+            .transpose()?;
+        if self.jwt_key.is_some() && claimed.is_none() && !signed {
+            return Err(AppError::Unauthorized(format!(
+                "a bearer token with a `{TENANT_CLAIM}` claim is required"
+            )));
+        }
+        let header = req
+            .headers()
+            .get(TENANT_HEADER)
+            .map(|v| {
+                v.to_str()
+                    .map_err(|e| AppError::BadRequest(e.to_string()))
+                    .and_then(|s| TenantId::parse(s).map_err(AppError::BadRequest))
+            })
+            .transpose()?;
+        let named = match (claimed, header) {
+            (Some(claimed), Some(header)) if claimed != header => {
+                return Err(AppError::Forbidden(format!(
+                    "{TENANT_HEADER} does not match the token's tenant"
+                )))
+            }
+            (Some(claimed), _) => Some(claimed),
+            (None, header) => header,
+        };
+        let tenant = match (bound, named) {
+            (Some(bound), Some(named)) if bound != named => {
+                return Err(AppError::Forbidden(format!(
+                    "the api key may only act for tenant `{bound}`"
+                )))
+            }
+            (Some(bound), _) => bound,
+            (None, named) => named.unwrap_or_default(),
+        };
+        Ok((tenant, user))
+    }
+}
+
+/// Attach the request's [`TenantId`] for the [`Tenant`] extractor, and run
+/// the rest of it on behalf of the token's user, if it names one. Share
+/// links name their own tenant and are passed through as they are.
+pub async fn resolve_tenant(
+    State(resolver): State<TenantResolver>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    if is_share_link(&req) {
+        return next.run(req).await;
+    }
+    match resolver.resolve(&req) {
+        Ok((tenant, user)) => {
+            req.extensions_mut().insert(tenant);
+            match user {
+                Some(user) => actor::scope(user, next.run(req)).await,
+                None => next.run(req).await,
+            }
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+/// The tenant a request acts for.
+pub struct Tenant(pub TenantId);
+
+impl<S: Send + Sync> FromRequestParts<S> for Tenant {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Tenant(
+            parts
+                .extensions
+                .get::<TenantId>()
+                .cloned()
+                .unwrap_or_default(),
+        ))
+    }
+}
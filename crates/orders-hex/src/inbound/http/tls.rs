@@ -0,0 +1,69 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use axum_server::tls_rustls::RustlsConfig;
+
+/// PEM certificate chain and private key for serving HTTPS.
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+}
+
+impl TlsConfig {
+    pub fn new(cert_path: impl Into<PathBuf>, key_path: impl Into<PathBuf>) -> Self {
+        Self {
+            cert_path: cert_path.into(),
+            key_path: key_path.into(),
+        }
+    }
+
+    /// Read the certificate and key into a rustls server config.
+    pub async fn load(&self) -> anyhow::Result<RustlsConfig> {
+        // Several crates in the tree pull in rustls; pick ring explicitly.
+        // An error only means a provider is already installed.
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        RustlsConfig::from_pem_file(&self.cert_path, &self.key_path)
+            .await
+            .with_context(|| {
+                format!(
+                    "loading tls cert {} / key {}",
+                    self.cert_path.display(),
+                    self.key_path.display()
+                )
+            })
+    }
+
+    /// Re-read both files into `rustls` on every SIGHUP. New handshakes use
+    /// the new certificate; a failed reload keeps serving the old one.
+    pub fn reload_on_sighup(&self, rustls: RustlsConfig) {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+
+            let mut hangups = match signal(SignalKind::hangup()) {
+                Ok(s) => s,
+                Err(e) => {
+                    tracing::warn!(error = %e, "cannot listen for SIGHUP; tls reload disabled");
+                    return;
+                }
+            };
+            let tls = self.clone();
+            tokio::spawn(async move {
+                while hangups.recv().await.is_some() {
+                    match rustls
+                        .reload_from_pem_file(&tls.cert_path, &tls.key_path)
+                        .await
+                    {
+                        Ok(()) => tracing::info!("reloaded tls certificate"),
+                        Err(e) => {
+                            tracing::error!(error = %e, "tls reload failed; keeping previous certificate")
+                        }
+                    }
+                }
+            });
+        }
+        #[cfg(not(unix))]
+        let _ = rustls;
+    }
+}
@@ -0,0 +1,76 @@
+//! URL versions of the API. Routes live under `/v1`; their original
+//! unversioned paths still answer, but with `Deprecation`, `Sunset` and a
+//! `Link` to the `/v1` path. A `/v2` with its own DTOs would be another
+//! router over the same `OrderService`, nested next to `/v1`.
+
+use axum::extract::{Request, State};
+use axum::http::{header, HeaderValue};
+use axum::middleware::Next;
+use axum::response::Response;
+use axum::Router;
+use chrono::{DateTime, Utc};
+
+pub const V1: &str = "/v1";
+
+/// `path` without its version prefix, for middleware that care about the
+/// route rather than the version it was reached through.
+pub fn unversioned(path: &str) -> &str {
+    let Some(rest) = path.strip_prefix("/v") else {
+        return path;
+    };
+    let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+    match &rest[digits..] {
+        tail if digits > 0 && tail.starts_with('/') => tail,
+        "" if digits > 0 => "/",
+        _ => path,
+    }
+}
+
+/// How the unversioned aliases announce their retirement.
+#[derive(Debug, Clone, Default)]
+pub struct LegacyRoutes {
+    /// Sent as `Sunset` when set.
+    pub sunset: Option<DateTime<Utc>>,
+}
+
+impl LegacyRoutes {
+    /// `api` under `/v1` and, deprecated, at its unversioned paths.
+    pub fn mount<S: Clone + Send + Sync + 'static>(&self, api: Router<S>) -> Router<S> {
+        Router::new().nest(V1, api.clone()).merge(api.route_layer(
+            axum::middleware::from_fn_with_state(self.clone(), deprecated),
+        ))
+    }
+}
+
+async fn deprecated(State(legacy): State<LegacyRoutes>, req: Request, next: Next) -> Response {
+    let successor = format!("<{V1}{}>; rel=\"successor-version\"", req.uri().path());
+    let mut res = next.run(req).await;
+    let headers = res.headers_mut();
+    headers.insert("deprecation", HeaderValue::from_static("true"));
+    if let Some(sunset) = legacy.sunset {
+        let date = sunset.format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+        if let Ok(value) = HeaderValue::from_str(&date) {
+            headers.insert("sunset", value);
+        }
+    }
+    if let Ok(value) = HeaderValue::from_str(&successor) {
+        headers.append(header::LINK, value);
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_only_a_version_segment() {
+        assert_eq!(unversioned("/v1/orders/42"), "/orders/42");
+        assert_eq!(unversioned("/v12/orders"), "/orders");
+        assert_eq!(unversioned("/v1"), "/");
+        assert_eq!(unversioned("/orders"), "/orders");
+        assert_eq!(unversioned("/v1orders"), "/v1orders");
+        assert_eq!(unversioned("/vx/orders"), "/vx/orders");
+        assert_eq!(unversioned("/v/orders"), "/v/orders");
+    }
+}
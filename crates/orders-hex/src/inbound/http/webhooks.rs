@@ -0,0 +1,24 @@
+use std::sync::Arc;
+
+use crate::application::auth::{self, OrderAction};
+use axum::extract::{Path, State};
+use axum::routing::post;
+use axum::{Json, Router};
+
+use crate::application::webhook_service::{TestDelivery, WebhookService};
+use crate::errors::AppError;
+
+/// Admin routes for verifying configured webhook receivers.
+pub fn webhook_router(hooks: Arc<WebhookService>) -> Router {
+    Router::new()
+        .route("/admin/webhooks/{id}/test", post(test_delivery))
+        .with_state(hooks)
+}
+
+async fn test_delivery(
+    State(hooks): State<Arc<WebhookService>>,
+    Path(id): Path<String>,
+) -> Result<Json<TestDelivery>, AppError> {
+    auth::authorize(OrderAction::Administer)?;
+    Ok(Json(hooks.send_test(&id).await?))
+}
@@ -0,0 +1,196 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::State;
+use axum::response::Response;
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::{self, error::RecvError};
+use uuid::Uuid;
+
+use super::tenant::Tenant;
+use crate::application::auth::{self, OrderAction};
+use crate::application::order_service::OrderService;
+use crate::errors::AppError;
+use orders_types::domain::events::{EventEnvelope, OrderEvent};
+use orders_types::domain::order::OrderStatus;
+use orders_types::domain::tenant::TenantId;
+use orders_types::ports::order_repository::OrderRepository;
+
+/// How often the server pings; a connection that misses one pong is closed.
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+/// Longest a single frame may wait on a slow client before it is dropped.
+const SEND_TIMEOUT: Duration = Duration::from_secs(5);
+/// Most order ids and statuses one connection may follow at once.
+const MAX_SUBSCRIPTIONS: usize = 1_000;
+
+/// Client -> server control messages.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ClientMessage {
+    Subscribe {
+        #[serde(default)]
+        order_ids: Vec<Uuid>,
+        #[serde(default)]
+        statuses: Vec<OrderStatus>,
+    },
+    Unsubscribe {
+        #[serde(default)]
+        order_ids: Vec<Uuid>,
+        #[serde(default)]
+        statuses: Vec<OrderStatus>,
+    },
+}
+
+/// Server -> client frames other than the order events themselves.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ControlFrame<'a> {
+    Subscribed {
+        order_ids: &'a HashSet<Uuid>,
+        statuses: &'a HashSet<OrderStatus>,
+    },
+    /// The connection fell behind and `missed` events were dropped.
+    Lagged {
+        missed: u64,
+    },
+    Error {
+        message: String,
+    },
+}
+
+#[derive(Default)]
+struct Subscription {
+    order_ids: HashSet<Uuid>,
+    statuses: HashSet<OrderStatus>,
+}
+
+impl Subscription {
+    fn matches(&self, event: &OrderEvent) -> bool {
+        self.order_ids.contains(&event.order_id())
+            || event.status().is_some_and(|s| self.statuses.contains(s))
+    }
+
+    /// Fails, changing nothing, when a subscribe would take the connection
+    /// past [`MAX_SUBSCRIPTIONS`].
+    fn apply(&mut self, msg: ClientMessage) -> Result<(), String> {
+        match msg {
+            ClientMessage::Subscribe {
+                order_ids,
+                statuses,
+            } => {
+                let new_ids: HashSet<_> = order_ids
+                    .into_iter()
+                    .filter(|id| !self.order_ids.contains(id))
+                    .collect();
+                let new_statuses: HashSet<_> = statuses
+                    .into_iter()
+                    .filter(|s| !self.statuses.contains(s))
+                    .collect();
+                let total =
+                    self.order_ids.len() + self.statuses.len() + new_ids.len() + new_statuses.len();
+                if total > MAX_SUBSCRIPTIONS {
+                    return Err(format!(
+                        "a connection may follow at most {MAX_SUBSCRIPTIONS} order ids and statuses"
+                    ));
+                }
+                self.order_ids.extend(new_ids);
+                self.statuses.extend(new_statuses);
+            }
+            ClientMessage::Unsubscribe {
+                order_ids,
+                statuses,
+            } => {
+                for id in order_ids {
+                    self.order_ids.remove(&id);
+                }
+                for s in statuses {
+                    self.statuses.remove(&s);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+pub async fn ws_handler<R>(
+    State(service): State<Arc<OrderService<R>>>,
+    Tenant(tenant): Tenant,
+    ws: WebSocketUpgrade,
+) -> Result<Response, AppError>
+where
+    R: OrderRepository + Send + Sync + 'static,
+{
+    auth::authorize(OrderAction::View)?;
+    let events = service.subscribe();
+    Ok(ws.on_upgrade(move |socket| run_connection(socket, tenant, events)))
+}
+
+async fn run_connection(
+    socket: WebSocket,
+    tenant: TenantId,
+    mut events: broadcast::Receiver<EventEnvelope>,
+) {
+    let (mut tx, mut rx) = socket.split();
+    let mut sub = Subscription::default();
+    let mut ping = tokio::time::interval(PING_INTERVAL);
+    ping.tick().await;
+    let mut awaiting_pong = false;
+
+    loop {
+        let frame = tokio::select! {
+            incoming = rx.next() => match incoming {
+                Some(Ok(Message::Text(text))) => match serde_json::from_str(&text) {
+                    Ok(msg) => match sub.apply(msg) {
+                        Ok(()) => json(&ControlFrame::Subscribed {
+                            order_ids: &sub.order_ids,
+                            statuses: &sub.statuses,
+                        }),
+                        Err(message) => json(&ControlFrame::Error { message }),
+                    },
+                    Err(e) => json(&ControlFrame::Error { message: e.to_string() }),
+                },
+                Some(Ok(Message::Pong(_))) => {
+                    awaiting_pong = false;
+                    continue;
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => continue,
+            },
+            event = events.recv() => match event {
+                Ok(env) if env.event.tenant_id() == &tenant && sub.matches(&env.event) => json(&env),
+                Ok(_) => continue,
+                Err(RecvError::Lagged(missed)) => json(&ControlFrame::Lagged { missed }),
+                Err(RecvError::Closed) => break,
+            },
+            _ = ping.tick() => {
+                if awaiting_pong {
+                    tracing::debug!("websocket client missed pong; closing");
+                    break;
+                }
+                awaiting_pong = true;
+                Message::Ping(Default::default())
+            }
+        };
+
+        match tokio::time::timeout(SEND_TIMEOUT, tx.send(frame)).await {
+            Ok(Ok(())) => {}
+            Ok(Err(_)) => break,
+            Err(_) => {
+                tracing::warn!("websocket client too slow; closing");
+                break;
+            }
+        }
+    }
+    let _ = tx.close().await;
+}
+
+fn json<T: Serialize>(value: &T) -> Message {
+    Message::Text(
+        serde_json::to_string(value)
+            .unwrap_or_else(|_| "{\"type\":\"error\"}".into())
+            .into(),
+    )
+}
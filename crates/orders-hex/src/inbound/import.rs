@@ -0,0 +1,516 @@
+//! Incremental parsers for bulk order imports. Input is pushed in arbitrary
+//! chunks and only the record being assembled is buffered, so memory stays
+//! bounded however large the upload is.
+
+use csv_core::{ReadRecordResult, Reader};
+use orders_types::domain::import::ImportRecord;
+use orders_types::domain::money::{Currency, Money};
+use orders_types::domain::order::OrderItem;
+
+/// Longest single NDJSON line or CSV row accepted.
+pub const MAX_RECORD_BYTES: usize = 1024 * 1024;
+/// Most CSV rows (items) grouped into one order.
+pub const MAX_ITEMS_PER_ORDER: usize = 1000;
+
+/// A parsed record, or why it could not be, with its starting line.
+pub type Parsed = (u64, Result<ImportRecord, String>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    /// One JSON order per line, shaped like a create request.
+    Ndjson,
+    /// One item per row under a header naming `customer_name`, `email`,
+    /// `item_name`, `qty` and `unit_price_cents`. Consecutive rows sharing a
+    /// non-empty `order_ref` form one order.
+    Csv,
+}
+
+impl ImportFormat {
+    /// From a media type such as `application/x-ndjson` or
+    /// `text/csv; charset=utf-8`.
+    pub fn from_mime(mime: &str) -> Option<Self> {
+        let essence = mime.split(';').next().unwrap_or_default().trim();
+        match essence.to_ascii_lowercase().as_str() {
+            "application/x-ndjson"
+            | "application/ndjson"
+            | "application/jsonl"
+            | "application/x-jsonlines" => Some(ImportFormat::Ndjson),
+            "text/csv" | "application/csv" => Some(ImportFormat::Csv),
+            _ => None,
+        }
+    }
+
+    /// From an upload's file name extension.
+    pub fn from_filename(name: &str) -> Option<Self> {
+        let ext = name.rsplit_once('.')?.1.to_ascii_lowercase();
+        match ext.as_str() {
+            "ndjson" | "jsonl" => Some(ImportFormat::Ndjson),
+            "csv" => Some(ImportFormat::Csv),
+            _ => None,
+        }
+    }
+}
+
+pub enum RecordParser {
+    Ndjson(NdjsonParser),
+    Csv(Box<CsvParser>),
+}
+
+impl RecordParser {
+    pub fn new(format: ImportFormat) -> Self {
+        match format {
+            ImportFormat::Ndjson => RecordParser::Ndjson(NdjsonParser::default()),
+            ImportFormat::Csv => RecordParser::Csv(Box::default()),
+        }
+    }
+
+    /// Feed the next chunk, appending every record it completes to `out`.
+    pub fn push(&mut self, chunk: &[u8], out: &mut Vec<Parsed>) {
+        if chunk.is_empty() {
+            return;
+        }
+        match self {
+            RecordParser::Ndjson(p) => p.push(chunk, out),
+            RecordParser::Csv(p) => p.feed(chunk, out),
+        }
+    }
+
+    /// Flush whatever the input ended on.
+    pub fn finish(&mut self, out: &mut Vec<Parsed>) {
+        match self {
+            RecordParser::Ndjson(p) => p.end_line(out),
+            RecordParser::Csv(p) => {
+                p.feed(&[], out);
+                p.flush_group(out);
+            }
+        }
+    }
+}
+
+pub struct NdjsonParser {
+    buf: Vec<u8>,
+    /// Line currently being buffered.
+    line: u64,
+    /// The current line overflowed and was reported; drop it to its end.
+    skipping: bool,
+}
+
+impl Default for NdjsonParser {
+    fn default() -> Self {
+        Self {
+            buf: Vec::new(),
+            line: 1,
+            skipping: false,
+        }
+    }
+}
+
+impl NdjsonParser {
+    fn push(&mut self, mut chunk: &[u8], out: &mut Vec<Parsed>) {
+        while let Some(pos) = chunk.iter().position(|&b| b == b'\n') {
+            self.take(&chunk[..pos], out);
+            self.end_line(out);
+            chunk = &chunk[pos + 1..];
+        }
+        self.take(chunk, out);
+    }
+
+    fn take(&mut self, bytes: &[u8], out: &mut Vec<Parsed>) {
+        if self.skipping {
+            return;
+        }
+        if self.buf.len() + bytes.len() > MAX_RECORD_BYTES {
+            out.push((
+                self.line,
+                Err(format!("line exceeds {MAX_RECORD_BYTES} bytes")),
+            ));
+            self.buf = Vec::new();
+            self.skipping = true;
+            return;
+        }
+        self.buf.extend_from_slice(bytes);
+    }
+
+    fn end_line(&mut self, out: &mut Vec<Parsed>) {
+        if !self.skipping {
+            let line = self.buf.trim_ascii();
+            if !line.is_empty() {
+                let record = serde_json::from_slice(line).map_err(|e| e.to_string());
+                out.push((self.line, record));
+            }
+        }
+        self.buf.clear();
+        self.skipping = false;
+        self.line += 1;
+    }
+}
+
+/// Column positions resolved from the CSV header.
+struct Columns {
+    order_ref: Option<usize>,
+    customer_name: usize,
+    email: usize,
+    item_name: usize,
+    qty: usize,
+    unit_price_cents: usize,
+    currency: Option<usize>,
+    weight_grams: Option<usize>,
+    sku: Option<usize>,
+    description: Option<usize>,
+}
+
+/// Rows collected for the order being assembled.
+struct Group {
+    order_ref: String,
+    line: u64,
+    record: ImportRecord,
+    error: Option<String>,
+}
+
+pub struct CsvParser {
+    reader: Reader,
+    fields: Vec<u8>,
+    ends: Vec<usize>,
+    used: usize,
+    nends: usize,
+    /// Line the row being read started on.
+    row_line: u64,
+    /// The current row overflowed and was reported; drop it to its end.
+    skipping: bool,
+    columns: Option<Result<Columns, ()>>,
+    group: Option<Group>,
+}
+
+impl Default for CsvParser {
+    fn default() -> Self {
+        Self {
+            reader: Reader::new(),
+            fields: vec![0; 1024],
+            ends: vec![0; 16],
+            used: 0,
+            nends: 0,
+            row_line: 1,
+            skipping: false,
+            columns: None,
+            group: None,
+        }
+    }
+}
+
+impl CsvParser {
+    /// Run `input` through the reader; an empty slice signals end of input.
+    fn feed(&mut self, mut input: &[u8], out: &mut Vec<Parsed>) {
+        loop {
+            let (res, nin, nout, nend) = self.reader.read_record(
+                input,
+                &mut self.fields[self.used..],
+                &mut self.ends[self.nends..],
+            );
+            input = &input[nin..];
+            self.used += nout;
+            self.nends += nend;
+            match res {
+                ReadRecordResult::InputEmpty => return,
+                ReadRecordResult::OutputFull => {
+                    if self.fields.len() >= MAX_RECORD_BYTES {
+                        if !self.skipping {
+                            out.push((
+                                self.row_line,
+                                Err(format!("row exceeds {MAX_RECORD_BYTES} bytes")),
+                            ));
+                            self.skipping = true;
+                        }
+                        self.used = 0;
+                        self.nends = 0;
+                    } else {
+                        let len = (self.fields.len() * 2).min(MAX_RECORD_BYTES);
+                        self.fields.resize(len, 0);
+                    }
+                }
+                ReadRecordResult::OutputEndsFull => {
+                    let len = self.ends.len() * 2;
+                    self.ends.resize(len, 0);
+                }
+                ReadRecordResult::Record => {
+                    if !self.skipping {
+                        self.on_row(out);
+                    }
+                    self.skipping = false;
+                    self.used = 0;
+                    self.nends = 0;
+                    self.row_line = self.reader.line();
+                }
+                ReadRecordResult::End => return,
+            }
+        }
+    }
+
+    fn on_row(&mut self, out: &mut Vec<Parsed>) {
+        let line = self.row_line;
+        let row = split_row(&self.fields, &self.ends[..self.nends]);
+        if row.iter().all(|f| f.is_empty()) {
+            return;
+        }
+        let columns = match &self.columns {
+            None => {
+                let header = header_columns(&row);
+                if let Err(e) = &header {
+                    out.push((line, Err(e.clone())));
+                }
+                self.columns = Some(header.map_err(|_| ()));
+                return;
+            }
+            // A bad header was reported once; the rest is unreadable.
+            Some(Err(())) => return,
+            Some(Ok(columns)) => columns,
+        };
+        let field = |i: usize| -> Result<&str, String> {
+            let raw = row.get(i).copied().unwrap_or_default();
+            std::str::from_utf8(raw)
+                .map(str::trim)
+                .map_err(|_| format!("column {} is not valid UTF-8", i + 1))
+        };
+        let order_ref = match columns.order_ref.map(field).transpose() {
+            Ok(r) => r.unwrap_or_default().to_string(),
+            Err(e) => {
+                flush(&mut self.group, out);
+                out.push((line, Err(e)));
+                return;
+            }
+        };
+        let item = parse_item(columns, &field);
+        let continues = !order_ref.is_empty()
+            && self
+                .group
+                .as_ref()
+                .is_some_and(|g| g.order_ref == order_ref);
+        if !continues {
+            flush(&mut self.group, out);
+            let (customer_name, email) = match (field(columns.customer_name), field(columns.email))
+            {
+                (Ok(name), Ok(email)) => (name.to_string(), email.to_string()),
+                (Err(e), _) | (_, Err(e)) => {
+                    out.push((line, Err(e)));
+                    return;
+                }
+            };
+            self.group = Some(Group {
+                order_ref,
+                line,
+                record: ImportRecord {
+                    customer_name,
+                    email,
+                    items: Vec::new(),
+                },
+                error: None,
+            });
+        }
+        let group = self.group.as_mut().expect("group started above");
+        if group.error.is_some() {
+            return;
+        }
+        match item {
+            Ok(_) if group.record.items.len() >= MAX_ITEMS_PER_ORDER => {
+                group.error = Some(format!("order has more than {MAX_ITEMS_PER_ORDER} items"));
+                group.record.items = Vec::new();
+            }
+            Ok(item) => group.record.items.push(item),
+            Err(e) => {
+                group.error = Some(format!("line {line}: {e}"));
+                group.record.items = Vec::new();
+            }
+        }
+    }
+
+    fn flush_group(&mut self, out: &mut Vec<Parsed>) {
+        flush(&mut self.group, out);
+    }
+}
+
+fn split_row<'a>(fields: &'a [u8], ends: &[usize]) -> Vec<&'a [u8]> {
+    let mut start = 0;
+    ends.iter()
+        .map(|&end| {
+            let field = &fields[start..end];
+            start = end;
+            field
+        })
+        .collect()
+}
+
+/// Emit the order being assembled, if any.
+fn flush(group: &mut Option<Group>, out: &mut Vec<Parsed>) {
+    if let Some(g) = group.take() {
+        out.push((g.line, g.error.map_or(Ok(g.record), Err)));
+    }
+}
+
+fn header_columns(row: &[&[u8]]) -> Result<Columns, String> {
+    let names: Vec<String> = row
+        .iter()
+        .map(|f| String::from_utf8_lossy(f).trim().to_ascii_lowercase())
+        .collect();
+    let find = |name: &str| names.iter().position(|n| n == name);
+    let require = |name: &str| find(name).ok_or_else(|| format!("missing column `{name}`"));
+    Ok(Columns {
+        order_ref: find("order_ref"),
+        customer_name: require("customer_name")?,
+        email: require("email")?,
+        item_name: require("item_name")?,
+        qty: require("qty")?,
+        unit_price_cents: require("unit_price_cents")?,
+        currency: find("currency"),
+        weight_grams: find("weight_grams"),
+        sku: find("sku"),
+        description: find("description"),
+    })
+}
+
+fn parse_item<'a>(
+    columns: &Columns,
+    field: &impl Fn(usize) -> Result<&'a str, String>,
+) -> Result<OrderItem, String> {
+    let qty = field(columns.qty)?;
+    let price = field(columns.unit_price_cents)?;
+    let currency = match columns.currency.map(field).transpose()? {
+        Some(code) if !code.trim().is_empty() => Currency::parse(code.trim())?,
+        _ => Currency::default(),
+    };
+    let weight_grams = match columns.weight_grams.map(field).transpose()? {
+        Some(w) if !w.trim().is_empty() => w
+            .trim()
+            .parse()
+            .map_err(|_| format!("weight_grams `{w}` is not a non-negative integer"))?,
+        _ => 0,
+    };
+    let optional = |column: Option<usize>| -> Result<Option<String>, String> {
+        Ok(column
+            .map(field)
+            .transpose()?
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(String::from))
+    };
+    Ok(OrderItem {
+        name: field(columns.item_name)?.to_string(),
+        qty: qty
+            .parse()
+            .map_err(|_| format!("qty `{qty}` is not a non-negative integer"))?,
+        unit_price: Money::new(
+            price
+                .parse()
+                .map_err(|_| format!("unit_price_cents `{price}` is not an integer"))?,
+            currency,
+        ),
+        weight_grams,
+        sku: optional(columns.sku)?,
+        description: optional(columns.description)?,
+        metadata: Default::default(),
+        discount_cents: 0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parse `input` fed `chunk` bytes at a time.
+    fn parse(format: ImportFormat, input: &str, chunk: usize) -> Vec<Parsed> {
+        let mut parser = RecordParser::new(format);
+        let mut out = Vec::new();
+        for piece in input.as_bytes().chunks(chunk) {
+            parser.push(piece, &mut out);
+        }
+        parser.finish(&mut out);
+        out
+    }
+
+    #[test]
+    fn ndjson_records_survive_any_chunking() {
+        let input = concat!(
+            r#"{"customer_name":"A","email":"a@x.io","items":[{"name":"W","qty":1,"unit_price_cents":5}]}"#,
+            "\n\n",
+            "not json\n",
+            r#"{"customer_name":"B","email":"b@x.io","items":[]}"#,
+        );
+        for chunk in [1, 7, 4096] {
+            let out = parse(ImportFormat::Ndjson, input, chunk);
+            let lines: Vec<_> = out.iter().map(|(l, r)| (*l, r.is_ok())).collect();
+            assert_eq!(lines, [(1, true), (3, false), (4, true)], "chunk {chunk}");
+        }
+    }
+
+    #[test]
+    fn ndjson_overlong_line_is_reported_once_and_skipped() {
+        let input = format!(
+            "{}\n{{\"customer_name\":\"B\",\"email\":\"b@x.io\",\"items\":[]}}\n",
+            "x".repeat(MAX_RECORD_BYTES + 10)
+        );
+        let out = parse(ImportFormat::Ndjson, &input, 64 * 1024);
+        assert_eq!(out.len(), 2);
+        assert!(out[0].1.as_ref().unwrap_err().contains("exceeds"));
+        assert_eq!(out[1].0, 2);
+        assert!(out[1].1.is_ok());
+    }
+
+    #[test]
+    fn csv_rows_group_by_order_ref() {
+        let input = "order_ref,customer_name,email,item_name,qty,unit_price_cents\n\
+                     1,Ann,ann@x.io,\"Widget, large\",2,500\n\
+                     1,Ann,ann@x.io,\"Multi\nline\",1,100\n\
+                     2,Bob,bob@x.io,Gadget,x,100\n\
+                     ,Cy,cy@x.io,Thing,1,1\n";
+        for chunk in [1, 5, 4096] {
+            let out = parse(ImportFormat::Csv, input, chunk);
+            assert_eq!(out.len(), 3, "chunk {chunk}");
+            let ann = out[0].1.as_ref().unwrap();
+            assert_eq!(out[0].0, 2);
+            assert_eq!(ann.items.len(), 2);
+            assert_eq!(ann.items[0].name, "Widget, large");
+            assert_eq!(ann.items[1].name, "Multi\nline");
+            assert_eq!(out[1].0, 5);
+            assert!(out[1].1.as_ref().unwrap_err().contains("qty"));
+            assert_eq!(out[2].1.as_ref().unwrap().customer_name, "Cy");
+        }
+    }
+
+    #[test]
+    fn csv_reads_optional_sku_and_description() {
+        let input = "customer_name,email,item_name,qty,unit_price_cents,sku,description\n\
+                     Ann,ann@x.io,Widget,1,500,WID-1,Large widget\n\
+                     Bob,bob@x.io,Gadget,1,100,,\n";
+        let out = parse(ImportFormat::Csv, input, 4096);
+        let ann = &out[0].1.as_ref().unwrap().items[0];
+        assert_eq!(ann.sku.as_deref(), Some("WID-1"));
+        assert_eq!(ann.description.as_deref(), Some("Large widget"));
+        let bob = &out[1].1.as_ref().unwrap().items[0];
+        assert_eq!(
+            (bob.sku.as_deref(), bob.description.as_deref()),
+            (None, None)
+        );
+    }
+
+    #[test]
+    fn csv_without_required_columns_is_rejected() {
+        let out = parse(ImportFormat::Csv, "customer_name,email\nAnn,a@x.io\n", 4096);
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].1.as_ref().unwrap_err(), "missing column `item_name`");
+    }
+
+    #[test]
+    fn formats_from_mime_and_filename() {
+        assert_eq!(
+            ImportFormat::from_mime("text/csv; charset=utf-8"),
+            Some(ImportFormat::Csv)
+        );
+        assert_eq!(
+            ImportFormat::from_mime("application/x-ndjson"),
+            Some(ImportFormat::Ndjson)
+        );
+        assert_eq!(
+            ImportFormat::from_filename("orders.JSONL"),
+            Some(ImportFormat::Ndjson)
+        );
+        assert_eq!(ImportFormat::from_filename("orders"), None);
+    }
+}
@@ -1 +1,4 @@
+#[cfg(feature = "graphql")]
+pub mod graphql;
 pub mod http;
+pub mod import;
@@ -1,4 +1,4 @@
-//! orders-hex: hexagonal Orders API library (core + inbound HTTP)
+//! orders-hex: hexagonal Orders API library (core + inbound HTTP + outbound adapters)
 
 pub mod config;
 pub mod errors;
@@ -8,3 +8,6 @@ pub mod application;
 pub use orders_types::{domain, ports};
 
 pub mod inbound; // HTTP adapter (server + handlers)
+pub mod outbound; // webhook delivery
+
+pub mod testing; // in-process server for integration tests
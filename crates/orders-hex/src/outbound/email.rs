@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use orders_types::ports::notifier::{Notification, NotificationKind};
+
+/// Subject and body of one kind of email. Both may use `{{customer_name}}`,
+/// `{{order_id}}`, `{{status}}`, `{{total}}`, `{{item_count}}` and
+/// `{{cancel_reason}}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmailTemplate {
+    pub subject: String,
+    pub body: String,
+}
+
+impl EmailTemplate {
+    /// `Subject: <subject>` on the first line, a blank line, then the body.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let (first, body) = text.split_once('\n').unwrap_or((text, ""));
+        let subject = first
+            .trim_end_matches('\r')
+            .strip_prefix("Subject:")
+            .ok_or("first line must be `Subject: ...`")?
+            .trim();
+        let body = body
+            .strip_prefix("\r\n")
+            .or_else(|| body.strip_prefix('\n'))
+            .unwrap_or(body);
+        Ok(Self {
+            subject: subject.to_string(),
+            body: body.to_string(),
+        })
+    }
+}
+
+/// A rendered email, ready to send.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// One template per [`NotificationKind`].
+#[derive(Debug, Clone)]
+pub struct EmailTemplates {
+    templates: HashMap<NotificationKind, EmailTemplate>,
+}
+
+impl Default for EmailTemplates {
+    fn default() -> Self {
+        let builtin = |subject: &str, body: &str| EmailTemplate {
+            subject: subject.into(),
+            body: body.into(),
+        };
+        Self {
+            templates: HashMap::from([
+                (
+                    NotificationKind::Created,
+                    builtin(
+                        "We received your order {{order_id}}",
+                        "Hi {{customer_name}},\n\nThanks for your order of {{item_count}} item(s), {{total}} in total.\n",
+                    ),
+                ),
+                (
+                    NotificationKind::Shipped,
+                    builtin(
+                        "Your order {{order_id}} has shipped",
+                        "Hi {{customer_name}},\n\nYour order is on its way.\n",
+                    ),
+                ),
+                (
+                    NotificationKind::Cancelled,
+                    builtin(
+                        "Your order {{order_id}} was cancelled",
+                        "Hi {{customer_name}},\n\nYour order was cancelled: {{cancel_reason}}.\n",
+                    ),
+                ),
+            ]),
+        }
+    }
+}
+
+impl EmailTemplates {
+    /// The built-in templates, overridden by `<kind>.txt` files in `dir`
+    /// (`created.txt`, `shipped.txt`, `cancelled.txt`) where present.
+    pub fn load_dir(dir: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let mut templates = Self::default();
+        for kind in NotificationKind::ALL {
+            let path = dir.as_ref().join(format!("{}.txt", kind.as_str()));
+            match std::fs::read_to_string(&path) {
+                Ok(text) => {
+                    let template = EmailTemplate::parse(&text)
+                        .map_err(|e| anyhow::anyhow!("{}: {e}", path.display()))?;
+                    templates.templates.insert(kind, template);
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(anyhow::anyhow!("{}: {e}", path.display())),
+            }
+        }
+        Ok(templates)
+    }
+
+    pub fn render(&self, notification: &Notification) -> Email {
+        let order = &notification.order;
+        let template = &self.templates[&notification.kind];
+        let values = [
+            ("customer_name", order.customer_name.clone()),
+            ("order_id", order.id.to_string()),
+            ("status", format!("{:?}", order.status)),
+            ("total", order.total.to_string()),
+            (
+                "item_count",
+                order
+                    .items
+                    .iter()
+                    .map(|i| u64::from(i.qty))
+                    .sum::<u64>()
+                    .to_string(),
+            ),
+            (
+                "cancel_reason",
+                order
+                    .cancellation
+                    .as_ref()
+                    .map(|c| c.reason.clone())
+                    .unwrap_or_default(),
+            ),
+        ];
+        let fill = |text: &str| {
+            values.iter().fold(text.to_string(), |text, (key, value)| {
+                text.replace(&format!("{{{{{key}}}}}"), value)
+            })
+        };
+        Email {
+            to: order.email.clone(),
+            subject: fill(&template.subject),
+            body: fill(&template.body),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use orders_types::domain::money::Money;
+    use orders_types::domain::order::{Order, OrderItem};
+
+    fn notification(kind: NotificationKind) -> Notification {
+        let mut order = Order::new(
+            "Ona".into(),
+            "ona@example.com".into(),
+            vec![OrderItem {
+                name: "Widget".into(),
+                qty: 3,
+                unit_price: Money::usd(250),
+                weight_grams: 0,
+                sku: None,
+                description: None,
+                metadata: Default::default(),
+                discount_cents: 0,
+            }],
+        )
+        .unwrap();
+        if kind == NotificationKind::Cancelled {
+            order.cancel("out of stock").unwrap();
+        }
+        Notification { kind, order }
+    }
+
+    #[test]
+    fn builtin_templates_fill_placeholders() {
+        let templates = EmailTemplates::default();
+        let created = notification(NotificationKind::Created);
+        let email = templates.render(&created);
+        assert_eq!(email.to, "ona@example.com");
+        assert_eq!(
+            email.subject,
+            format!("We received your order {}", created.order.id)
+        );
+        assert!(email.body.contains("3 item(s), 7.50 USD in total"));
+
+        let email = templates.render(&notification(NotificationKind::Cancelled));
+        assert!(email.body.contains("cancelled: out of stock."));
+    }
+
+    #[test]
+    fn templates_on_disk_override_the_builtins() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("shipped.txt"),
+            "Subject: {{customer_name}}, it shipped\n\nStatus: {{status}}\n",
+        )
+        .unwrap();
+        let templates = EmailTemplates::load_dir(dir.path()).unwrap();
+        let email = templates.render(&notification(NotificationKind::Shipped));
+        assert_eq!(email.subject, "Ona, it shipped");
+        assert_eq!(email.body, "Status: Pending\n");
+        // No file for `created`: the built-in one is kept.
+        assert!(templates
+            .render(&notification(NotificationKind::Created))
+            .subject
+            .starts_with("We received"));
+
+        std::fs::write(dir.path().join("created.txt"), "Hello\n").unwrap();
+        assert!(EmailTemplates::load_dir(dir.path()).is_err());
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use orders_types::ports::inventory::{InventoryError, InventoryService, StockLine};
+use uuid::Uuid;
+
+#[derive(Default)]
+struct Stock {
+    on_hand: HashMap<String, u32>,
+    reservations: HashMap<Uuid, Vec<StockLine>>,
+}
+
+impl Stock {
+    fn reserved(&self, sku: &str, except: Uuid) -> u32 {
+        self.reservations
+            .iter()
+            .filter(|(id, _)| **id != except)
+            .flat_map(|(_, lines)| lines)
+            .filter(|l| l.sku == sku)
+            .map(|l| l.qty)
+            .sum()
+    }
+}
+
+/// Stock levels kept in process. Only SKUs given a level with
+/// [`InMemoryInventory::set_stock`] are tracked; anything else is treated as
+/// always available.
+#[derive(Clone, Default)]
+pub struct InMemoryInventory {
+    stock: Arc<Mutex<Stock>>,
+}
+
+impl InMemoryInventory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the units of `sku` on hand, including any already reserved.
+    pub fn set_stock(&self, sku: impl Into<String>, qty: u32) {
+        self.lock().on_hand.insert(sku.into(), qty);
+    }
+
+    /// Units of `sku` neither reserved nor committed; `None` when untracked.
+    pub fn available(&self, sku: &str) -> Option<u32> {
+        let stock = self.lock();
+        let on_hand = *stock.on_hand.get(sku)?;
+        Some(on_hand.saturating_sub(stock.reserved(sku, Uuid::nil())))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Stock> {
+        self.stock.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait]
+impl InventoryService for InMemoryInventory {
+    async fn reserve(&self, order_id: Uuid, lines: &[StockLine]) -> Result<(), InventoryError> {
+        let mut stock = self.lock();
+        for line in lines {
+            let Some(&on_hand) = stock.on_hand.get(&line.sku) else {
+                continue;
+            };
+            let available = on_hand.saturating_sub(stock.reserved(&line.sku, order_id));
+            if line.qty > available {
+                return Err(InventoryError::InsufficientStock {
+                    sku: line.sku.clone(),
+                    requested: line.qty,
+                    available,
+                });
+            }
+        }
+        stock.reservations.insert(order_id, lines.to_vec());
+        Ok(())
+    }
+
+    async fn release(&self, order_id: Uuid) -> Result<(), InventoryError> {
+        self.lock().reservations.remove(&order_id);
+        Ok(())
+    }
+
+    async fn commit(&self, order_id: Uuid) -> Result<(), InventoryError> {
+        let mut stock = self.lock();
+        let Some(lines) = stock.reservations.remove(&order_id) else {
+            return Ok(());
+        };
+        for line in lines {
+            if let Some(on_hand) = stock.on_hand.get_mut(&line.sku) {
+                *on_hand = on_hand.saturating_sub(line.qty);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(sku: &str, qty: u32) -> StockLine {
+        StockLine {
+            sku: sku.into(),
+            qty,
+        }
+    }
+
+    #[tokio::test]
+    async fn reservations_hold_stock_until_released_or_committed() {
+        let inventory = InMemoryInventory::new();
+        inventory.set_stock("WIDGET", 5);
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+
+        inventory
+            .reserve(a, &[line("WIDGET", 3), line("UNTRACKED", 100)])
+            .await
+            .unwrap();
+        assert_eq!(inventory.available("WIDGET"), Some(2));
+        assert_eq!(inventory.available("UNTRACKED"), None);
+        assert_eq!(
+            inventory.reserve(b, &[line("WIDGET", 3)]).await,
+            Err(InventoryError::InsufficientStock {
+                sku: "WIDGET".into(),
+                requested: 3,
+                available: 2,
+            })
+        );
+
+        // Re-reserving replaces the order's own hold.
+        inventory.reserve(a, &[line("WIDGET", 5)]).await.unwrap();
+        assert_eq!(inventory.available("WIDGET"), Some(0));
+        inventory.reserve(a, &[line("WIDGET", 1)]).await.unwrap();
+
+        inventory.commit(a).await.unwrap();
+        assert_eq!(inventory.available("WIDGET"), Some(4));
+        inventory.reserve(b, &[line("WIDGET", 4)]).await.unwrap();
+        inventory.release(b).await.unwrap();
+        assert_eq!(inventory.available("WIDGET"), Some(4));
+    }
+}
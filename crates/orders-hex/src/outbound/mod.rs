@@ -0,0 +1,11 @@
+pub mod email;
+pub mod inventory;
+#[cfg(feature = "email-mx")]
+pub mod mx;
+pub mod payment;
+#[cfg(feature = "smtp")]
+pub mod smtp;
+#[cfg(feature = "stripe")]
+pub mod stripe;
+pub mod validator;
+pub mod webhook;
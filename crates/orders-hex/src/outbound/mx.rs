@@ -0,0 +1,68 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use hickory_resolver::{ResolveError, TokioResolver};
+use orders_types::ports::email_validation::EmailValidator;
+
+/// Refuses addresses whose domain can't receive mail: it doesn't exist,
+/// publishes a null MX (RFC 7505), or has neither MX nor address records to
+/// fall back to (RFC 5321 section 5.1). Lookups that fail any other way, or
+/// take longer than the timeout, accept the address.
+#[derive(Clone)]
+pub struct MxEmailValidator {
+    resolver: TokioResolver,
+    timeout: Duration,
+}
+
+impl MxEmailValidator {
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(2);
+
+    /// Resolve through the system's DNS configuration.
+    pub fn from_system_conf() -> anyhow::Result<Self> {
+        Ok(Self {
+            resolver: TokioResolver::builder_tokio()?.build(),
+            timeout: Self::DEFAULT_TIMEOUT,
+        })
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    async fn accepts_mail(&self, domain: &str) -> Result<bool, ResolveError> {
+        match self.resolver.mx_lookup(domain).await {
+            Ok(mx) => Ok(mx.iter().any(|record| !record.exchange().is_root())),
+            Err(e) if e.is_no_records_found() && !e.is_nx_domain() => {
+                match self.resolver.lookup_ip(domain).await {
+                    Ok(_) => Ok(true),
+                    Err(e) if e.is_no_records_found() => Ok(false),
+                    Err(e) => Err(e),
+                }
+            }
+            Err(e) if e.is_nx_domain() => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[async_trait]
+impl EmailValidator for MxEmailValidator {
+    async fn validate(&self, email: &str) -> Result<(), String> {
+        let Some((_, domain)) = email.rsplit_once('@') else {
+            return Ok(());
+        };
+        match tokio::time::timeout(self.timeout, self.accepts_mail(domain)).await {
+            Ok(Ok(true)) => Ok(()),
+            Ok(Ok(false)) => Err(format!("`{domain}` does not accept email")),
+            Ok(Err(e)) => {
+                tracing::warn!(%domain, error = %e, "MX lookup failed; accepting the address");
+                Ok(())
+            }
+            Err(_) => {
+                tracing::warn!(%domain, "MX lookup timed out; accepting the address");
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use orders_types::domain::money::Money;
+use orders_types::domain::order::Order;
+use orders_types::ports::payment_gateway::{PaymentError, PaymentGateway};
+
+/// What the mock provider knows about one payment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockPayment {
+    pub order_id: uuid::Uuid,
+    pub authorized: Money,
+    pub captured: i64,
+    pub refunded: i64,
+    /// The authorization was released without a capture.
+    pub voided: bool,
+}
+
+#[derive(Default)]
+struct Ledger {
+    payments: HashMap<String, MockPayment>,
+    next_id: u64,
+}
+
+/// In-memory payment provider for tests and local runs: nothing is charged,
+/// but amounts are tracked so over-capture and over-refund are refused like
+/// a real provider would.
+#[derive(Clone, Default)]
+pub struct MockPaymentGateway {
+    ledger: Arc<Mutex<Ledger>>,
+    decline_above: Option<i64>,
+    fail_captures: bool,
+}
+
+impl MockPaymentGateway {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decline authorizations for more than `amount_minor`, to exercise the
+    /// declined path.
+    pub fn declining_above(mut self, amount_minor: i64) -> Self {
+        self.decline_above = Some(amount_minor);
+        self
+    }
+
+    /// Answer every capture as unavailable, to exercise the path where an
+    /// authorization has to be voided.
+    pub fn failing_captures(mut self) -> Self {
+        self.fail_captures = true;
+        self
+    }
+
+    /// Every payment the provider has seen, in no particular order.
+    pub fn payments(&self) -> Vec<MockPayment> {
+        self.ledger().payments.values().cloned().collect()
+    }
+
+    pub fn payment(&self, payment_id: &str) -> Option<MockPayment> {
+        self.ledger().payments.get(payment_id).cloned()
+    }
+
+    fn ledger(&self) -> std::sync::MutexGuard<'_, Ledger> {
+        self.ledger.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn check_currency(payment: &MockPayment, amount: Money) -> Result<(), PaymentError> {
+    if payment.authorized.currency() != amount.currency() {
+        return Err(PaymentError::Declined(format!(
+            "payment is in {}, not {}",
+            payment.authorized.currency(),
+            amount.currency()
+        )));
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl PaymentGateway for MockPaymentGateway {
+    async fn authorize(&self, order: &Order, amount: Money) -> Result<String, PaymentError> {
+        if self
+            .decline_above
+            .is_some_and(|max| amount.amount_minor() > max)
+        {
+            return Err(PaymentError::Declined("insufficient funds".into()));
+        }
+        let mut ledger = self.ledger();
+        ledger.next_id += 1;
+        let id = format!("mock_pay_{}", ledger.next_id);
+        ledger.payments.insert(
+            id.clone(),
+            MockPayment {
+                order_id: order.id,
+                authorized: amount,
+                captured: 0,
+                refunded: 0,
+                voided: false,
+            },
+        );
+        Ok(id)
+    }
+
+    async fn capture(&self, payment_id: &str, amount: Money) -> Result<(), PaymentError> {
+        if self.fail_captures {
+            return Err(PaymentError::Unavailable("capture failed".into()));
+        }
+        let mut ledger = self.ledger();
+        let payment = ledger
+            .payments
+            .get_mut(payment_id)
+            .ok_or_else(|| PaymentError::Declined(format!("unknown payment {payment_id}")))?;
+        check_currency(payment, amount)?;
+        if payment.voided {
+            return Err(PaymentError::Declined("payment was voided".into()));
+        }
+        if payment.captured + amount.amount_minor() > payment.authorized.amount_minor() {
+            return Err(PaymentError::Declined(
+                "capture exceeds the authorized amount".into(),
+            ));
+        }
+        payment.captured += amount.amount_minor();
+        Ok(())
+    }
+
+    async fn void(&self, payment_id: &str) -> Result<(), PaymentError> {
+        let mut ledger = self.ledger();
+        let payment = ledger
+            .payments
+            .get_mut(payment_id)
+            .ok_or_else(|| PaymentError::Declined(format!("unknown payment {payment_id}")))?;
+        if payment.captured > 0 {
+            return Err(PaymentError::Declined(
+                "captured payments are refunded, not voided".into(),
+            ));
+        }
+        payment.voided = true;
+        Ok(())
+    }
+
+    async fn refund(&self, payment_id: &str, amount: Money) -> Result<(), PaymentError> {
+        let mut ledger = self.ledger();
+        let payment = ledger
+            .payments
+            .get_mut(payment_id)
+            .ok_or_else(|| PaymentError::Declined(format!("unknown payment {payment_id}")))?;
+        check_currency(payment, amount)?;
+        if payment.refunded + amount.amount_minor() > payment.captured {
+            return Err(PaymentError::Declined(
+                "refund exceeds the captured amount".into(),
+            ));
+        }
+        payment.refunded += amount.amount_minor();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use orders_types::domain::order::OrderItem;
+
+    #[tokio::test]
+    async fn tracks_captures_and_refunds() {
+        let order = Order::new(
+            "Lu".into(),
+            "lu@example.com".into(),
+            vec![OrderItem {
+                name: "Widget".into(),
+                qty: 1,
+                unit_price: Money::usd(1_000),
+                weight_grams: 0,
+                sku: None,
+                description: None,
+                metadata: Default::default(),
+                discount_cents: 0,
+            }],
+        )
+        .unwrap();
+        let gateway = MockPaymentGateway::new().declining_above(5_000);
+        assert!(matches!(
+            gateway.authorize(&order, Money::usd(5_001)).await,
+            Err(PaymentError::Declined(_))
+        ));
+
+        let id = gateway.authorize(&order, order.total).await.unwrap();
+        assert!(gateway.refund(&id, order.total).await.is_err());
+        gateway.capture(&id, order.total).await.unwrap();
+        assert!(gateway.capture(&id, Money::usd(1)).await.is_err());
+        gateway.refund(&id, Money::usd(400)).await.unwrap();
+        assert!(gateway.refund(&id, Money::usd(601)).await.is_err());
+
+        let payment = gateway.payment(&id).unwrap();
+        assert_eq!(payment.order_id, order.id);
+        assert_eq!((payment.captured, payment.refunded), (1_000, 400));
+    }
+}
@@ -0,0 +1,159 @@
+use async_trait::async_trait;
+use orders_types::domain::money::Money;
+use orders_types::domain::order::Order;
+use orders_types::ports::payment_gateway::{PaymentError, PaymentGateway};
+use serde::Deserialize;
+
+/// Charges orders through Stripe PaymentIntents with manual capture.
+///
+/// This is a skeleton: it charges the payment method given to
+/// [`StripePaymentGateway::with_payment_method`] (e.g. `pm_card_visa` with a
+/// test key) because collecting one from the customer is up to the checkout
+/// front end, which this service doesn't have yet.
+#[derive(Clone)]
+pub struct StripePaymentGateway {
+    secret_key: String,
+    base_url: String,
+    payment_method: Option<String>,
+    client: reqwest::Client,
+}
+
+#[derive(Deserialize)]
+struct PaymentIntent {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct ErrorEnvelope {
+    error: StripeError,
+}
+
+#[derive(Deserialize)]
+struct StripeError {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    message: Option<String>,
+}
+
+impl StripePaymentGateway {
+    pub fn new(secret_key: impl Into<String>) -> Self {
+        Self {
+            secret_key: secret_key.into(),
+            base_url: "https://api.stripe.com".into(),
+            payment_method: None,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Talk to another Stripe-compatible endpoint, e.g. `stripe-mock`.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_payment_method(mut self, payment_method: impl Into<String>) -> Self {
+        self.payment_method = Some(payment_method.into());
+        self
+    }
+
+    async fn post<T: for<'de> Deserialize<'de>>(
+        &self,
+        path: &str,
+        form: &[(&str, String)],
+        idempotency_key: Option<String>,
+    ) -> Result<T, PaymentError> {
+        let mut req = self
+            .client
+            .post(format!("{}{path}", self.base_url))
+            .bearer_auth(&self.secret_key)
+            .form(form);
+        if let Some(key) = idempotency_key {
+            req = req.header("Idempotency-Key", key);
+        }
+        let res = req
+            .send()
+            .await
+            .map_err(|e| PaymentError::Unavailable(e.to_string()))?;
+        let status = res.status();
+        if status.is_success() {
+            return res
+                .json()
+                .await
+                .map_err(|e| PaymentError::Unavailable(e.to_string()));
+        }
+        let error = res.json::<ErrorEnvelope>().await.ok().map(|e| e.error);
+        let message = error
+            .as_ref()
+            .and_then(|e| e.message.clone())
+            .unwrap_or_else(|| format!("stripe answered {status}"));
+        // Card and request errors won't succeed on retry; the rest might.
+        match error.map(|e| e.kind) {
+            Some(kind) if kind == "card_error" || kind == "invalid_request_error" => {
+                Err(PaymentError::Declined(message))
+            }
+            _ => Err(PaymentError::Unavailable(message)),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Ignored {}
+
+#[async_trait]
+impl PaymentGateway for StripePaymentGateway {
+    async fn authorize(&self, order: &Order, amount: Money) -> Result<String, PaymentError> {
+        let mut form = vec![
+            ("amount", amount.amount_minor().to_string()),
+            ("currency", amount.currency().as_str().to_ascii_lowercase()),
+            ("capture_method", "manual".to_string()),
+            ("metadata[order_id]", order.id.to_string()),
+            ("metadata[tenant_id]", order.tenant_id.to_string()),
+        ];
+        if let Some(pm) = &self.payment_method {
+            form.push(("payment_method", pm.clone()));
+            form.push(("confirm", "true".to_string()));
+        }
+        let intent: PaymentIntent = self
+            .post(
+                "/v1/payment_intents",
+                &form,
+                Some(format!("authorize-{}", order.id)),
+            )
+            .await?;
+        Ok(intent.id)
+    }
+
+    async fn capture(&self, payment_id: &str, amount: Money) -> Result<(), PaymentError> {
+        self.post::<Ignored>(
+            &format!("/v1/payment_intents/{payment_id}/capture"),
+            &[("amount_to_capture", amount.amount_minor().to_string())],
+            Some(format!("capture-{payment_id}")),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn void(&self, payment_id: &str) -> Result<(), PaymentError> {
+        self.post::<Ignored>(
+            &format!("/v1/payment_intents/{payment_id}/cancel"),
+            &[],
+            Some(format!("void-{payment_id}")),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn refund(&self, payment_id: &str, amount: Money) -> Result<(), PaymentError> {
+        self.post::<Ignored>(
+            "/v1/refunds",
+            &[
+                ("payment_intent", payment_id.to_string()),
+                ("amount", amount.amount_minor().to_string()),
+            ],
+            Some(format!("refund-{payment_id}")),
+        )
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,61 @@
+use async_trait::async_trait;
+use orders_types::domain::correlation::CorrelationId;
+use orders_types::domain::order::Order;
+use orders_types::ports::validation::{OrderValidator, Verdict};
+use serde::Serialize;
+
+use crate::application::correlation;
+
+/// Asks an external endpoint to accept candidate orders.
+///
+/// The endpoint receives `POST {"order":{...}}` and answers `2xx` with a
+/// [`Verdict`]; any other status is a failure, not a rejection. Timeouts are
+/// enforced by the caller.
+#[derive(Clone)]
+pub struct HttpOrderValidator {
+    url: String,
+    client: reqwest::Client,
+}
+
+#[derive(Serialize)]
+struct ValidationRequest<'a> {
+    order: &'a Order,
+}
+
+impl HttpOrderValidator {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl OrderValidator for HttpOrderValidator {
+    async fn validate(&self, order: &Order) -> Result<Verdict, String> {
+        let mut req = self
+            .client
+            .post(&self.url)
+            .json(&ValidationRequest { order });
+        if let Some(id) = correlation::current() {
+            req = req.header(CorrelationId::HEADER, id.as_str());
+        }
+        let res = req.send().await.map_err(|e| e.to_string())?;
+        if !res.status().is_success() {
+            return Err(format!("validator answered {}", res.status()));
+        }
+        res.json().await.map_err(|e| e.to_string())
+    }
+
+    /// Any HTTP answer to a `HEAD` proves the endpoint is reachable; only
+    /// connection failures and timeouts count as down.
+    async fn ping(&self) -> Result<(), String> {
+        self.client
+            .head(&self.url)
+            .send()
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
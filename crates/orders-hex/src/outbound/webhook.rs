@@ -0,0 +1,41 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use orders_types::domain::cloudevent::CONTENT_TYPE;
+use orders_types::ports::webhook::{WebhookResponse, WebhookTransport};
+
+/// Delivers webhooks over HTTP with a per-request timeout.
+#[derive(Clone)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    pub fn new(timeout: Duration) -> anyhow::Result<Self> {
+        let client = reqwest::Client::builder().timeout(timeout).build()?;
+        Ok(Self { client })
+    }
+}
+
+#[async_trait]
+impl WebhookTransport for ReqwestTransport {
+    async fn post(
+        &self,
+        url: &str,
+        headers: &[(&'static str, String)],
+        body: String,
+    ) -> Result<WebhookResponse, String> {
+        let mut req = self
+            .client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, CONTENT_TYPE)
+            .body(body);
+        for (name, value) in headers {
+            req = req.header(*name, value);
+        }
+        let res = req.send().await.map_err(|e| e.to_string())?;
+        let status = res.status().as_u16();
+        let body = res.text().await.map_err(|e| e.to_string())?;
+        Ok(WebhookResponse { status, body })
+    }
+}
@@ -0,0 +1,142 @@
+//! An in-process server for integration tests.
+//!
+//! [`TestServer`] binds ephemeral ports, for the API and the admin listener
+//! when one is configured, before the server task starts, so requests sent
+//! right after [`TestServer::start`] returns are queued by the kernel instead
+//! of racing startup. Dropping it stops the server. For what only settles
+//! later, such as a socket the server binds itself or a reloaded
+//! certificate, [`wait_until`] polls instead of sleeping.
+
+use std::future::Future;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+use crate::application::order_service::OrderService;
+use crate::inbound::http::{HttpServer, HttpServerConfig};
+use orders_types::ports::order_repository::OrderRepository;
+
+/// How long [`wait_until`] keeps trying.
+const READY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Poll `check` until it holds, failing after a few seconds with `what`
+/// still not true.
+pub async fn wait_until<F, Fut>(what: &str, mut check: F) -> anyhow::Result<()>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = bool>,
+{
+    let deadline = tokio::time::Instant::now() + READY_TIMEOUT;
+    while !check().await {
+        anyhow::ensure!(
+            tokio::time::Instant::now() < deadline,
+            "{what} within {READY_TIMEOUT:?}"
+        );
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    Ok(())
+}
+
+/// A config for a server passed to [`TestServer::start`], which picks the
+/// port itself.
+pub fn config() -> HttpServerConfig {
+    HttpServerConfig {
+        port: "0".into(),
+        tls: None,
+        admin_addr: None,
+    }
+}
+
+/// A running server and the guard that stops it.
+pub struct TestServer {
+    addr: SocketAddr,
+    base_url: String,
+    admin_addr: Option<SocketAddr>,
+    shutdown: Option<oneshot::Sender<()>>,
+    task: Option<JoinHandle<anyhow::Result<()>>>,
+}
+
+impl TestServer {
+    /// Serve `repo` through a default [`OrderService`] with no extras.
+    pub async fn spawn<R>(repo: R) -> anyhow::Result<Self>
+    where
+        R: OrderRepository + Send + Sync + 'static,
+    {
+        Self::start(HttpServer::new(OrderService::new(repo), config()).await?).await
+    }
+
+    /// Serve `server` on `127.0.0.1` at a free port, ignoring the one in its
+    /// config, and likewise for the admin listener when
+    /// [`HttpServerConfig::admin_addr`] is set.
+    pub async fn start<R>(server: HttpServer<R>) -> anyhow::Result<Self>
+    where
+        R: OrderRepository + Send + Sync + 'static,
+    {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let scheme = if server.config.tls.is_some() {
+            // Test certificates are issued for `localhost`.
+            "https://localhost"
+        } else {
+            "http://127.0.0.1"
+        };
+        let admin = match server.config.admin_addr {
+            Some(_) => Some(tokio::net::TcpListener::bind("127.0.0.1:0").await?),
+            None => None,
+        };
+        let admin_addr = admin.as_ref().map(|a| a.local_addr()).transpose()?;
+        let (shutdown, stopped) = oneshot::channel();
+        let task = tokio::spawn(server.serve_on(listener, admin, async {
+            let _ = stopped.await;
+        }));
+        Ok(Self {
+            addr,
+            base_url: format!("{scheme}:{}", addr.port()),
+            admin_addr,
+            shutdown: Some(shutdown),
+            task: Some(task),
+        })
+    }
+
+    /// E.g. `http://127.0.0.1:41234`, without a trailing slash.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// `path` (starting with `/`) on this server.
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{path}", self.base_url)
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// The admin listener's base URL, when the config asks for one.
+    pub fn admin_url(&self) -> Option<String> {
+        self.admin_addr.map(|a| format!("http://{a}"))
+    }
+
+    /// Stop accepting connections, let in-flight requests finish, and return
+    /// how the server ended.
+    pub async fn shutdown(mut self) -> anyhow::Result<()> {
+        if let Some(tx) = self.shutdown.take() {
+            let _ = tx.send(());
+        }
+        match self.task.take() {
+            Some(task) => task.await?,
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for TestServer {
+    /// Connections still open, such as a WebSocket, don't keep it running.
+    fn drop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+    }
+}
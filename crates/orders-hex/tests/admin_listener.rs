@@ -0,0 +1,100 @@
+use async_trait::async_trait;
+use orders_hex::application::order_service::OrderService;
+use orders_hex::inbound::http::slo::{SloTargets, SloTracker};
+use orders_hex::inbound::http::{HttpServer, HttpServerConfig};
+use orders_hex::testing::{self, TestServer};
+use orders_repo::memory::InMemoryRepo;
+use orders_types::ports::migrations::{MigrationSource, MigrationStatus, PendingMigration};
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+
+struct OneBehind;
+
+#[async_trait]
+impl MigrationSource for OneBehind {
+    async fn migration_status(&self) -> anyhow::Result<MigrationStatus> {
+        Ok(MigrationStatus {
+            current_version: Some(17),
+            pending: vec![PendingMigration {
+                version: 18,
+                description: "create fulfillments".into(),
+            }],
+        })
+    }
+}
+
+#[tokio::test]
+async fn operational_endpoints_live_on_the_admin_listener_only() {
+    let service = OrderService::new(InMemoryRepo::new());
+    let server = HttpServer::new(
+        service,
+        HttpServerConfig {
+            // `TestServer` binds its own port for it.
+            admin_addr: Some("127.0.0.1:0".parse().unwrap()),
+            ..testing::config()
+        },
+    )
+    .await
+    .unwrap()
+    .with_slo(SloTracker::new(SloTargets::default()))
+    .with_config_dump(json!({"server_port": "3000", "jwt_secret": "[REDACTED]"}))
+    .with_migrations(OneBehind);
+    let server = TestServer::start(server).await.unwrap();
+    let public = server.base_url();
+    let admin = server.admin_url().unwrap();
+    let client = reqwest::Client::new();
+    let get = |url: String| client.get(url).send();
+
+    for path in [
+        "/healthz",
+        "/readyz",
+        "/metrics",
+        "/config",
+        "/debug/runtime",
+    ] {
+        let res = get(format!("{public}{path}")).await.unwrap();
+        assert_eq!(
+            res.status(),
+            StatusCode::NOT_FOUND,
+            "{path} on the public port"
+        );
+    }
+    let res = get(format!("{public}/v1/orders")).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    for path in ["/healthz", "/readyz", "/metrics"] {
+        let res = get(format!("{admin}{path}")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK, "{path} on the admin port");
+    }
+    let config: Value = get(format!("{admin}/config"))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(config["jwt_secret"], "[REDACTED]");
+    let runtime: Value = get(format!("{admin}/debug/runtime"))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(runtime["workers"].as_u64().unwrap() >= 1);
+    assert!(runtime["pid"].as_u64().is_some());
+    let migrations: Value = get(format!("{admin}/migrations"))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(
+        migrations,
+        json!({
+            "current_version": 17,
+            "pending": [{"version": 18, "description": "create fulfillments"}]
+        })
+    );
+    // The admin listener only carries operational routes.
+    let res = get(format!("{admin}/v1/orders")).await.unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,126 @@
+use orders_hex::application::api_key_service::ApiKeyService;
+use orders_hex::application::order_service::OrderService;
+use orders_hex::inbound::http::HttpServer;
+use orders_hex::testing::{self, TestServer};
+use orders_repo::memory::InMemoryRepo;
+use reqwest::StatusCode;
+
+#[tokio::test]
+async fn keys_are_required_and_scopes_enforced() {
+    let repo = InMemoryRepo::new();
+    let keys = ApiKeyService::new(repo.clone()).with_bootstrap_key("root-secret");
+    let server = HttpServer::new(OrderService::new(repo), testing::config())
+        .await
+        .unwrap()
+        .with_api_keys(keys);
+    let server = TestServer::start(server).await.unwrap();
+    let addr = server.base_url();
+
+    let client = reqwest::Client::new();
+    let res = client.get(format!("{addr}/health")).send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let res = client.get(format!("{addr}/orders")).send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let minted: serde_json::Value = client
+        .post(format!("{addr}/admin/api-keys"))
+        .header("x-api-key", "root-secret")
+        .json(&serde_json::json!({"name": "reporting", "scopes": ["read"]}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let secret = minted["secret"].as_str().unwrap().to_string();
+    assert!(minted.get("key_hash").is_none());
+
+    let res = client
+        .get(format!("{addr}/orders"))
+        .header("x-api-key", &secret)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = client
+        .delete(format!("{addr}/orders/{}", uuid::Uuid::new_v4()))
+        .header("x-api-key", &secret)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(body["error"], "forbidden");
+    assert_eq!(body["details"]["action"], "delete");
+    assert_eq!(body["details"]["role"], "viewer");
+    assert_eq!(body["details"]["required_role"], "admin");
+
+    // Operators may create but not delete.
+    let operator: serde_json::Value = client
+        .post(format!("{addr}/admin/api-keys"))
+        .header("x-api-key", "root-secret")
+        .json(&serde_json::json!({"name": "ops", "scopes": ["write"], "role": "operator"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let ops_secret = operator["secret"].as_str().unwrap();
+    let created: serde_json::Value = client
+        .post(format!("{addr}/orders"))
+        .header("x-api-key", ops_secret)
+        .json(&serde_json::json!({
+            "customer_name": "Ops",
+            "email": "ops@example.com",
+            "items": [{"name": "Widget", "qty": 1, "unit_price_cents": 100}]
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let res = client
+        .delete(format!("{addr}/orders/{}", created["id"].as_str().unwrap()))
+        .header("x-api-key", ops_secret)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    let res = client
+        .delete(format!("{addr}/orders/{}", created["id"].as_str().unwrap()))
+        .header("x-api-key", "root-secret")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NO_CONTENT);
+
+    let res = client
+        .get(format!("{addr}/admin/api-keys"))
+        .header("x-api-key", &secret)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+    let res = client
+        .delete(format!(
+            "{addr}/admin/api-keys/{}",
+            minted["id"].as_str().unwrap()
+        ))
+        .header("x-api-key", "root-secret")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NO_CONTENT);
+
+    let res = client
+        .get(format!("{addr}/orders"))
+        .header("x-api-key", &secret)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+}
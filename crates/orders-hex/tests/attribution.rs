@@ -0,0 +1,118 @@
+use jsonwebtoken::{encode, EncodingKey, Header};
+use orders_hex::application::api_key_service::ApiKeyService;
+use orders_hex::application::order_service::OrderService;
+use orders_hex::inbound::http::HttpServer;
+use orders_hex::testing::{self, TestServer};
+use orders_repo::memory::InMemoryRepo;
+use serde_json::{json, Value};
+
+const SECRET: &[u8] = b"attribution-test-secret";
+
+/// A token for the default tenant, naming user `sub` if given.
+fn token(sub: Option<&str>) -> String {
+    let exp = chrono::Utc::now().timestamp() + 600;
+    let mut claims = json!({ "tenant_id": "default", "exp": exp });
+    if let Some(sub) = sub {
+        claims["sub"] = sub.into();
+    }
+    encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(SECRET),
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn orders_say_who_created_and_last_changed_them() {
+    let repo = InMemoryRepo::new();
+    let keys = ApiKeyService::new(repo.clone()).with_bootstrap_key("root-secret");
+    let server = HttpServer::new(
+        OrderService::new(repo.clone()).with_audit(repo),
+        testing::config(),
+    )
+    .await
+    .unwrap()
+    .with_api_keys(keys)
+    .with_tenant_jwt_secret(SECRET);
+    let server = TestServer::start(server).await.unwrap();
+    let addr = server.base_url();
+    let client = reqwest::Client::new();
+
+    let created: Value = client
+        .post(format!("{addr}/orders"))
+        .header("x-api-key", "root-secret")
+        .bearer_auth(token(None))
+        .json(&json!({
+            "customer_name": "Ann",
+            "email": "ann@example.com",
+            "items": [{"name": "Widget", "qty": 1, "unit_price_cents": 500}]
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let id = created["id"].as_str().unwrap().to_string();
+    let get = || async {
+        client
+            .get(format!("{addr}/orders/{id}"))
+            .header("x-api-key", "root-secret")
+            .bearer_auth(token(None))
+            .send()
+            .await
+            .unwrap()
+            .json::<Value>()
+            .await
+            .unwrap()
+    };
+    let order = get().await;
+    assert_eq!(order["created_by"], "bootstrap");
+    assert_eq!(order["updated_by"], "bootstrap");
+
+    // A bearer token naming a user attributes the change to them.
+    client
+        .patch(format!("{addr}/orders/{id}/status"))
+        .header("x-api-key", "root-secret")
+        .bearer_auth(token(Some("u-42")))
+        .json(&json!({"status": "Confirmed"}))
+        .send()
+        .await
+        .unwrap()
+        .error_for_status()
+        .unwrap();
+    let order = get().await;
+    assert_eq!(order["created_by"], "bootstrap");
+    assert_eq!(order["updated_by"], "user:u-42");
+
+    let history: Vec<Value> = client
+        .get(format!("{addr}/orders/{id}/history"))
+        .header("x-api-key", "root-secret")
+        .bearer_auth(token(None))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let actors: Vec<&str> = history
+        .iter()
+        .map(|e| e["actor"].as_str().unwrap())
+        .collect();
+    assert_eq!(actors, ["bootstrap", "user:u-42"]);
+
+    let audit: Vec<Value> = client
+        .get(format!("{addr}/orders/{id}/audit"))
+        .header("x-api-key", "root-secret")
+        .bearer_auth(token(None))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let actors: Vec<&str> = audit.iter().map(|e| e["actor"].as_str().unwrap()).collect();
+    assert_eq!(actors, ["bootstrap", "user:u-42"]);
+    assert_eq!(audit[1]["after"]["updated_by"], "user:u-42");
+}
@@ -0,0 +1,94 @@
+use orders_hex::application::api_key_service::ApiKeyService;
+use orders_hex::application::order_service::OrderService;
+use orders_hex::inbound::http::HttpServer;
+use orders_hex::testing::{self, TestServer};
+use orders_repo::memory::InMemoryRepo;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn mutations_are_audited_with_actor_and_snapshots() {
+    let repo = InMemoryRepo::new();
+    let keys = ApiKeyService::new(repo.clone()).with_bootstrap_key("root-secret");
+    let server = HttpServer::new(
+        OrderService::new(repo.clone()).with_audit(repo),
+        testing::config(),
+    )
+    .await
+    .unwrap()
+    .with_api_keys(keys);
+    let server = TestServer::start(server).await.unwrap();
+    let addr = server.base_url();
+    let client = reqwest::Client::new();
+
+    let order: Value = client
+        .post(format!("{addr}/orders"))
+        .header("x-api-key", "root-secret")
+        .json(&json!({
+            "customer_name": "Ann",
+            "email": "ann@example.com",
+            "items": [{"name": "Widget", "qty": 1, "unit_price_cents": 500}]
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let id = order["id"].as_str().unwrap().to_string();
+    client
+        .patch(format!("{addr}/orders/{id}/status"))
+        .header("x-api-key", "root-secret")
+        .json(&json!({"status": "Shipped"}))
+        .send()
+        .await
+        .unwrap();
+    let res = client
+        .delete(format!("{addr}/orders/{id}"))
+        .header("x-api-key", "root-secret")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NO_CONTENT);
+
+    // The history outlives the order.
+    let res = client
+        .get(format!("{addr}/orders/{id}/audit"))
+        .header("x-api-key", "root-secret")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let entries: Vec<Value> = res.json().await.unwrap();
+    let actions: Vec<&str> = entries
+        .iter()
+        .map(|e| e["action"].as_str().unwrap())
+        .collect();
+    assert_eq!(actions, ["created", "status_changed", "deleted"]);
+    assert!(entries.iter().all(|e| e["actor"] == "bootstrap"));
+    assert!(entries[0]["before"].is_null());
+    assert_eq!(entries[1]["before"]["status"], "Pending");
+    assert_eq!(entries[1]["after"]["status"], "Shipped");
+    assert_eq!(entries[2]["before"]["status"], "Shipped");
+    assert!(entries[2]["after"].is_null());
+
+    let res = client
+        .get(format!("{addr}/orders/{}/audit", uuid::Uuid::new_v4()))
+        .header("x-api-key", "root-secret")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    let page: Vec<Value> = client
+        .get(format!("{addr}/admin/audit?limit=2&offset=1"))
+        .header("x-api-key", "root-secret")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let actions: Vec<&str> = page.iter().map(|e| e["action"].as_str().unwrap()).collect();
+    assert_eq!(actions, ["status_changed", "created"], "newest first");
+}
@@ -0,0 +1,69 @@
+use orders_hex::application::order_service::OrderService;
+use orders_hex::inbound::http::HttpServer;
+use orders_hex::testing::{self, TestServer};
+use orders_repo::memory::InMemoryRepo;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn cancel_records_the_reason_and_refuses_shipped_orders() {
+    let service = OrderService::new(InMemoryRepo::new());
+    let server = HttpServer::new(service, testing::config()).await.unwrap();
+    let server = TestServer::start(server).await.unwrap();
+    let addr = server.base_url();
+    let client = reqwest::Client::new();
+    let create = || async {
+        let order: Value = client
+            .post(format!("{addr}/orders"))
+            .json(&json!({
+                "customer_name": "Ann",
+                "email": "ann@example.com",
+                "items": [{"name": "Widget", "qty": 1, "unit_price_cents": 500}]
+            }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        order["id"].as_str().unwrap().to_string()
+    };
+
+    let id = create().await;
+    let res = client
+        .post(format!("{addr}/orders/{id}/cancel"))
+        .json(&json!({"reason": "customer request"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let cancelled: Value = res.json().await.unwrap();
+    assert_eq!(cancelled["status"], "Cancelled");
+    assert_eq!(cancelled["cancellation"]["reason"], "customer request");
+    assert!(cancelled["cancellation"]["cancelled_at"].is_string());
+
+    let id = create().await;
+    let res = client
+        .post(format!("{addr}/orders/{id}/cancel"))
+        .json(&json!({"reason": ""}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    client
+        .patch(format!("{addr}/orders/{id}/status"))
+        .json(&json!({"status": "Shipped"}))
+        .send()
+        .await
+        .unwrap();
+    let res = client
+        .post(format!("{addr}/orders/{id}/cancel"))
+        .json(&json!({"reason": "too late"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["code"], "INVALID_TRANSITION");
+}
@@ -0,0 +1,140 @@
+use orders_hex::application::order_service::OrderService;
+use orders_hex::inbound::http::HttpServer;
+use orders_hex::testing::{self, TestServer};
+use orders_repo::memory::InMemoryRepo;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+
+async fn start() -> TestServer {
+    let repo = InMemoryRepo::new();
+    let service = OrderService::new(repo.clone()).with_discounts(repo);
+    let server = HttpServer::new(service, testing::config()).await.unwrap();
+    TestServer::start(server).await.unwrap()
+}
+
+async fn order_with(
+    client: &reqwest::Client,
+    addr: &str,
+    code: &str,
+    cents: i64,
+) -> (StatusCode, Value) {
+    let res = client
+        .post(format!("{addr}/orders"))
+        .json(&json!({
+            "customer_name": "Ann",
+            "email": "ann@example.com",
+            "items": [{"name": "Widget", "qty": 1, "unit_price_cents": cents}],
+            "discount_code": code,
+        }))
+        .send()
+        .await
+        .unwrap();
+    (res.status(), res.json().await.unwrap())
+}
+
+#[tokio::test]
+async fn codes_are_applied_recorded_and_limited() {
+    let server = start().await;
+    let addr = server.base_url();
+    let client = reqwest::Client::new();
+
+    let res = client
+        .post(format!("{addr}/admin/discounts"))
+        .json(&json!({
+            "code": "welcome10",
+            "kind": {"type": "percentage", "bps": 1000},
+            "min_order": {"amount_minor": 1000, "currency": "USD"},
+            "max_uses": 1
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let created: Value = res.json().await.unwrap();
+    assert_eq!(created["code"], "WELCOME10");
+
+    let (status, body) = order_with(&client, addr, "WELCOME10", 999).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["details"]["errors"][0]["field"], "discount_code");
+
+    let (status, body) = order_with(&client, addr, "welcome10", 2500).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let id = body["id"].as_str().unwrap();
+    let order: Value = client
+        .get(format!("{addr}/orders/{id}"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(order["total_cents"], 2250);
+    assert_eq!(order["discount_cents"], 250);
+    assert_eq!(
+        order["discount"],
+        json!({"code": "WELCOME10", "amount_cents": 250})
+    );
+
+    let (status, body) = order_with(&client, addr, "WELCOME10", 2500).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(body["details"]["errors"][0]["message"]
+        .as_str()
+        .unwrap()
+        .contains("no uses left"));
+
+    let (status, _) = order_with(&client, addr, "NOPE", 2500).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let listed: Value = client
+        .get(format!("{addr}/admin/discounts"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(listed[0]["uses"], 1);
+
+    let res = client
+        .delete(format!("{addr}/admin/discounts/welcome10"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NO_CONTENT);
+    let res = client
+        .delete(format!("{addr}/admin/discounts/welcome10"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["code"], "DISCOUNT_NOT_FOUND");
+}
+
+#[tokio::test]
+async fn invalid_definitions_and_duplicates_are_rejected() {
+    let server = start().await;
+    let addr = server.base_url();
+    let client = reqwest::Client::new();
+    let create = |body: Value| {
+        client
+            .post(format!("{addr}/admin/discounts"))
+            .json(&body)
+            .send()
+    };
+
+    let res = create(json!({"code": "BIG", "kind": {"type": "percentage", "bps": 20000}}))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let five_off = json!({"code": "FIVE", "kind": {"type": "fixed", "amount": {"amount_minor": 500, "currency": "USD"}}});
+    assert_eq!(
+        create(five_off.clone()).await.unwrap().status(),
+        StatusCode::CREATED
+    );
+    assert_eq!(
+        create(five_off).await.unwrap().status(),
+        StatusCode::UNPROCESSABLE_ENTITY
+    );
+}
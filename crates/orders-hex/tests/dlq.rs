@@ -0,0 +1,206 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use orders_hex::application::dead_letters::DeadLetterService;
+use orders_hex::application::order_service::OrderService;
+use orders_hex::inbound::http::slo::{SloTargets, SloTracker};
+use orders_hex::inbound::http::HttpServer;
+use orders_hex::testing::{self, TestServer};
+use orders_repo::memory::InMemoryRepo;
+use orders_types::domain::dead_letter::{DeadLetter, DeadLetterDepth, DeadLetterState};
+use orders_types::domain::events::OrderEvent;
+use orders_types::domain::outbox::OutboxRecord;
+use orders_types::domain::tenant::TenantId;
+use orders_types::ports::dead_letter::DeadLetterStore;
+use orders_types::ports::order_repository::RepoError;
+use reqwest::StatusCode;
+
+/// Letters `1..` in a vector; only what the admin routes use does anything.
+#[derive(Default)]
+struct Letters(Mutex<Vec<DeadLetter>>);
+
+#[async_trait]
+impl DeadLetterStore for Letters {
+    async fn dead_letter(
+        &self,
+        consumer: &str,
+        record: &OutboxRecord,
+        attempts: u32,
+        error: &str,
+    ) -> Result<u64, RepoError> {
+        let mut letters = self.0.lock().unwrap();
+        let id = letters.len() as u64 + 1;
+        letters.push(DeadLetter {
+            id,
+            consumer: consumer.to_string(),
+            record: record.clone(),
+            attempts,
+            error: error.to_string(),
+            failed_at: Utc::now(),
+            state: DeadLetterState::Dead,
+            replay_requested_at: None,
+            replayed_at: None,
+        });
+        Ok(id)
+    }
+
+    async fn get_dead_letter(&self, id: u64) -> Result<Option<DeadLetter>, RepoError> {
+        Ok(self.0.lock().unwrap().iter().find(|l| l.id == id).cloned())
+    }
+
+    async fn list_dead_letters(
+        &self,
+        state: Option<DeadLetterState>,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<DeadLetter>, RepoError> {
+        Ok(self
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .filter(|l| state.is_none_or(|s| l.state == s))
+            .skip(offset)
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    async fn request_replay(&self, id: u64) -> Result<Option<DeadLetter>, RepoError> {
+        let mut letters = self.0.lock().unwrap();
+        let Some(letter) = letters.iter_mut().find(|l| l.id == id) else {
+            return Ok(None);
+        };
+        if letter.state == DeadLetterState::Dead {
+            letter.state = DeadLetterState::ReplayRequested;
+            letter.replay_requested_at = Some(Utc::now());
+        }
+        Ok(Some(letter.clone()))
+    }
+
+    async fn replays_requested(
+        &self,
+        _consumer: &str,
+        _limit: usize,
+    ) -> Result<Vec<DeadLetter>, RepoError> {
+        Ok(Vec::new())
+    }
+
+    async fn finish_replay(
+        &self,
+        id: u64,
+        _attempts: u32,
+        _error: Option<&str>,
+    ) -> Result<(), RepoError> {
+        let mut letters = self.0.lock().unwrap();
+        letters[id as usize - 1].state = DeadLetterState::Replayed;
+        Ok(())
+    }
+
+    async fn dead_letter_depth(&self) -> Result<Vec<DeadLetterDepth>, RepoError> {
+        let letters = self.0.lock().unwrap();
+        let mut depth: Vec<DeadLetterDepth> = Vec::new();
+        for l in letters.iter() {
+            match depth
+                .iter_mut()
+                .find(|d| d.consumer == l.consumer && d.state == l.state)
+            {
+                Some(d) => d.count += 1,
+                None => depth.push(DeadLetterDepth {
+                    consumer: l.consumer.clone(),
+                    state: l.state,
+                    count: 1,
+                }),
+            }
+        }
+        Ok(depth)
+    }
+}
+
+#[tokio::test]
+async fn dead_letters_are_listed_replayed_and_counted() {
+    let store = Arc::new(Letters::default());
+    for seq in [3, 4] {
+        let record = OutboxRecord {
+            seq,
+            recorded_at: Utc::now(),
+            event: OrderEvent::Deleted {
+                id: uuid::Uuid::new_v4(),
+                tenant_id: TenantId::default(),
+            },
+        };
+        store
+            .dead_letter("webhook", &record, 5, "503 Service Unavailable")
+            .await
+            .unwrap();
+    }
+    store.finish_replay(2, 1, None).await.unwrap();
+    let letters = DeadLetterService::new(store);
+    letters.refresh_depth().await.unwrap();
+
+    let server = HttpServer::new(OrderService::new(InMemoryRepo::new()), testing::config())
+        .await
+        .unwrap()
+        .with_slo(SloTracker::new(SloTargets::default()))
+        .with_dead_letters(letters);
+    let server = TestServer::start(server).await.unwrap();
+    let addr = server.base_url();
+    let client = reqwest::Client::new();
+
+    let dead: serde_json::Value = client
+        .get(format!("{addr}/admin/dlq?state=dead"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let dead = dead.as_array().unwrap();
+    assert_eq!(dead.len(), 1);
+    assert_eq!(dead[0]["id"], 1);
+    assert_eq!(dead[0]["record"]["seq"], 3);
+    assert_eq!(dead[0]["error"], "503 Service Unavailable");
+    let res = client
+        .get(format!("{addr}/admin/dlq?state=lost"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let res = client
+        .post(format!("{addr}/admin/dlq/1/replay"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::ACCEPTED);
+    let letter: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(letter["state"], "replay_requested");
+    let res = client
+        .post(format!("{addr}/admin/dlq/2/replay"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+    let res = client
+        .post(format!("{addr}/admin/dlq/99/replay"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(body["code"], "DEAD_LETTER_NOT_FOUND");
+
+    let metrics = client
+        .get(format!("{addr}/metrics"))
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert!(metrics.contains("# TYPE orders_dlq_depth gauge"));
+    assert!(metrics.contains("orders_dlq_depth{consumer=\"webhook\",state=\"replay_requested\"} 1"));
+    assert!(metrics.contains("orders_dlq_depth{consumer=\"webhook\",state=\"replayed\"} 1"));
+}
@@ -0,0 +1,78 @@
+use orders_hex::application::order_service::OrderService;
+use orders_hex::inbound::http::HttpServer;
+use orders_hex::testing::{self, TestServer};
+use orders_repo::memory::InMemoryRepo;
+use reqwest::header::{ETAG, IF_NONE_MATCH};
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn gets_answer_304_until_the_order_changes() {
+    let service = OrderService::new(InMemoryRepo::new());
+    let server = HttpServer::new(service, testing::config()).await.unwrap();
+    let server = TestServer::start(server).await.unwrap();
+    let addr = server.base_url();
+    let client = reqwest::Client::new();
+    let order: Value = client
+        .post(format!("{addr}/orders"))
+        .json(&json!({
+            "customer_name": "Ann",
+            "email": "ann@example.com",
+            "items": [{"name": "Widget", "qty": 1, "unit_price_cents": 500}]
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let id = order["id"].as_str().unwrap();
+    let get = |url: String, etag: Option<String>| {
+        let req = client.get(url);
+        match etag {
+            Some(etag) => req.header(IF_NONE_MATCH, etag),
+            None => req,
+        }
+        .send()
+    };
+    let etag_of = |res: &reqwest::Response| res.headers()[ETAG].to_str().unwrap().to_string();
+
+    let res = get(format!("{addr}/orders/{id}"), None).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let tag = etag_of(&res);
+    assert!(tag.starts_with("W/\""));
+
+    let res = get(format!("{addr}/orders/{id}"), Some(tag.clone()))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(etag_of(&res), tag);
+    assert!(res.bytes().await.unwrap().is_empty());
+
+    let list = get(format!("{addr}/orders"), None).await.unwrap();
+    assert_eq!(list.status(), StatusCode::OK);
+    let list_tag = etag_of(&list);
+    let res = get(format!("{addr}/orders"), Some(list_tag.clone()))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+
+    let res = client
+        .patch(format!("{addr}/orders/{id}/status"))
+        .json(&json!({"status": "Confirmed"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = get(format!("{addr}/orders/{id}"), Some(tag.clone()))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_ne!(etag_of(&res), tag);
+    let res = get(format!("{addr}/orders"), Some(list_tag.clone()))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_ne!(etag_of(&res), list_tag);
+}
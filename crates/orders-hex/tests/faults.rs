@@ -0,0 +1,75 @@
+use orders_hex::testing::TestServer;
+use orders_repo::fault::{Fault, FaultInjectingRepo, FaultKind, FaultPlan};
+use orders_repo::memory::InMemoryRepo;
+use orders_repo::retry::{RetryPolicy, RetryingRepo};
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+async fn create(client: &reqwest::Client, addr: &str) -> reqwest::Response {
+    client
+        .post(format!("{addr}/orders"))
+        .json(&json!({
+            "customer_name": "Ann",
+            "email": "ann@example.com",
+            "items": [{"name": "Widget", "qty": 1, "unit_price_cents": 500}]
+        }))
+        .send()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn storage_faults_surface_as_their_status() {
+    let plan = FaultPlan::new()
+        .with_op("create", Fault::failing(1.0))
+        .with_op("begin", Fault::failing(1.0))
+        .with_op("get", Fault::failing(1.0).with_kind(FaultKind::Backend));
+    let server = TestServer::spawn(FaultInjectingRepo::new(InMemoryRepo::new(), plan))
+        .await
+        .unwrap();
+    let addr = server.base_url();
+    let client = reqwest::Client::new();
+
+    let res = create(&client, addr).await;
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["code"], "UNAVAILABLE");
+
+    let res = client
+        .get(format!("{addr}/orders/{}", uuid::Uuid::new_v4()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+#[tokio::test]
+async fn retries_hide_transient_faults_from_clients() {
+    let faulty = FaultInjectingRepo::new(
+        InMemoryRepo::new(),
+        FaultPlan::new().with_default(Fault::failing(0.3)),
+    )
+    .with_seed(1);
+    let repo = RetryingRepo::new(faulty.clone()).with_policy(RetryPolicy {
+        max_attempts: 30,
+        base_delay: Duration::ZERO,
+        max_delay: Duration::ZERO,
+    });
+    let server = TestServer::spawn(repo).await.unwrap();
+    let addr = server.base_url();
+    let client = reqwest::Client::new();
+
+    for _ in 0..20 {
+        let res = create(&client, addr).await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+        let order: Value = res.json().await.unwrap();
+        let res = client
+            .get(format!("{addr}/orders/{}", order["id"].as_str().unwrap()))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+    assert!(faulty.injected() > 0);
+}
@@ -0,0 +1,136 @@
+use orders_hex::application::api_key_service::ApiKeyService;
+use orders_hex::application::order_service::OrderService;
+use orders_hex::inbound::http::HttpServer;
+use orders_hex::testing::{self, TestServer};
+use orders_repo::sqlite::SqliteRepo;
+use orders_types::ports::outbox::OutboxStore;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn admins_can_force_a_status_the_state_machine_forbids() {
+    let dir = tempfile::tempdir().unwrap();
+    let url = format!("sqlite://{}", dir.path().join("orders.db").display());
+    let repo = SqliteRepo::new(&url).await.unwrap().with_outbox();
+    let keys = ApiKeyService::new(repo.clone()).with_bootstrap_key("root-secret");
+    let service = OrderService::new(repo.clone()).with_audit(repo.clone());
+    let mut events = service.subscribe();
+    let server = HttpServer::new(service, testing::config())
+        .await
+        .unwrap()
+        .with_api_keys(keys);
+    let server = TestServer::start(server).await.unwrap();
+    let addr = server.base_url();
+    let client = reqwest::Client::new();
+
+    let order: Value = client
+        .post(format!("{addr}/orders"))
+        .header("x-api-key", "root-secret")
+        .json(&json!({
+            "customer_name": "Ann",
+            "email": "ann@example.com",
+            "items": [{"name": "Widget", "qty": 1, "unit_price_cents": 500}]
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let id = order["id"].as_str().unwrap().to_string();
+    let res = client
+        .patch(format!("{addr}/orders/{id}/status"))
+        .header("x-api-key", "root-secret")
+        .json(&json!({"status": "Cancelled"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let res = client
+        .patch(format!("{addr}/orders/{id}/status"))
+        .header("x-api-key", "root-secret")
+        .json(&json!({"status": "Pending"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+
+    let operator: Value = client
+        .post(format!("{addr}/admin/api-keys"))
+        .header("x-api-key", "root-secret")
+        .json(&json!({"name": "ops", "scopes": ["write"], "role": "operator"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let force = |key: String, body: Value| {
+        client
+            .post(format!("{addr}/admin/orders/{id}/force-status"))
+            .header("x-api-key", key)
+            .json(&body)
+            .send()
+    };
+    let body = json!({"status": "Pending", "reason": "cancelled by mistake"});
+    let res = force(operator["secret"].as_str().unwrap().into(), body.clone())
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    let res = force(
+        "root-secret".into(),
+        json!({"status": "Pending", "reason": " "}),
+    )
+    .await
+    .unwrap();
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let res = force("root-secret".into(), body.clone()).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let order: Value = res.json().await.unwrap();
+    assert_eq!(order["status"], "Pending");
+    let res = force("root-secret".into(), body).await.unwrap();
+    assert_eq!(res.status(), StatusCode::CONFLICT, "already Pending");
+
+    let entries: Vec<Value> = client
+        .get(format!("{addr}/orders/{id}/audit"))
+        .header("x-api-key", "root-secret")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let last = entries.last().unwrap();
+    assert_eq!(last["action"], "status_overridden");
+    assert_eq!(last["reason"], "cancelled by mistake");
+    assert_eq!(last["before"]["status"], "Cancelled");
+    assert_eq!(last["after"]["status"], "Pending");
+
+    let history: Vec<Value> = client
+        .get(format!("{addr}/orders/{id}/history"))
+        .header("x-api-key", "root-secret")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(history.last().unwrap()["note"], "cancelled by mistake");
+
+    let mut kinds = Vec::new();
+    while let Ok(envelope) = events.try_recv() {
+        kinds.push(envelope.event.kind());
+    }
+    assert_eq!(kinds, ["created", "updated", "status_overridden"]);
+
+    // The outbox records the override as what it was, too.
+    let outbox: Vec<_> = repo
+        .outbox_after(0, 10)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|record| record.event.kind())
+        .collect();
+    assert_eq!(outbox, ["created", "updated", "status_overridden"]);
+}
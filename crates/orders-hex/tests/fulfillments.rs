@@ -0,0 +1,89 @@
+use orders_hex::application::order_service::OrderService;
+use orders_hex::inbound::http::HttpServer;
+use orders_hex::testing::{self, TestServer};
+use orders_repo::memory::InMemoryRepo;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn partial_shipments_ship_the_order_once_everything_went_out() {
+    let service = OrderService::new(InMemoryRepo::new());
+    let server = HttpServer::new(service, testing::config()).await.unwrap();
+    let server = TestServer::start(server).await.unwrap();
+    let addr = server.base_url();
+    let client = reqwest::Client::new();
+    let order: Value = client
+        .post(format!("{addr}/orders"))
+        .json(&json!({
+            "customer_name": "Ann",
+            "email": "ann@example.com",
+            "items": [
+                {"name": "Widget", "qty": 2, "unit_price_cents": 500},
+                {"name": "Gadget", "qty": 1, "unit_price_cents": 900}
+            ]
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let id = order["id"].as_str().unwrap();
+    let ship = |items: Value| {
+        client
+            .post(format!("{addr}/orders/{id}/fulfillments"))
+            .json(&json!({"items": items, "carrier": "UPS", "tracking_number": "1Z999"}))
+            .send()
+    };
+
+    let res = ship(json!([{"position": 0, "qty": 1}])).await.unwrap();
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let outcome: Value = res.json().await.unwrap();
+    assert_eq!(outcome["order"]["status"], "Pending");
+    assert_eq!(outcome["fulfillment"]["carrier"], "UPS");
+    assert!(outcome["fulfillment"]["shipped_at"].is_string());
+
+    let res = ship(json!([{"position": 0, "qty": 2}])).await.unwrap();
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["details"]["errors"][0]["field"], "items[0].qty");
+
+    let res = ship(json!([{"position": 0, "qty": 1}, {"position": 1, "qty": 1}]))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let outcome: Value = res.json().await.unwrap();
+    assert_eq!(outcome["order"]["status"], "Shipped");
+
+    let shipments: Value = client
+        .get(format!("{addr}/orders/{id}/fulfillments"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(shipments.as_array().unwrap().len(), 2);
+    assert_eq!(shipments[1]["items"][1], json!({"position": 1, "qty": 1}));
+
+    let history: Value = client
+        .get(format!("{addr}/orders/{id}/history"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(history[1]["note"], "all items fulfilled");
+
+    let res = ship(json!([{"position": 0, "qty": 1}])).await.unwrap();
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+
+    let missing = uuid::Uuid::new_v4();
+    let res = client
+        .get(format!("{addr}/orders/{missing}/fulfillments"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,143 @@
+use orders_hex::application::api_key_service::ApiKeyService;
+use orders_hex::application::order_service::OrderService;
+use orders_hex::inbound::http::HttpServer;
+use orders_hex::testing::{self, TestServer};
+use orders_repo::memory::InMemoryRepo;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn customer_data_is_exported_then_erased_keeping_totals() {
+    let repo = InMemoryRepo::new();
+    let keys = ApiKeyService::new(repo.clone()).with_bootstrap_key("root-secret");
+    let server = HttpServer::new(
+        OrderService::new(repo.clone()).with_audit(repo),
+        testing::config(),
+    )
+    .await
+    .unwrap()
+    .with_api_keys(keys);
+    let server = TestServer::start(server).await.unwrap();
+    let addr = server.base_url();
+    let client = reqwest::Client::new();
+
+    let mut totals = Vec::new();
+    for (name, email) in [
+        ("Ann", "ann@example.com"),
+        ("Ann", "ANN@example.com"),
+        ("Bo", "bo@example.com"),
+    ] {
+        let order: Value = client
+            .post(format!("{addr}/orders"))
+            .header("x-api-key", "root-secret")
+            .json(&json!({
+                "customer_name": name,
+                "email": email,
+                "items": [{"name": "Widget", "qty": 2, "unit_price_cents": 500}],
+                "shipping_address": {
+                    "line1": "1 Main St",
+                    "city": "Springfield",
+                    "region": "IL",
+                    "postal_code": "62701",
+                    "country": "US"
+                }
+            }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        totals.push(order["total"].clone());
+    }
+
+    let res = client
+        .get(format!("{addr}/customers/ann@example.com/export"))
+        .header("x-api-key", "root-secret")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let export: Value = res.json().await.unwrap();
+    assert_eq!(export["email"], "ann@example.com");
+    assert_eq!(export["orders"].as_array().unwrap().len(), 2);
+
+    let csv = client
+        .get(format!("{addr}/customers/ann@example.com/export"))
+        .header("x-api-key", "root-secret")
+        .header("accept", "text/csv")
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert_eq!(csv.lines().count(), 3);
+    assert!(csv.starts_with("id,"));
+
+    let res = client
+        .delete(format!("{addr}/customers/ann@example.com/data"))
+        .header("x-api-key", "root-secret")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let report: Value = res.json().await.unwrap();
+    assert_eq!(report["orders"], 2);
+    // A `created` and two `exported` entries per order.
+    assert_eq!(report["audit_entries"], 6);
+
+    let export: Value = client
+        .get(format!("{addr}/customers/ann@example.com/export"))
+        .header("x-api-key", "root-secret")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(export["orders"].as_array().unwrap().is_empty());
+
+    let orders: Value = client
+        .get(format!("{addr}/orders"))
+        .header("x-api-key", "root-secret")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let orders = orders["orders"].as_array().unwrap();
+    let erased: Vec<&Value> = orders
+        .iter()
+        .filter(|o| o["email"] == "erased@invalid")
+        .collect();
+    assert_eq!(erased.len(), 2);
+    for order in &erased {
+        assert_eq!(order["customer_name"], "[erased]");
+        assert_eq!(order["shipping_address"]["line1"], "[erased]");
+        assert_eq!(order["shipping_address"]["country"], "US");
+        assert_eq!(order["total"], totals[0]);
+    }
+    assert!(orders.iter().any(|o| o["email"] == "bo@example.com"));
+
+    // Earlier audit copies are scrubbed and the erasure itself is recorded.
+    let id = erased[0]["id"].as_str().unwrap();
+    let entries: Vec<Value> = client
+        .get(format!("{addr}/orders/{id}/audit"))
+        .header("x-api-key", "root-secret")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let actions: Vec<&str> = entries
+        .iter()
+        .map(|e| e["action"].as_str().unwrap())
+        .collect();
+    assert_eq!(actions, ["created", "exported", "exported", "anonymized"]);
+    assert!(!serde_json::to_string(&entries)
+        .unwrap()
+        .contains("example.com"));
+}
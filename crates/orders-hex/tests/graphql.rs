@@ -0,0 +1,136 @@
+#![cfg(feature = "graphql")]
+
+use orders_hex::application::order_service::OrderService;
+use orders_hex::inbound::http::HttpServer;
+use orders_hex::testing::{self, TestServer};
+use orders_repo::memory::InMemoryRepo;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+
+async fn start(graphiql: bool) -> TestServer {
+    let server = HttpServer::new(OrderService::new(InMemoryRepo::new()), testing::config())
+        .await
+        .unwrap()
+        .with_graphql(graphiql);
+    TestServer::start(server).await.unwrap()
+}
+
+async fn graphql(client: &reqwest::Client, addr: &str, query: &str, variables: Value) -> Value {
+    let res = client
+        .post(format!("{addr}/graphql"))
+        .json(&json!({ "query": query, "variables": variables }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    res.json().await.unwrap()
+}
+
+#[tokio::test]
+async fn queries_and_mutations_go_through_the_order_service() {
+    let server = start(false).await;
+    let addr = server.base_url();
+    let client = reqwest::Client::new();
+
+    let created = graphql(
+        &client,
+        addr,
+        "mutation($input: CreateOrderInput!) {
+            createOrder(input: $input) { id status totalCents currency }
+        }",
+        json!({"input": {
+            "customerName": "Ann",
+            "email": "ann@example.com",
+            "items": [{"name": "Widget", "qty": 2, "unitPriceCents": 250}]
+        }}),
+    )
+    .await;
+    let order = &created["data"]["createOrder"];
+    assert_eq!(order["status"], "PENDING");
+    assert_eq!(order["totalCents"], 500);
+    assert_eq!(order["currency"], "USD");
+    let id = order["id"].as_str().unwrap().to_string();
+
+    let updated = graphql(
+        &client,
+        addr,
+        "mutation($id: ID!) { updateStatus(id: $id, status: CONFIRMED, note: \"paid\") { status } }",
+        json!({"id": id}),
+    )
+    .await;
+    assert_eq!(updated["data"]["updateStatus"]["status"], "CONFIRMED");
+
+    let page = graphql(
+        &client,
+        addr,
+        "{ orders(filter: {status: CONFIRMED}, page: {limit: 10}) {
+            orders { id customerName items { name qty unitPriceCents } }
+            limit offset
+        } }",
+        json!({}),
+    )
+    .await;
+    let page = &page["data"]["orders"];
+    assert_eq!(page["limit"], 10);
+    assert_eq!(page["offset"], 0);
+    assert_eq!(page["orders"][0]["id"], id.as_str());
+    assert_eq!(page["orders"][0]["items"][0]["qty"], 2);
+
+    // The REST routes see the same order.
+    let rest: Value = client
+        .get(format!("{addr}/orders/{id}"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(rest["status"], "Confirmed");
+
+    let deleted = graphql(
+        &client,
+        addr,
+        "mutation($id: ID!) { deleteOrder(id: $id) }",
+        json!({"id": id}),
+    )
+    .await;
+    assert_eq!(deleted["data"]["deleteOrder"], true);
+
+    let missing = graphql(
+        &client,
+        addr,
+        "query($id: ID!) { order(id: $id) { id } }",
+        json!({"id": id}),
+    )
+    .await;
+    assert_eq!(
+        missing["errors"][0]["extensions"]["code"],
+        "ORDER_NOT_FOUND"
+    );
+
+    let invalid = graphql(
+        &client,
+        addr,
+        "mutation { createOrder(input: {customerName: \"\", email: \"nope\", items: []}) { id } }",
+        json!({}),
+    )
+    .await;
+    assert_eq!(
+        invalid["errors"][0]["extensions"]["code"],
+        "VALIDATION_FAILED"
+    );
+    assert!(invalid["errors"][0]["extensions"]["errors"].is_array());
+
+    // GraphiQL is only served in dev mode.
+    let res = client.get(format!("{addr}/graphql")).send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+}
+
+#[tokio::test]
+async fn graphiql_is_served_when_enabled() {
+    let server = start(true).await;
+    let addr = server.base_url();
+    let res = reqwest::get(format!("{addr}/graphql")).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(res.text().await.unwrap().contains("graphiql"));
+}
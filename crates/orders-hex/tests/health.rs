@@ -0,0 +1,59 @@
+use std::time::Duration;
+
+use orders_hex::application::order_service::OrderService;
+use orders_hex::inbound::http::HttpServer;
+use orders_hex::outbound::validator::HttpOrderValidator;
+use orders_hex::testing::{self, TestServer};
+use orders_repo::memory::InMemoryRepo;
+use orders_types::ports::validation::FailurePolicy;
+use reqwest::StatusCode;
+
+/// Serve with a validator that hangs up on every request.
+async fn start_with_dead_validator(policy: FailurePolicy) -> TestServer {
+    let validator = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let dead = format!("http://{}/check", validator.local_addr().unwrap());
+    tokio::spawn(async move {
+        while let Ok((conn, _)) = validator.accept().await {
+            drop(conn);
+        }
+    });
+    let service = OrderService::new(InMemoryRepo::new()).with_validator(
+        HttpOrderValidator::new(dead),
+        Duration::from_millis(200),
+        policy,
+    );
+    let server = HttpServer::new(service, testing::config()).await.unwrap();
+    TestServer::start(server).await.unwrap()
+}
+
+#[tokio::test]
+async fn readiness_reports_each_dependency() {
+    let server = start_with_dead_validator(FailurePolicy::FailClosed).await;
+    let addr = server.base_url();
+    let client = reqwest::Client::new();
+
+    let live = client.get(format!("{addr}/healthz")).send().await.unwrap();
+    assert_eq!(live.status(), StatusCode::OK);
+
+    let res = client.get(format!("{addr}/readyz")).send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(body["ready"], false);
+    assert_eq!(body["checks"][0]["name"], "repository");
+    assert_eq!(body["checks"][0]["status"], "up");
+    assert!(body["checks"][0]["latency_ms"].is_u64());
+    assert_eq!(body["checks"][1]["name"], "order_validator");
+    assert_eq!(body["checks"][1]["status"], "down");
+    assert_eq!(body["checks"][1]["required"], true);
+    assert!(body["checks"][1]["error"].is_string());
+
+    // Fail-open keeps taking orders, so a dead validator only degrades.
+    let server = start_with_dead_validator(FailurePolicy::FailOpen).await;
+    let addr = server.base_url();
+    let res = client.get(format!("{addr}/readyz")).send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(body["ready"], true);
+    assert_eq!(body["checks"][1]["status"], "down");
+    assert_eq!(body["checks"][1]["required"], false);
+}
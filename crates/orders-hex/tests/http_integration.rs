@@ -1,17 +1,14 @@
 use orders_hex::application::order_service::OrderService;
-use orders_hex::inbound::http::{HttpServer, HttpServerConfig};
+use orders_hex::inbound::http::body_log::{BodyLogConfig, BodyLogger};
+use orders_hex::inbound::http::HttpServer;
+use orders_hex::testing::{self, TestServer};
 use orders_repo::build_repo;
+use orders_repo::memory::InMemoryRepo;
+use orders_types::domain::filter::OrderPage;
+use orders_types::domain::money::Money;
 use orders_types::domain::order::{Order, OrderItem, OrderStatus};
 use serde::{Deserialize, Serialize};
 
-fn find_free_port() -> u16 {
-    std::net::TcpListener::bind("127.0.0.1:0")
-        .unwrap()
-        .local_addr()
-        .unwrap()
-        .port()
-}
-
 #[derive(Serialize)]
 struct OrderInput {
     customer_name: String,
@@ -26,22 +23,14 @@ struct UpdateStatus {
 
 #[tokio::test]
 async fn create_list_update_delete_over_http() {
-    let port = find_free_port();
-    let config = HttpServerConfig {
-        port: port.to_string(),
-    };
+    let config = testing::config();
 
     let repo = build_repo(None).await.expect("build repo");
     let service = OrderService::new(repo);
     let server = HttpServer::new(service, config).await.unwrap();
 
-    let addr = format!("http://127.0.0.1:{}", port);
-    let handle = tokio::spawn(async move {
-        server.run().await.expect("server run");
-    });
-
-    // Give the server a moment to start.
-    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    let server = TestServer::start(server).await.unwrap();
+    let addr = server.base_url();
 
     let client = reqwest::Client::new();
     let create_body = OrderInput {
@@ -50,7 +39,12 @@ async fn create_list_update_delete_over_http() {
         items: vec![OrderItem {
             name: "Widget".into(),
             qty: 1,
-            unit_price_cents: 500,
+            unit_price: Money::usd(500),
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
         }],
     };
 
@@ -81,17 +75,37 @@ async fn create_list_update_delete_over_http() {
         .unwrap();
     assert_eq!(fetched.customer_name, "HttpUser");
 
+    let res = client
+        .head(format!("{}/orders/{}", addr, id))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+    assert!(res.bytes().await.unwrap().is_empty());
+
     let list: Vec<Order> = client
         .get(format!("{}/orders", addr))
         .send()
         .await
         .unwrap()
-        .json()
+        .json::<OrderPage>()
         .await
-        .unwrap();
+        .unwrap()
+        .orders;
     assert_eq!(list.len(), 1);
     assert_eq!(list[0].id.to_string(), id);
 
+    let shipped: Vec<Order> = client
+        .get(format!("{}/orders?status=Shipped", addr))
+        .send()
+        .await
+        .unwrap()
+        .json::<OrderPage>()
+        .await
+        .unwrap()
+        .orders;
+    assert!(shipped.is_empty());
+
     let update_body = UpdateStatus {
         status: OrderStatus::Shipped,
     };
@@ -111,25 +125,16 @@ async fn create_list_update_delete_over_http() {
         .await
         .unwrap();
     assert_eq!(res.status(), reqwest::StatusCode::NO_CONTENT);
-
-    // stop server task
-    handle.abort();
 }
 
 #[tokio::test]
 async fn bad_request_and_not_found_paths() {
-    let port = find_free_port();
-    let config = HttpServerConfig {
-        port: port.to_string(),
-    };
+    let config = testing::config();
     let repo = build_repo(None).await.expect("build repo");
     let service = OrderService::new(repo);
     let server = HttpServer::new(service, config).await.unwrap();
-    let addr = format!("http://127.0.0.1:{}", port);
-    let handle = tokio::spawn(async move {
-        server.run().await.expect("server run");
-    });
-    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    let server = TestServer::start(server).await.unwrap();
+    let addr = server.base_url();
 
     let client = reqwest::Client::new();
     let bad_body = OrderInput {
@@ -143,7 +148,47 @@ async fn bad_request_and_not_found_paths() {
         .send()
         .await
         .unwrap();
-    assert_eq!(res.status(), reqwest::StatusCode::BAD_REQUEST);
+    assert_eq!(res.status(), reqwest::StatusCode::UNPROCESSABLE_ENTITY);
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(body["error"], "validation failed");
+    assert_eq!(body["code"], "VALIDATION_FAILED");
+    assert!(body["request_id"].is_string());
+    assert_eq!(body["details"]["errors"][0]["field"], "customer_name");
+    assert_eq!(body["details"]["errors"][2]["field"], "items");
+
+    let res = client
+        .post(format!("{}/orders", addr))
+        .json(&serde_json::json!({
+            "customer_name": "Ann",
+            "email": "a@b.com",
+            "items": [{"name": "A", "qty": -1, "unit_price_cents": 100}]
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::UNPROCESSABLE_ENTITY);
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(body["details"]["errors"][0]["field"], "items[0].qty");
+
+    let res = client
+        .post(format!("{}/orders", addr))
+        .json(&serde_json::json!({"customer_name": "Ann", "items": []}))
+        .send()
+        .await
+        .unwrap();
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(body["details"]["errors"][0]["field"], "email");
+
+    let res = client
+        .post(format!("{}/orders", addr))
+        .header("content-type", "application/json")
+        .body("{\"customer_name\": ")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::UNPROCESSABLE_ENTITY);
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(body["error"], "validation failed");
 
     let missing_id = uuid::Uuid::new_v4();
     let res = client
@@ -153,5 +198,716 @@ async fn bad_request_and_not_found_paths() {
         .unwrap();
     assert_eq!(res.status(), reqwest::StatusCode::NOT_FOUND);
 
-    handle.abort();
+    let res = client
+        .get(format!("{}/orders/{}", addr, missing_id))
+        .header("x-request-id", "req-42")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.headers()["x-request-id"], "req-42");
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(body["request_id"], "req-42");
+
+    let res = client
+        .get(format!("{}/orders/{}", addr, missing_id))
+        .header("x-request-id", "bad\tid")
+        .send()
+        .await
+        .unwrap();
+    let minted = res.headers()["x-request-id"].to_str().unwrap().to_string();
+    assert_ne!(minted, "bad\tid");
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(body["request_id"], minted.as_str());
+
+    let res = client
+        .head(format!("{}/orders/{}", addr, missing_id))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn orders_are_found_by_id_or_number() {
+    let server = TestServer::spawn(InMemoryRepo::new()).await.unwrap();
+    let client = reqwest::Client::new();
+
+    let mut created = Vec::new();
+    for name in ["Ann", "Bo"] {
+        let res = client
+            .post(server.url("/orders"))
+            .json(&serde_json::json!({
+                "customer_name": name,
+                "email": "a@b.com",
+                "items": [{"name": "Widget", "qty": 1, "unit_price_cents": 500}]
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), reqwest::StatusCode::CREATED);
+        created.push(res.json::<serde_json::Value>().await.unwrap());
+    }
+    let year = chrono::Utc::now().format("%Y");
+    assert_eq!(created[0]["order_number"], format!("ORD-{year}-000001"));
+    assert_eq!(created[1]["order_number"], format!("ORD-{year}-000002"));
+    let id = uuid::Uuid::parse_str(created[1]["id"].as_str().unwrap()).unwrap();
+    assert_eq!(id.get_version_num(), 7);
+
+    for reference in [
+        id.to_string(),
+        format!("ORD-{year}-000002"),
+        format!("ord-{year}-2"),
+    ] {
+        let res = client
+            .get(server.url(&format!("/orders/{reference}")))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), reqwest::StatusCode::OK, "{reference}");
+        let order: Order = res.json().await.unwrap();
+        assert_eq!(order.id, id);
+        assert_eq!(order.customer_name, "Bo");
+        assert_eq!(
+            order.order_number.map(|n| n.to_string()),
+            Some(format!("ORD-{year}-000002"))
+        );
+    }
+
+    let res = client
+        .get(server.url(&format!("/orders/ORD-{year}-000003")))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::NOT_FOUND);
+    let res = client
+        .get(server.url("/orders/order-two"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn orders_are_patched_until_they_ship() {
+    let server = TestServer::spawn(InMemoryRepo::new()).await.unwrap();
+    let client = reqwest::Client::new();
+    let res = client
+        .post(server.url("/orders"))
+        .json(&serde_json::json!({
+            "customer_name": "Ann",
+            "email": "ann@example.com",
+            "items": [{"name": "Widget", "qty": 1, "unit_price_cents": 500}],
+            "shipping_address": {
+                "line1": "1 Main St", "city": "Berlin", "postal_code": "10115", "country": "DE"
+            }
+        }))
+        .send()
+        .await
+        .unwrap();
+    let id = res.json::<serde_json::Value>().await.unwrap()["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let patch = |body: serde_json::Value| {
+        client
+            .patch(server.url(&format!("/orders/{id}")))
+            .header("content-type", "application/merge-patch+json")
+            .body(body.to_string())
+            .send()
+    };
+
+    let res = patch(serde_json::json!({
+        "email": "anna@example.com",
+        "shipping_address": {"city": "Bonn", "postal_code": "53111"},
+        "billing_address": null
+    }))
+    .await
+    .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+    let order: Order = res.json().await.unwrap();
+    assert_eq!(order.customer_name, "Ann");
+    assert_eq!(order.email, "anna@example.com");
+    let shipping = order.shipping_address.unwrap();
+    assert_eq!(
+        (shipping.line1.as_str(), shipping.city.as_str()),
+        ("1 Main St", "Bonn")
+    );
+
+    let res = patch(serde_json::json!({"customer_name": "", "total_cents": 1}))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::UNPROCESSABLE_ENTITY);
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(body["details"]["errors"][0]["field"], "total_cents");
+    let res = patch(serde_json::json!(["customer_name"])).await.unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::UNPROCESSABLE_ENTITY);
+
+    client
+        .patch(server.url(&format!("/orders/{id}/status")))
+        .json(&serde_json::json!({"status": "Shipped"}))
+        .send()
+        .await
+        .unwrap();
+    let res = patch(serde_json::json!({"customer_name": "Anna"}))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn emails_are_normalized_and_bad_ones_explained() {
+    let server = TestServer::spawn(InMemoryRepo::new()).await.unwrap();
+    let client = reqwest::Client::new();
+    let create = |email: &str| {
+        client
+            .post(server.url("/orders"))
+            .json(&serde_json::json!({
+                "customer_name": "Ann",
+                "email": email,
+                "items": [{"name": "Widget", "qty": 1, "unit_price_cents": 500}]
+            }))
+            .send()
+    };
+
+    let res = create(" Ann.Lee@Example.COM ").await.unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::CREATED);
+    let id = res.json::<serde_json::Value>().await.unwrap()["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let order: Order = client
+        .get(server.url(&format!("/orders/{id}")))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(order.email, "ann.lee@example.com");
+
+    for (email, message) in [
+        ("ann@lee@example.com", "must contain only one `@`"),
+        (
+            "ann..lee@example.com",
+            "the part before `@` must not start or end with `.` or contain `..`",
+        ),
+        ("ann@example_com", "`example_com` is not a valid domain"),
+    ] {
+        let res = create(email).await.unwrap();
+        assert_eq!(res.status(), reqwest::StatusCode::UNPROCESSABLE_ENTITY);
+        let body: serde_json::Value = res.json().await.unwrap();
+        assert_eq!(
+            body["details"]["errors"][0],
+            serde_json::json!({"field": "email", "message": message})
+        );
+    }
+}
+
+#[tokio::test]
+async fn orders_carry_metadata_and_filter_by_it() {
+    let server = TestServer::spawn(InMemoryRepo::new()).await.unwrap();
+    let client = reqwest::Client::new();
+    let mut ids = Vec::new();
+    for (email, campaign) in [("ann@example.com", "spring"), ("bob@example.com", "autumn")] {
+        let res = client
+            .post(server.url("/orders"))
+            .json(&serde_json::json!({
+                "customer_name": "Ann",
+                "email": email,
+                "items": [{"name": "Widget", "qty": 1, "unit_price_cents": 500}],
+                "metadata": {"campaign": campaign, "erp_id": format!("E-{email}")}
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), reqwest::StatusCode::CREATED);
+        let body: serde_json::Value = res.json().await.unwrap();
+        ids.push(body["id"].as_str().unwrap().to_string());
+    }
+    let listed = |query: &'static str| {
+        let client = client.clone();
+        let url = server.url(&format!("/orders?{query}"));
+        async move {
+            let page: serde_json::Value =
+                client.get(url).send().await.unwrap().json().await.unwrap();
+            page["orders"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|o| o["id"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        }
+    };
+    assert_eq!(listed("metadata.campaign=spring").await, [ids[0].clone()]);
+    assert!(listed("metadata.campaign=spring&metadata.erp_id=nope")
+        .await
+        .is_empty());
+
+    let res = client
+        .patch(server.url(&format!("/orders/{}", ids[1])))
+        .header("content-type", "application/merge-patch+json")
+        .body(r#"{"metadata": {"campaign": "spring", "erp_id": null}}"#)
+        .send()
+        .await
+        .unwrap();
+    let order: Order = res.json().await.unwrap();
+    assert_eq!(
+        order.metadata,
+        [("campaign".to_string(), "spring".to_string())].into()
+    );
+    assert_eq!(listed("metadata.campaign=spring").await.len(), 2);
+
+    let res = client
+        .post(server.url("/orders"))
+        .json(&serde_json::json!({
+            "customer_name": "Ann",
+            "email": "ann@example.com",
+            "items": [{"name": "Widget", "qty": 1, "unit_price_cents": 500}],
+            "metadata": {"erp_id": "x".repeat(513)}
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::UNPROCESSABLE_ENTITY);
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(body["details"]["errors"][0]["field"], "metadata.erp_id");
+}
+
+#[tokio::test]
+async fn double_submits_within_the_dedupe_window_get_the_first_order() {
+    let service =
+        OrderService::new(InMemoryRepo::new()).with_dedupe_window(chrono::Duration::seconds(60));
+    let server = HttpServer::new(service, testing::config()).await.unwrap();
+    let server = TestServer::start(server).await.unwrap();
+    let client = reqwest::Client::new();
+    let submit = |qty: u32| {
+        client
+            .post(server.url("/orders"))
+            .json(&serde_json::json!({
+                "customer_name": "Ann",
+                "email": "ann@example.com",
+                "items": [{"name": "Widget", "qty": qty, "unit_price_cents": 500}]
+            }))
+            .send()
+    };
+
+    let first = submit(1).await.unwrap();
+    assert_eq!(first.status(), reqwest::StatusCode::CREATED);
+    let first: serde_json::Value = first.json().await.unwrap();
+    let again = submit(1).await.unwrap();
+    assert_eq!(again.status(), reqwest::StatusCode::OK);
+    let again: serde_json::Value = again.json().await.unwrap();
+    assert_eq!(again["id"], first["id"]);
+    assert_eq!(again["order_number"], first["order_number"]);
+
+    let other = submit(2).await.unwrap();
+    assert_eq!(other.status(), reqwest::StatusCode::CREATED);
+    let page: OrderPage = client
+        .get(server.url("/orders"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(page.orders.len(), 2);
+}
+
+#[tokio::test]
+async fn rate_limited_requests_get_429_with_retry_after() {
+    use orders_hex::inbound::http::rate_limit::{
+        InMemoryRateLimitStore, KeySource, Quota, RateLimiter,
+    };
+
+    let config = testing::config();
+    let repo = build_repo(None).await.expect("build repo");
+    let limiter = RateLimiter::new(
+        InMemoryRateLimitStore::new(),
+        KeySource::Header("x-api-key".into()),
+        Quota {
+            burst: 1,
+            per_second: 0.1,
+        },
+    )
+    .with_quota(
+        "premium",
+        Quota {
+            burst: 5,
+            per_second: 1.0,
+        },
+    );
+    let server = HttpServer::new(OrderService::new(repo), config)
+        .await
+        .unwrap()
+        .with_rate_limiter(limiter);
+    let server = TestServer::start(server).await.unwrap();
+    let addr = server.base_url();
+
+    let client = reqwest::Client::new();
+    let get = |key: &'static str| {
+        client
+            .get(format!("{}/health", addr))
+            .header("x-api-key", key)
+            .send()
+    };
+    assert_eq!(
+        get("basic").await.unwrap().status(),
+        reqwest::StatusCode::OK
+    );
+    let limited = get("basic").await.unwrap();
+    assert_eq!(limited.status(), reqwest::StatusCode::TOO_MANY_REQUESTS);
+    let retry_after: u64 = limited.headers()["retry-after"]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!(retry_after >= 1);
+
+    for _ in 0..3 {
+        assert_eq!(
+            get("premium").await.unwrap().status(),
+            reqwest::StatusCode::OK
+        );
+    }
+}
+
+#[tokio::test]
+async fn list_sorts_by_allowed_fields_and_echoes_the_sort() {
+    let config = testing::config();
+    // Its own store: the default one is a file shared with the other tests.
+    let server = HttpServer::new(OrderService::new(InMemoryRepo::new()), config)
+        .await
+        .unwrap();
+    let server = TestServer::start(server).await.unwrap();
+    let addr = server.base_url();
+
+    let client = reqwest::Client::new();
+    for cents in [300, 100, 200] {
+        client
+            .post(format!("{}/orders", addr))
+            .json(&OrderInput {
+                customer_name: "Sorter".into(),
+                email: "sort@example.com".into(),
+                items: vec![OrderItem {
+                    name: "Widget".into(),
+                    qty: 1,
+                    unit_price: Money::usd(cents),
+                    weight_grams: 0,
+                    sku: None,
+                    description: None,
+                    metadata: Default::default(),
+                    discount_cents: 0,
+                }],
+            })
+            .send()
+            .await
+            .unwrap();
+    }
+
+    let page: serde_json::Value = client
+        .get(format!(
+            "{}/orders?sort=total_cents&order=desc&limit=2",
+            addr
+        ))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let totals: Vec<i64> = page["orders"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|o| o["total_cents"].as_i64().unwrap())
+        .collect();
+    assert_eq!(totals, [300, 200]);
+    assert_eq!(
+        page["sort"],
+        serde_json::json!({"field": "total_cents", "order": "desc"})
+    );
+    assert_eq!(page["limit"], 2);
+    assert_eq!(page["offset"], 0);
+
+    let page: OrderPage = client
+        .get(format!("{}/orders?sort=created_at", addr))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let totals: Vec<i64> = page.orders.iter().map(|o| o.total.amount_minor()).collect();
+    assert_eq!(totals, [300, 100, 200], "ascending by default");
+
+    for query in ["sort=email", "order=desc"] {
+        let res = client
+            .get(format!("{}/orders?{}", addr, query))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), reqwest::StatusCode::BAD_REQUEST, "{query}");
+        let body: serde_json::Value = res.json().await.unwrap();
+        assert_eq!(body["code"], "BAD_REQUEST");
+    }
+}
+
+#[tokio::test]
+async fn stats_aggregate_the_tenants_orders() {
+    let config = testing::config();
+    let server = HttpServer::new(OrderService::new(InMemoryRepo::new()), config)
+        .await
+        .unwrap();
+    let server = TestServer::start(server).await.unwrap();
+    let addr = server.base_url();
+
+    let client = reqwest::Client::new();
+    for cents in [100, 300] {
+        client
+            .post(format!("{}/orders", addr))
+            .json(&OrderInput {
+                customer_name: "Stats".into(),
+                email: "stats@example.com".into(),
+                items: vec![OrderItem {
+                    name: "Widget".into(),
+                    qty: 1,
+                    unit_price: Money::usd(cents),
+                    weight_grams: 0,
+                    sku: None,
+                    description: None,
+                    metadata: Default::default(),
+                    discount_cents: 0,
+                }],
+            })
+            .send()
+            .await
+            .unwrap();
+    }
+
+    let today = chrono::Utc::now().date_naive();
+    let stats: serde_json::Value = client
+        .get(format!("{}/orders/stats?from={}&to={}", addr, today, today))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(stats["total_orders"], 2);
+    assert_eq!(
+        stats["by_status"],
+        serde_json::json!([{"status": "Pending", "orders": 2}])
+    );
+    assert_eq!(stats["revenue"][0]["revenue_cents"], 400);
+    assert_eq!(stats["revenue"][0]["average_order_cents"], 200);
+    assert_eq!(stats["per_day"][0]["orders"], 2);
+
+    let res = client
+        .get(format!(
+            "{}/orders/stats?from=2024-02-01&to=2024-01-01",
+            addr
+        ))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::BAD_REQUEST);
+    let res = client
+        .get(format!("{}/orders/stats?from=yesterday", addr))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::BAD_REQUEST);
+
+    // Instants, as on the listing; `Z` needs no escaping in a query string.
+    let hour_ago = (chrono::Utc::now() - chrono::Duration::hours(1)).format("%Y-%m-%dT%H:%M:%SZ");
+    let stats: serde_json::Value = client
+        .get(format!("{addr}/orders/stats?created_after={hour_ago}"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(stats["total_orders"], 2);
+    let stats: serde_json::Value = client
+        .get(format!("{addr}/orders/stats?created_before={hour_ago}"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(stats["total_orders"], 0);
+    for query in [
+        "orders/stats?created_after=2024-13-01T00:00:00Z",
+        "orders?created_before=tomorrow",
+        "orders?created_after=2024-02-01T00:00:00Z&created_before=2024-01-01T00:00:00Z",
+        "orders/stats?created_after=2024-02-01T00:00:00Z&created_before=2024-02-01T00:00:00Z",
+    ] {
+        let res = client.get(format!("{addr}/{query}")).send().await.unwrap();
+        assert_eq!(res.status(), reqwest::StatusCode::BAD_REQUEST, "{query}");
+        let body: serde_json::Value = res.json().await.unwrap();
+        assert!(
+            body["error"].as_str().unwrap().contains("created_"),
+            "{query}: {body}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn orders_carry_addresses_and_filter_by_country() {
+    let config = testing::config();
+    let server = HttpServer::new(OrderService::new(InMemoryRepo::new()), config)
+        .await
+        .unwrap();
+    let server = TestServer::start(server).await.unwrap();
+    let addr = server.base_url();
+
+    let client = reqwest::Client::new();
+    let address = |country: &str| {
+        serde_json::json!({
+            "line1": "1 Main St",
+            "city": "Berlin",
+            "postal_code": "10115",
+            "country": country
+        })
+    };
+    let item = serde_json::json!([{"name": "A", "qty": 1, "unit_price_cents": 100}]);
+    let res = client
+        .post(format!("{}/orders", addr))
+        .json(&serde_json::json!({
+            "customer_name": "Ann",
+            "email": "ann@example.com",
+            "items": item,
+            "shipping_address": address("DE"),
+            "billing_address": address("AT")
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::CREATED);
+    let created: serde_json::Value = res.json().await.unwrap();
+    let order: serde_json::Value = client
+        .get(format!(
+            "{}/orders/{}",
+            addr,
+            created["id"].as_str().unwrap()
+        ))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(order["shipping_address"], address("DE"));
+    assert_eq!(order["billing_address"]["country"], "AT");
+
+    client
+        .post(format!("{}/orders", addr))
+        .json(&serde_json::json!({
+            "customer_name": "Bob",
+            "email": "bob@example.com",
+            "items": item
+        }))
+        .send()
+        .await
+        .unwrap();
+    let page: OrderPage = client
+        .get(format!("{}/orders?country=de", addr))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(page.orders.len(), 1);
+    assert_eq!(page.orders[0].customer_name, "Ann");
+
+    let res = client
+        .post(format!("{}/orders", addr))
+        .json(&serde_json::json!({
+            "customer_name": "Cy",
+            "email": "cy@example.com",
+            "items": item,
+            "shipping_address": address("Germany")
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::UNPROCESSABLE_ENTITY);
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(
+        body["details"]["errors"][0]["field"],
+        "shipping_address.country"
+    );
+}
+
+/// Collects everything logged through it.
+#[derive(Clone, Default)]
+struct LogBuffer(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+impl std::io::Write for LogBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn body_logging_redacts_and_passes_bodies_through() {
+    let logs = LogBuffer::default();
+    let writer = logs.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_env_filter("http_body=debug")
+        .with_ansi(false)
+        .with_writer(move || writer.clone())
+        .finish();
+    // The test runtime is single threaded, so the server logs through this.
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let config = testing::config();
+    let server = HttpServer::new(OrderService::new(InMemoryRepo::new()), config)
+        .await
+        .unwrap()
+        .with_body_logger(BodyLogger::new(BodyLogConfig::default()));
+    let server = TestServer::start(server).await.unwrap();
+    let addr = server.base_url();
+
+    let client = reqwest::Client::new();
+    let res = client
+        .post(format!("{}/orders", addr))
+        .json(&serde_json::json!({
+            "customer_name": "Ann",
+            "email": "ann@example.com",
+            "items": [{"name": "A", "qty": 2, "unit_price_cents": 100}]
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::CREATED);
+    let created: serde_json::Value = res.json().await.unwrap();
+    let order: Order = client
+        .get(format!(
+            "{}/orders/{}",
+            addr,
+            created["id"].as_str().unwrap()
+        ))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(order.email, "ann@example.com");
+    assert_eq!(order.total, Money::usd(200));
+
+    let logged = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+    assert_eq!(logged.matches("request body").count(), 2, "{logged}");
+    assert_eq!(logged.matches("response body").count(), 2, "{logged}");
+    assert!(logged.contains(r#""email":"[REDACTED]""#), "{logged}");
+    assert!(!logged.contains("ann@example.com"), "{logged}");
 }
@@ -0,0 +1,121 @@
+use orders_hex::application::order_service::OrderService;
+use orders_hex::inbound::http::HttpServer;
+use orders_hex::testing::{self, TestServer};
+use orders_repo::memory::InMemoryRepo;
+use reqwest::StatusCode;
+
+async fn start_server() -> TestServer {
+    let server = HttpServer::new(OrderService::new(InMemoryRepo::new()), testing::config())
+        .await
+        .unwrap();
+    TestServer::start(server).await.unwrap()
+}
+
+fn ndjson_line(i: usize) -> String {
+    format!(
+        r#"{{"customer_name":"C{i}","email":"c{i}@example.com","items":[{{"name":"W","qty":1,"unit_price_cents":100}}]}}"#
+    )
+}
+
+async fn order_count(client: &reqwest::Client, addr: &str) -> usize {
+    client
+        .get(format!("{addr}/orders"))
+        .send()
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap()["orders"]
+        .as_array()
+        .unwrap()
+        .len()
+}
+
+#[tokio::test]
+async fn ndjson_and_multipart_csv_imports_report_failures_by_line() {
+    let server = start_server().await;
+    let addr = server.base_url();
+    let client = reqwest::Client::new();
+
+    let body = format!(
+        "{}\n{{\"customer_name\":\"\",\"email\":\"x\",\"items\":[]}}\n{}\n",
+        ndjson_line(1),
+        ndjson_line(2)
+    );
+    let res = client
+        .post(format!("{addr}/orders/import"))
+        .header("content-type", "application/x-ndjson")
+        .body(body)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let summary: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(summary["records"], 3);
+    assert_eq!(summary["imported"], 2);
+    assert_eq!(summary["failed"], 1);
+    assert_eq!(summary["failures"][0]["line"], 2);
+
+    let csv = "order_ref,customer_name,email,item_name,qty,unit_price_cents\r\n\
+               a,Ann,ann@example.com,Widget,2,500\r\n\
+               a,Ann,ann@example.com,Gadget,1,250\r\n";
+    let multipart = format!(
+        "--XYZ\r\nContent-Disposition: form-data; name=\"note\"\r\n\r\nignored\r\n\
+         --XYZ\r\nContent-Disposition: form-data; name=\"file\"; filename=\"orders.csv\"\r\n\
+         Content-Type: application/octet-stream\r\n\r\n{csv}\r\n--XYZ--\r\n"
+    );
+    let summary: serde_json::Value = client
+        .post(format!("{addr}/orders/import"))
+        .header("content-type", "multipart/form-data; boundary=XYZ")
+        .body(multipart)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(summary["imported"], 1);
+    assert_eq!(summary["failed"], 0);
+    assert_eq!(order_count(&client, addr).await, 3);
+
+    let res = client
+        .post(format!("{addr}/orders/import"))
+        .header("content-type", "application/pdf")
+        .body("%PDF")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn event_stream_reports_progress_per_batch() {
+    let server = start_server().await;
+    let addr = server.base_url();
+    let client = reqwest::Client::new();
+    let body: String = (0..1200).map(|i| ndjson_line(i) + "\n").collect();
+
+    let res = client
+        .post(format!("{addr}/orders/import"))
+        .header("content-type", "application/x-ndjson")
+        .header("accept", "text/event-stream")
+        .body(body)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(res.headers()["content-type"]
+        .to_str()
+        .unwrap()
+        .starts_with("text/event-stream"));
+    let text = res.text().await.unwrap();
+    assert_eq!(text.matches("event: progress").count(), 2);
+    let done = text
+        .split("event: done\ndata: ")
+        .nth(1)
+        .and_then(|rest| rest.lines().next())
+        .unwrap();
+    let summary: serde_json::Value = serde_json::from_str(done).unwrap();
+    assert_eq!(summary["imported"], 1200);
+    assert_eq!(order_count(&client, addr).await, 1200);
+}
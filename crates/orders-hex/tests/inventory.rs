@@ -0,0 +1,69 @@
+use orders_hex::application::order_service::OrderService;
+use orders_hex::inbound::http::HttpServer;
+use orders_hex::outbound::inventory::InMemoryInventory;
+use orders_hex::testing::{self, TestServer};
+use orders_repo::memory::InMemoryRepo;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn orders_cannot_oversell_and_cancelling_returns_stock() {
+    let inventory = InMemoryInventory::new();
+    inventory.set_stock("Widget", 3);
+    let service = OrderService::new(InMemoryRepo::new()).with_inventory(inventory.clone());
+    let server = HttpServer::new(service, testing::config()).await.unwrap();
+    let server = TestServer::start(server).await.unwrap();
+    let addr = server.base_url();
+    let client = reqwest::Client::new();
+    let order = |qty: u32| {
+        client
+            .post(format!("{addr}/orders"))
+            .json(&json!({
+                "customer_name": "Ann",
+                "email": "ann@example.com",
+                "items": [{"name": "Widget", "qty": qty, "unit_price_cents": 100}]
+            }))
+            .send()
+    };
+
+    let res = order(2).await.unwrap();
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let first: Value = res.json().await.unwrap();
+    assert_eq!(inventory.available("Widget"), Some(1));
+
+    let res = order(2).await.unwrap();
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["code"], "INSUFFICIENT_STOCK");
+    assert_eq!(
+        body["details"],
+        json!({"sku": "Widget", "requested": 2, "available": 1})
+    );
+    assert_eq!(
+        client
+            .get(format!("{addr}/orders"))
+            .send()
+            .await
+            .unwrap()
+            .json::<Value>()
+            .await
+            .unwrap()["orders"]
+            .as_array()
+            .unwrap()
+            .len(),
+        1
+    );
+
+    let res = client
+        .post(format!(
+            "{addr}/orders/{}/cancel",
+            first["id"].as_str().unwrap()
+        ))
+        .json(&json!({"reason": "out of budget"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(inventory.available("Widget"), Some(3));
+    assert_eq!(order(3).await.unwrap().status(), StatusCode::CREATED);
+}
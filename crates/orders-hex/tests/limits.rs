@@ -0,0 +1,126 @@
+use orders_hex::application::order_service::OrderService;
+use orders_hex::domain::order::OrderLimits;
+use orders_hex::inbound::http::HttpServer;
+use orders_hex::testing::{self, TestServer};
+use orders_repo::memory::InMemoryRepo;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn oversized_bodies_and_fields_are_rejected() {
+    let service = OrderService::new(InMemoryRepo::new()).with_limits(OrderLimits {
+        max_items: 2,
+        max_customer_name_len: 10,
+        max_email_len: 20,
+        max_total_cents: None,
+    });
+    let server = HttpServer::new(service, testing::config())
+        .await
+        .unwrap()
+        .with_body_limit(1024);
+    let server = TestServer::start(server).await.unwrap();
+    let addr = server.base_url();
+    let client = reqwest::Client::new();
+    let item = json!({"name": "Widget", "qty": 1, "unit_price_cents": 500});
+
+    let res = client
+        .post(format!("{addr}/orders"))
+        .json(&json!({
+            "customer_name": "Ann",
+            "email": "ann@example.com",
+            "items": [item],
+            "padding": "x".repeat(2048)
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["code"], "PAYLOAD_TOO_LARGE");
+
+    let res = client
+        .post(format!("{addr}/orders"))
+        .json(&json!({
+            "customer_name": "Annabelle Lee",
+            "email": "annabelle.lee@example.com",
+            "items": [item, item, item]
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body: Value = res.json().await.unwrap();
+    let fields: Vec<&str> = body["details"]["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(fields, ["customer_name", "email", "items"]);
+
+    let order: Value = client
+        .post(format!("{addr}/orders"))
+        .json(&json!({
+            "customer_name": "Ann",
+            "email": "ann@example.com",
+            "items": [item, item]
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let id = order["id"].as_str().unwrap();
+    let res = client
+        .post(format!("{addr}/orders/{id}/items"))
+        .json(&json!({"items": [item]}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn order_totals_are_capped_and_never_wrap() {
+    let service = OrderService::new(InMemoryRepo::new()).with_limits(OrderLimits {
+        max_total_cents: Some(10_000),
+        ..OrderLimits::default()
+    });
+    let server = TestServer::start(HttpServer::new(service, testing::config()).await.unwrap())
+        .await
+        .unwrap();
+    let addr = server.base_url();
+    let client = reqwest::Client::new();
+    let place = |qty: u64, unit_price_cents: i64| {
+        client
+            .post(format!("{addr}/orders"))
+            .json(&json!({
+                "customer_name": "Ann",
+                "email": "ann@example.com",
+                "items": [{"name": "Widget", "qty": qty, "unit_price_cents": unit_price_cents}]
+            }))
+            .send()
+    };
+
+    let res = place(4, 2_500).await.unwrap();
+    assert_eq!(res.status(), StatusCode::CREATED);
+
+    let res = place(1, 10_001).await.unwrap();
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["details"]["errors"][0]["field"], "items");
+    assert_eq!(
+        body["details"]["errors"][0]["message"],
+        "the order total must be at most 100.00 USD"
+    );
+
+    let res = place(u64::from(u32::MAX), i64::MAX / 2).await.unwrap();
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["details"]["errors"][0]["field"], "items");
+    assert_eq!(
+        body["details"]["errors"][0]["message"],
+        "the order total is too large"
+    );
+}
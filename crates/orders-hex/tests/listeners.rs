@@ -0,0 +1,76 @@
+#![cfg(unix)]
+
+use std::os::fd::IntoRawFd;
+
+use orders_hex::application::order_service::OrderService;
+use orders_hex::inbound::http::{HttpServer, Listener};
+use orders_hex::testing;
+use orders_repo::memory::InMemoryRepo;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+async fn server(listener: Listener) -> tokio::task::JoinHandle<()> {
+    let server = HttpServer::new(OrderService::new(InMemoryRepo::new()), testing::config())
+        .await
+        .unwrap()
+        .with_listener(listener);
+    tokio::spawn(async move {
+        server.run().await.expect("server run");
+    })
+}
+
+/// Status line of a bare `GET path` over `stream`.
+async fn get<S: AsyncReadExt + AsyncWriteExt + Unpin>(mut stream: S, path: &str) -> String {
+    let req = format!("GET {path} HTTP/1.1\r\nHost: orders\r\nConnection: close\r\n\r\n");
+    stream.write_all(req.as_bytes()).await.unwrap();
+    let mut res = String::new();
+    stream.read_to_string(&mut res).await.unwrap();
+    res.lines().next().unwrap_or_default().to_string()
+}
+
+#[tokio::test]
+async fn serves_on_a_unix_socket_replacing_a_stale_one() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("orders.sock");
+    // Left behind by an earlier run.
+    drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+
+    let handle = server(Listener::Unix(path.clone())).await;
+    // The server binds the socket itself, replacing the stale one.
+    testing::wait_until("the server answers on the socket", || async {
+        match tokio::net::UnixStream::connect(&path).await {
+            Ok(stream) => get(stream, "/v1/orders").await == "HTTP/1.1 200 OK",
+            Err(_) => false,
+        }
+    })
+    .await
+    .unwrap();
+    handle.abort();
+}
+
+#[tokio::test]
+async fn refuses_to_replace_a_regular_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("orders.sock");
+    std::fs::write(&path, "data").unwrap();
+    let server = HttpServer::new(OrderService::new(InMemoryRepo::new()), testing::config())
+        .await
+        .unwrap()
+        .with_listener(Listener::Unix(path.clone()));
+    assert!(server.run().await.is_err());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "data");
+}
+
+#[tokio::test]
+async fn serves_on_a_socket_passed_by_systemd() {
+    let socket = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = socket.local_addr().unwrap();
+    // What systemd sets up before exec, pointed at our own socket.
+    std::env::set_var("LISTEN_FDS", "1");
+    std::env::set_var("LISTEN_FDS_FIRST_FD", socket.into_raw_fd().to_string());
+
+    // Already listening, so the connection waits for the server to accept.
+    let handle = server(Listener::Systemd).await;
+    let stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+    assert_eq!(get(stream, "/healthz").await, "HTTP/1.1 200 OK");
+    handle.abort();
+}
@@ -0,0 +1,90 @@
+use orders_hex::application::order_service::OrderService;
+use orders_hex::inbound::http::HttpServer;
+use orders_hex::testing::{self, TestServer};
+use orders_repo::memory::InMemoryRepo;
+use reqwest::header::{ACCEPT, CONTENT_TYPE};
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn orders_come_as_csv_or_msgpack_on_request() {
+    let service = OrderService::new(InMemoryRepo::new());
+    let server = HttpServer::new(service, testing::config()).await.unwrap();
+    let server = TestServer::start(server).await.unwrap();
+    let addr = server.base_url();
+    let client = reqwest::Client::new();
+    let order: Value = client
+        .post(format!("{addr}/orders"))
+        .json(&json!({
+            "customer_name": "Ann, Jr.",
+            "email": "ann@example.com",
+            "items": [{"name": "Widget", "qty": 2, "unit_price_cents": 500}],
+            "shipping_address": {
+                "line1": "1 Main St",
+                "city": "Springfield",
+                "postal_code": "12345",
+                "country": "US"
+            }
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let id = order["id"].as_str().unwrap();
+
+    let res = client
+        .get(format!("{addr}/orders"))
+        .header(ACCEPT, "text/csv")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()[CONTENT_TYPE], "text/csv; charset=utf-8");
+    let text = res.text().await.unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("id,"));
+    assert!(lines[0].contains(",shipping_address.country,"));
+    assert!(lines[1].starts_with(id));
+    assert!(lines[1].contains(",\"Ann, Jr.\","));
+
+    // Only the collection has a tabular form.
+    let res = client
+        .get(format!("{addr}/orders/{id}"))
+        .header(ACCEPT, "text/csv")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.headers()[CONTENT_TYPE], "application/json");
+
+    let json: Value = client
+        .get(format!("{addr}/orders/{id}"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let res = client
+        .get(format!("{addr}/orders/{id}"))
+        .header(ACCEPT, "application/msgpack")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()[CONTENT_TYPE], "application/msgpack");
+    let decoded: Value = rmp_serde::from_slice(&res.bytes().await.unwrap()).unwrap();
+    assert_eq!(decoded, json);
+
+    let res = client
+        .get(format!("{addr}/orders/{}", uuid::Uuid::new_v4()))
+        .header(ACCEPT, "application/msgpack")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    let decoded: Value = rmp_serde::from_slice(&res.bytes().await.unwrap()).unwrap();
+    assert_eq!(decoded["code"], "ORDER_NOT_FOUND");
+}
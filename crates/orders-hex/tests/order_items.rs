@@ -0,0 +1,98 @@
+use orders_hex::application::order_service::OrderService;
+use orders_hex::inbound::http::HttpServer;
+use orders_hex::testing::{self, TestServer};
+use orders_repo::memory::InMemoryRepo;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn pending_order_items_can_be_replaced_and_appended() {
+    let service = OrderService::new(InMemoryRepo::new());
+    let server = HttpServer::new(service, testing::config()).await.unwrap();
+    let server = TestServer::start(server).await.unwrap();
+    let addr = server.base_url();
+    let client = reqwest::Client::new();
+
+    let created: Value = client
+        .post(format!("{addr}/orders"))
+        .json(&json!({
+            "customer_name": "Ann",
+            "email": "ann@example.com",
+            "items": [{"name": "Widget", "qty": 1, "unit_price_cents": 500}]
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let items_url = format!("{addr}/orders/{}/items", created["id"].as_str().unwrap());
+
+    let res = client
+        .put(&items_url)
+        .json(&json!({"items": [{"name": "Gadget", "qty": 2, "unit_price_cents": 300}]}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let replaced: Value = res.json().await.unwrap();
+    assert_eq!(replaced["items"].as_array().unwrap().len(), 1);
+    assert_eq!(replaced["total_cents"], 600);
+
+    let res = client
+        .post(&items_url)
+        .json(&json!({"items": [{"name": "Bolt", "qty": 4, "unit_price_cents": 25}]}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let appended: Value = res.json().await.unwrap();
+    assert_eq!(appended["items"][1]["name"], "Bolt");
+    assert_eq!(appended["total_cents"], 700);
+    assert_eq!(appended["subtotal_cents"], 700);
+
+    let res = client
+        .post(&items_url)
+        .json(&json!({"items": [{"name": "Euro", "qty": 1, "unit_price_cents": 1, "currency": "EUR"}]}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["details"]["errors"][0]["field"], "items[0].currency");
+
+    let res = client
+        .put(&items_url)
+        .json(&json!({"items": []}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    client
+        .patch(format!(
+            "{addr}/orders/{}/status",
+            created["id"].as_str().unwrap()
+        ))
+        .json(&json!({"status": "Confirmed"}))
+        .send()
+        .await
+        .unwrap();
+    let res = client
+        .put(&items_url)
+        .json(&json!({"items": [{"name": "Late", "qty": 1, "unit_price_cents": 1}]}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["code"], "CONFLICT");
+
+    let res = client
+        .put(format!("{addr}/orders/{}/items", uuid::Uuid::new_v4()))
+        .json(&json!({"items": [{"name": "X", "qty": 1, "unit_price_cents": 1}]}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
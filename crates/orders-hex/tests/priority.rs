@@ -0,0 +1,57 @@
+use orders_hex::application::order_service::OrderService;
+use orders_hex::application::priority::{PriorityGate, PriorityLimits};
+use orders_hex::inbound::http::HttpServer;
+use orders_hex::testing::{self, TestServer};
+use orders_repo::memory::InMemoryRepo;
+
+#[tokio::test]
+async fn api_and_import_calls_are_admitted_per_class() {
+    let service =
+        OrderService::new(InMemoryRepo::new()).with_priority(PriorityGate::new(PriorityLimits {
+            capacity: 4,
+            background: 1,
+        }));
+    let server = HttpServer::new(service, testing::config()).await.unwrap();
+    let server = TestServer::start(server).await.unwrap();
+    let addr = server.base_url();
+    let client = reqwest::Client::new();
+
+    let res = client
+        .post(format!("{addr}/orders"))
+        .json(&serde_json::json!({
+            "customer_name": "Ann",
+            "email": "ann@example.com",
+            "items": [{"name": "Widget", "qty": 1, "unit_price_cents": 100}]
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::CREATED);
+    let res = client
+        .post(format!("{addr}/orders/import"))
+        .header("content-type", "application/x-ndjson")
+        .body(
+            r#"{"customer_name":"Bo","email":"bo@example.com","items":[{"name":"A","qty":1,"unit_price_cents":5}]}"#,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert!(res.status().is_success());
+    client.get(format!("{addr}/healthz")).send().await.unwrap();
+
+    let stats: serde_json::Value = client
+        .get(format!("{addr}/admin/priority"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(stats[0]["class"], "interactive");
+    assert_eq!(stats[0]["limit"], 4);
+    assert_eq!(stats[0]["admitted"], 1);
+    assert_eq!(stats[1]["class"], "background");
+    assert_eq!(stats[1]["limit"], 1);
+    assert_eq!(stats[1]["admitted"], 1);
+    assert_eq!(stats[1]["in_flight"], 0);
+}
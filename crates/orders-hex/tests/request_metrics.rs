@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+use orders_hex::application::api_key_service::ApiKeyService;
+use orders_hex::application::order_service::OrderService;
+use orders_hex::inbound::http::request_metrics::RequestMetrics;
+use orders_hex::inbound::http::slo::{SloTargets, SloTracker};
+use orders_hex::inbound::http::HttpServer;
+use orders_hex::testing::{self, TestServer};
+use orders_repo::memory::InMemoryRepo;
+use serde_json::json;
+
+#[tokio::test]
+async fn counts_requests_per_tenant_and_key_with_trace_exemplars() {
+    let repo = InMemoryRepo::new();
+    let keys = ApiKeyService::new(repo.clone()).with_bootstrap_key("root-secret");
+    let server = HttpServer::new(OrderService::new(repo), testing::config())
+        .await
+        .unwrap()
+        .with_api_keys(keys)
+        .with_slo(SloTracker::new(SloTargets::default()))
+        .with_request_metrics(RequestMetrics::new());
+    let server = TestServer::start(server).await.unwrap();
+    let addr = server.base_url();
+    let client = reqwest::Client::new();
+
+    let res = client
+        .post(format!("{addr}/v1/orders"))
+        .header("x-api-key", "root-secret")
+        .header("x-tenant-id", "acme")
+        .header("x-correlation-id", "trace-create")
+        .json(&json!({
+            "customer_name": "Ann",
+            "email": "ann@example.com",
+            "items": [{"name": "Widget", "qty": 2, "unit_price_cents": 2000}]
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::CREATED);
+    client
+        .get(format!("{addr}/orders/{}", uuid::Uuid::new_v4()))
+        .header("x-api-key", "root-secret")
+        .header("x-tenant-id", "acme")
+        .header("x-correlation-id", "trace-miss")
+        .send()
+        .await
+        .unwrap();
+
+    // Labels name tenants and keys, so scraping needs an admin key.
+    let res = client.get(format!("{addr}/metrics")).send().await.unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::UNAUTHORIZED);
+    let viewer = client
+        .post(format!("{addr}/v1/admin/api-keys"))
+        .header("x-api-key", "root-secret")
+        .json(&json!({"name": "dash", "scopes": ["read"]}))
+        .send()
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap()["secret"]
+        .as_str()
+        .unwrap()
+        .to_owned();
+    let res = client
+        .get(format!("{addr}/metrics"))
+        .header("x-api-key", viewer)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::FORBIDDEN);
+
+    let plain = client
+        .get(format!("{addr}/metrics"))
+        .header("x-api-key", "root-secret")
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert!(plain.contains(
+        "orders_tenant_requests_total{tenant=\"acme\",route=\"POST /orders\",status=\"2xx\"} 1\n"
+    ));
+    assert!(plain.contains(
+        "orders_tenant_requests_total{tenant=\"acme\",route=\"GET /orders/{id}\",status=\"4xx\"} 1\n"
+    ));
+    assert!(!plain.contains("trace_id"));
+
+    // The order value arrives through the event stream.
+    let mut open = String::new();
+    for _ in 0..50 {
+        let res = client
+            .get(format!("{addr}/metrics"))
+            .header("x-api-key", "root-secret")
+            .header("accept", "application/openmetrics-text; version=1.0.0")
+            .send()
+            .await
+            .unwrap();
+        assert!(res.headers()["content-type"]
+            .to_str()
+            .unwrap()
+            .starts_with("application/openmetrics-text"));
+        open = res.text().await.unwrap();
+        if open.contains("orders_order_value_minor_units_count") {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert!(open.ends_with("# EOF\n"));
+    assert!(open.contains(
+        "orders_tenant_requests_total{tenant=\"acme\",route=\"POST /orders\",status=\"2xx\"} 1 # {trace_id=\"trace-create\"} 1 "
+    ));
+    assert!(open.contains(
+        "orders_api_key_requests_total{api_key=\"bootstrap\",status=\"4xx\"} 1 # {trace_id=\"trace-miss\"} 1 "
+    ));
+    assert!(open.contains(
+        "orders_order_value_minor_units_bucket{tenant=\"acme\",currency=\"USD\",le=\"5000\"} 1 # {trace_id=\"trace-create\"} 4000 "
+    ));
+}
@@ -1,37 +1,46 @@
 use orders_hex::application::order_service::OrderService;
 use orders_repo::memory::InMemoryRepo;
+use orders_types::domain::money::Money;
 use orders_types::domain::order::{OrderItem, OrderStatus};
+use orders_types::domain::tenant::TenantId;
 
 // End-to-end service flow against the in-memory adapter.
 #[tokio::test]
 async fn create_list_update_delete_flow() {
     let repo = InMemoryRepo::new();
     let svc = OrderService::new(repo.clone());
+    let tenant = TenantId::default();
 
     let order = svc
         .create_order(
+            &tenant,
             "Eve".into(),
             "eve@example.com".into(),
             vec![OrderItem {
                 name: "Gadget".into(),
                 qty: 3,
-                unit_price_cents: 700,
+                unit_price: Money::usd(700),
+                weight_grams: 0,
+                sku: None,
+                description: None,
+                metadata: Default::default(),
+                discount_cents: 0,
             }],
         )
         .await
         .unwrap();
 
-    let list = svc.list_orders().await.unwrap();
+    let list = svc.list_orders(&tenant).await.unwrap();
     assert_eq!(list.len(), 1);
     assert_eq!(list[0].id, order.id);
 
     let updated = svc
-        .update_status(order.id, OrderStatus::Confirmed)
+        .update_status(&tenant, order.id, OrderStatus::Confirmed)
         .await
         .unwrap();
     assert_eq!(updated.status, OrderStatus::Confirmed);
 
-    svc.delete_order(order.id).await.unwrap();
-    let after_delete = svc.list_orders().await.unwrap();
+    svc.delete_order(&tenant, order.id).await.unwrap();
+    let after_delete = svc.list_orders(&tenant).await.unwrap();
     assert!(after_delete.is_empty());
 }
@@ -0,0 +1,146 @@
+use orders_hex::application::api_key_service::ApiKeyService;
+use orders_hex::application::order_service::OrderService;
+use orders_hex::inbound::http::HttpServer;
+use orders_hex::testing::{self, TestServer};
+use orders_repo::memory::InMemoryRepo;
+use orders_types::domain::share::ShareSigner;
+use reqwest::StatusCode;
+
+#[tokio::test]
+async fn share_links_bypass_keys_but_not_signatures() {
+    let repo = InMemoryRepo::new();
+    let keys = ApiKeyService::new(repo.clone()).with_bootstrap_key("root-secret");
+    let service = OrderService::new(repo).with_share_signer(
+        ShareSigner::new("share-secret").with_max_ttl(chrono::Duration::hours(1)),
+    );
+    let server = HttpServer::new(service, testing::config())
+        .await
+        .unwrap()
+        .with_api_keys(keys);
+    let server = TestServer::start(server).await.unwrap();
+    let addr = server.base_url();
+
+    let client = reqwest::Client::new();
+    let created: serde_json::Value = client
+        .post(format!("{addr}/orders"))
+        .header("x-api-key", "root-secret")
+        .header("x-tenant-id", "acme")
+        .json(&serde_json::json!({
+            "customer_name": "Ann",
+            "email": "ann@example.com",
+            "items": [{"name": "Widget", "qty": 1, "unit_price_cents": 500}]
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let id = created["id"].as_str().unwrap().to_string();
+
+    let res = client
+        .post(format!("{addr}/orders/{id}/share"))
+        .header("x-api-key", "root-secret")
+        .header("x-tenant-id", "acme")
+        .json(&serde_json::json!({"ttl_secs": 7200}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    // Out of range or non-positive ttls are refused rather than overflowing.
+    for ttl_secs in [i64::MAX, i64::MIN, 0, -60] {
+        let res = client
+            .post(format!("{addr}/orders/{id}/share"))
+            .header("x-api-key", "root-secret")
+            .header("x-tenant-id", "acme")
+            .json(&serde_json::json!({ "ttl_secs": ttl_secs }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST, "{ttl_secs}");
+    }
+
+    let res = client
+        .post(format!("{addr}/orders/{id}/share"))
+        .header("x-api-key", "root-secret")
+        .header("x-tenant-id", "acme")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let link: serde_json::Value = res.json().await.unwrap();
+    let path = link["path"].as_str().unwrap().to_string();
+    assert!(path.starts_with("/v1/orders/"));
+    assert!(path.contains("tenant=acme"));
+
+    // No API key and no tenant header: the link alone is enough.
+    let res = client.get(format!("{addr}{path}")).send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let order: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(order["id"], id.as_str());
+
+    // Re-pointing the link at another tenant or order breaks the signature.
+    let other_tenant = path.replace("tenant=acme", "tenant=globex");
+    let res = client
+        .get(format!("{addr}{other_tenant}"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    let other_order = path.replace(&id, &uuid::Uuid::new_v4().to_string());
+    let res = client
+        .get(format!("{addr}{other_order}"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+    // Without a signature the normal key check still applies.
+    let res = client
+        .get(format!("{addr}/orders/{id}"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn a_sig_parameter_does_not_open_other_routes() {
+    let repo = InMemoryRepo::new();
+    let keys = ApiKeyService::new(repo.clone()).with_bootstrap_key("root-secret");
+    let service = OrderService::new(repo.clone())
+        .with_audit(repo)
+        .with_share_signer(ShareSigner::new("share-secret"));
+    let server = HttpServer::new(service, testing::config())
+        .await
+        .unwrap()
+        .with_api_keys(keys);
+    let server = TestServer::start(server).await.unwrap();
+    let addr = server.base_url();
+    let client = reqwest::Client::new();
+    let id = uuid::Uuid::new_v4();
+
+    for path in [
+        "/v1/orders/stats".to_string(),
+        format!("/v1/orders/{id}/history"),
+        format!("/v1/orders/{id}/audit"),
+        format!("/v1/orders/{id}/fulfillments"),
+        format!("/orders/{id}/history"),
+    ] {
+        let res = client
+            .get(format!("{addr}{path}?sig=x&exp=9999999999"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED, "{path}");
+    }
+
+    // On the order route itself a bogus signature is refused, not waved
+    // through.
+    let res = client
+        .get(format!("{addr}/v1/orders/{id}?sig=00&exp=9999999999"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+}
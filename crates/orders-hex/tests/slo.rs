@@ -0,0 +1,70 @@
+use orders_hex::application::order_service::OrderService;
+use orders_hex::inbound::http::slo::{SloTargets, SloTracker};
+use orders_hex::inbound::http::HttpServer;
+use orders_hex::testing::{self, TestServer};
+use orders_repo::memory::InMemoryRepo;
+use orders_types::ports::metrics::MetricsSource;
+use reqwest::StatusCode;
+
+struct QueueDepth;
+
+impl MetricsSource for QueueDepth {
+    fn prometheus(&self) -> String {
+        "# TYPE orders_queue_depth gauge\norders_queue_depth 7\n".into()
+    }
+}
+
+#[tokio::test]
+async fn reports_per_route_availability_and_exports_metrics() {
+    let server = HttpServer::new(OrderService::new(InMemoryRepo::new()), testing::config())
+        .await
+        .unwrap()
+        .with_slo(SloTracker::new(SloTargets::default()))
+        .with_metrics(QueueDepth);
+    let server = TestServer::start(server).await.unwrap();
+    let addr = server.base_url();
+
+    let client = reqwest::Client::new();
+    for _ in 0..3 {
+        client.get(format!("{addr}/orders")).send().await.unwrap();
+    }
+    let res = client
+        .get(format!("{addr}/orders/{}", uuid::Uuid::new_v4()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    let report: serde_json::Value = client
+        .get(format!("{addr}/admin/slo"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(report["window_secs"], 3600);
+    let routes = report["routes"].as_array().unwrap();
+    let list = routes.iter().find(|r| r["route"] == "GET /orders").unwrap();
+    assert_eq!(list["requests"], 3);
+    assert_eq!(list["availability"], 1.0);
+    // A 404 is the caller's mistake and leaves the budget untouched.
+    let get = routes
+        .iter()
+        .find(|r| r["route"] == "GET /orders/{id}")
+        .unwrap();
+    assert_eq!(get["errors"], 0);
+    assert_eq!(get["error_budget_remaining"], 1.0);
+
+    let metrics = client
+        .get(format!("{addr}/metrics"))
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert!(metrics.contains("# TYPE orders_slo_burn_rate gauge"));
+    assert!(metrics.contains("orders_slo_window_requests{route=\"GET /orders\"} 3"));
+    assert!(metrics.contains("orders_queue_depth 7"));
+}
@@ -0,0 +1,123 @@
+//! Wire-format snapshots of response bodies. A change to a field name,
+//! shape or error code shows up as a diff of `tests/snapshots/*.snap`;
+//! review it with `cargo insta review` and commit the accepted snapshot
+//! with the change. Ids, order numbers and timestamps are redacted.
+
+use insta::assert_json_snapshot;
+use orders_hex::testing::TestServer;
+use orders_repo::memory::InMemoryRepo;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+
+async fn get(client: &reqwest::Client, url: String) -> (StatusCode, Value) {
+    let res = client.get(url).send().await.unwrap();
+    (res.status(), res.json().await.unwrap())
+}
+
+async fn create(client: &reqwest::Client, addr: &str, body: Value) -> (StatusCode, Value) {
+    let res = client
+        .post(format!("{addr}/orders"))
+        .json(&body)
+        .send()
+        .await
+        .unwrap();
+    (res.status(), res.json().await.unwrap())
+}
+
+fn order(customer: &str, gadget_cents: i64) -> Value {
+    json!({
+        "customer_name": customer,
+        "email": format!("{}@example.com", customer.to_lowercase()),
+        "items": [
+            {"name": "Widget", "qty": 2, "unit_price_cents": 1250, "sku": "W-1"},
+            {"name": "Gadget", "qty": 1, "unit_price_cents": gadget_cents}
+        ]
+    })
+}
+
+#[tokio::test]
+async fn order_body() {
+    let server = TestServer::spawn(InMemoryRepo::new()).await.unwrap();
+    let client = reqwest::Client::new();
+
+    let (status, created) = create(&client, server.base_url(), order("Ann", 4000)).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_json_snapshot!("created_order", created, {
+        ".id" => "[id]",
+        ".order_number" => "[order_number]",
+    });
+
+    let id = created["id"].as_str().unwrap();
+    let res = client
+        .patch(server.url(&format!("/orders/{id}/status")))
+        .json(&json!({"status": "Confirmed"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let (_, confirmed) = get(&client, server.url(&format!("/orders/{id}"))).await;
+    assert_json_snapshot!("confirmed_order", confirmed, {
+        ".id" => "[id]",
+        ".order_number" => "[order_number]",
+        ".created_at" => "[timestamp]",
+        ".updated_at" => "[timestamp]",
+        ".pricing.priced_at" => "[timestamp]",
+    });
+}
+
+#[tokio::test]
+async fn order_page_body() {
+    let server = TestServer::spawn(InMemoryRepo::new()).await.unwrap();
+    let client = reqwest::Client::new();
+    for (customer, cents) in [("Ann", 3000), ("Bob", 1000), ("Cy", 2000)] {
+        create(&client, server.base_url(), order(customer, cents)).await;
+    }
+
+    let (status, page) = get(
+        &client,
+        server.url("/orders?sort=total_cents&order=desc&limit=2&offset=1"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_json_snapshot!("order_page", page, {
+        ".orders[].id" => "[id]",
+        ".orders[].order_number" => "[order_number]",
+        ".orders[].created_at" => "[timestamp]",
+        ".orders[].updated_at" => "[timestamp]",
+    });
+}
+
+#[tokio::test]
+async fn error_bodies() {
+    let server = TestServer::spawn(InMemoryRepo::new()).await.unwrap();
+    let client = reqwest::Client::new();
+
+    let (status, not_found) = get(
+        &client,
+        server.url("/orders/00000000-0000-4000-8000-000000000000"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_json_snapshot!("not_found", not_found, {".request_id" => "[id]"});
+
+    let (status, invalid) = create(
+        &client,
+        server.base_url(),
+        json!({"customer_name": "", "email": "nope", "items": []}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_json_snapshot!("validation_failed", invalid, {".request_id" => "[id]"});
+
+    let (_, created) = create(&client, server.base_url(), order("Ann", 4000)).await;
+    let id = created["id"].as_str().unwrap();
+    let res = client
+        .patch(server.url(&format!("/orders/{id}/status")))
+        .json(&json!({"status": "Completed"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+    let transition: Value = res.json().await.unwrap();
+    assert_json_snapshot!("invalid_transition", transition, {".request_id" => "[id]"});
+}
@@ -0,0 +1,95 @@
+use orders_hex::application::order_service::OrderService;
+use orders_hex::inbound::http::HttpServer;
+use orders_hex::testing::{self, TestServer};
+use orders_repo::memory::InMemoryRepo;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn every_transition_is_kept_with_its_note() {
+    let service = OrderService::new(InMemoryRepo::new());
+    let server = HttpServer::new(service, testing::config()).await.unwrap();
+    let server = TestServer::start(server).await.unwrap();
+    let addr = server.base_url();
+    let client = reqwest::Client::new();
+
+    let order: Value = client
+        .post(format!("{addr}/orders"))
+        .json(&json!({
+            "customer_name": "Ann",
+            "email": "ann@example.com",
+            "items": [{"name": "Widget", "qty": 1, "unit_price_cents": 500}]
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let id = order["id"].as_str().unwrap().to_string();
+    for body in [
+        json!({"status": "Confirmed"}),
+        // Setting the same status again is not a transition.
+        json!({"status": "Confirmed"}),
+        json!({"status": "Shipped", "note": "tracking 1Z999"}),
+    ] {
+        let res = client
+            .patch(format!("{addr}/orders/{id}/status"))
+            .json(&body)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    let history: Vec<Value> = client
+        .get(format!("{addr}/orders/{id}/history"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let steps: Vec<(Value, Value)> = history
+        .iter()
+        .map(|e| (e["from"].clone(), e["to"].clone()))
+        .collect();
+    assert_eq!(
+        steps,
+        [
+            (Value::Null, json!("Pending")),
+            (json!("Pending"), json!("Confirmed")),
+            (json!("Confirmed"), json!("Shipped")),
+        ]
+    );
+    assert_eq!(history[2]["note"], "tracking 1Z999");
+    assert_eq!(history[2]["actor"], "anonymous");
+    assert!(history[1].get("note").is_none());
+
+    let plain: Value = client
+        .get(format!("{addr}/orders/{id}"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(plain.get("history").is_none());
+    let with_history: Value = client
+        .get(format!("{addr}/orders/{id}?include=history"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(with_history["status"], "Shipped");
+    assert_eq!(with_history["history"], json!(history));
+
+    let res = client
+        .get(format!("{addr}/orders/{}/history", uuid::Uuid::new_v4()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
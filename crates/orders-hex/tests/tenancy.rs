@@ -0,0 +1,347 @@
+use jsonwebtoken::{encode, EncodingKey, Header};
+use orders_hex::application::api_key_service::ApiKeyService;
+use orders_hex::application::order_service::OrderService;
+use orders_hex::inbound::http::HttpServer;
+use orders_hex::testing::{self, TestServer};
+use orders_repo::memory::InMemoryRepo;
+use orders_types::domain::request_signing::{RequestSignature, SignedRequest};
+use reqwest::StatusCode;
+use uuid::Uuid;
+
+const SECRET: &[u8] = b"tenancy-test-secret";
+
+fn token(tenant: &str) -> String {
+    let exp = chrono::Utc::now().timestamp() + 600;
+    encode(
+        &Header::default(),
+        &serde_json::json!({ "tenant_id": tenant, "exp": exp }),
+        &EncodingKey::from_secret(SECRET),
+    )
+    .unwrap()
+}
+
+fn order_body() -> serde_json::Value {
+    serde_json::json!({
+        "customer_name": "Ann",
+        "email": "ann@acme.test",
+        "items": [{"name": "Widget", "qty": 1, "unit_price_cents": 100}]
+    })
+}
+
+#[tokio::test]
+async fn tenants_are_isolated_by_header() {
+    let server = HttpServer::new(OrderService::new(InMemoryRepo::new()), testing::config())
+        .await
+        .unwrap();
+    let server = TestServer::start(server).await.unwrap();
+    let addr = server.base_url();
+
+    let client = reqwest::Client::new();
+    let created: serde_json::Value = client
+        .post(format!("{addr}/orders"))
+        .header("x-tenant-id", "acme")
+        .json(&order_body())
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let id = created["id"].as_str().unwrap();
+
+    let res = client
+        .get(format!("{addr}/orders/{id}"))
+        .header("x-tenant-id", "globex")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    // Requests without a tenant act for the default one.
+    let res = client
+        .get(format!("{addr}/orders/{id}"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    let res = client
+        .get(format!("{addr}/orders/{id}"))
+        .header("x-tenant-id", "acme")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let res = client
+        .get(format!("{addr}/orders"))
+        .header("x-tenant-id", "bad tenant!")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn tokens_decide_the_tenant_when_jwt_is_configured() {
+    let server = HttpServer::new(OrderService::new(InMemoryRepo::new()), testing::config())
+        .await
+        .unwrap()
+        .with_tenant_jwt_secret(SECRET);
+    let server = TestServer::start(server).await.unwrap();
+    let addr = server.base_url();
+
+    let client = reqwest::Client::new();
+    let created: serde_json::Value = client
+        .post(format!("{addr}/orders"))
+        .bearer_auth(token("acme"))
+        .json(&order_body())
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let id = created["id"].as_str().unwrap();
+
+    // Another tenant's token cannot see or touch it.
+    let listed: serde_json::Value = client
+        .get(format!("{addr}/orders"))
+        .bearer_auth(token("globex"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(listed["orders"], serde_json::json!([]));
+    let res = client
+        .delete(format!("{addr}/orders/{id}"))
+        .bearer_auth(token("globex"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    let res = client
+        .get(format!("{addr}/orders/{id}"))
+        .bearer_auth(token("acme"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let order: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(order["tenant_id"], "acme");
+
+    // The header alone is not trusted, nor a token without the claim, and
+    // it cannot override the token's tenant.
+    let res = client
+        .get(format!("{addr}/orders/{id}"))
+        .header("x-tenant-id", "acme")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    let exp = chrono::Utc::now().timestamp() + 600;
+    let unclaimed = encode(
+        &Header::default(),
+        &serde_json::json!({ "sub": "ann", "exp": exp }),
+        &EncodingKey::from_secret(SECRET),
+    )
+    .unwrap();
+    let res = client
+        .get(format!("{addr}/orders/{id}"))
+        .bearer_auth(unclaimed)
+        .header("x-tenant-id", "acme")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    let res = client
+        .get(format!("{addr}/orders/{id}"))
+        .bearer_auth(token("globex"))
+        .header("x-tenant-id", "acme")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    let res = client
+        .get(format!("{addr}/orders"))
+        .bearer_auth("not-a-jwt")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn api_keys_only_act_for_their_tenant() {
+    let repo = InMemoryRepo::new();
+    let keys = ApiKeyService::new(repo.clone()).with_bootstrap_key("root-secret");
+    let server = HttpServer::new(OrderService::new(repo), testing::config())
+        .await
+        .unwrap()
+        .with_api_keys(keys);
+    let server = TestServer::start(server).await.unwrap();
+    let addr = server.base_url();
+    let client = reqwest::Client::new();
+
+    let mut secrets = Vec::new();
+    for tenant in ["acme", "globex"] {
+        let minted: serde_json::Value = client
+            .post(format!("{addr}/admin/api-keys"))
+            .header("x-api-key", "root-secret")
+            .json(&serde_json::json!({"name": tenant, "scopes": ["write"], "tenant": tenant}))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(minted["tenant"], tenant);
+        secrets.push(minted["secret"].as_str().unwrap().to_string());
+    }
+    let (acme, globex) = (&secrets[0], &secrets[1]);
+
+    // The key's tenant applies without a header.
+    let created: serde_json::Value = client
+        .post(format!("{addr}/orders"))
+        .header("x-api-key", acme)
+        .json(&order_body())
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let id = created["id"].as_str().unwrap();
+
+    // A forged header does not reach another tenant's orders.
+    let res = client
+        .get(format!("{addr}/orders/{id}"))
+        .header("x-api-key", globex)
+        .header("x-tenant-id", "acme")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    let res = client
+        .delete(format!("{addr}/orders/{id}"))
+        .header("x-api-key", globex)
+        .header("x-tenant-id", "acme")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    let res = client
+        .get(format!("{addr}/orders/{id}"))
+        .header("x-api-key", globex)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    let res = client
+        .get(format!("{addr}/orders/{id}"))
+        .header("x-api-key", acme)
+        .header("x-tenant-id", "acme")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let order: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(order["tenant_id"], "acme");
+    // The bootstrap key from config may act for any tenant.
+    let res = client
+        .get(format!("{addr}/orders/{id}"))
+        .header("x-api-key", "root-secret")
+        .header("x-tenant-id", "acme")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn signed_requests_act_for_their_key_tenant_when_jwt_is_configured() {
+    let repo = InMemoryRepo::new();
+    let keys = ApiKeyService::new(repo.clone())
+        .with_bootstrap_key("root-secret")
+        .with_signing_secret("signing-secret");
+    let server = HttpServer::new(OrderService::new(repo), testing::config())
+        .await
+        .unwrap()
+        .with_api_keys(keys)
+        .with_tenant_jwt_secret(SECRET);
+    let server = TestServer::start(server).await.unwrap();
+    let addr = server.base_url();
+    let client = reqwest::Client::new();
+
+    let minted: serde_json::Value = client
+        .post(format!("{addr}/admin/api-keys"))
+        .header("x-api-key", "root-secret")
+        .json(&serde_json::json!({"name": "acme", "scopes": ["write"], "tenant": "acme"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let key_id: Uuid = minted["id"].as_str().unwrap().parse().unwrap();
+    let signing_key = minted["signing_key"].as_str().unwrap();
+    let signed = |method: &str, path: &str, body: &[u8]| {
+        RequestSignature::sign(
+            key_id,
+            signing_key,
+            &SignedRequest {
+                method,
+                path,
+                query: None,
+                body,
+            },
+            chrono::Utc::now(),
+        )
+        .header_value()
+    };
+
+    let body = serde_json::to_vec(&order_body()).unwrap();
+    let res = client
+        .post(format!("{addr}/orders"))
+        .header("authorization", signed("POST", "/orders", &body))
+        .header("content-type", "application/json")
+        .body(body)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let created: serde_json::Value = res.json().await.unwrap();
+    let path = format!("/orders/{}", created["id"].as_str().unwrap());
+    let order: serde_json::Value = client
+        .get(format!("{addr}{path}"))
+        .header("authorization", signed("GET", &path, b""))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(order["tenant_id"], "acme");
+
+    // Other tenants still can't be reached by naming them.
+    let res = client
+        .get(format!("{addr}/orders"))
+        .header("authorization", signed("GET", "/orders", b""))
+        .header("x-tenant-id", "globex")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+    let oversized = vec![b' '; 16 * 1024 * 1024 + 1];
+    let res = client
+        .post(format!("{addr}/orders"))
+        .header("authorization", signed("POST", "/orders", b""))
+        .header("content-type", "application/json")
+        .body(oversized)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
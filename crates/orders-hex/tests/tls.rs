@@ -0,0 +1,69 @@
+#![cfg(unix)]
+
+use orders_hex::application::order_service::OrderService;
+use orders_hex::inbound::http::{HttpServer, HttpServerConfig, TlsConfig};
+use orders_hex::testing::{self, TestServer};
+use orders_repo::memory::InMemoryRepo;
+use reqwest::StatusCode;
+
+/// Self-signed `localhost` certificate and key, as PEM.
+fn self_signed() -> (String, String) {
+    let certified = rcgen::generate_simple_self_signed(vec!["localhost".into()]).unwrap();
+    (certified.cert.pem(), certified.key_pair.serialize_pem())
+}
+
+/// A fresh client per call so no pooled connection outlives a reload.
+async fn health_trusting(addr: &str, cert_pem: &str) -> reqwest::Result<StatusCode> {
+    let client = reqwest::Client::builder()
+        .add_root_certificate(reqwest::Certificate::from_pem(cert_pem.as_bytes()).unwrap())
+        .build()
+        .unwrap();
+    Ok(client.get(format!("{addr}/health")).send().await?.status())
+}
+
+#[tokio::test]
+async fn serves_https_and_reloads_certificate_on_sighup() {
+    let dir = tempfile::tempdir().unwrap();
+    let cert_path = dir.path().join("cert.pem");
+    let key_path = dir.path().join("key.pem");
+    let (first_cert, first_key) = self_signed();
+    std::fs::write(&cert_path, &first_cert).unwrap();
+    std::fs::write(&key_path, &first_key).unwrap();
+
+    let server = HttpServer::new(
+        OrderService::new(InMemoryRepo::new()),
+        HttpServerConfig {
+            tls: Some(TlsConfig::new(&cert_path, &key_path)),
+            ..testing::config()
+        },
+    )
+    .await
+    .unwrap();
+    let server = TestServer::start(server).await.unwrap();
+    let port = server.addr().port();
+
+    let addr = server.base_url();
+    assert_eq!(
+        health_trusting(addr, &first_cert).await.unwrap(),
+        StatusCode::OK
+    );
+    let plain = reqwest::get(format!("http://127.0.0.1:{port}/health")).await;
+    assert!(plain.map_or(true, |r| !r.status().is_success()));
+
+    let (second_cert, second_key) = self_signed();
+    std::fs::write(&cert_path, &second_cert).unwrap();
+    std::fs::write(&key_path, &second_key).unwrap();
+    assert!(health_trusting(addr, &second_cert).await.is_err());
+
+    let status = std::process::Command::new("kill")
+        .args(["-HUP", &std::process::id().to_string()])
+        .status()
+        .unwrap();
+    assert!(status.success());
+    testing::wait_until("the new certificate is served", || async {
+        health_trusting(addr, &second_cert).await.ok() == Some(StatusCode::OK)
+    })
+    .await
+    .unwrap();
+    assert!(health_trusting(addr, &first_cert).await.is_err());
+}
@@ -0,0 +1,79 @@
+use std::time::Duration;
+
+use axum::routing::post;
+use axum::{Json, Router};
+use orders_hex::application::order_service::OrderService;
+use orders_hex::inbound::http::HttpServer;
+use orders_hex::outbound::validator::HttpOrderValidator;
+use orders_hex::testing::{self, TestServer};
+use orders_repo::memory::InMemoryRepo;
+use orders_types::ports::validation::FailurePolicy;
+use reqwest::StatusCode;
+
+/// Accepts orders unless the customer is named "Mallory"; a customer named
+/// "Slow" is answered only after a second.
+async fn spawn_validator() -> String {
+    let app = Router::new().route(
+        "/check",
+        post(|Json(body): Json<serde_json::Value>| async move {
+            let name = body["order"]["customer_name"].as_str().unwrap_or_default();
+            if name == "Slow" {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+            if name == "Mallory" {
+                Json(serde_json::json!({"accept": false, "reason": "customer blocked"}))
+            } else {
+                Json(serde_json::json!({"accept": true}))
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/check", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    url
+}
+
+#[tokio::test]
+async fn external_validator_gates_order_creation() {
+    let validator_url = spawn_validator().await;
+    let service = OrderService::new(InMemoryRepo::new()).with_validator(
+        HttpOrderValidator::new(validator_url),
+        Duration::from_millis(200),
+        FailurePolicy::FailClosed,
+    );
+    let server = HttpServer::new(service, testing::config()).await.unwrap();
+    let server = TestServer::start(server).await.unwrap();
+    let addr = server.base_url();
+
+    let client = reqwest::Client::new();
+    let create = |name: &str| {
+        client
+            .post(format!("{addr}/orders"))
+            .json(&serde_json::json!({
+                "customer_name": name,
+                "email": "v@example.com",
+                "items": [{"name": "Widget", "qty": 1, "unit_price_cents": 100}]
+            }))
+    };
+
+    let res = create("Alice").send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::CREATED);
+
+    let res = create("Mallory").send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(body["error"], "customer blocked");
+
+    let res = create("Slow").send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    let page: serde_json::Value = client
+        .get(format!("{addr}/orders"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(page["orders"].as_array().unwrap().len(), 1);
+}
@@ -0,0 +1,69 @@
+use chrono::{TimeZone, Utc};
+use orders_hex::application::order_service::OrderService;
+use orders_hex::inbound::http::HttpServer;
+use orders_hex::testing::{self, TestServer};
+use orders_repo::memory::InMemoryRepo;
+use reqwest::header::LINK;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn legacy_paths_alias_v1_with_deprecation_headers() {
+    let service = OrderService::new(InMemoryRepo::new());
+    let server = HttpServer::new(service, testing::config())
+        .await
+        .unwrap()
+        .with_legacy_sunset(Utc.with_ymd_and_hms(2027, 1, 1, 0, 0, 0).unwrap());
+    let server = TestServer::start(server).await.unwrap();
+    let addr = server.base_url();
+    let client = reqwest::Client::new();
+
+    let res = client
+        .post(format!("{addr}/v1/orders"))
+        .json(&json!({
+            "customer_name": "Ann",
+            "email": "ann@example.com",
+            "items": [{"name": "Widget", "qty": 1, "unit_price_cents": 500}]
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::CREATED);
+    assert!(res.headers().get("deprecation").is_none());
+    let order: Value = res.json().await.unwrap();
+    let id = order["id"].as_str().unwrap();
+
+    let res = client
+        .get(format!("{addr}/orders/{id}"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()["deprecation"], "true");
+    assert_eq!(res.headers()["sunset"], "Fri, 01 Jan 2027 00:00:00 GMT");
+    assert_eq!(
+        res.headers()[LINK].to_str().unwrap(),
+        format!("</v1/orders/{id}>; rel=\"successor-version\"")
+    );
+    let legacy: Value = res.json().await.unwrap();
+    let current: Value = client
+        .get(format!("{addr}/v1/orders/{id}"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(legacy, current);
+
+    // Probes stay unversioned.
+    let res = client.get(format!("{addr}/healthz")).send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(res.headers().get("deprecation").is_none());
+    let res = client
+        .get(format!("{addr}/v1/healthz"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,94 @@
+use std::sync::{Arc, Mutex};
+
+use axum::http::HeaderMap;
+use axum::routing::post;
+use axum::Router;
+use orders_hex::application::order_service::OrderService;
+use orders_hex::application::webhook_service::{sign, WebhookService, SIGNATURE_HEADER};
+use orders_hex::inbound::http::HttpServer;
+use orders_hex::outbound::webhook::ReqwestTransport;
+use orders_hex::testing::{self, TestServer};
+use orders_repo::memory::InMemoryRepo;
+use orders_types::domain::webhook::WebhookTarget;
+use reqwest::StatusCode;
+
+type Received = Arc<Mutex<Vec<(String, String)>>>;
+
+/// Receiver that records the signature header and body, then answers 202.
+async fn spawn_receiver() -> (String, Received) {
+    let received: Received = Arc::default();
+    let sink = received.clone();
+    let app = Router::new().route(
+        "/hook",
+        post(move |headers: HeaderMap, body: String| async move {
+            let sig = headers[SIGNATURE_HEADER].to_str().unwrap().to_string();
+            sink.lock().unwrap().push((sig, body));
+            (axum::http::StatusCode::ACCEPTED, "thanks")
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (url, received)
+}
+
+#[tokio::test]
+async fn test_delivery_reaches_receiver_signed() {
+    let (hook_url, received) = spawn_receiver().await;
+    let targets =
+        WebhookTarget::parse_list(&format!("crm={hook_url},dead=http://127.0.0.1:9/x")).unwrap();
+    let transport = ReqwestTransport::new(std::time::Duration::from_secs(5)).unwrap();
+    let server = HttpServer::new(OrderService::new(InMemoryRepo::new()), testing::config())
+        .await
+        .unwrap()
+        .with_webhooks(WebhookService::new(targets, "whsec", transport));
+    let server = TestServer::start(server).await.unwrap();
+    let addr = server.base_url();
+
+    let client = reqwest::Client::new();
+    let res = client
+        .post(format!("{addr}/admin/webhooks/crm/test"))
+        .header("x-correlation-id", "probe-1")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let report: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(report["status"], 202);
+    assert_eq!(report["body_excerpt"], "thanks");
+    assert!(report["latency_ms"].is_u64());
+
+    let (sig, body) = received.lock().unwrap().pop().unwrap();
+    let ts: u64 = sig
+        .strip_prefix("t=")
+        .and_then(|s| s.split(',').next())
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert_eq!(sig, sign("whsec", ts, &body));
+    let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(body["specversion"], "1.0");
+    assert_eq!(body["type"], "orders.order.created");
+    assert_eq!(body["test"], true);
+    assert_eq!(body["data"]["type"], "created");
+    assert_eq!(body["id"], report["event_id"]);
+    assert_eq!(body["correlationid"], "probe-1");
+
+    let unreachable: serde_json::Value = client
+        .post(format!("{addr}/admin/webhooks/dead/test"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(unreachable["status"].is_null());
+    assert!(unreachable["error"].is_string());
+
+    let res = client
+        .post(format!("{addr}/admin/webhooks/nope/test"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
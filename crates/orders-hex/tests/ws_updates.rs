@@ -0,0 +1,105 @@
+use futures_util::{SinkExt, StreamExt};
+use orders_hex::testing::TestServer;
+use orders_repo::memory::InMemoryRepo;
+use tokio_tungstenite::tungstenite::Message;
+
+async fn next_json(
+    ws: &mut (impl StreamExt<Item = Result<Message, tokio_tungstenite::tungstenite::Error>> + Unpin),
+) -> serde_json::Value {
+    loop {
+        let msg = tokio::time::timeout(std::time::Duration::from_secs(2), ws.next())
+            .await
+            .expect("frame in time")
+            .expect("stream open")
+            .expect("valid frame");
+        if let Message::Text(text) = msg {
+            return serde_json::from_str(&text).unwrap();
+        }
+    }
+}
+
+#[tokio::test]
+async fn subscribers_receive_matching_order_updates() {
+    let server = TestServer::spawn(InMemoryRepo::new()).await.unwrap();
+    let port = server.addr().port();
+
+    let (mut ws, _) = tokio_tungstenite::connect_async(format!("ws://127.0.0.1:{port}/ws"))
+        .await
+        .unwrap();
+    ws.send(Message::Text(
+        r#"{"action":"subscribe","statuses":["Pending"]}"#.into(),
+    ))
+    .await
+    .unwrap();
+    let ack = next_json(&mut ws).await;
+    assert_eq!(ack["type"], "subscribed");
+
+    let http = reqwest::Client::new();
+    let res = http
+        .post(format!("http://127.0.0.1:{port}/orders"))
+        .header("x-correlation-id", "checkout-7")
+        .json(&serde_json::json!({
+            "customer_name": "Ws",
+            "email": "ws@example.com",
+            "items": [{"name": "Widget", "qty": 1, "unit_price_cents": 100}]
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.headers()["x-correlation-id"], "checkout-7");
+    let created: serde_json::Value = res.json().await.unwrap();
+
+    let frame = next_json(&mut ws).await;
+    assert_eq!(frame["type"], "created");
+    assert_eq!(frame["order"]["id"], created["id"]);
+    assert_eq!(frame["correlation_id"], "checkout-7");
+
+    // Shipping moves the order out of the subscribed status set.
+    http.patch(format!(
+        "http://127.0.0.1:{port}/orders/{}/status",
+        created["id"].as_str().unwrap()
+    ))
+    .json(&serde_json::json!({"status": "Shipped"}))
+    .send()
+    .await
+    .unwrap();
+    ws.send(Message::Text(r#"{"action":"nope"}"#.into()))
+        .await
+        .unwrap();
+    let frame = next_json(&mut ws).await;
+    assert_eq!(frame["type"], "error");
+}
+
+#[tokio::test]
+async fn subscriptions_are_capped_per_connection() {
+    let server = TestServer::spawn(InMemoryRepo::new()).await.unwrap();
+    let port = server.addr().port();
+    let (mut ws, _) = tokio_tungstenite::connect_async(format!("ws://127.0.0.1:{port}/ws"))
+        .await
+        .unwrap();
+
+    let ids = |n: usize| {
+        (0..n)
+            .map(|_| uuid::Uuid::new_v4().to_string())
+            .collect::<Vec<_>>()
+    };
+    let subscribe = |order_ids: Vec<String>| {
+        Message::Text(
+            serde_json::json!({"action": "subscribe", "order_ids": order_ids})
+                .to_string()
+                .into(),
+        )
+    };
+    ws.send(subscribe(ids(999))).await.unwrap();
+    let ack = next_json(&mut ws).await;
+    assert_eq!(ack["order_ids"].as_array().unwrap().len(), 999);
+
+    // One more fits; two more would not, and neither is added.
+    ws.send(subscribe(ids(2))).await.unwrap();
+    let frame = next_json(&mut ws).await;
+    assert_eq!(frame["type"], "error");
+    ws.send(subscribe(ids(1))).await.unwrap();
+    let ack = next_json(&mut ws).await;
+    assert_eq!(ack["type"], "subscribed");
+    assert_eq!(ack["order_ids"].as_array().unwrap().len(), 1000);
+}
@@ -0,0 +1,62 @@
+use serde_json::Value;
+
+/// One replayable request recovered from an access log line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogEntry {
+    pub method: String,
+    pub path: String,
+    pub body: Option<Value>,
+}
+
+/// Parse a structured (JSON lines) access log.
+///
+/// Accepts both flat objects (`{"method":"GET","uri":"/orders"}`) and the
+/// `tracing-subscriber` JSON layout where fields are nested under `fields`.
+/// Lines that are not JSON or carry no method/uri are skipped.
+pub fn parse_log(input: &str) -> Vec<LogEntry> {
+    input.lines().filter_map(parse_line).collect()
+}
+
+pub fn parse_line(line: &str) -> Option<LogEntry> {
+    let value: Value = serde_json::from_str(line.trim()).ok()?;
+    let fields = value.get("fields").unwrap_or(&value);
+    // The HTTP trace layer emits a "request" and a "response" event per call;
+    // only the former should be replayed.
+    if let Some(msg) = fields.get("message").and_then(Value::as_str) {
+        if msg != "request" {
+            return None;
+        }
+    }
+    let method = fields.get("method")?.as_str()?.to_ascii_uppercase();
+    let uri = fields.get("uri").or_else(|| fields.get("path"))?.as_str()?;
+    let path = strip_origin(uri).to_string();
+    let body = fields.get("body").cloned().filter(|b| !b.is_null());
+    Some(LogEntry { method, path, body })
+}
+
+fn strip_origin(uri: &str) -> &str {
+    match uri.split_once("://") {
+        Some((_, rest)) => rest.find('/').map(|i| &rest[i..]).unwrap_or("/"),
+        None => uri,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_flat_and_tracing_layouts() {
+        let log = r#"{"method":"get","uri":"http://host:3000/orders"}
+not json
+{"timestamp":"t","fields":{"message":"request","method":"DELETE","uri":"/orders/abc"}}
+{"fields":{"message":"response","status":"204"}}
+{"method":"POST","path":"/orders","body":{"customer_name":"A"}}"#;
+        let entries = parse_log(log);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].method, "GET");
+        assert_eq!(entries[0].path, "/orders");
+        assert_eq!(entries[1].path, "/orders/abc");
+        assert!(entries[2].body.is_some());
+    }
+}
@@ -0,0 +1,69 @@
+//! orders-replay: replay structured access logs against an Orders API.
+//!
+//! ```bash
+//! cargo run -p orders-replay -- --log access.jsonl --target http://127.0.0.1:3000/ --rate 20 --anonymize
+//! ```
+
+mod anonymize;
+mod log;
+mod replay;
+
+use std::io::Read;
+use std::path::PathBuf;
+
+use clap::Parser;
+use orders_client::OrdersClient;
+
+use crate::replay::{replay, ReplayOptions};
+
+#[derive(Parser, Debug)]
+#[command(about = "Replay Orders API access logs against a target environment")]
+struct Args {
+    /// JSON-lines access log to read; `-` reads stdin.
+    #[arg(long)]
+    log: PathBuf,
+    /// Base URL of the target Orders API.
+    #[arg(long, default_value = "http://127.0.0.1:3000/")]
+    target: String,
+    /// Maximum requests per second (unlimited when omitted).
+    #[arg(long)]
+    rate: Option<f64>,
+    /// Replace customer names and emails with stable fakes before sending.
+    #[arg(long)]
+    anonymize: bool,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(std::env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string()))
+        .init();
+
+    let args = Args::parse();
+    let input = if args.log.as_os_str() == "-" {
+        let mut buf = String::new();
+        std::io::stdin().read_to_string(&mut buf)?;
+        buf
+    } else {
+        std::fs::read_to_string(&args.log)?
+    };
+
+    let entries = log::parse_log(&input);
+    tracing::info!(entries = entries.len(), target = %args.target, "replaying access log");
+
+    let client = OrdersClient::new(&args.target)?;
+    let opts = ReplayOptions {
+        rate: args.rate,
+        anonymize: args.anonymize,
+    };
+    let stats = replay(&client, entries, &opts).await;
+
+    println!(
+        "sent={} failed={} skipped={}",
+        stats.sent, stats.failed, stats.skipped
+    );
+    for (route, count) in &stats.by_route {
+        println!("  {route}: {count}");
+    }
+    Ok(())
+}
@@ -0,0 +1,147 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use anyhow::Context;
+use orders_client::{CreateOrderRequest, OrdersClient};
+use orders_types::domain::order::OrderStatus;
+use serde::Deserialize;
+
+use crate::anonymize::anonymize_body;
+use crate::log::LogEntry;
+
+#[derive(Debug, Clone)]
+pub struct ReplayOptions {
+    /// Maximum requests per second; `None` replays as fast as possible.
+    pub rate: Option<f64>,
+    pub anonymize: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct ReplayStats {
+    pub sent: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub by_route: BTreeMap<&'static str, usize>,
+}
+
+#[derive(Deserialize)]
+struct StatusBody {
+    status: OrderStatus,
+}
+
+pub async fn replay(
+    client: &OrdersClient,
+    entries: Vec<LogEntry>,
+    opts: &ReplayOptions,
+) -> ReplayStats {
+    let mut stats = ReplayStats::default();
+    let mut ticker = opts
+        .rate
+        .filter(|r| *r > 0.0)
+        .map(|r| tokio::time::interval(Duration::from_secs_f64(1.0 / r)));
+
+    for mut entry in entries {
+        if opts.anonymize {
+            if let Some(body) = entry.body.as_mut() {
+                anonymize_body(body);
+            }
+        }
+        let Some(route) = route_of(&entry) else {
+            tracing::debug!(method = %entry.method, path = %entry.path, "skipping unsupported request");
+            stats.skipped += 1;
+            continue;
+        };
+        if let Some(t) = ticker.as_mut() {
+            t.tick().await;
+        }
+        stats.sent += 1;
+        *stats.by_route.entry(route).or_default() += 1;
+        if let Err(err) = send(client, route, &entry).await {
+            tracing::warn!(method = %entry.method, path = %entry.path, error = %err, "replay failed");
+            stats.failed += 1;
+        }
+    }
+    stats
+}
+
+/// Path segments of `entry` without the query, or the `v1` prefix that
+/// versioned and legacy paths differ by.
+fn segments(entry: &LogEntry) -> Vec<&str> {
+    let path = entry.path.split('?').next().unwrap_or_default();
+    let mut segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    if segments.first() == Some(&"v1") {
+        segments.remove(0);
+    }
+    segments
+}
+
+fn route_of(entry: &LogEntry) -> Option<&'static str> {
+    match (entry.method.as_str(), segments(entry).as_slice()) {
+        ("POST", ["orders"]) if entry.body.is_some() => Some("create"),
+        ("GET", ["orders"]) => Some("list"),
+        ("GET", ["orders", _]) => Some("get"),
+        ("PATCH", ["orders", _, "status"]) if entry.body.is_some() => Some("update_status"),
+        ("DELETE", ["orders", _]) => Some("delete"),
+        _ => None,
+    }
+}
+
+async fn send(client: &OrdersClient, route: &str, entry: &LogEntry) -> anyhow::Result<()> {
+    let segments = segments(entry);
+    let id = segments.get(1).copied().unwrap_or_default();
+    let body = || entry.body.clone().unwrap_or_default();
+    match route {
+        "create" => {
+            let req: CreateOrderRequest =
+                serde_json::from_value(body()).context("invalid create body")?;
+            client.create_order(req).await?;
+        }
+        "list" => {
+            client.list_orders().await?;
+        }
+        "get" => {
+            client.get_order(id).await?;
+        }
+        "update_status" => {
+            let req: StatusBody = serde_json::from_value(body()).context("invalid status body")?;
+            client.update_status(id, req.status).await?;
+        }
+        "delete" => client.delete_order(id).await?,
+        _ => unreachable!("route_of only yields known routes"),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(method: &str, path: &str, body: bool) -> LogEntry {
+        LogEntry {
+            method: method.into(),
+            path: path.into(),
+            body: body.then(|| serde_json::json!({})),
+        }
+    }
+
+    #[test]
+    fn routes_known_requests_only() {
+        assert_eq!(route_of(&entry("POST", "/orders", true)), Some("create"));
+        assert_eq!(route_of(&entry("POST", "/orders", false)), None);
+        assert_eq!(
+            route_of(&entry("GET", "/orders?status=Pending", false)),
+            Some("list")
+        );
+        assert_eq!(route_of(&entry("GET", "/orders/1", false)), Some("get"));
+        assert_eq!(
+            route_of(&entry("PATCH", "/orders/1/status", true)),
+            Some("update_status")
+        );
+        assert_eq!(
+            route_of(&entry("DELETE", "/orders/1", false)),
+            Some("delete")
+        );
+        assert_eq!(route_of(&entry("GET", "/v1/orders/1", false)), Some("get"));
+        assert_eq!(route_of(&entry("GET", "/health", false)), None);
+    }
+}
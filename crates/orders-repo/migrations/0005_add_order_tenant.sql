@@ -0,0 +1,2 @@
+-- Orders written before multi-tenancy belong to the default tenant.
+ALTER TABLE orders ADD COLUMN tenant_id TEXT NOT NULL DEFAULT 'default';
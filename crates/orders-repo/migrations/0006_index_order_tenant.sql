@@ -0,0 +1 @@
+CREATE INDEX IF NOT EXISTS idx_orders_tenant ON orders (tenant_id);
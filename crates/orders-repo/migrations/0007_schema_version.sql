@@ -0,0 +1,4 @@
+-- Current schema version for operators and tooling; sqlx records the
+-- individual migrations in `_sqlx_migrations`.
+CREATE VIEW IF NOT EXISTS schema_version AS
+  SELECT MAX(version) AS version FROM _sqlx_migrations WHERE success = 1;
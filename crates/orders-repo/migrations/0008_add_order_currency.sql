@@ -0,0 +1,2 @@
+-- Orders stored before multi-currency support were priced in US dollars.
+ALTER TABLE orders ADD COLUMN currency TEXT NOT NULL DEFAULT 'USD';
@@ -0,0 +1,7 @@
+-- Breakdown of total_cents. Orders stored before tax and shipping were
+-- charged are all subtotal.
+ALTER TABLE orders ADD COLUMN subtotal_cents INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE orders ADD COLUMN discount_cents INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE orders ADD COLUMN tax_cents INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE orders ADD COLUMN shipping_cents INTEGER NOT NULL DEFAULT 0;
+UPDATE orders SET subtotal_cents = total_cents;
@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS discounts (
+  tenant_id TEXT NOT NULL,
+  code TEXT NOT NULL,
+  kind_json TEXT NOT NULL,
+  min_order_json TEXT,
+  expires_at TEXT,
+  max_uses INTEGER,
+  uses INTEGER NOT NULL DEFAULT 0,
+  created_at TEXT NOT NULL,
+  PRIMARY KEY (tenant_id, code)
+);
+
+-- The discount code applied to an order, if any.
+ALTER TABLE orders ADD COLUMN discount_json TEXT;
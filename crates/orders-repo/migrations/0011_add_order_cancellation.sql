@@ -0,0 +1,3 @@
+-- Why and when an order was cancelled; NULL for orders that weren't.
+ALTER TABLE orders ADD COLUMN cancel_reason TEXT;
+ALTER TABLE orders ADD COLUMN cancelled_at TEXT;
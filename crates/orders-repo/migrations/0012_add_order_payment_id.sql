@@ -0,0 +1,2 @@
+-- The payment provider's id for the charge taken when the order was confirmed.
+ALTER TABLE orders ADD COLUMN payment_id TEXT;
@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS audit_log (
+  id TEXT PRIMARY KEY,
+  tenant_id TEXT NOT NULL,
+  order_id TEXT NOT NULL,
+  action TEXT NOT NULL,
+  actor TEXT NOT NULL,
+  at TEXT NOT NULL,
+  before_json TEXT,
+  after_json TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_order ON audit_log (tenant_id, order_id, at);
+CREATE INDEX IF NOT EXISTS idx_audit_log_tenant_at ON audit_log (tenant_id, at);
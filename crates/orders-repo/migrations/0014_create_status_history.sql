@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS status_history (
+  tenant_id TEXT NOT NULL,
+  order_id TEXT NOT NULL,
+  from_status TEXT,
+  to_status TEXT NOT NULL,
+  at TEXT NOT NULL,
+  actor TEXT NOT NULL,
+  note TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_status_history_order ON status_history (tenant_id, order_id);
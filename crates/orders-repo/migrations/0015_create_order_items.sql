@@ -0,0 +1,30 @@
+-- Order lines get their own rows instead of a JSON array on the order.
+-- Plain `items_json` payloads are copied here; zstd-compressed (blob) ones
+-- are unpacked by the adapter right after migrating. `items_json` is left
+-- as an empty array and no longer read.
+CREATE TABLE IF NOT EXISTS order_items (
+  order_id TEXT NOT NULL REFERENCES orders (id) ON DELETE CASCADE,
+  position INTEGER NOT NULL,
+  name TEXT NOT NULL,
+  qty INTEGER NOT NULL,
+  unit_price_cents INTEGER NOT NULL,
+  currency TEXT NOT NULL DEFAULT 'USD',
+  weight_grams INTEGER NOT NULL DEFAULT 0,
+  PRIMARY KEY (order_id, position)
+);
+
+CREATE INDEX IF NOT EXISTS idx_order_items_name ON order_items (name);
+
+INSERT INTO order_items (order_id, position, name, qty, unit_price_cents, currency, weight_grams)
+SELECT
+  orders.id,
+  item.key,
+  json_extract(item.value, '$.name'),
+  json_extract(item.value, '$.qty'),
+  json_extract(item.value, '$.unit_price_cents'),
+  COALESCE(json_extract(item.value, '$.currency'), 'USD'),
+  COALESCE(json_extract(item.value, '$.weight_grams'), 0)
+FROM orders, json_each(orders.items_json) AS item
+WHERE typeof(orders.items_json) = 'text';
+
+UPDATE orders SET items_json = '[]' WHERE typeof(items_json) = 'text';
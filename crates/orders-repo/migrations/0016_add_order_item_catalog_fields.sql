@@ -0,0 +1,6 @@
+ALTER TABLE order_items ADD COLUMN sku TEXT;
+ALTER TABLE order_items ADD COLUMN description TEXT;
+ALTER TABLE order_items ADD COLUMN metadata_json TEXT;
+ALTER TABLE order_items ADD COLUMN discount_cents INTEGER NOT NULL DEFAULT 0;
+
+CREATE INDEX IF NOT EXISTS idx_order_items_sku ON order_items (sku);
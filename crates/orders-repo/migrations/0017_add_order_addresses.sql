@@ -0,0 +1,6 @@
+ALTER TABLE orders ADD COLUMN shipping_address_json TEXT;
+ALTER TABLE orders ADD COLUMN billing_address_json TEXT;
+-- Copied out of `shipping_address_json` so listings can filter on it.
+ALTER TABLE orders ADD COLUMN shipping_country TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_orders_tenant_shipping_country ON orders (tenant_id, shipping_country);
@@ -0,0 +1,19 @@
+-- Shipments of some or all of an order's lines; both tables go with the
+-- order (`ON DELETE CASCADE`).
+CREATE TABLE IF NOT EXISTS order_fulfillments (
+  id TEXT PRIMARY KEY NOT NULL,
+  tenant_id TEXT NOT NULL,
+  order_id TEXT NOT NULL REFERENCES orders (id) ON DELETE CASCADE,
+  carrier TEXT NOT NULL,
+  tracking_number TEXT NOT NULL,
+  shipped_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_order_fulfillments_order ON order_fulfillments (tenant_id, order_id);
+
+CREATE TABLE IF NOT EXISTS order_fulfillment_items (
+  fulfillment_id TEXT NOT NULL REFERENCES order_fulfillments (id) ON DELETE CASCADE,
+  position INTEGER NOT NULL,
+  qty INTEGER NOT NULL,
+  PRIMARY KEY (fulfillment_id, position)
+);
@@ -0,0 +1,16 @@
+-- Order events written in the same transaction as the change, for the
+-- orders-worker relay. sqlite serializes writers, so `seq` order is commit
+-- order; AUTOINCREMENT keeps a pruned `seq` from being handed out again.
+CREATE TABLE IF NOT EXISTS outbox (
+  seq INTEGER PRIMARY KEY AUTOINCREMENT,
+  tenant_id TEXT NOT NULL,
+  order_id TEXT NOT NULL,
+  event_json TEXT NOT NULL,
+  recorded_at TEXT NOT NULL
+);
+
+-- The last `seq` each relay consumer has handled.
+CREATE TABLE IF NOT EXISTS outbox_checkpoints (
+  consumer TEXT PRIMARY KEY NOT NULL,
+  seq INTEGER NOT NULL
+);
@@ -0,0 +1,23 @@
+-- Read model of orders, maintained by the orders-worker `projection` sink
+-- from the outbox. One row per order with its items flattened into columns
+-- and the whole order as JSON, so reads need no joins.
+CREATE TABLE IF NOT EXISTS order_views (
+  id TEXT NOT NULL,
+  tenant_id TEXT NOT NULL,
+  status TEXT NOT NULL,
+  customer_name TEXT NOT NULL,
+  email TEXT NOT NULL,
+  shipping_country TEXT,
+  total_cents INTEGER NOT NULL,
+  currency TEXT NOT NULL,
+  item_count INTEGER NOT NULL,
+  unit_count INTEGER NOT NULL,
+  item_names TEXT NOT NULL,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL,
+  order_json TEXT NOT NULL,
+  PRIMARY KEY (tenant_id, id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_order_views_tenant_created ON order_views (tenant_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_order_views_tenant_status ON order_views (tenant_id, status);
@@ -0,0 +1,2 @@
+-- Lets the stale-order sweep find old pending orders without a table scan.
+CREATE INDEX IF NOT EXISTS idx_orders_status_created ON orders (status, created_at);
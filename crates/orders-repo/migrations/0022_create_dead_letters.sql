@@ -0,0 +1,17 @@
+-- Outbox records a relay consumer gave up on, kept for replay. The record
+-- is copied whole so pruning the outbox doesn't lose it.
+CREATE TABLE IF NOT EXISTS dead_letters (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  consumer TEXT NOT NULL,
+  seq INTEGER NOT NULL,
+  record_json TEXT NOT NULL,
+  attempts INTEGER NOT NULL,
+  error TEXT NOT NULL,
+  failed_at TEXT NOT NULL,
+  state TEXT NOT NULL,
+  replay_requested_at TEXT,
+  replayed_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_dead_letters_consumer_state
+  ON dead_letters (consumer, state, id);
@@ -0,0 +1,8 @@
+-- Blind index of the email (an HMAC of the lowercased address), so orders
+-- can still be filtered by email once it is encrypted at rest. NULL while
+-- field encryption is off.
+ALTER TABLE orders ADD COLUMN email_index TEXT;
+ALTER TABLE order_views ADD COLUMN email_index TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_orders_tenant_email_index ON orders (tenant_id, email_index);
+CREATE INDEX IF NOT EXISTS idx_order_views_tenant_email_index ON order_views (tenant_id, email_index);
@@ -0,0 +1,3 @@
+-- Lets listings and stats bounded by creation time scan only that slice of
+-- a tenant's orders.
+CREATE INDEX IF NOT EXISTS idx_orders_tenant_created ON orders (tenant_id, created_at);
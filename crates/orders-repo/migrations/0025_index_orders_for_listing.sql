@@ -0,0 +1,14 @@
+-- Indexes for the filters and sorts of `GET /orders`, each led by the tenant
+-- every listing is scoped to. A status filter sorted or bounded by creation
+-- time reads one slice of the status index in order. Email lookups match
+-- case-insensitively, as the filter does, and carry the creation time so
+-- SQLite prefers them over `idx_orders_tenant_created` for a sorted listing.
+CREATE INDEX IF NOT EXISTS idx_orders_tenant_status_created ON orders (tenant_id, status, created_at);
+CREATE INDEX IF NOT EXISTS idx_orders_tenant_email ON orders (tenant_id, email COLLATE NOCASE, created_at);
+
+CREATE INDEX IF NOT EXISTS idx_order_views_tenant_status_created ON order_views (tenant_id, status, created_at);
+CREATE INDEX IF NOT EXISTS idx_order_views_tenant_email ON order_views (tenant_id, email COLLATE NOCASE, created_at);
+
+-- Prefixes of the indexes above, so they only cost writes.
+DROP INDEX IF EXISTS idx_orders_tenant;
+DROP INDEX IF EXISTS idx_order_views_tenant_status;
@@ -0,0 +1,12 @@
+-- Sequence behind each order's human-readable number (`ORD-<year>-<seq>`,
+-- the year taken from `created_at`). Existing orders are numbered in the
+-- order they were created; new ones take the next number on insert.
+ALTER TABLE orders ADD COLUMN order_seq INTEGER;
+
+UPDATE orders SET order_seq = (
+    SELECT numbered.seq
+    FROM (SELECT id, ROW_NUMBER() OVER (ORDER BY created_at, id) AS seq FROM orders) AS numbered
+    WHERE numbered.id = orders.id
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_orders_order_seq ON orders (order_seq);
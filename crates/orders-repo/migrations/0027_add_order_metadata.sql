@@ -0,0 +1,9 @@
+-- Integrators' key/value references on an order, as a JSON object; NULL
+-- when there are none. `order_views` carries a copy so listings from the
+-- read model can filter on it too.
+ALTER TABLE orders ADD COLUMN metadata_json TEXT;
+
+ALTER TABLE order_views ADD COLUMN metadata_json TEXT;
+
+UPDATE order_views SET metadata_json = json_extract(order_json, '$.metadata')
+WHERE json_extract(order_json, '$.metadata') IS NOT NULL;
@@ -0,0 +1,5 @@
+-- Who placed each order and who last changed it, e.g. `key:<id>` or
+-- `user:<sub>`; NULL for orders stored before changes were attributed.
+ALTER TABLE orders ADD COLUMN created_by TEXT;
+
+ALTER TABLE orders ADD COLUMN updated_by TEXT;
@@ -0,0 +1,2 @@
+-- Why a change was made, given for admin status overrides; NULL otherwise.
+ALTER TABLE audit_log ADD COLUMN reason TEXT;
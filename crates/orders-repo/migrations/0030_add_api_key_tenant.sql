@@ -0,0 +1,3 @@
+-- The tenant an API key is bound to; existing keys keep acting for the
+-- default one.
+ALTER TABLE api_keys ADD COLUMN tenant_id TEXT NOT NULL DEFAULT 'default';
@@ -0,0 +1,3 @@
+-- The amount captured with payment_id at confirmation; refunds return it
+-- even if the total changes afterwards.
+ALTER TABLE orders ADD COLUMN captured_cents INTEGER;
@@ -0,0 +1,3 @@
+-- How a re-pricing moved the order's pricing, kept for `repriced` entries;
+-- NULL otherwise.
+ALTER TABLE audit_log ADD COLUMN pricing_diff_json TEXT;
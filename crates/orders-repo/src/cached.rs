@@ -0,0 +1,620 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use orders_types::domain::api_key::ApiKey;
+use orders_types::domain::audit::AuditEntry;
+use orders_types::domain::discount::Discount;
+use orders_types::domain::filter::OrderFilter;
+use orders_types::domain::fulfillment::Fulfillment;
+use orders_types::domain::history::OrderHistoryEntry;
+use orders_types::domain::integrity::{IntegrityReport, StatusMapping};
+use orders_types::domain::order::{Order, OrderStatus};
+use orders_types::domain::order_number::OrderNumber;
+use orders_types::domain::stats::{OrderStats, StatsRange};
+use orders_types::domain::tenant::TenantId;
+use orders_types::ports::api_key_repository::ApiKeyRepository;
+use orders_types::ports::audit_repository::AuditRepository;
+use orders_types::ports::discount_repository::DiscountRepository;
+use orders_types::ports::metrics::MetricsSource;
+use orders_types::ports::order_repository::{OrderRepository, RepoError};
+use orders_types::ports::unit_of_work::{ChangeKind, UnitOfWork};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::sqlite::SqliteRepo;
+
+/// Orders kept in memory when no [capacity](CachedRepo::with_capacity) is
+/// set.
+pub const DEFAULT_CACHE_CAPACITY: usize = 10_000;
+
+/// sqlite fronted by an in-memory cache of orders.
+///
+/// sqlite is the source of truth: every write lands there first and only
+/// then in memory, and a failed write drops the cached copy. Reads by id try
+/// memory and fall back to sqlite, caching what they find. Listings, counts,
+/// stats and everything that isn't an order always go to sqlite, since the
+/// cache only holds the orders that were touched. The cache holds at most
+/// [`capacity`](Self::with_capacity) orders, dropping the least recently
+/// used first, and optionally forgets orders after a [ttl](Self::with_ttl).
+#[derive(Clone)]
+pub struct CachedRepo {
+    sqlite: SqliteRepo,
+    memory: OrderCache,
+    metrics: CacheMetrics,
+}
+
+impl CachedRepo {
+    pub fn new(sqlite: SqliteRepo) -> Self {
+        Self {
+            sqlite,
+            memory: OrderCache::new(DEFAULT_CACHE_CAPACITY, None),
+            metrics: CacheMetrics::default(),
+        }
+    }
+
+    /// Keep at most `capacity` orders in memory; at least one is kept.
+    /// Drops what is cached so far.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.memory = OrderCache::new(capacity, self.memory.ttl);
+        self
+    }
+
+    /// Go back to sqlite for orders cached longer than `ttl` ago, so
+    /// changes made by other processes show up within it. Drops what is
+    /// cached so far.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.memory = OrderCache::new(self.memory.capacity, Some(ttl));
+        self
+    }
+
+    /// Orders currently held in memory.
+    pub fn cached_len(&self) -> usize {
+        self.memory.len()
+    }
+
+    pub fn sqlite(&self) -> &SqliteRepo {
+        &self.sqlite
+    }
+
+    /// Hit and miss counters, shared with this repo.
+    pub fn metrics(&self) -> CacheMetrics {
+        self.metrics.clone()
+    }
+
+    /// Cache the outcome of a write that went through to sqlite.
+    fn write_through(
+        &self,
+        id: Uuid,
+        stored: Result<Option<Order>, RepoError>,
+    ) -> Result<Option<Order>, RepoError> {
+        match &stored {
+            Ok(Some(order)) => self.memory.insert(order.clone()),
+            // Gone, or in an unknown state after an error.
+            Ok(None) | Err(_) => self.memory.remove(id),
+        }
+        stored
+    }
+}
+
+/// Orders by id, bounded by a capacity with least recently used eviction
+/// and an optional time to live.
+#[derive(Clone)]
+struct OrderCache {
+    capacity: usize,
+    ttl: Option<Duration>,
+    entries: Arc<Mutex<Entries>>,
+}
+
+#[derive(Default)]
+struct Entries {
+    orders: HashMap<Uuid, Entry>,
+    /// Ids by the tick of their last use, oldest first.
+    recency: BTreeMap<u64, Uuid>,
+    tick: u64,
+}
+
+struct Entry {
+    order: Order,
+    used: u64,
+    cached_at: Instant,
+}
+
+impl Entries {
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    /// Store `entry` as the most recently used.
+    fn touch(&mut self, mut entry: Entry) {
+        entry.used = self.next_tick();
+        self.recency.insert(entry.used, entry.order.id);
+        self.orders.insert(entry.order.id, entry);
+    }
+
+    fn remove(&mut self, id: Uuid) -> Option<Entry> {
+        let entry = self.orders.remove(&id)?;
+        self.recency.remove(&entry.used);
+        Some(entry)
+    }
+}
+
+impl OrderCache {
+    fn new(capacity: usize, ttl: Option<Duration>) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            ttl,
+            entries: Arc::default(),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Entries> {
+        // Every update leaves the entries consistent, so a panic elsewhere
+        // doesn't make them unusable.
+        self.entries
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// The cached order, when it belongs to `tenant` and hasn't expired.
+    fn get(&self, tenant: &TenantId, id: Uuid) -> Option<Order> {
+        let mut entries = self.lock();
+        let entry = entries.orders.get(&id)?;
+        if self.ttl.is_some_and(|ttl| entry.cached_at.elapsed() >= ttl) {
+            entries.remove(id);
+            return None;
+        }
+        if &entry.order.tenant_id != tenant {
+            return None;
+        }
+        let order = entry.order.clone();
+        let entry = entries.remove(id)?;
+        entries.touch(entry);
+        Some(order)
+    }
+
+    fn insert(&self, order: Order) {
+        let mut entries = self.lock();
+        entries.remove(order.id);
+        while entries.orders.len() >= self.capacity {
+            let Some((_, oldest)) = entries.recency.pop_first() else {
+                break;
+            };
+            entries.orders.remove(&oldest);
+        }
+        entries.touch(Entry {
+            order,
+            used: 0,
+            cached_at: Instant::now(),
+        });
+    }
+
+    fn remove(&self, id: Uuid) {
+        self.lock().remove(id);
+    }
+
+    fn clear(&self) {
+        *self.lock() = Entries::default();
+    }
+
+    fn len(&self) -> usize {
+        self.lock().orders.len()
+    }
+}
+
+/// Reads by id answered from memory (`hits`) or sqlite (`misses`).
+#[derive(Clone, Default)]
+pub struct CacheMetrics {
+    hits: Arc<AtomicU64>,
+    misses: Arc<AtomicU64>,
+}
+
+impl CacheMetrics {
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Share of reads served from memory; `0` before the first read.
+    pub fn hit_rate(&self) -> f64 {
+        let (hits, misses) = (self.hits(), self.misses());
+        match hits + misses {
+            0 => 0.0,
+            total => hits as f64 / total as f64,
+        }
+    }
+
+    fn record(&self, hit: bool) {
+        let counter = if hit { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl CacheMetrics {
+    /// OpenMetrics names a counter family without the `_total` of its
+    /// sample; the Prometheus text format names it after the sample.
+    fn render(&self, openmetrics: bool) -> String {
+        let mut out = String::new();
+        for (name, help, value) in [
+            (
+                "orders_repo_cache_hits_total",
+                "Order reads served from the memory cache.",
+                self.hits(),
+            ),
+            (
+                "orders_repo_cache_misses_total",
+                "Order reads that fell through to sqlite.",
+                self.misses(),
+            ),
+        ] {
+            let family = if openmetrics {
+                name.trim_end_matches("_total")
+            } else {
+                name
+            };
+            let _ = writeln!(
+                out,
+                "# HELP {family} {help}\n# TYPE {family} counter\n{name} {value}"
+            );
+        }
+        let _ = writeln!(
+            out,
+            "# HELP orders_repo_cache_hit_rate Share of order reads served from memory.\n\
+             # TYPE orders_repo_cache_hit_rate gauge\n\
+             orders_repo_cache_hit_rate {}",
+            self.hit_rate()
+        );
+        out
+    }
+}
+
+impl MetricsSource for CacheMetrics {
+    fn prometheus(&self) -> String {
+        self.render(false)
+    }
+
+    fn openmetrics(&self) -> String {
+        self.render(true)
+    }
+}
+
+/// A sqlite transaction that drops the orders it wrote from the cache once
+/// it is over, so the next read sees what was committed.
+struct CachedUnit<'a> {
+    inner: Box<dyn UnitOfWork + 'a>,
+    memory: &'a OrderCache,
+    touched: Vec<Uuid>,
+}
+
+#[async_trait]
+impl UnitOfWork for CachedUnit<'_> {
+    async fn create(&mut self, order: Order) -> Result<Order, RepoError> {
+        self.touched.push(order.id);
+        self.inner.create(order).await
+    }
+
+    async fn update(&mut self, order: Order) -> Result<Option<Order>, RepoError> {
+        self.touched.push(order.id);
+        self.inner.update(order).await
+    }
+
+    async fn update_from(
+        &mut self,
+        order: Order,
+        read: &Order,
+        kind: ChangeKind,
+    ) -> Result<Option<Order>, RepoError> {
+        self.touched.push(order.id);
+        self.inner.update_from(order, read, kind).await
+    }
+
+    async fn update_status(
+        &mut self,
+        tenant: &TenantId,
+        id: Uuid,
+        status: OrderStatus,
+    ) -> Result<Option<Order>, RepoError> {
+        self.touched.push(id);
+        self.inner.update_status(tenant, id, status).await
+    }
+
+    async fn record_transition(
+        &mut self,
+        tenant: &TenantId,
+        id: Uuid,
+        entry: OrderHistoryEntry,
+    ) -> Result<(), RepoError> {
+        self.inner.record_transition(tenant, id, entry).await
+    }
+
+    async fn record_fulfillment(
+        &mut self,
+        tenant: &TenantId,
+        id: Uuid,
+        fulfillment: Fulfillment,
+    ) -> Result<(), RepoError> {
+        self.inner.record_fulfillment(tenant, id, fulfillment).await
+    }
+
+    async fn commit(self: Box<Self>) -> Result<(), RepoError> {
+        let unit = *self;
+        let committed = unit.inner.commit().await;
+        // Dropped even when the commit failed: the next read goes to sqlite.
+        for id in &unit.touched {
+            unit.memory.remove(*id);
+        }
+        committed
+    }
+
+    async fn rollback(self: Box<Self>) -> Result<(), RepoError> {
+        // Nothing reached the cache.
+        self.inner.rollback().await
+    }
+}
+
+#[async_trait]
+impl OrderRepository for CachedRepo {
+    async fn begin(&self) -> Result<Box<dyn UnitOfWork + '_>, RepoError> {
+        Ok(Box::new(CachedUnit {
+            inner: self.sqlite.begin().await?,
+            memory: &self.memory,
+            touched: Vec::new(),
+        }))
+    }
+
+    async fn create(&self, order: Order) -> Result<Order, RepoError> {
+        let order = self.sqlite.create(order).await?;
+        self.memory.insert(order.clone());
+        Ok(order)
+    }
+
+    async fn create_many(&self, orders: Vec<Order>) -> Result<(), RepoError> {
+        // Not cached: the stored orders have numbers these don't.
+        self.sqlite.create_many(orders).await
+    }
+
+    async fn get(&self, tenant: &TenantId, id: Uuid) -> Result<Option<Order>, RepoError> {
+        if let Some(order) = self.memory.get(tenant, id) {
+            self.metrics.record(true);
+            return Ok(Some(order));
+        }
+        self.metrics.record(false);
+        let stored = self.sqlite.get(tenant, id).await?;
+        if let Some(order) = &stored {
+            self.memory.insert(order.clone());
+        }
+        Ok(stored)
+    }
+
+    /// Always read from SQLite, which indexes numbers; the order found is
+    /// cached for lookups by id.
+    async fn get_by_number(
+        &self,
+        tenant: &TenantId,
+        number: &OrderNumber,
+    ) -> Result<Option<Order>, RepoError> {
+        let stored = self.sqlite.get_by_number(tenant, number).await?;
+        if let Some(order) = &stored {
+            self.memory.insert(order.clone());
+        }
+        Ok(stored)
+    }
+
+    async fn list(&self, tenant: &TenantId) -> Result<Vec<Order>, RepoError> {
+        self.sqlite.list(tenant).await
+    }
+
+    async fn list_filtered(
+        &self,
+        tenant: &TenantId,
+        filter: &OrderFilter,
+    ) -> Result<Vec<Order>, RepoError> {
+        self.sqlite.list_filtered(tenant, filter).await
+    }
+
+    async fn exists(&self, tenant: &TenantId, id: Uuid) -> Result<bool, RepoError> {
+        if self.memory.get(tenant, id).is_some() {
+            self.metrics.record(true);
+            return Ok(true);
+        }
+        self.metrics.record(false);
+        self.sqlite.exists(tenant, id).await
+    }
+
+    async fn count(&self, tenant: &TenantId, filter: &OrderFilter) -> Result<usize, RepoError> {
+        self.sqlite.count(tenant, filter).await
+    }
+
+    async fn stale_pending(
+        &self,
+        before: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<Order>, RepoError> {
+        self.sqlite.stale_pending(before, limit).await
+    }
+
+    async fn scan(&self, after: Option<Uuid>, limit: usize) -> Result<Vec<Order>, RepoError> {
+        self.sqlite.scan(after, limit).await
+    }
+
+    async fn aggregate(
+        &self,
+        tenant: &TenantId,
+        range: &StatsRange,
+    ) -> Result<OrderStats, RepoError> {
+        self.sqlite.aggregate(tenant, range).await
+    }
+
+    async fn update_status(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        status: OrderStatus,
+    ) -> Result<Option<Order>, RepoError> {
+        let stored = self.sqlite.update_status(tenant, id, status).await;
+        self.write_through(id, stored)
+    }
+
+    async fn update(&self, order: Order) -> Result<Option<Order>, RepoError> {
+        let id = order.id;
+        let stored = self.sqlite.update(order).await;
+        self.write_through(id, stored)
+    }
+
+    async fn update_items(
+        &self,
+        order: &Order,
+        read_at: DateTime<Utc>,
+    ) -> Result<Option<Order>, RepoError> {
+        let stored = self.sqlite.update_items(order, read_at).await;
+        self.write_through(order.id, stored)
+    }
+
+    async fn delete(&self, tenant: &TenantId, id: Uuid) -> Result<bool, RepoError> {
+        let deleted = self.sqlite.delete(tenant, id).await;
+        // Dropped even when sqlite failed: the next read goes to sqlite.
+        self.memory.remove(id);
+        deleted
+    }
+
+    async fn record_transition(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        entry: OrderHistoryEntry,
+    ) -> Result<(), RepoError> {
+        self.sqlite.record_transition(tenant, id, entry).await
+    }
+
+    async fn status_history(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+    ) -> Result<Vec<OrderHistoryEntry>, RepoError> {
+        self.sqlite.status_history(tenant, id).await
+    }
+
+    async fn record_fulfillment(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        fulfillment: Fulfillment,
+    ) -> Result<(), RepoError> {
+        self.sqlite
+            .record_fulfillment(tenant, id, fulfillment)
+            .await
+    }
+
+    async fn fulfillments(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+    ) -> Result<Vec<Fulfillment>, RepoError> {
+        self.sqlite.fulfillments(tenant, id).await
+    }
+
+    async fn check_integrity(
+        &self,
+        mapping: &StatusMapping,
+        fix: bool,
+    ) -> Result<IntegrityReport, RepoError> {
+        let report = self.sqlite.check_integrity(mapping, fix).await;
+        if fix {
+            // Rows may have been rewritten underneath the cache.
+            self.memory.clear();
+        }
+        report
+    }
+
+    async fn ping(&self) -> Result<(), RepoError> {
+        self.sqlite.ping().await
+    }
+}
+
+#[async_trait]
+impl ApiKeyRepository for CachedRepo {
+    async fn create_key(&self, key: ApiKey) -> Result<ApiKey, RepoError> {
+        self.sqlite.create_key(key).await
+    }
+
+    async fn find_key_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>, RepoError> {
+        self.sqlite.find_key_by_hash(key_hash).await
+    }
+
+    async fn find_key(&self, id: Uuid) -> Result<Option<ApiKey>, RepoError> {
+        self.sqlite.find_key(id).await
+    }
+
+    async fn list_keys(&self) -> Result<Vec<ApiKey>, RepoError> {
+        self.sqlite.list_keys().await
+    }
+
+    async fn revoke_key(&self, id: Uuid) -> Result<bool, RepoError> {
+        self.sqlite.revoke_key(id).await
+    }
+}
+
+#[async_trait]
+impl DiscountRepository for CachedRepo {
+    async fn create_discount(&self, discount: Discount) -> Result<bool, RepoError> {
+        self.sqlite.create_discount(discount).await
+    }
+
+    async fn get_discount(
+        &self,
+        tenant: &TenantId,
+        code: &str,
+    ) -> Result<Option<Discount>, RepoError> {
+        self.sqlite.get_discount(tenant, code).await
+    }
+
+    async fn list_discounts(&self, tenant: &TenantId) -> Result<Vec<Discount>, RepoError> {
+        self.sqlite.list_discounts(tenant).await
+    }
+
+    async fn redeem_discount(&self, tenant: &TenantId, code: &str) -> Result<bool, RepoError> {
+        self.sqlite.redeem_discount(tenant, code).await
+    }
+
+    async fn release_discount(&self, tenant: &TenantId, code: &str) -> Result<(), RepoError> {
+        self.sqlite.release_discount(tenant, code).await
+    }
+
+    async fn delete_discount(&self, tenant: &TenantId, code: &str) -> Result<bool, RepoError> {
+        self.sqlite.delete_discount(tenant, code).await
+    }
+}
+
+#[async_trait]
+impl AuditRepository for CachedRepo {
+    async fn record_audit(&self, entry: AuditEntry) -> Result<(), RepoError> {
+        self.sqlite.record_audit(entry).await
+    }
+
+    async fn order_audit(
+        &self,
+        tenant: &TenantId,
+        order_id: Uuid,
+    ) -> Result<Vec<AuditEntry>, RepoError> {
+        self.sqlite.order_audit(tenant, order_id).await
+    }
+
+    async fn list_audit(
+        &self,
+        tenant: &TenantId,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<AuditEntry>, RepoError> {
+        self.sqlite.list_audit(tenant, limit, offset).await
+    }
+
+    async fn anonymize_audit(&self, tenant: &TenantId, order_id: Uuid) -> Result<u64, RepoError> {
+        self.sqlite.anonymize_audit(tenant, order_id).await
+    }
+}
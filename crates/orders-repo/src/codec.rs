@@ -0,0 +1,53 @@
+//! Decoding of legacy `items_json` payloads.
+//!
+//! Order lines used to be stored as JSON on the order row, zstd-compressed
+//! when the `compression` feature was on. They now live in `order_items`;
+//! this is only needed to unpack compressed payloads the SQL migration
+//! can't read. Plain payloads pass through unchanged.
+
+use orders_types::ports::order_repository::RepoError;
+
+/// Frame header every zstd payload starts with.
+pub const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+pub fn is_compressed(stored: &[u8]) -> bool {
+    stored.starts_with(&ZSTD_MAGIC)
+}
+
+pub fn decode(stored: &[u8]) -> Result<std::borrow::Cow<'_, [u8]>, RepoError> {
+    if !is_compressed(stored) {
+        return Ok(std::borrow::Cow::Borrowed(stored));
+    }
+    #[cfg(feature = "compression")]
+    {
+        zstd::decode_all(stored)
+            .map(std::borrow::Cow::Owned)
+            .map_err(RepoError::serialization)
+    }
+    #[cfg(not(feature = "compression"))]
+    {
+        Err(RepoError::serialization(
+            "compressed payload found; enable the `compression` feature",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_passes_through() {
+        assert!(!is_compressed(b"[1,2]"));
+        assert_eq!(&*decode(b"[1,2]").unwrap(), b"[1,2]");
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn zstd_payloads_decode() {
+        let big = format!("[{}]", vec!["{\"name\":\"Widget\"}"; 50].join(","));
+        let stored = zstd::encode_all(big.as_bytes(), zstd::DEFAULT_COMPRESSION_LEVEL).unwrap();
+        assert!(is_compressed(&stored));
+        assert_eq!(&*decode(&stored).unwrap(), big.as_bytes());
+    }
+}
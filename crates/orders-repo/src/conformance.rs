@@ -0,0 +1,880 @@
+//! Behaviour every [`OrderRepository`] adapter must share, as one reusable
+//! suite. An adapter proves itself from its own tests:
+//!
+//! ```ignore
+//! #[tokio::test]
+//! async fn conforms() {
+//!     orders_repo::conformance::run_conformance_suite(|| async { MyRepo::new() }).await;
+//! }
+//! ```
+//!
+//! Failures panic, like any other assertion in a test.
+
+use std::future::Future;
+
+use chrono::{Datelike, NaiveDate};
+use orders_types::domain::actor::Actor;
+use orders_types::domain::address::Address;
+use orders_types::domain::filter::{OrderFilter, SortField, SortOrder};
+use orders_types::domain::fulfillment::{FulfilledItem, Fulfillment};
+use orders_types::domain::history::OrderHistoryEntry;
+use orders_types::domain::money::{Currency, Money};
+use orders_types::domain::order::{Order, OrderItem, OrderStatus};
+use orders_types::domain::order_number::OrderNumber;
+use orders_types::domain::stats::{OrderStats, StatsRange};
+use orders_types::domain::tenant::TenantId;
+use orders_types::ports::order_repository::{OrderRepository, RepoError};
+use orders_types::ports::pricing::{ItemPriceRules, PricingPolicy, ShippingRule};
+use orders_types::ports::unit_of_work::ChangeKind;
+use uuid::Uuid;
+
+/// Run every case, each against a fresh, empty repository from `factory`.
+pub async fn run_conformance_suite<R, F, Fut>(mut factory: F)
+where
+    R: OrderRepository,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = R>,
+{
+    crud_flow(&factory().await).await;
+    missing_rows(&factory().await).await;
+    charges_and_currency_round_trip(&factory().await).await;
+    item_catalog_fields_round_trip(&factory().await).await;
+    addresses_round_trip_and_filter_by_country(&factory().await).await;
+    metadata_round_trips_and_filters(&factory().await).await;
+    actors_round_trip(&factory().await).await;
+    queries_are_scoped_to_the_tenant(&factory().await).await;
+    order_numbers_are_assigned_in_sequence(&factory().await).await;
+    exists_and_count(&factory().await).await;
+    update_items_refuses_stale_or_non_pending_orders(&factory().await).await;
+    cancellation_round_trips(&factory().await).await;
+    payment_round_trips(&factory().await).await;
+    status_history_goes_with_the_order(&factory().await).await;
+    committed_units_of_work_are_visible(&factory().await).await;
+    conditional_updates_refuse_stale_reads(&factory().await).await;
+    rolled_back_units_of_work_leave_nothing_behind(&factory().await).await;
+    fulfillments_go_with_the_order(&factory().await).await;
+    list_filtered_sorts_and_pages(&factory().await).await;
+    list_filtered_by_creation_range(&factory().await).await;
+    stale_pending_sweeps_every_tenant(&factory().await).await;
+    scan_pages_through_every_tenant(&factory().await).await;
+    aggregate_matches_in_memory_stats(&factory().await).await;
+}
+
+fn order(name: &str, email: &str, unit_price: Money) -> Order {
+    Order::new(
+        name.into(),
+        email.into(),
+        vec![OrderItem {
+            name: "Widget".into(),
+            qty: 1,
+            unit_price,
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
+        }],
+    )
+    .expect("valid order")
+}
+
+async fn crud_flow(repo: &impl OrderRepository) {
+    let tenant = TenantId::default();
+    let mut order = order("Test", "test@example.com", Money::usd(500));
+    order.items[0].qty = 2;
+
+    let created = repo.create(order.clone()).await.unwrap();
+    assert_eq!(created.id, order.id);
+
+    let fetched = repo.get(&tenant, order.id).await.unwrap().unwrap();
+    assert_eq!(fetched.customer_name, "Test");
+    assert_eq!(fetched.items[0].qty, 2);
+
+    assert_eq!(repo.list(&tenant).await.unwrap().len(), 1);
+
+    let updated = repo
+        .update_status(&tenant, order.id, OrderStatus::Shipped)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(updated.status, OrderStatus::Shipped);
+
+    assert!(repo.delete(&tenant, order.id).await.unwrap());
+    assert!(repo.get(&tenant, order.id).await.unwrap().is_none());
+}
+
+async fn missing_rows(repo: &impl OrderRepository) {
+    let tenant = TenantId::default();
+    let missing_id = Uuid::new_v4();
+
+    assert!(repo.get(&tenant, missing_id).await.unwrap().is_none());
+    assert!(repo
+        .update_status(&tenant, missing_id, OrderStatus::Shipped)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(!repo.delete(&tenant, missing_id).await.unwrap());
+}
+
+async fn charges_and_currency_round_trip(repo: &impl OrderRepository) {
+    let policy = PricingPolicy {
+        tax_rate_bps: 1_000,
+        shipping: ShippingRule::Flat { cents: 500 },
+    };
+    let order = Order::new_priced(
+        "Yuki".into(),
+        "yuki@example.com".into(),
+        vec![OrderItem {
+            name: "Tea".into(),
+            qty: 3,
+            unit_price: Money::new(400, Currency::JPY),
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
+        }],
+        &policy,
+    )
+    .unwrap();
+    repo.create(order.clone()).await.unwrap();
+
+    let fetched = repo
+        .get(&TenantId::default(), order.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(fetched.total, Money::new(1820, Currency::JPY));
+    assert_eq!(fetched.charges, order.charges);
+    assert_eq!(fetched.items[0].unit_price.currency(), Currency::JPY);
+}
+
+async fn item_catalog_fields_round_trip(repo: &impl OrderRepository) {
+    let order = Order::new(
+        "Ann".into(),
+        "ann@example.com".into(),
+        vec![OrderItem {
+            name: "Widget".into(),
+            qty: 2,
+            unit_price: Money::usd(500),
+            weight_grams: 0,
+            sku: Some("WID-1".into()),
+            description: Some("Large widget".into()),
+            metadata: [("color".to_string(), "red".to_string())].into(),
+            discount_cents: 150,
+        }],
+    )
+    .unwrap();
+    assert_eq!(order.total, Money::usd(850));
+    repo.create(order.clone()).await.unwrap();
+
+    let fetched = repo
+        .get(&TenantId::default(), order.id)
+        .await
+        .unwrap()
+        .unwrap();
+    let item = &fetched.items[0];
+    assert_eq!(item.sku.as_deref(), Some("WID-1"));
+    assert_eq!(item.description.as_deref(), Some("Large widget"));
+    assert_eq!(item.metadata, order.items[0].metadata);
+    assert_eq!(item.discount_cents, 150);
+    assert_eq!(fetched.charges.discount_cents, 150);
+}
+
+async fn addresses_round_trip_and_filter_by_country(repo: &impl OrderRepository) {
+    let tenant = TenantId::default();
+    let address = |country: &str| Address {
+        line1: "1 Main St".into(),
+        line2: Some("Suite 2".into()),
+        city: "Springfield".into(),
+        region: None,
+        postal_code: "62701".into(),
+        country: country.into(),
+    };
+    let german = order("Ann", "ann@example.com", Money::usd(100))
+        .with_addresses(Some(address("DE")), Some(address("FR")));
+    let french =
+        order("Bob", "bob@example.com", Money::usd(200)).with_addresses(Some(address("FR")), None);
+    let nowhere = order("Cy", "cy@example.com", Money::usd(300));
+    for o in [&german, &french, &nowhere] {
+        repo.create(o.clone()).await.unwrap();
+    }
+
+    let fetched = repo.get(&tenant, german.id).await.unwrap().unwrap();
+    assert_eq!(fetched.shipping_address, german.shipping_address);
+    assert_eq!(fetched.billing_address, german.billing_address);
+    assert_eq!(
+        repo.get(&tenant, nowhere.id)
+            .await
+            .unwrap()
+            .unwrap()
+            .shipping_address,
+        None
+    );
+
+    // Billing country doesn't count, and the match ignores case.
+    let filter = OrderFilter::default().with_country("fr");
+    let found = repo.list_filtered(&tenant, &filter).await.unwrap();
+    assert_eq!(found.iter().map(|o| o.id).collect::<Vec<_>>(), [french.id]);
+    assert_eq!(repo.count(&tenant, &filter).await.unwrap(), 1);
+
+    let mut moved = fetched;
+    moved.shipping_address = Some(address("FR"));
+    repo.update(moved).await.unwrap();
+    assert_eq!(repo.count(&tenant, &filter).await.unwrap(), 2);
+}
+
+async fn metadata_round_trips_and_filters(repo: &impl OrderRepository) {
+    let tenant = TenantId::default();
+    let tagged = |pairs: &[(&str, &str)]| {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    };
+    let spring = order("Ann", "ann@example.com", Money::usd(100))
+        .with_metadata(tagged(&[("erp_id", "E-1"), ("campaign", "spring")]));
+    let autumn = order("Bob", "bob@example.com", Money::usd(200))
+        .with_metadata(tagged(&[("erp_id", "E-2"), ("campaign", "autumn")]));
+    let untagged = order("Cy", "cy@example.com", Money::usd(300));
+    for o in [&spring, &autumn, &untagged] {
+        repo.create(o.clone()).await.unwrap();
+    }
+
+    let fetched = repo.get(&tenant, spring.id).await.unwrap().unwrap();
+    assert_eq!(fetched.metadata, spring.metadata);
+    let fetched_untagged = repo.get(&tenant, untagged.id).await.unwrap().unwrap();
+    assert!(fetched_untagged.metadata.is_empty());
+
+    let filter = OrderFilter::default().with_metadata("campaign", "spring");
+    let found = repo.list_filtered(&tenant, &filter).await.unwrap();
+    assert_eq!(found.iter().map(|o| o.id).collect::<Vec<_>>(), [spring.id]);
+    let both = filter.clone().with_metadata("erp_id", "E-2");
+    assert_eq!(repo.count(&tenant, &both).await.unwrap(), 0);
+
+    let mut retagged = fetched;
+    retagged.metadata = tagged(&[("campaign", "spring"), ("erp_id", "E-9")]);
+    let mut autumn = repo.get(&tenant, autumn.id).await.unwrap().unwrap();
+    autumn.metadata.insert("campaign".into(), "spring".into());
+    repo.update(retagged).await.unwrap();
+    repo.update(autumn).await.unwrap();
+    assert_eq!(repo.count(&tenant, &filter).await.unwrap(), 2);
+    let erp = OrderFilter::default().with_metadata("erp_id", "E-9");
+    let found = repo.list_filtered(&tenant, &erp).await.unwrap();
+    assert_eq!(found.iter().map(|o| o.id).collect::<Vec<_>>(), [spring.id]);
+}
+
+async fn actors_round_trip(repo: &impl OrderRepository) {
+    let tenant = TenantId::default();
+    let key = Actor::ApiKey(Uuid::new_v4());
+    let unattributed = order("Ann", "ann@example.com", Money::usd(100));
+    let created = order("Bob", "bob@example.com", Money::usd(200)).with_creator(key.clone());
+    repo.create(unattributed.clone()).await.unwrap();
+    repo.create(created.clone()).await.unwrap();
+
+    let fetched = repo.get(&tenant, unattributed.id).await.unwrap().unwrap();
+    assert_eq!((fetched.created_by, fetched.updated_by), (None, None));
+    let mut fetched = repo.get(&tenant, created.id).await.unwrap().unwrap();
+    assert_eq!(fetched.created_by, Some(key.clone()));
+    assert_eq!(fetched.updated_by, Some(key.clone()));
+
+    let user = Actor::User("u-42".into());
+    fetched.updated_by = Some(user.clone());
+    repo.update(fetched).await.unwrap();
+    let fetched = repo.get(&tenant, created.id).await.unwrap().unwrap();
+    assert_eq!(fetched.created_by, Some(key));
+    assert_eq!(fetched.updated_by, Some(user));
+}
+
+async fn queries_are_scoped_to_the_tenant(repo: &impl OrderRepository) {
+    let acme = TenantId::parse("acme").unwrap();
+    let globex = TenantId::parse("globex").unwrap();
+    let order = order("Ann", "ann@acme.test", Money::usd(100)).with_tenant(acme.clone());
+    repo.create(order.clone()).await.unwrap();
+
+    assert!(repo.get(&globex, order.id).await.unwrap().is_none());
+    assert!(repo.list(&globex).await.unwrap().is_empty());
+    assert!(repo
+        .update_status(&globex, order.id, OrderStatus::Cancelled)
+        .await
+        .unwrap()
+        .is_none());
+    let mut hijacked = order.clone().with_tenant(globex.clone());
+    hijacked.customer_name = "Mallory".into();
+    assert!(repo.update(hijacked).await.unwrap().is_none());
+    assert!(!repo.delete(&globex, order.id).await.unwrap());
+
+    let stored = repo.get(&acme, order.id).await.unwrap().unwrap();
+    assert_eq!(stored.tenant_id, acme);
+    assert_eq!(stored.customer_name, "Ann");
+    assert_eq!(stored.status, OrderStatus::Pending);
+}
+
+async fn order_numbers_are_assigned_in_sequence(repo: &impl OrderRepository) {
+    let acme = TenantId::parse("acme").unwrap();
+    let globex = TenantId::parse("globex").unwrap();
+    let first = repo
+        .create(order("Ann", "ann@acme.test", Money::usd(100)).with_tenant(acme.clone()))
+        .await
+        .unwrap();
+    let second = repo
+        .create(order("Bo", "bo@globex.test", Money::usd(100)).with_tenant(globex.clone()))
+        .await
+        .unwrap();
+    let (a, b) = (first.order_number.unwrap(), second.order_number.unwrap());
+    assert_eq!(a.year(), first.created_at.year());
+    assert_eq!(b.seq(), a.seq() + 1, "one sequence across tenants");
+
+    let found = repo.get_by_number(&acme, &a).await.unwrap().unwrap();
+    assert_eq!(found.id, first.id);
+    assert_eq!(found.order_number, Some(a));
+    assert!(repo.get_by_number(&globex, &a).await.unwrap().is_none());
+    let wrong_year = OrderNumber::new(a.year() - 1, a.seq());
+    assert!(repo
+        .get_by_number(&acme, &wrong_year)
+        .await
+        .unwrap()
+        .is_none());
+
+    // Updates carry no number and keep the one assigned.
+    let mut renamed = first.clone();
+    renamed.order_number = None;
+    renamed.customer_name = "Anna".into();
+    repo.update(renamed).await.unwrap().unwrap();
+    let stored = repo.get(&acme, first.id).await.unwrap().unwrap();
+    assert_eq!(stored.order_number, Some(a));
+
+    // A restored order keeps its number and later ones follow it.
+    let mut restored = order("Cy", "cy@acme.test", Money::usd(100)).with_tenant(acme.clone());
+    let kept = OrderNumber::new(restored.created_at.year(), b.seq() + 10);
+    restored.order_number = Some(kept);
+    assert_eq!(
+        repo.create(restored).await.unwrap().order_number,
+        Some(kept)
+    );
+    let next = repo
+        .create(order("Di", "di@acme.test", Money::usd(100)).with_tenant(acme.clone()))
+        .await
+        .unwrap();
+    assert_eq!(next.order_number.unwrap().seq(), kept.seq() + 1);
+}
+
+async fn exists_and_count(repo: &impl OrderRepository) {
+    let acme = TenantId::parse("acme").unwrap();
+    for email in ["ann@acme.test", "ANN@acme.test", "bo@acme.test"] {
+        let order = order("Ann", email, Money::usd(100)).with_tenant(acme.clone());
+        repo.create(order).await.unwrap();
+    }
+    let first = repo.list(&acme).await.unwrap()[0].clone();
+    repo.update_status(&acme, first.id, OrderStatus::Shipped)
+        .await
+        .unwrap();
+
+    assert!(repo.exists(&acme, first.id).await.unwrap());
+    assert!(!repo.exists(&TenantId::default(), first.id).await.unwrap());
+    assert!(!repo.exists(&acme, Uuid::new_v4()).await.unwrap());
+
+    let all = OrderFilter::default().with_offset(2);
+    assert_eq!(repo.count(&acme, &all).await.unwrap(), 3, "paging ignored");
+    let ann = OrderFilter::default().with_email("ann@ACME.test");
+    assert_eq!(
+        repo.count(&acme, &ann).await.unwrap(),
+        2,
+        "email is case-insensitive"
+    );
+    let shipped = OrderFilter::default().with_status(OrderStatus::Shipped);
+    assert_eq!(repo.count(&acme, &shipped).await.unwrap(), 1);
+    assert_eq!(repo.count(&TenantId::default(), &all).await.unwrap(), 0);
+}
+
+async fn update_items_refuses_stale_or_non_pending_orders(repo: &impl OrderRepository) {
+    let tenant = TenantId::default();
+    let order = order("Ed", "ed@example.com", Money::usd(100));
+    repo.create(order.clone()).await.unwrap();
+
+    let mut edited = order.clone();
+    let mut items = order.items.clone();
+    items[0].qty = 3;
+    edited.replace_items(items, &ItemPriceRules).unwrap();
+    repo.update_items(&edited, order.updated_at)
+        .await
+        .unwrap()
+        .unwrap();
+    let stored = repo.get(&tenant, order.id).await.unwrap().unwrap();
+    assert_eq!(stored.items[0].qty, 3);
+    assert_eq!(stored.total.amount_minor(), 300);
+
+    // Read before the edit above: the row has moved on.
+    let err = repo
+        .update_items(&edited, order.updated_at)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, RepoError::Conflict(_)), "stale read: {err}");
+
+    repo.update_status(&tenant, order.id, OrderStatus::Confirmed)
+        .await
+        .unwrap();
+    let stored = repo.get(&tenant, order.id).await.unwrap().unwrap();
+    let err = repo
+        .update_items(&edited, stored.updated_at)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, RepoError::Conflict(_)), "not pending: {err}");
+
+    let mut missing = edited.clone();
+    missing.id = Uuid::new_v4();
+    assert!(repo
+        .update_items(&missing, missing.updated_at)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+async fn cancellation_round_trips(repo: &impl OrderRepository) {
+    let tenant = TenantId::default();
+    let mut order = order("Kit", "kit@example.com", Money::usd(100));
+    repo.create(order.clone()).await.unwrap();
+    let stored = repo.get(&tenant, order.id).await.unwrap().unwrap();
+    assert!(stored.cancellation.is_none());
+
+    order.cancel("ordered twice").unwrap();
+    repo.update(order.clone()).await.unwrap();
+    let stored = repo.get(&tenant, order.id).await.unwrap().unwrap();
+    assert_eq!(stored.status, OrderStatus::Cancelled);
+    assert_eq!(stored.cancellation, order.cancellation);
+}
+
+async fn payment_round_trips(repo: &impl OrderRepository) {
+    let tenant = TenantId::default();
+    let mut order = order("Lu", "lu@example.com", Money::usd(100));
+    repo.create(order.clone()).await.unwrap();
+    let stored = repo.get(&tenant, order.id).await.unwrap().unwrap();
+    assert!(stored.captured.is_none());
+
+    order.payment_id = Some("pay_1".into());
+    order.captured = Some(Money::usd(100));
+    repo.update(order.clone()).await.unwrap();
+    let stored = repo.get(&tenant, order.id).await.unwrap().unwrap();
+    assert_eq!(stored.payment_id.as_deref(), Some("pay_1"));
+    assert_eq!(stored.captured, Some(Money::usd(100)));
+}
+
+async fn status_history_goes_with_the_order(repo: &impl OrderRepository) {
+    let tenant = TenantId::default();
+    let order = order("Mo", "mo@example.com", Money::usd(100));
+    repo.create(order.clone()).await.unwrap();
+    let entries = [
+        OrderHistoryEntry::new(None, OrderStatus::Pending, order.created_at, "system", None),
+        OrderHistoryEntry::new(
+            Some(OrderStatus::Pending),
+            OrderStatus::Cancelled,
+            order.created_at,
+            "key:a",
+            Some("ordered twice".into()),
+        ),
+    ];
+    for entry in entries.clone() {
+        repo.record_transition(&tenant, order.id, entry)
+            .await
+            .unwrap();
+    }
+    assert_eq!(
+        repo.status_history(&tenant, order.id).await.unwrap(),
+        entries
+    );
+    assert!(repo
+        .status_history(&TenantId::parse("other").unwrap(), order.id)
+        .await
+        .unwrap()
+        .is_empty());
+
+    assert!(repo.delete(&tenant, order.id).await.unwrap());
+    assert!(repo
+        .status_history(&tenant, order.id)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+async fn committed_units_of_work_are_visible(repo: &impl OrderRepository) {
+    let tenant = TenantId::default();
+    let order = order("Uma", "uma@example.com", Money::usd(100));
+    let entry =
+        OrderHistoryEntry::new(None, OrderStatus::Pending, order.created_at, "system", None);
+    let mut unit = repo.begin().await.unwrap();
+    unit.create(order.clone()).await.unwrap();
+    unit.record_transition(&tenant, order.id, entry.clone())
+        .await
+        .unwrap();
+    let shipped = unit
+        .update_status(&tenant, order.id, OrderStatus::Shipped)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(unit
+        .update_status(&tenant, Uuid::new_v4(), OrderStatus::Shipped)
+        .await
+        .unwrap()
+        .is_none());
+    unit.commit().await.unwrap();
+
+    let stored = repo.get(&tenant, order.id).await.unwrap().unwrap();
+    assert_eq!(stored.status, OrderStatus::Shipped);
+    assert_eq!(stored.updated_at, shipped.updated_at);
+    assert_eq!(
+        repo.status_history(&tenant, order.id).await.unwrap(),
+        [entry]
+    );
+}
+
+async fn conditional_updates_refuse_stale_reads(repo: &impl OrderRepository) {
+    let tenant = TenantId::default();
+    let read = repo
+        .create(order("Vic", "vic@example.com", Money::usd(100)))
+        .await
+        .unwrap();
+    let mut confirmed = read.clone();
+    confirmed.update_status(OrderStatus::Confirmed);
+    let mut cancelled = read.clone();
+    cancelled.update_status(OrderStatus::Cancelled);
+
+    let mut unit = repo.begin().await.unwrap();
+    let stored = unit
+        .update_from(confirmed, &read, ChangeKind::Updated)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.order_number, read.order_number);
+    unit.commit().await.unwrap();
+    // Computed from the same read, so it would overwrite the confirmation.
+    let mut unit = repo.begin().await.unwrap();
+    assert!(matches!(
+        unit.update_from(cancelled.clone(), &read, ChangeKind::Updated)
+            .await,
+        Err(RepoError::Conflict(_))
+    ));
+    drop(unit);
+    let stored = repo.get(&tenant, read.id).await.unwrap().unwrap();
+    assert_eq!(stored.status, OrderStatus::Confirmed);
+
+    let mut missing = cancelled;
+    missing.id = Uuid::new_v4();
+    let mut unit = repo.begin().await.unwrap();
+    assert!(unit
+        .update_from(missing.clone(), &missing, ChangeKind::Updated)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+async fn rolled_back_units_of_work_leave_nothing_behind(repo: &impl OrderRepository) {
+    let tenant = TenantId::default();
+    let read = repo
+        .create(order("Wes", "wes@example.com", Money::usd(100)))
+        .await
+        .unwrap();
+    let fresh = order("Xan", "xan@example.com", Money::usd(100));
+    let mut shipped = read.clone();
+    shipped.update_status(OrderStatus::Shipped);
+    let entry = OrderHistoryEntry::new(
+        Some(OrderStatus::Pending),
+        OrderStatus::Shipped,
+        shipped.updated_at,
+        "system",
+        None,
+    );
+    let shipment = Fulfillment::new(
+        vec![FulfilledItem {
+            position: 0,
+            qty: 1,
+        }],
+        "UPS",
+        "1Z1",
+        read.created_at,
+    );
+
+    for commit in [false, true] {
+        let mut unit = repo.begin().await.unwrap();
+        unit.create(fresh.clone()).await.unwrap();
+        unit.record_fulfillment(&tenant, read.id, shipment.clone())
+            .await
+            .unwrap();
+        unit.update_from(shipped.clone(), &read, ChangeKind::Updated)
+            .await
+            .unwrap()
+            .unwrap();
+        unit.record_transition(&tenant, read.id, entry.clone())
+            .await
+            .unwrap();
+        if commit {
+            unit.commit().await.unwrap();
+        } else {
+            unit.rollback().await.unwrap();
+            let stored = repo.get(&tenant, read.id).await.unwrap().unwrap();
+            assert_eq!(stored.status, OrderStatus::Pending);
+            assert!(repo.get(&tenant, fresh.id).await.unwrap().is_none());
+            assert!(repo
+                .fulfillments(&tenant, read.id)
+                .await
+                .unwrap()
+                .is_empty());
+            assert!(repo
+                .status_history(&tenant, read.id)
+                .await
+                .unwrap()
+                .is_empty());
+        }
+    }
+    let stored = repo.get(&tenant, read.id).await.unwrap().unwrap();
+    assert_eq!(stored.status, OrderStatus::Shipped);
+    assert!(repo.get(&tenant, fresh.id).await.unwrap().is_some());
+    assert_eq!(repo.fulfillments(&tenant, read.id).await.unwrap().len(), 1);
+    assert_eq!(
+        repo.status_history(&tenant, read.id).await.unwrap(),
+        [entry]
+    );
+}
+
+async fn fulfillments_go_with_the_order(repo: &impl OrderRepository) {
+    let tenant = TenantId::default();
+    let mut order = order("Flo", "flo@example.com", Money::usd(100));
+    order.items[0].qty = 3;
+    repo.create(order.clone()).await.unwrap();
+    let shipments = [
+        Fulfillment::new(
+            vec![FulfilledItem {
+                position: 0,
+                qty: 1,
+            }],
+            "UPS",
+            "1Z1",
+            order.created_at,
+        ),
+        Fulfillment::new(
+            vec![FulfilledItem {
+                position: 0,
+                qty: 2,
+            }],
+            "DHL",
+            "JD2",
+            order.created_at,
+        ),
+    ];
+    for shipment in shipments.clone() {
+        repo.record_fulfillment(&tenant, order.id, shipment)
+            .await
+            .unwrap();
+    }
+    assert_eq!(
+        repo.fulfillments(&tenant, order.id).await.unwrap(),
+        shipments
+    );
+    assert!(repo
+        .fulfillments(&TenantId::parse("other").unwrap(), order.id)
+        .await
+        .unwrap()
+        .is_empty());
+
+    assert!(repo.delete(&tenant, order.id).await.unwrap());
+    assert!(repo
+        .fulfillments(&tenant, order.id)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+async fn list_filtered_sorts_and_pages(repo: &impl OrderRepository) {
+    let tenant = TenantId::default();
+    for (cents, status) in [
+        (300, OrderStatus::Shipped),
+        (100, OrderStatus::Pending),
+        (200, OrderStatus::Confirmed),
+    ] {
+        let mut order = order("Sid", "sid@example.com", Money::usd(cents));
+        order.status = status;
+        repo.create(order).await.unwrap();
+    }
+    let totals = |orders: Vec<Order>| {
+        orders
+            .iter()
+            .map(|o| o.total.amount_minor())
+            .collect::<Vec<_>>()
+    };
+
+    let filter = OrderFilter::default()
+        .with_sort(SortField::TotalCents, SortOrder::Desc)
+        .with_offset(1);
+    assert_eq!(
+        totals(repo.list_filtered(&tenant, &filter).await.unwrap()),
+        [200, 100]
+    );
+    let filter = OrderFilter::default()
+        .with_sort(SortField::Status, SortOrder::Asc)
+        .with_limit(2);
+    assert_eq!(
+        totals(repo.list_filtered(&tenant, &filter).await.unwrap()),
+        [200, 100],
+        "Confirmed, Pending"
+    );
+    let filter = OrderFilter::default()
+        .with_status(OrderStatus::Shipped)
+        .with_sort(SortField::CreatedAt, SortOrder::Asc);
+    assert_eq!(
+        totals(repo.list_filtered(&tenant, &filter).await.unwrap()),
+        [300]
+    );
+}
+
+async fn list_filtered_by_creation_range(repo: &impl OrderRepository) {
+    let tenant = TenantId::default();
+    let mut ids = Vec::new();
+    for (hour, millis) in [(9, 0), (10, 0), (10, 500), (11, 0)] {
+        let mut order = order("Ada", "ada@example.com", Money::usd(100));
+        order.created_at = NaiveDate::from_ymd_opt(2024, 3, 1)
+            .unwrap()
+            .and_hms_milli_opt(hour, 0, 0, millis)
+            .unwrap()
+            .and_utc();
+        ids.push(order.id);
+        repo.create(order).await.unwrap();
+    }
+    let at = |hour| {
+        NaiveDate::from_ymd_opt(2024, 3, 1)
+            .unwrap()
+            .and_hms_opt(hour, 0, 0)
+            .unwrap()
+            .and_utc()
+    };
+
+    // After is inclusive and before exclusive, so ranges tile.
+    let filter = OrderFilter::default()
+        .with_created_after(at(10))
+        .with_created_before(at(11))
+        .with_sort(SortField::CreatedAt, SortOrder::Asc);
+    let found = repo.list_filtered(&tenant, &filter).await.unwrap();
+    assert_eq!(found.iter().map(|o| o.id).collect::<Vec<_>>(), ids[1..3]);
+    assert_eq!(repo.count(&tenant, &filter).await.unwrap(), 2);
+
+    let before = OrderFilter::default().with_created_before(at(10));
+    assert_eq!(repo.count(&tenant, &before).await.unwrap(), 1);
+    let after = OrderFilter::default().with_created_after(at(12));
+    assert!(repo
+        .list_filtered(&tenant, &after)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+async fn stale_pending_sweeps_every_tenant(repo: &impl OrderRepository) {
+    let day = |d| {
+        NaiveDate::from_ymd_opt(2024, 3, d)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_utc()
+    };
+    let other = TenantId::parse("other").unwrap();
+    let mut ids = Vec::new();
+    for (d, tenant, status) in [
+        (3, TenantId::default(), OrderStatus::Pending),
+        (1, other.clone(), OrderStatus::Pending),
+        (2, TenantId::default(), OrderStatus::Confirmed),
+        (9, TenantId::default(), OrderStatus::Pending),
+    ] {
+        let mut order = order("Ada", "ada@example.com", Money::usd(100));
+        order.created_at = day(d);
+        order.tenant_id = tenant;
+        order.status = status;
+        ids.push(order.id);
+        repo.create(order).await.unwrap();
+    }
+
+    let stale = repo.stale_pending(day(5), 10).await.unwrap();
+    assert_eq!(
+        stale.iter().map(|o| o.id).collect::<Vec<_>>(),
+        [ids[1], ids[0]]
+    );
+    assert_eq!(stale[0].tenant_id, other);
+    let first = repo.stale_pending(day(5), 1).await.unwrap();
+    assert_eq!(first[0].id, ids[1]);
+    assert!(repo.stale_pending(day(1), 10).await.unwrap().is_empty());
+}
+
+async fn scan_pages_through_every_tenant(repo: &impl OrderRepository) {
+    let other = TenantId::parse("other").unwrap();
+    let mut ids = Vec::new();
+    for i in 0..5 {
+        let mut order = order("Ada", "ada@example.com", Money::usd(100));
+        if i % 2 == 0 {
+            order.tenant_id = other.clone();
+        }
+        ids.push(order.id);
+        repo.create(order).await.unwrap();
+    }
+    ids.sort();
+
+    let mut seen = Vec::new();
+    let mut after = None;
+    loop {
+        let page = repo.scan(after, 2).await.unwrap();
+        assert!(page.len() <= 2);
+        let Some(last) = page.last() else { break };
+        after = Some(last.id);
+        seen.extend(page.iter().map(|o| o.id));
+    }
+    assert_eq!(seen, ids);
+    let tail = repo.scan(Some(ids[2]), 10).await.unwrap();
+    assert_eq!(tail.iter().map(|o| o.id).collect::<Vec<_>>(), ids[3..]);
+}
+
+async fn aggregate_matches_in_memory_stats(repo: &impl OrderRepository) {
+    let mut orders = Vec::new();
+    for (cents, currency, status, day) in [
+        (100, Currency::USD, OrderStatus::Pending, 1),
+        (300, Currency::USD, OrderStatus::Shipped, 2),
+        (500, Currency::USD, OrderStatus::Cancelled, 2),
+        (1000, Currency::EUR, OrderStatus::Pending, 2),
+        (999, Currency::USD, OrderStatus::Pending, 9),
+    ] {
+        let mut order = order("Ada", "ada@example.com", Money::new(cents, currency));
+        order.status = status;
+        // Late in the day, so a store bucketing by local time would slip.
+        order.created_at = NaiveDate::from_ymd_opt(2024, 1, day)
+            .unwrap()
+            .and_hms_opt(23, 59, 0)
+            .unwrap()
+            .and_utc();
+        repo.create(order.clone()).await.unwrap();
+        orders.push(order);
+    }
+    let range = StatsRange {
+        from: NaiveDate::from_ymd_opt(2024, 1, 1),
+        to: NaiveDate::from_ymd_opt(2024, 1, 2),
+        ..StatsRange::default()
+    };
+    let tenant = TenantId::default();
+    let stats = repo.aggregate(&tenant, &range).await.unwrap();
+    assert_eq!(stats, OrderStats::compute(&orders, range));
+    assert_eq!(stats.total_orders, 4);
+    assert_eq!(stats.per_day.len(), 2);
+
+    let all = repo
+        .aggregate(&tenant, &StatsRange::default())
+        .await
+        .unwrap();
+    assert_eq!(all.total_orders, 5);
+    // Instants narrow the days further: only the orders of the 2nd.
+    let narrowed = StatsRange {
+        created_after: Some(orders[1].created_at),
+        created_before: Some(orders[4].created_at),
+        ..range
+    };
+    let stats = repo.aggregate(&tenant, &narrowed).await.unwrap();
+    assert_eq!(stats, OrderStats::compute(&orders, narrowed));
+    assert_eq!(stats.total_orders, 3);
+    let other = repo
+        .aggregate(&TenantId::parse("other").unwrap(), &range)
+        .await
+        .unwrap();
+    assert_eq!(other.total_orders, 0);
+}
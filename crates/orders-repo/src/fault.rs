@@ -0,0 +1,559 @@
+//! Injected repository failures, for testing how callers cope with them.
+//!
+//! [`FaultInjectingRepo`] wraps any adapter and, per a [`FaultPlan`], delays
+//! calls and fails a share of them with a chosen [`RepoError`] before they
+//! reach the adapter, so a failed call never takes effect. Put it under a
+//! [`RetryingRepo`](crate::retry::RetryingRepo) to exercise retries, or
+//! behind the service to see what clients get back. Never use it in
+//! production.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use orders_types::domain::api_key::ApiKey;
+use orders_types::domain::audit::AuditEntry;
+use orders_types::domain::discount::Discount;
+use orders_types::domain::filter::OrderFilter;
+use orders_types::domain::fulfillment::Fulfillment;
+use orders_types::domain::history::OrderHistoryEntry;
+use orders_types::domain::integrity::{IntegrityReport, StatusMapping};
+use orders_types::domain::order::{Order, OrderStatus};
+use orders_types::domain::order_number::OrderNumber;
+use orders_types::domain::stats::{OrderStats, StatsRange};
+use orders_types::domain::tenant::TenantId;
+use orders_types::ports::api_key_repository::ApiKeyRepository;
+use orders_types::ports::audit_repository::AuditRepository;
+use orders_types::ports::discount_repository::DiscountRepository;
+use orders_types::ports::order_repository::{OrderRepository, RepoError};
+use orders_types::ports::unit_of_work::UnitOfWork;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Operation names a [`FaultPlan`] accepts: the repository port methods.
+pub const OPERATIONS: &[&str] = &[
+    "begin",
+    "create",
+    "create_many",
+    "get",
+    "get_by_number",
+    "list",
+    "list_filtered",
+    "exists",
+    "count",
+    "aggregate",
+    "stale_pending",
+    "scan",
+    "update_status",
+    "update",
+    "update_items",
+    "delete",
+    "check_integrity",
+    "record_transition",
+    "status_history",
+    "record_fulfillment",
+    "fulfillments",
+    "ping",
+    "create_key",
+    "find_key_by_hash",
+    "find_key",
+    "list_keys",
+    "revoke_key",
+    "create_discount",
+    "get_discount",
+    "list_discounts",
+    "redeem_discount",
+    "release_discount",
+    "delete_discount",
+    "record_audit",
+    "order_audit",
+    "list_audit",
+    "anonymize_audit",
+];
+
+/// The error an injected failure is reported as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaultKind {
+    Transient,
+    Backend,
+    Serialization,
+    Conflict,
+    NotFound,
+}
+
+impl FaultKind {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "transient" => Ok(Self::Transient),
+            "backend" => Ok(Self::Backend),
+            "serialization" => Ok(Self::Serialization),
+            "conflict" => Ok(Self::Conflict),
+            "not_found" | "notfound" => Ok(Self::NotFound),
+            other => Err(format!(
+                "unknown fault `{other}` (expected transient, backend, serialization, conflict or not_found)"
+            )),
+        }
+    }
+
+    fn error(self, op: &str) -> RepoError {
+        let message = format!("injected fault in {op}");
+        match self {
+            Self::Transient => RepoError::transient(message),
+            Self::Backend => RepoError::backend(message),
+            Self::Serialization => RepoError::serialization(message),
+            Self::Conflict => RepoError::Conflict(message),
+            Self::NotFound => RepoError::NotFound(message),
+        }
+    }
+}
+
+/// What happens to the calls of one operation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fault {
+    /// Share of calls that fail, from `0.0` (none) to `1.0` (all).
+    pub failure_rate: f64,
+    pub kind: FaultKind,
+    /// Wait before every call, failing or not.
+    pub latency: Duration,
+}
+
+impl Fault {
+    /// Fail this share of calls with a transient error.
+    pub fn failing(failure_rate: f64) -> Self {
+        Self {
+            failure_rate,
+            kind: FaultKind::Transient,
+            latency: Duration::ZERO,
+        }
+    }
+
+    /// Delay every call without failing any.
+    pub fn slow(latency: Duration) -> Self {
+        Self {
+            failure_rate: 0.0,
+            kind: FaultKind::Transient,
+            latency,
+        }
+    }
+
+    pub fn with_kind(mut self, kind: FaultKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+}
+
+/// Faults by operation, with one for the operations not named.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FaultPlan {
+    default: Option<Fault>,
+    ops: HashMap<&'static str, Fault>,
+}
+
+impl FaultPlan {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// For every operation without its own fault, except `ping`: a health
+    /// check only fails when asked to by name.
+    pub fn with_default(mut self, fault: Fault) -> Self {
+        self.default = Some(fault);
+        self
+    }
+
+    /// Panics on a name not in [`OPERATIONS`].
+    pub fn with_op(mut self, op: &str, fault: Fault) -> Self {
+        let op = OPERATIONS
+            .iter()
+            .find(|known| **known == op)
+            .unwrap_or_else(|| panic!("unknown repository operation `{op}`"));
+        self.ops.insert(op, fault);
+        self
+    }
+
+    /// The fault for `op`, if any.
+    pub fn fault(&self, op: &str) -> Option<&Fault> {
+        self.ops
+            .get(op)
+            .or(self.default.as_ref().filter(|_| op != "ping"))
+    }
+
+    /// A plan from comma-separated rules `op:rate[:kind[:latency_ms]]`,
+    /// where `op` is an operation name or `*` for the default, e.g.
+    /// `*:0.05,create:0.5:backend,get:0:transient:200`.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let mut plan = Self::new();
+        for rule in s.split(',').map(str::trim).filter(|r| !r.is_empty()) {
+            let mut parts = rule.split(':').map(str::trim);
+            let op = parts.next().unwrap_or_default();
+            let failure_rate: f64 = parts
+                .next()
+                .ok_or_else(|| format!("fault rule `{rule}` has no failure rate"))?
+                .parse()
+                .map_err(|_| format!("fault rule `{rule}` has an invalid failure rate"))?;
+            if !(0.0..=1.0).contains(&failure_rate) {
+                return Err(format!(
+                    "fault rule `{rule}`: the failure rate must be between 0 and 1"
+                ));
+            }
+            let mut fault = Fault::failing(failure_rate);
+            if let Some(kind) = parts.next() {
+                fault.kind = FaultKind::parse(kind)?;
+            }
+            if let Some(ms) = parts.next() {
+                let ms = ms
+                    .parse()
+                    .map_err(|_| format!("fault rule `{rule}` has an invalid latency"))?;
+                fault.latency = Duration::from_millis(ms);
+            }
+            if parts.next().is_some() {
+                return Err(format!("fault rule `{rule}` has too many parts"));
+            }
+            match op {
+                "*" => plan.default = Some(fault),
+                op if OPERATIONS.contains(&op) => plan = plan.with_op(op, fault),
+                op => return Err(format!("unknown repository operation `{op}`")),
+            }
+        }
+        Ok(plan)
+    }
+}
+
+/// Any adapter with calls delayed and failed per its [`FaultPlan`].
+///
+/// Only the calls on the repository are faulted; the operations of a
+/// [`UnitOfWork`] from [`begin`](OrderRepository::begin) go straight to the
+/// adapter.
+#[derive(Clone)]
+pub struct FaultInjectingRepo<R> {
+    inner: R,
+    plan: Arc<FaultPlan>,
+    rng: Arc<AtomicU64>,
+    injected: Arc<AtomicU64>,
+}
+
+impl<R> FaultInjectingRepo<R> {
+    pub fn new(inner: R, plan: FaultPlan) -> Self {
+        Self {
+            inner,
+            plan: Arc::new(plan),
+            rng: Arc::new(AtomicU64::new(Uuid::new_v4().as_u64_pair().0)),
+            injected: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Draw failures from `seed`, so a single-threaded run fails the same
+    /// calls every time.
+    pub fn with_seed(self, seed: u64) -> Self {
+        // xorshift gets stuck at zero.
+        self.rng.store(seed.max(1), Ordering::Relaxed);
+        self
+    }
+
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn plan(&self) -> &FaultPlan {
+        &self.plan
+    }
+
+    /// Failures injected so far, across clones.
+    pub fn injected(&self) -> u64 {
+        self.injected.load(Ordering::Relaxed)
+    }
+
+    /// A number in `[0, 1)`.
+    fn roll(&self) -> f64 {
+        let step = |mut x: u64| {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x
+        };
+        let prev = self
+            .rng
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |x| Some(step(x)))
+            .unwrap_or_else(|x| x);
+        (step(prev) >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Wait out `op`'s latency, then fail it or let it through.
+    async fn inject(&self, op: &'static str) -> Result<(), RepoError> {
+        let Some(fault) = self.plan.fault(op) else {
+            return Ok(());
+        };
+        if !fault.latency.is_zero() {
+            tokio::time::sleep(fault.latency).await;
+        }
+        if fault.failure_rate > 0.0 && self.roll() < fault.failure_rate {
+            self.injected.fetch_add(1, Ordering::Relaxed);
+            tracing::debug!(op, kind = ?fault.kind, "injecting repository fault");
+            return Err(fault.kind.error(op));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<R: OrderRepository> OrderRepository for FaultInjectingRepo<R> {
+    async fn begin(&self) -> Result<Box<dyn UnitOfWork + '_>, RepoError> {
+        self.inject("begin").await?;
+        self.inner.begin().await
+    }
+
+    async fn create(&self, order: Order) -> Result<Order, RepoError> {
+        self.inject("create").await?;
+        self.inner.create(order).await
+    }
+
+    async fn create_many(&self, orders: Vec<Order>) -> Result<(), RepoError> {
+        self.inject("create_many").await?;
+        self.inner.create_many(orders).await
+    }
+
+    async fn get(&self, tenant: &TenantId, id: Uuid) -> Result<Option<Order>, RepoError> {
+        self.inject("get").await?;
+        self.inner.get(tenant, id).await
+    }
+
+    async fn get_by_number(
+        &self,
+        tenant: &TenantId,
+        number: &OrderNumber,
+    ) -> Result<Option<Order>, RepoError> {
+        self.inject("get_by_number").await?;
+        self.inner.get_by_number(tenant, number).await
+    }
+
+    async fn list(&self, tenant: &TenantId) -> Result<Vec<Order>, RepoError> {
+        self.inject("list").await?;
+        self.inner.list(tenant).await
+    }
+
+    async fn list_filtered(
+        &self,
+        tenant: &TenantId,
+        filter: &OrderFilter,
+    ) -> Result<Vec<Order>, RepoError> {
+        self.inject("list_filtered").await?;
+        self.inner.list_filtered(tenant, filter).await
+    }
+
+    async fn exists(&self, tenant: &TenantId, id: Uuid) -> Result<bool, RepoError> {
+        self.inject("exists").await?;
+        self.inner.exists(tenant, id).await
+    }
+
+    async fn count(&self, tenant: &TenantId, filter: &OrderFilter) -> Result<usize, RepoError> {
+        self.inject("count").await?;
+        self.inner.count(tenant, filter).await
+    }
+
+    async fn aggregate(
+        &self,
+        tenant: &TenantId,
+        range: &StatsRange,
+    ) -> Result<OrderStats, RepoError> {
+        self.inject("aggregate").await?;
+        self.inner.aggregate(tenant, range).await
+    }
+
+    async fn stale_pending(
+        &self,
+        before: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<Order>, RepoError> {
+        self.inject("stale_pending").await?;
+        self.inner.stale_pending(before, limit).await
+    }
+
+    async fn scan(&self, after: Option<Uuid>, limit: usize) -> Result<Vec<Order>, RepoError> {
+        self.inject("scan").await?;
+        self.inner.scan(after, limit).await
+    }
+
+    async fn update_status(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        status: OrderStatus,
+    ) -> Result<Option<Order>, RepoError> {
+        self.inject("update_status").await?;
+        self.inner.update_status(tenant, id, status).await
+    }
+
+    async fn update(&self, order: Order) -> Result<Option<Order>, RepoError> {
+        self.inject("update").await?;
+        self.inner.update(order).await
+    }
+
+    async fn update_items(
+        &self,
+        order: &Order,
+        read_at: DateTime<Utc>,
+    ) -> Result<Option<Order>, RepoError> {
+        self.inject("update_items").await?;
+        self.inner.update_items(order, read_at).await
+    }
+
+    async fn delete(&self, tenant: &TenantId, id: Uuid) -> Result<bool, RepoError> {
+        self.inject("delete").await?;
+        self.inner.delete(tenant, id).await
+    }
+
+    async fn check_integrity(
+        &self,
+        mapping: &StatusMapping,
+        fix: bool,
+    ) -> Result<IntegrityReport, RepoError> {
+        self.inject("check_integrity").await?;
+        self.inner.check_integrity(mapping, fix).await
+    }
+
+    async fn record_transition(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        entry: OrderHistoryEntry,
+    ) -> Result<(), RepoError> {
+        self.inject("record_transition").await?;
+        self.inner.record_transition(tenant, id, entry).await
+    }
+
+    async fn status_history(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+    ) -> Result<Vec<OrderHistoryEntry>, RepoError> {
+        self.inject("status_history").await?;
+        self.inner.status_history(tenant, id).await
+    }
+
+    async fn record_fulfillment(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        fulfillment: Fulfillment,
+    ) -> Result<(), RepoError> {
+        self.inject("record_fulfillment").await?;
+        self.inner.record_fulfillment(tenant, id, fulfillment).await
+    }
+
+    async fn fulfillments(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+    ) -> Result<Vec<Fulfillment>, RepoError> {
+        self.inject("fulfillments").await?;
+        self.inner.fulfillments(tenant, id).await
+    }
+
+    async fn ping(&self) -> Result<(), RepoError> {
+        self.inject("ping").await?;
+        self.inner.ping().await
+    }
+}
+
+#[async_trait]
+impl<R: ApiKeyRepository> ApiKeyRepository for FaultInjectingRepo<R> {
+    async fn create_key(&self, key: ApiKey) -> Result<ApiKey, RepoError> {
+        self.inject("create_key").await?;
+        self.inner.create_key(key).await
+    }
+
+    async fn find_key_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>, RepoError> {
+        self.inject("find_key_by_hash").await?;
+        self.inner.find_key_by_hash(key_hash).await
+    }
+
+    async fn find_key(&self, id: Uuid) -> Result<Option<ApiKey>, RepoError> {
+        self.inject("find_key").await?;
+        self.inner.find_key(id).await
+    }
+
+    async fn list_keys(&self) -> Result<Vec<ApiKey>, RepoError> {
+        self.inject("list_keys").await?;
+        self.inner.list_keys().await
+    }
+
+    async fn revoke_key(&self, id: Uuid) -> Result<bool, RepoError> {
+        self.inject("revoke_key").await?;
+        self.inner.revoke_key(id).await
+    }
+}
+
+#[async_trait]
+impl<R: DiscountRepository> DiscountRepository for FaultInjectingRepo<R> {
+    async fn create_discount(&self, discount: Discount) -> Result<bool, RepoError> {
+        self.inject("create_discount").await?;
+        self.inner.create_discount(discount).await
+    }
+
+    async fn get_discount(
+        &self,
+        tenant: &TenantId,
+        code: &str,
+    ) -> Result<Option<Discount>, RepoError> {
+        self.inject("get_discount").await?;
+        self.inner.get_discount(tenant, code).await
+    }
+
+    async fn list_discounts(&self, tenant: &TenantId) -> Result<Vec<Discount>, RepoError> {
+        self.inject("list_discounts").await?;
+        self.inner.list_discounts(tenant).await
+    }
+
+    async fn redeem_discount(&self, tenant: &TenantId, code: &str) -> Result<bool, RepoError> {
+        self.inject("redeem_discount").await?;
+        self.inner.redeem_discount(tenant, code).await
+    }
+
+    async fn release_discount(&self, tenant: &TenantId, code: &str) -> Result<(), RepoError> {
+        self.inject("release_discount").await?;
+        self.inner.release_discount(tenant, code).await
+    }
+
+    async fn delete_discount(&self, tenant: &TenantId, code: &str) -> Result<bool, RepoError> {
+        self.inject("delete_discount").await?;
+        self.inner.delete_discount(tenant, code).await
+    }
+}
+
+#[async_trait]
+impl<R: AuditRepository> AuditRepository for FaultInjectingRepo<R> {
+    async fn record_audit(&self, entry: AuditEntry) -> Result<(), RepoError> {
+        self.inject("record_audit").await?;
+        self.inner.record_audit(entry).await
+    }
+
+    async fn order_audit(
+        &self,
+        tenant: &TenantId,
+        order_id: Uuid,
+    ) -> Result<Vec<AuditEntry>, RepoError> {
+        self.inject("order_audit").await?;
+        self.inner.order_audit(tenant, order_id).await
+    }
+
+    async fn list_audit(
+        &self,
+        tenant: &TenantId,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<AuditEntry>, RepoError> {
+        self.inject("list_audit").await?;
+        self.inner.list_audit(tenant, limit, offset).await
+    }
+
+    async fn anonymize_audit(&self, tenant: &TenantId, order_id: Uuid) -> Result<u64, RepoError> {
+        self.inject("anonymize_audit").await?;
+        self.inner.anonymize_audit(tenant, order_id).await
+    }
+}
@@ -0,0 +1,671 @@
+//! Latency histograms and slow-call logging for repository calls.
+//!
+//! [`InstrumentedRepo`] wraps any adapter and times every call, including
+//! the writes and commit of a [`UnitOfWork`], into one histogram per
+//! operation. A call that takes at least the slow threshold is logged at
+//! `warn` with its operation and, when it has one, the order id, and counted
+//! in `orders_repo_slow_operations_total`.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use orders_types::domain::api_key::ApiKey;
+use orders_types::domain::audit::AuditEntry;
+use orders_types::domain::discount::Discount;
+use orders_types::domain::filter::OrderFilter;
+use orders_types::domain::fulfillment::Fulfillment;
+use orders_types::domain::history::OrderHistoryEntry;
+use orders_types::domain::integrity::{IntegrityReport, StatusMapping};
+use orders_types::domain::order::{Order, OrderStatus};
+use orders_types::domain::order_number::OrderNumber;
+use orders_types::domain::stats::{OrderStats, StatsRange};
+use orders_types::domain::tenant::TenantId;
+use orders_types::ports::api_key_repository::ApiKeyRepository;
+use orders_types::ports::audit_repository::AuditRepository;
+use orders_types::ports::discount_repository::DiscountRepository;
+use orders_types::ports::metrics::MetricsSource;
+use orders_types::ports::order_repository::{OrderRepository, RepoError};
+use orders_types::ports::unit_of_work::{ChangeKind, UnitOfWork};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Upper bounds, in seconds, of the latency histogram buckets.
+const LATENCY_BUCKETS: [f64; 12] = [
+    0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0,
+];
+
+#[derive(Debug, Default)]
+struct Histogram {
+    /// Calls per bucket of [`LATENCY_BUCKETS`], the last one above them all;
+    /// not cumulative.
+    counts: [u64; LATENCY_BUCKETS.len() + 1],
+    sum: f64,
+    slow: u64,
+}
+
+/// Per-operation latency histograms and slow-call counts behind the
+/// `orders_repo_operation_duration_seconds` and
+/// `orders_repo_slow_operations_total` series. Cheap to clone; clones share
+/// counts.
+#[derive(Clone, Default)]
+pub struct LatencyMetrics {
+    ops: Arc<Mutex<BTreeMap<&'static str, Histogram>>>,
+}
+
+impl LatencyMetrics {
+    fn observe(&self, op: &'static str, elapsed: Duration, slow: bool) {
+        let secs = elapsed.as_secs_f64();
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|&le| secs <= le)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        let mut ops = self.ops.lock().expect("latency metrics poisoned");
+        let histogram = ops.entry(op).or_default();
+        histogram.counts[bucket] += 1;
+        histogram.sum += secs;
+        histogram.slow += u64::from(slow);
+    }
+
+    /// Calls timed for `op` so far.
+    pub fn count(&self, op: &str) -> u64 {
+        let ops = self.ops.lock().expect("latency metrics poisoned");
+        ops.get(op).map_or(0, |h| h.counts.iter().sum())
+    }
+
+    /// Calls to `op` that reached the slow threshold so far.
+    pub fn slow(&self, op: &str) -> u64 {
+        let ops = self.ops.lock().expect("latency metrics poisoned");
+        ops.get(op).map_or(0, |h| h.slow)
+    }
+
+    /// OpenMetrics names a counter family without the `_total` of its
+    /// sample; the Prometheus text format names it after the sample.
+    fn render(&self, openmetrics: bool) -> String {
+        let ops = self.ops.lock().expect("latency metrics poisoned");
+        let mut out = String::new();
+        let name = "orders_repo_operation_duration_seconds";
+        let _ = writeln!(
+            out,
+            "# HELP {name} Time spent in repository calls, by operation.\n\
+             # TYPE {name} histogram"
+        );
+        for (op, histogram) in ops.iter() {
+            let mut cumulative = 0;
+            let bounds = LATENCY_BUCKETS
+                .iter()
+                .map(|le| le.to_string())
+                .chain(std::iter::once("+Inf".to_string()));
+            for (i, le) in bounds.enumerate() {
+                cumulative += histogram.counts[i];
+                let _ = writeln!(out, "{name}_bucket{{op=\"{op}\",le=\"{le}\"}} {cumulative}");
+            }
+            let _ = writeln!(out, "{name}_sum{{op=\"{op}\"}} {}", histogram.sum);
+            let _ = writeln!(out, "{name}_count{{op=\"{op}\"}} {cumulative}");
+        }
+        let name = "orders_repo_slow_operations_total";
+        let family = if openmetrics {
+            name.trim_end_matches("_total")
+        } else {
+            name
+        };
+        let _ = writeln!(
+            out,
+            "# HELP {family} Repository calls that reached the slow threshold, by operation.\n\
+             # TYPE {family} counter"
+        );
+        for (op, histogram) in ops.iter() {
+            let _ = writeln!(out, "{name}{{op=\"{op}\"}} {}", histogram.slow);
+        }
+        out
+    }
+}
+
+impl MetricsSource for LatencyMetrics {
+    fn prometheus(&self) -> String {
+        self.render(false)
+    }
+
+    fn openmetrics(&self) -> String {
+        self.render(true)
+    }
+}
+
+/// Where timings go and when a call counts as slow; shared by a repo and
+/// the units of work it begins.
+#[derive(Clone)]
+struct Timer {
+    metrics: LatencyMetrics,
+    slow_threshold: Option<Duration>,
+}
+
+impl Timer {
+    async fn time<T, Fut>(
+        &self,
+        op: &'static str,
+        order_id: Option<Uuid>,
+        call: Fut,
+    ) -> Result<T, RepoError>
+    where
+        Fut: Future<Output = Result<T, RepoError>>,
+    {
+        let started = Instant::now();
+        let result = call.await;
+        let elapsed = started.elapsed();
+        let slow = self.slow_threshold.is_some_and(|t| elapsed >= t);
+        if slow {
+            tracing::warn!(
+                op,
+                order_id = order_id.map(tracing::field::display),
+                elapsed_ms = elapsed.as_millis() as u64,
+                failed = result.is_err(),
+                "slow repository call"
+            );
+        }
+        self.metrics.observe(op, elapsed, slow);
+        result
+    }
+}
+
+/// Any adapter with every call timed into [`LatencyMetrics`], and calls
+/// slower than the [threshold](Self::with_slow_threshold) logged.
+#[derive(Clone)]
+pub struct InstrumentedRepo<R> {
+    inner: R,
+    timer: Timer,
+}
+
+impl<R> InstrumentedRepo<R> {
+    /// Times calls without logging any as slow.
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            timer: Timer {
+                metrics: LatencyMetrics::default(),
+                slow_threshold: None,
+            },
+        }
+    }
+
+    /// Log calls that take at least `threshold`.
+    pub fn with_slow_threshold(mut self, threshold: Duration) -> Self {
+        self.timer.slow_threshold = Some(threshold);
+        self
+    }
+
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    /// Latency histograms, shared with this repo.
+    pub fn metrics(&self) -> LatencyMetrics {
+        self.timer.metrics.clone()
+    }
+
+    pub fn slow_threshold(&self) -> Option<Duration> {
+        self.timer.slow_threshold
+    }
+}
+
+#[async_trait]
+impl<R: OrderRepository> OrderRepository for InstrumentedRepo<R> {
+    async fn begin(&self) -> Result<Box<dyn UnitOfWork + '_>, RepoError> {
+        let inner = self.timer.time("begin", None, self.inner.begin()).await?;
+        Ok(Box::new(InstrumentedUnit {
+            inner,
+            timer: &self.timer,
+        }))
+    }
+
+    async fn create(&self, order: Order) -> Result<Order, RepoError> {
+        let id = order.id;
+        self.timer
+            .time("create", Some(id), self.inner.create(order))
+            .await
+    }
+
+    async fn create_many(&self, orders: Vec<Order>) -> Result<(), RepoError> {
+        self.timer
+            .time("create_many", None, self.inner.create_many(orders))
+            .await
+    }
+
+    async fn get(&self, tenant: &TenantId, id: Uuid) -> Result<Option<Order>, RepoError> {
+        self.timer
+            .time("get", Some(id), self.inner.get(tenant, id))
+            .await
+    }
+
+    async fn get_by_number(
+        &self,
+        tenant: &TenantId,
+        number: &OrderNumber,
+    ) -> Result<Option<Order>, RepoError> {
+        self.timer
+            .time(
+                "get_by_number",
+                None,
+                self.inner.get_by_number(tenant, number),
+            )
+            .await
+    }
+
+    async fn list(&self, tenant: &TenantId) -> Result<Vec<Order>, RepoError> {
+        self.timer.time("list", None, self.inner.list(tenant)).await
+    }
+
+    async fn list_filtered(
+        &self,
+        tenant: &TenantId,
+        filter: &OrderFilter,
+    ) -> Result<Vec<Order>, RepoError> {
+        self.timer
+            .time(
+                "list_filtered",
+                None,
+                self.inner.list_filtered(tenant, filter),
+            )
+            .await
+    }
+
+    async fn exists(&self, tenant: &TenantId, id: Uuid) -> Result<bool, RepoError> {
+        self.timer
+            .time("exists", Some(id), self.inner.exists(tenant, id))
+            .await
+    }
+
+    async fn count(&self, tenant: &TenantId, filter: &OrderFilter) -> Result<usize, RepoError> {
+        self.timer
+            .time("count", None, self.inner.count(tenant, filter))
+            .await
+    }
+
+    async fn aggregate(
+        &self,
+        tenant: &TenantId,
+        range: &StatsRange,
+    ) -> Result<OrderStats, RepoError> {
+        self.timer
+            .time("aggregate", None, self.inner.aggregate(tenant, range))
+            .await
+    }
+
+    async fn stale_pending(
+        &self,
+        before: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<Order>, RepoError> {
+        self.timer
+            .time(
+                "stale_pending",
+                None,
+                self.inner.stale_pending(before, limit),
+            )
+            .await
+    }
+
+    async fn scan(&self, after: Option<Uuid>, limit: usize) -> Result<Vec<Order>, RepoError> {
+        self.timer
+            .time("scan", None, self.inner.scan(after, limit))
+            .await
+    }
+
+    async fn update_status(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        status: OrderStatus,
+    ) -> Result<Option<Order>, RepoError> {
+        self.timer
+            .time(
+                "update_status",
+                Some(id),
+                self.inner.update_status(tenant, id, status),
+            )
+            .await
+    }
+
+    async fn update(&self, order: Order) -> Result<Option<Order>, RepoError> {
+        let id = order.id;
+        self.timer
+            .time("update", Some(id), self.inner.update(order))
+            .await
+    }
+
+    async fn update_items(
+        &self,
+        order: &Order,
+        read_at: DateTime<Utc>,
+    ) -> Result<Option<Order>, RepoError> {
+        self.timer
+            .time(
+                "update_items",
+                Some(order.id),
+                self.inner.update_items(order, read_at),
+            )
+            .await
+    }
+
+    async fn delete(&self, tenant: &TenantId, id: Uuid) -> Result<bool, RepoError> {
+        self.timer
+            .time("delete", Some(id), self.inner.delete(tenant, id))
+            .await
+    }
+
+    async fn check_integrity(
+        &self,
+        mapping: &StatusMapping,
+        fix: bool,
+    ) -> Result<IntegrityReport, RepoError> {
+        self.timer
+            .time(
+                "check_integrity",
+                None,
+                self.inner.check_integrity(mapping, fix),
+            )
+            .await
+    }
+
+    async fn record_transition(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        entry: OrderHistoryEntry,
+    ) -> Result<(), RepoError> {
+        self.timer
+            .time(
+                "record_transition",
+                Some(id),
+                self.inner.record_transition(tenant, id, entry),
+            )
+            .await
+    }
+
+    async fn status_history(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+    ) -> Result<Vec<OrderHistoryEntry>, RepoError> {
+        self.timer
+            .time(
+                "status_history",
+                Some(id),
+                self.inner.status_history(tenant, id),
+            )
+            .await
+    }
+
+    async fn record_fulfillment(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        fulfillment: Fulfillment,
+    ) -> Result<(), RepoError> {
+        self.timer
+            .time(
+                "record_fulfillment",
+                Some(id),
+                self.inner.record_fulfillment(tenant, id, fulfillment),
+            )
+            .await
+    }
+
+    async fn fulfillments(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+    ) -> Result<Vec<Fulfillment>, RepoError> {
+        self.timer
+            .time(
+                "fulfillments",
+                Some(id),
+                self.inner.fulfillments(tenant, id),
+            )
+            .await
+    }
+
+    async fn ping(&self) -> Result<(), RepoError> {
+        self.timer.time("ping", None, self.inner.ping()).await
+    }
+}
+
+/// A unit of work whose writes and commit are timed like the repo's calls.
+struct InstrumentedUnit<'a> {
+    inner: Box<dyn UnitOfWork + 'a>,
+    timer: &'a Timer,
+}
+
+#[async_trait]
+impl UnitOfWork for InstrumentedUnit<'_> {
+    async fn create(&mut self, order: Order) -> Result<Order, RepoError> {
+        let id = order.id;
+        self.timer
+            .time("create", Some(id), self.inner.create(order))
+            .await
+    }
+
+    async fn update(&mut self, order: Order) -> Result<Option<Order>, RepoError> {
+        let id = order.id;
+        self.timer
+            .time("update", Some(id), self.inner.update(order))
+            .await
+    }
+
+    async fn update_from(
+        &mut self,
+        order: Order,
+        read: &Order,
+        kind: ChangeKind,
+    ) -> Result<Option<Order>, RepoError> {
+        let id = order.id;
+        self.timer
+            .time(
+                "update_from",
+                Some(id),
+                self.inner.update_from(order, read, kind),
+            )
+            .await
+    }
+
+    async fn update_status(
+        &mut self,
+        tenant: &TenantId,
+        id: Uuid,
+        status: OrderStatus,
+    ) -> Result<Option<Order>, RepoError> {
+        self.timer
+            .time(
+                "update_status",
+                Some(id),
+                self.inner.update_status(tenant, id, status),
+            )
+            .await
+    }
+
+    async fn record_transition(
+        &mut self,
+        tenant: &TenantId,
+        id: Uuid,
+        entry: OrderHistoryEntry,
+    ) -> Result<(), RepoError> {
+        self.timer
+            .time(
+                "record_transition",
+                Some(id),
+                self.inner.record_transition(tenant, id, entry),
+            )
+            .await
+    }
+
+    async fn record_fulfillment(
+        &mut self,
+        tenant: &TenantId,
+        id: Uuid,
+        fulfillment: Fulfillment,
+    ) -> Result<(), RepoError> {
+        self.timer
+            .time(
+                "record_fulfillment",
+                Some(id),
+                self.inner.record_fulfillment(tenant, id, fulfillment),
+            )
+            .await
+    }
+
+    async fn commit(self: Box<Self>) -> Result<(), RepoError> {
+        self.timer.time("commit", None, self.inner.commit()).await
+    }
+
+    async fn rollback(self: Box<Self>) -> Result<(), RepoError> {
+        self.timer
+            .time("rollback", None, self.inner.rollback())
+            .await
+    }
+}
+
+#[async_trait]
+impl<R: ApiKeyRepository> ApiKeyRepository for InstrumentedRepo<R> {
+    async fn create_key(&self, key: ApiKey) -> Result<ApiKey, RepoError> {
+        self.timer
+            .time("create_key", None, self.inner.create_key(key))
+            .await
+    }
+
+    async fn find_key_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>, RepoError> {
+        self.timer
+            .time(
+                "find_key_by_hash",
+                None,
+                self.inner.find_key_by_hash(key_hash),
+            )
+            .await
+    }
+
+    async fn find_key(&self, id: Uuid) -> Result<Option<ApiKey>, RepoError> {
+        self.timer
+            .time("find_key", None, self.inner.find_key(id))
+            .await
+    }
+
+    async fn list_keys(&self) -> Result<Vec<ApiKey>, RepoError> {
+        self.timer
+            .time("list_keys", None, self.inner.list_keys())
+            .await
+    }
+
+    async fn revoke_key(&self, id: Uuid) -> Result<bool, RepoError> {
+        self.timer
+            .time("revoke_key", None, self.inner.revoke_key(id))
+            .await
+    }
+}
+
+#[async_trait]
+impl<R: DiscountRepository> DiscountRepository for InstrumentedRepo<R> {
+    async fn create_discount(&self, discount: Discount) -> Result<bool, RepoError> {
+        self.timer
+            .time(
+                "create_discount",
+                None,
+                self.inner.create_discount(discount),
+            )
+            .await
+    }
+
+    async fn get_discount(
+        &self,
+        tenant: &TenantId,
+        code: &str,
+    ) -> Result<Option<Discount>, RepoError> {
+        self.timer
+            .time("get_discount", None, self.inner.get_discount(tenant, code))
+            .await
+    }
+
+    async fn list_discounts(&self, tenant: &TenantId) -> Result<Vec<Discount>, RepoError> {
+        self.timer
+            .time("list_discounts", None, self.inner.list_discounts(tenant))
+            .await
+    }
+
+    async fn redeem_discount(&self, tenant: &TenantId, code: &str) -> Result<bool, RepoError> {
+        self.timer
+            .time(
+                "redeem_discount",
+                None,
+                self.inner.redeem_discount(tenant, code),
+            )
+            .await
+    }
+
+    async fn release_discount(&self, tenant: &TenantId, code: &str) -> Result<(), RepoError> {
+        self.timer
+            .time(
+                "release_discount",
+                None,
+                self.inner.release_discount(tenant, code),
+            )
+            .await
+    }
+
+    async fn delete_discount(&self, tenant: &TenantId, code: &str) -> Result<bool, RepoError> {
+        self.timer
+            .time(
+                "delete_discount",
+                None,
+                self.inner.delete_discount(tenant, code),
+            )
+            .await
+    }
+}
+
+#[async_trait]
+impl<R: AuditRepository> AuditRepository for InstrumentedRepo<R> {
+    async fn record_audit(&self, entry: AuditEntry) -> Result<(), RepoError> {
+        let id = entry.order_id;
+        self.timer
+            .time("record_audit", Some(id), self.inner.record_audit(entry))
+            .await
+    }
+
+    async fn order_audit(
+        &self,
+        tenant: &TenantId,
+        order_id: Uuid,
+    ) -> Result<Vec<AuditEntry>, RepoError> {
+        self.timer
+            .time(
+                "order_audit",
+                Some(order_id),
+                self.inner.order_audit(tenant, order_id),
+            )
+            .await
+    }
+
+    async fn list_audit(
+        &self,
+        tenant: &TenantId,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<AuditEntry>, RepoError> {
+        self.timer
+            .time(
+                "list_audit",
+                None,
+                self.inner.list_audit(tenant, limit, offset),
+            )
+            .await
+    }
+
+    async fn anonymize_audit(&self, tenant: &TenantId, order_id: Uuid) -> Result<u64, RepoError> {
+        self.timer
+            .time(
+                "anonymize_audit",
+                Some(order_id),
+                self.inner.anonymize_audit(tenant, order_id),
+            )
+            .await
+    }
+}
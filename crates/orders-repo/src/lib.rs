@@ -1,142 +1,600 @@
 #[cfg(not(any(feature = "memory", feature = "sqlite")))]
 compile_error!("Enable a repo feature: `memory` or `sqlite`.");
 
+use chrono::{DateTime, Utc};
+use orders_types::domain::api_key::ApiKey;
+use orders_types::domain::audit::AuditEntry;
+use orders_types::domain::discount::Discount;
+use orders_types::domain::filter::OrderFilter;
+use orders_types::domain::fulfillment::Fulfillment;
+use orders_types::domain::history::OrderHistoryEntry;
+use orders_types::domain::integrity::{IntegrityReport, StatusMapping};
 use orders_types::domain::order::*;
+use orders_types::domain::order_number::OrderNumber;
+use orders_types::domain::stats::{OrderStats, StatsRange};
+use orders_types::domain::tenant::TenantId;
+use orders_types::ports::api_key_repository::ApiKeyRepository;
+use orders_types::ports::audit_repository::AuditRepository;
+use orders_types::ports::discount_repository::DiscountRepository;
+use orders_types::ports::field_encryption::KeyProvider;
+use orders_types::ports::migrations::{MigrationSource, MigrationStatus, PendingMigration};
 use orders_types::ports::order_repository::OrderRepository;
 use orders_types::ports::order_repository::RepoError;
+use orders_types::ports::unit_of_work::UnitOfWork;
+use std::sync::Arc;
 use uuid::Uuid;
 
+#[cfg(all(feature = "memory", feature = "sqlite"))]
+pub mod cached;
+pub mod codec;
+pub mod conformance;
+pub mod fault;
+pub mod instrument;
 #[cfg(feature = "memory")]
 pub mod memory;
 #[cfg(feature = "sqlite")]
+pub mod pii;
+pub mod pool;
+pub mod retry;
+#[cfg(feature = "sqlite")]
 pub mod sqlite;
 
-pub struct Repo {
+/// Which adapter backs a [`Repo`], chosen at runtime (e.g. `REPO_BACKEND`).
+/// Only backends whose Cargo feature is enabled can actually be built.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepoBackend {
+    Memory,
+    Sqlite,
+    /// Recognised so configs can name it; no adapter exists yet.
+    Postgres,
+}
+
+impl RepoBackend {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "memory" => Ok(Self::Memory),
+            "sqlite" => Ok(Self::Sqlite),
+            "postgres" | "postgresql" => Ok(Self::Postgres),
+            other => Err(format!(
+                "unknown repo backend `{other}` (expected memory, sqlite or postgres)"
+            )),
+        }
+    }
+
+    /// The backend a connection URL is for, from its scheme: `memory:`,
+    /// `sqlite:` or `postgres:`/`postgresql:`.
+    pub fn from_url(url: &str) -> Result<Self, String> {
+        let scheme = url.split_once(':').map(|(scheme, _)| scheme).unwrap_or("");
+        match scheme.to_ascii_lowercase().as_str() {
+            "memory" => Ok(Self::Memory),
+            "sqlite" => Ok(Self::Sqlite),
+            "postgres" | "postgresql" => Ok(Self::Postgres),
+            _ => Err(format!(
+                "cannot tell the backend of `{url}` (expected a memory:, sqlite: or postgres: URL)"
+            )),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Memory => "memory",
+            Self::Sqlite => "sqlite",
+            Self::Postgres => "postgres",
+        }
+    }
+}
+
+impl Default for RepoBackend {
+    /// sqlite when compiled in, otherwise memory.
+    fn default() -> Self {
+        if cfg!(feature = "sqlite") {
+            Self::Sqlite
+        } else {
+            Self::Memory
+        }
+    }
+}
+
+/// Exactly one adapter, possibly behind [retries](RepoOptions::retry),
+/// [timing](RepoOptions::slow_query_threshold) or
+/// [injected faults](RepoOptions::faults); every port call goes to it.
+#[derive(Clone)]
+pub enum Repo {
     #[cfg(feature = "memory")]
-    memory: memory::InMemoryRepo,
+    Memory(memory::InMemoryRepo),
     #[cfg(feature = "sqlite")]
-    sqlite: sqlite::SqliteRepo,
+    Sqlite(sqlite::SqliteRepo),
+    /// sqlite behind a memory cache; see [`RepoOptions::cache`].
+    #[cfg(all(feature = "memory", feature = "sqlite"))]
+    Cached(cached::CachedRepo),
+    /// Another repo with transient failures retried; see
+    /// [`RepoOptions::retry`].
+    Retrying(Box<retry::RetryingRepo<Repo>>),
+    /// Another repo with every call timed; see
+    /// [`RepoOptions::slow_query_threshold`].
+    Instrumented(Box<instrument::InstrumentedRepo<Repo>>),
+    /// Another repo with failures injected; see [`RepoOptions::faults`].
+    Faulty(Box<fault::FaultInjectingRepo<Repo>>),
+}
+
+/// A schema migration shipped with an adapter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationInfo {
+    pub version: i64,
+    pub description: String,
+}
+
+/// Adapter tuning that applies regardless of which backend is compiled in.
+#[derive(Debug, Clone, Default)]
+pub struct RepoOptions {
+    /// Backend to build; taken from the URL's scheme when unset, and the
+    /// build's [default](RepoBackend::default) without a URL either.
+    pub backend: Option<RepoBackend>,
+    /// Leave pending migrations for an explicit [`Repo::migrate`].
+    pub skip_migrations: bool,
+    /// Connection pool and locking settings (sqlite only).
+    pub pool: pool::PoolOptions,
+    /// Front sqlite with a write-through memory cache of orders; needs both
+    /// the `memory` and `sqlite` features.
+    pub cache: bool,
+    /// Orders the [cache](Self::cache) holds before dropping the least
+    /// recently used; [`cached::DEFAULT_CACHE_CAPACITY`] when unset.
+    pub cache_capacity: Option<usize>,
+    /// How long the [cache](Self::cache) serves an order before reading it
+    /// from sqlite again; kept until evicted when unset.
+    pub cache_ttl: Option<std::time::Duration>,
+    /// Record every order change in the outbox table for a relay to
+    /// forward; sqlite only. See [`sqlite::SqliteRepo::with_outbox`].
+    pub outbox: bool,
+    /// Encrypt personal data at rest under these keys; sqlite only, the
+    /// memory backend keeps nothing at rest. See
+    /// [`sqlite::SqliteRepo::with_field_encryption`].
+    pub pii_keys: Option<Arc<dyn KeyProvider>>,
+    /// Retry calls that fail with a [transient](RepoError::Transient) error
+    /// under this policy; not retried when unset.
+    pub retry: Option<retry::RetryPolicy>,
+    /// Delay and fail calls to the adapter per this plan, underneath any
+    /// retries; for resilience tests only.
+    pub faults: Option<fault::FaultPlan>,
+    /// Time every call into [`Repo::latency_metrics`] and log the ones that
+    /// take at least this long; not instrumented when unset. Sits between
+    /// retries and faults, so each attempt is timed on its own and injected
+    /// latency counts.
+    pub slow_query_threshold: Option<std::time::Duration>,
 }
 
 pub async fn build_repo(url: Option<&str>) -> anyhow::Result<Repo> {
-    Repo::build_repo(url).await
+    build_repo_with(url, RepoOptions::default()).await
+}
+
+pub async fn build_repo_with(url: Option<&str>, options: RepoOptions) -> anyhow::Result<Repo> {
+    let retry = options.retry;
+    let faults = options.faults.clone();
+    let slow_query_threshold = options.slow_query_threshold;
+    let mut repo = build_adapter(url, options).await?;
+    if let Some(plan) = faults {
+        tracing::warn!("injecting repository faults");
+        repo = Repo::Faulty(Box::new(fault::FaultInjectingRepo::new(repo, plan)));
+    }
+    if let Some(threshold) = slow_query_threshold {
+        repo = Repo::Instrumented(Box::new(
+            instrument::InstrumentedRepo::new(repo).with_slow_threshold(threshold),
+        ));
+    }
+    Ok(match retry {
+        Some(policy) => {
+            Repo::Retrying(Box::new(retry::RetryingRepo::new(repo).with_policy(policy)))
+        }
+        None => repo,
+    })
+}
+
+async fn build_adapter(url: Option<&str>, options: RepoOptions) -> anyhow::Result<Repo> {
+    let backend = match (options.backend, url) {
+        (Some(backend), _) => backend,
+        (None, Some(url)) => RepoBackend::from_url(url).map_err(|e| anyhow::anyhow!(e))?,
+        (None, None) => RepoBackend::default(),
+    };
+    #[cfg(not(feature = "sqlite"))]
+    let _ = url;
+    match backend {
+        #[cfg(feature = "memory")]
+        RepoBackend::Memory if options.outbox => {
+            anyhow::bail!("the outbox needs the sqlite backend")
+        }
+        #[cfg(feature = "memory")]
+        RepoBackend::Memory => Ok(Repo::Memory(memory::InMemoryRepo::new())),
+        #[cfg(feature = "sqlite")]
+        RepoBackend::Sqlite => {
+            let url = url.unwrap_or("sqlite://orders.db");
+            let mut sqlite = sqlite::SqliteRepo::connect_with(url, &options.pool).await?;
+            if options.outbox {
+                sqlite = sqlite.with_outbox();
+            }
+            if let Some(keys) = options.pii_keys {
+                sqlite = sqlite.with_field_encryption(keys);
+            }
+            if !options.skip_migrations {
+                sqlite.migrate().await?;
+            }
+            if options.cache {
+                #[cfg(feature = "memory")]
+                {
+                    let mut cached = cached::CachedRepo::new(sqlite);
+                    if let Some(capacity) = options.cache_capacity {
+                        cached = cached.with_capacity(capacity);
+                    }
+                    if let Some(ttl) = options.cache_ttl {
+                        cached = cached.with_ttl(ttl);
+                    }
+                    return Ok(Repo::Cached(cached));
+                }
+                #[cfg(not(feature = "memory"))]
+                anyhow::bail!("the repo cache needs the `memory` feature as well");
+            }
+            Ok(Repo::Sqlite(sqlite))
+        }
+        RepoBackend::Postgres => anyhow::bail!("repo backend `postgres` is not implemented"),
+        #[allow(unreachable_patterns)]
+        other => anyhow::bail!(
+            "repo backend `{}` is not compiled in; rebuild with `--features {}`",
+            other.as_str(),
+            other.as_str()
+        ),
+    }
 }
 
 impl Repo {
-    #[cfg(all(feature = "memory", not(feature = "sqlite")))]
-    pub async fn build_repo(_: Option<&str>) -> anyhow::Result<Self> {
-        Ok(Self {
-            memory: crate::memory::InMemoryRepo::new(),
-        })
+    pub fn backend(&self) -> RepoBackend {
+        match self {
+            #[cfg(feature = "memory")]
+            Repo::Memory(_) => RepoBackend::Memory,
+            #[cfg(feature = "sqlite")]
+            Repo::Sqlite(_) => RepoBackend::Sqlite,
+            #[cfg(all(feature = "memory", feature = "sqlite"))]
+            Repo::Cached(_) => RepoBackend::Sqlite,
+            Repo::Retrying(r) => r.inner().backend(),
+            Repo::Instrumented(r) => r.inner().backend(),
+            Repo::Faulty(r) => r.inner().backend(),
+        }
+    }
+
+    /// Cache hit and miss counters, when the repo is [cached](RepoOptions::cache).
+    #[cfg(all(feature = "memory", feature = "sqlite"))]
+    pub fn cache_metrics(&self) -> Option<cached::CacheMetrics> {
+        match self {
+            Repo::Cached(r) => Some(r.metrics()),
+            Repo::Retrying(r) => r.inner().cache_metrics(),
+            Repo::Instrumented(r) => r.inner().cache_metrics(),
+            Repo::Faulty(r) => r.inner().cache_metrics(),
+            _ => None,
+        }
     }
 
-    #[cfg(all(feature = "sqlite", not(feature = "memory")))]
-    pub async fn build_repo(database_url: Option<&str>) -> anyhow::Result<Self> {
-        let url = database_url.unwrap_or("sqlite://orders.db");
-        let sqlite = sqlite::SqliteRepo::new(url).await?;
-        Ok(Self { sqlite })
+    /// Per-operation latency histograms, when the repo is
+    /// [instrumented](RepoOptions::slow_query_threshold).
+    pub fn latency_metrics(&self) -> Option<instrument::LatencyMetrics> {
+        match self {
+            Repo::Instrumented(r) => Some(r.metrics()),
+            Repo::Retrying(r) => r.inner().latency_metrics(),
+            _ => None,
+        }
+    }
+
+    /// The sqlite adapter underneath, for the ports only it implements such
+    /// as the read model; `None` on the memory backend.
+    #[cfg(feature = "sqlite")]
+    pub fn sqlite(&self) -> Option<&sqlite::SqliteRepo> {
+        match self {
+            #[cfg(feature = "memory")]
+            Repo::Memory(_) => None,
+            Repo::Sqlite(r) => Some(r),
+            #[cfg(feature = "memory")]
+            Repo::Cached(r) => Some(r.sqlite()),
+            Repo::Retrying(r) => r.inner().sqlite(),
+            Repo::Instrumented(r) => r.inner().sqlite(),
+            Repo::Faulty(r) => r.inner().sqlite(),
+        }
     }
 
-    // If both features are enabled
-    #[cfg(all(feature = "sqlite", feature = "memory"))]
-    pub async fn build_repo(database_url: Option<&str>) -> anyhow::Result<Self> {
-        let memory = crate::memory::InMemoryRepo::new();
-        let url = database_url.unwrap_or("sqlite://orders.db");
-        let sqlite = sqlite::SqliteRepo::new(url).await?;
-        Ok(Self { memory, sqlite })
+    /// Apply pending schema migrations, returning the ones that ran. The
+    /// memory backend has no schema.
+    pub async fn migrate(&self) -> anyhow::Result<Vec<MigrationInfo>> {
+        match self {
+            #[cfg(feature = "memory")]
+            Repo::Memory(_) => Ok(Vec::new()),
+            #[cfg(feature = "sqlite")]
+            Repo::Sqlite(r) => r.migrate().await,
+            #[cfg(all(feature = "memory", feature = "sqlite"))]
+            Repo::Cached(r) => r.sqlite().migrate().await,
+            Repo::Retrying(r) => Box::pin(r.inner().migrate()).await,
+            Repo::Instrumented(r) => Box::pin(r.inner().migrate()).await,
+            Repo::Faulty(r) => Box::pin(r.inner().migrate()).await,
+        }
+    }
+
+    /// Migrations this build knows about that haven't been applied yet.
+    pub async fn pending_migrations(&self) -> anyhow::Result<Vec<MigrationInfo>> {
+        match self {
+            #[cfg(feature = "memory")]
+            Repo::Memory(_) => Ok(Vec::new()),
+            #[cfg(feature = "sqlite")]
+            Repo::Sqlite(r) => r.pending_migrations().await,
+            #[cfg(all(feature = "memory", feature = "sqlite"))]
+            Repo::Cached(r) => r.sqlite().pending_migrations().await,
+            Repo::Retrying(r) => Box::pin(r.inner().pending_migrations()).await,
+            Repo::Instrumented(r) => Box::pin(r.inner().pending_migrations()).await,
+            Repo::Faulty(r) => Box::pin(r.inner().pending_migrations()).await,
+        }
+    }
+
+    /// Highest applied migration; `None` for the memory backend.
+    pub async fn schema_version(&self) -> anyhow::Result<Option<i64>> {
+        match self {
+            #[cfg(feature = "memory")]
+            Repo::Memory(_) => Ok(None),
+            #[cfg(feature = "sqlite")]
+            Repo::Sqlite(r) => r.schema_version().await,
+            #[cfg(all(feature = "memory", feature = "sqlite"))]
+            Repo::Cached(r) => r.sqlite().schema_version().await,
+            Repo::Retrying(r) => Box::pin(r.inner().schema_version()).await,
+            Repo::Instrumented(r) => Box::pin(r.inner().schema_version()).await,
+            Repo::Faulty(r) => Box::pin(r.inner().schema_version()).await,
+        }
     }
 }
 
-#[cfg(all(feature = "memory", not(feature = "sqlite")))]
+#[async_trait::async_trait]
+impl MigrationSource for Repo {
+    async fn migration_status(&self) -> anyhow::Result<MigrationStatus> {
+        Ok(MigrationStatus {
+            current_version: self.schema_version().await?,
+            pending: self
+                .pending_migrations()
+                .await?
+                .into_iter()
+                .map(|m| PendingMigration {
+                    version: m.version,
+                    description: m.description,
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Forward a call to whichever adapter `self` holds.
+macro_rules! dispatch {
+    ($self:expr, $repo:ident => $call:expr) => {
+        match $self {
+            #[cfg(feature = "memory")]
+            Repo::Memory($repo) => $call,
+            #[cfg(feature = "sqlite")]
+            Repo::Sqlite($repo) => $call,
+            #[cfg(all(feature = "memory", feature = "sqlite"))]
+            Repo::Cached($repo) => $call,
+            Repo::Retrying($repo) => $call,
+            Repo::Instrumented($repo) => $call,
+            Repo::Faulty($repo) => $call,
+        }
+    };
+}
+
 #[async_trait::async_trait]
 impl OrderRepository for Repo {
+    async fn begin(&self) -> Result<Box<dyn UnitOfWork + '_>, RepoError> {
+        dispatch!(self, r => r.begin().await)
+    }
+
     async fn create(&self, order: Order) -> Result<Order, RepoError> {
-        self.memory.create(order).await
+        dispatch!(self, r => r.create(order).await)
     }
 
-    async fn get(&self, id: Uuid) -> Result<Option<Order>, RepoError> {
-        self.memory.get(id).await
+    async fn create_many(&self, orders: Vec<Order>) -> Result<(), RepoError> {
+        dispatch!(self, r => r.create_many(orders).await)
     }
 
-    async fn list(&self) -> Result<Vec<Order>, RepoError> {
-        self.memory.list().await
+    async fn get(&self, tenant: &TenantId, id: Uuid) -> Result<Option<Order>, RepoError> {
+        dispatch!(self, r => r.get(tenant, id).await)
     }
 
-    async fn update_status(
+    async fn get_by_number(
         &self,
-        id: Uuid,
-        status: OrderStatus,
+        tenant: &TenantId,
+        number: &OrderNumber,
     ) -> Result<Option<Order>, RepoError> {
-        self.memory.update_status(id, status).await
+        dispatch!(self, r => r.get_by_number(tenant, number).await)
     }
 
-    async fn delete(&self, id: Uuid) -> Result<bool, RepoError> {
-        self.memory.delete(id).await
+    async fn list(&self, tenant: &TenantId) -> Result<Vec<Order>, RepoError> {
+        dispatch!(self, r => r.list(tenant).await)
     }
-}
 
-#[cfg(all(feature = "sqlite", not(feature = "memory")))]
-#[async_trait::async_trait]
-impl OrderRepository for Repo {
-    async fn create(&self, order: Order) -> Result<Order, RepoError> {
-        self.sqlite.create(order).await
+    async fn list_filtered(
+        &self,
+        tenant: &TenantId,
+        filter: &OrderFilter,
+    ) -> Result<Vec<Order>, RepoError> {
+        dispatch!(self, r => r.list_filtered(tenant, filter).await)
+    }
+
+    async fn exists(&self, tenant: &TenantId, id: Uuid) -> Result<bool, RepoError> {
+        dispatch!(self, r => r.exists(tenant, id).await)
+    }
+
+    async fn count(&self, tenant: &TenantId, filter: &OrderFilter) -> Result<usize, RepoError> {
+        dispatch!(self, r => r.count(tenant, filter).await)
     }
 
-    async fn get(&self, id: Uuid) -> Result<Option<Order>, RepoError> {
-        self.sqlite.get(id).await
+    async fn aggregate(
+        &self,
+        tenant: &TenantId,
+        range: &StatsRange,
+    ) -> Result<OrderStats, RepoError> {
+        dispatch!(self, r => r.aggregate(tenant, range).await)
+    }
+
+    async fn stale_pending(
+        &self,
+        before: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<Order>, RepoError> {
+        dispatch!(self, r => r.stale_pending(before, limit).await)
     }
 
-    async fn list(&self) -> Result<Vec<Order>, RepoError> {
-        self.sqlite.list().await
+    async fn scan(&self, after: Option<Uuid>, limit: usize) -> Result<Vec<Order>, RepoError> {
+        dispatch!(self, r => r.scan(after, limit).await)
     }
 
     async fn update_status(
         &self,
+        tenant: &TenantId,
         id: Uuid,
         status: OrderStatus,
     ) -> Result<Option<Order>, RepoError> {
-        self.sqlite.update_status(id, status).await
+        dispatch!(self, r => r.update_status(tenant, id, status).await)
     }
 
-    async fn delete(&self, id: Uuid) -> Result<bool, RepoError> {
-        self.sqlite.delete(id).await
+    async fn update(&self, order: Order) -> Result<Option<Order>, RepoError> {
+        dispatch!(self, r => r.update(order).await)
+    }
+
+    async fn update_items(
+        &self,
+        order: &Order,
+        read_at: DateTime<Utc>,
+    ) -> Result<Option<Order>, RepoError> {
+        dispatch!(self, r => r.update_items(order, read_at).await)
+    }
+
+    async fn delete(&self, tenant: &TenantId, id: Uuid) -> Result<bool, RepoError> {
+        dispatch!(self, r => r.delete(tenant, id).await)
+    }
+
+    async fn check_integrity(
+        &self,
+        mapping: &StatusMapping,
+        fix: bool,
+    ) -> Result<IntegrityReport, RepoError> {
+        dispatch!(self, r => r.check_integrity(mapping, fix).await)
+    }
+
+    async fn record_transition(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        entry: OrderHistoryEntry,
+    ) -> Result<(), RepoError> {
+        dispatch!(self, r => r.record_transition(tenant, id, entry).await)
+    }
+
+    async fn status_history(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+    ) -> Result<Vec<OrderHistoryEntry>, RepoError> {
+        dispatch!(self, r => r.status_history(tenant, id).await)
+    }
+
+    async fn record_fulfillment(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        fulfillment: Fulfillment,
+    ) -> Result<(), RepoError> {
+        dispatch!(self, r => r.record_fulfillment(tenant, id, fulfillment).await)
+    }
+
+    async fn fulfillments(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+    ) -> Result<Vec<Fulfillment>, RepoError> {
+        dispatch!(self, r => r.fulfillments(tenant, id).await)
+    }
+
+    async fn ping(&self) -> Result<(), RepoError> {
+        dispatch!(self, r => r.ping().await)
     }
 }
 
-#[cfg(all(feature = "sqlite", feature = "memory"))]
 #[async_trait::async_trait]
-impl OrderRepository for Repo {
-    async fn create(&self, order: Order) -> Result<Order, RepoError> {
-        // let order  = self.memory.create(order).await?;
-        self.sqlite.create(order).await
+impl ApiKeyRepository for Repo {
+    async fn create_key(&self, key: ApiKey) -> Result<ApiKey, RepoError> {
+        dispatch!(self, r => r.create_key(key).await)
     }
 
-    async fn get(&self, id: Uuid) -> Result<Option<Order>, RepoError> {
-        // let order = self.memory.get(id).await?;
-        // if order.is_none() {
-        //     self.sqlite.get(id).await
-        // } else {
-        //     Ok(order)
-        // }
-        self.sqlite.get(id).await
+    async fn find_key_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>, RepoError> {
+        dispatch!(self, r => r.find_key_by_hash(key_hash).await)
     }
 
-    async fn list(&self) -> Result<Vec<Order>, RepoError> {
-        // self.memory.list().await
-        self.sqlite.list().await
+    async fn find_key(&self, id: Uuid) -> Result<Option<ApiKey>, RepoError> {
+        dispatch!(self, r => r.find_key(id).await)
     }
 
-    async fn update_status(
+    async fn list_keys(&self) -> Result<Vec<ApiKey>, RepoError> {
+        dispatch!(self, r => r.list_keys().await)
+    }
+
+    async fn revoke_key(&self, id: Uuid) -> Result<bool, RepoError> {
+        dispatch!(self, r => r.revoke_key(id).await)
+    }
+}
+
+#[async_trait::async_trait]
+impl DiscountRepository for Repo {
+    async fn create_discount(&self, discount: Discount) -> Result<bool, RepoError> {
+        dispatch!(self, r => r.create_discount(discount).await)
+    }
+
+    async fn get_discount(
         &self,
-        id: Uuid,
-        status: OrderStatus,
-    ) -> Result<Option<Order>, RepoError> {
-        // self.memory.update_status(id, status).await
-        self.sqlite.update_status(id, status).await
+        tenant: &TenantId,
+        code: &str,
+    ) -> Result<Option<Discount>, RepoError> {
+        dispatch!(self, r => r.get_discount(tenant, code).await)
+    }
+
+    async fn list_discounts(&self, tenant: &TenantId) -> Result<Vec<Discount>, RepoError> {
+        dispatch!(self, r => r.list_discounts(tenant).await)
+    }
+
+    async fn redeem_discount(&self, tenant: &TenantId, code: &str) -> Result<bool, RepoError> {
+        dispatch!(self, r => r.redeem_discount(tenant, code).await)
+    }
+
+    async fn release_discount(&self, tenant: &TenantId, code: &str) -> Result<(), RepoError> {
+        dispatch!(self, r => r.release_discount(tenant, code).await)
+    }
+
+    async fn delete_discount(&self, tenant: &TenantId, code: &str) -> Result<bool, RepoError> {
+        dispatch!(self, r => r.delete_discount(tenant, code).await)
+    }
+}
+
+#[async_trait::async_trait]
+impl AuditRepository for Repo {
+    async fn record_audit(&self, entry: AuditEntry) -> Result<(), RepoError> {
+        dispatch!(self, r => r.record_audit(entry).await)
+    }
+
+    async fn order_audit(
+        &self,
+        tenant: &TenantId,
+        order_id: Uuid,
+    ) -> Result<Vec<AuditEntry>, RepoError> {
+        dispatch!(self, r => r.order_audit(tenant, order_id).await)
+    }
+
+    async fn list_audit(
+        &self,
+        tenant: &TenantId,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<AuditEntry>, RepoError> {
+        dispatch!(self, r => r.list_audit(tenant, limit, offset).await)
     }
 
-    async fn delete(&self, id: Uuid) -> Result<bool, RepoError> {
-        self.memory.delete(id).await
-        // self.sqlite.delete(id).await
+    async fn anonymize_audit(&self, tenant: &TenantId, order_id: Uuid) -> Result<u64, RepoError> {
+        dispatch!(self, r => r.anonymize_audit(tenant, order_id).await)
     }
 }
@@ -1,19 +1,47 @@
 use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Utc};
 use dashmap::DashMap;
+use orders_types::domain::api_key::ApiKey;
+use orders_types::domain::audit::AuditEntry;
+use orders_types::domain::discount::Discount;
+use orders_types::domain::filter::OrderFilter;
+use orders_types::domain::fulfillment::Fulfillment;
+use orders_types::domain::history::OrderHistoryEntry;
 use orders_types::domain::order::{Order, OrderStatus};
+use orders_types::domain::order_number::OrderNumber;
+use orders_types::domain::tenant::TenantId;
+use orders_types::ports::api_key_repository::ApiKeyRepository;
+use orders_types::ports::audit_repository::AuditRepository;
+use orders_types::ports::discount_repository::DiscountRepository;
 use orders_types::ports::order_repository::{OrderRepository, RepoError};
-use std::sync::Arc;
+use orders_types::ports::unit_of_work::{ChangeKind, UnitOfWork};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use uuid::Uuid;
 
 #[derive(Clone)]
 pub struct InMemoryRepo {
     pub map: Arc<DashMap<Uuid, Order>>,
+    pub api_keys: Arc<DashMap<Uuid, ApiKey>>,
+    pub discounts: Arc<DashMap<(TenantId, String), Discount>>,
+    pub history: Arc<DashMap<(TenantId, Uuid), Vec<OrderHistoryEntry>>>,
+    pub fulfillments: Arc<DashMap<(TenantId, Uuid), Vec<Fulfillment>>>,
+    /// Audit entries in the order they were recorded.
+    pub audit: Arc<Mutex<Vec<AuditEntry>>>,
+    /// The highest order number sequence handed out or restored so far.
+    pub order_seq: Arc<AtomicU64>,
 }
 
 impl InMemoryRepo {
     pub fn new() -> Self {
         Self {
             map: Arc::new(DashMap::new()),
+            api_keys: Arc::new(DashMap::new()),
+            discounts: Arc::new(DashMap::new()),
+            history: Arc::new(DashMap::new()),
+            fulfillments: Arc::new(DashMap::new()),
+            audit: Arc::new(Mutex::new(Vec::new())),
+            order_seq: Arc::new(AtomicU64::new(0)),
         }
     }
 }
@@ -24,34 +52,500 @@ impl Default for InMemoryRepo {
     }
 }
 
+/// Writes that go straight to the maps, like the repo's own methods, and
+/// are undone, newest first, unless the unit commits. A conditional update
+/// checks and writes while holding the order's entry, so concurrent units
+/// can't both pass the check.
+struct MemoryUnit<'a> {
+    repo: &'a InMemoryRepo,
+    undo: Vec<Undo>,
+}
+
+/// How to take back one write of a [`MemoryUnit`].
+enum Undo {
+    Created(Uuid),
+    /// The order as it was before the write.
+    Updated(Box<Order>),
+    Transition(TenantId, Uuid),
+    Fulfillment(TenantId, Uuid, Uuid),
+}
+
+impl MemoryUnit<'_> {
+    /// Note the stored copy of `id` before it is overwritten.
+    fn keep(&mut self, id: Uuid) {
+        if let Some(order) = self.repo.map.get(&id) {
+            self.undo.push(Undo::Updated(Box::new(order.clone())));
+        }
+    }
+
+    fn undo(&mut self) {
+        while let Some(undo) = self.undo.pop() {
+            match undo {
+                Undo::Created(id) => {
+                    self.repo.map.remove(&id);
+                }
+                Undo::Updated(order) => {
+                    self.repo.map.insert(order.id, *order);
+                }
+                Undo::Transition(tenant, id) => {
+                    if let Some(mut history) = self.repo.history.get_mut(&(tenant, id)) {
+                        history.pop();
+                    }
+                }
+                Undo::Fulfillment(tenant, id, fulfillment) => {
+                    if let Some(mut shipped) = self.repo.fulfillments.get_mut(&(tenant, id)) {
+                        shipped.retain(|f| f.id != fulfillment);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Drop for MemoryUnit<'_> {
+    fn drop(&mut self) {
+        self.undo();
+    }
+}
+
+#[async_trait]
+impl UnitOfWork for MemoryUnit<'_> {
+    async fn create(&mut self, order: Order) -> Result<Order, RepoError> {
+        let order = self.repo.create(order).await?;
+        self.undo.push(Undo::Created(order.id));
+        Ok(order)
+    }
+
+    async fn update(&mut self, order: Order) -> Result<Option<Order>, RepoError> {
+        self.keep(order.id);
+        self.repo.update(order).await
+    }
+
+    async fn update_from(
+        &mut self,
+        order: Order,
+        read: &Order,
+        _kind: ChangeKind,
+    ) -> Result<Option<Order>, RepoError> {
+        let Some(mut stored) = self.repo.map.get_mut(&order.id) else {
+            return Ok(None);
+        };
+        if stored.tenant_id != order.tenant_id {
+            return Ok(None);
+        }
+        if !stored.same_version(read) {
+            return Err(RepoError::Conflict(format!(
+                "order {} was modified",
+                order.id
+            )));
+        }
+        let order = Order {
+            order_number: stored.order_number,
+            created_by: stored.created_by.clone(),
+            ..order
+        };
+        let before = std::mem::replace(&mut *stored, order.clone());
+        self.undo.push(Undo::Updated(Box::new(before)));
+        Ok(Some(order))
+    }
+
+    async fn update_status(
+        &mut self,
+        tenant: &TenantId,
+        id: Uuid,
+        status: OrderStatus,
+    ) -> Result<Option<Order>, RepoError> {
+        self.keep(id);
+        self.repo.update_status(tenant, id, status).await
+    }
+
+    async fn record_transition(
+        &mut self,
+        tenant: &TenantId,
+        id: Uuid,
+        entry: OrderHistoryEntry,
+    ) -> Result<(), RepoError> {
+        self.repo.record_transition(tenant, id, entry).await?;
+        self.undo.push(Undo::Transition(tenant.clone(), id));
+        Ok(())
+    }
+
+    async fn record_fulfillment(
+        &mut self,
+        tenant: &TenantId,
+        id: Uuid,
+        fulfillment: Fulfillment,
+    ) -> Result<(), RepoError> {
+        let fulfillment_id = fulfillment.id;
+        self.repo
+            .record_fulfillment(tenant, id, fulfillment)
+            .await?;
+        self.undo
+            .push(Undo::Fulfillment(tenant.clone(), id, fulfillment_id));
+        Ok(())
+    }
+
+    async fn commit(mut self: Box<Self>) -> Result<(), RepoError> {
+        self.undo.clear();
+        Ok(())
+    }
+
+    async fn rollback(self: Box<Self>) -> Result<(), RepoError> {
+        // Dropping undoes the writes.
+        Ok(())
+    }
+}
+
 #[async_trait]
 impl OrderRepository for InMemoryRepo {
-    async fn create(&self, order: Order) -> Result<Order, RepoError> {
+    async fn begin(&self) -> Result<Box<dyn UnitOfWork + '_>, RepoError> {
+        Ok(Box::new(MemoryUnit {
+            repo: self,
+            undo: Vec::new(),
+        }))
+    }
+
+    async fn create(&self, mut order: Order) -> Result<Order, RepoError> {
+        match order.order_number {
+            Some(number) => {
+                self.order_seq.fetch_max(number.seq(), Ordering::SeqCst);
+            }
+            None => {
+                let seq = self.order_seq.fetch_add(1, Ordering::SeqCst) + 1;
+                order.order_number = Some(OrderNumber::new(order.created_at.year(), seq));
+            }
+        }
         self.map.insert(order.id, order.clone());
         Ok(order)
     }
 
-    async fn get(&self, id: Uuid) -> Result<Option<Order>, RepoError> {
-        Ok(self.map.get(&id).map(|r| r.clone()))
+    async fn get(&self, tenant: &TenantId, id: Uuid) -> Result<Option<Order>, RepoError> {
+        Ok(self
+            .map
+            .get(&id)
+            .filter(|r| &r.tenant_id == tenant)
+            .map(|r| r.clone()))
+    }
+
+    async fn list(&self, tenant: &TenantId) -> Result<Vec<Order>, RepoError> {
+        Ok(self
+            .map
+            .iter()
+            .filter(|kv| &kv.value().tenant_id == tenant)
+            .map(|kv| kv.value().clone())
+            .collect())
     }
 
-    async fn list(&self) -> Result<Vec<Order>, RepoError> {
-        Ok(self.map.iter().map(|kv| kv.value().clone()).collect())
+    async fn exists(&self, tenant: &TenantId, id: Uuid) -> Result<bool, RepoError> {
+        Ok(self.map.get(&id).is_some_and(|r| &r.tenant_id == tenant))
+    }
+
+    async fn count(&self, tenant: &TenantId, filter: &OrderFilter) -> Result<usize, RepoError> {
+        Ok(self
+            .map
+            .iter()
+            .filter(|kv| &kv.value().tenant_id == tenant && filter.matches(kv.value()))
+            .count())
+    }
+
+    async fn stale_pending(
+        &self,
+        before: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<Order>, RepoError> {
+        let mut stale: Vec<Order> = self
+            .map
+            .iter()
+            .filter(|kv| {
+                kv.value().status == OrderStatus::Pending && kv.value().created_at < before
+            })
+            .map(|kv| kv.value().clone())
+            .collect();
+        stale.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id)));
+        stale.truncate(limit);
+        Ok(stale)
+    }
+
+    async fn scan(&self, after: Option<Uuid>, limit: usize) -> Result<Vec<Order>, RepoError> {
+        let mut page: Vec<Order> = self
+            .map
+            .iter()
+            .filter(|kv| after.is_none_or(|after| *kv.key() > after))
+            .map(|kv| kv.value().clone())
+            .collect();
+        page.sort_by_key(|o| o.id);
+        page.truncate(limit);
+        Ok(page)
     }
 
     async fn update_status(
         &self,
+        tenant: &TenantId,
         id: Uuid,
         status: OrderStatus,
     ) -> Result<Option<Order>, RepoError> {
         if let Some(mut v) = self.map.get_mut(&id) {
-            v.update_status(status);
-            return Ok(Some(v.clone()));
+            if &v.tenant_id == tenant {
+                v.update_status(status);
+                return Ok(Some(v.clone()));
+            }
         }
         Ok(None)
     }
 
-    async fn delete(&self, id: Uuid) -> Result<bool, RepoError> {
-        Ok(self.map.remove(&id).is_some())
+    async fn update(&self, order: Order) -> Result<Option<Order>, RepoError> {
+        if let Some(mut v) = self.map.get_mut(&order.id) {
+            if v.tenant_id == order.tenant_id {
+                let order = Order {
+                    order_number: v.order_number,
+                    created_by: v.created_by.clone(),
+                    ..order
+                };
+                *v = order.clone();
+                return Ok(Some(order));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn update_items(
+        &self,
+        order: &Order,
+        read_at: DateTime<Utc>,
+    ) -> Result<Option<Order>, RepoError> {
+        let Some(mut v) = self.map.get_mut(&order.id) else {
+            return Ok(None);
+        };
+        if v.tenant_id != order.tenant_id {
+            return Ok(None);
+        }
+        if v.updated_at != read_at || !v.items_editable() {
+            return Err(RepoError::Conflict(format!(
+                "order {} was modified",
+                order.id
+            )));
+        }
+        *v = order.clone();
+        Ok(Some(order.clone()))
+    }
+
+    async fn delete(&self, tenant: &TenantId, id: Uuid) -> Result<bool, RepoError> {
+        let deleted = self
+            .map
+            .remove_if(&id, |_, order| &order.tenant_id == tenant)
+            .is_some();
+        if deleted {
+            self.history.remove(&(tenant.clone(), id));
+            self.fulfillments.remove(&(tenant.clone(), id));
+        }
+        Ok(deleted)
+    }
+
+    async fn record_transition(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        entry: OrderHistoryEntry,
+    ) -> Result<(), RepoError> {
+        self.history
+            .entry((tenant.clone(), id))
+            .or_default()
+            .push(entry);
+        Ok(())
+    }
+
+    async fn status_history(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+    ) -> Result<Vec<OrderHistoryEntry>, RepoError> {
+        Ok(self
+            .history
+            .get(&(tenant.clone(), id))
+            .map(|h| h.clone())
+            .unwrap_or_default())
+    }
+
+    async fn record_fulfillment(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        fulfillment: Fulfillment,
+    ) -> Result<(), RepoError> {
+        self.fulfillments
+            .entry((tenant.clone(), id))
+            .or_default()
+            .push(fulfillment);
+        Ok(())
+    }
+
+    async fn fulfillments(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+    ) -> Result<Vec<Fulfillment>, RepoError> {
+        Ok(self
+            .fulfillments
+            .get(&(tenant.clone(), id))
+            .map(|f| f.clone())
+            .unwrap_or_default())
+    }
+}
+
+#[async_trait]
+impl ApiKeyRepository for InMemoryRepo {
+    async fn create_key(&self, key: ApiKey) -> Result<ApiKey, RepoError> {
+        self.api_keys.insert(key.id, key.clone());
+        Ok(key)
+    }
+
+    async fn find_key_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>, RepoError> {
+        Ok(self
+            .api_keys
+            .iter()
+            .find(|kv| kv.value().key_hash == key_hash)
+            .map(|kv| kv.value().clone()))
+    }
+
+    async fn find_key(&self, id: Uuid) -> Result<Option<ApiKey>, RepoError> {
+        Ok(self.api_keys.get(&id).map(|kv| kv.value().clone()))
+    }
+
+    async fn list_keys(&self) -> Result<Vec<ApiKey>, RepoError> {
+        Ok(self.api_keys.iter().map(|kv| kv.value().clone()).collect())
+    }
+
+    async fn revoke_key(&self, id: Uuid) -> Result<bool, RepoError> {
+        if let Some(mut k) = self.api_keys.get_mut(&id) {
+            k.revoked_at.get_or_insert_with(Utc::now);
+            return Ok(true);
+        }
+        Ok(false)
+    }
+}
+
+#[async_trait]
+impl DiscountRepository for InMemoryRepo {
+    async fn create_discount(&self, discount: Discount) -> Result<bool, RepoError> {
+        let key = (discount.tenant_id.clone(), discount.code.clone());
+        match self.discounts.entry(key) {
+            dashmap::mapref::entry::Entry::Occupied(_) => Ok(false),
+            dashmap::mapref::entry::Entry::Vacant(slot) => {
+                slot.insert(discount);
+                Ok(true)
+            }
+        }
+    }
+
+    async fn get_discount(
+        &self,
+        tenant: &TenantId,
+        code: &str,
+    ) -> Result<Option<Discount>, RepoError> {
+        Ok(self
+            .discounts
+            .get(&(tenant.clone(), code.to_string()))
+            .map(|d| d.clone()))
+    }
+
+    async fn list_discounts(&self, tenant: &TenantId) -> Result<Vec<Discount>, RepoError> {
+        let mut list: Vec<Discount> = self
+            .discounts
+            .iter()
+            .filter(|kv| &kv.key().0 == tenant)
+            .map(|kv| kv.value().clone())
+            .collect();
+        list.sort_by(|a, b| a.code.cmp(&b.code));
+        Ok(list)
+    }
+
+    async fn redeem_discount(&self, tenant: &TenantId, code: &str) -> Result<bool, RepoError> {
+        let Some(mut d) = self.discounts.get_mut(&(tenant.clone(), code.to_string())) else {
+            return Ok(false);
+        };
+        if d.max_uses.is_some_and(|max| d.uses >= max) {
+            return Ok(false);
+        }
+        d.uses += 1;
+        Ok(true)
+    }
+
+    async fn release_discount(&self, tenant: &TenantId, code: &str) -> Result<(), RepoError> {
+        if let Some(mut d) = self.discounts.get_mut(&(tenant.clone(), code.to_string())) {
+            d.uses = d.uses.saturating_sub(1);
+        }
+        Ok(())
+    }
+
+    async fn delete_discount(&self, tenant: &TenantId, code: &str) -> Result<bool, RepoError> {
+        Ok(self
+            .discounts
+            .remove(&(tenant.clone(), code.to_string()))
+            .is_some())
+    }
+}
+
+#[async_trait]
+impl AuditRepository for InMemoryRepo {
+    async fn record_audit(&self, entry: AuditEntry) -> Result<(), RepoError> {
+        self.audit
+            .lock()
+            .map_err(|e| RepoError::backend(e.to_string()))?
+            .push(entry);
+        Ok(())
+    }
+
+    async fn order_audit(
+        &self,
+        tenant: &TenantId,
+        order_id: Uuid,
+    ) -> Result<Vec<AuditEntry>, RepoError> {
+        let audit = self
+            .audit
+            .lock()
+            .map_err(|e| RepoError::backend(e.to_string()))?;
+        Ok(audit
+            .iter()
+            .filter(|e| &e.tenant_id == tenant && e.order_id == order_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn list_audit(
+        &self,
+        tenant: &TenantId,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<AuditEntry>, RepoError> {
+        let audit = self
+            .audit
+            .lock()
+            .map_err(|e| RepoError::backend(e.to_string()))?;
+        Ok(audit
+            .iter()
+            .rev()
+            .filter(|e| &e.tenant_id == tenant)
+            .skip(offset)
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    async fn anonymize_audit(&self, tenant: &TenantId, order_id: Uuid) -> Result<u64, RepoError> {
+        let mut audit = self
+            .audit
+            .lock()
+            .map_err(|e| RepoError::backend(e.to_string()))?;
+        let mut changed = 0;
+        for entry in audit
+            .iter_mut()
+            .filter(|e| &e.tenant_id == tenant && e.order_id == order_id)
+        {
+            for order in [&mut entry.before, &mut entry.after].into_iter().flatten() {
+                order.anonymize();
+            }
+            changed += 1;
+        }
+        Ok(changed)
     }
 }
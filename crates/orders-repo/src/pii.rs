@@ -0,0 +1,247 @@
+//! Encryption of an order's personal data at rest.
+//!
+//! `email` and `customer_name` are sealed with AES-256-GCM under the
+//! [`KeyProvider`]'s current key and stored as `enc:v1:<key id>:<hex of
+//! nonce and ciphertext>`, the field name bound in as associated data so a
+//! value can't be moved to another field. Values without the prefix were
+//! written before encryption was turned on and pass through unchanged.
+//! Emails also get a blind index, an HMAC of the lowercased address, so
+//! they can still be looked up.
+
+use std::sync::Arc;
+
+use orders_types::domain::events::OrderEvent;
+use orders_types::domain::order::Order;
+use orders_types::ports::field_encryption::KeyProvider;
+use orders_types::ports::order_repository::RepoError;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::hmac;
+use ring::rand::{SecureRandom, SystemRandom};
+
+const PREFIX: &str = "enc:v1:";
+
+pub const EMAIL: &str = "email";
+pub const CUSTOMER_NAME: &str = "customer_name";
+
+#[derive(Debug)]
+pub struct FieldCipher {
+    keys: Arc<dyn KeyProvider>,
+    rng: SystemRandom,
+}
+
+impl FieldCipher {
+    pub fn new(keys: Arc<dyn KeyProvider>) -> Self {
+        Self {
+            keys,
+            rng: SystemRandom::new(),
+        }
+    }
+
+    fn aead_key(&self, id: &str) -> Result<LessSafeKey, RepoError> {
+        let key = self
+            .keys
+            .key(id)
+            .ok_or_else(|| RepoError::backend(format!("no encryption key `{id}`")))?;
+        let key = UnboundKey::new(&AES_256_GCM, &key)
+            .map_err(|_| RepoError::backend(format!("encryption key `{id}` is unusable")))?;
+        Ok(LessSafeKey::new(key))
+    }
+
+    /// `plain` sealed under the current key.
+    pub fn seal(&self, field: &str, plain: &str) -> Result<String, RepoError> {
+        let id = self.keys.current_key_id();
+        let key = self.aead_key(id)?;
+        let mut nonce = [0u8; NONCE_LEN];
+        self.rng
+            .fill(&mut nonce)
+            .map_err(|_| RepoError::backend("no randomness for a nonce"))?;
+        let mut sealed = plain.as_bytes().to_vec();
+        key.seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(field.as_bytes()),
+            &mut sealed,
+        )
+        .map_err(|_| RepoError::backend(format!("cannot encrypt {field}")))?;
+        let mut out = nonce.to_vec();
+        out.extend_from_slice(&sealed);
+        Ok(format!("{PREFIX}{id}:{}", hex::encode(out)))
+    }
+
+    /// The plaintext of a stored value; plaintext is returned as is.
+    pub fn open(&self, field: &str, stored: &str) -> Result<String, RepoError> {
+        let Some(rest) = stored.strip_prefix(PREFIX) else {
+            return Ok(stored.to_string());
+        };
+        let bad = || RepoError::serialization(format!("cannot decrypt {field}"));
+        let (id, hex) = rest.split_once(':').ok_or_else(bad)?;
+        let bytes = hex::decode(hex).map_err(|_| bad())?;
+        if bytes.len() < NONCE_LEN {
+            return Err(bad());
+        }
+        let (nonce, sealed) = bytes.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| bad())?;
+        let mut sealed = sealed.to_vec();
+        let plain = self
+            .aead_key(id)?
+            .open_in_place(nonce, Aad::from(field.as_bytes()), &mut sealed)
+            .map_err(|_| bad())?;
+        String::from_utf8(plain.to_vec()).map_err(|_| bad())
+    }
+
+    /// Whether `stored` is sealed under the current key, i.e. needs no
+    /// re-encryption.
+    pub fn is_current(&self, stored: &str) -> bool {
+        stored
+            .strip_prefix(PREFIX)
+            .and_then(|rest| rest.split_once(':'))
+            .is_some_and(|(id, _)| id == self.keys.current_key_id())
+    }
+
+    /// Blind index of `email`; equal for addresses that differ only in
+    /// ASCII case, like the plaintext `COLLATE NOCASE` match.
+    pub fn email_index(&self, email: &str) -> String {
+        let key = hmac::Key::new(hmac::HMAC_SHA256, &self.keys.index_key());
+        hex::encode(hmac::sign(&key, email.to_ascii_lowercase().as_bytes()))
+    }
+
+    pub fn seal_order(&self, order: &Order) -> Result<Order, RepoError> {
+        let mut sealed = order.clone();
+        sealed.email = self.seal(EMAIL, &order.email)?;
+        sealed.customer_name = self.seal(CUSTOMER_NAME, &order.customer_name)?;
+        Ok(sealed)
+    }
+
+    pub fn open_order(&self, mut order: Order) -> Result<Order, RepoError> {
+        order.email = self.open(EMAIL, &order.email)?;
+        order.customer_name = self.open(CUSTOMER_NAME, &order.customer_name)?;
+        Ok(order)
+    }
+
+    pub fn order_is_current(&self, order: &Order) -> bool {
+        self.is_current(&order.email) && self.is_current(&order.customer_name)
+    }
+
+    /// `order` sealed under the current key, or `None` if it already is.
+    pub fn reseal_order(&self, order: Order) -> Result<Option<Order>, RepoError> {
+        if self.order_is_current(&order) {
+            return Ok(None);
+        }
+        self.seal_order(&self.open_order(order)?).map(Some)
+    }
+
+    pub fn reseal_event(&self, event: OrderEvent) -> Result<Option<OrderEvent>, RepoError> {
+        Ok(match event {
+            OrderEvent::Created { order } => self
+                .reseal_order(order)?
+                .map(|order| OrderEvent::Created { order }),
+            OrderEvent::Updated { order } => self
+                .reseal_order(order)?
+                .map(|order| OrderEvent::Updated { order }),
+            OrderEvent::StatusOverridden {
+                order,
+                from,
+                reason,
+            } => self
+                .reseal_order(order)?
+                .map(|order| OrderEvent::StatusOverridden {
+                    order,
+                    from,
+                    reason,
+                }),
+            OrderEvent::Deleted { .. } => None,
+        })
+    }
+
+    pub fn seal_event(&self, event: &OrderEvent) -> Result<OrderEvent, RepoError> {
+        Ok(match event {
+            OrderEvent::Created { order } => OrderEvent::Created {
+                order: self.seal_order(order)?,
+            },
+            OrderEvent::Updated { order } => OrderEvent::Updated {
+                order: self.seal_order(order)?,
+            },
+            OrderEvent::StatusOverridden {
+                order,
+                from,
+                reason,
+            } => OrderEvent::StatusOverridden {
+                order: self.seal_order(order)?,
+                from: from.clone(),
+                reason: reason.clone(),
+            },
+            OrderEvent::Deleted { .. } => event.clone(),
+        })
+    }
+
+    pub fn open_event(&self, event: OrderEvent) -> Result<OrderEvent, RepoError> {
+        Ok(match event {
+            OrderEvent::Created { order } => OrderEvent::Created {
+                order: self.open_order(order)?,
+            },
+            OrderEvent::Updated { order } => OrderEvent::Updated {
+                order: self.open_order(order)?,
+            },
+            OrderEvent::StatusOverridden {
+                order,
+                from,
+                reason,
+            } => OrderEvent::StatusOverridden {
+                order: self.open_order(order)?,
+                from,
+                reason,
+            },
+            deleted @ OrderEvent::Deleted { .. } => deleted,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use orders_types::ports::field_encryption::StaticKeys;
+
+    const K1: &str = "1111111111111111111111111111111111111111111111111111111111111111";
+    const K2: &str = "2222222222222222222222222222222222222222222222222222222222222222";
+
+    fn cipher(keys: &str) -> FieldCipher {
+        FieldCipher::new(Arc::new(StaticKeys::parse(keys, K1).unwrap()))
+    }
+
+    #[test]
+    fn sealed_values_open_under_any_known_key() {
+        let old = cipher(&format!("k1={K1}"));
+        let sealed = old.seal(EMAIL, "a@example.com").unwrap();
+        assert!(sealed.starts_with("enc:v1:k1:"));
+        assert_ne!(sealed, old.seal(EMAIL, "a@example.com").unwrap());
+
+        let rotated = cipher(&format!("k2={K2},k1={K1}"));
+        assert_eq!(rotated.open(EMAIL, &sealed).unwrap(), "a@example.com");
+        assert!(old.is_current(&sealed));
+        assert!(!rotated.is_current(&sealed));
+        assert!(cipher(&format!("k2={K2}")).open(EMAIL, &sealed).is_err());
+    }
+
+    #[test]
+    fn tampered_or_moved_values_do_not_open() {
+        let cipher = cipher(&format!("k1={K1}"));
+        let sealed = cipher.seal(EMAIL, "a@example.com").unwrap();
+        assert!(cipher.open(CUSTOMER_NAME, &sealed).is_err());
+        let flipped = if sealed.ends_with('0') { "1" } else { "0" };
+        let tampered = format!("{}{flipped}", &sealed[..sealed.len() - 1]);
+        assert!(cipher.open(EMAIL, &tampered).is_err());
+    }
+
+    #[test]
+    fn plaintext_passes_through_and_is_not_current() {
+        let cipher = cipher(&format!("k1={K1}"));
+        assert_eq!(
+            cipher.open(EMAIL, "a@example.com").unwrap(),
+            "a@example.com"
+        );
+        assert!(!cipher.is_current("a@example.com"));
+        assert_eq!(
+            cipher.email_index("A@Example.com"),
+            cipher.email_index("a@example.com")
+        );
+    }
+}
@@ -0,0 +1,32 @@
+//! Connection pool settings for the SQL adapters.
+//!
+//! The defaults favour concurrent writers: WAL lets readers proceed during a
+//! write, and the busy timeout makes a second writer wait for the lock
+//! instead of failing with `SQLITE_BUSY`.
+
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolOptions {
+    pub max_connections: u32,
+    /// How long a query waits for a free connection before failing.
+    pub acquire_timeout: Duration,
+    /// Close connections idle for this long; kept open when unset.
+    pub idle_timeout: Option<Duration>,
+    /// Use the write-ahead log (sqlite); ignored for in-memory databases.
+    pub wal: bool,
+    /// How long a write waits on a locked database (sqlite).
+    pub busy_timeout: Duration,
+}
+
+impl Default for PoolOptions {
+    fn default() -> Self {
+        Self {
+            max_connections: 10,
+            acquire_timeout: Duration::from_secs(30),
+            idle_timeout: Some(Duration::from_secs(600)),
+            wal: true,
+            busy_timeout: Duration::from_secs(5),
+        }
+    }
+}
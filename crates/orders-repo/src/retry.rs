@@ -0,0 +1,394 @@
+//! Retries for repository calls that fail with [`RepoError::Transient`].
+//!
+//! [`RetryingRepo`] wraps any adapter and repeats a call that failed with a
+//! transient error, such as `SQLITE_BUSY` outlasting the busy timeout or a
+//! dropped connection, backing off exponentially with full jitter between
+//! attempts. Every other error, and the last transient one, is returned as
+//! is. Adapters decide what counts as transient; only errors where the
+//! statement did not take effect should be flagged, since a retried write
+//! runs again.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use orders_types::domain::api_key::ApiKey;
+use orders_types::domain::audit::AuditEntry;
+use orders_types::domain::discount::Discount;
+use orders_types::domain::filter::OrderFilter;
+use orders_types::domain::fulfillment::Fulfillment;
+use orders_types::domain::history::OrderHistoryEntry;
+use orders_types::domain::integrity::{IntegrityReport, StatusMapping};
+use orders_types::domain::order::{Order, OrderStatus};
+use orders_types::domain::order_number::OrderNumber;
+use orders_types::domain::stats::{OrderStats, StatsRange};
+use orders_types::domain::tenant::TenantId;
+use orders_types::ports::api_key_repository::ApiKeyRepository;
+use orders_types::ports::audit_repository::AuditRepository;
+use orders_types::ports::discount_repository::DiscountRepository;
+use orders_types::ports::order_repository::{OrderRepository, RepoError};
+use orders_types::ports::unit_of_work::UnitOfWork;
+use std::future::Future;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// How often and how patiently to retry a transient failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Calls made per operation, including the first; `1` never retries.
+    pub max_attempts: u32,
+    /// Upper bound of the wait before the first retry; doubled for each one
+    /// after.
+    pub base_delay: Duration,
+    /// Longest wait between two attempts.
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(20),
+            max_delay: Duration::from_millis(500),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The wait before retry number `retry` (from 0): a random share of
+    /// `base_delay * 2^retry`, capped at `max_delay`. The randomness keeps
+    /// callers that failed together from retrying in lockstep.
+    pub fn delay(&self, retry: u32) -> Duration {
+        let ceiling = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_delay);
+        let nanos = ceiling.as_nanos().min(u64::MAX as u128) as u64;
+        if nanos == 0 {
+            return Duration::ZERO;
+        }
+        let random = Uuid::new_v4().as_u64_pair().0;
+        Duration::from_nanos(random % (nanos + 1))
+    }
+}
+
+/// Any adapter with transient failures retried per its [`RetryPolicy`].
+///
+/// Retries happen call by call: the operations of a [`UnitOfWork`] from
+/// [`begin`](OrderRepository::begin) are not retried, since the whole unit
+/// would have to start over.
+#[derive(Clone)]
+pub struct RetryingRepo<R> {
+    inner: R,
+    policy: RetryPolicy,
+}
+
+impl<R> RetryingRepo<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            policy: RetryPolicy::default(),
+        }
+    }
+
+    pub fn with_policy(mut self, policy: RetryPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn policy(&self) -> RetryPolicy {
+        self.policy
+    }
+
+    /// Run `call` until it succeeds, fails with a non-transient error or
+    /// runs out of attempts.
+    async fn retry<T, F, Fut>(&self, op: &'static str, mut call: F) -> Result<T, RepoError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, RepoError>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match call().await {
+                Err(e) if e.is_transient() && attempt < self.policy.max_attempts => {
+                    let delay = self.policy.delay(attempt - 1);
+                    tracing::warn!(op, attempt, ?delay, error = %e, "retrying repository call");
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<R: OrderRepository> OrderRepository for RetryingRepo<R> {
+    async fn begin(&self) -> Result<Box<dyn UnitOfWork + '_>, RepoError> {
+        self.retry("begin", || self.inner.begin()).await
+    }
+
+    async fn create(&self, order: Order) -> Result<Order, RepoError> {
+        self.retry("create", || self.inner.create(order.clone()))
+            .await
+    }
+
+    async fn create_many(&self, orders: Vec<Order>) -> Result<(), RepoError> {
+        self.retry("create_many", || self.inner.create_many(orders.clone()))
+            .await
+    }
+
+    async fn get(&self, tenant: &TenantId, id: Uuid) -> Result<Option<Order>, RepoError> {
+        self.retry("get", || self.inner.get(tenant, id)).await
+    }
+
+    async fn get_by_number(
+        &self,
+        tenant: &TenantId,
+        number: &OrderNumber,
+    ) -> Result<Option<Order>, RepoError> {
+        self.retry("get_by_number", || self.inner.get_by_number(tenant, number))
+            .await
+    }
+
+    async fn list(&self, tenant: &TenantId) -> Result<Vec<Order>, RepoError> {
+        self.retry("list", || self.inner.list(tenant)).await
+    }
+
+    async fn list_filtered(
+        &self,
+        tenant: &TenantId,
+        filter: &OrderFilter,
+    ) -> Result<Vec<Order>, RepoError> {
+        self.retry("list_filtered", || self.inner.list_filtered(tenant, filter))
+            .await
+    }
+
+    async fn exists(&self, tenant: &TenantId, id: Uuid) -> Result<bool, RepoError> {
+        self.retry("exists", || self.inner.exists(tenant, id)).await
+    }
+
+    async fn count(&self, tenant: &TenantId, filter: &OrderFilter) -> Result<usize, RepoError> {
+        self.retry("count", || self.inner.count(tenant, filter))
+            .await
+    }
+
+    async fn aggregate(
+        &self,
+        tenant: &TenantId,
+        range: &StatsRange,
+    ) -> Result<OrderStats, RepoError> {
+        self.retry("aggregate", || self.inner.aggregate(tenant, range))
+            .await
+    }
+
+    async fn stale_pending(
+        &self,
+        before: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<Order>, RepoError> {
+        self.retry("stale_pending", || self.inner.stale_pending(before, limit))
+            .await
+    }
+
+    async fn scan(&self, after: Option<Uuid>, limit: usize) -> Result<Vec<Order>, RepoError> {
+        self.retry("scan", || self.inner.scan(after, limit)).await
+    }
+
+    async fn update_status(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        status: OrderStatus,
+    ) -> Result<Option<Order>, RepoError> {
+        self.retry("update_status", || {
+            self.inner.update_status(tenant, id, status.clone())
+        })
+        .await
+    }
+
+    async fn update(&self, order: Order) -> Result<Option<Order>, RepoError> {
+        self.retry("update", || self.inner.update(order.clone()))
+            .await
+    }
+
+    async fn update_items(
+        &self,
+        order: &Order,
+        read_at: DateTime<Utc>,
+    ) -> Result<Option<Order>, RepoError> {
+        self.retry("update_items", || self.inner.update_items(order, read_at))
+            .await
+    }
+
+    async fn delete(&self, tenant: &TenantId, id: Uuid) -> Result<bool, RepoError> {
+        self.retry("delete", || self.inner.delete(tenant, id)).await
+    }
+
+    async fn check_integrity(
+        &self,
+        mapping: &StatusMapping,
+        fix: bool,
+    ) -> Result<IntegrityReport, RepoError> {
+        self.retry("check_integrity", || {
+            self.inner.check_integrity(mapping, fix)
+        })
+        .await
+    }
+
+    async fn record_transition(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        entry: OrderHistoryEntry,
+    ) -> Result<(), RepoError> {
+        self.retry("record_transition", || {
+            self.inner.record_transition(tenant, id, entry.clone())
+        })
+        .await
+    }
+
+    async fn status_history(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+    ) -> Result<Vec<OrderHistoryEntry>, RepoError> {
+        self.retry("status_history", || self.inner.status_history(tenant, id))
+            .await
+    }
+
+    async fn record_fulfillment(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        fulfillment: Fulfillment,
+    ) -> Result<(), RepoError> {
+        self.retry("record_fulfillment", || {
+            self.inner
+                .record_fulfillment(tenant, id, fulfillment.clone())
+        })
+        .await
+    }
+
+    async fn fulfillments(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+    ) -> Result<Vec<Fulfillment>, RepoError> {
+        self.retry("fulfillments", || self.inner.fulfillments(tenant, id))
+            .await
+    }
+
+    /// Not retried: a health check should report the failure it saw.
+    async fn ping(&self) -> Result<(), RepoError> {
+        self.inner.ping().await
+    }
+}
+
+#[async_trait]
+impl<R: ApiKeyRepository> ApiKeyRepository for RetryingRepo<R> {
+    async fn create_key(&self, key: ApiKey) -> Result<ApiKey, RepoError> {
+        self.retry("create_key", || self.inner.create_key(key.clone()))
+            .await
+    }
+
+    async fn find_key_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>, RepoError> {
+        self.retry("find_key_by_hash", || self.inner.find_key_by_hash(key_hash))
+            .await
+    }
+
+    async fn find_key(&self, id: Uuid) -> Result<Option<ApiKey>, RepoError> {
+        self.retry("find_key", || self.inner.find_key(id)).await
+    }
+
+    async fn list_keys(&self) -> Result<Vec<ApiKey>, RepoError> {
+        self.retry("list_keys", || self.inner.list_keys()).await
+    }
+
+    async fn revoke_key(&self, id: Uuid) -> Result<bool, RepoError> {
+        self.retry("revoke_key", || self.inner.revoke_key(id)).await
+    }
+}
+
+#[async_trait]
+impl<R: DiscountRepository> DiscountRepository for RetryingRepo<R> {
+    async fn create_discount(&self, discount: Discount) -> Result<bool, RepoError> {
+        self.retry("create_discount", || {
+            self.inner.create_discount(discount.clone())
+        })
+        .await
+    }
+
+    async fn get_discount(
+        &self,
+        tenant: &TenantId,
+        code: &str,
+    ) -> Result<Option<Discount>, RepoError> {
+        self.retry("get_discount", || self.inner.get_discount(tenant, code))
+            .await
+    }
+
+    async fn list_discounts(&self, tenant: &TenantId) -> Result<Vec<Discount>, RepoError> {
+        self.retry("list_discounts", || self.inner.list_discounts(tenant))
+            .await
+    }
+
+    async fn redeem_discount(&self, tenant: &TenantId, code: &str) -> Result<bool, RepoError> {
+        self.retry("redeem_discount", || {
+            self.inner.redeem_discount(tenant, code)
+        })
+        .await
+    }
+
+    async fn release_discount(&self, tenant: &TenantId, code: &str) -> Result<(), RepoError> {
+        self.retry("release_discount", || {
+            self.inner.release_discount(tenant, code)
+        })
+        .await
+    }
+
+    async fn delete_discount(&self, tenant: &TenantId, code: &str) -> Result<bool, RepoError> {
+        self.retry("delete_discount", || {
+            self.inner.delete_discount(tenant, code)
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl<R: AuditRepository> AuditRepository for RetryingRepo<R> {
+    async fn record_audit(&self, entry: AuditEntry) -> Result<(), RepoError> {
+        self.retry("record_audit", || self.inner.record_audit(entry.clone()))
+            .await
+    }
+
+    async fn order_audit(
+        &self,
+        tenant: &TenantId,
+        order_id: Uuid,
+    ) -> Result<Vec<AuditEntry>, RepoError> {
+        self.retry("order_audit", || self.inner.order_audit(tenant, order_id))
+            .await
+    }
+
+    async fn list_audit(
+        &self,
+        tenant: &TenantId,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<AuditEntry>, RepoError> {
+        self.retry("list_audit", || {
+            self.inner.list_audit(tenant, limit, offset)
+        })
+        .await
+    }
+
+    async fn anonymize_audit(&self, tenant: &TenantId, order_id: Uuid) -> Result<u64, RepoError> {
+        self.retry("anonymize_audit", || {
+            self.inner.anonymize_audit(tenant, order_id)
+        })
+        .await
+    }
+}
@@ -1,63 +1,451 @@
 use async_trait::async_trait;
-use chrono::{DateTime, Utc};
-use orders_types::domain::order::{Order, OrderItem, OrderStatus};
+use chrono::{DateTime, Datelike, Utc};
+use orders_types::domain::actor::Actor;
+use orders_types::domain::address::Address;
+use orders_types::domain::api_key::{ApiKey, Role, Scope};
+use orders_types::domain::audit::{AuditAction, AuditEntry};
+use orders_types::domain::dead_letter::{DeadLetter, DeadLetterDepth, DeadLetterState};
+use orders_types::domain::discount::{AppliedDiscount, Discount};
+use orders_types::domain::events::OrderEvent;
+use orders_types::domain::filter::OrderFilter;
+use orders_types::domain::fulfillment::{FulfilledItem, Fulfillment};
+use orders_types::domain::history::OrderHistoryEntry;
+use orders_types::domain::integrity::{IntegrityIssue, IntegrityReport, StatusMapping};
+use orders_types::domain::money::{Currency, Money};
+use orders_types::domain::order::{Cancellation, Order, OrderItem, OrderStatus};
+use orders_types::domain::order_number::OrderNumber;
+use orders_types::domain::outbox::OutboxRecord;
+use orders_types::domain::pricing::{Charges, PricingSnapshot};
+use orders_types::domain::stats::{OrderStats, StatsRange};
+use orders_types::domain::tenant::TenantId;
+use orders_types::ports::api_key_repository::ApiKeyRepository;
+use orders_types::ports::audit_repository::AuditRepository;
+use orders_types::ports::dead_letter::DeadLetterStore;
+use orders_types::ports::discount_repository::DiscountRepository;
+use orders_types::ports::field_encryption::KeyProvider;
+use orders_types::ports::order_read_repository::{OrderProjection, OrderReadRepository};
 use orders_types::ports::order_repository::{OrderRepository, RepoError};
+use orders_types::ports::outbox::OutboxStore;
+use orders_types::ports::unit_of_work::{ChangeKind, UnitOfWork};
 use serde_json;
-use sqlx::sqlite::SqliteConnectOptions;
-use sqlx::{FromRow, SqlitePool};
+use sqlx::migrate::{Migrate, Migrator};
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions};
+use sqlx::{FromRow, QueryBuilder, Sqlite, SqliteConnection, SqlitePool, Transaction};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
 use std::str::FromStr;
+use std::sync::Arc;
 use uuid::Uuid;
 
+use crate::codec;
+use crate::pii::{self, FieldCipher};
+use crate::pool::PoolOptions;
+use crate::MigrationInfo;
+
+/// Rows [`SqliteRepo::reencrypt_pii`] rewrote, per table.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReencryptReport {
+    pub orders: u64,
+    pub order_views: u64,
+    pub audit_entries: u64,
+    pub outbox: u64,
+    pub dead_letters: u64,
+}
+
+/// Rows read per query by [`SqliteRepo::reencrypt_pii`].
+const REENCRYPT_BATCH: i64 = 500;
+
+/// Queries with fixed SQL are checked against the migrated schema at compile
+/// time with `sqlx::query!` and friends, from the cache in `.sqlx/`; rerun
+/// `sqlx_prepare.sh` at the workspace root after changing one or adding a
+/// migration. Only SQL assembled at runtime (filtered listings, stats, batched item loads
+/// and schema bootstrapping) goes through the unchecked `sqlx::query`.
+#[derive(Clone)]
 pub struct SqliteRepo {
     pool: SqlitePool,
+    /// Write an [`OutboxRecord`] with every order change; see
+    /// [`SqliteRepo::with_outbox`].
+    outbox: bool,
+    /// Seals personal data before it's written; see
+    /// [`SqliteRepo::with_field_encryption`].
+    cipher: Option<Arc<FieldCipher>>,
 }
 
+/// Columns of [`DbOrder`] for queries built at runtime; the checked queries
+/// spell them out.
+const ORDER_COLUMNS: &str = "id, tenant_id, customer_name, email, total_cents, currency, subtotal_cents, discount_cents, tax_cents, shipping_cents, status, created_at, updated_at, pricing_json, discount_json, payment_id, captured_cents, cancel_reason, cancelled_at, shipping_address_json, billing_address_json, order_seq, metadata_json, created_by, updated_by";
+
 #[derive(FromRow)]
 struct DbOrder {
     id: String,
+    tenant_id: String,
     customer_name: String,
     email: String,
     total_cents: i64,
+    currency: String,
+    subtotal_cents: i64,
+    discount_cents: i64,
+    tax_cents: i64,
+    shipping_cents: i64,
     status: String,
     created_at: String,
     updated_at: String,
-    items_json: String,
+    pricing_json: Option<String>,
+    discount_json: Option<String>,
+    payment_id: Option<String>,
+    captured_cents: Option<i64>,
+    cancel_reason: Option<String>,
+    cancelled_at: Option<String>,
+    shipping_address_json: Option<String>,
+    billing_address_json: Option<String>,
+    order_seq: Option<i64>,
+    metadata_json: Option<String>,
+    created_by: Option<String>,
+    updated_by: Option<String>,
 }
 
 impl DbOrder {
-    fn into_order(self) -> Result<Order, RepoError> {
-        let status = match self.status.as_str() {
-            "Pending" => OrderStatus::Pending,
-            "Confirmed" => OrderStatus::Confirmed,
-            "Shipped" => OrderStatus::Shipped,
-            "Cancelled" => OrderStatus::Cancelled,
-            "Completed" => OrderStatus::Completed,
-            _ => OrderStatus::Pending,
-        };
-        let items: Vec<OrderItem> = serde_json::from_str(&self.items_json)
-            .map_err(|e| RepoError::DbError(e.to_string()))?;
+    /// Unknown statuses are an error rather than a guess; see
+    /// [`OrderRepository::check_integrity`] for repairing them.
+    fn into_order(self, items: Vec<DbOrderItem>) -> Result<Order, RepoError> {
+        let status = OrderStatus::parse(&self.status).ok_or_else(|| {
+            RepoError::serialization(format!(
+                "order {} has unknown status `{}`",
+                self.id, self.status
+            ))
+        })?;
+        self.into_order_with(status, items)
+    }
+
+    fn into_order_with(
+        self,
+        status: OrderStatus,
+        items: Vec<DbOrderItem>,
+    ) -> Result<Order, RepoError> {
+        let items = items
+            .into_iter()
+            .map(DbOrderItem::into_item)
+            .collect::<Result<Vec<_>, _>>()?;
         let created_at = DateTime::parse_from_rfc3339(&self.created_at)
-            .map_err(|e| RepoError::DbError(e.to_string()))?
+            .map_err(RepoError::serialization)?
             .with_timezone(&Utc);
         let updated_at = DateTime::parse_from_rfc3339(&self.updated_at)
-            .map_err(|e| RepoError::DbError(e.to_string()))?
+            .map_err(RepoError::serialization)?
             .with_timezone(&Utc);
-        let id = Uuid::parse_str(&self.id).map_err(|e| RepoError::DbError(e.to_string()))?;
+        let order_number = self
+            .order_seq
+            .map(|seq| OrderNumber::new(created_at.year(), seq as u64));
+        let pricing: Option<PricingSnapshot> = self
+            .pricing_json
+            .as_deref()
+            .map(serde_json::from_str)
+            .transpose()
+            .map_err(RepoError::serialization)?;
+        let discount: Option<AppliedDiscount> = self
+            .discount_json
+            .as_deref()
+            .map(serde_json::from_str)
+            .transpose()
+            .map_err(RepoError::serialization)?;
+        let address = |json: Option<String>| -> Result<Option<Address>, RepoError> {
+            json.as_deref()
+                .map(serde_json::from_str)
+                .transpose()
+                .map_err(RepoError::serialization)
+        };
+        let shipping_address = address(self.shipping_address_json)?;
+        let billing_address = address(self.billing_address_json)?;
+        let metadata = self
+            .metadata_json
+            .as_deref()
+            .map(serde_json::from_str)
+            .transpose()
+            .map_err(RepoError::serialization)?
+            .unwrap_or_default();
+        let actor = |s: Option<String>| -> Result<Option<Actor>, RepoError> {
+            s.as_deref()
+                .map(Actor::parse)
+                .transpose()
+                .map_err(RepoError::serialization)
+        };
+        let created_by = actor(self.created_by)?;
+        let updated_by = actor(self.updated_by)?;
+        let cancellation = match (self.cancel_reason, self.cancelled_at) {
+            (Some(reason), Some(at)) => Some(Cancellation {
+                reason,
+                cancelled_at: DateTime::parse_from_rfc3339(&at)
+                    .map_err(RepoError::serialization)?
+                    .with_timezone(&Utc),
+            }),
+            _ => None,
+        };
+        let id = Uuid::parse_str(&self.id).map_err(RepoError::serialization)?;
+        let tenant_id = TenantId::parse(&self.tenant_id).map_err(RepoError::serialization)?;
+        let currency = Currency::parse(&self.currency).map_err(RepoError::serialization)?;
         Ok(Order {
             id,
+            order_number,
+            tenant_id,
             customer_name: self.customer_name,
             email: self.email,
             items,
-            total_cents: self.total_cents,
+            total: Money::new(self.total_cents, currency),
+            charges: Charges {
+                subtotal_cents: self.subtotal_cents,
+                discount_cents: self.discount_cents,
+                tax_cents: self.tax_cents,
+                shipping_cents: self.shipping_cents,
+            },
+            discount,
             status,
             created_at,
             updated_at,
+            pricing,
+            payment_id: self.payment_id,
+            captured: self.captured_cents.map(|c| Money::new(c, currency)),
+            cancellation,
+            shipping_address,
+            billing_address,
+            metadata,
+            created_by,
+            updated_by,
+        })
+    }
+}
+
+/// Columns of [`DbOrderItem`], as for [`ORDER_COLUMNS`].
+const ITEM_COLUMNS: &str = "order_id, name, qty, unit_price_cents, currency, weight_grams, sku, description, metadata_json, discount_cents";
+
+#[derive(FromRow)]
+struct DbOrderItem {
+    order_id: String,
+    name: String,
+    qty: i64,
+    unit_price_cents: i64,
+    currency: String,
+    weight_grams: i64,
+    sku: Option<String>,
+    description: Option<String>,
+    metadata_json: Option<String>,
+    discount_cents: i64,
+}
+
+impl DbOrderItem {
+    fn into_item(self) -> Result<OrderItem, RepoError> {
+        let count = |n: i64| u32::try_from(n).map_err(RepoError::serialization);
+        let currency = Currency::parse(&self.currency).map_err(RepoError::serialization)?;
+        Ok(OrderItem {
+            name: self.name,
+            qty: count(self.qty)?,
+            unit_price: Money::new(self.unit_price_cents, currency),
+            weight_grams: count(self.weight_grams)?,
+            sku: self.sku,
+            description: self.description,
+            metadata: self
+                .metadata_json
+                .as_deref()
+                .map(serde_json::from_str)
+                .transpose()
+                .map_err(RepoError::serialization)?
+                .unwrap_or_default(),
+            discount_cents: self.discount_cents,
+        })
+    }
+}
+
+struct DbApiKey {
+    id: String,
+    name: String,
+    key_hash: String,
+    scopes: String,
+    role: Option<String>,
+    tenant_id: String,
+    created_at: String,
+    revoked_at: Option<String>,
+}
+
+impl DbApiKey {
+    fn into_key(self) -> Result<ApiKey, RepoError> {
+        let parse_ts = |s: &str| {
+            DateTime::parse_from_rfc3339(s)
+                .map(|d| d.with_timezone(&Utc))
+                .map_err(RepoError::serialization)
+        };
+        Ok(ApiKey {
+            id: Uuid::parse_str(&self.id).map_err(RepoError::serialization)?,
+            name: self.name,
+            key_hash: self.key_hash,
+            scopes: self.scopes.split(',').filter_map(Scope::parse).collect(),
+            role: self.role.as_deref().and_then(Role::parse),
+            tenant: TenantId::parse(&self.tenant_id).map_err(RepoError::serialization)?,
+            created_at: parse_ts(&self.created_at)?,
+            revoked_at: self.revoked_at.as_deref().map(parse_ts).transpose()?,
+        })
+    }
+}
+
+struct DbDiscount {
+    tenant_id: String,
+    code: String,
+    kind_json: String,
+    min_order_json: Option<String>,
+    expires_at: Option<String>,
+    max_uses: Option<i64>,
+    uses: i64,
+    created_at: String,
+}
+
+impl DbDiscount {
+    fn into_discount(self) -> Result<Discount, RepoError> {
+        let db = RepoError::serialization::<String>;
+        let parse_ts = |s: &str| {
+            DateTime::parse_from_rfc3339(s)
+                .map(|d| d.with_timezone(&Utc))
+                .map_err(|e| db(e.to_string()))
+        };
+        Ok(Discount {
+            tenant_id: TenantId::parse(&self.tenant_id).map_err(db)?,
+            code: self.code,
+            kind: serde_json::from_str(&self.kind_json).map_err(|e| db(e.to_string()))?,
+            min_order: self
+                .min_order_json
+                .as_deref()
+                .map(serde_json::from_str)
+                .transpose()
+                .map_err(|e| db(e.to_string()))?,
+            expires_at: self.expires_at.as_deref().map(parse_ts).transpose()?,
+            max_uses: self.max_uses.map(|n| n as u32),
+            uses: self.uses as u32,
+            created_at: parse_ts(&self.created_at)?,
+        })
+    }
+}
+
+struct DbHistoryEntry {
+    from_status: Option<String>,
+    to_status: String,
+    at: String,
+    actor: String,
+    note: Option<String>,
+}
+
+impl DbHistoryEntry {
+    fn into_entry(self) -> Result<OrderHistoryEntry, RepoError> {
+        let status = |s: &str| {
+            OrderStatus::parse(s)
+                .ok_or_else(|| RepoError::serialization(format!("unknown status `{s}`")))
+        };
+        Ok(OrderHistoryEntry {
+            from: self.from_status.as_deref().map(status).transpose()?,
+            to: status(&self.to_status)?,
+            at: DateTime::parse_from_rfc3339(&self.at)
+                .map(|d| d.with_timezone(&Utc))
+                .map_err(RepoError::serialization)?,
+            actor: self.actor,
+            note: self.note,
+        })
+    }
+}
+
+struct DbFulfillment {
+    id: String,
+    carrier: String,
+    tracking_number: String,
+    shipped_at: String,
+}
+
+impl DbFulfillment {
+    fn into_fulfillment(self, items: Vec<FulfilledItem>) -> Result<Fulfillment, RepoError> {
+        Ok(Fulfillment {
+            id: Uuid::parse_str(&self.id).map_err(RepoError::serialization)?,
+            items,
+            carrier: self.carrier,
+            tracking_number: self.tracking_number,
+            shipped_at: DateTime::parse_from_rfc3339(&self.shipped_at)
+                .map(|d| d.with_timezone(&Utc))
+                .map_err(RepoError::serialization)?,
+        })
+    }
+}
+
+struct DbFulfilledItem {
+    fulfillment_id: String,
+    position: i64,
+    qty: i64,
+}
+
+struct DbAuditEntry {
+    id: String,
+    tenant_id: String,
+    order_id: String,
+    action: String,
+    actor: String,
+    at: String,
+    before_json: Option<String>,
+    after_json: Option<String>,
+    reason: Option<String>,
+    pricing_diff_json: Option<String>,
+}
+
+impl DbAuditEntry {
+    fn into_entry(self) -> Result<AuditEntry, RepoError> {
+        let db = RepoError::serialization::<String>;
+        let order = |json: Option<String>| {
+            json.as_deref()
+                .map(serde_json::from_str)
+                .transpose()
+                .map_err(|e| db(e.to_string()))
+        };
+        Ok(AuditEntry {
+            id: Uuid::parse_str(&self.id).map_err(|e| db(e.to_string()))?,
+            tenant_id: TenantId::parse(&self.tenant_id).map_err(db)?,
+            order_id: Uuid::parse_str(&self.order_id).map_err(|e| db(e.to_string()))?,
+            action: AuditAction::parse(&self.action).map_err(db)?,
+            actor: self.actor,
+            at: DateTime::parse_from_rfc3339(&self.at)
+                .map(|d| d.with_timezone(&Utc))
+                .map_err(|e| db(e.to_string()))?,
+            before: order(self.before_json)?,
+            after: order(self.after_json)?,
+            reason: self.reason,
+            pricing_diff: self
+                .pricing_diff_json
+                .as_deref()
+                .map(serde_json::from_str)
+                .transpose()
+                .map_err(|e| db(e.to_string()))?,
         })
     }
 }
 
+/// Schema migrations from `migrations/`, applied in version order.
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// Last migration applied by the ad-hoc startup DDL that predates migration
+/// tracking.
+const LAST_UNTRACKED_MIGRATION: i64 = 6;
+
 impl SqliteRepo {
+    /// Connect and apply any pending migrations.
     pub async fn new(database_url: &str) -> anyhow::Result<Self> {
+        Self::new_with(database_url, &PoolOptions::default()).await
+    }
+
+    /// [`SqliteRepo::new`] with explicit pool settings.
+    pub async fn new_with(database_url: &str, pool: &PoolOptions) -> anyhow::Result<Self> {
+        let repo = Self::connect_with(database_url, pool).await?;
+        repo.migrate().await?;
+        Ok(repo)
+    }
+
+    /// Connect without applying migrations, e.g. to inspect
+    /// [`SqliteRepo::pending_migrations`] first.
+    pub async fn connect(database_url: &str) -> anyhow::Result<Self> {
+        Self::connect_with(database_url, &PoolOptions::default()).await
+    }
+
+    /// [`SqliteRepo::connect`] with explicit pool settings.
+    pub async fn connect_with(database_url: &str, pool: &PoolOptions) -> anyhow::Result<Self> {
         // Ensure on-disk SQLite target directory exists (no-op for in-memory).
         if let Some(path) = database_url.strip_prefix("sqlite://") {
             if path != ":memory:" {
@@ -70,90 +458,2321 @@ impl SqliteRepo {
             }
         }
 
-        let options = SqliteConnectOptions::from_str(database_url)?.create_if_missing(true);
+        let mut options = SqliteConnectOptions::from_str(database_url)?
+            .create_if_missing(true)
+            .busy_timeout(pool.busy_timeout);
+        if pool.wal {
+            options = options.journal_mode(SqliteJournalMode::Wal);
+        }
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(pool.max_connections)
+            .acquire_timeout(pool.acquire_timeout)
+            .idle_timeout(pool.idle_timeout)
+            .connect_with(options)
+            .await?;
+        adopt_untracked_schema(&pool).await?;
 
-        let pool = SqlitePool::connect_with(options).await?;
+        Ok(Self {
+            pool,
+            outbox: false,
+            cipher: None,
+        })
+    }
 
-        // Run migration from migration file.
-        let ddl = include_str!("../migrations/0001_create_orders.sql");
-        sqlx::query(ddl).execute(&pool).await?;
+    /// Record every order change in the `outbox` table, in the transaction
+    /// that makes it, for a relay such as `orders-worker` to forward. Leave
+    /// it off when nothing consumes the outbox, or the table only grows.
+    pub fn with_outbox(mut self) -> Self {
+        self.outbox = true;
+        self
+    }
 
-        Ok(Self { pool })
+    /// Encrypt each order's `email` and `customer_name` under `keys`
+    /// wherever this adapter stores them: orders, the read model, the
+    /// outbox, dead letters and the audit log. Rows written before keep
+    /// reading as they are; [`SqliteRepo::reencrypt_pii`] seals them.
+    /// Sorting by customer name orders by ciphertext once this is on.
+    pub fn with_field_encryption(mut self, keys: Arc<dyn KeyProvider>) -> Self {
+        self.cipher = Some(Arc::new(FieldCipher::new(keys)));
+        self
     }
-}
 
-#[async_trait]
-impl OrderRepository for SqliteRepo {
-    async fn create(&self, order: Order) -> Result<Order, RepoError> {
-        let items_json =
-            serde_json::to_string(&order.items).map_err(|e| RepoError::DbError(e.to_string()))?;
-        sqlx::query(
-            "INSERT INTO orders (id, customer_name, email, total_cents, status, created_at, updated_at, items_json)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
-        )
-        .bind(order.id.to_string())
-        .bind(&order.customer_name)
-        .bind(&order.email)
-        .bind(order.total_cents)
-        .bind(format!("{:?}", order.status))
-        .bind(order.created_at.to_rfc3339())
-        .bind(order.updated_at.to_rfc3339())
-        .bind(items_json)
-        .execute(&self.pool)
+    /// `order` as it's stored.
+    fn at_rest<'a>(&self, order: &'a Order) -> Result<Cow<'a, Order>, RepoError> {
+        match &self.cipher {
+            Some(cipher) => cipher.seal_order(order).map(Cow::Owned),
+            None => Ok(Cow::Borrowed(order)),
+        }
+    }
+
+    /// A stored order with its personal data decrypted.
+    fn opened(&self, order: Order) -> Result<Order, RepoError> {
+        match &self.cipher {
+            Some(cipher) => cipher.open_order(order),
+            None => Ok(order),
+        }
+    }
+
+    fn opened_event(&self, event: OrderEvent) -> Result<OrderEvent, RepoError> {
+        match &self.cipher {
+            Some(cipher) => cipher.open_event(event),
+            None => Ok(event),
+        }
+    }
+
+    /// What the email filter compares `email_index` with; `None` without
+    /// encryption, when the column is empty.
+    fn email_index(&self, email: Option<&str>) -> Option<String> {
+        Some(self.cipher.as_ref()?.email_index(email?))
+    }
+
+    /// `select` over the rows of `tenant` that `filter` matches, mirroring
+    /// `OrderFilter::matches`: exact status, ASCII case-insensitive email
+    /// and country, half-open creation range, exact metadata entries.
+    ///
+    /// Only the predicates in use are written out. A `?N IS NULL OR ...`
+    /// guard would hide the bound value from SQLite, which picks its index
+    /// when it prepares the statement. Works on `orders` and `order_views`
+    /// alike.
+    fn filtered(
+        &self,
+        select: &str,
+        tenant: &TenantId,
+        filter: &OrderFilter,
+    ) -> QueryBuilder<'static, Sqlite> {
+        let mut query = QueryBuilder::new(select);
+        query
+            .push(" WHERE tenant_id = ")
+            .push_bind(tenant.as_str().to_owned());
+        if let Some(status) = &filter.status {
+            query
+                .push(" AND status = ")
+                .push_bind(format!("{:?}", status));
+        }
+        match (&filter.email, self.email_index(filter.email.as_deref())) {
+            (Some(email), None) => {
+                query
+                    .push(" AND email = ")
+                    .push_bind(email.clone())
+                    .push(" COLLATE NOCASE");
+            }
+            // Repeating the tenant in each arm lets SQLite answer the `OR`
+            // from both email indexes instead of scanning the tenant.
+            (Some(email), Some(index)) => {
+                query
+                    .push(" AND ((tenant_id = ")
+                    .push_bind(tenant.as_str().to_owned())
+                    .push(" AND email = ")
+                    .push_bind(email.clone())
+                    .push(" COLLATE NOCASE) OR (tenant_id = ")
+                    .push_bind(tenant.as_str().to_owned())
+                    .push(" AND email_index = ")
+                    .push_bind(index)
+                    .push("))");
+            }
+            (None, _) => {}
+        }
+        if let Some(country) = &filter.country {
+            query
+                .push(" AND shipping_country = ")
+                .push_bind(country.clone())
+                .push(" COLLATE NOCASE");
+        }
+        // Timestamps are written by `to_rfc3339` in UTC, so they compare
+        // correctly as text.
+        if let Some(after) = filter.created_after {
+            query
+                .push(" AND created_at >= ")
+                .push_bind(after.to_rfc3339());
+        }
+        if let Some(before) = filter.created_before {
+            query
+                .push(" AND created_at < ")
+                .push_bind(before.to_rfc3339());
+        }
+        for (key, value) in &filter.metadata {
+            query
+                .push(" AND EXISTS (SELECT 1 FROM json_each(metadata_json) WHERE key = ")
+                .push_bind(key.clone())
+                .push(" AND value = ")
+                .push_bind(value.clone())
+                .push(")");
+        }
+        query
+    }
+
+    /// Apply pending migrations, returning the ones that ran.
+    pub async fn migrate(&self) -> anyhow::Result<Vec<MigrationInfo>> {
+        let pending = self.pending_migrations().await?;
+        MIGRATOR.run(&self.pool).await?;
+        for m in &pending {
+            tracing::info!(version = m.version, description = %m.description, "applied migration");
+        }
+        let unpacked = self.unpack_compressed_items().await?;
+        if unpacked > 0 {
+            tracing::info!(
+                orders = unpacked,
+                "moved compressed items_json into order_items"
+            );
+        }
+        Ok(pending)
+    }
+
+    /// Move lines still held in zstd-compressed `items_json` blobs, which the
+    /// `order_items` migration can't read, into `order_items`. Returns the
+    /// number of orders moved.
+    async fn unpack_compressed_items(&self) -> anyhow::Result<usize> {
+        let rows: Vec<(String, Vec<u8>)> =
+            sqlx::query_as("SELECT id, items_json FROM orders WHERE typeof(items_json) = 'blob'")
+                .fetch_all(&self.pool)
+                .await?;
+        for (id, stored) in &rows {
+            let items: Vec<OrderItem> = serde_json::from_slice(&codec::decode(stored)?)?;
+            let mut tx = self.pool.begin().await?;
+            insert_items(&mut tx, id, &items).await?;
+            sqlx::query("UPDATE orders SET items_json = '[]' WHERE id = ?")
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+        }
+        Ok(rows.len())
+    }
+
+    /// Migrations known to this build that the database hasn't applied yet.
+    pub async fn pending_migrations(&self) -> anyhow::Result<Vec<MigrationInfo>> {
+        let applied: Vec<(i64,)> = if table_exists(&self.pool, "_sqlx_migrations").await? {
+            sqlx::query_as("SELECT version FROM _sqlx_migrations WHERE success = 1")
+                .fetch_all(&self.pool)
+                .await?
+        } else {
+            Vec::new()
+        };
+        Ok(MIGRATOR
+            .iter()
+            .filter(|m| !applied.iter().any(|(v,)| *v == m.version))
+            .map(|m| MigrationInfo {
+                version: m.version,
+                description: m.description.to_string(),
+            })
+            .collect())
+    }
+
+    /// Highest applied migration, from the `schema_version` view.
+    pub async fn schema_version(&self) -> anyhow::Result<Option<i64>> {
+        if !table_exists(&self.pool, "schema_version").await? {
+            return Ok(None);
+        }
+        let (version,): (Option<i64>,) = sqlx::query_as("SELECT version FROM schema_version")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(version)
+    }
+
+    /// Seal personal data stored in plaintext or under an older key with
+    /// the current key, so old keys can be retired. Rows are rewritten a
+    /// batch at a time and only if unchanged since they were read; a rerun
+    /// skips rows that are already current.
+    pub async fn reencrypt_pii(&self) -> anyhow::Result<ReencryptReport> {
+        let cipher = self
+            .cipher
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("field encryption is not configured"))?;
+        let mut report = ReencryptReport::default();
+
+        let mut after = 0i64;
+        loop {
+            let rows = sqlx::query!(
+                "SELECT rowid, customer_name, email FROM orders WHERE rowid > ?
+                 ORDER BY rowid LIMIT ?",
+                after,
+                REENCRYPT_BATCH,
+            )
+            .fetch_all(&self.pool)
+            .await?;
+            let Some(last) = rows.last() else {
+                break;
+            };
+            after = last.rowid;
+            for row in rows {
+                let (rowid, name, email) = (row.rowid, row.customer_name, row.email);
+                if cipher.is_current(&name) && cipher.is_current(&email) {
+                    continue;
+                }
+                let plain_email = cipher.open(pii::EMAIL, &email)?;
+                let plain_name = cipher.open(pii::CUSTOMER_NAME, &name)?;
+                let sealed_name = cipher.seal(pii::CUSTOMER_NAME, &plain_name)?;
+                let sealed_email = cipher.seal(pii::EMAIL, &plain_email)?;
+                let index = cipher.email_index(&plain_email);
+                let res = sqlx::query!(
+                    "UPDATE orders SET customer_name = ?, email = ?, email_index = ?
+                     WHERE rowid = ? AND customer_name = ? AND email = ?",
+                    sealed_name,
+                    sealed_email,
+                    index,
+                    rowid,
+                    name,
+                    email,
+                )
+                .execute(&self.pool)
+                .await?;
+                report.orders += res.rows_affected();
+            }
+        }
+
+        let mut after = 0i64;
+        loop {
+            let rows = sqlx::query!(
+                "SELECT rowid, order_json FROM order_views WHERE rowid > ?
+                 ORDER BY rowid LIMIT ?",
+                after,
+                REENCRYPT_BATCH,
+            )
+            .fetch_all(&self.pool)
+            .await?;
+            let Some(last) = rows.last() else {
+                break;
+            };
+            after = last.rowid;
+            for row in rows {
+                let (rowid, json) = (row.rowid, row.order_json);
+                let order: Order = serde_json::from_str(&json)?;
+                if cipher.order_is_current(&order) {
+                    continue;
+                }
+                let order = cipher.open_order(order)?;
+                let sealed = cipher.seal_order(&order)?;
+                let index = cipher.email_index(&order.email);
+                let sealed_json = serde_json::to_string(&sealed)?;
+                let res = sqlx::query!(
+                    "UPDATE order_views SET customer_name = ?, email = ?, email_index = ?,
+                       order_json = ?
+                     WHERE rowid = ? AND order_json = ?",
+                    sealed.customer_name,
+                    sealed.email,
+                    index,
+                    sealed_json,
+                    rowid,
+                    json,
+                )
+                .execute(&self.pool)
+                .await?;
+                report.order_views += res.rows_affected();
+            }
+        }
+
+        for column in ["before_json", "after_json"] {
+            report.audit_entries += self
+                .reseal_json("audit_log", column, |order: Order| {
+                    cipher.reseal_order(order)
+                })
+                .await?;
+        }
+        report.outbox = self
+            .reseal_json("outbox", "event_json", |event: OrderEvent| {
+                cipher.reseal_event(event)
+            })
+            .await?;
+        report.dead_letters = self
+            .reseal_json("dead_letters", "record_json", |record: OutboxRecord| {
+                Ok(cipher
+                    .reseal_event(record.event.clone())?
+                    .map(|event| OutboxRecord { event, ..record }))
+            })
+            .await?;
+        Ok(report)
+    }
+
+    /// Rewrite each value of the JSON `column` of `table` that `reseal`
+    /// changes, returning how many were. `table` and `column` are ours,
+    /// never the caller's.
+    async fn reseal_json<T: serde::Serialize + serde::de::DeserializeOwned>(
+        &self,
+        table: &str,
+        column: &str,
+        reseal: impl Fn(T) -> Result<Option<T>, RepoError>,
+    ) -> anyhow::Result<u64> {
+        let mut resealed = 0;
+        let mut after = 0i64;
+        loop {
+            let rows: Vec<(i64, String)> = sqlx::query_as(&format!(
+                "SELECT rowid, {column} FROM {table} WHERE rowid > ? AND {column} IS NOT NULL
+                 ORDER BY rowid LIMIT ?"
+            ))
+            .bind(after)
+            .bind(REENCRYPT_BATCH)
+            .fetch_all(&self.pool)
+            .await?;
+            let Some(&(last, _)) = rows.last() else {
+                break;
+            };
+            after = last;
+            for (rowid, json) in rows {
+                let Some(value) = reseal(serde_json::from_str(&json)?)? else {
+                    continue;
+                };
+                let res = sqlx::query(&format!(
+                    "UPDATE {table} SET {column} = ? WHERE rowid = ? AND {column} = ?"
+                ))
+                .bind(serde_json::to_string(&value)?)
+                .bind(rowid)
+                .bind(&json)
+                .execute(&self.pool)
+                .await?;
+                resealed += res.rows_affected();
+            }
+        }
+        Ok(resealed)
+    }
+
+    /// Insert `order` and its items, and return it with its order number:
+    /// the one it came with, or the next in sequence.
+    async fn insert_order(
+        &self,
+        conn: &mut SqliteConnection,
+        mut order: Order,
+    ) -> Result<Order, RepoError> {
+        let stored = self.at_rest(&order)?;
+        let row = OrderRow::new(&order, self.email_index(Some(&order.email)))?;
+        let created_at = order.created_at.to_rfc3339();
+        let order_seq = order.order_number.map(|n| n.seq() as i64);
+        let created_by = order.created_by.as_ref().map(Actor::to_string);
+        let captured_cents = order.captured.map(|m| m.amount_minor());
+        let seq = sqlx::query_scalar!(
+            r#"INSERT INTO orders (id, tenant_id, customer_name, email, total_cents, currency, subtotal_cents, discount_cents, tax_cents, shipping_cents, status, created_at, updated_at, pricing_json, discount_json, payment_id, captured_cents, cancel_reason, cancelled_at, shipping_address_json, billing_address_json, shipping_country, email_index, items_json, order_seq, metadata_json, created_by, updated_by)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, '[]', COALESCE(?, (SELECT COALESCE(MAX(order_seq), 0) + 1 FROM orders)), ?, ?, ?)
+             RETURNING order_seq AS "order_seq!: i64""#,
+            row.id,
+            row.tenant_id,
+            stored.customer_name,
+            stored.email,
+            row.total_cents,
+            row.currency,
+            order.charges.subtotal_cents,
+            order.charges.discount_cents,
+            order.charges.tax_cents,
+            order.charges.shipping_cents,
+            row.status,
+            created_at,
+            row.updated_at,
+            row.pricing_json,
+            row.discount_json,
+            order.payment_id,
+            captured_cents,
+            row.cancel_reason,
+            row.cancelled_at,
+            row.shipping_address_json,
+            row.billing_address_json,
+            row.shipping_country,
+            row.email_index,
+            order_seq,
+            row.metadata_json,
+            created_by,
+            row.updated_by,
+        )
+        .fetch_one(&mut *conn)
         .await
-        .map_err(|e| RepoError::DbError(e.to_string()))?;
+        .map_err(sqlx_error)?;
+        insert_items(conn, &order.id.to_string(), &order.items).await?;
+        order.order_number = Some(OrderNumber::new(order.created_at.year(), seq as u64));
         Ok(order)
     }
 
-    async fn get(&self, id: Uuid) -> Result<Option<Order>, RepoError> {
-        let row: Option<DbOrder> = sqlx::query_as(
-            "SELECT id, customer_name, email, total_cents, status, created_at, updated_at, items_json FROM orders WHERE id = ?",
+    /// Queue `event` for the relay as part of the transaction on `conn`.
+    async fn append_outbox(
+        &self,
+        conn: &mut SqliteConnection,
+        event: OrderEvent,
+    ) -> Result<(), RepoError> {
+        if !self.outbox {
+            return Ok(());
+        }
+        let event = match &self.cipher {
+            Some(cipher) => cipher.seal_event(&event)?,
+            None => event,
+        };
+        let json = serde_json::to_string(&event).map_err(RepoError::serialization)?;
+        let tenant_id = event.tenant_id().as_str();
+        let order_id = event.order_id().to_string();
+        let recorded_at = Utc::now().to_rfc3339();
+        sqlx::query!(
+            "INSERT INTO outbox (tenant_id, order_id, event_json, recorded_at) VALUES (?, ?, ?, ?)",
+            tenant_id,
+            order_id,
+            json,
+            recorded_at,
         )
-        .bind(id.to_string())
-        .fetch_optional(&self.pool)
+        .execute(&mut *conn)
         .await
-        .map_err(|e| RepoError::DbError(e.to_string()))?;
-        Ok(row.map(|r| r.into_order()).transpose()?)
+        .map_err(sqlx_error)?;
+        Ok(())
     }
 
-    async fn list(&self) -> Result<Vec<Order>, RepoError> {
-        let rows: Vec<DbOrder> = sqlx::query_as(
-            "SELECT id, customer_name, email, total_cents, status, created_at, updated_at, items_json FROM orders",
+    async fn create_in(
+        &self,
+        conn: &mut SqliteConnection,
+        order: Order,
+    ) -> Result<Order, RepoError> {
+        let order = self.insert_order(conn, order).await?;
+        self.append_outbox(
+            conn,
+            OrderEvent::Created {
+                order: order.clone(),
+            },
         )
-        .fetch_all(&self.pool)
-        .await
-        .map_err(|e| RepoError::DbError(e.to_string()))?;
+        .await?;
+        Ok(order)
+    }
 
-        rows.into_iter()
-            .map(|r| r.into_order())
-            .collect::<Result<Vec<_>, _>>()
+    /// Store every mutable field of `order` and return it with its order
+    /// number; `None` when it doesn't exist. With `read`, only while the row
+    /// still has its status and `updated_at`; a conflict otherwise. The
+    /// outbox records the change as `kind`.
+    async fn update_in(
+        &self,
+        conn: &mut SqliteConnection,
+        mut order: Order,
+        read: Option<&Order>,
+        kind: ChangeKind,
+    ) -> Result<Option<Order>, RepoError> {
+        let stored = self.at_rest(&order)?;
+        let row = OrderRow::new(&order, self.email_index(Some(&order.email)))?;
+        let captured_cents = order.captured.map(|m| m.amount_minor());
+        let read_status = read.map(|r| format!("{:?}", r.status));
+        let read_at = read.map(|r| r.updated_at.to_rfc3339());
+        let updated = sqlx::query_scalar!(
+            "UPDATE orders SET customer_name = ?, email = ?, total_cents = ?, currency = ?, subtotal_cents = ?, discount_cents = ?, tax_cents = ?, shipping_cents = ?, status = ?, updated_at = ?, pricing_json = ?, discount_json = ?, payment_id = ?, captured_cents = ?, cancel_reason = ?, cancelled_at = ?, shipping_address_json = ?, billing_address_json = ?, shipping_country = ?, email_index = ?, metadata_json = ?, updated_by = ?
+             WHERE id = ? AND tenant_id = ?
+               AND status = COALESCE(?, status) AND updated_at = COALESCE(?, updated_at)
+             RETURNING order_seq",
+            stored.customer_name,
+            stored.email,
+            row.total_cents,
+            row.currency,
+            order.charges.subtotal_cents,
+            order.charges.discount_cents,
+            order.charges.tax_cents,
+            order.charges.shipping_cents,
+            row.status,
+            row.updated_at,
+            row.pricing_json,
+            row.discount_json,
+            order.payment_id,
+            captured_cents,
+            row.cancel_reason,
+            row.cancelled_at,
+            row.shipping_address_json,
+            row.billing_address_json,
+            row.shipping_country,
+            row.email_index,
+            row.metadata_json,
+            row.updated_by,
+            row.id,
+            row.tenant_id,
+            read_status,
+            read_at,
+        )
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(sqlx_error)?;
+        let Some(seq) = updated else {
+            if read.is_some()
+                && load_order(conn, &order.tenant_id, order.id)
+                    .await?
+                    .is_some()
+            {
+                return Err(RepoError::Conflict(format!(
+                    "order {} was modified",
+                    order.id
+                )));
+            }
+            return Ok(None);
+        };
+        order.order_number = seq.map(|seq| OrderNumber::new(order.created_at.year(), seq as u64));
+        replace_items(conn, &order).await?;
+        self.append_outbox(conn, kind.event(order.clone())).await?;
+        Ok(Some(order))
     }
 
-    async fn update_status(
+    async fn update_status_in(
         &self,
+        conn: &mut SqliteConnection,
+        tenant: &TenantId,
         id: Uuid,
         status: OrderStatus,
     ) -> Result<Option<Order>, RepoError> {
-        let status_s = format!("{:?}", status);
-        let updated = sqlx::query("UPDATE orders SET status = ?, updated_at = ? WHERE id = ?")
-            .bind(status_s)
-            .bind(Utc::now().to_rfc3339())
-            .bind(id.to_string())
-            .execute(&self.pool)
-            .await
-            .map_err(|e| RepoError::DbError(e.to_string()))?;
+        let status = format!("{:?}", status);
+        let updated_at = Utc::now().to_rfc3339();
+        let id_text = id.to_string();
+        let tenant_id = tenant.as_str();
+        let updated = sqlx::query!(
+            "UPDATE orders SET status = ?, updated_at = ? WHERE id = ? AND tenant_id = ?",
+            status,
+            updated_at,
+            id_text,
+            tenant_id,
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(sqlx_error)?;
         if updated.rows_affected() == 0 {
             return Ok(None);
         }
-        self.get(id).await
+        let order = load_order(conn, tenant, id)
+            .await?
+            .map(|order| self.opened(order))
+            .transpose()?;
+        if let Some(order) = &order {
+            self.append_outbox(
+                conn,
+                OrderEvent::Updated {
+                    order: order.clone(),
+                },
+            )
+            .await?;
+        }
+        Ok(order)
     }
 
-    async fn delete(&self, id: Uuid) -> Result<bool, RepoError> {
-        let res = sqlx::query("DELETE FROM orders WHERE id = ?")
-            .bind(id.to_string())
-            .execute(&self.pool)
+    /// Lines of the given orders, keyed by order id, in their original order.
+    async fn load_items(
+        &self,
+        order_ids: &[&str],
+    ) -> Result<HashMap<String, Vec<DbOrderItem>>, RepoError> {
+        let mut items: HashMap<String, Vec<DbOrderItem>> = HashMap::new();
+        // Well under sqlite's limit on bound parameters.
+        for chunk in order_ids.chunks(500) {
+            let placeholders = vec!["?"; chunk.len()].join(", ");
+            let sql = format!(
+                "SELECT {ITEM_COLUMNS} FROM order_items WHERE order_id IN ({placeholders})
+                 ORDER BY order_id, position"
+            );
+            let mut query = sqlx::query_as::<_, DbOrderItem>(&sql);
+            for id in chunk {
+                query = query.bind(*id);
+            }
+            let rows = query.fetch_all(&self.pool).await.map_err(sqlx_error)?;
+            for row in rows {
+                items.entry(row.order_id.clone()).or_default().push(row);
+            }
+        }
+        Ok(items)
+    }
+
+    /// The orders of `rows`, with their lines loaded in one batch.
+    async fn with_items(&self, rows: Vec<DbOrder>) -> Result<Vec<Order>, RepoError> {
+        let ids: Vec<&str> = rows.iter().map(|r| r.id.as_str()).collect();
+        let mut items = self.load_items(&ids).await?;
+        rows.into_iter()
+            .map(|r| {
+                let lines = items.remove(&r.id).unwrap_or_default();
+                r.into_order(lines).and_then(|order| self.opened(order))
+            })
+            .collect()
+    }
+}
+
+/// Databases created before migrations were tracked already have (part of)
+/// the schema but no `_sqlx_migrations` rows. Finish the old startup DDL and
+/// record those migrations as applied so the migrator doesn't rerun them.
+async fn adopt_untracked_schema(pool: &SqlitePool) -> anyhow::Result<()> {
+    if table_exists(pool, "_sqlx_migrations").await? || !table_exists(pool, "orders").await? {
+        return Ok(());
+    }
+    let mut conn = pool.acquire().await?;
+    let ddl = include_str!("../migrations/0001_create_orders.sql");
+    sqlx::query(ddl).execute(&mut *conn).await?;
+    if !column_exists(pool, "orders", "pricing_json").await? {
+        let ddl = include_str!("../migrations/0002_add_pricing_snapshot.sql");
+        sqlx::query(ddl).execute(&mut *conn).await?;
+    }
+    let ddl = include_str!("../migrations/0003_create_api_keys.sql");
+    sqlx::query(ddl).execute(&mut *conn).await?;
+    if !column_exists(pool, "api_keys", "role").await? {
+        let ddl = include_str!("../migrations/0004_add_api_key_role.sql");
+        sqlx::query(ddl).execute(&mut *conn).await?;
+    }
+    if !column_exists(pool, "orders", "tenant_id").await? {
+        let ddl = include_str!("../migrations/0005_add_order_tenant.sql");
+        sqlx::query(ddl).execute(&mut *conn).await?;
+    }
+    let ddl = include_str!("../migrations/0006_index_order_tenant.sql");
+    sqlx::query(ddl).execute(&mut *conn).await?;
+
+    conn.ensure_migrations_table().await?;
+    for m in MIGRATOR
+        .iter()
+        .filter(|m| m.version <= LAST_UNTRACKED_MIGRATION)
+    {
+        sqlx::query(
+            "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time)
+             VALUES (?, ?, TRUE, ?, 0)",
+        )
+        .bind(m.version)
+        .bind(&*m.description)
+        .bind(&*m.checksum)
+        .execute(&mut *conn)
+        .await?;
+    }
+    tracing::info!("adopted untracked schema at version {LAST_UNTRACKED_MIGRATION}");
+    Ok(())
+}
+
+async fn table_exists(pool: &SqlitePool, name: &str) -> anyhow::Result<bool> {
+    let row: Option<(i64,)> =
+        sqlx::query_as("SELECT 1 FROM sqlite_master WHERE type IN ('table', 'view') AND name = ?")
+            .bind(name)
+            .fetch_optional(pool)
+            .await?;
+    Ok(row.is_some())
+}
+
+async fn column_exists(pool: &SqlitePool, table: &str, column: &str) -> anyhow::Result<bool> {
+    let names: Vec<(String,)> =
+        sqlx::query_as(&format!("SELECT name FROM pragma_table_info('{table}')"))
+            .fetch_all(pool)
+            .await?;
+    Ok(names.iter().any(|(n,)| n == column))
+}
+
+/// Append `filter`'s page to `query`. A negative limit means none.
+/// Map a sqlx error to a [`RepoError`] by what the caller can do about it.
+/// A busy or locked database, no free connection in the pool, or a broken
+/// connection are [transient](RepoError::Transient); a unique key that is
+/// already taken is a [conflict](RepoError::Conflict).
+fn sqlx_error(e: sqlx::Error) -> RepoError {
+    const SQLITE_BUSY: i32 = 5;
+    const SQLITE_LOCKED: i32 = 6;
+    match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => {
+            RepoError::Conflict(db.message().to_string())
+        }
+        // Extended result codes keep the primary code in the low byte.
+        sqlx::Error::Database(db)
+            if db
+                .code()
+                .and_then(|code| code.parse::<i32>().ok())
+                .is_some_and(|code| matches!(code & 0xff, SQLITE_BUSY | SQLITE_LOCKED)) =>
+        {
+            RepoError::transient(e)
+        }
+        sqlx::Error::PoolTimedOut | sqlx::Error::Io(_) | sqlx::Error::WorkerCrashed => {
+            RepoError::transient(e)
+        }
+        sqlx::Error::RowNotFound => RepoError::NotFound(e.to_string()),
+        sqlx::Error::ColumnDecode { .. } | sqlx::Error::Decode(_) => RepoError::serialization(e),
+        _ => RepoError::backend(e),
+    }
+}
+
+fn push_page(query: &mut QueryBuilder<'static, Sqlite>, filter: &OrderFilter) {
+    let limit = filter
+        .limit
+        .map_or(-1, |l| i64::try_from(l).unwrap_or(i64::MAX));
+    let offset = i64::try_from(filter.offset.unwrap_or(0)).unwrap_or(i64::MAX);
+    query
+        .push(" LIMIT ")
+        .push_bind(limit)
+        .push(" OFFSET ")
+        .push_bind(offset);
+}
+
+async fn insert_items(
+    conn: &mut SqliteConnection,
+    order_id: &str,
+    items: &[OrderItem],
+) -> Result<(), RepoError> {
+    for (position, item) in items.iter().enumerate() {
+        let position = position as i64;
+        let qty = i64::from(item.qty);
+        let unit_price_cents = item.unit_price.amount_minor();
+        let currency = item.unit_price.currency().as_str().to_owned();
+        let weight_grams = i64::from(item.weight_grams);
+        let metadata_json = metadata_json(&item.metadata)?;
+        sqlx::query!(
+            "INSERT INTO order_items (order_id, position, name, qty, unit_price_cents, currency, weight_grams, sku, description, metadata_json, discount_cents)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            order_id,
+            position,
+            item.name,
+            qty,
+            unit_price_cents,
+            currency,
+            weight_grams,
+            item.sku,
+            item.description,
+            metadata_json,
+            item.discount_cents,
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(sqlx_error)?;
+    }
+    Ok(())
+}
+
+/// Order `id` as seen inside the transaction on `conn`.
+async fn load_order(
+    conn: &mut SqliteConnection,
+    tenant: &TenantId,
+    id: Uuid,
+) -> Result<Option<Order>, RepoError> {
+    let id = id.to_string();
+    let tenant_id = tenant.as_str();
+    let row = sqlx::query_as!(
+        DbOrder,
+        r#"SELECT id AS "id!", tenant_id, customer_name, email, total_cents, currency, subtotal_cents, discount_cents, tax_cents, shipping_cents, status, created_at, updated_at, pricing_json, discount_json, payment_id, captured_cents, cancel_reason, cancelled_at, shipping_address_json, billing_address_json, order_seq, metadata_json, created_by, updated_by
+         FROM orders WHERE id = ? AND tenant_id = ?"#,
+        id,
+        tenant_id,
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(sqlx_error)?;
+    let Some(row) = row else {
+        return Ok(None);
+    };
+    let items = sqlx::query_as!(
+        DbOrderItem,
+        "SELECT order_id, name, qty, unit_price_cents, currency, weight_grams, sku, description, metadata_json, discount_cents
+         FROM order_items WHERE order_id = ? ORDER BY position",
+        row.id,
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(sqlx_error)?;
+    row.into_order(items).map(Some)
+}
+
+async fn insert_transition(
+    conn: &mut SqliteConnection,
+    tenant: &TenantId,
+    id: Uuid,
+    entry: OrderHistoryEntry,
+) -> Result<(), RepoError> {
+    let tenant_id = tenant.as_str();
+    let order_id = id.to_string();
+    let from_status = entry.from.map(|s| format!("{s:?}"));
+    let to_status = format!("{:?}", entry.to);
+    let at = entry.at.to_rfc3339();
+    sqlx::query!(
+        "INSERT INTO status_history (tenant_id, order_id, from_status, to_status, at, actor, note)
+         VALUES (?, ?, ?, ?, ?, ?, ?)",
+        tenant_id,
+        order_id,
+        from_status,
+        to_status,
+        at,
+        entry.actor,
+        entry.note,
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(sqlx_error)?;
+    Ok(())
+}
+
+async fn insert_fulfillment(
+    conn: &mut SqliteConnection,
+    tenant: &TenantId,
+    id: Uuid,
+    fulfillment: Fulfillment,
+) -> Result<(), RepoError> {
+    let fulfillment_id = fulfillment.id.to_string();
+    let tenant_id = tenant.as_str();
+    let order_id = id.to_string();
+    let shipped_at = fulfillment.shipped_at.to_rfc3339();
+    sqlx::query!(
+        "INSERT INTO order_fulfillments (id, tenant_id, order_id, carrier, tracking_number, shipped_at)
+         VALUES (?, ?, ?, ?, ?, ?)",
+        fulfillment_id,
+        tenant_id,
+        order_id,
+        fulfillment.carrier,
+        fulfillment.tracking_number,
+        shipped_at,
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(sqlx_error)?;
+    for item in &fulfillment.items {
+        let position = item.position as i64;
+        sqlx::query!(
+            "INSERT INTO order_fulfillment_items (fulfillment_id, position, qty) VALUES (?, ?, ?)",
+            fulfillment_id,
+            position,
+            item.qty,
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(sqlx_error)?;
+    }
+    Ok(())
+}
+
+async fn replace_items(conn: &mut SqliteConnection, order: &Order) -> Result<(), RepoError> {
+    let order_id = order.id.to_string();
+    sqlx::query!("DELETE FROM order_items WHERE order_id = ?", order_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(sqlx_error)?;
+    insert_items(conn, &order_id, &order.items).await
+}
+
+/// The columns of `order` that its insert and update both write, encoded
+/// for storage. Bound by reference, as the checked query macros need.
+struct OrderRow<'a> {
+    id: String,
+    tenant_id: &'a str,
+    total_cents: i64,
+    currency: String,
+    status: String,
+    updated_at: String,
+    pricing_json: Option<String>,
+    discount_json: Option<String>,
+    cancel_reason: Option<&'a str>,
+    cancelled_at: Option<String>,
+    shipping_address_json: Option<String>,
+    billing_address_json: Option<String>,
+    shipping_country: Option<&'a str>,
+    email_index: Option<String>,
+    metadata_json: Option<String>,
+    updated_by: Option<String>,
+}
+
+impl<'a> OrderRow<'a> {
+    fn new(order: &'a Order, email_index: Option<String>) -> Result<Self, RepoError> {
+        Ok(Self {
+            id: order.id.to_string(),
+            tenant_id: order.tenant_id.as_str(),
+            total_cents: order.total.amount_minor(),
+            currency: order.total.currency().as_str().to_owned(),
+            status: format!("{:?}", order.status),
+            updated_at: order.updated_at.to_rfc3339(),
+            pricing_json: pricing_json(order)?,
+            discount_json: discount_json(order)?,
+            cancel_reason: order.cancellation.as_ref().map(|c| c.reason.as_str()),
+            cancelled_at: order
+                .cancellation
+                .as_ref()
+                .map(|c| c.cancelled_at.to_rfc3339()),
+            shipping_address_json: address_json(&order.shipping_address)?,
+            billing_address_json: address_json(&order.billing_address)?,
+            shipping_country: order.shipping_address.as_ref().map(|a| a.country.as_str()),
+            email_index,
+            metadata_json: metadata_json(&order.metadata)?,
+            updated_by: order.updated_by.as_ref().map(Actor::to_string),
+        })
+    }
+}
+
+fn metadata_json(metadata: &BTreeMap<String, String>) -> Result<Option<String>, RepoError> {
+    if metadata.is_empty() {
+        return Ok(None);
+    }
+    serde_json::to_string(metadata)
+        .map(Some)
+        .map_err(RepoError::serialization)
+}
+
+fn address_json(address: &Option<Address>) -> Result<Option<String>, RepoError> {
+    address
+        .as_ref()
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(RepoError::serialization)
+}
+
+fn pricing_json(order: &Order) -> Result<Option<String>, RepoError> {
+    order
+        .pricing
+        .as_ref()
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(RepoError::serialization)
+}
+
+fn discount_json(order: &Order) -> Result<Option<String>, RepoError> {
+    order
+        .discount
+        .as_ref()
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(RepoError::serialization)
+}
+
+/// Writes in one sqlite transaction, outbox events included.
+struct SqliteUnit<'a> {
+    repo: &'a SqliteRepo,
+    tx: Transaction<'static, Sqlite>,
+}
+
+#[async_trait]
+impl UnitOfWork for SqliteUnit<'_> {
+    async fn create(&mut self, order: Order) -> Result<Order, RepoError> {
+        self.repo.create_in(&mut self.tx, order).await
+    }
+
+    async fn update(&mut self, order: Order) -> Result<Option<Order>, RepoError> {
+        self.repo
+            .update_in(&mut self.tx, order, None, ChangeKind::Updated)
             .await
-            .map_err(|e| RepoError::DbError(e.to_string()))?;
-        Ok(res.rows_affected() > 0)
+    }
+
+    async fn update_from(
+        &mut self,
+        order: Order,
+        read: &Order,
+        kind: ChangeKind,
+    ) -> Result<Option<Order>, RepoError> {
+        self.repo
+            .update_in(&mut self.tx, order, Some(read), kind)
+            .await
+    }
+
+    async fn update_status(
+        &mut self,
+        tenant: &TenantId,
+        id: Uuid,
+        status: OrderStatus,
+    ) -> Result<Option<Order>, RepoError> {
+        self.repo
+            .update_status_in(&mut self.tx, tenant, id, status)
+            .await
+    }
+
+    async fn record_transition(
+        &mut self,
+        tenant: &TenantId,
+        id: Uuid,
+        entry: OrderHistoryEntry,
+    ) -> Result<(), RepoError> {
+        insert_transition(&mut self.tx, tenant, id, entry).await
+    }
+
+    async fn record_fulfillment(
+        &mut self,
+        tenant: &TenantId,
+        id: Uuid,
+        fulfillment: Fulfillment,
+    ) -> Result<(), RepoError> {
+        insert_fulfillment(&mut self.tx, tenant, id, fulfillment).await
+    }
+
+    async fn commit(self: Box<Self>) -> Result<(), RepoError> {
+        self.tx.commit().await.map_err(sqlx_error)
+    }
+
+    async fn rollback(self: Box<Self>) -> Result<(), RepoError> {
+        self.tx.rollback().await.map_err(sqlx_error)
+    }
+}
+
+#[async_trait]
+impl OrderRepository for SqliteRepo {
+    async fn begin(&self) -> Result<Box<dyn UnitOfWork + '_>, RepoError> {
+        let tx = self.pool.begin().await.map_err(sqlx_error)?;
+        Ok(Box::new(SqliteUnit { repo: self, tx }))
+    }
+
+    async fn create(&self, order: Order) -> Result<Order, RepoError> {
+        let mut tx = self.pool.begin().await.map_err(sqlx_error)?;
+        let order = self.create_in(&mut tx, order).await?;
+        tx.commit().await.map_err(sqlx_error)?;
+        Ok(order)
+    }
+
+    async fn create_many(&self, orders: Vec<Order>) -> Result<(), RepoError> {
+        let mut tx = self.pool.begin().await.map_err(sqlx_error)?;
+        for order in orders {
+            self.create_in(&mut tx, order).await?;
+        }
+        tx.commit().await.map_err(sqlx_error)
+    }
+
+    async fn get(&self, tenant: &TenantId, id: Uuid) -> Result<Option<Order>, RepoError> {
+        let id = id.to_string();
+        let tenant_id = tenant.as_str();
+        let row = sqlx::query_as!(
+            DbOrder,
+            r#"SELECT id AS "id!", tenant_id, customer_name, email, total_cents, currency, subtotal_cents, discount_cents, tax_cents, shipping_cents, status, created_at, updated_at, pricing_json, discount_json, payment_id, captured_cents, cancel_reason, cancelled_at, shipping_address_json, billing_address_json, order_seq, metadata_json, created_by, updated_by
+             FROM orders WHERE id = ? AND tenant_id = ?"#,
+            id,
+            tenant_id,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        Ok(self.with_items(row.into_iter().collect()).await?.pop())
+    }
+
+    async fn get_by_number(
+        &self,
+        tenant: &TenantId,
+        number: &OrderNumber,
+    ) -> Result<Option<Order>, RepoError> {
+        let Ok(seq) = i64::try_from(number.seq()) else {
+            return Ok(None);
+        };
+        let tenant_id = tenant.as_str();
+        let row = sqlx::query_as!(
+            DbOrder,
+            r#"SELECT id AS "id!", tenant_id, customer_name, email, total_cents, currency, subtotal_cents, discount_cents, tax_cents, shipping_cents, status, created_at, updated_at, pricing_json, discount_json, payment_id, captured_cents, cancel_reason, cancelled_at, shipping_address_json, billing_address_json, order_seq, metadata_json, created_by, updated_by
+             FROM orders WHERE order_seq = ? AND tenant_id = ?"#,
+            seq,
+            tenant_id,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        // The sequence alone is unique; the year has to match too.
+        Ok(self
+            .with_items(row.into_iter().collect())
+            .await?
+            .pop()
+            .filter(|o| o.order_number.as_ref() == Some(number)))
+    }
+
+    async fn list(&self, tenant: &TenantId) -> Result<Vec<Order>, RepoError> {
+        let tenant_id = tenant.as_str();
+        let rows = sqlx::query_as!(
+            DbOrder,
+            r#"SELECT id AS "id!", tenant_id, customer_name, email, total_cents, currency, subtotal_cents, discount_cents, tax_cents, shipping_cents, status, created_at, updated_at, pricing_json, discount_json, payment_id, captured_cents, cancel_reason, cancelled_at, shipping_address_json, billing_address_json, order_seq, metadata_json, created_by, updated_by
+             FROM orders WHERE tenant_id = ?"#,
+            tenant_id,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        self.with_items(rows).await
+    }
+
+    async fn list_filtered(
+        &self,
+        tenant: &TenantId,
+        filter: &OrderFilter,
+    ) -> Result<Vec<Order>, RepoError> {
+        let sort = filter.sorting().map_err(RepoError::backend)?;
+        // Column names come from the `SortField` allow-list, never the caller.
+        let order_by = match sort {
+            Some(sort) => format!(
+                " ORDER BY {} {}, id ASC",
+                sort.field.as_str(),
+                sort.order.as_sql()
+            ),
+            None => String::new(),
+        };
+        let mut query = self.filtered(
+            &format!("SELECT {ORDER_COLUMNS} FROM orders"),
+            tenant,
+            filter,
+        );
+        query.push(order_by);
+        push_page(&mut query, filter);
+        let rows: Vec<DbOrder> = query
+            .build_query_as()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(sqlx_error)?;
+        self.with_items(rows).await
+    }
+
+    async fn stale_pending(
+        &self,
+        before: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<Order>, RepoError> {
+        let before = before.to_rfc3339();
+        let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+        let rows = sqlx::query_as!(
+            DbOrder,
+            r#"SELECT id AS "id!", tenant_id, customer_name, email, total_cents, currency, subtotal_cents, discount_cents, tax_cents, shipping_cents, status, created_at, updated_at, pricing_json, discount_json, payment_id, captured_cents, cancel_reason, cancelled_at, shipping_address_json, billing_address_json, order_seq, metadata_json, created_by, updated_by
+             FROM orders WHERE status = 'Pending' AND created_at < ?
+             ORDER BY created_at ASC, id ASC LIMIT ?"#,
+            before,
+            limit,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        self.with_items(rows).await
+    }
+
+    async fn scan(&self, after: Option<Uuid>, limit: usize) -> Result<Vec<Order>, RepoError> {
+        // Every id sorts after the empty string.
+        let after = after.map(|id| id.to_string()).unwrap_or_default();
+        let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+        let rows = sqlx::query_as!(
+            DbOrder,
+            r#"SELECT id AS "id!", tenant_id, customer_name, email, total_cents, currency, subtotal_cents, discount_cents, tax_cents, shipping_cents, status, created_at, updated_at, pricing_json, discount_json, payment_id, captured_cents, cancel_reason, cancelled_at, shipping_address_json, billing_address_json, order_seq, metadata_json, created_by, updated_by
+             FROM orders WHERE id > ? ORDER BY id LIMIT ?"#,
+            after,
+            limit,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        self.with_items(rows).await
+    }
+
+    async fn exists(&self, tenant: &TenantId, id: Uuid) -> Result<bool, RepoError> {
+        let id = id.to_string();
+        let tenant_id = tenant.as_str();
+        let row = sqlx::query_scalar!(
+            "SELECT 1 FROM orders WHERE id = ? AND tenant_id = ?",
+            id,
+            tenant_id,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        Ok(row.is_some())
+    }
+
+    async fn count(&self, tenant: &TenantId, filter: &OrderFilter) -> Result<usize, RepoError> {
+        let (count,): (i64,) = self
+            .filtered("SELECT COUNT(*) FROM orders", tenant, filter)
+            .build_query_as()
+            .fetch_one(&self.pool)
+            .await
+            .map_err(sqlx_error)?;
+        Ok(count as usize)
+    }
+
+    async fn aggregate(
+        &self,
+        tenant: &TenantId,
+        range: &StatsRange,
+    ) -> Result<OrderStats, RepoError> {
+        // `created_at` is RFC 3339 in UTC, so its first ten characters are the
+        // day, and a bare day sorts before every instant in it. Comparing the
+        // column itself keeps `idx_orders_tenant_created` usable.
+        let from = range.from.map(|d| d.to_string());
+        // The day after `to`; none after the last representable day.
+        let until = range.to.and_then(|d| d.succ_opt()).map(|d| d.to_string());
+        let after = range.created_after.map(|t| t.to_rfc3339());
+        let before = range.created_before.map(|t| t.to_rfc3339());
+        let scope = |select: &str| {
+            let mut query = QueryBuilder::new(select);
+            query
+                .push(" FROM orders WHERE tenant_id = ")
+                .push_bind(tenant.as_str().to_owned());
+            for lower in [&from, &after].into_iter().flatten() {
+                query.push(" AND created_at >= ").push_bind(lower.clone());
+            }
+            for upper in [&until, &before].into_iter().flatten() {
+                query.push(" AND created_at < ").push_bind(upper.clone());
+            }
+            query
+        };
+        let db = sqlx_error;
+
+        let mut query = scope("SELECT status, COUNT(*)");
+        query.push(" GROUP BY status");
+        let statuses: Vec<(String, i64)> = query
+            .build_query_as()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(db)?;
+        let mut query = scope("SELECT currency, COUNT(*), SUM(total_cents)");
+        query.push(" AND status != 'Cancelled' GROUP BY currency");
+        let revenue: Vec<(String, i64, i64)> = query
+            .build_query_as()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(db)?;
+        let mut query = scope("SELECT substr(created_at, 1, 10) AS day, COUNT(*)");
+        query.push(" GROUP BY day");
+        let days: Vec<(String, i64)> = query
+            .build_query_as()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(db)?;
+
+        let statuses = statuses
+            .into_iter()
+            .map(|(status, n)| {
+                OrderStatus::parse(&status)
+                    .map(|s| (s, n as u64))
+                    .ok_or_else(|| RepoError::serialization(format!("unknown status `{status}`")))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let revenue = revenue
+            .into_iter()
+            .map(|(currency, n, total)| {
+                Currency::parse(&currency)
+                    .map(|c| (c, n as u64, total))
+                    .map_err(RepoError::serialization)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let days = days
+            .into_iter()
+            .map(|(day, n)| {
+                day.parse()
+                    .map(|d| (d, n as u64))
+                    .map_err(|e| RepoError::serialization(format!("day `{day}`: {e}")))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(OrderStats::assemble(*range, statuses, revenue, days))
+    }
+
+    async fn update_status(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        status: OrderStatus,
+    ) -> Result<Option<Order>, RepoError> {
+        let mut tx = self.pool.begin().await.map_err(sqlx_error)?;
+        let order = self.update_status_in(&mut tx, tenant, id, status).await?;
+        if order.is_some() {
+            tx.commit().await.map_err(sqlx_error)?;
+        }
+        Ok(order)
+    }
+
+    async fn update(&self, order: Order) -> Result<Option<Order>, RepoError> {
+        let mut tx = self.pool.begin().await.map_err(sqlx_error)?;
+        let Some(order) = self
+            .update_in(&mut tx, order, None, ChangeKind::Updated)
+            .await?
+        else {
+            return Ok(None);
+        };
+        tx.commit().await.map_err(sqlx_error)?;
+        Ok(Some(order))
+    }
+
+    async fn update_items(
+        &self,
+        order: &Order,
+        read_at: DateTime<Utc>,
+    ) -> Result<Option<Order>, RepoError> {
+        let mut tx = self.pool.begin().await.map_err(sqlx_error)?;
+        let row = OrderRow::new(order, None)?;
+        let read_at = read_at.to_rfc3339();
+        let updated = sqlx::query!(
+            "UPDATE orders SET total_cents = ?, currency = ?, subtotal_cents = ?, discount_cents = ?, tax_cents = ?, shipping_cents = ?, updated_at = ?, discount_json = ?, updated_by = ?
+             WHERE id = ? AND tenant_id = ? AND status = 'Pending' AND updated_at = ?",
+            row.total_cents,
+            row.currency,
+            order.charges.subtotal_cents,
+            order.charges.discount_cents,
+            order.charges.tax_cents,
+            order.charges.shipping_cents,
+            row.updated_at,
+            row.discount_json,
+            row.updated_by,
+            row.id,
+            row.tenant_id,
+            read_at,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(sqlx_error)?;
+        if updated.rows_affected() == 0 {
+            drop(tx);
+            if self.exists(&order.tenant_id, order.id).await? {
+                return Err(RepoError::Conflict(format!(
+                    "order {} was modified",
+                    order.id
+                )));
+            }
+            return Ok(None);
+        }
+        replace_items(&mut tx, order).await?;
+        self.append_outbox(
+            &mut tx,
+            OrderEvent::Updated {
+                order: order.clone(),
+            },
+        )
+        .await?;
+        tx.commit().await.map_err(sqlx_error)?;
+        Ok(Some(order.clone()))
+    }
+
+    async fn delete(&self, tenant: &TenantId, id: Uuid) -> Result<bool, RepoError> {
+        let mut tx = self.pool.begin().await.map_err(sqlx_error)?;
+        // Its `order_items` and fulfillment rows go with it (`ON DELETE
+        // CASCADE`).
+        let order_id = id.to_string();
+        let tenant_id = tenant.as_str();
+        let res = sqlx::query!(
+            "DELETE FROM orders WHERE id = ? AND tenant_id = ?",
+            order_id,
+            tenant_id,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(sqlx_error)?;
+        sqlx::query!(
+            "DELETE FROM status_history WHERE order_id = ? AND tenant_id = ?",
+            order_id,
+            tenant_id,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(sqlx_error)?;
+        if res.rows_affected() > 0 {
+            self.append_outbox(
+                &mut tx,
+                OrderEvent::Deleted {
+                    id,
+                    tenant_id: tenant.clone(),
+                },
+            )
+            .await?;
+        }
+        tx.commit().await.map_err(sqlx_error)?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn record_transition(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        entry: OrderHistoryEntry,
+    ) -> Result<(), RepoError> {
+        let mut conn = self.pool.acquire().await.map_err(sqlx_error)?;
+        insert_transition(&mut conn, tenant, id, entry).await
+    }
+
+    async fn status_history(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+    ) -> Result<Vec<OrderHistoryEntry>, RepoError> {
+        let tenant_id = tenant.as_str();
+        let order_id = id.to_string();
+        let rows = sqlx::query_as!(
+            DbHistoryEntry,
+            "SELECT from_status, to_status, at, actor, note FROM status_history
+             WHERE tenant_id = ? AND order_id = ? ORDER BY rowid",
+            tenant_id,
+            order_id,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        rows.into_iter().map(DbHistoryEntry::into_entry).collect()
+    }
+
+    async fn record_fulfillment(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        fulfillment: Fulfillment,
+    ) -> Result<(), RepoError> {
+        let mut tx = self.pool.begin().await.map_err(sqlx_error)?;
+        insert_fulfillment(&mut tx, tenant, id, fulfillment).await?;
+        tx.commit().await.map_err(sqlx_error)
+    }
+
+    async fn fulfillments(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+    ) -> Result<Vec<Fulfillment>, RepoError> {
+        let tenant_id = tenant.as_str();
+        let order_id = id.to_string();
+        let rows = sqlx::query_as!(
+            DbFulfillment,
+            "SELECT id, carrier, tracking_number, shipped_at FROM order_fulfillments
+             WHERE tenant_id = ? AND order_id = ? ORDER BY rowid",
+            tenant_id,
+            order_id,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        let items = sqlx::query_as!(
+            DbFulfilledItem,
+            "SELECT i.fulfillment_id, i.position, i.qty FROM order_fulfillment_items i
+             JOIN order_fulfillments f ON f.id = i.fulfillment_id
+             WHERE f.tenant_id = ? AND f.order_id = ? ORDER BY i.position",
+            tenant_id,
+            order_id,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        let mut by_fulfillment: HashMap<String, Vec<FulfilledItem>> = HashMap::new();
+        for item in items {
+            by_fulfillment
+                .entry(item.fulfillment_id)
+                .or_default()
+                .push(FulfilledItem {
+                    position: usize::try_from(item.position).map_err(RepoError::serialization)?,
+                    qty: u32::try_from(item.qty).map_err(RepoError::serialization)?,
+                });
+        }
+        rows.into_iter()
+            .map(|row| {
+                let items = by_fulfillment.remove(&row.id).unwrap_or_default();
+                row.into_fulfillment(items)
+            })
+            .collect()
+    }
+
+    async fn check_integrity(
+        &self,
+        mapping: &StatusMapping,
+        fix: bool,
+    ) -> Result<IntegrityReport, RepoError> {
+        let rows = sqlx::query_as!(
+            DbOrder,
+            r#"SELECT id AS "id!", tenant_id, customer_name, email, total_cents, currency, subtotal_cents, discount_cents, tax_cents, shipping_cents, status, created_at, updated_at, pricing_json, discount_json, payment_id, captured_cents, cancel_reason, cancelled_at, shipping_address_json, billing_address_json, order_seq, metadata_json, created_by, updated_by
+             FROM orders"#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        let ids: Vec<&str> = rows.iter().map(|r| r.id.as_str()).collect();
+        let mut items = self.load_items(&ids).await?;
+
+        let mut report = IntegrityReport::default();
+        for row in rows {
+            report.checked += 1;
+            let order_id = row.id.clone();
+            let status = match OrderStatus::parse(&row.status) {
+                Some(status) => status,
+                None => match mapping.get(&row.status) {
+                    Some(mapped) if fix => {
+                        let status = format!("{:?}", mapped);
+                        sqlx::query!(
+                            "UPDATE orders SET status = ? WHERE id = ?",
+                            status,
+                            order_id,
+                        )
+                        .execute(&self.pool)
+                        .await
+                        .map_err(sqlx_error)?;
+                        report.fixed += 1;
+                        mapped.clone()
+                    }
+                    mapped_to => {
+                        report.issues.push(IntegrityIssue::UnknownStatus {
+                            order_id: order_id.clone(),
+                            value: row.status.clone(),
+                            mapped_to: mapped_to.cloned(),
+                        });
+                        // Still validate the rest of the row.
+                        OrderStatus::Pending
+                    }
+                },
+            };
+            let lines = items.remove(&order_id).unwrap_or_default();
+            if let Err(e) = row
+                .into_order_with(status, lines)
+                .and_then(|order| self.opened(order))
+            {
+                report.issues.push(IntegrityIssue::InvalidRow {
+                    order_id,
+                    reason: e.to_string(),
+                });
+            }
+        }
+        Ok(report)
+    }
+
+    async fn ping(&self) -> Result<(), RepoError> {
+        sqlx::query("SELECT 1")
+            .execute(&self.pool)
+            .await
+            .map(|_| ())
+            .map_err(sqlx_error)
+    }
+}
+
+#[async_trait]
+impl ApiKeyRepository for SqliteRepo {
+    async fn create_key(&self, key: ApiKey) -> Result<ApiKey, RepoError> {
+        let scopes = key
+            .scopes
+            .iter()
+            .map(Scope::as_str)
+            .collect::<Vec<_>>()
+            .join(",");
+        let id = key.id.to_string();
+        let role = key.role.as_ref().map(Role::as_str);
+        let tenant_id = key.tenant.as_str();
+        let created_at = key.created_at.to_rfc3339();
+        let revoked_at = key.revoked_at.map(|t| t.to_rfc3339());
+        sqlx::query!(
+            "INSERT INTO api_keys (id, name, key_hash, scopes, role, tenant_id, created_at, revoked_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            id,
+            key.name,
+            key.key_hash,
+            scopes,
+            role,
+            tenant_id,
+            created_at,
+            revoked_at,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        Ok(key)
+    }
+
+    async fn find_key_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>, RepoError> {
+        let row = sqlx::query_as!(
+            DbApiKey,
+            r#"SELECT id AS "id!", name, key_hash, scopes, role, tenant_id, created_at, revoked_at FROM api_keys WHERE key_hash = ?"#,
+            key_hash,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        row.map(|r| r.into_key()).transpose()
+    }
+
+    async fn find_key(&self, id: Uuid) -> Result<Option<ApiKey>, RepoError> {
+        let id = id.to_string();
+        let row = sqlx::query_as!(
+            DbApiKey,
+            r#"SELECT id AS "id!", name, key_hash, scopes, role, tenant_id, created_at, revoked_at FROM api_keys WHERE id = ?"#,
+            id,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        row.map(|r| r.into_key()).transpose()
+    }
+
+    async fn list_keys(&self) -> Result<Vec<ApiKey>, RepoError> {
+        let rows = sqlx::query_as!(
+            DbApiKey,
+            r#"SELECT id AS "id!", name, key_hash, scopes, role, tenant_id, created_at, revoked_at FROM api_keys ORDER BY created_at"#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        rows.into_iter().map(|r| r.into_key()).collect()
+    }
+
+    async fn revoke_key(&self, id: Uuid) -> Result<bool, RepoError> {
+        let now = Utc::now().to_rfc3339();
+        let id = id.to_string();
+        let res = sqlx::query!(
+            "UPDATE api_keys SET revoked_at = COALESCE(revoked_at, ?) WHERE id = ?",
+            now,
+            id,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        Ok(res.rows_affected() > 0)
+    }
+}
+
+#[async_trait]
+impl DiscountRepository for SqliteRepo {
+    async fn create_discount(&self, discount: Discount) -> Result<bool, RepoError> {
+        let kind_json = serde_json::to_string(&discount.kind).map_err(RepoError::serialization)?;
+        let min_order_json = discount
+            .min_order
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(RepoError::serialization)?;
+        let tenant_id = discount.tenant_id.as_str();
+        let expires_at = discount.expires_at.map(|t| t.to_rfc3339());
+        let max_uses = discount.max_uses.map(i64::from);
+        let uses = i64::from(discount.uses);
+        let created_at = discount.created_at.to_rfc3339();
+        let res = sqlx::query!(
+            "INSERT INTO discounts (tenant_id, code, kind_json, min_order_json, expires_at, max_uses, uses, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+             ON CONFLICT (tenant_id, code) DO NOTHING",
+            tenant_id,
+            discount.code,
+            kind_json,
+            min_order_json,
+            expires_at,
+            max_uses,
+            uses,
+            created_at,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn get_discount(
+        &self,
+        tenant: &TenantId,
+        code: &str,
+    ) -> Result<Option<Discount>, RepoError> {
+        let tenant_id = tenant.as_str();
+        let row = sqlx::query_as!(
+            DbDiscount,
+            "SELECT tenant_id, code, kind_json, min_order_json, expires_at, max_uses, uses, created_at
+             FROM discounts WHERE tenant_id = ? AND code = ?",
+            tenant_id,
+            code,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        row.map(DbDiscount::into_discount).transpose()
+    }
+
+    async fn list_discounts(&self, tenant: &TenantId) -> Result<Vec<Discount>, RepoError> {
+        let tenant_id = tenant.as_str();
+        let rows = sqlx::query_as!(
+            DbDiscount,
+            "SELECT tenant_id, code, kind_json, min_order_json, expires_at, max_uses, uses, created_at
+             FROM discounts WHERE tenant_id = ? ORDER BY code",
+            tenant_id,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        rows.into_iter().map(DbDiscount::into_discount).collect()
+    }
+
+    async fn redeem_discount(&self, tenant: &TenantId, code: &str) -> Result<bool, RepoError> {
+        let tenant_id = tenant.as_str();
+        let res = sqlx::query!(
+            "UPDATE discounts SET uses = uses + 1
+             WHERE tenant_id = ? AND code = ? AND (max_uses IS NULL OR uses < max_uses)",
+            tenant_id,
+            code,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn release_discount(&self, tenant: &TenantId, code: &str) -> Result<(), RepoError> {
+        let tenant_id = tenant.as_str();
+        sqlx::query!(
+            "UPDATE discounts SET uses = uses - 1 WHERE tenant_id = ? AND code = ? AND uses > 0",
+            tenant_id,
+            code,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        Ok(())
+    }
+
+    async fn delete_discount(&self, tenant: &TenantId, code: &str) -> Result<bool, RepoError> {
+        let tenant_id = tenant.as_str();
+        let res = sqlx::query!(
+            "DELETE FROM discounts WHERE tenant_id = ? AND code = ?",
+            tenant_id,
+            code,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        Ok(res.rows_affected() > 0)
+    }
+}
+
+#[async_trait]
+impl AuditRepository for SqliteRepo {
+    async fn record_audit(&self, entry: AuditEntry) -> Result<(), RepoError> {
+        let json = |order: &Option<Order>| {
+            order
+                .as_ref()
+                .map(|order| {
+                    serde_json::to_string(&*self.at_rest(order)?).map_err(RepoError::serialization)
+                })
+                .transpose()
+        };
+        let id = entry.id.to_string();
+        let tenant_id = entry.tenant_id.as_str();
+        let order_id = entry.order_id.to_string();
+        let action = entry.action.as_str();
+        let at = entry.at.to_rfc3339();
+        let before_json = json(&entry.before)?;
+        let after_json = json(&entry.after)?;
+        let pricing_diff_json = entry
+            .pricing_diff
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(RepoError::serialization)?;
+        sqlx::query!(
+            "INSERT INTO audit_log (id, tenant_id, order_id, action, actor, at, before_json, after_json, reason, pricing_diff_json)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            id,
+            tenant_id,
+            order_id,
+            action,
+            entry.actor,
+            at,
+            before_json,
+            after_json,
+            entry.reason,
+            pricing_diff_json,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        Ok(())
+    }
+
+    async fn order_audit(
+        &self,
+        tenant: &TenantId,
+        order_id: Uuid,
+    ) -> Result<Vec<AuditEntry>, RepoError> {
+        let tenant_id = tenant.as_str();
+        let order_id = order_id.to_string();
+        let rows = sqlx::query_as!(
+            DbAuditEntry,
+            r#"SELECT id AS "id!", tenant_id, order_id, action, actor, at, before_json, after_json, reason, pricing_diff_json
+             FROM audit_log WHERE tenant_id = ? AND order_id = ?
+             ORDER BY at, rowid"#,
+            tenant_id,
+            order_id,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        rows.into_iter()
+            .map(|row| self.opened_entry(row.into_entry()?))
+            .collect()
+    }
+
+    async fn list_audit(
+        &self,
+        tenant: &TenantId,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<AuditEntry>, RepoError> {
+        let tenant_id = tenant.as_str();
+        let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+        let offset = i64::try_from(offset).unwrap_or(i64::MAX);
+        let rows = sqlx::query_as!(
+            DbAuditEntry,
+            r#"SELECT id AS "id!", tenant_id, order_id, action, actor, at, before_json, after_json, reason, pricing_diff_json
+             FROM audit_log WHERE tenant_id = ?
+             ORDER BY at DESC, rowid DESC LIMIT ? OFFSET ?"#,
+            tenant_id,
+            limit,
+            offset,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        rows.into_iter()
+            .map(|row| self.opened_entry(row.into_entry()?))
+            .collect()
+    }
+
+    async fn anonymize_audit(&self, tenant: &TenantId, order_id: Uuid) -> Result<u64, RepoError> {
+        let entries = self.order_audit(tenant, order_id).await?;
+        let json = |order: Option<Order>| {
+            order
+                .map(|mut order| {
+                    order.anonymize();
+                    serde_json::to_string(&*self.at_rest(&order)?).map_err(RepoError::serialization)
+                })
+                .transpose()
+        };
+        let mut tx = self.pool.begin().await.map_err(sqlx_error)?;
+        for entry in &entries {
+            let before_json = json(entry.before.clone())?;
+            let after_json = json(entry.after.clone())?;
+            let id = entry.id.to_string();
+            sqlx::query!(
+                "UPDATE audit_log SET before_json = ?, after_json = ? WHERE id = ?",
+                before_json,
+                after_json,
+                id,
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(sqlx_error)?;
+        }
+        tx.commit().await.map_err(sqlx_error)?;
+        Ok(entries.len() as u64)
+    }
+}
+
+impl SqliteRepo {
+    fn opened_entry(&self, mut entry: AuditEntry) -> Result<AuditEntry, RepoError> {
+        entry.before = entry.before.map(|o| self.opened(o)).transpose()?;
+        entry.after = entry.after.map(|o| self.opened(o)).transpose()?;
+        Ok(entry)
+    }
+}
+
+struct DbOutboxRecord {
+    seq: i64,
+    event_json: String,
+    recorded_at: String,
+}
+
+impl DbOutboxRecord {
+    fn into_record(self) -> Result<OutboxRecord, RepoError> {
+        let db = RepoError::serialization::<String>;
+        Ok(OutboxRecord {
+            seq: u64::try_from(self.seq).map_err(|e| db(e.to_string()))?,
+            recorded_at: DateTime::parse_from_rfc3339(&self.recorded_at)
+                .map(|d| d.with_timezone(&Utc))
+                .map_err(|e| db(e.to_string()))?,
+            event: serde_json::from_str(&self.event_json).map_err(|e| db(e.to_string()))?,
+        })
+    }
+}
+
+#[async_trait]
+impl OutboxStore for SqliteRepo {
+    async fn outbox_after(&self, after: u64, limit: usize) -> Result<Vec<OutboxRecord>, RepoError> {
+        let after = after as i64;
+        let limit = limit as i64;
+        let rows = sqlx::query_as!(
+            DbOutboxRecord,
+            "SELECT seq, event_json, recorded_at FROM outbox WHERE seq > ? ORDER BY seq LIMIT ?",
+            after,
+            limit,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        rows.into_iter()
+            .map(|row| {
+                let mut record = row.into_record()?;
+                record.event = self.opened_event(record.event)?;
+                Ok(record)
+            })
+            .collect()
+    }
+
+    async fn checkpoint(&self, consumer: &str) -> Result<u64, RepoError> {
+        let seq = sqlx::query_scalar!(
+            "SELECT seq FROM outbox_checkpoints WHERE consumer = ?",
+            consumer,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        seq.map_or(Ok(0), |seq| {
+            u64::try_from(seq).map_err(RepoError::serialization)
+        })
+    }
+
+    async fn save_checkpoint(&self, consumer: &str, seq: u64) -> Result<(), RepoError> {
+        let seq = seq as i64;
+        sqlx::query!(
+            "INSERT INTO outbox_checkpoints (consumer, seq) VALUES (?, ?)
+             ON CONFLICT (consumer) DO UPDATE SET seq = excluded.seq",
+            consumer,
+            seq,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        Ok(())
+    }
+
+    async fn prune_outbox(&self) -> Result<u64, RepoError> {
+        let res = sqlx::query!(
+            "DELETE FROM outbox WHERE seq <= (SELECT MIN(seq) FROM outbox_checkpoints)"
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        Ok(res.rows_affected())
+    }
+
+    async fn restore_outbox(&self, records: &[OutboxRecord]) -> Result<(), RepoError> {
+        let mut tx = self.pool.begin().await.map_err(sqlx_error)?;
+        for record in records {
+            let event = match &self.cipher {
+                Some(cipher) => cipher.seal_event(&record.event)?,
+                None => record.event.clone(),
+            };
+            let json = serde_json::to_string(&event).map_err(RepoError::serialization)?;
+            let seq = i64::try_from(record.seq).map_err(RepoError::serialization)?;
+            let tenant_id = event.tenant_id().as_str();
+            let order_id = event.order_id().to_string();
+            let recorded_at = record.recorded_at.to_rfc3339();
+            sqlx::query!(
+                "INSERT INTO outbox (seq, tenant_id, order_id, event_json, recorded_at) VALUES (?, ?, ?, ?, ?)",
+                seq,
+                tenant_id,
+                order_id,
+                json,
+                recorded_at,
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(sqlx_error)?;
+        }
+        tx.commit().await.map_err(sqlx_error)
+    }
+}
+
+#[async_trait]
+impl OrderProjection for SqliteRepo {
+    async fn project(&self, event: &OrderEvent) -> Result<(), RepoError> {
+        let order = match event {
+            OrderEvent::Created { order }
+            | OrderEvent::Updated { order }
+            | OrderEvent::StatusOverridden { order, .. } => order,
+            OrderEvent::Deleted { id, tenant_id } => {
+                let tenant_id = tenant_id.as_str();
+                let id = id.to_string();
+                sqlx::query!(
+                    "DELETE FROM order_views WHERE tenant_id = ? AND id = ?",
+                    tenant_id,
+                    id,
+                )
+                .execute(&self.pool)
+                .await
+                .map_err(sqlx_error)?;
+                return Ok(());
+            }
+        };
+        let stored = self.at_rest(order)?;
+        let json = serde_json::to_string(&*stored).map_err(RepoError::serialization)?;
+        let row = OrderRow::new(order, self.email_index(Some(&order.email)))?;
+        let item_count = order.items.len() as i64;
+        let unit_count = order.items.iter().map(|i| i64::from(i.qty)).sum::<i64>();
+        let names: Vec<&str> = order.items.iter().map(|i| i.name.as_str()).collect();
+        let item_names = names.join("\n");
+        let created_at = order.created_at.to_rfc3339();
+        // The guard on `updated_at` (RFC 3339 in UTC, so comparable as text)
+        // keeps a redelivered older event from rolling the view back.
+        sqlx::query!(
+            "INSERT INTO order_views (id, tenant_id, status, customer_name, email,
+               shipping_country, total_cents, currency, item_count, unit_count, item_names,
+               created_at, updated_at, order_json, email_index, metadata_json)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+             ON CONFLICT (tenant_id, id) DO UPDATE SET
+               status = excluded.status, customer_name = excluded.customer_name,
+               email = excluded.email, email_index = excluded.email_index,
+               shipping_country = excluded.shipping_country,
+               total_cents = excluded.total_cents, currency = excluded.currency,
+               item_count = excluded.item_count, unit_count = excluded.unit_count,
+               item_names = excluded.item_names, updated_at = excluded.updated_at,
+               order_json = excluded.order_json, metadata_json = excluded.metadata_json
+             WHERE excluded.updated_at >= order_views.updated_at",
+            row.id,
+            row.tenant_id,
+            row.status,
+            stored.customer_name,
+            stored.email,
+            row.shipping_country,
+            row.total_cents,
+            row.currency,
+            item_count,
+            unit_count,
+            item_names,
+            created_at,
+            row.updated_at,
+            json,
+            row.email_index,
+            row.metadata_json,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        Ok(())
+    }
+}
+
+impl SqliteRepo {
+    fn view_order(&self, json: String) -> Result<Order, RepoError> {
+        self.opened(serde_json::from_str(&json).map_err(RepoError::serialization)?)
+    }
+}
+
+#[async_trait]
+impl OrderReadRepository for SqliteRepo {
+    async fn get_view(&self, tenant: &TenantId, id: Uuid) -> Result<Option<Order>, RepoError> {
+        let tenant_id = tenant.as_str();
+        let id = id.to_string();
+        let json = sqlx::query_scalar!(
+            "SELECT order_json FROM order_views WHERE tenant_id = ? AND id = ?",
+            tenant_id,
+            id,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        json.map(|json| self.view_order(json)).transpose()
+    }
+
+    async fn list_views(
+        &self,
+        tenant: &TenantId,
+        filter: &OrderFilter,
+    ) -> Result<Vec<Order>, RepoError> {
+        let sort = filter.sorting().map_err(RepoError::backend)?;
+        // Same predicates and allow-listed sort columns as `list_filtered`.
+        let order_by = match sort {
+            Some(sort) => format!(
+                " ORDER BY {} {}, id ASC",
+                sort.field.as_str(),
+                sort.order.as_sql()
+            ),
+            None => String::new(),
+        };
+        let mut query = self.filtered("SELECT order_json FROM order_views", tenant, filter);
+        query.push(order_by);
+        push_page(&mut query, filter);
+        let rows: Vec<(String,)> = query
+            .build_query_as()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(sqlx_error)?;
+        rows.into_iter()
+            .map(|(json,)| self.view_order(json))
+            .collect()
+    }
+
+    async fn count_views(
+        &self,
+        tenant: &TenantId,
+        filter: &OrderFilter,
+    ) -> Result<usize, RepoError> {
+        let (count,): (i64,) = self
+            .filtered("SELECT COUNT(*) FROM order_views", tenant, filter)
+            .build_query_as()
+            .fetch_one(&self.pool)
+            .await
+            .map_err(sqlx_error)?;
+        Ok(count as usize)
+    }
+}
+
+struct DbDeadLetter {
+    id: i64,
+    consumer: String,
+    record_json: String,
+    attempts: i64,
+    error: String,
+    failed_at: String,
+    state: String,
+    replay_requested_at: Option<String>,
+    replayed_at: Option<String>,
+}
+
+impl DbDeadLetter {
+    fn into_letter(self) -> Result<DeadLetter, RepoError> {
+        let db = RepoError::serialization::<String>;
+        let time = |s: &str| {
+            DateTime::parse_from_rfc3339(s)
+                .map(|d| d.with_timezone(&Utc))
+                .map_err(|e| db(e.to_string()))
+        };
+        Ok(DeadLetter {
+            id: u64::try_from(self.id).map_err(|e| db(e.to_string()))?,
+            consumer: self.consumer,
+            record: serde_json::from_str(&self.record_json).map_err(|e| db(e.to_string()))?,
+            attempts: u32::try_from(self.attempts).map_err(|e| db(e.to_string()))?,
+            error: self.error,
+            failed_at: time(&self.failed_at)?,
+            state: DeadLetterState::parse(&self.state).map_err(db)?,
+            replay_requested_at: self.replay_requested_at.as_deref().map(time).transpose()?,
+            replayed_at: self.replayed_at.as_deref().map(time).transpose()?,
+        })
+    }
+}
+
+impl SqliteRepo {
+    fn opened_letter(&self, mut letter: DeadLetter) -> Result<DeadLetter, RepoError> {
+        letter.record.event = self.opened_event(letter.record.event)?;
+        Ok(letter)
+    }
+}
+
+#[async_trait]
+impl DeadLetterStore for SqliteRepo {
+    async fn dead_letter(
+        &self,
+        consumer: &str,
+        record: &OutboxRecord,
+        attempts: u32,
+        error: &str,
+    ) -> Result<u64, RepoError> {
+        let record = match &self.cipher {
+            Some(cipher) => Cow::Owned(OutboxRecord {
+                event: cipher.seal_event(&record.event)?,
+                ..record.clone()
+            }),
+            None => Cow::Borrowed(record),
+        };
+        let json = serde_json::to_string(&*record).map_err(RepoError::serialization)?;
+        let seq = record.seq as i64;
+        let attempts = i64::from(attempts);
+        let failed_at = Utc::now().to_rfc3339();
+        let state = DeadLetterState::Dead.as_str();
+        let res = sqlx::query!(
+            "INSERT INTO dead_letters (consumer, seq, record_json, attempts, error, failed_at, state)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+            consumer,
+            seq,
+            json,
+            attempts,
+            error,
+            failed_at,
+            state,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        Ok(res.last_insert_rowid() as u64)
+    }
+
+    async fn get_dead_letter(&self, id: u64) -> Result<Option<DeadLetter>, RepoError> {
+        let id = id as i64;
+        let row = sqlx::query_as!(
+            DbDeadLetter,
+            r#"SELECT id AS "id!", consumer, record_json, attempts, error, failed_at, state,
+               replay_requested_at, replayed_at
+             FROM dead_letters WHERE id = ?"#,
+            id,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        row.map(|row| self.opened_letter(row.into_letter()?))
+            .transpose()
+    }
+
+    async fn list_dead_letters(
+        &self,
+        state: Option<DeadLetterState>,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<DeadLetter>, RepoError> {
+        let state = state.map(|s| s.as_str());
+        let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+        let offset = i64::try_from(offset).unwrap_or(i64::MAX);
+        let rows = sqlx::query_as!(
+            DbDeadLetter,
+            r#"SELECT id AS "id!", consumer, record_json, attempts, error, failed_at, state,
+               replay_requested_at, replayed_at
+             FROM dead_letters
+             WHERE (?1 IS NULL OR state = ?1)
+             ORDER BY id DESC LIMIT ?2 OFFSET ?3"#,
+            state,
+            limit,
+            offset,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        rows.into_iter()
+            .map(|row| self.opened_letter(row.into_letter()?))
+            .collect()
+    }
+
+    async fn request_replay(&self, id: u64) -> Result<Option<DeadLetter>, RepoError> {
+        let requested = DeadLetterState::ReplayRequested.as_str();
+        let now = Utc::now().to_rfc3339();
+        let row_id = id as i64;
+        let dead = DeadLetterState::Dead.as_str();
+        sqlx::query!(
+            "UPDATE dead_letters SET state = ?, replay_requested_at = ?
+             WHERE id = ? AND state = ?",
+            requested,
+            now,
+            row_id,
+            dead,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        self.get_dead_letter(id).await
+    }
+
+    async fn replays_requested(
+        &self,
+        consumer: &str,
+        limit: usize,
+    ) -> Result<Vec<DeadLetter>, RepoError> {
+        let requested = DeadLetterState::ReplayRequested.as_str();
+        let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+        let rows = sqlx::query_as!(
+            DbDeadLetter,
+            r#"SELECT id AS "id!", consumer, record_json, attempts, error, failed_at, state,
+               replay_requested_at, replayed_at
+             FROM dead_letters
+             WHERE consumer = ? AND state = ? ORDER BY id LIMIT ?"#,
+            consumer,
+            requested,
+            limit,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        rows.into_iter()
+            .map(|row| self.opened_letter(row.into_letter()?))
+            .collect()
+    }
+
+    async fn finish_replay(
+        &self,
+        id: u64,
+        attempts: u32,
+        error: Option<&str>,
+    ) -> Result<(), RepoError> {
+        let now = Utc::now().to_rfc3339();
+        let attempts = i64::from(attempts);
+        let id = id as i64;
+        match error {
+            None => {
+                let state = DeadLetterState::Replayed.as_str();
+                sqlx::query!(
+                    "UPDATE dead_letters SET state = ?, attempts = attempts + ?, replayed_at = ?
+                     WHERE id = ?",
+                    state,
+                    attempts,
+                    now,
+                    id,
+                )
+                .execute(&self.pool)
+                .await
+            }
+            Some(error) => {
+                let state = DeadLetterState::Dead.as_str();
+                sqlx::query!(
+                    "UPDATE dead_letters SET state = ?, attempts = attempts + ?, error = ?,
+                       failed_at = ?
+                     WHERE id = ?",
+                    state,
+                    attempts,
+                    error,
+                    now,
+                    id,
+                )
+                .execute(&self.pool)
+                .await
+            }
+        }
+        .map_err(sqlx_error)?;
+        Ok(())
+    }
+
+    async fn dead_letter_depth(&self) -> Result<Vec<DeadLetterDepth>, RepoError> {
+        let rows = sqlx::query!(
+            r#"SELECT consumer, state, COUNT(*) AS "count!: i64" FROM dead_letters
+             GROUP BY consumer, state ORDER BY consumer, state"#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(sqlx_error)?;
+        rows.into_iter()
+            .map(|row| {
+                Ok(DeadLetterDepth {
+                    consumer: row.consumer,
+                    state: DeadLetterState::parse(&row.state).map_err(RepoError::serialization)?,
+                    count: row.count as u64,
+                })
+            })
+            .collect()
     }
 }
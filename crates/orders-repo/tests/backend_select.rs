@@ -0,0 +1,199 @@
+use orders_repo::{build_repo_with, RepoBackend, RepoOptions};
+use orders_types::domain::money::Money;
+use orders_types::domain::order::{Order, OrderItem};
+use orders_types::domain::tenant::TenantId;
+use orders_types::ports::order_repository::OrderRepository;
+
+fn options(backend: RepoBackend) -> RepoOptions {
+    RepoOptions {
+        backend: Some(backend),
+        ..Default::default()
+    }
+}
+
+fn order() -> Order {
+    Order::new(
+        "Test".into(),
+        "test@example.com".into(),
+        vec![OrderItem {
+            name: "Widget".into(),
+            qty: 1,
+            unit_price: Money::usd(100),
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
+        }],
+    )
+    .unwrap()
+}
+
+/// Every operation must land on the same backend: a delete is visible to the
+/// following read.
+async fn assert_consistent(repo: &impl OrderRepository) {
+    let tenant = TenantId::default();
+    let order = repo.create(order()).await.unwrap();
+    assert!(repo.exists(&tenant, order.id).await.unwrap());
+    assert_eq!(repo.list(&tenant).await.unwrap().len(), 1);
+    assert!(repo.delete(&tenant, order.id).await.unwrap());
+    assert!(repo.get(&tenant, order.id).await.unwrap().is_none());
+    assert!(repo.list(&tenant).await.unwrap().is_empty());
+}
+
+#[test]
+fn parses_backend_names() {
+    assert_eq!(RepoBackend::parse("memory"), Ok(RepoBackend::Memory));
+    assert_eq!(RepoBackend::parse(" SQLite "), Ok(RepoBackend::Sqlite));
+    assert_eq!(RepoBackend::parse("postgres"), Ok(RepoBackend::Postgres));
+    assert!(RepoBackend::parse("mysql").is_err());
+}
+
+#[test]
+fn infers_backend_from_url_scheme() {
+    assert_eq!(RepoBackend::from_url("memory://"), Ok(RepoBackend::Memory));
+    assert_eq!(
+        RepoBackend::from_url("sqlite://data/orders.db"),
+        Ok(RepoBackend::Sqlite)
+    );
+    assert_eq!(
+        RepoBackend::from_url("sqlite::memory:"),
+        Ok(RepoBackend::Sqlite)
+    );
+    assert_eq!(
+        RepoBackend::from_url("postgresql://db/orders"),
+        Ok(RepoBackend::Postgres)
+    );
+    assert!(RepoBackend::from_url("orders.db").is_err());
+    assert!(RepoBackend::from_url("mysql://db/orders").is_err());
+}
+
+#[test]
+fn default_backend_prefers_sqlite() {
+    let expected = if cfg!(feature = "sqlite") {
+        RepoBackend::Sqlite
+    } else {
+        RepoBackend::Memory
+    };
+    assert_eq!(RepoBackend::default(), expected);
+}
+
+#[cfg(feature = "memory")]
+#[tokio::test]
+async fn selects_memory_backend() {
+    let repo = build_repo_with(None, options(RepoBackend::Memory))
+        .await
+        .unwrap();
+    assert_eq!(repo.backend(), RepoBackend::Memory);
+    assert_consistent(&repo).await;
+}
+
+#[cfg(feature = "memory")]
+#[tokio::test]
+async fn retries_wrap_the_selected_backend() {
+    let options = RepoOptions {
+        retry: Some(orders_repo::retry::RetryPolicy::default()),
+        ..options(RepoBackend::Memory)
+    };
+    let repo = build_repo_with(None, options).await.unwrap();
+    assert!(matches!(repo, orders_repo::Repo::Retrying(_)));
+    assert_eq!(repo.backend(), RepoBackend::Memory);
+    assert_consistent(&repo).await;
+}
+
+#[cfg(feature = "memory")]
+#[tokio::test]
+async fn faults_sit_under_retries() {
+    use orders_repo::fault::{Fault, FaultPlan};
+
+    let options = RepoOptions {
+        retry: Some(orders_repo::retry::RetryPolicy::default()),
+        faults: Some(FaultPlan::new().with_default(Fault::failing(0.0))),
+        ..options(RepoBackend::Memory)
+    };
+    let repo = build_repo_with(None, options).await.unwrap();
+    let orders_repo::Repo::Retrying(retrying) = &repo else {
+        panic!("retries should be outermost");
+    };
+    assert!(matches!(retrying.inner(), orders_repo::Repo::Faulty(_)));
+    assert_eq!(repo.backend(), RepoBackend::Memory);
+    assert_consistent(&repo).await;
+}
+
+#[cfg(feature = "memory")]
+#[tokio::test]
+async fn memory_url_selects_memory_backend() {
+    let repo = build_repo_with(Some("memory://"), RepoOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(repo.backend(), RepoBackend::Memory);
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn sqlite_url_selects_sqlite_backend() {
+    let dir = tempfile::tempdir().unwrap();
+    let url = format!("sqlite://{}", dir.path().join("orders.db").display());
+    let repo = build_repo_with(Some(&url), RepoOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(repo.backend(), RepoBackend::Sqlite);
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn selects_sqlite_backend() {
+    let dir = tempfile::tempdir().unwrap();
+    let url = format!("sqlite://{}", dir.path().join("orders.db").display());
+    let repo = build_repo_with(Some(&url), options(RepoBackend::Sqlite))
+        .await
+        .unwrap();
+    assert_eq!(repo.backend(), RepoBackend::Sqlite);
+    assert_consistent(&repo).await;
+}
+
+#[cfg(not(feature = "sqlite"))]
+#[tokio::test]
+async fn rejects_backend_not_compiled_in() {
+    let err = build_repo_with(None, options(RepoBackend::Sqlite))
+        .await
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("--features sqlite"));
+}
+
+#[tokio::test]
+async fn postgres_is_not_available_yet() {
+    assert!(build_repo_with(None, options(RepoBackend::Postgres))
+        .await
+        .is_err());
+    assert!(
+        build_repo_with(Some("postgres://db/orders"), RepoOptions::default())
+            .await
+            .is_err()
+    );
+}
+
+#[cfg(feature = "memory")]
+#[tokio::test]
+async fn timing_sits_between_retries_and_faults() {
+    use orders_repo::fault::{Fault, FaultPlan};
+
+    let options = RepoOptions {
+        retry: Some(orders_repo::retry::RetryPolicy::default()),
+        faults: Some(FaultPlan::new().with_default(Fault::failing(0.0))),
+        slow_query_threshold: Some(std::time::Duration::from_millis(100)),
+        ..options(RepoBackend::Memory)
+    };
+    let repo = build_repo_with(None, options).await.unwrap();
+    let orders_repo::Repo::Retrying(retrying) = &repo else {
+        panic!("retries should be outermost");
+    };
+    let orders_repo::Repo::Instrumented(instrumented) = retrying.inner() else {
+        panic!("timing should sit under retries");
+    };
+    assert!(matches!(instrumented.inner(), orders_repo::Repo::Faulty(_)));
+    assert_consistent(&repo).await;
+    let metrics = repo.latency_metrics().expect("instrumented");
+    assert!(metrics.count("create") > 0);
+}
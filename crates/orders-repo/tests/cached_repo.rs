@@ -0,0 +1,165 @@
+#![cfg(all(feature = "memory", feature = "sqlite"))]
+
+use orders_repo::cached::CachedRepo;
+use orders_repo::sqlite::SqliteRepo;
+use orders_repo::{build_repo_with, RepoOptions};
+use orders_types::domain::money::Money;
+use orders_types::domain::order::{Order, OrderItem, OrderStatus};
+use orders_types::domain::tenant::TenantId;
+use orders_types::ports::metrics::MetricsSource;
+use orders_types::ports::order_repository::OrderRepository;
+use std::time::Duration;
+use uuid::Uuid;
+
+fn db_url(dir: &tempfile::TempDir) -> String {
+    format!(
+        "sqlite://{}",
+        dir.path()
+            .join(format!("orders-{}.db", Uuid::new_v4()))
+            .display()
+    )
+}
+
+fn order() -> Order {
+    Order::new(
+        "Cai".into(),
+        "cai@example.com".into(),
+        vec![OrderItem {
+            name: "Widget".into(),
+            qty: 1,
+            unit_price: Money::usd(100),
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
+        }],
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn cached_repo_conforms() {
+    let dir = tempfile::tempdir().unwrap();
+    orders_repo::conformance::run_conformance_suite(|| {
+        let url = db_url(&dir);
+        async move { CachedRepo::new(SqliteRepo::new(&url).await.unwrap()) }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn reads_hit_memory_and_writes_reach_sqlite() {
+    let dir = tempfile::tempdir().unwrap();
+    let url = db_url(&dir);
+    let sqlite = SqliteRepo::new(&url).await.unwrap();
+    let repo = CachedRepo::new(sqlite.clone());
+    let tenant = TenantId::default();
+
+    let order = repo.create(order()).await.unwrap();
+    assert!(sqlite.get(&tenant, order.id).await.unwrap().is_some());
+    repo.get(&tenant, order.id).await.unwrap().unwrap();
+    let metrics = repo.metrics();
+    assert_eq!((metrics.hits(), metrics.misses()), (1, 0));
+
+    repo.update_status(&tenant, order.id, OrderStatus::Shipped)
+        .await
+        .unwrap();
+    let stored = sqlite.get(&tenant, order.id).await.unwrap().unwrap();
+    assert_eq!(stored.status, OrderStatus::Shipped);
+    let cached = repo.get(&tenant, order.id).await.unwrap().unwrap();
+    assert_eq!(cached.status, OrderStatus::Shipped);
+
+    // Deleting goes to both: nothing is left to serve from memory.
+    assert!(repo.delete(&tenant, order.id).await.unwrap());
+    assert!(sqlite.get(&tenant, order.id).await.unwrap().is_none());
+    assert!(repo.get(&tenant, order.id).await.unwrap().is_none());
+    assert_eq!((metrics.hits(), metrics.misses()), (2, 1));
+}
+
+#[tokio::test]
+async fn misses_fall_back_to_sqlite_and_backfill() {
+    let dir = tempfile::tempdir().unwrap();
+    let url = db_url(&dir);
+    let sqlite = SqliteRepo::new(&url).await.unwrap();
+    // Written before the cache existed, e.g. by an earlier process.
+    let order = sqlite.create(order()).await.unwrap();
+    let repo = CachedRepo::new(sqlite);
+    let tenant = TenantId::default();
+
+    for _ in 0..3 {
+        repo.get(&tenant, order.id).await.unwrap().unwrap();
+    }
+    let metrics = repo.metrics();
+    assert_eq!((metrics.hits(), metrics.misses()), (2, 1));
+    assert!((metrics.hit_rate() - 2.0 / 3.0).abs() < 1e-9);
+    let scrape = metrics.prometheus();
+    assert!(scrape.contains("orders_repo_cache_hits_total 2"));
+    assert!(scrape.contains("orders_repo_cache_misses_total 1"));
+    assert!(scrape.contains("# TYPE orders_repo_cache_hit_rate gauge"));
+}
+
+#[tokio::test]
+async fn the_cache_evicts_the_least_recently_used_order_past_its_capacity() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = CachedRepo::new(SqliteRepo::new(&db_url(&dir)).await.unwrap()).with_capacity(2);
+    let tenant = TenantId::default();
+
+    let first = repo.create(order()).await.unwrap();
+    let second = repo.create(order()).await.unwrap();
+    // Reading the first makes the second the oldest.
+    repo.get(&tenant, first.id).await.unwrap().unwrap();
+    let third = repo.create(order()).await.unwrap();
+    assert_eq!(repo.cached_len(), 2);
+
+    let metrics = repo.metrics();
+    for id in [first.id, third.id, second.id] {
+        repo.get(&tenant, id).await.unwrap().unwrap();
+    }
+    // The first read plus the first and third from memory; the second from sqlite.
+    assert_eq!((metrics.hits(), metrics.misses()), (3, 1));
+    assert_eq!(repo.cached_len(), 2);
+}
+
+#[tokio::test]
+async fn cached_orders_expire_after_the_ttl() {
+    let dir = tempfile::tempdir().unwrap();
+    let url = db_url(&dir);
+    let sqlite = SqliteRepo::new(&url).await.unwrap();
+    let repo = CachedRepo::new(sqlite.clone()).with_ttl(Duration::from_millis(50));
+    let tenant = TenantId::default();
+
+    let order = repo.create(order()).await.unwrap();
+    // Changed behind the cache, e.g. by another process.
+    sqlite
+        .update_status(&tenant, order.id, OrderStatus::Shipped)
+        .await
+        .unwrap();
+    let cached = repo.get(&tenant, order.id).await.unwrap().unwrap();
+    assert_eq!(cached.status, OrderStatus::Pending);
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    let reread = repo.get(&tenant, order.id).await.unwrap().unwrap();
+    assert_eq!(reread.status, OrderStatus::Shipped);
+    let metrics = repo.metrics();
+    assert_eq!((metrics.hits(), metrics.misses()), (1, 1));
+}
+
+#[tokio::test]
+async fn cache_option_builds_a_cached_sqlite_repo() {
+    let dir = tempfile::tempdir().unwrap();
+    let url = db_url(&dir);
+    let options = RepoOptions {
+        cache: true,
+        cache_capacity: Some(1),
+        ..Default::default()
+    };
+    let repo = build_repo_with(Some(&url), options).await.unwrap();
+    assert_eq!(repo.backend(), orders_repo::RepoBackend::Sqlite);
+    assert!(repo.cache_metrics().is_some());
+
+    let plain = build_repo_with(Some(&db_url(&dir)), RepoOptions::default())
+        .await
+        .unwrap();
+    assert!(plain.cache_metrics().is_none());
+}
@@ -0,0 +1,156 @@
+#![cfg(feature = "memory")]
+
+use orders_repo::fault::{Fault, FaultInjectingRepo, FaultKind, FaultPlan};
+use orders_repo::memory::InMemoryRepo;
+use orders_repo::retry::{RetryPolicy, RetryingRepo};
+use orders_types::domain::money::Money;
+use orders_types::domain::order::{Order, OrderItem};
+use orders_types::ports::order_repository::{OrderRepository, RepoError};
+use std::time::{Duration, Instant};
+
+fn order() -> Order {
+    Order::new(
+        "Ines".into(),
+        "ines@example.com".into(),
+        vec![OrderItem {
+            name: "Widget".into(),
+            qty: 1,
+            unit_price: Money::usd(100),
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
+        }],
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn an_empty_plan_conforms() {
+    orders_repo::conformance::run_conformance_suite(|| async {
+        FaultInjectingRepo::new(InMemoryRepo::new(), FaultPlan::new())
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn failed_calls_never_reach_the_adapter() {
+    let plan =
+        FaultPlan::new().with_op("create", Fault::failing(1.0).with_kind(FaultKind::Backend));
+    let repo = FaultInjectingRepo::new(InMemoryRepo::new(), plan);
+    let order = order();
+
+    let err = repo.create(order.clone()).await.unwrap_err();
+    assert!(matches!(err, RepoError::Backend(_)), "{err}");
+    assert_eq!(repo.injected(), 1);
+    assert!(repo
+        .get(&order.tenant_id, order.id)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn failures_follow_the_rate_and_replay_from_a_seed() {
+    let plan = FaultPlan::new().with_default(Fault::failing(0.3));
+    let outcomes = |seed| {
+        let repo = FaultInjectingRepo::new(InMemoryRepo::new(), plan.clone()).with_seed(seed);
+        async move {
+            let mut failed = Vec::new();
+            for _ in 0..1000 {
+                failed.push(repo.list(&Default::default()).await.is_err());
+            }
+            failed
+        }
+    };
+
+    let first = outcomes(7).await;
+    assert_eq!(first, outcomes(7).await);
+    assert_ne!(first, outcomes(8).await);
+    let failures = first.iter().filter(|f| **f).count();
+    assert!((200..400).contains(&failures), "{failures} of 1000 failed");
+}
+
+#[tokio::test]
+async fn named_operations_override_the_default_and_ping_needs_a_name() {
+    let plan = FaultPlan::new()
+        .with_default(Fault::failing(1.0))
+        .with_op("get", Fault::failing(0.0));
+    let repo = FaultInjectingRepo::new(InMemoryRepo::new(), plan);
+    let order = order();
+
+    assert!(repo.create(order.clone()).await.unwrap_err().is_transient());
+    assert!(repo
+        .get(&order.tenant_id, order.id)
+        .await
+        .unwrap()
+        .is_none());
+    repo.ping().await.unwrap();
+}
+
+#[tokio::test]
+async fn latency_delays_every_call() {
+    let plan = FaultPlan::new().with_op("get", Fault::slow(Duration::from_millis(50)));
+    let repo = FaultInjectingRepo::new(InMemoryRepo::new(), plan);
+    let order = order();
+
+    let started = Instant::now();
+    repo.create(order.clone()).await.unwrap();
+    assert!(started.elapsed() < Duration::from_millis(50));
+    let started = Instant::now();
+    repo.get(&order.tenant_id, order.id).await.unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(50));
+}
+
+#[tokio::test]
+async fn retries_ride_out_transient_faults() {
+    let faulty = FaultInjectingRepo::new(
+        InMemoryRepo::new(),
+        FaultPlan::new().with_default(Fault::failing(0.5)),
+    )
+    .with_seed(42);
+    let repo = RetryingRepo::new(faulty.clone()).with_policy(RetryPolicy {
+        max_attempts: 20,
+        base_delay: Duration::ZERO,
+        max_delay: Duration::ZERO,
+    });
+
+    for _ in 0..50 {
+        let order = order();
+        repo.create(order.clone()).await.unwrap();
+        assert!(repo
+            .get(&order.tenant_id, order.id)
+            .await
+            .unwrap()
+            .is_some());
+    }
+    assert!(faulty.injected() > 0);
+}
+
+#[test]
+fn plans_parse_from_rules() {
+    let plan = FaultPlan::parse("*:0.05, create:0.5:backend, get:0:transient:200").unwrap();
+    assert_eq!(plan.fault("list"), Some(&Fault::failing(0.05)));
+    assert_eq!(plan.fault("ping"), None);
+    assert_eq!(
+        plan.fault("create"),
+        Some(&Fault::failing(0.5).with_kind(FaultKind::Backend))
+    );
+    assert_eq!(
+        plan.fault("get"),
+        Some(&Fault::slow(Duration::from_millis(200)))
+    );
+    assert_eq!(FaultPlan::parse("").unwrap(), FaultPlan::new());
+
+    for bad in [
+        "creat:0.1",
+        "get",
+        "get:2",
+        "get:0.1:flaky",
+        "get:0.1:backend:x",
+        "get:0:backend:1:2",
+    ] {
+        assert!(FaultPlan::parse(bad).is_err(), "{bad}");
+    }
+}
@@ -0,0 +1,83 @@
+#![cfg(feature = "memory")]
+
+use orders_repo::fault::{Fault, FaultInjectingRepo, FaultPlan};
+use orders_repo::instrument::InstrumentedRepo;
+use orders_repo::memory::InMemoryRepo;
+use orders_types::domain::money::Money;
+use orders_types::domain::order::{Order, OrderItem};
+use orders_types::ports::metrics::MetricsSource;
+use orders_types::ports::order_repository::{OrderRepository, RepoError};
+use std::time::Duration;
+use uuid::Uuid;
+
+fn order() -> Order {
+    Order::new(
+        "Ines".into(),
+        "ines@example.com".into(),
+        vec![OrderItem {
+            name: "Widget".into(),
+            qty: 1,
+            unit_price: Money::usd(100),
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
+        }],
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn times_calls_and_counts_the_slow_ones() {
+    let plan = FaultPlan::new().with_op("get", Fault::slow(Duration::from_millis(30)));
+    let repo = InstrumentedRepo::new(FaultInjectingRepo::new(InMemoryRepo::new(), plan))
+        .with_slow_threshold(Duration::from_millis(20));
+    let metrics = repo.metrics();
+
+    let created = repo.create(order()).await.unwrap();
+    let found = repo.get(&created.tenant_id, created.id).await.unwrap();
+    assert_eq!(found.map(|o| o.id), Some(created.id));
+    repo.get(&created.tenant_id, Uuid::new_v4()).await.unwrap();
+
+    assert_eq!(metrics.count("create"), 1);
+    assert_eq!(metrics.slow("create"), 0);
+    assert_eq!(metrics.count("get"), 2);
+    assert_eq!(metrics.slow("get"), 2);
+
+    let text = metrics.prometheus();
+    assert!(text.contains("# TYPE orders_repo_operation_duration_seconds histogram"));
+    assert!(
+        text.contains("orders_repo_operation_duration_seconds_bucket{op=\"get\",le=\"0.025\"} 0\n")
+    );
+    assert!(
+        text.contains("orders_repo_operation_duration_seconds_bucket{op=\"get\",le=\"+Inf\"} 2\n")
+    );
+    assert!(text.contains("orders_repo_operation_duration_seconds_count{op=\"create\"} 1\n"));
+    assert!(text.contains("orders_repo_slow_operations_total{op=\"get\"} 2\n"));
+    assert!(metrics
+        .openmetrics()
+        .contains("# TYPE orders_repo_slow_operations counter"));
+}
+
+#[tokio::test]
+async fn failed_calls_and_unit_of_work_writes_are_timed() {
+    let plan = FaultPlan::new().with_op("delete", Fault::failing(1.0));
+    let repo = InstrumentedRepo::new(FaultInjectingRepo::new(InMemoryRepo::new(), plan));
+    let metrics = repo.metrics();
+    assert_eq!(repo.slow_threshold(), None);
+
+    let mut unit = repo.begin().await.unwrap();
+    let created = unit.create(order()).await.unwrap();
+    unit.commit().await.unwrap();
+    let err = repo
+        .delete(&created.tenant_id, created.id)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, RepoError::Transient(_)));
+
+    for op in ["begin", "create", "commit", "delete"] {
+        assert_eq!(metrics.count(op), 1, "{op}");
+        assert_eq!(metrics.slow(op), 0, "{op}");
+    }
+}
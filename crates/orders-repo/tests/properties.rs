@@ -0,0 +1,82 @@
+//! Whatever order goes in comes back out, numbered, for every generated
+//! order.
+
+use orders_types::arbitrary::fake_order;
+use orders_types::domain::history::OrderHistoryEntry;
+use orders_types::domain::order::Order;
+use orders_types::fixtures::FakeOrder;
+use orders_types::ports::order_repository::OrderRepository;
+use proptest::prelude::*;
+
+/// Store `order` with `history` and check that both read back unchanged,
+/// but for the order number the repository assigned.
+async fn round_trip(
+    repo: &impl OrderRepository,
+    order: Order,
+    history: Vec<OrderHistoryEntry>,
+) -> Result<(), TestCaseError> {
+    let (tenant, id) = (order.tenant_id.clone(), order.id);
+    let mut unit = repo.begin().await.unwrap();
+    let created = unit.create(order.clone()).await.unwrap();
+    prop_assert!(order.order_number.is_none() && created.order_number.is_some());
+    let order = Order {
+        order_number: created.order_number,
+        ..order
+    };
+    for entry in &history {
+        unit.record_transition(&tenant, id, entry.clone())
+            .await
+            .unwrap();
+    }
+    unit.commit().await.unwrap();
+
+    let stored = repo.get(&tenant, id).await.unwrap();
+    prop_assert_eq!(
+        serde_json::to_value(stored).unwrap(),
+        serde_json::to_value(Some(order)).unwrap()
+    );
+    prop_assert_eq!(repo.status_history(&tenant, id).await.unwrap(), history);
+    Ok(())
+}
+
+fn runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+}
+
+#[cfg(feature = "memory")]
+proptest! {
+    #[test]
+    fn memory_repo_stores_orders_unchanged(
+        order in any::<Order>(),
+        FakeOrder { order: aged, history } in fake_order(),
+    ) {
+        let repo = orders_repo::memory::InMemoryRepo::new();
+        runtime().block_on(async {
+            round_trip(&repo, order, Vec::new()).await?;
+            round_trip(&repo, aged, history).await
+        })?;
+    }
+}
+
+#[cfg(feature = "sqlite")]
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn sqlite_repo_stores_orders_unchanged(
+        order in any::<Order>(),
+        FakeOrder { order: aged, history } in fake_order(),
+    ) {
+        let dir = tempfile::tempdir().unwrap();
+        let url = format!("sqlite://{}", dir.path().join("orders.db").display());
+        let rt = runtime();
+        let repo = rt.block_on(orders_repo::sqlite::SqliteRepo::new(&url)).unwrap();
+        rt.block_on(async {
+            round_trip(&repo, order, Vec::new()).await?;
+            round_trip(&repo, aged, history).await
+        })?;
+    }
+}
@@ -0,0 +1,240 @@
+#![cfg(feature = "memory")]
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use orders_repo::memory::InMemoryRepo;
+use orders_repo::retry::{RetryPolicy, RetryingRepo};
+use orders_types::domain::fulfillment::Fulfillment;
+use orders_types::domain::history::OrderHistoryEntry;
+use orders_types::domain::money::Money;
+use orders_types::domain::order::{Order, OrderItem, OrderStatus};
+use orders_types::domain::tenant::TenantId;
+use orders_types::ports::order_repository::{OrderRepository, RepoError};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+fn order() -> Order {
+    Order::new(
+        "Ines".into(),
+        "ines@example.com".into(),
+        vec![OrderItem {
+            name: "Widget".into(),
+            qty: 1,
+            unit_price: Money::usd(100),
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
+        }],
+    )
+    .unwrap()
+}
+
+fn quick(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts,
+        base_delay: Duration::from_millis(1),
+        max_delay: Duration::from_millis(5),
+    }
+}
+
+/// Memory repo whose `create` and `get` fail with `error` until `failures`
+/// runs out.
+#[derive(Clone)]
+struct Flaky {
+    inner: InMemoryRepo,
+    failures: Arc<AtomicU32>,
+    calls: Arc<AtomicU32>,
+    error: fn() -> RepoError,
+}
+
+impl Flaky {
+    fn new(failures: u32) -> Self {
+        Self {
+            inner: InMemoryRepo::new(),
+            failures: Arc::new(AtomicU32::new(failures)),
+            calls: Arc::new(AtomicU32::new(0)),
+            error: || RepoError::Transient("database is locked".into()),
+        }
+    }
+
+    fn trip(&self) -> Result<(), RepoError> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        let failing = self
+            .failures
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+            .is_ok();
+        if failing {
+            Err((self.error)())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[async_trait]
+impl OrderRepository for Flaky {
+    async fn create(&self, order: Order) -> Result<Order, RepoError> {
+        self.trip()?;
+        self.inner.create(order).await
+    }
+    async fn get(&self, tenant: &TenantId, id: Uuid) -> Result<Option<Order>, RepoError> {
+        self.trip()?;
+        self.inner.get(tenant, id).await
+    }
+    async fn list(&self, tenant: &TenantId) -> Result<Vec<Order>, RepoError> {
+        self.inner.list(tenant).await
+    }
+    async fn stale_pending(
+        &self,
+        before: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<Order>, RepoError> {
+        self.inner.stale_pending(before, limit).await
+    }
+    async fn scan(&self, after: Option<Uuid>, limit: usize) -> Result<Vec<Order>, RepoError> {
+        self.inner.scan(after, limit).await
+    }
+    async fn update_status(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        status: OrderStatus,
+    ) -> Result<Option<Order>, RepoError> {
+        self.inner.update_status(tenant, id, status).await
+    }
+    async fn update(&self, order: Order) -> Result<Option<Order>, RepoError> {
+        self.inner.update(order).await
+    }
+    async fn delete(&self, tenant: &TenantId, id: Uuid) -> Result<bool, RepoError> {
+        self.inner.delete(tenant, id).await
+    }
+    async fn record_transition(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        entry: OrderHistoryEntry,
+    ) -> Result<(), RepoError> {
+        self.inner.record_transition(tenant, id, entry).await
+    }
+    async fn status_history(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+    ) -> Result<Vec<OrderHistoryEntry>, RepoError> {
+        self.inner.status_history(tenant, id).await
+    }
+    async fn record_fulfillment(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        fulfillment: Fulfillment,
+    ) -> Result<(), RepoError> {
+        self.inner.record_fulfillment(tenant, id, fulfillment).await
+    }
+    async fn fulfillments(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+    ) -> Result<Vec<Fulfillment>, RepoError> {
+        self.inner.fulfillments(tenant, id).await
+    }
+}
+
+#[tokio::test]
+async fn retrying_repo_conforms() {
+    orders_repo::conformance::run_conformance_suite(|| async {
+        RetryingRepo::new(InMemoryRepo::new())
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn transient_failures_are_retried_until_they_clear() {
+    let flaky = Flaky::new(2);
+    let repo = RetryingRepo::new(flaky.clone()).with_policy(quick(3));
+    let order = order();
+
+    repo.create(order.clone()).await.unwrap();
+    assert_eq!(flaky.calls.load(Ordering::SeqCst), 3);
+    let stored = repo.get(&order.tenant_id, order.id).await.unwrap();
+    assert_eq!(stored.map(|o| o.id), Some(order.id));
+}
+
+#[tokio::test]
+async fn the_last_transient_failure_is_returned_once_attempts_run_out() {
+    let flaky = Flaky::new(5);
+    let repo = RetryingRepo::new(flaky.clone()).with_policy(quick(3));
+
+    let err = repo.create(order()).await.unwrap_err();
+    assert!(err.is_transient(), "{err}");
+    assert_eq!(flaky.calls.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn other_failures_are_not_retried() {
+    let mut flaky = Flaky::new(1);
+    flaky.error = || RepoError::Conflict("order was modified".into());
+    let repo = RetryingRepo::new(flaky.clone()).with_policy(quick(3));
+
+    let err = repo.create(order()).await.unwrap_err();
+    assert!(matches!(err, RepoError::Conflict(_)), "{err}");
+    assert_eq!(flaky.calls.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn backoff_grows_with_jitter_up_to_the_cap() {
+    let policy = RetryPolicy {
+        max_attempts: 10,
+        base_delay: Duration::from_millis(10),
+        max_delay: Duration::from_millis(50),
+    };
+    for _ in 0..100 {
+        assert!(policy.delay(0) <= Duration::from_millis(10));
+        assert!(policy.delay(2) <= Duration::from_millis(40));
+        assert!(policy.delay(30) <= Duration::from_millis(50));
+    }
+    let delays: std::collections::HashSet<_> = (0..20).map(|_| policy.delay(3)).collect();
+    assert!(delays.len() > 1, "no jitter: {delays:?}");
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn a_locked_sqlite_database_is_transient_and_retried() {
+    use orders_repo::pool::PoolOptions;
+    use orders_repo::sqlite::SqliteRepo;
+    use sqlx::Connection;
+
+    let dir = tempfile::tempdir().unwrap();
+    let url = format!("sqlite://{}", dir.path().join("orders.db").display());
+    let pool = PoolOptions {
+        busy_timeout: Duration::ZERO,
+        ..Default::default()
+    };
+    let sqlite = SqliteRepo::new_with(&url, &pool).await.unwrap();
+
+    let mut locker = sqlx::SqliteConnection::connect(&url).await.unwrap();
+    sqlx::query("BEGIN EXCLUSIVE")
+        .execute(&mut locker)
+        .await
+        .unwrap();
+    let err = sqlite.create(order()).await.unwrap_err();
+    assert!(err.is_transient(), "{err}");
+
+    let repo = RetryingRepo::new(sqlite).with_policy(RetryPolicy {
+        max_attempts: 20,
+        base_delay: Duration::from_millis(20),
+        max_delay: Duration::from_millis(50),
+    });
+    let release = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        sqlx::query("COMMIT").execute(&mut locker).await.unwrap();
+    });
+    let order = order();
+    repo.create(order.clone()).await.unwrap();
+    release.await.unwrap();
+    assert!(repo.exists(&order.tenant_id, order.id).await.unwrap());
+}
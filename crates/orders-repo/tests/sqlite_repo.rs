@@ -1,9 +1,12 @@
 #![cfg(feature = "sqlite")]
 
 use orders_repo::sqlite::SqliteRepo;
+use orders_types::domain::money::Money;
 use orders_types::domain::order::{OrderItem, OrderStatus};
-use orders_types::ports::order_repository::OrderRepository;
+use orders_types::domain::tenant::TenantId;
+use orders_types::ports::order_repository::{OrderRepository, RepoError};
 use std::path::PathBuf;
+use std::str::FromStr;
 use uuid::Uuid;
 
 fn temp_db_url() -> (tempfile::TempDir, String) {
@@ -15,57 +18,1105 @@ fn temp_db_url() -> (tempfile::TempDir, String) {
 }
 
 #[tokio::test]
-async fn sqlite_repo_crud_flow() {
+async fn ping_round_trips_to_the_database() {
     let (_dir, url) = temp_db_url();
     let repo = SqliteRepo::new(&url).await.unwrap();
+    repo.ping().await.unwrap();
+}
+
+#[tokio::test]
+async fn sqlite_repo_conforms() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    orders_repo::conformance::run_conformance_suite(|| {
+        let url = format!(
+            "sqlite://{}",
+            dir.path()
+                .join(format!("orders-{}.db", Uuid::new_v4()))
+                .display()
+        );
+        async move { SqliteRepo::new(&url).await.unwrap() }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn discount_redemptions_stop_at_max_uses() {
+    use orders_types::domain::discount::{Discount, DiscountKind};
+    use orders_types::ports::discount_repository::DiscountRepository;
+
+    let (_dir, url) = temp_db_url();
+    let repo = SqliteRepo::new(&url).await.unwrap();
+    let tenant = TenantId::default();
+    let mut discount = Discount::new(
+        "TWICE",
+        DiscountKind::Fixed {
+            amount: Money::usd(200),
+        },
+    );
+    discount.max_uses = Some(2);
+    assert!(repo.create_discount(discount.clone()).await.unwrap());
+    assert!(!repo.create_discount(discount).await.unwrap());
+
+    assert!(repo.redeem_discount(&tenant, "TWICE").await.unwrap());
+    assert!(repo.redeem_discount(&tenant, "TWICE").await.unwrap());
+    assert!(!repo.redeem_discount(&tenant, "TWICE").await.unwrap());
+    repo.release_discount(&tenant, "TWICE").await.unwrap();
+
+    let stored = repo.get_discount(&tenant, "TWICE").await.unwrap().unwrap();
+    assert_eq!(stored.uses, 1);
+    assert_eq!(
+        stored.kind,
+        DiscountKind::Fixed {
+            amount: Money::usd(200)
+        }
+    );
+    assert_eq!(repo.list_discounts(&tenant).await.unwrap().len(), 1);
+    assert!(repo.delete_discount(&tenant, "TWICE").await.unwrap());
+    assert!(repo.get_discount(&tenant, "TWICE").await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn sqlite_api_keys_roundtrip_and_revoke() {
+    use orders_types::domain::api_key::{ApiKey, Scope};
+    use orders_types::ports::api_key_repository::ApiKeyRepository;
 
+    let (_dir, url) = temp_db_url();
+    let repo = SqliteRepo::new(&url).await.unwrap();
+    let key = ApiKey::new(
+        "ci".into(),
+        "hash-1".into(),
+        vec![Scope::Read, Scope::Write],
+    )
+    .with_tenant(TenantId::parse("acme").unwrap());
+    repo.create_key(key.clone()).await.unwrap();
+
+    let found = repo.find_key_by_hash("hash-1").await.unwrap().unwrap();
+    assert_eq!(found.scopes, vec![Scope::Read, Scope::Write]);
+    assert_eq!(found.tenant.as_str(), "acme");
+    assert!(found.is_active());
+    assert_eq!(
+        repo.find_key(key.id).await.unwrap().unwrap().key_hash,
+        "hash-1"
+    );
+    assert!(repo.find_key(Uuid::new_v4()).await.unwrap().is_none());
+
+    assert!(repo.revoke_key(key.id).await.unwrap());
+    let found = repo.find_key_by_hash("hash-1").await.unwrap().unwrap();
+    assert!(!found.is_active());
+    assert_eq!(repo.list_keys().await.unwrap().len(), 1);
+    assert!(!repo.revoke_key(Uuid::new_v4()).await.unwrap());
+}
+
+#[tokio::test]
+async fn migration_moves_items_json_into_order_items() {
+    let (_dir, url) = temp_db_url();
+    let legacy = Uuid::new_v4();
+    let opts = sqlx::sqlite::SqliteConnectOptions::from_str(&url)
+        .unwrap()
+        .create_if_missing(true);
+    let pool = sqlx::SqlitePool::connect_with(opts).await.unwrap();
+    {
+        for ddl in [
+            include_str!("../migrations/0001_create_orders.sql"),
+            include_str!("../migrations/0002_add_pricing_snapshot.sql"),
+        ] {
+            sqlx::query(ddl).execute(&pool).await.unwrap();
+        }
+        let now = chrono::Utc::now().to_rfc3339();
+        sqlx::query(
+            "INSERT INTO orders (id, customer_name, email, total_cents, status, created_at, updated_at, items_json)
+             VALUES (?, 'Old', 'old@example.com', 600, 'Pending', ?, ?, ?)",
+        )
+        .bind(legacy.to_string())
+        .bind(&now)
+        .bind(&now)
+        .bind(r#"[{"name":"Widget","qty":2,"unit_price_cents":250},{"name":"Gadget","qty":1,"unit_price_cents":100,"currency":"USD","weight_grams":40}]"#)
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+
+    let repo = SqliteRepo::new(&url).await.unwrap();
+    let order = repo
+        .get(&TenantId::default(), legacy)
+        .await
+        .unwrap()
+        .unwrap();
+    let lines: Vec<_> = order
+        .items
+        .iter()
+        .map(|i| (i.name.as_str(), i.qty, i.unit_price, i.weight_grams))
+        .collect();
+    assert_eq!(
+        lines,
+        [
+            ("Widget", 2, Money::usd(250), 0),
+            ("Gadget", 1, Money::usd(100), 40)
+        ]
+    );
+    let (rows,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM order_items")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(rows, 2);
+
+    // Lines go with their order.
+    assert!(repo.delete(&TenantId::default(), legacy).await.unwrap());
+    let (rows,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM order_items")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(rows, 0);
+}
+
+#[cfg(feature = "compression")]
+#[tokio::test]
+async fn migrate_unpacks_compressed_items_json() {
+    let (_dir, url) = temp_db_url();
+    let repo = SqliteRepo::new(&url).await.unwrap();
+    let order = orders_types::domain::order::Order::new(
+        "Big".into(),
+        "big@example.com".into(),
+        (0..40)
+            .map(|i| OrderItem {
+                name: format!("Widget {i}"),
+                qty: 1,
+                unit_price: Money::usd(100),
+                weight_grams: 0,
+                sku: None,
+                description: None,
+                metadata: Default::default(),
+                discount_cents: 0,
+            })
+            .collect(),
+    )
+    .unwrap();
+    repo.create(order.clone()).await.unwrap();
+
+    // As written by the old `items_json` compression.
+    let opts = sqlx::sqlite::SqliteConnectOptions::from_str(&url).unwrap();
+    let pool = sqlx::SqlitePool::connect_with(opts).await.unwrap();
+    let json = serde_json::to_vec(&order.items).unwrap();
+    let blob = zstd::encode_all(json.as_slice(), zstd::DEFAULT_COMPRESSION_LEVEL).unwrap();
+    sqlx::query("DELETE FROM order_items")
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query("UPDATE orders SET items_json = ?")
+        .bind(blob)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    repo.migrate().await.unwrap();
+    let stored = repo
+        .get(&TenantId::default(), order.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.items.len(), 40);
+    assert_eq!(stored.items[39].name, "Widget 39");
+    let (kind,): (String,) = sqlx::query_as("SELECT typeof(items_json) FROM orders")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(kind, "text");
+}
+
+#[tokio::test]
+async fn integrity_pass_reports_and_maps_legacy_statuses() {
+    use orders_types::domain::integrity::{IntegrityIssue, StatusMapping};
+
+    let (_dir, url) = temp_db_url();
+    let repo = SqliteRepo::new(&url).await.unwrap();
     let order = orders_types::domain::order::Order::new(
-        "Test".into(),
-        "test@example.com".into(),
+        "Legacy".into(),
+        "legacy@example.com".into(),
         vec![OrderItem {
             name: "Widget".into(),
-            qty: 2,
-            unit_price_cents: 500,
+            qty: 1,
+            unit_price: Money::usd(100),
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
         }],
     )
     .unwrap();
+    repo.create(order.clone()).await.unwrap();
+
+    let pool = sqlx::SqlitePool::connect(&url).await.unwrap();
+    sqlx::query("UPDATE orders SET status = 'shipped_v1' WHERE id = ?")
+        .bind(order.id.to_string())
+        .execute(&pool)
+        .await
+        .unwrap();
 
-    let created = repo.create(order.clone()).await.unwrap();
-    assert_eq!(created.id, order.id);
+    // Reads no longer guess a status for unknown values.
+    assert!(repo.get(&TenantId::default(), order.id).await.is_err());
 
-    let fetched = repo.get(order.id).await.unwrap().unwrap();
-    assert_eq!(fetched.customer_name, "Test");
+    let report = repo
+        .check_integrity(&StatusMapping::default(), true)
+        .await
+        .unwrap();
+    assert_eq!(report.checked, 1);
+    assert_eq!(report.fixed, 0);
+    assert_eq!(
+        report.issues,
+        vec![IntegrityIssue::UnknownStatus {
+            order_id: order.id.to_string(),
+            value: "shipped_v1".into(),
+            mapped_to: None,
+        }]
+    );
 
-    let listed = repo.list().await.unwrap();
-    assert_eq!(listed.len(), 1);
+    let mapping = StatusMapping::parse("shipped_v1=Shipped").unwrap();
+    let dry_run = repo.check_integrity(&mapping, false).await.unwrap();
+    assert_eq!(dry_run.fixed, 0);
+    assert!(matches!(
+        &dry_run.issues[0],
+        IntegrityIssue::UnknownStatus {
+            mapped_to: Some(OrderStatus::Shipped),
+            ..
+        }
+    ));
 
-    let updated = repo
-        .update_status(order.id, OrderStatus::Shipped)
+    let fixed = repo.check_integrity(&mapping, true).await.unwrap();
+    assert_eq!(fixed.fixed, 1);
+    assert!(fixed.is_clean());
+    let repaired = repo
+        .get(&TenantId::default(), order.id)
         .await
         .unwrap()
         .unwrap();
-    assert_eq!(updated.status, OrderStatus::Shipped);
+    assert_eq!(repaired.status, OrderStatus::Shipped);
+}
+
+#[tokio::test]
+async fn migrations_are_tracked_and_reported() {
+    let (_dir, url) = temp_db_url();
+    let fresh = SqliteRepo::connect(&url).await.unwrap();
+    let pending = fresh.pending_migrations().await.unwrap();
+    assert_eq!(pending.first().unwrap().version, 1);
+    assert!(pending.windows(2).all(|w| w[0].version < w[1].version));
+    assert_eq!(fresh.schema_version().await.unwrap(), None);
 
-    let deleted = repo.delete(order.id).await.unwrap();
-    assert!(deleted);
-    assert!(repo.get(order.id).await.unwrap().is_none());
+    let applied = fresh.migrate().await.unwrap();
+    assert_eq!(applied, pending);
+    assert!(fresh.pending_migrations().await.unwrap().is_empty());
+    assert_eq!(
+        fresh.schema_version().await.unwrap(),
+        Some(pending.last().unwrap().version)
+    );
+    assert!(fresh.migrate().await.unwrap().is_empty());
 }
 
 #[tokio::test]
-async fn sqlite_repo_handles_missing_rows() {
+async fn adopts_schema_created_before_migration_tracking() {
     let (_dir, url) = temp_db_url();
+    {
+        // What the original startup DDL left behind: no tenants, no api keys.
+        let opts = sqlx::sqlite::SqliteConnectOptions::from_str(&url)
+            .unwrap()
+            .create_if_missing(true);
+        let pool = sqlx::SqlitePool::connect_with(opts).await.unwrap();
+        for ddl in [
+            include_str!("../migrations/0001_create_orders.sql"),
+            include_str!("../migrations/0002_add_pricing_snapshot.sql"),
+        ] {
+            sqlx::query(ddl).execute(&pool).await.unwrap();
+        }
+        let now = chrono::Utc::now().to_rfc3339();
+        sqlx::query(
+            "INSERT INTO orders (id, customer_name, email, total_cents, status, created_at, updated_at, items_json)
+             VALUES (?, 'Old', 'old@example.com', 100, 'Pending', ?, ?, '[]')",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(&now)
+        .bind(&now)
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+
     let repo = SqliteRepo::new(&url).await.unwrap();
-    let missing_id = uuid::Uuid::new_v4();
+    assert!(repo.pending_migrations().await.unwrap().is_empty());
+    assert!(repo.schema_version().await.unwrap().unwrap() >= 7);
+    let orders = repo.list(&TenantId::default()).await.unwrap();
+    assert_eq!(orders.len(), 1);
+    assert_eq!(orders[0].customer_name, "Old");
+}
 
-    let missing = repo.get(missing_id).await.unwrap();
-    assert!(missing.is_none());
+#[tokio::test]
+async fn create_many_is_all_or_nothing() {
+    let (_dir, url) = temp_db_url();
+    let repo = SqliteRepo::new(&url).await.unwrap();
+    let order = |name: &str| {
+        orders_types::domain::order::Order::new(
+            name.into(),
+            "batch@example.com".into(),
+            vec![OrderItem {
+                name: "Widget".into(),
+                qty: 1,
+                unit_price: Money::usd(100),
+                weight_grams: 0,
+                sku: None,
+                description: None,
+                metadata: Default::default(),
+                discount_cents: 0,
+            }],
+        )
+        .unwrap()
+    };
+
+    let (a, b) = (order("A"), order("B"));
+    repo.create_many(vec![a.clone(), b]).await.unwrap();
+    assert_eq!(repo.list(&TenantId::default()).await.unwrap().len(), 2);
+
+    // A duplicate id fails the whole batch, including the new order before it.
+    assert!(repo.create_many(vec![order("C"), a]).await.is_err());
+    assert_eq!(repo.list(&TenantId::default()).await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn audit_log_round_trips_and_pages_newest_first() {
+    use orders_types::domain::audit::{AuditAction, AuditEntry};
+    use orders_types::ports::audit_repository::AuditRepository;
 
-    let updated = repo
-        .update_status(missing_id, OrderStatus::Shipped)
+    let (_dir, url) = temp_db_url();
+    let repo = SqliteRepo::new(&url).await.unwrap();
+    let order = orders_types::domain::order::Order::new(
+        "Lee".into(),
+        "lee@example.com".into(),
+        vec![OrderItem {
+            name: "Widget".into(),
+            qty: 1,
+            unit_price: Money::usd(100),
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
+        }],
+    )
+    .unwrap();
+    let mut shipped = order.clone();
+    shipped.update_status(OrderStatus::Shipped);
+    repo.record_audit(AuditEntry::created("key:a", order.clone()))
         .await
         .unwrap();
-    assert!(updated.is_none());
+    repo.record_audit(AuditEntry::changed("key:b", order.clone(), shipped.clone()))
+        .await
+        .unwrap();
+    repo.record_audit(AuditEntry::deleted("system", shipped))
+        .await
+        .unwrap();
+
+    let tenant = TenantId::default();
+    let history = repo.order_audit(&tenant, order.id).await.unwrap();
+    let actions: Vec<AuditAction> = history.iter().map(|e| e.action).collect();
+    assert_eq!(
+        actions,
+        [
+            AuditAction::Created,
+            AuditAction::StatusChanged,
+            AuditAction::Deleted
+        ]
+    );
+    assert_eq!(history[1].actor, "key:b");
+    assert_eq!(
+        history[1].after.as_ref().unwrap().status,
+        OrderStatus::Shipped
+    );
+    assert!(history[2].after.is_none());
+
+    let page = repo.list_audit(&tenant, 1, 1).await.unwrap();
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].action, AuditAction::StatusChanged);
+    assert!(repo
+        .list_audit(&TenantId::parse("other").unwrap(), 10, 0)
+        .await
+        .unwrap()
+        .is_empty());
+
+    assert_eq!(repo.anonymize_audit(&tenant, order.id).await.unwrap(), 3);
+    let history = repo.order_audit(&tenant, order.id).await.unwrap();
+    assert!(history
+        .iter()
+        .flat_map(|e| e.before.iter().chain(&e.after))
+        .all(|o| o.email == "erased@invalid" && o.customer_name == "[erased]"));
+    assert_eq!(history[1].after.as_ref().unwrap().total, order.total);
+}
+
+#[tokio::test]
+async fn audit_log_keeps_the_reason_for_a_status_override() {
+    use orders_types::domain::audit::{AuditAction, AuditEntry};
+    use orders_types::ports::audit_repository::AuditRepository;
+
+    let (_dir, url) = temp_db_url();
+    let repo = SqliteRepo::new(&url).await.unwrap();
+    let order = orders_types::domain::order::Order::new(
+        "Lee".into(),
+        "lee@example.com".into(),
+        vec![OrderItem {
+            name: "Widget".into(),
+            qty: 1,
+            unit_price: Money::usd(100),
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
+        }],
+    )
+    .unwrap();
+    let mut cancelled = order.clone();
+    cancelled.update_status(OrderStatus::Cancelled);
+    repo.record_audit(AuditEntry::created("key:a", cancelled.clone()))
+        .await
+        .unwrap();
+    repo.record_audit(AuditEntry::overridden(
+        "bootstrap",
+        cancelled,
+        order.clone(),
+        "cancelled by mistake",
+    ))
+    .await
+    .unwrap();
+
+    let history = repo
+        .order_audit(&TenantId::default(), order.id)
+        .await
+        .unwrap();
+    assert_eq!(history[0].reason, None);
+    assert_eq!(history[1].action, AuditAction::StatusOverridden);
+    assert_eq!(history[1].reason.as_deref(), Some("cancelled by mistake"));
+}
+
+#[tokio::test]
+async fn concurrent_writers_wait_for_the_lock() {
+    use orders_repo::pool::PoolOptions;
+    use orders_types::domain::order::Order;
 
-    let deleted = repo.delete(missing_id).await.unwrap();
-    assert!(!deleted);
+    let (dir, url) = temp_db_url();
+    let pool = PoolOptions {
+        max_connections: 8,
+        ..Default::default()
+    };
+    let repo = SqliteRepo::new_with(&url, &pool).await.unwrap();
+    let writes = (0..32).map(|i| {
+        let repo = repo.clone();
+        tokio::spawn(async move {
+            let order = Order::new(
+                format!("Customer {i}"),
+                format!("c{i}@example.com"),
+                vec![OrderItem {
+                    name: "Widget".into(),
+                    qty: 1,
+                    unit_price: Money::usd(100),
+                    weight_grams: 0,
+                    sku: None,
+                    description: None,
+                    metadata: Default::default(),
+                    discount_cents: 0,
+                }],
+            )
+            .unwrap();
+            repo.create(order).await
+        })
+    });
+    for write in writes {
+        write.await.unwrap().unwrap();
+    }
+    let all = orders_types::domain::filter::OrderFilter::default();
+    assert_eq!(repo.count(&TenantId::default(), &all).await.unwrap(), 32);
+    // The write-ahead log sits next to the database file.
+    let wal = std::fs::read_dir(dir.path())
+        .unwrap()
+        .filter_map(Result::ok)
+        .any(|e| e.file_name().to_string_lossy().ends_with("-wal"));
+    assert!(wal);
+}
+
+#[tokio::test]
+async fn units_of_work_roll_back_order_history_and_outbox_together() {
+    use orders_types::domain::fulfillment::{FulfilledItem, Fulfillment};
+    use orders_types::domain::history::OrderHistoryEntry;
+    use orders_types::domain::order::Order;
+    use orders_types::ports::outbox::OutboxStore;
+
+    let (_dir, url) = temp_db_url();
+    let repo = SqliteRepo::new(&url).await.unwrap().with_outbox();
+    let tenant = TenantId::default();
+    let order = Order::new(
+        "Lee".into(),
+        "lee@example.com".into(),
+        vec![OrderItem {
+            name: "Widget".into(),
+            qty: 1,
+            unit_price: Money::usd(100),
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
+        }],
+    )
+    .unwrap();
+    let entry =
+        OrderHistoryEntry::new(None, OrderStatus::Pending, order.created_at, "system", None);
+
+    let mut unit = repo.begin().await.unwrap();
+    unit.create(order.clone()).await.unwrap();
+    unit.record_transition(&tenant, order.id, entry.clone())
+        .await
+        .unwrap();
+    unit.rollback().await.unwrap();
+    // Dropping a unit rolls it back too.
+    let mut unit = repo.begin().await.unwrap();
+    unit.create(order.clone()).await.unwrap();
+    drop(unit);
+    assert!(repo.get(&tenant, order.id).await.unwrap().is_none());
+    assert!(repo
+        .status_history(&tenant, order.id)
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(repo.outbox_after(0, 10).await.unwrap().is_empty());
+
+    let mut unit = repo.begin().await.unwrap();
+    unit.create(order.clone()).await.unwrap();
+    unit.record_transition(&tenant, order.id, entry)
+        .await
+        .unwrap();
+    unit.commit().await.unwrap();
+    assert!(repo.get(&tenant, order.id).await.unwrap().is_some());
+    assert_eq!(
+        repo.status_history(&tenant, order.id).await.unwrap().len(),
+        1
+    );
+    assert_eq!(repo.outbox_after(0, 10).await.unwrap().len(), 1);
+
+    // A shipment and the status change it causes go together too.
+    let shipment = Fulfillment::new(
+        vec![FulfilledItem {
+            position: 0,
+            qty: 1,
+        }],
+        "UPS",
+        "1Z1",
+        order.created_at,
+    );
+    let mut unit = repo.begin().await.unwrap();
+    unit.record_fulfillment(&tenant, order.id, shipment.clone())
+        .await
+        .unwrap();
+    unit.update_status(&tenant, order.id, OrderStatus::Shipped)
+        .await
+        .unwrap();
+    drop(unit);
+    assert!(repo
+        .fulfillments(&tenant, order.id)
+        .await
+        .unwrap()
+        .is_empty());
+    let stored = repo.get(&tenant, order.id).await.unwrap().unwrap();
+    assert_eq!(stored.status, OrderStatus::Pending);
+
+    let mut unit = repo.begin().await.unwrap();
+    unit.record_fulfillment(&tenant, order.id, shipment)
+        .await
+        .unwrap();
+    unit.update_status(&tenant, order.id, OrderStatus::Shipped)
+        .await
+        .unwrap();
+    unit.commit().await.unwrap();
+    assert_eq!(repo.fulfillments(&tenant, order.id).await.unwrap().len(), 1);
+    let stored = repo.get(&tenant, order.id).await.unwrap().unwrap();
+    assert_eq!(stored.status, OrderStatus::Shipped);
+}
+
+#[tokio::test]
+async fn outbox_records_committed_changes_in_order() {
+    use orders_types::domain::events::OrderEvent;
+    use orders_types::domain::order::Order;
+    use orders_types::ports::outbox::OutboxStore;
+
+    let (_dir, url) = temp_db_url();
+    let order = || {
+        Order::new(
+            "Lee".into(),
+            "lee@example.com".into(),
+            vec![OrderItem {
+                name: "Widget".into(),
+                qty: 1,
+                unit_price: Money::usd(100),
+                weight_grams: 0,
+                sku: None,
+                description: None,
+                metadata: Default::default(),
+                discount_cents: 0,
+            }],
+        )
+        .unwrap()
+    };
+    let tenant = TenantId::default();
+
+    // Off by default.
+    let plain = SqliteRepo::new(&url).await.unwrap();
+    plain.create(order()).await.unwrap();
+    assert!(plain.outbox_after(0, 10).await.unwrap().is_empty());
+
+    let repo = plain.with_outbox();
+    let a = repo.create(order()).await.unwrap();
+    repo.update_status(&tenant, a.id, OrderStatus::Shipped)
+        .await
+        .unwrap();
+    // Nothing changed, nothing recorded.
+    assert!(repo
+        .update_status(&tenant, Uuid::new_v4(), OrderStatus::Shipped)
+        .await
+        .unwrap()
+        .is_none());
+    let b = order();
+    // A failed batch records nothing either.
+    assert!(repo.create_many(vec![b.clone(), a.clone()]).await.is_err());
+    assert!(repo.delete(&tenant, a.id).await.unwrap());
+
+    let records = repo.outbox_after(0, 10).await.unwrap();
+    let seqs: Vec<u64> = records.iter().map(|r| r.seq).collect();
+    assert!(seqs.windows(2).all(|w| w[0] < w[1]));
+    assert!(matches!(&records[0].event, OrderEvent::Created { order } if order.id == a.id));
+    assert!(matches!(
+        &records[1].event,
+        OrderEvent::Updated { order } if order.status == OrderStatus::Shipped
+    ));
+    assert!(matches!(&records[2].event, OrderEvent::Deleted { id, .. } if *id == a.id));
+    assert_eq!(records.len(), 3);
+    assert_eq!(repo.outbox_after(seqs[0], 1).await.unwrap()[0].seq, seqs[1]);
+
+    assert_eq!(repo.checkpoint("kafka").await.unwrap(), 0);
+    repo.save_checkpoint("kafka", seqs[2]).await.unwrap();
+    repo.save_checkpoint("webhook", seqs[0]).await.unwrap();
+    assert_eq!(repo.checkpoint("kafka").await.unwrap(), seqs[2]);
+    // Only what both consumers have handled goes.
+    assert_eq!(repo.prune_outbox().await.unwrap(), 1);
+    assert_eq!(repo.outbox_after(0, 10).await.unwrap()[0].seq, seqs[1]);
+}
+
+#[tokio::test]
+async fn projection_keeps_the_read_model_in_step() {
+    use orders_types::domain::events::OrderEvent;
+    use orders_types::domain::filter::{OrderFilter, SortField, SortOrder};
+    use orders_types::domain::order::Order;
+    use orders_types::ports::order_read_repository::{OrderProjection, OrderReadRepository};
+
+    let (_dir, url) = temp_db_url();
+    let repo = SqliteRepo::new(&url).await.unwrap();
+    let tenant = TenantId::default();
+    let order = |qty| {
+        Order::new(
+            "Lee".into(),
+            "lee@example.com".into(),
+            vec![OrderItem {
+                name: "Widget".into(),
+                qty,
+                unit_price: Money::usd(100),
+                weight_grams: 0,
+                sku: None,
+                description: None,
+                metadata: Default::default(),
+                discount_cents: 0,
+            }],
+        )
+        .unwrap()
+    };
+    let a = order(1);
+    let b = order(3).with_metadata([("erp_id".to_string(), "E-3".to_string())].into());
+    repo.project(&OrderEvent::Created { order: a.clone() })
+        .await
+        .unwrap();
+    repo.project(&OrderEvent::Created { order: b.clone() })
+        .await
+        .unwrap();
+    // Projected views are not orders of the write store.
+    assert!(repo.get(&tenant, a.id).await.unwrap().is_none());
+
+    let mut shipped = a.clone();
+    shipped.status = OrderStatus::Shipped;
+    shipped.updated_at = a.updated_at + chrono::Duration::seconds(1);
+    repo.project(&OrderEvent::Updated {
+        order: shipped.clone(),
+    })
+    .await
+    .unwrap();
+    // A redelivered older event doesn't roll the view back.
+    repo.project(&OrderEvent::Created { order: a.clone() })
+        .await
+        .unwrap();
+    let view = repo.get_view(&tenant, a.id).await.unwrap().unwrap();
+    assert_eq!(view.status, OrderStatus::Shipped);
+    assert_eq!(view.items[0].qty, 1);
+
+    let by_total = OrderFilter {
+        sort: Some(SortField::TotalCents),
+        order: Some(SortOrder::Desc),
+        ..Default::default()
+    };
+    let ids: Vec<Uuid> = repo
+        .list_views(&tenant, &by_total)
+        .await
+        .unwrap()
+        .iter()
+        .map(|o| o.id)
+        .collect();
+    assert_eq!(ids, [b.id, a.id]);
+    let shipped_only = OrderFilter::default().with_status(OrderStatus::Shipped);
+    assert_eq!(repo.count_views(&tenant, &shipped_only).await.unwrap(), 1);
+    let by_erp = OrderFilter::default().with_metadata("erp_id", "E-3");
+    let views = repo.list_views(&tenant, &by_erp).await.unwrap();
+    assert_eq!(views.iter().map(|o| o.id).collect::<Vec<_>>(), [b.id]);
+    assert_eq!(views[0].metadata, b.metadata);
+    let other = TenantId::parse("other").unwrap();
+    assert!(repo.get_view(&other, a.id).await.unwrap().is_none());
+
+    repo.project(&OrderEvent::Deleted {
+        id: a.id,
+        tenant_id: tenant.clone(),
+    })
+    .await
+    .unwrap();
+    assert!(repo.get_view(&tenant, a.id).await.unwrap().is_none());
+    assert_eq!(
+        repo.count_views(&tenant, &OrderFilter::default())
+            .await
+            .unwrap(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn dead_letters_park_replay_and_count() {
+    use chrono::Utc;
+    use orders_types::domain::dead_letter::DeadLetterState;
+    use orders_types::domain::events::OrderEvent;
+    use orders_types::domain::outbox::OutboxRecord;
+    use orders_types::ports::dead_letter::DeadLetterStore;
+
+    let (_dir, url) = temp_db_url();
+    let repo = SqliteRepo::new(&url).await.unwrap();
+    let record = |seq| OutboxRecord {
+        seq,
+        recorded_at: Utc::now(),
+        event: OrderEvent::Deleted {
+            id: Uuid::new_v4(),
+            tenant_id: TenantId::default(),
+        },
+    };
+    let a = repo
+        .dead_letter("webhook", &record(3), 5, "503 Service Unavailable")
+        .await
+        .unwrap();
+    let b = repo
+        .dead_letter("webhook", &record(4), 5, "timed out")
+        .await
+        .unwrap();
+    repo.dead_letter("kafka", &record(3), 2, "broker down")
+        .await
+        .unwrap();
+
+    let letter = repo.get_dead_letter(a).await.unwrap().unwrap();
+    assert_eq!((letter.record.seq, letter.attempts), (3, 5));
+    assert_eq!(letter.state, DeadLetterState::Dead);
+    assert!(repo.get_dead_letter(999).await.unwrap().is_none());
+    let newest = repo.list_dead_letters(None, 2, 0).await.unwrap();
+    assert_eq!(newest[1].id, b);
+
+    let requested = repo.request_replay(a).await.unwrap().unwrap();
+    assert_eq!(requested.state, DeadLetterState::ReplayRequested);
+    assert!(requested.replay_requested_at.is_some());
+    repo.request_replay(b).await.unwrap();
+    assert!(repo.request_replay(999).await.unwrap().is_none());
+    let due = repo.replays_requested("webhook", 10).await.unwrap();
+    assert_eq!(due.iter().map(|l| l.id).collect::<Vec<_>>(), vec![a, b]);
+    assert!(repo
+        .replays_requested("kafka", 10)
+        .await
+        .unwrap()
+        .is_empty());
+
+    repo.finish_replay(a, 1, None).await.unwrap();
+    repo.finish_replay(b, 1, Some("still down")).await.unwrap();
+    let replayed = repo.get_dead_letter(a).await.unwrap().unwrap();
+    assert_eq!(replayed.state, DeadLetterState::Replayed);
+    assert_eq!(replayed.attempts, 6);
+    // A replayed letter can't be replayed again.
+    let again = repo.request_replay(a).await.unwrap().unwrap();
+    assert_eq!(again.state, DeadLetterState::Replayed);
+    let parked = repo.get_dead_letter(b).await.unwrap().unwrap();
+    assert_eq!(
+        (parked.state, parked.error.as_str()),
+        (DeadLetterState::Dead, "still down")
+    );
+
+    let dead = repo
+        .list_dead_letters(Some(DeadLetterState::Dead), 10, 0)
+        .await
+        .unwrap();
+    assert_eq!(dead.len(), 2);
+    let depth = repo.dead_letter_depth().await.unwrap();
+    let counts: Vec<_> = depth
+        .iter()
+        .map(|d| (d.consumer.as_str(), d.state, d.count))
+        .collect();
+    assert_eq!(
+        counts,
+        vec![
+            ("kafka", DeadLetterState::Dead, 1),
+            ("webhook", DeadLetterState::Dead, 1),
+            ("webhook", DeadLetterState::Replayed, 1),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn pii_is_encrypted_at_rest_and_resealed_on_rotation() {
+    use orders_types::domain::events::OrderEvent;
+    use orders_types::domain::filter::OrderFilter;
+    use orders_types::domain::order::Order;
+    use orders_types::ports::field_encryption::StaticKeys;
+    use orders_types::ports::order_read_repository::{OrderProjection, OrderReadRepository};
+    use orders_types::ports::outbox::OutboxStore;
+    use std::sync::Arc;
+
+    const K1: &str = "1111111111111111111111111111111111111111111111111111111111111111";
+    const K2: &str = "2222222222222222222222222222222222222222222222222222222222222222";
+    let keys = |spec: &str| Arc::new(StaticKeys::parse(spec, K1).unwrap());
+    let (_dir, url) = temp_db_url();
+    let tenant = TenantId::default();
+    let order = |name: &str, email: &str| {
+        Order::new(
+            name.into(),
+            email.into(),
+            vec![OrderItem {
+                name: "Widget".into(),
+                qty: 1,
+                unit_price: Money::usd(100),
+                weight_grams: 0,
+                sku: None,
+                description: None,
+                metadata: Default::default(),
+                discount_cents: 0,
+            }],
+        )
+        .unwrap()
+    };
+    let pii = |o: &Order| (o.id, o.customer_name.clone(), o.email.clone());
+    let stored = |id: Uuid| {
+        let url = url.clone();
+        async move {
+            let pool = sqlx::SqlitePool::connect(&url).await.unwrap();
+            let row: (String, String) =
+                sqlx::query_as("SELECT customer_name, email FROM orders WHERE id = ?")
+                    .bind(id.to_string())
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap();
+            row
+        }
+    };
+
+    // Written before encryption was turned on.
+    let plain = SqliteRepo::new(&url).await.unwrap().with_outbox();
+    let legacy = plain.create(order("Ada", "ada@example.com")).await.unwrap();
+    assert_eq!(stored(legacy.id).await.1, "ada@example.com");
+
+    let repo = SqliteRepo::new(&url)
+        .await
+        .unwrap()
+        .with_outbox()
+        .with_field_encryption(keys(&format!("k1={K1}")));
+    let fresh = repo.create(order("Lee", "lee@example.com")).await.unwrap();
+    let (name, email) = stored(fresh.id).await;
+    assert!(name.starts_with("enc:v1:k1:") && email.starts_with("enc:v1:k1:"));
+    assert_eq!(
+        pii(&repo.get(&tenant, fresh.id).await.unwrap().unwrap()),
+        pii(&fresh)
+    );
+    assert_eq!(
+        pii(&repo.get(&tenant, legacy.id).await.unwrap().unwrap()),
+        pii(&legacy)
+    );
+    // Sealed emails are still found, case-insensitively, through the index.
+    let by_email = OrderFilter {
+        email: Some("LEE@example.com".into()),
+        ..Default::default()
+    };
+    let found = repo.list_filtered(&tenant, &by_email).await.unwrap();
+    assert_eq!(found.iter().map(|o| o.id).collect::<Vec<_>>(), [fresh.id]);
+    assert_eq!(repo.count(&tenant, &by_email).await.unwrap(), 1);
+    repo.project(&OrderEvent::Created {
+        order: fresh.clone(),
+    })
+    .await
+    .unwrap();
+    assert_eq!(repo.count_views(&tenant, &by_email).await.unwrap(), 1);
+    let records = repo.outbox_after(0, 10).await.unwrap();
+    assert!(
+        matches!(&records[1].event, OrderEvent::Created { order } if order.email == "lee@example.com")
+    );
+
+    // Rotate: k2 seals, k1 still opens until everything is resealed.
+    let rotated = SqliteRepo::new(&url)
+        .await
+        .unwrap()
+        .with_outbox()
+        .with_field_encryption(keys(&format!("k2={K2},k1={K1}")));
+    assert_eq!(
+        pii(&rotated.get(&tenant, fresh.id).await.unwrap().unwrap()),
+        pii(&fresh)
+    );
+    let report = rotated.reencrypt_pii().await.unwrap();
+    assert_eq!(
+        (report.orders, report.order_views, report.outbox),
+        (2, 1, 2)
+    );
+    assert!(stored(legacy.id).await.1.starts_with("enc:v1:k2:"));
+    assert_eq!(rotated.reencrypt_pii().await.unwrap().orders, 0);
+
+    let k2_only = SqliteRepo::new(&url)
+        .await
+        .unwrap()
+        .with_field_encryption(keys(&format!("k2={K2}")));
+    assert_eq!(
+        pii(&k2_only.get(&tenant, legacy.id).await.unwrap().unwrap()),
+        pii(&legacy)
+    );
+    assert_eq!(
+        pii(&k2_only.get_view(&tenant, fresh.id).await.unwrap().unwrap()),
+        pii(&fresh)
+    );
+    let by_ada = OrderFilter {
+        email: Some("ada@example.com".into()),
+        ..Default::default()
+    };
+    assert_eq!(k2_only.count(&tenant, &by_ada).await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn listing_a_hundred_thousand_orders_stays_within_budget() {
+    use orders_types::domain::filter::{OrderFilter, SortField, SortOrder};
+    use std::time::{Duration, Instant};
+
+    let (_dir, url) = temp_db_url();
+    let repo = SqliteRepo::new(&url).await.unwrap();
+    let tenant = TenantId::default();
+    let start = chrono::Utc::now() - chrono::Duration::days(30);
+    let statuses = [
+        OrderStatus::Pending,
+        OrderStatus::Confirmed,
+        OrderStatus::Shipped,
+        OrderStatus::Cancelled,
+    ];
+    for chunk in 0..10 {
+        let orders = (0..10_000)
+            .map(|i| {
+                let n = chunk * 10_000 + i;
+                let mut order = orders_types::domain::order::Order::new(
+                    format!("Customer {n}"),
+                    format!("customer{}@example.com", n % 5_000),
+                    vec![OrderItem {
+                        name: "Widget".into(),
+                        qty: 1,
+                        unit_price: Money::usd(100),
+                        weight_grams: 0,
+                        sku: None,
+                        description: None,
+                        metadata: Default::default(),
+                        discount_cents: 0,
+                    }],
+                )
+                .unwrap();
+                order.status = statuses[n % statuses.len()].clone();
+                order.created_at = start + chrono::Duration::seconds(n as i64 * 25);
+                order
+            })
+            .collect();
+        repo.create_many(orders).await.unwrap();
+    }
+
+    let page = |filter: OrderFilter| OrderFilter {
+        limit: Some(50),
+        sort: Some(SortField::CreatedAt),
+        order: Some(SortOrder::Desc),
+        ..filter
+    };
+    let cases = [
+        ("newest", page(OrderFilter::default()), 50),
+        (
+            "by status",
+            page(OrderFilter {
+                status: Some(OrderStatus::Shipped),
+                ..OrderFilter::default()
+            }),
+            50,
+        ),
+        (
+            "by email",
+            page(OrderFilter {
+                email: Some("CUSTOMER42@example.com".into()),
+                ..OrderFilter::default()
+            }),
+            20,
+        ),
+        (
+            "by status in a day",
+            page(OrderFilter {
+                status: Some(OrderStatus::Pending),
+                created_after: Some(start + chrono::Duration::days(10)),
+                created_before: Some(start + chrono::Duration::days(11)),
+                ..OrderFilter::default()
+            }),
+            50,
+        ),
+    ];
+    // Generous for a debug build on a shared runner; a full table scan and
+    // sort per query blows through it.
+    let budget = Duration::from_millis(250);
+    for (name, filter, expected) in cases {
+        let started = Instant::now();
+        let orders = repo.list_filtered(&tenant, &filter).await.unwrap();
+        let count = repo.count(&tenant, &filter).await.unwrap();
+        let elapsed = started.elapsed();
+        assert_eq!(orders.len(), expected, "{name}");
+        assert!(count >= expected, "{name}");
+        assert!(elapsed < budget, "{name} took {elapsed:?}");
+    }
+}
+
+#[tokio::test]
+async fn storing_an_order_twice_is_a_conflict() {
+    use orders_types::domain::order::Order;
+
+    let (_dir, url) = temp_db_url();
+    let repo = SqliteRepo::new(&url).await.unwrap();
+    let order = Order::new(
+        "Noor".into(),
+        "noor@example.com".into(),
+        vec![OrderItem {
+            name: "Widget".into(),
+            qty: 1,
+            unit_price: Money::usd(100),
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
+        }],
+    )
+    .unwrap();
+    repo.create(order.clone()).await.unwrap();
+    let err = repo.create(order).await.unwrap_err();
+    assert!(matches!(err, RepoError::Conflict(_)), "{err}");
 }
@@ -0,0 +1,228 @@
+//! [`proptest`] strategies for the domain types, and [`Arbitrary`] impls
+//! built on them. Only built with the `proptest` feature.
+//!
+//! Every value they produce passes [`Order::check`], so an adapter's
+//! property tests can store what they are given and compare what comes
+//! back. The strategies are public for tests that need to narrow one part,
+//! e.g. items in a single currency.
+
+use proptest::collection::{btree_map, vec};
+use proptest::prelude::*;
+use uuid::{Builder, Uuid};
+
+use crate::domain::money::{Currency, Money};
+use crate::domain::order::{Order, OrderItem, OrderStatus};
+use crate::domain::tenant::TenantId;
+use crate::fixtures::{FakeOrder, FakeOrders};
+
+/// Version 4 ids, drawn from the test's seed so failures replay.
+pub fn uuid() -> impl Strategy<Value = Uuid> {
+    any::<[u8; 16]>().prop_map(|bytes| Builder::from_random_bytes(bytes).into_uuid())
+}
+
+pub fn tenant_id() -> impl Strategy<Value = TenantId> {
+    prop_oneof![
+        Just(TenantId::default()),
+        "[A-Za-z0-9_-]{1,64}".prop_map(|s| TenantId::parse(&s).unwrap()),
+    ]
+}
+
+pub fn currency() -> impl Strategy<Value = Currency> {
+    prop_oneof![
+        Just(Currency::USD),
+        Just(Currency::EUR),
+        Just(Currency::GBP),
+        Just(Currency::JPY),
+    ]
+}
+
+pub fn order_status() -> impl Strategy<Value = OrderStatus> {
+    prop_oneof![
+        Just(OrderStatus::Pending),
+        Just(OrderStatus::Confirmed),
+        Just(OrderStatus::Shipped),
+        Just(OrderStatus::Cancelled),
+        Just(OrderStatus::Completed),
+    ]
+}
+
+/// A customer name and an email address.
+pub fn customer() -> impl Strategy<Value = (String, String)> {
+    (
+        "[A-Z][a-z]{0,15}( [A-Z][a-z'-]{0,15}){0,2}",
+        "[a-z0-9]{1,8}([._+-][a-z0-9]{1,8}){0,2}@[a-z0-9]([a-z0-9-]{0,18}[a-z0-9])?\\.[a-z]{2,6}",
+    )
+}
+
+/// A line priced in `currency`, with a discount of at most its price.
+pub fn order_item(currency: Currency) -> impl Strategy<Value = OrderItem> {
+    (
+        "[A-Za-z0-9][A-Za-z0-9 ()-]{0,40}",
+        1..=1_000u32,
+        0..=1_000_000i64,
+        0..=20_000u32,
+        proptest::option::of("[A-Za-z0-9][A-Za-z0-9._-]{0,63}"),
+        proptest::option::of("\\PC{0,80}"),
+        btree_map("[a-z][a-z0-9_]{0,15}", "\\PC{0,30}", 0..4),
+        0..=100i64,
+    )
+        .prop_map(
+            move |(name, qty, unit, weight_grams, sku, description, metadata, discount_pct)| {
+                let gross = unit * i64::from(qty);
+                OrderItem {
+                    name,
+                    qty,
+                    unit_price: Money::new(unit, currency),
+                    weight_grams,
+                    sku,
+                    description,
+                    metadata,
+                    discount_cents: gross * discount_pct / 100,
+                }
+            },
+        )
+}
+
+/// One to eight lines sharing a currency, as an order requires.
+pub fn order_items() -> impl Strategy<Value = Vec<OrderItem>> {
+    currency().prop_flat_map(|c| vec(order_item(c), 1..=8))
+}
+
+/// A new, pending order as [`Order::new`] makes it.
+pub fn new_order() -> impl Strategy<Value = Order> {
+    (customer(), order_items(), tenant_id(), uuid()).prop_map(
+        |((customer_name, email), items, tenant, id)| {
+            let mut order = Order::new(customer_name, email, items)
+                .expect("generated order data is valid")
+                .with_tenant(tenant);
+            order.id = id;
+            order
+        },
+    )
+}
+
+/// An order from [`FakeOrders`], in any status, with the history that led
+/// there.
+pub fn fake_order() -> impl Strategy<Value = FakeOrder> {
+    (any::<u64>(), tenant_id())
+        .prop_map(|(seed, tenant)| FakeOrders::new(seed).with_tenant(tenant).next_order())
+}
+
+impl Arbitrary for OrderStatus {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        order_status().boxed()
+    }
+}
+
+impl Arbitrary for OrderItem {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        currency().prop_flat_map(order_item).boxed()
+    }
+}
+
+/// New orders and orders further along their lifecycle.
+impl Arbitrary for Order {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        prop_oneof![new_order(), fake_order().prop_map(|f| f.order)].boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::pricing::PricingSnapshot;
+    use crate::ports::pricing::ItemPriceRules;
+
+    /// How far along the lifecycle a status is; transitions never go back.
+    fn rank(status: &OrderStatus) -> u8 {
+        match status {
+            OrderStatus::Pending => 0,
+            OrderStatus::Confirmed => 1,
+            OrderStatus::Shipped => 2,
+            OrderStatus::Completed | OrderStatus::Cancelled => 3,
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn generated_orders_pass_validation(order in any::<Order>()) {
+            let errors = Order::check(&order.customer_name, &order.email, &order.items);
+            prop_assert!(errors.is_empty(), "{errors:?}");
+        }
+
+        #[test]
+        fn the_total_is_the_sum_of_the_lines(order in new_order()) {
+            let gross: i64 = order
+                .items
+                .iter()
+                .map(|it| i64::from(it.qty) * it.unit_price.amount_minor())
+                .sum();
+            let discounts: i64 = order.items.iter().map(|it| it.discount_cents).sum();
+            prop_assert_eq!(order.charges.subtotal_cents, gross);
+            prop_assert_eq!(order.charges.discount_cents, discounts);
+            prop_assert_eq!(order.total.amount_minor(), gross - discounts);
+            prop_assert_eq!(order.total.currency(), order.items[0].unit_price.currency());
+        }
+
+        #[test]
+        fn frozen_pricing_keeps_the_total(mut order in new_order()) {
+            let total = order.total;
+            prop_assert!(order.freeze_pricing(PricingSnapshot::compute(&order.items, &ItemPriceRules).unwrap()));
+            prop_assert_eq!(order.total, total);
+        }
+
+        #[test]
+        fn transitions_only_move_forward(from in any::<OrderStatus>(), to in any::<OrderStatus>()) {
+            prop_assert!(from.can_transition_to(&from));
+            if from != to && from.can_transition_to(&to) {
+                prop_assert!(rank(&to) > rank(&from), "{from:?} -> {to:?}");
+                prop_assert!(!to.can_transition_to(&from), "{to:?} -> {from:?}");
+            }
+            if matches!(from, OrderStatus::Cancelled | OrderStatus::Completed) {
+                prop_assert_eq!(from.can_transition_to(&to), from == to);
+            }
+        }
+
+        #[test]
+        fn histories_are_legal_paths(FakeOrder { order, history } in fake_order()) {
+            prop_assert_eq!(history[0].from.as_ref(), None);
+            prop_assert_eq!(&history[0].to, &OrderStatus::Pending);
+            for pair in history.windows(2) {
+                let from = pair[1].from.as_ref().unwrap();
+                prop_assert_eq!(from, &pair[0].to);
+                prop_assert!(from.can_transition_to(&pair[1].to));
+            }
+            prop_assert_eq!(&history.last().unwrap().to, &order.status);
+        }
+
+        #[test]
+        fn orders_survive_a_serde_round_trip(order in any::<Order>()) {
+            let json = serde_json::to_value(&order).unwrap();
+            let back: Order = serde_json::from_value(json.clone()).unwrap();
+            prop_assert_eq!(serde_json::to_value(&back).unwrap(), json);
+        }
+
+        #[test]
+        fn items_and_statuses_survive_a_serde_round_trip(
+            item in any::<OrderItem>(),
+            status in any::<OrderStatus>(),
+        ) {
+            let back: OrderItem = serde_json::from_str(&serde_json::to_string(&item).unwrap()).unwrap();
+            prop_assert_eq!(
+                serde_json::to_value(back).unwrap(),
+                serde_json::to_value(&item).unwrap()
+            );
+            let back: OrderStatus = serde_json::from_str(&serde_json::to_string(&status).unwrap()).unwrap();
+            prop_assert_eq!(back, status);
+        }
+    }
+}
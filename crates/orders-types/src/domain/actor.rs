@@ -0,0 +1,93 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Who made a change. Serialized, and kept in the audit log and status
+/// history, as its string form: `user:<sub>`, `key:<id>`, `bootstrap`,
+/// `anonymous` or `system`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Actor {
+    /// An end user, by the `sub` claim of their verified bearer token.
+    User(String),
+    /// A stored API key, by id.
+    ApiKey(Uuid),
+    /// The bootstrap admin key from config.
+    Bootstrap,
+    /// A request without credentials, when API keys are off.
+    Anonymous,
+    /// Work not started by a request: background jobs, CLI seeding.
+    #[default]
+    System,
+}
+
+impl Actor {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "bootstrap" => return Ok(Self::Bootstrap),
+            "anonymous" => return Ok(Self::Anonymous),
+            "system" => return Ok(Self::System),
+            _ => {}
+        }
+        if let Some(sub) = s.strip_prefix("user:").filter(|sub| !sub.is_empty()) {
+            return Ok(Self::User(sub.to_string()));
+        }
+        if let Some(id) = s
+            .strip_prefix("key:")
+            .and_then(|id| Uuid::parse_str(id).ok())
+        {
+            return Ok(Self::ApiKey(id));
+        }
+        Err(format!("unknown actor `{s}`"))
+    }
+}
+
+impl fmt::Display for Actor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::User(sub) => write!(f, "user:{sub}"),
+            Self::ApiKey(id) => write!(f, "key:{id}"),
+            Self::Bootstrap => f.write_str("bootstrap"),
+            Self::Anonymous => f.write_str("anonymous"),
+            Self::System => f.write_str("system"),
+        }
+    }
+}
+
+impl TryFrom<String> for Actor {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Actor::parse(&s)
+    }
+}
+
+impl From<Actor> for String {
+    fn from(actor: Actor) -> Self {
+        actor.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_its_string_form() {
+        let key = Uuid::new_v4();
+        for actor in [
+            Actor::User("u-42".into()),
+            Actor::ApiKey(key),
+            Actor::Bootstrap,
+            Actor::Anonymous,
+            Actor::System,
+        ] {
+            assert_eq!(Actor::parse(&actor.to_string()), Ok(actor));
+        }
+        assert_eq!(Actor::ApiKey(key).to_string(), format!("key:{key}"));
+        assert!(Actor::parse("key:nope").is_err());
+        assert!(Actor::parse("user:").is_err());
+        assert!(Actor::parse("alice").is_err());
+    }
+}
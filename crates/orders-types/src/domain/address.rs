@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::order::FieldError;
+
+/// A postal address for shipping or billing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Address {
+    pub line1: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line2: Option<String>,
+    pub city: String,
+    /// State, province or county, where the country has them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    pub postal_code: String,
+    /// ISO 3166-1 alpha-2 code, e.g. `DE`.
+    pub country: String,
+}
+
+impl Address {
+    pub const MAX_LINE_LEN: usize = 200;
+    pub const MAX_POSTAL_CODE_LEN: usize = 16;
+
+    /// Every problem with the address, addressed relative to `path` (e.g.
+    /// `shipping_address`); empty when it is valid.
+    pub fn check(&self, path: &str) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        let mut text = |field: &str, value: &str, required: bool| {
+            if required && value.trim().is_empty() {
+                errors.push(FieldError::new(
+                    format!("{path}.{field}"),
+                    "must not be empty",
+                ));
+            } else if value.chars().count() > Self::MAX_LINE_LEN {
+                errors.push(FieldError::new(
+                    format!("{path}.{field}"),
+                    format!("must be at most {} characters", Self::MAX_LINE_LEN),
+                ));
+            }
+        };
+        text("line1", &self.line1, true);
+        text("line2", self.line2.as_deref().unwrap_or_default(), false);
+        text("city", &self.city, true);
+        text("region", self.region.as_deref().unwrap_or_default(), false);
+
+        let postal = self.postal_code.trim();
+        if postal.is_empty()
+            || postal.len() > Self::MAX_POSTAL_CODE_LEN
+            || !postal
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == ' ' || c == '-')
+        {
+            errors.push(FieldError::new(
+                format!("{path}.postal_code"),
+                format!(
+                    "must be 1 to {} letters, digits, spaces or `-`",
+                    Self::MAX_POSTAL_CODE_LEN
+                ),
+            ));
+        }
+        if !is_country_code(&self.country) {
+            errors.push(FieldError::new(
+                format!("{path}.country"),
+                "must be an ISO 3166-1 alpha-2 code, e.g. `DE`",
+            ));
+        }
+        errors
+    }
+}
+
+fn is_country_code(code: &str) -> bool {
+    code.len() == 2 && code.chars().all(|c| c.is_ascii_uppercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address() -> Address {
+        Address {
+            line1: "1 Main St".into(),
+            line2: None,
+            city: "Springfield".into(),
+            region: Some("IL".into()),
+            postal_code: "62701".into(),
+            country: "US".into(),
+        }
+    }
+
+    #[test]
+    fn valid_addresses_pass() {
+        assert!(address().check("shipping_address").is_empty());
+    }
+
+    #[test]
+    fn check_reports_every_field() {
+        let bad = Address {
+            line1: " ".into(),
+            line2: Some("x".repeat(Address::MAX_LINE_LEN + 1)),
+            city: String::new(),
+            region: None,
+            postal_code: "62701!".into(),
+            country: "usa".into(),
+        };
+        let fields: Vec<_> = bad
+            .check("billing_address")
+            .into_iter()
+            .map(|e| e.field)
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                "billing_address.line1",
+                "billing_address.line2",
+                "billing_address.city",
+                "billing_address.postal_code",
+                "billing_address.country"
+            ]
+        );
+    }
+}
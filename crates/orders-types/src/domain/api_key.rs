@@ -0,0 +1,156 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::tenant::TenantId;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    Read,
+    Write,
+    Admin,
+}
+
+impl Scope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::Read => "read",
+            Scope::Write => "write",
+            Scope::Admin => "admin",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "read" => Some(Scope::Read),
+            "write" => Some(Scope::Write),
+            "admin" => Some(Scope::Admin),
+            _ => None,
+        }
+    }
+}
+
+/// Coarse caller role used by the order service's access policy.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Viewer,
+    Operator,
+    Admin,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Viewer => "viewer",
+            Role::Operator => "operator",
+            Role::Admin => "admin",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "viewer" => Some(Role::Viewer),
+            "operator" => Some(Role::Operator),
+            "admin" => Some(Role::Admin),
+            _ => None,
+        }
+    }
+
+    /// Role implied by a set of scopes when none is assigned explicitly.
+    pub fn from_scopes(scopes: &[Scope]) -> Self {
+        if scope_allows(scopes, Scope::Admin) {
+            Role::Admin
+        } else if scope_allows(scopes, Scope::Write) {
+            Role::Operator
+        } else {
+            Role::Viewer
+        }
+    }
+}
+
+/// A stored API key. Only the hash of the secret is kept; the plaintext is
+/// shown once when the key is minted.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub name: String,
+    #[serde(skip_serializing)]
+    pub key_hash: String,
+    pub scopes: Vec<Scope>,
+    /// Explicit role; derived from `scopes` when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<Role>,
+    /// The only tenant the key may act for; keys from before tenant binding
+    /// belong to the default one.
+    #[serde(default)]
+    pub tenant: TenantId,
+    pub created_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl ApiKey {
+    pub fn new(name: String, key_hash: String, scopes: Vec<Scope>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name,
+            key_hash,
+            scopes,
+            role: None,
+            tenant: TenantId::default(),
+            created_at: Utc::now(),
+            revoked_at: None,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.revoked_at.is_none()
+    }
+
+    pub fn with_role(mut self, role: Role) -> Self {
+        self.role = Some(role);
+        self
+    }
+
+    pub fn with_tenant(mut self, tenant: TenantId) -> Self {
+        self.tenant = tenant;
+        self
+    }
+
+    pub fn effective_role(&self) -> Role {
+        self.role.unwrap_or_else(|| Role::from_scopes(&self.scopes))
+    }
+}
+
+/// `admin` implies every other scope, `write` implies `read`.
+pub fn scope_allows(granted: &[Scope], scope: Scope) -> bool {
+    granted.iter().any(|g| match g {
+        Scope::Admin => true,
+        Scope::Write => matches!(scope, Scope::Write | Scope::Read),
+        Scope::Read => scope == Scope::Read,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scopes_are_hierarchical() {
+        assert!(scope_allows(&[Scope::Admin], Scope::Write));
+        assert!(scope_allows(&[Scope::Write], Scope::Read));
+        assert!(!scope_allows(&[Scope::Write], Scope::Admin));
+        assert!(!scope_allows(&[Scope::Read], Scope::Write));
+        assert!(!scope_allows(&[], Scope::Read));
+    }
+
+    #[test]
+    fn role_defaults_from_scopes_unless_explicit() {
+        let key = ApiKey::new("k".into(), "h".into(), vec![Scope::Read, Scope::Write]);
+        assert_eq!(key.effective_role(), Role::Operator);
+        assert_eq!(key.with_role(Role::Viewer).effective_role(), Role::Viewer);
+        assert_eq!(Role::from_scopes(&[Scope::Admin]), Role::Admin);
+        assert_eq!(Role::from_scopes(&[]), Role::Viewer);
+    }
+}
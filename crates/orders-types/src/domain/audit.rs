@@ -0,0 +1,194 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::order::Order;
+use crate::domain::pricing::PricingDiff;
+use crate::domain::tenant::TenantId;
+
+/// What a mutation did to an order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Created,
+    /// Items or pricing changed; the status did not.
+    Updated,
+    StatusChanged,
+    /// An admin forced the status past the transition rules.
+    StatusOverridden,
+    /// Frozen pricing recomputed against the current rules.
+    Repriced,
+    Deleted,
+    /// Included in a customer data export.
+    Exported,
+    /// The customer's personal data was erased.
+    Anonymized,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::Created => "created",
+            AuditAction::Updated => "updated",
+            AuditAction::StatusChanged => "status_changed",
+            AuditAction::StatusOverridden => "status_overridden",
+            AuditAction::Repriced => "repriced",
+            AuditAction::Deleted => "deleted",
+            AuditAction::Exported => "exported",
+            AuditAction::Anonymized => "anonymized",
+        }
+    }
+
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "created" => Ok(AuditAction::Created),
+            "updated" => Ok(AuditAction::Updated),
+            "status_changed" => Ok(AuditAction::StatusChanged),
+            "status_overridden" => Ok(AuditAction::StatusOverridden),
+            "repriced" => Ok(AuditAction::Repriced),
+            "deleted" => Ok(AuditAction::Deleted),
+            "exported" => Ok(AuditAction::Exported),
+            "anonymized" => Ok(AuditAction::Anonymized),
+            other => Err(format!("unknown audit action `{other}`")),
+        }
+    }
+}
+
+/// One recorded mutation of an order, with the order before and after it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub id: Uuid,
+    #[serde(default)]
+    pub tenant_id: TenantId,
+    pub order_id: Uuid,
+    pub action: AuditAction,
+    /// Who made the change, e.g. `key:<id>`, or `system` for work not
+    /// started by an API caller.
+    pub actor: String,
+    pub at: DateTime<Utc>,
+    /// Absent for [`AuditAction::Created`].
+    pub before: Option<Order>,
+    /// Absent for [`AuditAction::Deleted`].
+    pub after: Option<Order>,
+    /// Why the change was made; only kept for
+    /// [`AuditAction::StatusOverridden`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// How the pricing moved; only kept for [`AuditAction::Repriced`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pricing_diff: Option<PricingDiff>,
+}
+
+impl AuditEntry {
+    fn record(
+        order: &Order,
+        action: AuditAction,
+        actor: String,
+        before: Option<Order>,
+        after: Option<Order>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            tenant_id: order.tenant_id.clone(),
+            order_id: order.id,
+            action,
+            actor,
+            at: Utc::now(),
+            before,
+            after,
+            reason: None,
+            pricing_diff: None,
+        }
+    }
+
+    pub fn created(actor: impl Into<String>, order: Order) -> Self {
+        Self::record(
+            &order,
+            AuditAction::Created,
+            actor.into(),
+            None,
+            Some(order.clone()),
+        )
+    }
+
+    /// `before` and `after` are the same order; [`AuditAction::StatusChanged`]
+    /// when its status differs, [`AuditAction::Updated`] otherwise.
+    pub fn changed(actor: impl Into<String>, before: Order, after: Order) -> Self {
+        let action = if before.status == after.status {
+            AuditAction::Updated
+        } else {
+            AuditAction::StatusChanged
+        };
+        Self::record(
+            &after,
+            action,
+            actor.into(),
+            Some(before),
+            Some(after.clone()),
+        )
+    }
+
+    /// An admin override of the status, with the reason given for it.
+    pub fn overridden(
+        actor: impl Into<String>,
+        before: Order,
+        after: Order,
+        reason: impl Into<String>,
+    ) -> Self {
+        Self {
+            reason: Some(reason.into()),
+            ..Self::record(
+                &after,
+                AuditAction::StatusOverridden,
+                actor.into(),
+                Some(before),
+                Some(after.clone()),
+            )
+        }
+    }
+
+    /// A re-pricing of the order, with the before/after diff of its pricing.
+    pub fn repriced(
+        actor: impl Into<String>,
+        before: Order,
+        after: Order,
+        diff: PricingDiff,
+    ) -> Self {
+        Self {
+            pricing_diff: Some(diff),
+            ..Self::record(
+                &after,
+                AuditAction::Repriced,
+                actor.into(),
+                Some(before),
+                Some(after.clone()),
+            )
+        }
+    }
+
+    /// No copies: the export itself holds the data.
+    pub fn exported(actor: impl Into<String>, order: &Order) -> Self {
+        Self::record(order, AuditAction::Exported, actor.into(), None, None)
+    }
+
+    /// Only the anonymized order; a `before` copy would keep what was erased.
+    pub fn anonymized(actor: impl Into<String>, order: Order) -> Self {
+        Self::record(
+            &order,
+            AuditAction::Anonymized,
+            actor.into(),
+            None,
+            Some(order.clone()),
+        )
+    }
+
+    pub fn deleted(actor: impl Into<String>, order: Order) -> Self {
+        Self::record(
+            &order,
+            AuditAction::Deleted,
+            actor.into(),
+            Some(order.clone()),
+            None,
+        )
+    }
+}
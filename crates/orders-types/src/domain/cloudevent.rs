@@ -0,0 +1,137 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::correlation::CorrelationId;
+use crate::domain::events::OrderEvent;
+use crate::domain::outbox::OutboxRecord;
+
+/// The CloudEvents version every envelope declares.
+pub const SPEC_VERSION: &str = "1.0";
+/// `Content-Type` of a CloudEvent sent whole as the body (structured mode).
+pub const CONTENT_TYPE: &str = "application/cloudevents+json";
+/// `source` of events published by this service unless configured
+/// otherwise.
+pub const DEFAULT_SOURCE: &str = "/orders";
+
+/// A [CloudEvents 1.0](https://github.com/cloudevents/spec/blob/v1.0.2/cloudevents/spec.md)
+/// envelope in its JSON format: the context attributes plus `data`. Every
+/// published order event and webhook delivery has this shape, e.g.
+///
+/// ```json
+/// {"specversion":"1.0","id":"42","source":"/orders","type":"orders.order.created",
+///  "subject":"<order id>","time":"...","datacontenttype":"application/json",
+///  "tenantid":"default","data":{"type":"created","order":{...}}}
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CloudEvent<T> {
+    pub specversion: String,
+    /// Unique within `source`; a redelivery keeps its id, so receivers can
+    /// drop ids they have seen.
+    pub id: String,
+    pub source: String,
+    /// `orders.order.<created|updated|deleted>` for order events.
+    #[serde(rename = "type")]
+    pub event_type: String,
+    /// The order the event is about.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    pub time: DateTime<Utc>,
+    pub datacontenttype: String,
+    /// Extension: the tenant the order belongs to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenantid: Option<String>,
+    /// Extension: the request that caused the event, when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlationid: Option<String>,
+    /// The `sequence` extension: the event's outbox position, zero-padded so
+    /// it also orders as a string.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<String>,
+    /// Extension: set on synthetic deliveries, which receivers should ignore.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub test: bool,
+    pub data: T,
+}
+
+/// What every order event is published as.
+pub type OrderCloudEvent = CloudEvent<OrderEvent>;
+
+impl CloudEvent<OrderEvent> {
+    pub fn order(
+        id: impl Into<String>,
+        source: impl Into<String>,
+        time: DateTime<Utc>,
+        event: OrderEvent,
+    ) -> Self {
+        Self {
+            specversion: SPEC_VERSION.to_string(),
+            id: id.into(),
+            source: source.into(),
+            event_type: format!("orders.order.{}", event.kind()),
+            subject: Some(event.order_id().to_string()),
+            time,
+            datacontenttype: "application/json".to_string(),
+            tenantid: Some(event.tenant_id().to_string()),
+            correlationid: None,
+            sequence: None,
+            test: false,
+            data: event,
+        }
+    }
+
+    /// `record` from `source`, with its outbox position as both `id` and
+    /// `sequence`.
+    pub fn from_outbox(record: &OutboxRecord, source: impl Into<String>) -> Self {
+        let mut event = Self::order(
+            record.seq.to_string(),
+            source,
+            record.recorded_at,
+            record.event.clone(),
+        );
+        event.sequence = Some(format!("{:020}", record.seq));
+        event
+    }
+
+    pub fn with_correlation_id(mut self, id: &CorrelationId) -> Self {
+        self.correlationid = Some(id.to_string());
+        self
+    }
+
+    pub fn with_test(mut self, test: bool) -> Self {
+        self.test = test;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::tenant::TenantId;
+    use uuid::Uuid;
+
+    #[test]
+    fn outbox_records_become_order_cloud_events() {
+        let id = Uuid::new_v4();
+        let record = OutboxRecord {
+            seq: 42,
+            recorded_at: Utc::now(),
+            event: OrderEvent::Deleted {
+                id,
+                tenant_id: TenantId::parse("acme").unwrap(),
+            },
+        };
+        let event = CloudEvent::from_outbox(&record, DEFAULT_SOURCE);
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["specversion"], "1.0");
+        assert_eq!(json["id"], "42");
+        assert_eq!(json["type"], "orders.order.deleted");
+        assert_eq!(json["subject"], id.to_string());
+        assert_eq!(json["tenantid"], "acme");
+        assert_eq!(json["sequence"], "00000000000000000042");
+        assert_eq!(json["data"]["type"], "deleted");
+        assert!(json.get("correlationid").is_none() && json.get("test").is_none());
+
+        let back: OrderCloudEvent = serde_json::from_value(json).unwrap();
+        assert_eq!(back.data.order_id(), id);
+    }
+}
@@ -0,0 +1,101 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Ties together everything one inbound request causes: its log lines, the
+/// events it publishes and the deliveries those events trigger.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CorrelationId(String);
+
+impl CorrelationId {
+    /// Header carrying the id in requests, responses and webhook deliveries.
+    pub const HEADER: &'static str = "x-correlation-id";
+
+    pub fn new() -> Self {
+        Self(Uuid::new_v4().to_string())
+    }
+
+    /// 1-128 ASCII letters, digits, `-`, `_`, `.` or `:`, so caller-chosen
+    /// ids are safe to echo into headers and logs.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let valid = !s.is_empty()
+            && s.len() <= 128
+            && s.bytes()
+                .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b':'));
+        if valid {
+            Ok(Self(s.to_string()))
+        } else {
+            Err(format!("invalid correlation id `{s}`"))
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Default for CorrelationId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for CorrelationId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Names one inbound request, unlike a [`CorrelationId`] that may span
+/// several; error bodies report it as `request_id`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RequestId(String);
+
+impl RequestId {
+    pub const HEADER: &'static str = "x-request-id";
+
+    pub fn new() -> Self {
+        Self(Uuid::new_v4().to_string())
+    }
+
+    /// Same rules as [`CorrelationId::parse`].
+    pub fn parse(s: &str) -> Result<Self, String> {
+        CorrelationId::parse(s)
+            .map(|id| Self(id.0))
+            .map_err(|_| format!("invalid request id `{s}`"))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Default for RequestId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_accepts_trace_ids_and_rejects_header_injection() {
+        let id = CorrelationId::parse("req-01:abc.def").unwrap();
+        assert_eq!(id.as_str(), "req-01:abc.def");
+        assert!(CorrelationId::parse("").is_err());
+        assert!(CorrelationId::parse("a\r\nx-evil: 1").is_err());
+        assert!(CorrelationId::parse(&"x".repeat(129)).is_err());
+        assert!(CorrelationId::parse(CorrelationId::new().as_str()).is_ok());
+    }
+}
@@ -0,0 +1,64 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::outbox::OutboxRecord;
+
+/// Where a [`DeadLetter`] stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeadLetterState {
+    /// Parked after its sink gave up on it.
+    Dead,
+    /// An operator asked for it to be delivered again; the relay picks it up
+    /// on its next poll.
+    ReplayRequested,
+    /// Delivered by a replay.
+    Replayed,
+}
+
+impl DeadLetterState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeadLetterState::Dead => "dead",
+            DeadLetterState::ReplayRequested => "replay_requested",
+            DeadLetterState::Replayed => "replayed",
+        }
+    }
+
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "dead" => Ok(DeadLetterState::Dead),
+            "replay_requested" => Ok(DeadLetterState::ReplayRequested),
+            "replayed" => Ok(DeadLetterState::Replayed),
+            other => Err(format!(
+                "unknown dead letter state `{other}` (expected dead, replay_requested or replayed)"
+            )),
+        }
+    }
+}
+
+/// An outbox record a sink could not deliver within its attempts, kept so
+/// it can be replayed once the receiver is back.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub id: u64,
+    /// The sink that gave up, by its checkpoint name.
+    pub consumer: String,
+    pub record: OutboxRecord,
+    /// Deliveries tried, over the original run and every replay.
+    pub attempts: u32,
+    /// Why the last attempt failed.
+    pub error: String,
+    pub failed_at: DateTime<Utc>,
+    pub state: DeadLetterState,
+    pub replay_requested_at: Option<DateTime<Utc>>,
+    pub replayed_at: Option<DateTime<Utc>>,
+}
+
+/// How many dead letters `consumer` has in `state`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeadLetterDepth {
+    pub consumer: String,
+    pub state: DeadLetterState,
+    pub count: u64,
+}
@@ -0,0 +1,218 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::money::Money;
+use crate::domain::order::FieldError;
+use crate::domain::tenant::TenantId;
+
+/// How much a discount takes off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum DiscountKind {
+    /// A share of the order total in basis points (`1000` is 10%).
+    Percentage { bps: u32 },
+    /// A fixed amount, only usable on orders in the same currency.
+    Fixed { amount: Money },
+}
+
+/// A redeemable discount code, scoped to one tenant.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Discount {
+    pub code: String,
+    #[serde(default)]
+    pub tenant_id: TenantId,
+    pub kind: DiscountKind,
+    /// Smallest order total, before the discount, it applies to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_order: Option<Money>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Redemptions allowed in total; unlimited when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_uses: Option<u32>,
+    #[serde(default)]
+    pub uses: u32,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Why a discount can't be applied to an order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiscountRefusal {
+    Expired,
+    UsedUp,
+    BelowMinimum(Money),
+    CurrencyMismatch,
+}
+
+impl fmt::Display for DiscountRefusal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiscountRefusal::Expired => f.write_str("has expired"),
+            DiscountRefusal::UsedUp => f.write_str("has no uses left"),
+            DiscountRefusal::BelowMinimum(min) => {
+                write!(f, "needs an order total of at least {min}")
+            }
+            DiscountRefusal::CurrencyMismatch => {
+                f.write_str("is not valid for orders in this currency")
+            }
+        }
+    }
+}
+
+/// The discount taken off an order, recorded when it is created.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppliedDiscount {
+    pub code: String,
+    pub amount_cents: i64,
+}
+
+impl Discount {
+    /// Codes are matched case-insensitively and stored uppercase.
+    pub fn normalize_code(code: &str) -> String {
+        code.trim().to_ascii_uppercase()
+    }
+
+    pub fn new(code: &str, kind: DiscountKind) -> Self {
+        Self {
+            code: Self::normalize_code(code),
+            tenant_id: TenantId::default(),
+            kind,
+            min_order: None,
+            expires_at: None,
+            max_uses: None,
+            uses: 0,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Every problem with the definition itself; empty when it can be stored.
+    pub fn check(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        if self.code.is_empty()
+            || !self
+                .code
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+        {
+            errors.push(FieldError::new(
+                "code",
+                "must be letters, digits, `-` or `_`",
+            ));
+        }
+        match self.kind {
+            DiscountKind::Percentage { bps } if bps == 0 || bps > 10_000 => {
+                errors.push(FieldError::new("kind.bps", "must be between 1 and 10000"))
+            }
+            DiscountKind::Fixed { amount } if amount.amount_minor() <= 0 => {
+                errors.push(FieldError::new("kind.amount", "must be positive"))
+            }
+            _ => {}
+        }
+        errors
+    }
+
+    /// Whether this code may be used on an order totalling `total` at `now`,
+    /// and if so how much it takes off (never more than the total).
+    pub fn amount_off(&self, total: Money, now: DateTime<Utc>) -> Result<i64, DiscountRefusal> {
+        if self.expires_at.is_some_and(|at| at <= now) {
+            return Err(DiscountRefusal::Expired);
+        }
+        if self.max_uses.is_some_and(|max| self.uses >= max) {
+            return Err(DiscountRefusal::UsedUp);
+        }
+        if let Some(min) = self.min_order {
+            if min.currency() != total.currency() {
+                return Err(DiscountRefusal::CurrencyMismatch);
+            }
+            if total.amount_minor() < min.amount_minor() {
+                return Err(DiscountRefusal::BelowMinimum(min));
+            }
+        }
+        let off = match self.kind {
+            // Widened, as `total * bps` need not fit an i64; the result,
+            // clamped to the total, does.
+            DiscountKind::Percentage { bps } => {
+                i128::from(total.amount_minor()) * i128::from(bps) / 10_000
+            }
+            DiscountKind::Fixed { amount } => {
+                if amount.currency() != total.currency() {
+                    return Err(DiscountRefusal::CurrencyMismatch);
+                }
+                i128::from(amount.amount_minor())
+            }
+        };
+        let off = off.clamp(0, i128::from(total.amount_minor().max(0)));
+        Ok(i64::try_from(off).expect("clamped to an i64"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::money::Currency;
+
+    #[test]
+    fn percentage_and_fixed_amounts_never_exceed_the_total() {
+        let now = Utc::now();
+        let ten_percent = Discount::new("save10", DiscountKind::Percentage { bps: 1_000 });
+        assert_eq!(ten_percent.code, "SAVE10");
+        assert_eq!(ten_percent.amount_off(Money::usd(2_599), now), Ok(259));
+        assert_eq!(
+            ten_percent.amount_off(Money::usd(i64::MAX), now),
+            Ok(i64::MAX / 10)
+        );
+
+        let five_off = Discount::new(
+            "FIVE",
+            DiscountKind::Fixed {
+                amount: Money::usd(500),
+            },
+        );
+        assert_eq!(five_off.amount_off(Money::usd(2_000), now), Ok(500));
+        assert_eq!(five_off.amount_off(Money::usd(300), now), Ok(300));
+        assert_eq!(
+            five_off.amount_off(Money::new(2_000, Currency::EUR), now),
+            Err(DiscountRefusal::CurrencyMismatch)
+        );
+    }
+
+    #[test]
+    fn expiry_usage_and_minimum_are_enforced() {
+        let now = Utc::now();
+        let mut d = Discount::new("SPRING", DiscountKind::Percentage { bps: 500 });
+        d.expires_at = Some(now - chrono::Duration::seconds(1));
+        assert_eq!(
+            d.amount_off(Money::usd(100), now),
+            Err(DiscountRefusal::Expired)
+        );
+
+        d.expires_at = None;
+        d.max_uses = Some(2);
+        d.uses = 2;
+        assert_eq!(
+            d.amount_off(Money::usd(100), now),
+            Err(DiscountRefusal::UsedUp)
+        );
+
+        d.uses = 1;
+        d.min_order = Some(Money::usd(5_000));
+        assert_eq!(
+            d.amount_off(Money::usd(4_999), now),
+            Err(DiscountRefusal::BelowMinimum(Money::usd(5_000)))
+        );
+        assert_eq!(d.amount_off(Money::usd(5_000), now), Ok(250));
+    }
+
+    #[test]
+    fn definitions_are_checked() {
+        let bad = Discount::new("no spaces", DiscountKind::Percentage { bps: 20_000 });
+        let errors = bad.check();
+        let fields: Vec<_> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, ["code", "kind.bps"]);
+        assert!(Discount::new("OK-1", DiscountKind::Percentage { bps: 1 })
+            .check()
+            .is_empty());
+    }
+}
@@ -0,0 +1,126 @@
+//! Customer email addresses: the syntax an order accepts and the form it is
+//! stored in.
+
+/// Longest local part (before `@`) RFC 5321 allows.
+pub const MAX_LOCAL_LEN: usize = 64;
+/// Longest domain RFC 5321 allows.
+pub const MAX_DOMAIN_LEN: usize = 253;
+
+/// `email` as stored: surrounding whitespace trimmed and lowercased. Local
+/// parts are case-sensitive in theory, but no mail provider treats them so,
+/// and one spelling per customer keeps lookups and duplicates simple.
+pub fn normalize(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+/// Whether `email` is an `addr-spec` in the common dot-atom form of RFC
+/// 5322: a local part of letters, digits and ``!#$%&'*+-/=?^_`{|}~``
+/// separated by single dots, `@`, and a host name within the RFC 5321
+/// lengths. Quoted local parts, address literals such as `user@[10.0.0.1]`
+/// and non-ASCII addresses are refused; hardly any checkout can deliver to
+/// them. The message says what is wrong, for a `422`.
+pub fn check(email: &str) -> Result<(), String> {
+    if email.is_empty() {
+        return Err("must not be empty".into());
+    }
+    if email.chars().any(char::is_whitespace) {
+        return Err("must not contain spaces".into());
+    }
+    let Some((local, domain)) = email.split_once('@') else {
+        return Err("must contain `@`".into());
+    };
+    if domain.contains('@') {
+        return Err("must contain only one `@`".into());
+    }
+    check_local(local)?;
+    check_domain(domain)
+}
+
+fn check_local(local: &str) -> Result<(), String> {
+    if local.is_empty() {
+        return Err("must have a name before `@`".into());
+    }
+    if local.len() > MAX_LOCAL_LEN {
+        return Err(format!(
+            "the part before `@` must be at most {MAX_LOCAL_LEN} characters"
+        ));
+    }
+    if let Some(c) = local
+        .chars()
+        .find(|&c| !(c.is_ascii_alphanumeric() || c == '.' || "!#$%&'*+-/=?^_`{|}~".contains(c)))
+    {
+        return Err(format!("`{c}` is not allowed before `@`"));
+    }
+    if local.starts_with('.') || local.ends_with('.') || local.contains("..") {
+        return Err("the part before `@` must not start or end with `.` or contain `..`".into());
+    }
+    Ok(())
+}
+
+fn check_domain(domain: &str) -> Result<(), String> {
+    if domain.is_empty() {
+        return Err("must have a domain after `@`".into());
+    }
+    if domain.len() > MAX_DOMAIN_LEN {
+        return Err(format!(
+            "the domain must be at most {MAX_DOMAIN_LEN} characters"
+        ));
+    }
+    let valid_label = |label: &str| {
+        (1..=63).contains(&label.len())
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    };
+    if !domain.split('.').all(valid_label) {
+        return Err(format!("`{domain}` is not a valid domain"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_case_and_whitespace() {
+        assert_eq!(normalize("  Ann.Lee@Example.COM\n"), "ann.lee@example.com");
+    }
+
+    #[test]
+    fn accepts_dot_atoms_and_says_what_is_wrong_otherwise() {
+        for ok in [
+            "ann@example.com",
+            "ann.lee+orders@mail.example.co.uk",
+            "o'neil@example.com",
+            "erased@invalid",
+            "a@xn--bcher-kva.example",
+        ] {
+            assert_eq!(check(ok), Ok(()), "{ok}");
+        }
+        for (bad, message) in [
+            ("", "must not be empty"),
+            ("ann lee@example.com", "must not contain spaces"),
+            ("ann.example.com", "must contain `@`"),
+            ("ann@lee@example.com", "must contain only one `@`"),
+            ("@example.com", "must have a name before `@`"),
+            ("ann@", "must have a domain after `@`"),
+            ("ann(x)@example.com", "`(` is not allowed before `@`"),
+            (
+                ".ann@example.com",
+                "the part before `@` must not start or end with `.` or contain `..`",
+            ),
+            (
+                "ann..lee@example.com",
+                "the part before `@` must not start or end with `.` or contain `..`",
+            ),
+            ("ann@example..com", "`example..com` is not a valid domain"),
+            ("ann@-example.com", "`-example.com` is not a valid domain"),
+            ("ann@[10.0.0.1]", "`[10.0.0.1]` is not a valid domain"),
+        ] {
+            assert_eq!(check(bad), Err(message.to_string()), "{bad}");
+        }
+        let long_local = format!("{}@example.com", "a".repeat(MAX_LOCAL_LEN + 1));
+        assert!(check(&long_local).unwrap_err().contains("at most 64"));
+    }
+}
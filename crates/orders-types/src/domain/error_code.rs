@@ -0,0 +1,88 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Stable, machine-readable reason carried in every error response as
+/// `"code"`. Clients branch on these; the English `"error"` text may change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    BadRequest,
+    Unauthorized,
+    Forbidden,
+    RoleDenied,
+    OrderNotFound,
+    WebhookNotFound,
+    ApiKeyNotFound,
+    DiscountNotFound,
+    DeadLetterNotFound,
+    /// Something else the request relies on does not exist.
+    NotFound,
+    ValidationFailed,
+    /// The request body is over the server's size limit.
+    PayloadTooLarge,
+    InvalidTransition,
+    /// The resource changed or is in a state that no longer allows the edit.
+    Conflict,
+    InsufficientStock,
+    Rejected,
+    RateLimited,
+    Unavailable,
+    Internal,
+    /// A code this build does not know yet, e.g. from a newer server.
+    #[serde(other)]
+    Unknown,
+}
+
+impl ErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::BadRequest => "BAD_REQUEST",
+            ErrorCode::Unauthorized => "UNAUTHORIZED",
+            ErrorCode::Forbidden => "FORBIDDEN",
+            ErrorCode::RoleDenied => "ROLE_DENIED",
+            ErrorCode::OrderNotFound => "ORDER_NOT_FOUND",
+            ErrorCode::WebhookNotFound => "WEBHOOK_NOT_FOUND",
+            ErrorCode::ApiKeyNotFound => "API_KEY_NOT_FOUND",
+            ErrorCode::DiscountNotFound => "DISCOUNT_NOT_FOUND",
+            ErrorCode::DeadLetterNotFound => "DEAD_LETTER_NOT_FOUND",
+            ErrorCode::NotFound => "NOT_FOUND",
+            ErrorCode::ValidationFailed => "VALIDATION_FAILED",
+            ErrorCode::PayloadTooLarge => "PAYLOAD_TOO_LARGE",
+            ErrorCode::InvalidTransition => "INVALID_TRANSITION",
+            ErrorCode::Conflict => "CONFLICT",
+            ErrorCode::InsufficientStock => "INSUFFICIENT_STOCK",
+            ErrorCode::Rejected => "REJECTED",
+            ErrorCode::RateLimited => "RATE_LIMITED",
+            ErrorCode::Unavailable => "UNAVAILABLE",
+            ErrorCode::Internal => "INTERNAL",
+            ErrorCode::Unknown => "UNKNOWN",
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_as_screaming_snake_and_tolerates_new_codes() {
+        for code in [
+            ErrorCode::OrderNotFound,
+            ErrorCode::InvalidTransition,
+            ErrorCode::ValidationFailed,
+        ] {
+            let json = serde_json::to_string(&code).unwrap();
+            assert_eq!(json, format!("\"{}\"", code.as_str()));
+            assert_eq!(serde_json::from_str::<ErrorCode>(&json).unwrap(), code);
+        }
+        let unknown: ErrorCode = serde_json::from_str("\"PAYMENT_DECLINED\"").unwrap();
+        assert_eq!(unknown, ErrorCode::Unknown);
+    }
+}
@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::actor::Actor;
+use crate::domain::correlation::CorrelationId;
+use crate::domain::order::{Order, OrderStatus};
+use crate::domain::tenant::TenantId;
+
+/// Change notification emitted whenever an order is mutated.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OrderEvent {
+    Created {
+        order: Order,
+    },
+    Updated {
+        order: Order,
+    },
+    /// An admin set the status directly, outside the transition rules.
+    StatusOverridden {
+        order: Order,
+        from: OrderStatus,
+        reason: String,
+    },
+    Deleted {
+        id: Uuid,
+        #[serde(default)]
+        tenant_id: TenantId,
+    },
+}
+
+impl OrderEvent {
+    pub fn order_id(&self) -> Uuid {
+        match self {
+            OrderEvent::Created { order }
+            | OrderEvent::Updated { order }
+            | OrderEvent::StatusOverridden { order, .. } => order.id,
+            OrderEvent::Deleted { id, .. } => *id,
+        }
+    }
+
+    pub fn tenant_id(&self) -> &TenantId {
+        match self {
+            OrderEvent::Created { order }
+            | OrderEvent::Updated { order }
+            | OrderEvent::StatusOverridden { order, .. } => &order.tenant_id,
+            OrderEvent::Deleted { tenant_id, .. } => tenant_id,
+        }
+    }
+
+    /// The serialized `type` tag: `created`, `updated`, `status_overridden`
+    /// or `deleted`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            OrderEvent::Created { .. } => "created",
+            OrderEvent::Updated { .. } => "updated",
+            OrderEvent::StatusOverridden { .. } => "status_overridden",
+            OrderEvent::Deleted { .. } => "deleted",
+        }
+    }
+
+    /// Status after the change; `None` once the order is gone.
+    pub fn status(&self) -> Option<&OrderStatus> {
+        match self {
+            OrderEvent::Created { order }
+            | OrderEvent::Updated { order }
+            | OrderEvent::StatusOverridden { order, .. } => Some(&order.status),
+            OrderEvent::Deleted { .. } => None,
+        }
+    }
+}
+
+/// An [`OrderEvent`] as published: the event plus the correlation id of the
+/// request that caused it and who made the change. Serializes flat, e.g.
+/// `{"correlation_id":"...","actor":"key:...","type":"created","order":{...}}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventEnvelope {
+    pub correlation_id: CorrelationId,
+    #[serde(default)]
+    pub actor: Actor,
+    #[serde(flatten)]
+    pub event: OrderEvent,
+}
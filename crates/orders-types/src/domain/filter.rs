@@ -0,0 +1,390 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::order::{Order, OrderStatus};
+
+/// Fields orders can be listed by; anything else is rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortField {
+    CreatedAt,
+    UpdatedAt,
+    TotalCents,
+    /// By status name, alphabetically.
+    Status,
+}
+
+impl SortField {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortField::CreatedAt => "created_at",
+            SortField::UpdatedAt => "updated_at",
+            SortField::TotalCents => "total_cents",
+            SortField::Status => "status",
+        }
+    }
+
+    fn compare(&self, a: &Order, b: &Order) -> Ordering {
+        match self {
+            SortField::CreatedAt => a.created_at.cmp(&b.created_at),
+            SortField::UpdatedAt => a.updated_at.cmp(&b.updated_at),
+            SortField::TotalCents => a.total.amount_minor().cmp(&b.total.amount_minor()),
+            SortField::Status => format!("{:?}", a.status).cmp(&format!("{:?}", b.status)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+}
+
+/// The sort a listing was made with. Ties are broken by order id, ascending,
+/// so pages are stable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderSort {
+    pub field: SortField,
+    pub order: SortOrder,
+}
+
+impl OrderSort {
+    pub fn compare(&self, a: &Order, b: &Order) -> Ordering {
+        let primary = self.field.compare(a, b);
+        let primary = match self.order {
+            SortOrder::Asc => primary,
+            SortOrder::Desc => primary.reverse(),
+        };
+        primary.then_with(|| a.id.cmp(&b.id))
+    }
+}
+
+/// One page of `GET /orders`, echoing how it was sorted and paged.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderPage {
+    pub orders: Vec<Order>,
+    /// `None` when no sort was asked for; the order is then unspecified.
+    pub sort: Option<OrderSort>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    #[serde(default)]
+    pub offset: usize,
+}
+
+/// `created_after` must come before `created_before`; otherwise nothing
+/// could match, which is almost certainly a mistake by the caller.
+pub fn check_created_range(
+    after: Option<DateTime<Utc>>,
+    before: Option<DateTime<Utc>>,
+) -> Result<(), String> {
+    match (after, before) {
+        (Some(after), Some(before)) if after >= before => Err(format!(
+            "`created_after` {} must be before `created_before` {}",
+            after.to_rfc3339(),
+            before.to_rfc3339()
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Criteria for listing orders. Shared by the HTTP server (query string) and
+/// `orders-client`, so both sides encode it identically.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderFilter {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<OrderStatus>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    /// Country of the shipping address, case-insensitive.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    /// Created at or after this instant.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_after: Option<DateTime<Utc>>,
+    /// Created strictly before this instant.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_before: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sort: Option<SortField>,
+    /// Direction for `sort`; ascending when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<SortOrder>,
+    /// Entries the order's metadata must all have. The keys are the
+    /// caller's, so serde leaves this out and it travels as
+    /// `metadata.<key>=<value>` parameters instead; see
+    /// [`OrderFilter::metadata_params`].
+    #[serde(skip)]
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl OrderFilter {
+    /// Prefix of the query parameters that filter on metadata.
+    pub const METADATA_PARAM_PREFIX: &'static str = "metadata.";
+
+    pub fn with_status(mut self, status: OrderStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    pub fn with_email(mut self, email: impl Into<String>) -> Self {
+        self.email = Some(email.into());
+        self
+    }
+
+    pub fn with_country(mut self, country: impl Into<String>) -> Self {
+        self.country = Some(country.into());
+        self
+    }
+
+    pub fn with_created_after(mut self, at: DateTime<Utc>) -> Self {
+        self.created_after = Some(at);
+        self
+    }
+
+    pub fn with_created_before(mut self, at: DateTime<Utc>) -> Self {
+        self.created_before = Some(at);
+        self
+    }
+
+    /// Only orders whose metadata has `key` set to `value`.
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// `metadata` as query parameters, to send alongside the serialized
+    /// filter.
+    pub fn metadata_params(&self) -> Vec<(String, &str)> {
+        self.metadata
+            .iter()
+            .map(|(k, v)| (format!("{}{k}", Self::METADATA_PARAM_PREFIX), v.as_str()))
+            .collect()
+    }
+
+    /// Add the `metadata.<key>` parameters among `params` to `metadata`,
+    /// ignoring the rest.
+    pub fn with_metadata_params<K, V>(mut self, params: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: AsRef<str>,
+        V: Into<String>,
+    {
+        for (name, value) in params {
+            if let Some(key) = name.as_ref().strip_prefix(Self::METADATA_PARAM_PREFIX) {
+                self.metadata.insert(key.to_owned(), value.into());
+            }
+        }
+        self
+    }
+
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn with_offset(mut self, offset: usize) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    pub fn with_sort(mut self, field: SortField, order: SortOrder) -> Self {
+        self.sort = Some(field);
+        self.order = Some(order);
+        self
+    }
+
+    /// The requested sort; `order` alone is an error since there is nothing
+    /// to apply it to.
+    pub fn sorting(&self) -> Result<Option<OrderSort>, String> {
+        match (self.sort, self.order) {
+            (Some(field), order) => Ok(Some(OrderSort {
+                field,
+                order: order.unwrap_or_default(),
+            })),
+            (None, Some(_)) => Err("`order` needs a `sort` field".into()),
+            (None, None) => Ok(None),
+        }
+    }
+
+    /// The creation range; see [`check_created_range`].
+    pub fn check_range(&self) -> Result<(), String> {
+        check_created_range(self.created_after, self.created_before)
+    }
+
+    /// Whether `order` satisfies the predicate part (everything but paging).
+    pub fn matches(&self, order: &Order) -> bool {
+        self.status.as_ref().is_none_or(|s| &order.status == s)
+            && self
+                .email
+                .as_deref()
+                .is_none_or(|e| order.email.eq_ignore_ascii_case(e))
+            && self.country.as_deref().is_none_or(|c| {
+                order
+                    .shipping_address
+                    .as_ref()
+                    .is_some_and(|a| a.country.eq_ignore_ascii_case(c))
+            })
+            && self.created_after.is_none_or(|t| order.created_at >= t)
+            && self.created_before.is_none_or(|t| order.created_at < t)
+            && self
+                .metadata
+                .iter()
+                .all(|(k, v)| order.metadata.get(k) == Some(v))
+    }
+
+    /// Filter, sort then page an in-memory list of orders. An invalid sort
+    /// (see [`OrderFilter::sorting`]) is ignored.
+    pub fn apply(&self, orders: Vec<Order>) -> Vec<Order> {
+        let mut orders: Vec<Order> = orders.into_iter().filter(|o| self.matches(o)).collect();
+        if let Ok(Some(sort)) = self.sorting() {
+            orders.sort_by(|a, b| sort.compare(a, b));
+        }
+        orders
+            .into_iter()
+            .skip(self.offset.unwrap_or(0))
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::money::Money;
+    use crate::domain::order::OrderItem;
+
+    fn order(email: &str, status: OrderStatus) -> Order {
+        let mut o = Order::new(
+            "Name".into(),
+            email.into(),
+            vec![OrderItem {
+                name: "A".into(),
+                qty: 1,
+                unit_price: Money::usd(100),
+                weight_grams: 0,
+                sku: None,
+                description: None,
+                metadata: Default::default(),
+                discount_cents: 0,
+            }],
+        )
+        .unwrap();
+        o.status = status;
+        o
+    }
+
+    #[test]
+    fn filters_by_status_and_email_then_pages() {
+        let orders = vec![
+            order("a@x.com", OrderStatus::Pending),
+            order("b@x.com", OrderStatus::Shipped),
+            order("A@X.com", OrderStatus::Pending),
+            order("a@x.com", OrderStatus::Pending),
+        ];
+        let f = OrderFilter::default()
+            .with_status(OrderStatus::Pending)
+            .with_email("a@x.com");
+        assert_eq!(f.apply(orders.clone()).len(), 3);
+        assert_eq!(
+            f.clone().with_offset(1).with_limit(1).apply(orders).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn sorts_before_paging_and_breaks_ties_by_id() {
+        let mut orders = vec![
+            order("a@x.com", OrderStatus::Shipped),
+            order("b@x.com", OrderStatus::Pending),
+            order("c@x.com", OrderStatus::Confirmed),
+        ];
+        orders[0].total = Money::usd(300);
+        orders[1].total = Money::usd(100);
+        orders[2].total = Money::usd(300);
+        let emails = |f: &OrderFilter| {
+            f.apply(orders.clone())
+                .into_iter()
+                .map(|o| o.email)
+                .collect::<Vec<_>>()
+        };
+
+        let by_status = OrderFilter::default().with_sort(SortField::Status, SortOrder::Asc);
+        assert_eq!(emails(&by_status), ["c@x.com", "b@x.com", "a@x.com"]);
+
+        let by_total = OrderFilter::default()
+            .with_sort(SortField::TotalCents, SortOrder::Desc)
+            .with_limit(2);
+        let mut tied = vec![orders[0].clone(), orders[2].clone()];
+        tied.sort_by_key(|o| o.id);
+        assert_eq!(
+            emails(&by_total),
+            tied.into_iter().map(|o| o.email).collect::<Vec<_>>()
+        );
+
+        let order_only = OrderFilter {
+            order: Some(SortOrder::Desc),
+            ..OrderFilter::default()
+        };
+        assert!(order_only.sorting().is_err());
+    }
+
+    #[test]
+    fn created_range_is_half_open() {
+        let o = order("a@example.com", OrderStatus::Pending);
+        let at = o.created_at;
+        assert!(OrderFilter::default().with_created_after(at).matches(&o));
+        assert!(!OrderFilter::default().with_created_before(at).matches(&o));
+        let later = at + chrono::Duration::seconds(1);
+        assert!(OrderFilter::default()
+            .with_created_after(at)
+            .with_created_before(later)
+            .matches(&o));
+        assert!(!OrderFilter::default().with_created_after(later).matches(&o));
+    }
+
+    #[test]
+    fn metadata_entries_must_all_match() {
+        let o = order("a@example.com", OrderStatus::Pending).with_metadata(
+            [("erp_id", "E-1"), ("campaign", "spring")]
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .into(),
+        );
+        let erp = OrderFilter::default().with_metadata("erp_id", "E-1");
+        assert!(erp.matches(&o));
+        assert!(erp.clone().with_metadata("campaign", "spring").matches(&o));
+        assert!(!erp.clone().with_metadata("campaign", "autumn").matches(&o));
+        assert!(!OrderFilter::default()
+            .with_metadata("region", "eu")
+            .matches(&o));
+
+        let params = erp.metadata_params();
+        assert_eq!(params, [("metadata.erp_id".to_string(), "E-1")]);
+        let parsed = OrderFilter::default()
+            .with_metadata_params([("metadata.erp_id", "E-1"), ("status", "Pending")]);
+        assert_eq!(parsed, erp);
+    }
+
+    #[test]
+    fn serializes_only_set_fields() {
+        let f = OrderFilter::default().with_status(OrderStatus::Shipped);
+        let json = serde_json::to_value(&f).unwrap();
+        assert_eq!(json, serde_json::json!({"status": "Shipped"}));
+    }
+}
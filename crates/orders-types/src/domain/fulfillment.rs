@@ -0,0 +1,194 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::order::{FieldError, Order};
+
+/// Some quantity of one order line, identified by its position in
+/// [`Order::items`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FulfilledItem {
+    pub position: usize,
+    pub qty: u32,
+}
+
+/// One shipment of some or all of an order's items.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fulfillment {
+    pub id: Uuid,
+    pub items: Vec<FulfilledItem>,
+    pub carrier: String,
+    pub tracking_number: String,
+    pub shipped_at: DateTime<Utc>,
+}
+
+impl Fulfillment {
+    pub const MAX_CARRIER_LEN: usize = 64;
+    pub const MAX_TRACKING_NUMBER_LEN: usize = 128;
+
+    pub fn new(
+        items: Vec<FulfilledItem>,
+        carrier: impl Into<String>,
+        tracking_number: impl Into<String>,
+        shipped_at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            items,
+            carrier: carrier.into(),
+            tracking_number: tracking_number.into(),
+            shipped_at,
+        }
+    }
+
+    /// Every problem with shipping this on top of `earlier` fulfillments of
+    /// `order`: unknown or repeated lines, empty quantities and more than is
+    /// left to ship. Empty when it is valid.
+    pub fn check(&self, order: &Order, earlier: &[Fulfillment]) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        for (field, value, max) in [
+            ("carrier", &self.carrier, Self::MAX_CARRIER_LEN),
+            (
+                "tracking_number",
+                &self.tracking_number,
+                Self::MAX_TRACKING_NUMBER_LEN,
+            ),
+        ] {
+            if value.trim().is_empty() {
+                errors.push(FieldError::new(field, "must not be empty"));
+            } else if value.chars().count() > max {
+                errors.push(FieldError::new(
+                    field,
+                    format!("must be at most {max} characters"),
+                ));
+            }
+        }
+        if self.items.is_empty() {
+            errors.push(FieldError::new("items", "must not be empty"));
+        }
+        let mut remaining = remaining_qty(order, earlier);
+        for (i, item) in self.items.iter().enumerate() {
+            if self.items[..i].iter().any(|p| p.position == item.position) {
+                errors.push(FieldError::new(
+                    format!("items[{i}].position"),
+                    "appears more than once",
+                ));
+                continue;
+            }
+            let Some(left) = remaining.get_mut(item.position) else {
+                errors.push(FieldError::new(
+                    format!("items[{i}].position"),
+                    format!("order has no item at position {}", item.position),
+                ));
+                continue;
+            };
+            if item.qty == 0 {
+                errors.push(FieldError::new(
+                    format!("items[{i}].qty"),
+                    "must be greater than zero",
+                ));
+            } else if item.qty > *left {
+                errors.push(FieldError::new(
+                    format!("items[{i}].qty"),
+                    format!("only {left} left to ship at position {}", item.position),
+                ));
+            } else {
+                *left -= item.qty;
+            }
+        }
+        errors
+    }
+}
+
+/// Quantity of each order line not yet covered by `fulfillments`, by
+/// position.
+pub fn remaining_qty(order: &Order, fulfillments: &[Fulfillment]) -> Vec<u32> {
+    let mut remaining: Vec<u32> = order.items.iter().map(|i| i.qty).collect();
+    for item in fulfillments.iter().flat_map(|f| &f.items) {
+        if let Some(left) = remaining.get_mut(item.position) {
+            *left = left.saturating_sub(item.qty);
+        }
+    }
+    remaining
+}
+
+/// Whether `fulfillments` ship every unit of every line of `order`.
+pub fn fully_fulfilled(order: &Order, fulfillments: &[Fulfillment]) -> bool {
+    remaining_qty(order, fulfillments).iter().all(|&q| q == 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::money::Money;
+    use crate::domain::order::OrderItem;
+
+    fn order() -> Order {
+        let item = |name: &str, qty| OrderItem {
+            name: name.into(),
+            qty,
+            unit_price: Money::usd(100),
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
+        };
+        Order::new(
+            "Ann".into(),
+            "ann@example.com".into(),
+            vec![item("A", 2), item("B", 1)],
+        )
+        .unwrap()
+    }
+
+    fn ship(items: &[(usize, u32)]) -> Fulfillment {
+        Fulfillment::new(
+            items
+                .iter()
+                .map(|&(position, qty)| FulfilledItem { position, qty })
+                .collect(),
+            "UPS",
+            "1Z999",
+            Utc::now(),
+        )
+    }
+
+    #[test]
+    fn partial_shipments_add_up() {
+        let order = order();
+        let first = ship(&[(0, 1)]);
+        assert!(first.check(&order, &[]).is_empty());
+        let mut shipped = vec![first];
+        assert_eq!(remaining_qty(&order, &shipped), [1, 1]);
+        assert!(!fully_fulfilled(&order, &shipped));
+
+        let second = ship(&[(0, 1), (1, 1)]);
+        assert!(second.check(&order, &shipped).is_empty());
+        shipped.push(second);
+        assert!(fully_fulfilled(&order, &shipped));
+    }
+
+    #[test]
+    fn check_rejects_unknown_lines_and_overshipping() {
+        let order = order();
+        let earlier = [ship(&[(0, 2)])];
+        let mut bad = ship(&[(0, 1), (5, 1), (1, 0), (1, 1)]);
+        bad.carrier = " ".into();
+        let fields: Vec<_> = bad
+            .check(&order, &earlier)
+            .into_iter()
+            .map(|e| e.field)
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                "carrier",
+                "items[0].qty",
+                "items[1].position",
+                "items[2].qty",
+                "items[3].position"
+            ]
+        );
+    }
+}
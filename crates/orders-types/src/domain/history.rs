@@ -0,0 +1,35 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::order::OrderStatus;
+
+/// One status change of an order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderHistoryEntry {
+    /// `None` for the entry recorded when the order was created.
+    pub from: Option<OrderStatus>,
+    pub to: OrderStatus,
+    pub at: DateTime<Utc>,
+    /// Who made the change; see [`crate::domain::audit::AuditEntry::actor`].
+    pub actor: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl OrderHistoryEntry {
+    pub fn new(
+        from: Option<OrderStatus>,
+        to: OrderStatus,
+        at: DateTime<Utc>,
+        actor: impl Into<String>,
+        note: Option<String>,
+    ) -> Self {
+        Self {
+            from,
+            to,
+            at,
+            actor: actor.into(),
+            note,
+        }
+    }
+}
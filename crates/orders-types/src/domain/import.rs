@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::order::OrderItem;
+
+/// Failures kept in [`ImportProgress::failures`]; later ones are only counted.
+pub const MAX_REPORTED_FAILURES: usize = 100;
+
+/// One order read from an import file; the same shape as a create request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportRecord {
+    pub customer_name: String,
+    pub email: String,
+    pub items: Vec<OrderItem>,
+}
+
+/// A record that was not imported. `line` is where it starts in the source,
+/// counting from 1.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportFailure {
+    pub line: u64,
+    pub message: String,
+}
+
+/// Running totals of an import; the final value is its summary.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ImportProgress {
+    /// Bytes of the upload consumed so far.
+    pub bytes: u64,
+    /// Records parsed, whether or not they were imported.
+    pub records: u64,
+    pub imported: u64,
+    pub failed: u64,
+    /// The first [`MAX_REPORTED_FAILURES`] failures.
+    pub failures: Vec<ImportFailure>,
+}
+
+impl ImportProgress {
+    pub fn record_failure(&mut self, line: u64, message: impl Into<String>) {
+        self.failed += 1;
+        if self.failures.len() < MAX_REPORTED_FAILURES {
+            self.failures.push(ImportFailure {
+                line,
+                message: message.into(),
+            });
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::order::OrderStatus;
+
+/// Explicit translation of legacy/unknown stored status strings.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct StatusMapping(pub HashMap<String, OrderStatus>);
+
+impl StatusMapping {
+    /// Parse `legacy=Status` pairs separated by commas, e.g.
+    /// `"shipped=Shipped,done=Completed"`.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut map = HashMap::new();
+        for pair in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (legacy, status) = pair
+                .split_once('=')
+                .ok_or_else(|| format!("expected legacy=Status, got `{pair}`"))?;
+            let status = OrderStatus::parse(status.trim())
+                .ok_or_else(|| format!("unknown target status `{}`", status.trim()))?;
+            map.insert(legacy.trim().to_string(), status);
+        }
+        Ok(Self(map))
+    }
+
+    pub fn get(&self, legacy: &str) -> Option<&OrderStatus> {
+        self.0.get(legacy)
+    }
+
+    /// Entries from `other` take precedence.
+    pub fn merged(mut self, other: StatusMapping) -> Self {
+        self.0.extend(other.0);
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum IntegrityIssue {
+    UnknownStatus {
+        order_id: String,
+        value: String,
+        /// Target from the mapping table, if one is configured.
+        mapped_to: Option<OrderStatus>,
+    },
+    InvalidRow {
+        order_id: String,
+        reason: String,
+    },
+}
+
+/// Result of scanning stored orders for values the domain cannot represent.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    pub checked: usize,
+    pub fixed: usize,
+    pub issues: Vec<IntegrityIssue>,
+}
+
+impl IntegrityReport {
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_mapping_spec() {
+        let m = StatusMapping::parse("shipped=Shipped, done = Completed,").unwrap();
+        assert_eq!(m.get("shipped"), Some(&OrderStatus::Shipped));
+        assert_eq!(m.get("done"), Some(&OrderStatus::Completed));
+        assert!(StatusMapping::parse("x=Nope").is_err());
+        assert!(StatusMapping::parse("broken").is_err());
+    }
+}
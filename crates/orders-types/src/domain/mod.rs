@@ -1 +1,29 @@
+pub mod actor;
+pub mod address;
+pub mod api_key;
+pub mod audit;
+pub mod cloudevent;
+pub mod correlation;
+pub mod dead_letter;
+pub mod discount;
+pub mod email;
+pub mod error_code;
+pub mod events;
+pub mod filter;
+pub mod fulfillment;
+pub mod history;
+pub mod import;
+pub mod integrity;
+pub mod money;
 pub mod order;
+pub mod order_number;
+pub mod order_patch;
+pub mod outbox;
+pub mod pricing;
+pub mod request_signing;
+pub mod share;
+pub mod stats;
+pub mod tenant;
+pub mod webhook;
+
+pub use order::OrderValidationError;
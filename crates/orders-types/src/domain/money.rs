@@ -0,0 +1,300 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// ISO 4217 alphabetic currency code, e.g. `USD`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Currency([u8; 3]);
+
+impl Currency {
+    pub const USD: Currency = Currency(*b"USD");
+    pub const EUR: Currency = Currency(*b"EUR");
+    pub const GBP: Currency = Currency(*b"GBP");
+    pub const JPY: Currency = Currency(*b"JPY");
+
+    /// Three ASCII letters; lowercase is accepted and normalised.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.as_bytes() {
+            [a, b, c] if [a, b, c].iter().all(|x| x.is_ascii_alphabetic()) => Ok(Self([
+                a.to_ascii_uppercase(),
+                b.to_ascii_uppercase(),
+                c.to_ascii_uppercase(),
+            ])),
+            _ => Err(format!("invalid currency code `{s}`")),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        // Only ever built from ASCII letters.
+        std::str::from_utf8(&self.0).unwrap_or("???")
+    }
+
+    /// Digits after the decimal point for the minor unit (`2` for cents).
+    pub fn minor_digits(&self) -> u32 {
+        match &self.0 {
+            b"JPY" | b"KRW" | b"VND" | b"CLP" | b"ISK" | b"UGX" | b"XAF" | b"XOF" => 0,
+            b"BHD" | b"IQD" | b"JOD" | b"KWD" | b"LYD" | b"OMR" | b"TND" => 3,
+            _ => 2,
+        }
+    }
+}
+
+/// Orders and items stored before currencies existed were in dollars.
+impl Default for Currency {
+    fn default() -> Self {
+        Currency::USD
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFrom<String> for Currency {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Currency::parse(&s)
+    }
+}
+
+impl From<Currency> for String {
+    fn from(c: Currency) -> Self {
+        c.as_str().to_string()
+    }
+}
+
+/// An amount in the minor unit of its currency (cents for `USD`). Amounts in
+/// different currencies never combine silently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Money {
+    amount_minor: i64,
+    currency: Currency,
+}
+
+impl Money {
+    pub fn new(amount_minor: i64, currency: Currency) -> Self {
+        Self {
+            amount_minor,
+            currency,
+        }
+    }
+
+    pub fn usd(cents: i64) -> Self {
+        Self::new(cents, Currency::USD)
+    }
+
+    pub fn zero(currency: Currency) -> Self {
+        Self::new(0, currency)
+    }
+
+    pub fn amount_minor(&self) -> i64 {
+        self.amount_minor
+    }
+
+    pub fn currency(&self) -> Currency {
+        self.currency
+    }
+
+    /// `None` on overflow or when the currencies differ.
+    pub fn checked_add(self, other: Money) -> Option<Money> {
+        if self.currency != other.currency {
+            return None;
+        }
+        Some(Self::new(
+            self.amount_minor.checked_add(other.amount_minor)?,
+            self.currency,
+        ))
+    }
+
+    /// `None` on overflow.
+    pub fn checked_mul(self, factor: i64) -> Option<Money> {
+        Some(Self::new(
+            self.amount_minor.checked_mul(factor)?,
+            self.currency,
+        ))
+    }
+
+    /// Parse a decimal major-unit amount such as `"12.5"` or `"-0.99"`.
+    /// More fractional digits than the currency has is an error, never a
+    /// rounding.
+    pub fn from_major_str(s: &str, currency: Currency) -> Result<Self, String> {
+        let invalid = || format!("invalid {currency} amount `{s}`");
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        let (whole, frac) = digits.split_once('.').unwrap_or((digits, ""));
+        let scale = currency.minor_digits() as usize;
+        if whole.is_empty()
+            || frac.len() > scale
+            || !whole
+                .bytes()
+                .chain(frac.bytes())
+                .all(|b| b.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+        let padded = format!("{whole}{frac:0<scale$}");
+        let amount: i64 = padded.parse().map_err(|_| invalid())?;
+        Ok(Self::new(if negative { -amount } else { amount }, currency))
+    }
+
+    /// The amount in major units with the currency's decimals, e.g. `"12.50"`.
+    pub fn to_major_string(&self) -> String {
+        let scale = self.currency.minor_digits();
+        let sign = if self.amount_minor < 0 { "-" } else { "" };
+        let abs = self.amount_minor.unsigned_abs();
+        if scale == 0 {
+            return format!("{sign}{abs}");
+        }
+        let unit = 10u64.pow(scale);
+        format!(
+            "{sign}{}.{:0width$}",
+            abs / unit,
+            abs % unit,
+            width = scale as usize
+        )
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.to_major_string(), self.currency)
+    }
+}
+
+/// Serde adapters that keep the pre-`Money` wire format: an amount field in
+/// minor units next to a `currency` field, which defaults to `USD` when
+/// absent. Use with `#[serde(flatten, with = "...")]`.
+macro_rules! flat_money {
+    ($module:ident, $field:literal) => {
+        pub mod $module {
+            use super::{Currency, Money};
+            use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+            #[derive(Serialize, Deserialize)]
+            struct Flat {
+                #[serde(rename = $field)]
+                amount_minor: i64,
+                #[serde(default)]
+                currency: Currency,
+            }
+
+            pub fn serialize<S: Serializer>(money: &Money, s: S) -> Result<S::Ok, S::Error> {
+                Flat {
+                    amount_minor: money.amount_minor(),
+                    currency: money.currency(),
+                }
+                .serialize(s)
+            }
+
+            pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Money, D::Error> {
+                let flat = Flat::deserialize(d)?;
+                Ok(Money::new(flat.amount_minor, flat.currency))
+            }
+        }
+    };
+}
+
+flat_money!(unit_price_cents, "unit_price_cents");
+flat_money!(total_cents, "total_cents");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn currency_codes_are_three_letters_and_normalised() {
+        assert_eq!(Currency::parse("eur").unwrap(), Currency::EUR);
+        assert!(Currency::parse("EURO").is_err());
+        assert!(Currency::parse("U$D").is_err());
+        assert!(Currency::parse("").is_err());
+        assert_eq!(serde_json::to_string(&Currency::GBP).unwrap(), "\"GBP\"");
+        assert_eq!(
+            serde_json::from_str::<Currency>("\"jpy\"").unwrap(),
+            Currency::JPY
+        );
+        assert!(serde_json::from_str::<Currency>("\"dollars\"").is_err());
+    }
+
+    #[test]
+    fn money_serializes_as_amount_and_currency() {
+        let m = Money::new(1250, Currency::EUR);
+        let json = serde_json::to_value(m).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"amount_minor": 1250, "currency": "EUR"})
+        );
+        assert_eq!(serde_json::from_value::<Money>(json).unwrap(), m);
+        assert!(serde_json::from_value::<Money>(serde_json::json!({"amount_minor": 1})).is_err());
+    }
+
+    #[test]
+    fn flat_adapter_reads_legacy_and_current_shapes() {
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct Line {
+            #[serde(flatten, with = "unit_price_cents")]
+            price: Money,
+        }
+
+        let current = Line {
+            price: Money::new(300, Currency::GBP),
+        };
+        let json = serde_json::to_value(&current).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"unit_price_cents": 300, "currency": "GBP"})
+        );
+        assert_eq!(serde_json::from_value::<Line>(json).unwrap(), current);
+
+        let legacy: Line = serde_json::from_str(r#"{"unit_price_cents": 499}"#).unwrap();
+        assert_eq!(legacy.price, Money::usd(499));
+        assert!(serde_json::from_str::<Line>(r#"{"currency": "USD"}"#).is_err());
+        assert!(
+            serde_json::from_str::<Line>(r#"{"unit_price_cents": 1, "currency": "usdollar"}"#)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn arithmetic_refuses_mixed_currencies_and_overflow() {
+        let usd = Money::usd(100);
+        assert_eq!(usd.checked_add(Money::usd(50)), Some(Money::usd(150)));
+        assert_eq!(usd.checked_add(Money::new(50, Currency::EUR)), None);
+        assert_eq!(Money::usd(i64::MAX).checked_add(Money::usd(1)), None);
+        assert_eq!(usd.checked_mul(3), Some(Money::usd(300)));
+        assert_eq!(Money::usd(i64::MAX).checked_mul(2), None);
+    }
+
+    #[test]
+    fn major_unit_conversions_respect_currency_decimals() {
+        assert_eq!(
+            Money::from_major_str("12.5", Currency::USD),
+            Ok(Money::usd(1250))
+        );
+        assert_eq!(
+            Money::from_major_str("-0.99", Currency::USD),
+            Ok(Money::usd(-99))
+        );
+        assert_eq!(
+            Money::from_major_str("1500", Currency::JPY),
+            Ok(Money::new(1500, Currency::JPY))
+        );
+        assert!(Money::from_major_str("1.5", Currency::JPY).is_err());
+        assert!(Money::from_major_str("1.999", Currency::USD).is_err());
+        assert!(Money::from_major_str(".5", Currency::USD).is_err());
+        assert!(Money::from_major_str("1e3", Currency::USD).is_err());
+
+        assert_eq!(Money::usd(1250).to_major_string(), "12.50");
+        assert_eq!(Money::usd(-7).to_major_string(), "-0.07");
+        assert_eq!(Money::new(1500, Currency::JPY).to_major_string(), "1500");
+        assert_eq!(
+            Money::new(1234, Currency::parse("KWD").unwrap()).to_string(),
+            "1.234 KWD"
+        );
+    }
+}
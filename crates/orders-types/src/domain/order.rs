@@ -1,8 +1,20 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+use crate::domain::actor::Actor;
+use crate::domain::address::Address;
+use crate::domain::discount::AppliedDiscount;
+use crate::domain::email;
+use crate::domain::money::{Currency, Money};
+use crate::domain::order_number::OrderNumber;
+use crate::domain::pricing::{Charges, PricingSnapshot};
+use crate::domain::tenant::TenantId;
+use crate::ports::pricing::{ItemPriceRules, PricingRules};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum OrderStatus {
     Pending,
     Confirmed,
@@ -11,65 +23,759 @@ pub enum OrderStatus {
     Completed,
 }
 
+impl OrderStatus {
+    /// Parse the canonical stored/serialized name (e.g. `"Shipped"`).
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "Pending" => Some(OrderStatus::Pending),
+            "Confirmed" => Some(OrderStatus::Confirmed),
+            "Shipped" => Some(OrderStatus::Shipped),
+            "Cancelled" => Some(OrderStatus::Cancelled),
+            "Completed" => Some(OrderStatus::Completed),
+            _ => None,
+        }
+    }
+
+    /// Whether an order may move from `self` to `next`. Orders only move
+    /// forward; `Cancelled` and `Completed` are final. Setting the current
+    /// status again is allowed and changes nothing.
+    pub fn can_transition_to(&self, next: &OrderStatus) -> bool {
+        use OrderStatus::*;
+        self == next
+            || matches!(
+                (self, next),
+                (Pending, Confirmed | Shipped | Cancelled)
+                    | (Confirmed, Shipped | Completed | Cancelled)
+                    | (Shipped, Completed)
+            )
+    }
+}
+
+/// Serialized as `{"name","qty","unit_price_cents","currency","weight_grams"}`
+/// plus the optional catalog fields; items without a `currency` are in `USD`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderItem {
     pub name: String,
     pub qty: u32,
-    pub unit_price_cents: i64,
+    #[serde(flatten, with = "crate::domain::money::unit_price_cents")]
+    pub unit_price: Money,
+    /// Weight of one unit, for weight-based shipping; `0` when unknown.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub weight_grams: u32,
+    /// Catalog identifier; see [`OrderItem::check_sku`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sku: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Free-form key/value pairs, e.g. a catalog variant.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
+    /// Taken off the whole line (not per unit), in the line's currency.
+    #[serde(default, skip_serializing_if = "is_zero_cents")]
+    pub discount_cents: i64,
+}
+
+fn is_zero(n: &u32) -> bool {
+    *n == 0
+}
+
+fn is_zero_cents(n: &i64) -> bool {
+    *n == 0
+}
+
+impl OrderItem {
+    pub const MAX_SKU_LEN: usize = 64;
+    pub const MAX_DESCRIPTION_LEN: usize = 1000;
+    pub const MAX_METADATA_ENTRIES: usize = 32;
+    pub const MAX_METADATA_KEY_LEN: usize = 64;
+    pub const MAX_METADATA_VALUE_LEN: usize = 512;
+
+    /// SKUs are 1 to [`MAX_SKU_LEN`](Self::MAX_SKU_LEN) ASCII letters, digits,
+    /// `-`, `_` or `.`.
+    pub fn check_sku(sku: &str) -> Result<(), String> {
+        if sku.is_empty() || sku.len() > Self::MAX_SKU_LEN {
+            return Err(format!("must be 1 to {} characters", Self::MAX_SKU_LEN));
+        }
+        if !sku
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        {
+            return Err("may only contain letters, digits, `-`, `_` and `.`".into());
+        }
+        Ok(())
+    }
+
+    /// Price of all units before tax, less the line discount; fails like
+    /// [`Order::validate`] when that is more than an `i64` holds.
+    pub fn net_cents(&self) -> Result<i64, OrderValidationError> {
+        self.unit_price
+            .amount_minor()
+            .checked_mul(i64::from(self.qty))
+            .and_then(|gross| gross.checked_sub(self.discount_cents))
+            .ok_or(OrderValidationError::TotalOverflow)
+    }
+
+    /// Problems with the optional catalog fields, addressed relative to
+    /// `path` (e.g. `items[0]`).
+    fn check_details(&self, path: &str, errors: &mut Vec<FieldError>) {
+        if let Some(sku) = &self.sku {
+            if let Err(message) = Self::check_sku(sku) {
+                errors.push(FieldError::new(format!("{path}.sku"), message));
+            }
+        }
+        if self
+            .description
+            .as_ref()
+            .is_some_and(|d| d.chars().count() > Self::MAX_DESCRIPTION_LEN)
+        {
+            errors.push(FieldError::new(
+                format!("{path}.description"),
+                format!("must be at most {} characters", Self::MAX_DESCRIPTION_LEN),
+            ));
+        }
+        errors.extend(check_metadata(&self.metadata, &format!("{path}.metadata")));
+        if self.discount_cents < 0 {
+            errors.push(FieldError::new(
+                format!("{path}.discount_cents"),
+                "must not be negative",
+            ));
+        } else if self
+            .unit_price
+            .amount_minor()
+            .checked_mul(i64::from(self.qty))
+            .is_some_and(|gross| self.discount_cents > gross)
+        {
+            errors.push(FieldError::new(
+                format!("{path}.discount_cents"),
+                "must not exceed the line price",
+            ));
+        }
+    }
+}
+
+/// Problems with a metadata map at `field`: more than
+/// [`OrderItem::MAX_METADATA_ENTRIES`] entries, empty or overlong keys and
+/// overlong values. Orders and their items share the limits.
+pub fn check_metadata(metadata: &BTreeMap<String, String>, field: &str) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    if metadata.len() > OrderItem::MAX_METADATA_ENTRIES {
+        errors.push(FieldError::new(
+            field,
+            format!(
+                "must have at most {} entries",
+                OrderItem::MAX_METADATA_ENTRIES
+            ),
+        ));
+    }
+    for (key, value) in metadata {
+        if key.is_empty() || key.len() > OrderItem::MAX_METADATA_KEY_LEN {
+            errors.push(FieldError::new(
+                field,
+                format!(
+                    "keys must be 1 to {} characters",
+                    OrderItem::MAX_METADATA_KEY_LEN
+                ),
+            ));
+        } else if value.len() > OrderItem::MAX_METADATA_VALUE_LEN {
+            errors.push(FieldError::new(
+                format!("{field}.{key}"),
+                format!(
+                    "must be at most {} characters",
+                    OrderItem::MAX_METADATA_VALUE_LEN
+                ),
+            ));
+        }
+    }
+    errors
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Order {
     pub id: Uuid,
+    /// Human-readable number assigned by the repository when the order is
+    /// first stored; `None` until then.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order_number: Option<OrderNumber>,
+    #[serde(default)]
+    pub tenant_id: TenantId,
     pub customer_name: String,
     pub email: String,
     pub items: Vec<OrderItem>,
-    pub total_cents: i64,
+    /// Serialized as `total_cents` plus `currency`, in the items' currency.
+    #[serde(flatten, with = "crate::domain::money::total_cents")]
+    pub total: Money,
+    /// Subtotal, discount, tax and shipping that make up `total`.
+    #[serde(flatten)]
+    pub charges: Charges,
+    /// Discount code taken off `total`, included in `discount_cents`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub discount: Option<AppliedDiscount>,
     pub status: OrderStatus,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Pricing frozen at confirmation; only replaced through [`Order::reprice`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pricing: Option<PricingSnapshot>,
+    /// The payment provider's id for the charge taken at confirmation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payment_id: Option<String>,
+    /// What was captured with `payment_id`; refunds return this rather than
+    /// the current total.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub captured: Option<Money>,
+    /// Why and when the order was cancelled; set by [`Order::cancel`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cancellation: Option<Cancellation>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shipping_address: Option<Address>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub billing_address: Option<Address>,
+    /// The integrator's own references, e.g. an ERP id or a campaign tag;
+    /// see [`check_metadata`].
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
+    /// Who placed the order; `None` for orders stored before changes were
+    /// attributed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<Actor>,
+    /// Who last changed the order; see `created_by`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_by: Option<Actor>,
+}
+
+/// Recorded when an order is cancelled.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cancellation {
+    pub reason: String,
+    pub cancelled_at: DateTime<Utc>,
+}
+
+/// One failed check on submitted order data, addressed by its JSON path
+/// (e.g. `items[0].qty`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Why [`Order::new`] refused the data for an order. Each names the field
+/// at fault, so callers can report it without parsing the message.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum OrderValidationError {
+    #[error("customer_name: must not be empty")]
+    EmptyName,
+    #[error("email: {0}")]
+    InvalidEmail(String),
+    #[error("items: must not be empty")]
+    NoItems,
+    #[error("items[{index}].qty: must be > 0")]
+    ZeroQty { index: usize },
+    #[error("items[{index}].unit_price_cents: must not be negative")]
+    NegativePrice { index: usize },
+    #[error("items[{index}].currency: must match the order currency {expected}")]
+    MixedCurrency { index: usize, expected: Currency },
+    /// An item's SKU, description, metadata or discount; see
+    /// [`OrderItem::check_sku`] and [`check_metadata`].
+    #[error("{}: {}", .0.field, .0.message)]
+    ItemDetail(FieldError),
+    /// The items add up to more than an `i64` of minor units holds.
+    #[error("items: the order total is too large")]
+    TotalOverflow,
+    /// A cancellation without a reason; see [`Order::cancel`].
+    #[error("reason: must not be empty")]
+    EmptyReason,
+}
+
+impl OrderValidationError {
+    /// The JSON path of the field at fault, e.g. `items[0].qty`.
+    pub fn field(&self) -> String {
+        match self {
+            Self::EmptyName => "customer_name".into(),
+            Self::InvalidEmail(_) => "email".into(),
+            Self::NoItems | Self::TotalOverflow => "items".into(),
+            Self::ZeroQty { index } => format!("items[{index}].qty"),
+            Self::NegativePrice { index } => format!("items[{index}].unit_price_cents"),
+            Self::MixedCurrency { index, .. } => format!("items[{index}].currency"),
+            Self::ItemDetail(e) => e.field.clone(),
+            Self::EmptyReason => "reason".into(),
+        }
+    }
+
+    /// What is wrong with [`field`](Self::field), for a `422`.
+    pub fn message(&self) -> String {
+        match self {
+            Self::EmptyName | Self::NoItems | Self::EmptyReason => "must not be empty".into(),
+            Self::InvalidEmail(message) => message.clone(),
+            Self::ZeroQty { .. } => "must be > 0".into(),
+            Self::NegativePrice { .. } => "must not be negative".into(),
+            Self::MixedCurrency { expected, .. } => {
+                format!("must match the order currency {expected}")
+            }
+            Self::ItemDetail(e) => e.message.clone(),
+            Self::TotalOverflow => "the order total is too large".into(),
+        }
+    }
+}
+
+/// Why [`Order::replace_items`] or [`Order::cancel`] left an order as it
+/// was.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum OrderChangeError {
+    #[error("items of a {0:?} order cannot be changed")]
+    ItemsLocked(OrderStatus),
+    #[error("a {0:?} order cannot be cancelled")]
+    NotCancellable(OrderStatus),
+    #[error(transparent)]
+    Invalid(#[from] OrderValidationError),
+}
+
+/// Why [`Order::reprice`] left an order as it was.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum RepriceError {
+    #[error("the order has no frozen pricing to re-price")]
+    NotPriced,
+    #[error("a {0:?} order can no longer be re-priced")]
+    Status(OrderStatus),
+    #[error("the order's payment has already been captured")]
+    Captured,
+}
+
+impl From<OrderValidationError> for FieldError {
+    fn from(e: OrderValidationError) -> Self {
+        FieldError::new(e.field(), e.message())
+    }
+}
+
+/// How big submitted order data may be. The defaults suit most shops;
+/// deployments can tighten or relax them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderLimits {
+    pub max_items: usize,
+    /// In characters.
+    pub max_customer_name_len: usize,
+    /// In characters; 254 is the longest address SMTP can deliver to.
+    pub max_email_len: usize,
+    /// Largest order total in minor units, after tax, shipping and
+    /// discounts; `None` for no cap beyond what an `i64` holds.
+    pub max_total_cents: Option<i64>,
+}
+
+impl Default for OrderLimits {
+    fn default() -> Self {
+        Self {
+            max_items: 1000,
+            max_customer_name_len: 200,
+            max_email_len: 254,
+            max_total_cents: None,
+        }
+    }
+}
+
+impl OrderLimits {
+    /// Fields of a new order over the limits.
+    pub fn check(&self, customer_name: &str, email: &str, items: &[OrderItem]) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        if customer_name.chars().count() > self.max_customer_name_len {
+            errors.push(FieldError::new(
+                "customer_name",
+                format!("must be at most {} characters", self.max_customer_name_len),
+            ));
+        }
+        if email.chars().count() > self.max_email_len {
+            errors.push(FieldError::new(
+                "email",
+                format!("must be at most {} characters", self.max_email_len),
+            ));
+        }
+        errors.extend(self.check_items(items));
+        errors
+    }
+
+    /// Whether an order may hold `items`.
+    pub fn check_items(&self, items: &[OrderItem]) -> Option<FieldError> {
+        (items.len() > self.max_items).then(|| {
+            FieldError::new(
+                "items",
+                format!("must have at most {} items", self.max_items),
+            )
+        })
+    }
+
+    /// Whether an order may come to `total`.
+    pub fn check_total(&self, total: Money) -> Option<FieldError> {
+        let max = self.max_total_cents?;
+        (total.amount_minor() > max).then(|| {
+            FieldError::new(
+                "items",
+                format!(
+                    "the order total must be at most {}",
+                    Money::new(max, total.currency())
+                ),
+            )
+        })
+    }
 }
 
 impl Order {
-    pub fn new(
-        customer_name: String,
-        email: String,
-        items: Vec<OrderItem>,
-    ) -> anyhow::Result<Self> {
+    /// What [`Order::anonymize`] leaves in place of personal data.
+    pub const ERASED: &'static str = "[erased]";
+    pub const ERASED_EMAIL: &'static str = "erased@invalid";
+
+    /// Every problem with the data for a new order, not just the first;
+    /// empty when [`Order::new`] would accept it. `email` is checked as it
+    /// will be stored; see [`email::check`].
+    pub fn validate(
+        customer_name: &str,
+        email: &str,
+        items: &[OrderItem],
+    ) -> Vec<OrderValidationError> {
+        let mut errors = Vec::new();
         if customer_name.trim().is_empty() {
-            anyhow::bail!("customer_name empty");
+            errors.push(OrderValidationError::EmptyName);
         }
-        if !email.contains('@') {
-            anyhow::bail!("invalid email");
+        if let Err(message) = email::check(&email::normalize(email)) {
+            errors.push(OrderValidationError::InvalidEmail(message));
         }
         if items.is_empty() {
-            anyhow::bail!("items empty");
+            errors.push(OrderValidationError::NoItems);
         }
-        for it in &items {
+        for (index, it) in items.iter().enumerate() {
             if it.qty == 0 {
-                anyhow::bail!("item qty must be > 0");
+                errors.push(OrderValidationError::ZeroQty { index });
             }
+            if it.unit_price.amount_minor() < 0 {
+                errors.push(OrderValidationError::NegativePrice { index });
+            }
+            let mut details = Vec::new();
+            it.check_details(&format!("items[{index}]"), &mut details);
+            errors.extend(details.into_iter().map(OrderValidationError::ItemDetail));
         }
-        let total = items
-            .iter()
-            .map(|it| (it.qty as i64) * it.unit_price_cents)
-            .sum();
-        let now = Utc::now();
+        if let Some(expected) = items.first().map(|it| it.unit_price.currency()) {
+            for (index, it) in items.iter().enumerate().skip(1) {
+                if it.unit_price.currency() != expected {
+                    errors.push(OrderValidationError::MixedCurrency { index, expected });
+                }
+            }
+        }
+        let total = items.iter().try_fold(0i64, |sum, it| {
+            it.unit_price
+                .amount_minor()
+                .checked_mul(i64::from(it.qty))
+                .and_then(|line| sum.checked_add(line))
+        });
+        if total.is_none() {
+            errors.push(OrderValidationError::TotalOverflow);
+        }
+        errors
+    }
+
+    /// [`Order::validate`] as field errors, for a `422`.
+    pub fn check(customer_name: &str, email: &str, items: &[OrderItem]) -> Vec<FieldError> {
+        Self::validate(customer_name, email, items)
+            .into_iter()
+            .map(FieldError::from)
+            .collect()
+    }
+
+    /// A pending order priced at the submitted unit prices, with no tax or
+    /// shipping.
+    pub fn new(
+        customer_name: String,
+        email: String,
+        items: Vec<OrderItem>,
+    ) -> Result<Self, OrderValidationError> {
+        Self::new_priced(customer_name, email, items, &ItemPriceRules)
+    }
+
+    /// A pending order with tax and shipping from `rules`. The breakdown is
+    /// only an estimate until [`Order::freeze_pricing`] at confirmation.
+    /// The email is stored [normalized](email::normalize).
+    pub fn new_priced(
+        customer_name: String,
+        email: String,
+        items: Vec<OrderItem>,
+        rules: &dyn PricingRules,
+    ) -> Result<Self, OrderValidationError> {
+        Self::new_priced_at(customer_name, email, items, rules, Utc::now())
+    }
+
+    /// [`Order::new_priced`], created at `now`. Fails with the first of
+    /// [`Order::validate`]'s errors.
+    pub fn new_priced_at(
+        customer_name: String,
+        email: String,
+        items: Vec<OrderItem>,
+        rules: &dyn PricingRules,
+        now: DateTime<Utc>,
+    ) -> Result<Self, OrderValidationError> {
+        if let Some(e) = Self::validate(&customer_name, &email, &items)
+            .into_iter()
+            .next()
+        {
+            return Err(e);
+        }
+        let currency = items[0].unit_price.currency();
+        let priced = PricingSnapshot::compute_at(&items, rules, now)?;
         Ok(Self {
-            id: Uuid::new_v4(),
+            id: Uuid::now_v7(),
+            order_number: None,
+            tenant_id: TenantId::default(),
             customer_name,
-            email,
+            email: email::normalize(&email),
             items,
-            total_cents: total,
+            total: Money::new(priced.total_cents(), currency),
+            charges: priced.charges(),
+            discount: None,
             status: OrderStatus::Pending,
             created_at: now,
             updated_at: now,
+            pricing: None,
+            payment_id: None,
+            captured: None,
+            cancellation: None,
+            shipping_address: None,
+            billing_address: None,
+            metadata: BTreeMap::new(),
+            created_by: None,
+            updated_by: None,
         })
     }
 
+    /// Hex SHA-256 of who ordered what: `email` as stored (see
+    /// [`email::normalize`]) and `items` as submitted, in order. Orders with
+    /// the same fingerprint are the same order placed twice.
+    pub fn fingerprint(email: &str, items: &[OrderItem]) -> String {
+        let mut hash = Sha256::new();
+        hash.update(email::normalize(email));
+        hash.update(b"\n");
+        // Items serialize the same way every time: fields in declaration
+        // order and metadata sorted by key.
+        hash.update(serde_json::to_vec(items).unwrap_or_default());
+        hex::encode(hash.finalize())
+    }
+
+    /// Replace the time-ordered id [`Order::new`] gave it.
+    pub fn with_id(mut self, id: Uuid) -> Self {
+        self.id = id;
+        self
+    }
+
+    pub fn with_tenant(mut self, tenant_id: TenantId) -> Self {
+        self.tenant_id = tenant_id;
+        self
+    }
+
+    /// Where the order ships to and who is billed; see [`Address::check`].
+    pub fn with_addresses(mut self, shipping: Option<Address>, billing: Option<Address>) -> Self {
+        self.shipping_address = shipping;
+        self.billing_address = billing;
+        self
+    }
+
+    pub fn with_metadata(mut self, metadata: BTreeMap<String, String>) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Attribute the order's creation, which is also its latest change, to
+    /// `actor`.
+    pub fn with_creator(mut self, actor: Actor) -> Self {
+        self.created_by = Some(actor.clone());
+        self.updated_by = Some(actor);
+        self
+    }
+
     pub fn update_status(&mut self, status: OrderStatus) {
+        self.update_status_at(status, Utc::now());
+    }
+
+    /// [`Order::update_status`], changed at `now`.
+    pub fn update_status_at(&mut self, status: OrderStatus, now: DateTime<Utc>) {
         self.status = status;
-        self.updated_at = Utc::now();
+        self.updated_at = now;
+    }
+
+    /// Attach the confirmation-time pricing, updated as of its
+    /// [`priced_at`](PricingSnapshot::priced_at). A snapshot that is already
+    /// frozen is left untouched and `false` is returned.
+    pub fn freeze_pricing(&mut self, snapshot: PricingSnapshot) -> bool {
+        if self.pricing.is_some() {
+            return false;
+        }
+        self.set_charges(&snapshot);
+        self.updated_at = snapshot.priced_at();
+        self.pricing = Some(snapshot);
+        true
+    }
+
+    /// Whether `other` is the same version of this order: the same status,
+    /// last changed at the same time. Conditional writes compare the stored
+    /// order with the copy a change was computed from this way.
+    pub fn same_version(&self, other: &Order) -> bool {
+        self.status == other.status && self.updated_at == other.updated_at
+    }
+
+    /// Whether the items can still be changed: only pending orders, which
+    /// have no frozen pricing yet, are editable.
+    pub fn items_editable(&self) -> bool {
+        self.status == OrderStatus::Pending
+    }
+
+    /// Replace the items of a pending order and price it again with `rules`.
+    /// An applied discount code is kept, but never takes off more than the
+    /// new total.
+    pub fn replace_items(
+        &mut self,
+        items: Vec<OrderItem>,
+        rules: &dyn PricingRules,
+    ) -> Result<(), OrderChangeError> {
+        self.replace_items_at(items, rules, Utc::now())
+    }
+
+    /// [`Order::replace_items`], changed at `now`.
+    pub fn replace_items_at(
+        &mut self,
+        items: Vec<OrderItem>,
+        rules: &dyn PricingRules,
+        now: DateTime<Utc>,
+    ) -> Result<(), OrderChangeError> {
+        if !self.items_editable() {
+            return Err(OrderChangeError::ItemsLocked(self.status.clone()));
+        }
+        if let Some(e) = Self::validate(&self.customer_name, &self.email, &items)
+            .into_iter()
+            .next()
+        {
+            return Err(e.into());
+        }
+        let currency = items[0].unit_price.currency();
+        let priced = PricingSnapshot::compute_at(&items, rules, now)?;
+        if let Some(d) = self.discount.as_mut() {
+            d.amount_cents = d.amount_cents.min(priced.total_cents().max(0));
+        }
+        self.items = items;
+        self.total = Money::zero(currency);
+        self.set_charges(&priced);
+        self.updated_at = now;
+        Ok(())
+    }
+
+    /// Whether the order may still be cancelled: only before it ships.
+    pub fn cancellable(&self) -> bool {
+        matches!(self.status, OrderStatus::Pending | OrderStatus::Confirmed)
+    }
+
+    /// Whether the customer has been charged. Orders are charged when they
+    /// are confirmed, which is also when their pricing is frozen.
+    pub fn is_paid(&self) -> bool {
+        self.pricing.is_some()
+    }
+
+    /// Cancel the order, recording `reason`.
+    pub fn cancel(&mut self, reason: &str) -> Result<(), OrderChangeError> {
+        self.cancel_at(reason, Utc::now())
+    }
+
+    /// [`Order::cancel`], cancelled at `now`.
+    pub fn cancel_at(&mut self, reason: &str, now: DateTime<Utc>) -> Result<(), OrderChangeError> {
+        if !self.cancellable() {
+            return Err(OrderChangeError::NotCancellable(self.status.clone()));
+        }
+        let reason = reason.trim();
+        if reason.is_empty() {
+            return Err(OrderValidationError::EmptyReason.into());
+        }
+        self.update_status_at(OrderStatus::Cancelled, now);
+        self.cancellation = Some(Cancellation {
+            reason: reason.to_string(),
+            cancelled_at: self.updated_at,
+        });
+        Ok(())
+    }
+
+    /// Take a discount off the total, replacing any applied before. It stays
+    /// applied, for the same amount, when pricing is frozen or re-priced.
+    /// The order is left as it was if the amounts overflow.
+    pub fn apply_discount(
+        &mut self,
+        discount: AppliedDiscount,
+    ) -> Result<(), OrderValidationError> {
+        let delta = discount
+            .amount_cents
+            .checked_sub(self.discount_off())
+            .ok_or(OrderValidationError::TotalOverflow)?;
+        let discount_cents = self
+            .charges
+            .discount_cents
+            .checked_add(delta)
+            .ok_or(OrderValidationError::TotalOverflow)?;
+        let total = self
+            .total
+            .amount_minor()
+            .checked_sub(delta)
+            .ok_or(OrderValidationError::TotalOverflow)?;
+        self.discount = Some(discount);
+        self.charges.discount_cents = discount_cents;
+        self.total = Money::new(total, self.total.currency());
+        Ok(())
+    }
+
+    fn discount_off(&self) -> i64 {
+        self.discount.as_ref().map_or(0, |d| d.amount_cents)
+    }
+
+    /// Totals from `snapshot`, less any applied discount code.
+    fn set_charges(&mut self, snapshot: &PricingSnapshot) {
+        let off = self.discount_off();
+        self.charges = snapshot.charges();
+        self.charges.discount_cents += off;
+        self.total = Money::new(snapshot.total_cents() - off, self.total.currency());
+    }
+
+    /// Replace the customer's personal data with placeholders, for an
+    /// erasure request. Items, totals, statuses and each address's region
+    /// and country stay for accounting and tax reporting. `updated_at` is
+    /// left to the caller, so stored copies can be scrubbed as they were.
+    pub fn anonymize(&mut self) {
+        self.customer_name = Self::ERASED.into();
+        self.email = Self::ERASED_EMAIL.into();
+        for address in [&mut self.shipping_address, &mut self.billing_address]
+            .into_iter()
+            .flatten()
+        {
+            address.line1 = Self::ERASED.into();
+            address.line2 = None;
+            address.city = Self::ERASED.into();
+            address.postal_code = Self::ERASED.into();
+        }
+    }
+
+    /// Replace the frozen pricing, returning the previous snapshot. The
+    /// order is updated as of the new snapshot's `priced_at`. Only confirmed
+    /// orders whose payment hasn't been captured can be re-priced; otherwise
+    /// the order is left untouched.
+    pub fn reprice(&mut self, snapshot: PricingSnapshot) -> Result<PricingSnapshot, RepriceError> {
+        if !matches!(self.status, OrderStatus::Pending | OrderStatus::Confirmed) {
+            return Err(RepriceError::Status(self.status.clone()));
+        }
+        if self.payment_id.is_some() || self.captured.is_some() {
+            return Err(RepriceError::Captured);
+        }
+        if self.pricing.is_none() {
+            return Err(RepriceError::NotPriced);
+        }
+        self.set_charges(&snapshot);
+        self.updated_at = snapshot.priced_at();
+        Ok(self.pricing.replace(snapshot).expect("checked above"))
     }
 }
 
@@ -83,19 +789,71 @@ mod tests {
             OrderItem {
                 name: "A".into(),
                 qty: 2,
-                unit_price_cents: 500,
+                unit_price: Money::usd(500),
+                weight_grams: 0,
+                sku: None,
+                description: None,
+                metadata: Default::default(),
+                discount_cents: 0,
             },
             OrderItem {
                 name: "B".into(),
                 qty: 1,
-                unit_price_cents: 250,
+                unit_price: Money::usd(250),
+                weight_grams: 0,
+                sku: None,
+                description: None,
+                metadata: Default::default(),
+                discount_cents: 0,
             },
         ];
         let order = Order::new("Alice".into(), "a@b.com".into(), items).unwrap();
-        assert_eq!(order.total_cents, 1250);
+        assert_eq!(order.total.amount_minor(), 1250);
         assert_eq!(order.status, OrderStatus::Pending);
     }
 
+    #[test]
+    fn limits_name_each_oversized_field() {
+        let item = OrderItem {
+            name: "A".into(),
+            qty: 1,
+            unit_price: Money::usd(100),
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
+        };
+        let limits = OrderLimits {
+            max_items: 2,
+            max_customer_name_len: 3,
+            max_email_len: 7,
+            max_total_cents: Some(1000),
+        };
+        assert!(limits
+            .check("Åsa", "a@b.com", &[item.clone(), item.clone()])
+            .is_empty());
+        let fields: Vec<String> = limits
+            .check("Anna", "ann@b.com", &vec![item; 3])
+            .into_iter()
+            .map(|e| e.field)
+            .collect();
+        assert_eq!(fields, ["customer_name", "email", "items"]);
+
+        assert_eq!(limits.check_total(Money::usd(1000)), None);
+        assert_eq!(
+            limits.check_total(Money::usd(1001)),
+            Some(FieldError::new(
+                "items",
+                "the order total must be at most 10.00 USD"
+            ))
+        );
+        assert_eq!(
+            OrderLimits::default().check_total(Money::usd(i64::MAX)),
+            None
+        );
+    }
+
     #[test]
     fn validation_errors() {
         let empty_name = Order::new(
@@ -104,10 +862,15 @@ mod tests {
             vec![OrderItem {
                 name: "A".into(),
                 qty: 1,
-                unit_price_cents: 100,
+                unit_price: Money::usd(100),
+                weight_grams: 0,
+                sku: None,
+                description: None,
+                metadata: Default::default(),
+                discount_cents: 0,
             }],
         );
-        assert!(empty_name.is_err());
+        assert_eq!(empty_name.unwrap_err(), OrderValidationError::EmptyName);
 
         let bad_email = Order::new(
             "Bob".into(),
@@ -115,13 +878,21 @@ mod tests {
             vec![OrderItem {
                 name: "A".into(),
                 qty: 1,
-                unit_price_cents: 100,
+                unit_price: Money::usd(100),
+                weight_grams: 0,
+                sku: None,
+                description: None,
+                metadata: Default::default(),
+                discount_cents: 0,
             }],
         );
-        assert!(bad_email.is_err());
+        assert_eq!(
+            bad_email.unwrap_err(),
+            OrderValidationError::InvalidEmail("must contain `@`".into())
+        );
 
         let empty_items = Order::new("Bob".into(), "b@c.com".into(), vec![]);
-        assert!(empty_items.is_err());
+        assert_eq!(empty_items.unwrap_err(), OrderValidationError::NoItems);
 
         let zero_qty = Order::new(
             "Bob".into(),
@@ -129,27 +900,561 @@ mod tests {
             vec![OrderItem {
                 name: "A".into(),
                 qty: 0,
-                unit_price_cents: 100,
+                unit_price: Money::usd(100),
+                weight_grams: 0,
+                sku: None,
+                description: None,
+                metadata: Default::default(),
+                discount_cents: 0,
             }],
         );
-        assert!(zero_qty.is_err());
+        assert_eq!(
+            zero_qty.unwrap_err(),
+            OrderValidationError::ZeroQty { index: 0 }
+        );
+    }
+
+    #[test]
+    fn negative_prices_and_overflowing_totals_are_refused() {
+        let item = |qty, cents| OrderItem {
+            name: "A".into(),
+            qty,
+            unit_price: Money::usd(cents),
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
+        };
+        let negative = Order::new(
+            "Bob".into(),
+            "b@c.com".into(),
+            vec![item(1, 100), item(1, -1)],
+        );
+        let err = negative.unwrap_err();
+        assert_eq!(err, OrderValidationError::NegativePrice { index: 1 });
+        assert_eq!(
+            FieldError::from(err),
+            FieldError::new("items[1].unit_price_cents", "must not be negative")
+        );
+
+        let huge = Order::new(
+            "Bob".into(),
+            "b@c.com".into(),
+            vec![item(2, i64::MAX / 2 + 1)],
+        );
+        assert_eq!(huge.unwrap_err(), OrderValidationError::TotalOverflow);
+        let fits = Order::new("Bob".into(), "b@c.com".into(), vec![item(1, i64::MAX)]);
+        assert!(fits.is_ok());
+    }
+
+    #[test]
+    fn check_reports_every_field() {
+        let items = vec![
+            OrderItem {
+                name: "A".into(),
+                qty: 1,
+                unit_price: Money::usd(100),
+                weight_grams: 0,
+                sku: None,
+                description: None,
+                metadata: Default::default(),
+                discount_cents: 0,
+            },
+            OrderItem {
+                name: "B".into(),
+                qty: 0,
+                unit_price: Money::usd(100),
+                weight_grams: 0,
+                sku: None,
+                description: None,
+                metadata: Default::default(),
+                discount_cents: 0,
+            },
+        ];
+        let errors = Order::check(" ", "nope", &items);
+        let fields: Vec<_> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, ["customer_name", "email", "items[1].qty"]);
+        assert!(Order::check("Ann", "a@b.com", &items[..1]).is_empty());
+    }
+
+    #[test]
+    fn catalog_fields_are_validated() {
+        let item = |sku: &str, discount_cents: i64| OrderItem {
+            name: "A".into(),
+            qty: 2,
+            unit_price: Money::usd(100),
+            weight_grams: 0,
+            sku: Some(sku.into()),
+            description: None,
+            metadata: [("".to_string(), "x".to_string())].into(),
+            discount_cents,
+        };
+        let errors = Order::check("Ann", "a@b.com", &[item("WID 1", -1), item("ok", 201)]);
+        let fields: Vec<_> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            [
+                "items[0].sku",
+                "items[0].metadata",
+                "items[0].discount_cents",
+                "items[1].metadata",
+                "items[1].discount_cents"
+            ]
+        );
+        assert!(OrderItem::check_sku("WID-1_a.2").is_ok());
+        assert!(OrderItem::check_sku(&"A".repeat(65)).is_err());
+
+        let mut ok = item("WID-1", 50);
+        ok.metadata.clear();
+        let order = Order::new("Ann".into(), "a@b.com".into(), vec![ok]).unwrap();
+        assert_eq!(order.charges.discount_cents, 50);
+        assert_eq!(order.total, Money::usd(150));
+        let json = serde_json::to_value(&order).unwrap();
+        assert_eq!(json["items"][0]["sku"], "WID-1");
+        assert_eq!(json["items"][0]["discount_cents"], 50);
+        assert!(json["items"][0].get("metadata").is_none());
+    }
+
+    #[test]
+    fn mixed_currencies_are_rejected() {
+        use crate::domain::money::Currency;
+
+        let items = vec![
+            OrderItem {
+                name: "A".into(),
+                qty: 1,
+                unit_price: Money::new(100, Currency::EUR),
+                weight_grams: 0,
+                sku: None,
+                description: None,
+                metadata: Default::default(),
+                discount_cents: 0,
+            },
+            OrderItem {
+                name: "B".into(),
+                qty: 1,
+                unit_price: Money::usd(100),
+                weight_grams: 0,
+                sku: None,
+                description: None,
+                metadata: Default::default(),
+                discount_cents: 0,
+            },
+        ];
+        let errors = Order::check("Ann", "a@b.com", &items);
+        assert_eq!(
+            errors,
+            [FieldError::new(
+                "items[1].currency",
+                "must match the order currency EUR"
+            )]
+        );
+        assert!(Order::new("Ann".into(), "a@b.com".into(), items.clone()).is_err());
+
+        let order = Order::new("Ann".into(), "a@b.com".into(), items[..1].to_vec()).unwrap();
+        assert_eq!(order.total, Money::new(100, Currency::EUR));
+        let json = serde_json::to_value(&order).unwrap();
+        assert_eq!(json["total_cents"], 100);
+        assert_eq!(json["currency"], "EUR");
+        assert_eq!(json["items"][0]["currency"], "EUR");
     }
 
     #[test]
     fn update_status_mutates_timestamp() {
-        let mut order = Order::new(
+        let created = DateTime::parse_from_rfc3339("2026-01-01T09:00:00Z")
+            .unwrap()
+            .to_utc();
+        let mut order = Order::new_priced_at(
             "Carol".into(),
             "c@d.com".into(),
             vec![OrderItem {
                 name: "A".into(),
                 qty: 1,
-                unit_price_cents: 100,
+                unit_price: Money::usd(100),
+                weight_grams: 0,
+                sku: None,
+                description: None,
+                metadata: Default::default(),
+                discount_cents: 0,
             }],
+            &ItemPriceRules,
+            created,
         )
         .unwrap();
-        let before = order.updated_at;
-        order.update_status(OrderStatus::Shipped);
+        assert_eq!((order.created_at, order.updated_at), (created, created));
+        let shipped = created + chrono::Duration::seconds(1);
+        order.update_status_at(OrderStatus::Shipped, shipped);
         assert_eq!(order.status, OrderStatus::Shipped);
-        assert!(order.updated_at > before);
+        assert_eq!((order.created_at, order.updated_at), (created, shipped));
+    }
+
+    #[test]
+    fn transitions_only_move_forward() {
+        use OrderStatus::*;
+        assert!(Pending.can_transition_to(&Shipped));
+        assert!(Confirmed.can_transition_to(&Completed));
+        assert!(Shipped.can_transition_to(&Shipped));
+        assert!(!Shipped.can_transition_to(&Pending));
+        assert!(!Shipped.can_transition_to(&Cancelled));
+        assert!(!Cancelled.can_transition_to(&Confirmed));
+        assert!(!Completed.can_transition_to(&Shipped));
+    }
+
+    #[test]
+    fn priced_orders_include_tax_and_shipping() {
+        use crate::ports::pricing::{PricingPolicy, ShippingRule};
+
+        let policy = PricingPolicy {
+            tax_rate_bps: 800,
+            shipping: ShippingRule::ByWeight {
+                base_cents: 200,
+                per_kg_cents: 100,
+            },
+        };
+        let order = Order::new_priced(
+            "Eve".into(),
+            "e@f.com".into(),
+            vec![OrderItem {
+                name: "A".into(),
+                qty: 2,
+                unit_price: Money::usd(1000),
+                weight_grams: 700,
+                sku: None,
+                description: None,
+                metadata: Default::default(),
+                discount_cents: 0,
+            }],
+            &policy,
+        )
+        .unwrap();
+        assert_eq!(order.charges.subtotal_cents, 2000);
+        assert_eq!(order.charges.tax_cents, 160);
+        assert_eq!(order.charges.shipping_cents, 400);
+        assert_eq!(order.total.amount_minor(), 2560);
+        assert!(order.pricing.is_none());
+
+        let json = serde_json::to_value(&order).unwrap();
+        assert_eq!(json["subtotal_cents"], 2000);
+        assert_eq!(json["tax_cents"], 160);
+        assert_eq!(json["shipping_cents"], 400);
+        assert_eq!(json["total_cents"], 2560);
+        assert_eq!(json["items"][0]["weight_grams"], 700);
+    }
+
+    #[test]
+    fn applied_discounts_survive_repricing() {
+        let mut order = Order::new(
+            "Fay".into(),
+            "f@g.com".into(),
+            vec![OrderItem {
+                name: "A".into(),
+                qty: 2,
+                unit_price: Money::usd(1000),
+                weight_grams: 0,
+                sku: None,
+                description: None,
+                metadata: Default::default(),
+                discount_cents: 0,
+            }],
+        )
+        .unwrap();
+        order
+            .apply_discount(AppliedDiscount {
+                code: "SAVE5".into(),
+                amount_cents: 500,
+            })
+            .unwrap();
+        assert_eq!(order.total.amount_minor(), 1500);
+        assert_eq!(order.charges.discount_cents, 500);
+
+        let snap = PricingSnapshot::compute(&order.items, &ItemPriceRules).unwrap();
+        order.freeze_pricing(snap);
+        assert_eq!(order.total.amount_minor(), 1500);
+        assert_eq!(order.charges.discount_cents, 500);
+
+        let json = serde_json::to_value(&order).unwrap();
+        assert_eq!(json["discount"]["code"], "SAVE5");
+        assert_eq!(json["discount"]["amount_cents"], 500);
+    }
+
+    #[test]
+    fn replacing_items_reprices_pending_orders_only() {
+        let item = |qty, cents| OrderItem {
+            name: "A".into(),
+            qty,
+            unit_price: Money::usd(cents),
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
+        };
+        let mut order = Order::new("Gus".into(), "g@h.com".into(), vec![item(1, 1000)]).unwrap();
+        order
+            .apply_discount(AppliedDiscount {
+                code: "FIVE".into(),
+                amount_cents: 500,
+            })
+            .unwrap();
+
+        order
+            .replace_items(vec![item(3, 1000)], &ItemPriceRules)
+            .unwrap();
+        assert_eq!(order.charges.subtotal_cents, 3000);
+        assert_eq!(order.total.amount_minor(), 2500);
+
+        order
+            .replace_items(vec![item(1, 200)], &ItemPriceRules)
+            .unwrap();
+        assert_eq!(order.discount.as_ref().unwrap().amount_cents, 200);
+        assert_eq!(order.total.amount_minor(), 0);
+
+        assert_eq!(
+            order.replace_items(vec![], &ItemPriceRules),
+            Err(OrderChangeError::Invalid(OrderValidationError::NoItems))
+        );
+        order.update_status(OrderStatus::Confirmed);
+        assert_eq!(
+            order.replace_items(vec![item(1, 100)], &ItemPriceRules),
+            Err(OrderChangeError::ItemsLocked(OrderStatus::Confirmed))
+        );
+        assert_eq!(order.items[0].unit_price.amount_minor(), 200);
+    }
+
+    #[test]
+    fn only_unshipped_orders_can_be_cancelled() {
+        let mut order = Order::new(
+            "Hal".into(),
+            "h@i.com".into(),
+            vec![OrderItem {
+                name: "A".into(),
+                qty: 1,
+                unit_price: Money::usd(100),
+                weight_grams: 0,
+                sku: None,
+                description: None,
+                metadata: Default::default(),
+                discount_cents: 0,
+            }],
+        )
+        .unwrap();
+        assert!(!order.is_paid());
+        assert_eq!(
+            order.cancel("  "),
+            Err(OrderChangeError::Invalid(OrderValidationError::EmptyReason))
+        );
+
+        let mut shipped = order.clone();
+        shipped.update_status(OrderStatus::Shipped);
+        assert_eq!(
+            shipped.cancel("changed my mind"),
+            Err(OrderChangeError::NotCancellable(OrderStatus::Shipped))
+        );
+        assert!(shipped.cancellation.is_none());
+
+        order.cancel(" changed my mind ").unwrap();
+        assert_eq!(order.status, OrderStatus::Cancelled);
+        let cancellation = order.cancellation.as_ref().unwrap();
+        assert_eq!(cancellation.reason, "changed my mind");
+        assert_eq!(cancellation.cancelled_at, order.updated_at);
+        assert!(order.cancel("again").is_err());
+    }
+
+    #[test]
+    fn frozen_pricing_is_not_overwritten() {
+        let mut order = Order::new(
+            "Dan".into(),
+            "d@e.com".into(),
+            vec![OrderItem {
+                name: "A".into(),
+                qty: 3,
+                unit_price: Money::usd(100),
+                weight_grams: 0,
+                sku: None,
+                description: None,
+                metadata: Default::default(),
+                discount_cents: 0,
+            }],
+        )
+        .unwrap();
+        let snap = PricingSnapshot::compute(&order.items, &ItemPriceRules).unwrap();
+        assert!(order.freeze_pricing(snap.clone()));
+        assert_eq!(order.total.amount_minor(), 300);
+
+        order.items[0].unit_price = Money::usd(999);
+        let again = PricingSnapshot::compute(&order.items, &ItemPriceRules).unwrap();
+        assert!(!order.freeze_pricing(again.clone()));
+        assert_eq!(order.pricing.as_ref(), Some(&snap));
+
+        let previous = order.reprice(again);
+        assert_eq!(previous, Ok(snap));
+        assert_eq!(order.total.amount_minor(), 2997);
+    }
+
+    #[test]
+    fn reprice_leaves_unpriced_captured_and_shipped_orders_alone() {
+        let mut order = Order::new(
+            "Al".into(),
+            "al@example.com".into(),
+            vec![OrderItem {
+                name: "A".into(),
+                qty: 1,
+                unit_price: Money::usd(100),
+                weight_grams: 0,
+                sku: None,
+                description: None,
+                metadata: Default::default(),
+                discount_cents: 0,
+            }],
+        )
+        .unwrap();
+        let snap = PricingSnapshot::compute(&order.items, &ItemPriceRules).unwrap();
+        order.items[0].unit_price = Money::usd(500);
+        let dearer = PricingSnapshot::compute(&order.items, &ItemPriceRules).unwrap();
+
+        let untouched = (order.total, order.charges, order.pricing.clone());
+        assert_eq!(order.reprice(dearer.clone()), Err(RepriceError::NotPriced));
+        assert_eq!(
+            (order.total, order.charges, order.pricing.clone()),
+            untouched
+        );
+
+        order.freeze_pricing(snap);
+        order.update_status(OrderStatus::Confirmed);
+        order.payment_id = Some("pay_1".into());
+        order.captured = Some(order.total);
+        let untouched = (order.total, order.charges, order.pricing.clone());
+        assert_eq!(order.reprice(dearer.clone()), Err(RepriceError::Captured));
+        assert_eq!(
+            (order.total, order.charges, order.pricing.clone()),
+            untouched
+        );
+
+        order.payment_id = None;
+        order.captured = None;
+        order.update_status(OrderStatus::Shipped);
+        let untouched = (order.total, order.charges, order.pricing.clone());
+        assert_eq!(
+            order.reprice(dearer),
+            Err(RepriceError::Status(OrderStatus::Shipped))
+        );
+        assert_eq!(
+            (order.total, order.charges, order.pricing.clone()),
+            untouched
+        );
+    }
+
+    #[test]
+    fn line_and_discount_arithmetic_is_checked() {
+        let item = |unit, qty, discount_cents| OrderItem {
+            name: "A".into(),
+            qty,
+            unit_price: Money::usd(unit),
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents,
+        };
+        let overflow = Err(OrderValidationError::TotalOverflow);
+        assert_eq!(item(i64::MAX, 2, 0).net_cents(), overflow);
+        assert_eq!(item(i64::MAX, 1, -1).net_cents(), overflow);
+        assert_eq!(item(i64::MAX, 1, 0).net_cents(), Ok(i64::MAX));
+
+        // A line discounted down to nothing already counts i64::MAX off.
+        let mut order = Order::new(
+            "Ida".into(),
+            "ida@example.com".into(),
+            vec![item(i64::MAX, 1, i64::MAX)],
+        )
+        .unwrap();
+        assert_eq!(order.charges.discount_cents, i64::MAX);
+        let before = (order.total, order.charges);
+        let code = AppliedDiscount {
+            code: "ONE".into(),
+            amount_cents: 1,
+        };
+        assert_eq!(
+            order.apply_discount(code),
+            Err(OrderValidationError::TotalOverflow)
+        );
+        assert_eq!((order.total, order.charges), before);
+        assert!(order.discount.is_none());
+    }
+
+    #[test]
+    fn anonymize_keeps_totals_and_tax_location() {
+        let address = Address {
+            line1: "1 Main St".into(),
+            line2: Some("Flat 2".into()),
+            city: "Berlin".into(),
+            region: Some("BE".into()),
+            postal_code: "10115".into(),
+            country: "DE".into(),
+        };
+        let mut order = Order::new(
+            "Eve".into(),
+            "eve@example.com".into(),
+            vec![OrderItem {
+                name: "A".into(),
+                qty: 2,
+                unit_price: Money::usd(150),
+                weight_grams: 0,
+                sku: None,
+                description: None,
+                metadata: Default::default(),
+                discount_cents: 0,
+            }],
+        )
+        .unwrap()
+        .with_addresses(Some(address), None);
+        let updated_at = order.updated_at;
+        order.anonymize();
+        assert_eq!(
+            (order.customer_name.as_str(), order.email.as_str()),
+            (Order::ERASED, Order::ERASED_EMAIL)
+        );
+        let shipping = order.shipping_address.as_ref().unwrap();
+        assert_eq!(
+            (shipping.city.as_str(), shipping.line2.as_deref()),
+            (Order::ERASED, None)
+        );
+        assert_eq!(
+            (shipping.region.as_deref(), shipping.country.as_str()),
+            (Some("BE"), "DE")
+        );
+        assert_eq!(order.total.amount_minor(), 300);
+        assert_eq!(order.updated_at, updated_at);
+    }
+
+    #[test]
+    fn fingerprint_ignores_email_case_and_padding_but_not_items() {
+        let item = |qty| OrderItem {
+            name: "A".into(),
+            qty,
+            unit_price: Money::usd(150),
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
+        };
+        let print = Order::fingerprint("eve@example.com", &[item(1), item(2)]);
+        for email in ["Eve@Example.com", " eve@example.com\t", "EVE@EXAMPLE.COM "] {
+            assert_eq!(print, Order::fingerprint(email, &[item(1), item(2)]));
+        }
+        assert_eq!(
+            Order::fingerprint("Émile@example.com", &[item(1)]),
+            Order::fingerprint("émile@example.com", &[item(1)])
+        );
+        assert_ne!(
+            print,
+            Order::fingerprint("eve@example.com", &[item(2), item(1)])
+        );
+        assert_ne!(
+            print,
+            Order::fingerprint("bob@example.com", &[item(1), item(2)])
+        );
     }
 }
@@ -0,0 +1,106 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// The number an order is quoted by, e.g. `ORD-2026-000123`: the year it was
+/// created and a sequence number the repository hands out in increasing
+/// order when the order is stored. The sequence is shared by every tenant
+/// and carries on across years; it has at least six digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct OrderNumber {
+    year: i32,
+    seq: u64,
+}
+
+impl OrderNumber {
+    pub const PREFIX: &'static str = "ORD";
+
+    pub fn new(year: i32, seq: u64) -> Self {
+        Self { year, seq }
+    }
+
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    /// `ORD-<year>-<seq>`, ignoring ASCII case.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let invalid = || format!("invalid order number `{s}`");
+        let mut parts = s.trim().splitn(3, '-');
+        let (Some(prefix), Some(year), Some(seq)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+        let digits = |p: &str, min: usize| p.len() >= min && p.bytes().all(|b| b.is_ascii_digit());
+        if !prefix.eq_ignore_ascii_case(Self::PREFIX) || !digits(year, 4) || !digits(seq, 1) {
+            return Err(invalid());
+        }
+        Ok(Self {
+            year: year.parse().map_err(|_| invalid())?,
+            seq: seq.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+impl fmt::Display for OrderNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}-{:06}", Self::PREFIX, self.year, self.seq)
+    }
+}
+
+impl FromStr for OrderNumber {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl Serialize for OrderNumber {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for OrderNumber {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Self::parse(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_round_trip_through_text() {
+        let number = OrderNumber::new(2026, 123);
+        assert_eq!(number.to_string(), "ORD-2026-000123");
+        assert_eq!(OrderNumber::parse("ORD-2026-000123"), Ok(number));
+        assert_eq!(OrderNumber::parse("ord-2026-123"), Ok(number));
+        assert_eq!(
+            OrderNumber::new(2026, 1_234_567).to_string(),
+            "ORD-2026-1234567"
+        );
+        for bad in [
+            "",
+            "ORD-2026",
+            "INV-2026-000123",
+            "ORD-26-000123",
+            "ORD-2026-12a",
+            "ORD-2026-",
+        ] {
+            assert!(OrderNumber::parse(bad).is_err(), "{bad}");
+        }
+        assert_eq!(
+            serde_json::to_value(number).unwrap(),
+            serde_json::json!("ORD-2026-000123")
+        );
+    }
+}
@@ -0,0 +1,320 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::domain::address::Address;
+use crate::domain::email;
+use crate::domain::order::{check_metadata, FieldError, Order};
+
+/// A JSON Merge Patch (RFC 7396) of an order's customer details and
+/// addresses and metadata, as taken by `PATCH /orders/{id}`. A member sets its field,
+/// `null` clears an optional one and anything left out stays as it is;
+/// addresses merge member by member, so `{"shipping_address":{"city":"Bonn"}}`
+/// only moves the city, and `{"metadata":{"erp_id":null}}` only drops one key.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct OrderPatch(pub Map<String, Value>);
+
+impl OrderPatch {
+    /// Media type of a merge patch document.
+    pub const CONTENT_TYPE: &'static str = "application/merge-patch+json";
+
+    /// The fields a patch may change.
+    pub const FIELDS: &'static [&'static str] = &[
+        "customer_name",
+        "email",
+        "shipping_address",
+        "billing_address",
+        "metadata",
+    ];
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_customer_name(self, name: impl Into<String>) -> Self {
+        self.with("customer_name", Value::String(name.into()))
+    }
+
+    pub fn with_email(self, email: impl Into<String>) -> Self {
+        self.with("email", Value::String(email.into()))
+    }
+
+    /// Replace the shipping address, or clear it with `None`.
+    pub fn with_shipping_address(self, address: Option<Address>) -> Self {
+        self.with("shipping_address", address_value(address))
+    }
+
+    /// Replace the billing address, or clear it with `None`.
+    pub fn with_billing_address(self, address: Option<Address>) -> Self {
+        self.with("billing_address", address_value(address))
+    }
+
+    /// Set one metadata entry, or remove it with `None`.
+    pub fn with_metadata_entry(mut self, key: impl Into<String>, value: Option<String>) -> Self {
+        let metadata = self
+            .0
+            .entry("metadata")
+            .or_insert_with(|| Value::Object(Map::new()));
+        if let Value::Object(entries) = metadata {
+            entries.insert(key.into(), value.map_or(Value::Null, Value::String));
+        }
+        self
+    }
+
+    fn with(mut self, field: &str, value: Value) -> Self {
+        self.0.insert(field.to_owned(), value);
+        self
+    }
+
+    /// `order` with the patch applied and `updated_at` set to `now`, or
+    /// `None` when the patch changes nothing. Fails with every problem found:
+    /// members that aren't in [`Self::FIELDS`], values of the wrong shape and
+    /// a result that doesn't pass the order's own checks.
+    pub fn apply_at(
+        &self,
+        order: &Order,
+        now: DateTime<Utc>,
+    ) -> Result<Option<Order>, Vec<FieldError>> {
+        let mut errors: Vec<FieldError> = self
+            .0
+            .keys()
+            .filter(|k| !Self::FIELDS.contains(&k.as_str()))
+            .map(|k| FieldError::new(k.clone(), "cannot be changed"))
+            .collect();
+
+        let mut doc = Map::new();
+        doc.insert("customer_name".into(), order.customer_name.clone().into());
+        doc.insert("email".into(), order.email.clone().into());
+        for (field, address) in [
+            ("shipping_address", &order.shipping_address),
+            ("billing_address", &order.billing_address),
+        ] {
+            if let Some(address) = address {
+                doc.insert(field.into(), address_value(Some(address.clone())));
+            }
+        }
+        if !order.metadata.is_empty() {
+            doc.insert(
+                "metadata".into(),
+                Value::Object(
+                    order
+                        .metadata
+                        .iter()
+                        .map(|(k, v)| (k.clone(), v.clone().into()))
+                        .collect(),
+                ),
+            );
+        }
+        let mut doc = Value::Object(doc);
+        merge(&mut doc, &Value::Object(self.0.clone()));
+
+        let mut patched = order.clone();
+        match required::<String>(&doc, "customer_name") {
+            Ok(name) => patched.customer_name = name,
+            Err(e) => errors.push(e),
+        }
+        match required::<String>(&doc, "email") {
+            Ok(email) => patched.email = email::normalize(&email),
+            Err(e) => errors.push(e),
+        }
+        match optional::<Address>(&doc, "shipping_address") {
+            Ok(address) => patched.shipping_address = address,
+            Err(e) => errors.push(e),
+        }
+        match optional::<Address>(&doc, "billing_address") {
+            Ok(address) => patched.billing_address = address,
+            Err(e) => errors.push(e),
+        }
+        match optional::<BTreeMap<String, String>>(&doc, "metadata") {
+            Ok(metadata) => patched.metadata = metadata.unwrap_or_default(),
+            Err(e) => errors.push(e),
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        errors.extend(Order::check(
+            &patched.customer_name,
+            &patched.email,
+            &patched.items,
+        ));
+        if let Some(address) = &patched.shipping_address {
+            errors.extend(address.check("shipping_address"));
+        }
+        if let Some(address) = &patched.billing_address {
+            errors.extend(address.check("billing_address"));
+        }
+        errors.extend(check_metadata(&patched.metadata, "metadata"));
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let unchanged = patched.customer_name == order.customer_name
+            && patched.email == order.email
+            && patched.shipping_address == order.shipping_address
+            && patched.billing_address == order.billing_address
+            && patched.metadata == order.metadata;
+        if unchanged {
+            return Ok(None);
+        }
+        patched.updated_at = now;
+        Ok(Some(patched))
+    }
+}
+
+fn address_value(address: Option<Address>) -> Value {
+    // An address always serializes to a JSON object.
+    serde_json::to_value(address).unwrap_or(Value::Null)
+}
+
+/// RFC 7396 section 2: merge `patch` into `target`.
+fn merge(target: &mut Value, patch: &Value) {
+    let Value::Object(members) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    if let Value::Object(target) = target {
+        for (name, value) in members {
+            if value.is_null() {
+                target.remove(name);
+            } else {
+                merge(target.entry(name.clone()).or_insert(Value::Null), value);
+            }
+        }
+    }
+}
+
+fn required<T: DeserializeOwned>(doc: &Value, field: &str) -> Result<T, FieldError> {
+    match optional(doc, field)? {
+        Some(value) => Ok(value),
+        None => Err(FieldError::new(field, "must not be null")),
+    }
+}
+
+fn optional<T: DeserializeOwned>(doc: &Value, field: &str) -> Result<Option<T>, FieldError> {
+    doc.get(field)
+        .map(T::deserialize)
+        .transpose()
+        .map_err(|e| FieldError::new(field, e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::money::Money;
+    use crate::domain::order::OrderItem;
+    use serde_json::json;
+
+    fn order() -> Order {
+        let item = OrderItem {
+            name: "Widget".into(),
+            qty: 1,
+            unit_price: Money::usd(500),
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
+        };
+        let shipping = Address {
+            line1: "1 Main St".into(),
+            line2: None,
+            city: "Berlin".into(),
+            region: None,
+            postal_code: "10115".into(),
+            country: "DE".into(),
+        };
+        Order::new("Ann".into(), "ann@example.com".into(), vec![item])
+            .unwrap()
+            .with_addresses(Some(shipping), None)
+    }
+
+    fn patch(value: Value) -> OrderPatch {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn members_set_merge_and_clear_fields() {
+        let order = order();
+        let now = order.created_at + chrono::Duration::seconds(5);
+        let patched = patch(json!({
+            "customer_name": "Anna",
+            "shipping_address": {"city": "Bonn", "postal_code": "53111"},
+        }))
+        .apply_at(&order, now)
+        .unwrap()
+        .unwrap();
+        assert_eq!(patched.customer_name, "Anna");
+        assert_eq!(patched.email, order.email);
+        let shipping = patched.shipping_address.as_ref().unwrap();
+        assert_eq!(
+            (shipping.line1.as_str(), shipping.city.as_str()),
+            ("1 Main St", "Bonn")
+        );
+        assert_eq!(patched.updated_at, now);
+
+        let cleared = patch(json!({"shipping_address": null}))
+            .apply_at(&patched, now)
+            .unwrap()
+            .unwrap();
+        assert_eq!(cleared.shipping_address, None);
+
+        let same = OrderPatch::new()
+            .with_customer_name("Anna")
+            .with_billing_address(None);
+        assert!(matches!(same.apply_at(&patched, now), Ok(None)));
+    }
+
+    #[test]
+    fn metadata_merges_key_by_key() {
+        let order = order().with_metadata([("erp_id".into(), "E-1".into())].into());
+        let patched = OrderPatch::new()
+            .with_metadata_entry("campaign", Some("spring".into()))
+            .apply_at(&order, order.created_at)
+            .unwrap()
+            .unwrap();
+        assert_eq!(patched.metadata.len(), 2);
+
+        let dropped = OrderPatch::new()
+            .with_metadata_entry("erp_id", None)
+            .apply_at(&patched, order.created_at)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            dropped.metadata,
+            BTreeMap::from([("campaign".to_string(), "spring".to_string())])
+        );
+
+        let errors = patch(json!({"metadata": {"erp_id": 7, "": "x"}}))
+            .apply_at(&order, order.created_at)
+            .unwrap_err();
+        assert_eq!(errors[0].field, "metadata");
+    }
+
+    #[test]
+    fn invalid_patches_report_every_field() {
+        let order = order();
+        let errors = patch(json!({
+            "customer_name": null,
+            "email": "nope",
+            "status": "Shipped",
+            "billing_address": {"line1": "x"},
+        }))
+        .apply_at(&order, order.created_at)
+        .unwrap_err();
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, ["status", "customer_name", "billing_address"]);
+
+        let errors = patch(json!({"email": "nope"}))
+            .apply_at(&order, order.created_at)
+            .unwrap_err();
+        assert_eq!(errors[0].field, "email");
+    }
+}
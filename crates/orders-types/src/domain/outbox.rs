@@ -0,0 +1,17 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::events::OrderEvent;
+
+/// An [`OrderEvent`] stored by the transaction that made the change, so it
+/// is relayed if and only if the change was committed. Serializes flat, e.g.
+/// `{"seq":7,"recorded_at":"...","type":"updated","order":{...}}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxRecord {
+    /// Position in the outbox; increases with every committed change, so
+    /// consumers can checkpoint it and drop redeliveries they have seen.
+    pub seq: u64,
+    pub recorded_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub event: OrderEvent,
+}
@@ -0,0 +1,313 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::order::{OrderItem, OrderValidationError};
+use crate::ports::pricing::PricingRules;
+
+/// Price of a single line as quoted by the catalog rules at snapshot time.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PricedLine {
+    pub name: String,
+    pub qty: u32,
+    pub unit_price_cents: i64,
+    pub discount_cents: i64,
+    /// Tax rate in basis points (1/100th of a percent).
+    pub tax_rate_bps: u32,
+    pub tax_cents: i64,
+    pub line_total_cents: i64,
+}
+
+/// How an order's total breaks down:
+/// `total = subtotal - discount + tax + shipping`, all in the order currency's
+/// minor unit. Orders stored before the breakdown existed read as all zero.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Charges {
+    #[serde(default)]
+    pub subtotal_cents: i64,
+    #[serde(default)]
+    pub discount_cents: i64,
+    #[serde(default)]
+    pub tax_cents: i64,
+    #[serde(default)]
+    pub shipping_cents: i64,
+}
+
+/// Frozen pricing of an order. Fields are only readable; a snapshot can only be
+/// produced by [`PricingSnapshot::compute`] or [`PricingSnapshot::compute_at`],
+/// so it never drifts once attached.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PricingSnapshot {
+    lines: Vec<PricedLine>,
+    subtotal_cents: i64,
+    discount_cents: i64,
+    tax_cents: i64,
+    /// Absent in snapshots frozen before shipping was charged.
+    #[serde(default)]
+    shipping_cents: i64,
+    total_cents: i64,
+    priced_at: DateTime<Utc>,
+}
+
+impl PricingSnapshot {
+    /// Fails with [`OrderValidationError::TotalOverflow`] when any amount
+    /// is too large for an `i64` of minor units, rather than wrapping.
+    pub fn compute(
+        items: &[OrderItem],
+        rules: &dyn PricingRules,
+    ) -> Result<Self, OrderValidationError> {
+        Self::compute_at(items, rules, Utc::now())
+    }
+
+    /// [`PricingSnapshot::compute`], priced at `now`.
+    pub fn compute_at(
+        items: &[OrderItem],
+        rules: &dyn PricingRules,
+        now: DateTime<Utc>,
+    ) -> Result<Self, OrderValidationError> {
+        let overflow = || OrderValidationError::TotalOverflow;
+        let mut lines = Vec::with_capacity(items.len());
+        let (mut subtotal, mut discount, mut tax) = (0i64, 0i64, 0i64);
+        for item in items {
+            let quote = rules.quote(item);
+            let gross = quote
+                .unit_price_cents
+                .checked_mul(i64::from(item.qty))
+                .ok_or_else(overflow)?;
+            let net = gross
+                .checked_sub(quote.discount_cents)
+                .ok_or_else(overflow)?;
+            // The tax itself may fit where `net * bps` would not.
+            let line_tax = i64::try_from(i128::from(net) * i128::from(quote.tax_rate_bps) / 10_000)
+                .map_err(|_| overflow())?;
+            let line_total = net.checked_add(line_tax).ok_or_else(overflow)?;
+            subtotal = subtotal.checked_add(gross).ok_or_else(overflow)?;
+            discount = discount
+                .checked_add(quote.discount_cents)
+                .ok_or_else(overflow)?;
+            tax = tax.checked_add(line_tax).ok_or_else(overflow)?;
+            lines.push(PricedLine {
+                name: item.name.clone(),
+                qty: item.qty,
+                unit_price_cents: quote.unit_price_cents,
+                discount_cents: quote.discount_cents,
+                tax_rate_bps: quote.tax_rate_bps,
+                tax_cents: line_tax,
+                line_total_cents: line_total,
+            });
+        }
+        let shipping = rules.shipping_cents(items);
+        let total = subtotal
+            .checked_sub(discount)
+            .and_then(|t| t.checked_add(tax))
+            .and_then(|t| t.checked_add(shipping))
+            .ok_or_else(overflow)?;
+        Ok(Self {
+            lines,
+            subtotal_cents: subtotal,
+            discount_cents: discount,
+            tax_cents: tax,
+            shipping_cents: shipping,
+            total_cents: total,
+            priced_at: now,
+        })
+    }
+
+    pub fn lines(&self) -> &[PricedLine] {
+        &self.lines
+    }
+
+    pub fn subtotal_cents(&self) -> i64 {
+        self.subtotal_cents
+    }
+
+    pub fn discount_cents(&self) -> i64 {
+        self.discount_cents
+    }
+
+    pub fn tax_cents(&self) -> i64 {
+        self.tax_cents
+    }
+
+    pub fn shipping_cents(&self) -> i64 {
+        self.shipping_cents
+    }
+
+    pub fn total_cents(&self) -> i64 {
+        self.total_cents
+    }
+
+    pub fn charges(&self) -> Charges {
+        Charges {
+            subtotal_cents: self.subtotal_cents,
+            discount_cents: self.discount_cents,
+            tax_cents: self.tax_cents,
+            shipping_cents: self.shipping_cents,
+        }
+    }
+
+    pub fn priced_at(&self) -> DateTime<Utc> {
+        self.priced_at
+    }
+
+    /// Line-by-line comparison against a newer snapshot. Fails with
+    /// [`OrderValidationError::TotalOverflow`] when the change in total
+    /// doesn't fit an `i64`.
+    pub fn diff(&self, after: &PricingSnapshot) -> Result<PricingDiff, OrderValidationError> {
+        let delta = after
+            .total_cents
+            .checked_sub(self.total_cents)
+            .ok_or(OrderValidationError::TotalOverflow)?;
+        let lines = self
+            .lines
+            .iter()
+            .zip(after.lines.iter())
+            .filter(|(b, a)| b != a)
+            .map(|(b, a)| LineDiff {
+                name: b.name.clone(),
+                before_total_cents: b.line_total_cents,
+                after_total_cents: a.line_total_cents,
+            })
+            .collect();
+        Ok(PricingDiff {
+            before_total_cents: self.total_cents,
+            after_total_cents: after.total_cents,
+            delta_cents: delta,
+            lines,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LineDiff {
+    pub name: String,
+    pub before_total_cents: i64,
+    pub after_total_cents: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PricingDiff {
+    pub before_total_cents: i64,
+    pub after_total_cents: i64,
+    pub delta_cents: i64,
+    pub lines: Vec<LineDiff>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::money::Money;
+    use crate::ports::pricing::{ItemPriceRules, LineQuote};
+
+    struct TenPercentOffTaxed;
+
+    impl PricingRules for TenPercentOffTaxed {
+        fn quote(&self, item: &OrderItem) -> LineQuote {
+            let gross = item.unit_price.amount_minor() * item.qty as i64;
+            LineQuote {
+                unit_price_cents: item.unit_price.amount_minor(),
+                discount_cents: gross / 10,
+                tax_rate_bps: 2_000,
+            }
+        }
+    }
+
+    fn items() -> Vec<OrderItem> {
+        vec![OrderItem {
+            name: "A".into(),
+            qty: 2,
+            unit_price: Money::usd(500),
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
+        }]
+    }
+
+    #[test]
+    fn compute_applies_discount_then_tax() {
+        let snap = PricingSnapshot::compute(&items(), &TenPercentOffTaxed).unwrap();
+        assert_eq!(snap.subtotal_cents(), 1000);
+        assert_eq!(snap.discount_cents(), 100);
+        assert_eq!(snap.tax_cents(), 180);
+        assert_eq!(snap.total_cents(), 1080);
+    }
+
+    #[test]
+    fn shipping_is_added_after_tax() {
+        use crate::ports::pricing::{PricingPolicy, ShippingRule};
+
+        let policy = PricingPolicy {
+            tax_rate_bps: 1_000,
+            shipping: ShippingRule::Flat { cents: 250 },
+        };
+        let snap = PricingSnapshot::compute(&items(), &policy).unwrap();
+        assert_eq!(snap.tax_cents(), 100);
+        assert_eq!(snap.shipping_cents(), 250);
+        assert_eq!(snap.total_cents(), 1350);
+        assert_eq!(
+            snap.charges(),
+            Charges {
+                subtotal_cents: 1000,
+                discount_cents: 0,
+                tax_cents: 100,
+                shipping_cents: 250,
+            }
+        );
+    }
+
+    #[test]
+    fn amounts_too_large_for_i64_are_an_error_not_a_wrap() {
+        use crate::ports::pricing::{PricingPolicy, ShippingRule};
+
+        let line = |qty, cents| OrderItem {
+            qty,
+            unit_price: Money::usd(cents),
+            ..items().remove(0)
+        };
+        let at_max = [line(1, i64::MAX)];
+        let snap = PricingSnapshot::compute(&at_max, &ItemPriceRules).unwrap();
+        assert_eq!(snap.total_cents(), i64::MAX);
+
+        let overflow = Err(OrderValidationError::TotalOverflow);
+        assert_eq!(
+            PricingSnapshot::compute(&[line(2, i64::MAX / 2 + 1)], &ItemPriceRules),
+            overflow
+        );
+        assert_eq!(
+            PricingSnapshot::compute(&[line(1, i64::MAX), line(1, 1)], &ItemPriceRules),
+            overflow
+        );
+        let shipped = PricingPolicy {
+            tax_rate_bps: 0,
+            shipping: ShippingRule::Flat { cents: 1 },
+        };
+        assert_eq!(PricingSnapshot::compute(&at_max, &shipped), overflow);
+
+        let at_min = PricingSnapshot::compute(&[line(1, i64::MIN)], &ItemPriceRules).unwrap();
+        assert_eq!(snap.diff(&at_min), Err(OrderValidationError::TotalOverflow));
+        // 10% tax on a net that fits, but whose tax doesn't.
+        let taxed = PricingPolicy {
+            tax_rate_bps: 1_000,
+            shipping: ShippingRule::Free,
+        };
+        let big = [line(1, i64::MAX / 10 * 9)];
+        assert!(PricingSnapshot::compute(&big, &taxed).is_ok());
+        assert_eq!(PricingSnapshot::compute(&at_max, &taxed), overflow);
+    }
+
+    #[test]
+    fn diff_reports_changed_lines_only() {
+        let before = PricingSnapshot::compute(&items(), &ItemPriceRules).unwrap();
+        let after = PricingSnapshot::compute(&items(), &TenPercentOffTaxed).unwrap();
+        let diff = before.diff(&after).unwrap();
+        assert_eq!(diff.delta_cents, 80);
+        assert_eq!(diff.lines.len(), 1);
+
+        let same = before
+            .diff(&PricingSnapshot::compute(&items(), &ItemPriceRules).unwrap())
+            .unwrap();
+        assert_eq!(same.delta_cents, 0);
+        assert!(same.lines.is_empty());
+    }
+}
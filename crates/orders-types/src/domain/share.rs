@@ -0,0 +1,202 @@
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::domain::tenant::TenantId;
+
+/// Query parameters of a signed, expiring read-only order link:
+/// `/orders/{id}?exp=...&sig=...[&tenant=...]`. Shared by the server, which
+/// verifies them, and `orders-client`, which can mint them offline.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShareToken {
+    /// Expiry as unix seconds.
+    pub exp: i64,
+    /// Hex HMAC-SHA256 over tenant, order id and `exp`.
+    pub sig: String,
+    /// Owning tenant; omitted for the default one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<TenantId>,
+}
+
+impl ShareToken {
+    pub fn tenant(&self) -> TenantId {
+        self.tenant.clone().unwrap_or_default()
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum ShareError {
+    #[error("share link ttl {requested}s exceeds the maximum of {max}s")]
+    TtlTooLong { requested: i64, max: i64 },
+    #[error("share link ttl must be positive")]
+    TtlNotPositive,
+    #[error("share link expired")]
+    Expired,
+    #[error("invalid share link signature")]
+    BadSignature,
+}
+
+/// Mints and verifies [`ShareToken`]s.
+///
+/// Verification allows `clock_skew` either way between the signer's and the
+/// verifier's clocks: a link is accepted until `exp + clock_skew`, and one
+/// claiming to live longer than `max_ttl + clock_skew` from now is rejected.
+#[derive(Clone)]
+pub struct ShareSigner {
+    secret: Vec<u8>,
+    max_ttl: Duration,
+    clock_skew: Duration,
+}
+
+impl ShareSigner {
+    pub const DEFAULT_MAX_TTL_SECS: i64 = 7 * 24 * 60 * 60;
+    pub const DEFAULT_CLOCK_SKEW_SECS: i64 = 60;
+
+    pub fn new(secret: impl AsRef<[u8]>) -> Self {
+        Self {
+            secret: secret.as_ref().to_vec(),
+            max_ttl: Duration::seconds(Self::DEFAULT_MAX_TTL_SECS),
+            clock_skew: Duration::seconds(Self::DEFAULT_CLOCK_SKEW_SECS),
+        }
+    }
+
+    pub fn with_max_ttl(mut self, max_ttl: Duration) -> Self {
+        self.max_ttl = max_ttl;
+        self
+    }
+
+    pub fn with_clock_skew(mut self, clock_skew: Duration) -> Self {
+        self.clock_skew = clock_skew;
+        self
+    }
+
+    pub fn max_ttl(&self) -> Duration {
+        self.max_ttl
+    }
+
+    fn mac(&self, tenant: &TenantId, id: Uuid, exp: i64) -> Hmac<Sha256> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.secret).expect("hmac accepts any key length");
+        mac.update(format!("{tenant}:{id}:{exp}").as_bytes());
+        mac
+    }
+
+    /// Token granting read access to order `id` of `tenant` for `ttl` from
+    /// `now`.
+    pub fn sign(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        ttl: Duration,
+        now: DateTime<Utc>,
+    ) -> Result<ShareToken, ShareError> {
+        if ttl <= Duration::zero() {
+            return Err(ShareError::TtlNotPositive);
+        }
+        if ttl > self.max_ttl {
+            return Err(ShareError::TtlTooLong {
+                requested: ttl.num_seconds(),
+                max: self.max_ttl.num_seconds(),
+            });
+        }
+        let exp = (now + ttl).timestamp();
+        let sig = hex::encode(self.mac(tenant, id, exp).finalize().into_bytes());
+        Ok(ShareToken {
+            exp,
+            sig,
+            tenant: (tenant.as_str() != TenantId::DEFAULT).then(|| tenant.clone()),
+        })
+    }
+
+    /// Check `token` grants access to order `id` at `now`.
+    pub fn verify(
+        &self,
+        id: Uuid,
+        token: &ShareToken,
+        now: DateTime<Utc>,
+    ) -> Result<(), ShareError> {
+        let sig = hex::decode(&token.sig).map_err(|_| ShareError::BadSignature)?;
+        self.mac(&token.tenant(), id, token.exp)
+            .verify_slice(&sig)
+            .map_err(|_| ShareError::BadSignature)?;
+        let now = now.timestamp();
+        if now > token.exp + self.clock_skew.num_seconds() {
+            return Err(ShareError::Expired);
+        }
+        if token.exp > now + (self.max_ttl + self.clock_skew).num_seconds() {
+            // Signed with a longer TTL than this server allows.
+            return Err(ShareError::TtlTooLong {
+                requested: token.exp - now,
+                max: self.max_ttl.num_seconds(),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verifies_within_skew_and_rejects_tampering() {
+        let signer = ShareSigner::new("secret").with_clock_skew(Duration::seconds(30));
+        let acme = TenantId::parse("acme").unwrap();
+        let id = Uuid::new_v4();
+        let now = Utc::now();
+        let token = signer.sign(&acme, id, Duration::minutes(5), now).unwrap();
+        assert_eq!(token.tenant(), acme);
+
+        assert!(signer.verify(id, &token, now).is_ok());
+        // Verifier's clock runs slightly ahead of the expiry.
+        assert!(signer
+            .verify(id, &token, now + Duration::seconds(5 * 60 + 20))
+            .is_ok());
+        assert_eq!(
+            signer.verify(id, &token, now + Duration::seconds(5 * 60 + 31)),
+            Err(ShareError::Expired)
+        );
+        assert_eq!(
+            signer.verify(Uuid::new_v4(), &token, now),
+            Err(ShareError::BadSignature)
+        );
+        let mut other_tenant = token.clone();
+        other_tenant.tenant = None;
+        assert_eq!(
+            signer.verify(id, &other_tenant, now),
+            Err(ShareError::BadSignature)
+        );
+        let mut extended = token.clone();
+        extended.exp += 3600;
+        assert_eq!(
+            signer.verify(id, &extended, now),
+            Err(ShareError::BadSignature)
+        );
+    }
+
+    #[test]
+    fn enforces_max_ttl_on_both_sides() {
+        let id = Uuid::new_v4();
+        let now = Utc::now();
+        let lenient = ShareSigner::new("secret");
+        let strict = ShareSigner::new("secret").with_max_ttl(Duration::hours(1));
+        assert!(matches!(
+            strict.sign(&TenantId::default(), id, Duration::hours(2), now),
+            Err(ShareError::TtlTooLong { .. })
+        ));
+        assert_eq!(
+            strict.sign(&TenantId::default(), id, Duration::zero(), now),
+            Err(ShareError::TtlNotPositive)
+        );
+        let long = lenient
+            .sign(&TenantId::default(), id, Duration::hours(2), now)
+            .unwrap();
+        assert!(long.tenant.is_none());
+        assert!(matches!(
+            strict.verify(id, &long, now),
+            Err(ShareError::TtlTooLong { .. })
+        ));
+    }
+}
@@ -0,0 +1,252 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::filter::check_created_range;
+#[cfg(doc)]
+use crate::domain::filter::OrderFilter;
+use crate::domain::money::Currency;
+use crate::domain::order::{Order, OrderStatus};
+
+/// Creation days to aggregate over, both ends inclusive, in UTC, narrowed
+/// further by the same half-open `created_after`/`created_before` instants
+/// [`OrderFilter`] takes. An open end is unbounded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatsRange {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<NaiveDate>,
+    /// Created at or after this instant.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_after: Option<DateTime<Utc>>,
+    /// Created strictly before this instant.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_before: Option<DateTime<Utc>>,
+}
+
+impl StatsRange {
+    pub fn check(&self) -> Result<(), String> {
+        match (self.from, self.to) {
+            (Some(from), Some(to)) if from > to => Err(format!("`from` {from} is after `to` {to}")),
+            _ => check_created_range(self.created_after, self.created_before),
+        }
+    }
+
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        let day = at.date_naive();
+        self.from.is_none_or(|from| day >= from)
+            && self.to.is_none_or(|to| day <= to)
+            && self.created_after.is_none_or(|t| at >= t)
+            && self.created_before.is_none_or(|t| at < t)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatusCount {
+    pub status: OrderStatus,
+    pub orders: u64,
+}
+
+/// Revenue in one currency; amounts in different currencies are never added
+/// up.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CurrencyRevenue {
+    pub currency: Currency,
+    pub orders: u64,
+    pub revenue_cents: i64,
+    /// `revenue_cents / orders`, rounded toward zero.
+    pub average_order_cents: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DayCount {
+    pub date: NaiveDate,
+    pub orders: u64,
+}
+
+/// Aggregates over the orders created in [`OrderStats::range`]. Revenue
+/// leaves out cancelled orders; counts include them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderStats {
+    pub range: StatsRange,
+    pub total_orders: u64,
+    /// Statuses with at least one order, in lifecycle order.
+    pub by_status: Vec<StatusCount>,
+    /// One entry per currency, by currency code.
+    pub revenue: Vec<CurrencyRevenue>,
+    /// Days with at least one order, oldest first.
+    pub per_day: Vec<DayCount>,
+}
+
+impl OrderStats {
+    /// Build the stats from raw parts. `by_status`, `revenue` (as currency,
+    /// order count and total) and `per_day` may come in any order.
+    pub fn assemble(
+        range: StatsRange,
+        by_status: impl IntoIterator<Item = (OrderStatus, u64)>,
+        revenue: impl IntoIterator<Item = (Currency, u64, i64)>,
+        per_day: impl IntoIterator<Item = (NaiveDate, u64)>,
+    ) -> Self {
+        let mut by_status: Vec<StatusCount> = by_status
+            .into_iter()
+            .map(|(status, orders)| StatusCount { status, orders })
+            .collect();
+        by_status.sort_by_key(|c| lifecycle_rank(&c.status));
+        let mut revenue: Vec<CurrencyRevenue> = revenue
+            .into_iter()
+            .map(|(currency, orders, revenue_cents)| CurrencyRevenue {
+                currency,
+                orders,
+                revenue_cents,
+                average_order_cents: match orders {
+                    0 => 0,
+                    n => revenue_cents / n as i64,
+                },
+            })
+            .collect();
+        revenue.sort_by(|a, b| a.currency.as_str().cmp(b.currency.as_str()));
+        let mut per_day: Vec<DayCount> = per_day
+            .into_iter()
+            .map(|(date, orders)| DayCount { date, orders })
+            .collect();
+        per_day.sort_by_key(|d| d.date);
+        Self {
+            range,
+            total_orders: by_status.iter().map(|c| c.orders).sum(),
+            by_status,
+            revenue,
+            per_day,
+        }
+    }
+
+    /// Aggregate in memory, for stores that can't do it themselves.
+    pub fn compute(orders: &[Order], range: StatsRange) -> Self {
+        let mut by_status: Vec<(OrderStatus, u64)> = Vec::new();
+        let mut revenue: Vec<(Currency, u64, i64)> = Vec::new();
+        let mut per_day: BTreeMap<NaiveDate, u64> = BTreeMap::new();
+        for order in orders.iter().filter(|o| range.contains(o.created_at)) {
+            match by_status.iter_mut().find(|(s, _)| s == &order.status) {
+                Some((_, n)) => *n += 1,
+                None => by_status.push((order.status.clone(), 1)),
+            }
+            if order.status != OrderStatus::Cancelled {
+                let currency = order.total.currency();
+                match revenue.iter_mut().find(|(c, _, _)| c == &currency) {
+                    Some((_, n, total)) => {
+                        *n += 1;
+                        *total += order.total.amount_minor();
+                    }
+                    None => revenue.push((currency, 1, order.total.amount_minor())),
+                }
+            }
+            *per_day.entry(order.created_at.date_naive()).or_default() += 1;
+        }
+        Self::assemble(range, by_status, revenue, per_day)
+    }
+}
+
+fn lifecycle_rank(status: &OrderStatus) -> u8 {
+    match status {
+        OrderStatus::Pending => 0,
+        OrderStatus::Confirmed => 1,
+        OrderStatus::Shipped => 2,
+        OrderStatus::Completed => 3,
+        OrderStatus::Cancelled => 4,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::money::Money;
+    use crate::domain::order::OrderItem;
+
+    fn order(cents: i64, currency: Currency, status: OrderStatus, day: u32) -> Order {
+        let mut o = Order::new(
+            "Name".into(),
+            "a@x.com".into(),
+            vec![OrderItem {
+                name: "A".into(),
+                qty: 1,
+                unit_price: Money::new(cents, currency),
+                weight_grams: 0,
+                sku: None,
+                description: None,
+                metadata: Default::default(),
+                discount_cents: 0,
+            }],
+        )
+        .unwrap();
+        o.status = status;
+        o.created_at = NaiveDate::from_ymd_opt(2024, 1, day)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_utc();
+        o
+    }
+
+    #[test]
+    fn counts_revenue_and_days_within_the_range() {
+        let orders = [
+            order(100, Currency::USD, OrderStatus::Pending, 1),
+            order(300, Currency::USD, OrderStatus::Shipped, 2),
+            order(500, Currency::USD, OrderStatus::Cancelled, 2),
+            order(1000, Currency::EUR, OrderStatus::Pending, 2),
+            order(999, Currency::USD, OrderStatus::Pending, 9),
+        ];
+        let range = StatsRange {
+            from: NaiveDate::from_ymd_opt(2024, 1, 1),
+            to: NaiveDate::from_ymd_opt(2024, 1, 2),
+            ..StatsRange::default()
+        };
+        let stats = OrderStats::compute(&orders, range);
+        assert_eq!(stats.total_orders, 4);
+        assert_eq!(
+            stats.by_status,
+            [
+                StatusCount {
+                    status: OrderStatus::Pending,
+                    orders: 2
+                },
+                StatusCount {
+                    status: OrderStatus::Shipped,
+                    orders: 1
+                },
+                StatusCount {
+                    status: OrderStatus::Cancelled,
+                    orders: 1
+                },
+            ]
+        );
+        assert_eq!(stats.revenue.len(), 2);
+        assert_eq!(stats.revenue[0].currency, Currency::EUR);
+        assert_eq!(
+            stats.revenue[1],
+            CurrencyRevenue {
+                currency: Currency::USD,
+                orders: 2,
+                revenue_cents: 400,
+                average_order_cents: 200,
+            }
+        );
+        let days: Vec<u64> = stats.per_day.iter().map(|d| d.orders).collect();
+        assert_eq!(days, [1, 3]);
+
+        let backwards = StatsRange {
+            from: range.to,
+            to: range.from,
+            ..StatsRange::default()
+        };
+        assert!(backwards.check().is_err());
+        let instant = orders[1].created_at;
+        let empty = StatsRange {
+            created_after: Some(instant),
+            created_before: Some(instant),
+            ..StatsRange::default()
+        };
+        assert!(empty.check().is_err());
+    }
+}
@@ -0,0 +1,57 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Isolation boundary for orders; every repository query is scoped to one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TenantId(String);
+
+impl TenantId {
+    /// Tenant for callers that don't name one, and for rows that predate
+    /// multi-tenancy.
+    pub const DEFAULT: &'static str = "default";
+
+    /// 1-64 ASCII letters, digits, `-` or `_`.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let valid = !s.is_empty()
+            && s.len() <= 64
+            && s.bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_');
+        if valid {
+            Ok(Self(s.to_string()))
+        } else {
+            Err(format!("invalid tenant id `{s}`"))
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Default for TenantId {
+    fn default() -> Self {
+        Self(Self::DEFAULT.to_string())
+    }
+}
+
+impl fmt::Display for TenantId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rejects_unsafe_ids() {
+        assert_eq!(TenantId::parse("acme-01").unwrap().as_str(), "acme-01");
+        assert!(TenantId::parse("").is_err());
+        assert!(TenantId::parse("a b").is_err());
+        assert!(TenantId::parse(&"x".repeat(65)).is_err());
+        assert_eq!(TenantId::default().as_str(), TenantId::DEFAULT);
+    }
+}
@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+
+/// An operator-configured receiver of order events.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookTarget {
+    pub id: String,
+    pub url: String,
+}
+
+impl WebhookTarget {
+    /// Parse `id=url` pairs separated by commas, e.g.
+    /// `"crm=https://crm.example/hooks,ops=https://ops.example/in"`.
+    pub fn parse_list(spec: &str) -> Result<Vec<Self>, String> {
+        let mut targets: Vec<Self> = Vec::new();
+        for pair in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (id, url) = pair
+                .split_once('=')
+                .ok_or_else(|| format!("expected id=url, got `{pair}`"))?;
+            let (id, url) = (id.trim(), url.trim());
+            if id.is_empty() {
+                return Err(format!("empty webhook id in `{pair}`"));
+            }
+            if !(url.starts_with("http://") || url.starts_with("https://")) {
+                return Err(format!("webhook `{id}` url must be http(s), got `{url}`"));
+            }
+            if targets.iter().any(|t| t.id == id) {
+                return Err(format!("duplicate webhook id `{id}`"));
+            }
+            targets.push(Self {
+                id: id.to_string(),
+                url: url.to_string(),
+            });
+        }
+        Ok(targets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_target_list() {
+        let t =
+            WebhookTarget::parse_list("crm=https://crm.test/h, ops = http://ops.test,").unwrap();
+        assert_eq!(t.len(), 2);
+        assert_eq!(t[1].id, "ops");
+        assert_eq!(t[1].url, "http://ops.test");
+        assert!(WebhookTarget::parse_list("crm=ftp://x").is_err());
+        assert!(WebhookTarget::parse_list("a=http://x,a=http://y").is_err());
+        assert!(WebhookTarget::parse_list("broken").is_err());
+    }
+}
@@ -0,0 +1,436 @@
+//! Realistic generated orders, for seeding development databases and for
+//! property tests. Only built with the `fixtures` feature.
+//!
+//! [`FakeOrders`] is deterministic: the same seed yields the same orders,
+//! ids included. Each order comes with the status history a real one would
+//! have, backdated to a creation instant spread over a recent window.
+
+use chrono::{DateTime, Duration, Utc};
+use uuid::{Builder, Uuid};
+
+use crate::domain::history::OrderHistoryEntry;
+use crate::domain::money::Money;
+use crate::domain::order::{Order, OrderItem, OrderStatus};
+use crate::domain::pricing::PricingSnapshot;
+use crate::domain::tenant::TenantId;
+use crate::ports::pricing::ItemPriceRules;
+
+const FIRST_NAMES: &[&str] = &[
+    "Alice", "Bruno", "Chen", "Dana", "Elif", "Farah", "Gustavo", "Hana", "Ivan", "Jonas", "Keiko",
+    "Liam", "Maya", "Nikhil", "Olga", "Priya", "Quentin", "Rosa", "Samir", "Tomasz", "Uma", "Vera",
+    "Wei", "Yusuf", "Zanele",
+];
+const LAST_NAMES: &[&str] = &[
+    "Anders", "Brooks", "Costa", "Diaz", "Eriksen", "Fischer", "Garcia", "Huang", "Ito", "Jensen",
+    "Kowalski", "Lopez", "Moreau", "Novak", "Okafor", "Petrov", "Quinn", "Rossi", "Silva",
+    "Tanaka", "Van Dijk", "Walsh", "Yilmaz",
+];
+const DOMAINS: &[&str] = &["example.com", "example.org", "example.net", "mail.example"];
+
+/// One catalog entry.
+struct Product {
+    sku: &'static str,
+    name: &'static str,
+    description: Option<&'static str>,
+    unit_price_cents: i64,
+    weight_grams: u32,
+}
+
+const CATALOG: &[Product] = &[
+    Product {
+        sku: "WID-001",
+        name: "Widget",
+        description: Some("Standard widget, brushed steel"),
+        unit_price_cents: 500,
+        weight_grams: 120,
+    },
+    Product {
+        sku: "GAD-010",
+        name: "Gadget",
+        description: None,
+        unit_price_cents: 1_250,
+        weight_grams: 340,
+    },
+    Product {
+        sku: "SPR-200",
+        name: "Sprocket",
+        description: Some("24-tooth, fits 3/32\" chain"),
+        unit_price_cents: 199,
+        weight_grams: 45,
+    },
+    Product {
+        sku: "GIZ-300",
+        name: "Gizmo",
+        description: None,
+        unit_price_cents: 2_999,
+        weight_grams: 800,
+    },
+    Product {
+        sku: "DOO-042",
+        name: "Doohickey",
+        description: Some("Assorted colours"),
+        unit_price_cents: 749,
+        weight_grams: 60,
+    },
+    Product {
+        sku: "THG-900",
+        name: "Thingamajig",
+        description: None,
+        unit_price_cents: 4_500,
+        weight_grams: 1_500,
+    },
+    Product {
+        sku: "CBL-USB-C",
+        name: "USB-C cable",
+        description: Some("1 m, braided"),
+        unit_price_cents: 899,
+        weight_grams: 40,
+    },
+    Product {
+        sku: "ADP-EU-US",
+        name: "Travel adapter",
+        description: None,
+        unit_price_cents: 1_599,
+        weight_grams: 90,
+    },
+    Product {
+        sku: "BAT-AA-8",
+        name: "AA batteries (8 pack)",
+        description: None,
+        unit_price_cents: 649,
+        weight_grams: 190,
+    },
+    Product {
+        sku: "LMP-DESK",
+        name: "Desk lamp",
+        description: Some("LED, warm white"),
+        unit_price_cents: 3_450,
+        weight_grams: 1_100,
+    },
+    Product {
+        sku: "MUG-350",
+        name: "Mug",
+        description: None,
+        unit_price_cents: 1_200,
+        weight_grams: 380,
+    },
+    Product {
+        sku: "NTB-A5",
+        name: "Notebook",
+        description: Some("A5, dotted"),
+        unit_price_cents: 950,
+        weight_grams: 250,
+    },
+];
+
+/// Where generated orders end up, weighted so most are open or done, and few
+/// cancelled.
+const STATUSES: &[(OrderStatus, u64)] = &[
+    (OrderStatus::Pending, 30),
+    (OrderStatus::Confirmed, 20),
+    (OrderStatus::Shipped, 15),
+    (OrderStatus::Completed, 25),
+    (OrderStatus::Cancelled, 10),
+];
+
+const CANCEL_REASONS: &[&str] = &[
+    "customer changed their mind",
+    "ordered by mistake",
+    "found a better price",
+    "delivery too slow",
+];
+
+/// Who generated changes are attributed to.
+const ACTOR: &str = "system";
+
+/// A generated order and its status history, oldest first.
+#[derive(Debug, Clone)]
+pub struct FakeOrder {
+    pub order: Order,
+    pub history: Vec<OrderHistoryEntry>,
+}
+
+/// Deterministic order generator; also an endless [`Iterator`].
+#[derive(Debug, Clone)]
+pub struct FakeOrders {
+    state: u64,
+    tenant: TenantId,
+    now: DateTime<Utc>,
+    spread: Duration,
+}
+
+impl FakeOrders {
+    /// Orders of the default tenant created during the 90 days before now.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            // xorshift gets stuck on zero.
+            state: seed.max(1),
+            tenant: TenantId::default(),
+            now: Utc::now(),
+            spread: Duration::days(90),
+        }
+    }
+
+    pub fn with_tenant(mut self, tenant: TenantId) -> Self {
+        self.tenant = tenant;
+        self
+    }
+
+    /// No generated instant is later than `now`; pin it for data that is
+    /// the same on every run.
+    pub fn with_now(mut self, now: DateTime<Utc>) -> Self {
+        self.now = now;
+        self
+    }
+
+    /// How far before `now` orders may have been created.
+    pub fn with_spread(mut self, spread: Duration) -> Self {
+        self.spread = spread.max(Duration::zero());
+        self
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        // xorshift64*
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n.max(1)
+    }
+
+    fn pick<'a, T>(&mut self, xs: &'a [T]) -> &'a T {
+        &xs[self.below(xs.len() as u64) as usize]
+    }
+
+    fn chance(&mut self, percent: u64) -> bool {
+        self.below(100) < percent
+    }
+
+    /// Between `min` and `max` minutes.
+    fn minutes(&mut self, min: i64, max: i64) -> Duration {
+        Duration::minutes(min + self.below((max - min + 1) as u64) as i64)
+    }
+
+    /// A random version 4 id.
+    pub fn id(&mut self) -> Uuid {
+        let mut bytes = [0u8; 16];
+        bytes[..8].copy_from_slice(&self.next_u64().to_le_bytes());
+        bytes[8..].copy_from_slice(&self.next_u64().to_le_bytes());
+        Builder::from_random_bytes(bytes).into_uuid()
+    }
+
+    /// A customer name and a plausible email address for it.
+    pub fn customer(&mut self) -> (String, String) {
+        let first = *self.pick(FIRST_NAMES);
+        let last = *self.pick(LAST_NAMES);
+        let domain = *self.pick(DOMAINS);
+        let (f, l) = (
+            first.to_ascii_lowercase(),
+            last.to_ascii_lowercase().replace(' ', ""),
+        );
+        let local = match self.below(4) {
+            0 => format!("{f}.{l}"),
+            1 => format!("{}{l}", &f[..1]),
+            2 => format!("{f}_{l}{}", 1 + self.below(99)),
+            _ => f,
+        };
+        (format!("{first} {last}"), format!("{local}@{domain}"))
+    }
+
+    /// A line for a catalog product, usually of a single unit.
+    pub fn item(&mut self) -> OrderItem {
+        let product = self.pick(CATALOG);
+        self.line(product)
+    }
+
+    fn line(&mut self, product: &Product) -> OrderItem {
+        let qty = match self.below(10) {
+            0..=5 => 1,
+            6..=8 => 2 + self.below(3) as u32,
+            _ => 5 + self.below(6) as u32,
+        };
+        OrderItem {
+            name: product.name.into(),
+            qty,
+            unit_price: Money::usd(product.unit_price_cents),
+            weight_grams: product.weight_grams,
+            sku: Some(product.sku.into()),
+            description: product.description.map(Into::into),
+            metadata: Default::default(),
+            discount_cents: 0,
+        }
+    }
+
+    /// One to four lines, each for a different product.
+    pub fn items(&mut self) -> Vec<OrderItem> {
+        let lines = 1 + self.below(4) as usize;
+        let mut picked: Vec<&Product> = Vec::with_capacity(lines);
+        while picked.len() < lines {
+            let product = self.pick(CATALOG);
+            if !picked.iter().any(|p| p.sku == product.sku) {
+                picked.push(product);
+            }
+        }
+        picked.into_iter().map(|p| self.line(p)).collect()
+    }
+
+    /// A status drawn with the weights of [`STATUSES`].
+    pub fn status(&mut self) -> OrderStatus {
+        let total: u64 = STATUSES.iter().map(|(_, w)| w).sum();
+        let mut roll = self.below(total);
+        for (status, weight) in STATUSES {
+            if roll < *weight {
+                return status.clone();
+            }
+            roll -= weight;
+        }
+        OrderStatus::Pending
+    }
+
+    /// The next order: created somewhere in the spread, then moved along the
+    /// lifecycle to a [`status`](Self::status) the way the service would,
+    /// with pricing frozen at confirmation.
+    pub fn next_order(&mut self) -> FakeOrder {
+        let (customer_name, email) = self.customer();
+        let items = self.items();
+        let target = self.status();
+        let mut order = Order::new(customer_name, email, items)
+            .expect("generated orders are valid")
+            .with_tenant(self.tenant.clone());
+        order.id = self.id();
+        let spread = self.spread.num_seconds().max(0) as u64;
+        let mut at = self.now - Duration::seconds(self.below(spread + 1) as i64);
+        order.created_at = at;
+        order.updated_at = at;
+        let mut history = vec![OrderHistoryEntry::new(
+            None,
+            OrderStatus::Pending,
+            at,
+            ACTOR,
+            None,
+        )];
+
+        let path: &[OrderStatus] = match target {
+            OrderStatus::Pending => &[],
+            OrderStatus::Confirmed => &[OrderStatus::Confirmed],
+            OrderStatus::Shipped => &[OrderStatus::Confirmed, OrderStatus::Shipped],
+            OrderStatus::Completed => &[
+                OrderStatus::Confirmed,
+                OrderStatus::Shipped,
+                OrderStatus::Completed,
+            ],
+            // Most cancellations happen before confirmation.
+            OrderStatus::Cancelled if self.chance(70) => &[OrderStatus::Cancelled],
+            OrderStatus::Cancelled => &[OrderStatus::Confirmed, OrderStatus::Cancelled],
+        };
+        for next in path {
+            let wait = match next {
+                OrderStatus::Confirmed => self.minutes(2, 6 * 60),
+                OrderStatus::Shipped => self.minutes(12 * 60, 3 * 24 * 60),
+                OrderStatus::Completed => self.minutes(2 * 24 * 60, 7 * 24 * 60),
+                _ => self.minutes(5, 2 * 24 * 60),
+            };
+            at = (at + wait).min(self.now);
+            let from = order.status.clone();
+            let mut note = None;
+            match next {
+                OrderStatus::Confirmed => {
+                    order.freeze_pricing(
+                        PricingSnapshot::compute(&order.items, &ItemPriceRules)
+                            .expect("generated orders are valid"),
+                    );
+                    order.update_status(OrderStatus::Confirmed);
+                }
+                OrderStatus::Cancelled => {
+                    let reason = *self.pick(CANCEL_REASONS);
+                    order.cancel(reason).expect("open orders can be cancelled");
+                    if let Some(c) = order.cancellation.as_mut() {
+                        c.cancelled_at = at;
+                    }
+                    note = Some(reason.to_string());
+                }
+                _ => order.update_status(next.clone()),
+            }
+            order.updated_at = at;
+            history.push(OrderHistoryEntry::new(
+                Some(from),
+                next.clone(),
+                at,
+                ACTOR,
+                note,
+            ));
+        }
+        FakeOrder { order, history }
+    }
+}
+
+impl Iterator for FakeOrders {
+    type Item = FakeOrder;
+
+    fn next(&mut self) -> Option<FakeOrder> {
+        Some(self.next_order())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<Utc> {
+        "2026-06-01T12:00:00Z".parse().unwrap()
+    }
+
+    #[test]
+    fn the_same_seed_gives_the_same_orders() {
+        let a: Vec<_> = FakeOrders::new(42).with_now(now()).take(50).collect();
+        let b: Vec<_> = FakeOrders::new(42).with_now(now()).take(50).collect();
+        for (a, b) in a.iter().zip(&b) {
+            assert_eq!(a.order.id, b.order.id);
+            assert_eq!(a.order.email, b.order.email);
+            assert_eq!(a.order.created_at, b.order.created_at);
+            assert_eq!(a.history, b.history);
+        }
+        let c = FakeOrders::new(43).with_now(now()).next_order();
+        assert_ne!(c.order.id, a[0].order.id);
+    }
+
+    #[test]
+    fn orders_are_valid_and_follow_the_lifecycle() {
+        let spread = Duration::days(30);
+        let orders = FakeOrders::new(7)
+            .with_now(now())
+            .with_spread(spread)
+            .with_tenant(TenantId::parse("acme").unwrap());
+        let mut seen = std::collections::HashSet::new();
+        for FakeOrder { order, history } in orders.take(500) {
+            assert!(Order::check(&order.customer_name, &order.email, &order.items).is_empty());
+            assert_eq!(order.tenant_id.as_str(), "acme");
+            assert!(order.created_at >= now() - spread && order.created_at <= now());
+            assert!(order.updated_at >= order.created_at && order.updated_at <= now());
+
+            assert_eq!(history[0].from, None);
+            assert_eq!(history[0].at, order.created_at);
+            for pair in history.windows(2) {
+                let from = pair[1].from.as_ref().unwrap();
+                assert_eq!(from, &pair[0].to);
+                assert!(from.can_transition_to(&pair[1].to));
+                assert!(pair[1].at >= pair[0].at);
+            }
+            let last = history.last().unwrap();
+            assert_eq!(last.to, order.status);
+            assert_eq!(last.at, order.updated_at);
+
+            let confirmed = history.iter().any(|h| h.to == OrderStatus::Confirmed);
+            assert_eq!(order.pricing.is_some(), confirmed);
+            assert_eq!(
+                order.cancellation.is_some(),
+                order.status == OrderStatus::Cancelled
+            );
+            seen.insert(format!("{:?}", order.status));
+        }
+        assert_eq!(seen.len(), STATUSES.len(), "{seen:?}");
+    }
+}
@@ -1,2 +1,6 @@
+#[cfg(feature = "proptest")]
+pub mod arbitrary;
 pub mod domain;
+#[cfg(feature = "fixtures")]
+pub mod fixtures;
 pub mod ports;
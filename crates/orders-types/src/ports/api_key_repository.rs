@@ -0,0 +1,15 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::domain::api_key::ApiKey;
+use crate::ports::order_repository::RepoError;
+
+#[async_trait]
+pub trait ApiKeyRepository: Send + Sync + 'static {
+    async fn create_key(&self, key: ApiKey) -> Result<ApiKey, RepoError>;
+    async fn find_key_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>, RepoError>;
+    async fn find_key(&self, id: Uuid) -> Result<Option<ApiKey>, RepoError>;
+    async fn list_keys(&self) -> Result<Vec<ApiKey>, RepoError>;
+    /// Mark a key revoked; `false` when no such key exists.
+    async fn revoke_key(&self, id: Uuid) -> Result<bool, RepoError>;
+}
@@ -0,0 +1,29 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::domain::audit::AuditEntry;
+use crate::domain::tenant::TenantId;
+use crate::ports::order_repository::RepoError;
+
+/// Append-only record of order mutations, kept for compliance review.
+#[async_trait]
+pub trait AuditRepository: Send + Sync + 'static {
+    async fn record_audit(&self, entry: AuditEntry) -> Result<(), RepoError>;
+    /// Every entry for one order, oldest first.
+    async fn order_audit(
+        &self,
+        tenant: &TenantId,
+        order_id: Uuid,
+    ) -> Result<Vec<AuditEntry>, RepoError>;
+    /// One page of the tenant's entries, newest first.
+    async fn list_audit(
+        &self,
+        tenant: &TenantId,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<AuditEntry>, RepoError>;
+    /// Apply [`Order::anonymize`](crate::domain::order::Order::anonymize) to
+    /// the order copies in one order's entries, for an erasure request; the
+    /// only rewrite of this log. Returns how many entries changed.
+    async fn anonymize_audit(&self, tenant: &TenantId, order_id: Uuid) -> Result<u64, RepoError>;
+}
@@ -0,0 +1,48 @@
+use async_trait::async_trait;
+
+use crate::domain::dead_letter::{DeadLetter, DeadLetterDepth, DeadLetterState};
+use crate::domain::outbox::OutboxRecord;
+use crate::ports::order_repository::RepoError;
+
+/// Outbox records relays gave up on. The relay parks and replays them; the
+/// admin API lists them and asks for replays.
+#[async_trait]
+pub trait DeadLetterStore: Send + Sync + 'static {
+    /// Park `record` after `consumer` failed it `attempts` times, the last
+    /// with `error`. Returns the new letter's id.
+    async fn dead_letter(
+        &self,
+        consumer: &str,
+        record: &OutboxRecord,
+        attempts: u32,
+        error: &str,
+    ) -> Result<u64, RepoError>;
+    async fn get_dead_letter(&self, id: u64) -> Result<Option<DeadLetter>, RepoError>;
+    /// Letters in `state` (any when `None`), newest first.
+    async fn list_dead_letters(
+        &self,
+        state: Option<DeadLetterState>,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<DeadLetter>, RepoError>;
+    /// Mark letter `id` for the relay to deliver again. Returns it as it is
+    /// now, or `None` when there is no such letter. Replayed letters are
+    /// left alone.
+    async fn request_replay(&self, id: u64) -> Result<Option<DeadLetter>, RepoError>;
+    /// `consumer`'s letters waiting for a replay, oldest first.
+    async fn replays_requested(
+        &self,
+        consumer: &str,
+        limit: usize,
+    ) -> Result<Vec<DeadLetter>, RepoError>;
+    /// Record a replay of letter `id`: delivered, or failed `attempts` more
+    /// times with `error`, which parks it again.
+    async fn finish_replay(
+        &self,
+        id: u64,
+        attempts: u32,
+        error: Option<&str>,
+    ) -> Result<(), RepoError>;
+    /// Letters per consumer and state, for queue depth metrics.
+    async fn dead_letter_depth(&self) -> Result<Vec<DeadLetterDepth>, RepoError>;
+}
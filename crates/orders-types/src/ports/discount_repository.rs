@@ -0,0 +1,24 @@
+use async_trait::async_trait;
+
+use crate::domain::discount::Discount;
+use crate::domain::tenant::TenantId;
+use crate::ports::order_repository::RepoError;
+
+#[async_trait]
+pub trait DiscountRepository: Send + Sync + 'static {
+    /// Store a new code; `false` when the tenant already has one by that name.
+    async fn create_discount(&self, discount: Discount) -> Result<bool, RepoError>;
+    async fn get_discount(
+        &self,
+        tenant: &TenantId,
+        code: &str,
+    ) -> Result<Option<Discount>, RepoError>;
+    async fn list_discounts(&self, tenant: &TenantId) -> Result<Vec<Discount>, RepoError>;
+    /// Count one use of a code, atomically, unless its `max_uses` is already
+    /// reached; `false` when nothing was counted.
+    async fn redeem_discount(&self, tenant: &TenantId, code: &str) -> Result<bool, RepoError>;
+    /// Give back a use counted by [`DiscountRepository::redeem_discount`]
+    /// when the order it was for is not stored after all.
+    async fn release_discount(&self, tenant: &TenantId, code: &str) -> Result<(), RepoError>;
+    async fn delete_discount(&self, tenant: &TenantId, code: &str) -> Result<bool, RepoError>;
+}
@@ -0,0 +1,12 @@
+use async_trait::async_trait;
+
+/// Checks on a customer's email address beyond its syntax, e.g. that its
+/// domain accepts mail. Consulted for new orders and email changes, with
+/// the address already normalized and syntactically valid.
+#[async_trait]
+pub trait EmailValidator: Send + Sync + 'static {
+    /// Why `email` should be refused, for the `422` on `email`; `Ok` to
+    /// accept. A check that can't be made right now (e.g. DNS timing out)
+    /// should accept rather than turn customers away.
+    async fn validate(&self, email: &str) -> Result<(), String>;
+}
@@ -0,0 +1,127 @@
+//! Keys for encrypting personal data (an order's `email` and
+//! `customer_name`) at rest. Adapters seal new values under the current key
+//! and open old ones under any key the provider still has, so a key is
+//! rotated by making a new one current, re-encrypting existing rows and only
+//! then dropping the old one. A KMS-backed provider would unwrap its data
+//! keys once at startup and hand them out the same way.
+
+use std::fmt;
+
+/// An AES-256 key.
+pub type DataKey = [u8; 32];
+
+pub trait KeyProvider: Send + Sync + fmt::Debug {
+    /// Id of the key new values are sealed under; stored next to them.
+    fn current_key_id(&self) -> &str;
+    /// Key `id`, or `None` once it has been retired.
+    fn key(&self, id: &str) -> Option<DataKey>;
+    /// Key of the blind index that lets sealed emails still be looked up.
+    /// Changing it means reindexing every row, so it doesn't rotate with
+    /// the data keys.
+    fn index_key(&self) -> DataKey;
+}
+
+/// Keys given in configuration, e.g. `PII_ENCRYPTION_KEYS` and
+/// `PII_INDEX_KEY`.
+#[derive(Clone)]
+pub struct StaticKeys {
+    /// The first is current.
+    keys: Vec<(String, DataKey)>,
+    index: DataKey,
+}
+
+impl StaticKeys {
+    /// `id=<64 hex digits>` pairs separated by commas, current key first,
+    /// e.g. `k2=…,k1=…`, and the index key as 64 hex digits. Ids are
+    /// letters, digits, `-` and `_`.
+    pub fn parse(keys: &str, index_key: &str) -> Result<Self, String> {
+        let keys = keys
+            .split(',')
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (id, hex) = pair
+                    .split_once('=')
+                    .ok_or_else(|| format!("expected id=key, got `{pair}`"))?;
+                let id = id.trim();
+                if id.is_empty()
+                    || !id
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+                {
+                    return Err(format!("invalid key id `{id}`"));
+                }
+                let key = parse_key(hex).map_err(|e| format!("key `{id}`: {e}"))?;
+                Ok((id.to_string(), key))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        if keys.is_empty() {
+            return Err("no keys given".into());
+        }
+        for (i, (id, _)) in keys.iter().enumerate() {
+            if keys[..i].iter().any(|(other, _)| other == id) {
+                return Err(format!("key id `{id}` given twice"));
+            }
+        }
+        let index = parse_key(index_key).map_err(|e| format!("index key: {e}"))?;
+        Ok(Self { keys, index })
+    }
+}
+
+fn parse_key(hex: &str) -> Result<DataKey, String> {
+    let bytes = hex::decode(hex.trim()).map_err(|e| e.to_string())?;
+    DataKey::try_from(bytes.as_slice())
+        .map_err(|_| format!("expected 32 bytes (64 hex digits), got {}", bytes.len()))
+}
+
+impl KeyProvider for StaticKeys {
+    fn current_key_id(&self) -> &str {
+        &self.keys[0].0
+    }
+
+    fn key(&self, id: &str) -> Option<DataKey> {
+        self.keys.iter().find(|(k, _)| k == id).map(|(_, key)| *key)
+    }
+
+    fn index_key(&self) -> DataKey {
+        self.index
+    }
+}
+
+/// Key ids only.
+impl fmt::Debug for StaticKeys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StaticKeys")
+            .field(
+                "keys",
+                &self.keys.iter().map(|(id, _)| id).collect::<Vec<_>>(),
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const A: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+    const B: &str = "0000000000000000000000000000000000000000000000000000000000000002";
+
+    #[test]
+    fn first_key_is_current_and_older_ones_still_open() {
+        let keys = StaticKeys::parse(&format!("k2={B}, k1={A}"), A).unwrap();
+        assert_eq!(keys.current_key_id(), "k2");
+        assert_eq!(keys.key("k1").unwrap()[31], 1);
+        assert!(keys.key("k0").is_none());
+        assert!(!format!("{keys:?}").contains(B));
+    }
+
+    #[test]
+    fn bad_keys_are_rejected() {
+        assert!(StaticKeys::parse("", A).is_err());
+        assert!(StaticKeys::parse("k1=abcd", A).is_err());
+        assert!(StaticKeys::parse(&format!("k:1={A}"), A).is_err());
+        assert!(StaticKeys::parse(&format!("k1={A},k1={B}"), A).is_err());
+        assert!(StaticKeys::parse(&format!("k1={A}"), "zz").is_err());
+    }
+}
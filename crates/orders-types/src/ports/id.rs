@@ -0,0 +1,77 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+/// Where new record ids come from, so tests can predict them and stores
+/// can pick a layout that suits their indexes.
+pub trait IdGenerator: Send + Sync + 'static {
+    fn new_id(&self) -> Uuid;
+}
+
+/// Random version 4 ids.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RandomIds;
+
+impl IdGenerator for RandomIds {
+    fn new_id(&self) -> Uuid {
+        Uuid::new_v4()
+    }
+}
+
+/// Version 7 ids, which start with a millisecond timestamp. Ids made later
+/// sort later, so inserts land at the end of a B-tree index instead of all
+/// over it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TimeOrderedIds;
+
+impl IdGenerator for TimeOrderedIds {
+    fn new_id(&self) -> Uuid {
+        Uuid::now_v7()
+    }
+}
+
+/// `00000000-0000-0000-0000-000000000001`, then `…02` and so on, for tests.
+/// Clones share the counter.
+#[derive(Debug, Clone, Default)]
+pub struct SequentialIds {
+    last: Arc<AtomicU64>,
+}
+
+impl SequentialIds {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl IdGenerator for SequentialIds {
+    fn new_id(&self) -> Uuid {
+        Uuid::from_u128(u128::from(self.last.fetch_add(1, Ordering::Relaxed) + 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn time_ordered_ids_sort_by_creation() {
+        let ids: Vec<_> = (0..3)
+            .map(|_| {
+                std::thread::sleep(std::time::Duration::from_millis(2));
+                TimeOrderedIds.new_id()
+            })
+            .collect();
+        assert!(ids.iter().all(|id| id.get_version_num() == 7));
+        assert!(ids.windows(2).all(|w| w[0] < w[1]), "{ids:?}");
+    }
+
+    #[test]
+    fn sequential_ids_count_up_across_clones() {
+        let ids = SequentialIds::new();
+        let copy = ids.clone();
+        assert_eq!(ids.new_id(), Uuid::from_u128(1));
+        assert_eq!(copy.new_id(), Uuid::from_u128(2));
+        assert_eq!(RandomIds.new_id().get_version_num(), 4);
+    }
+}
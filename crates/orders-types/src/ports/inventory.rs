@@ -0,0 +1,108 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::order::OrderItem;
+
+/// `qty` units of one stock keeping unit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StockLine {
+    pub sku: String,
+    pub qty: u32,
+}
+
+impl StockLine {
+    /// The stock an order's items need, one line per SKU. Items are keyed by
+    /// name until they carry a SKU of their own.
+    pub fn for_items(items: &[OrderItem]) -> Vec<StockLine> {
+        let mut lines: Vec<StockLine> = Vec::new();
+        for item in items {
+            match lines.iter_mut().find(|l| l.sku == item.name) {
+                Some(line) => line.qty = line.qty.saturating_add(item.qty),
+                None => lines.push(StockLine {
+                    sku: item.name.clone(),
+                    qty: item.qty,
+                }),
+            }
+        }
+        lines
+    }
+
+    /// Parse `sku=qty` pairs separated by commas, e.g. `Widget=10,Gadget=5`.
+    pub fn parse_list(s: &str) -> Result<Vec<StockLine>, String> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (sku, qty) = pair
+                    .split_once('=')
+                    .ok_or_else(|| format!("expected sku=qty, got `{pair}`"))?;
+                let qty = qty
+                    .trim()
+                    .parse()
+                    .map_err(|e| format!("quantity for `{}`: {e}", sku.trim()))?;
+                Ok(StockLine {
+                    sku: sku.trim().to_string(),
+                    qty,
+                })
+            })
+            .collect()
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum InventoryError {
+    #[error("insufficient stock for {sku}: {requested} requested, {available} available")]
+    InsufficientStock {
+        sku: String,
+        requested: u32,
+        available: u32,
+    },
+    #[error("inventory unavailable: {0}")]
+    Unavailable(String),
+}
+
+/// Stock levels shared by every order. Stock is reserved when an order is
+/// created, released when it is cancelled and committed (taken off the
+/// shelf for good) when it ships.
+#[async_trait]
+pub trait InventoryService: Send + Sync + 'static {
+    /// Hold `lines` for `order_id`, all or nothing. Reserving again for the
+    /// same order replaces its reservation, so only the difference has to be
+    /// in stock.
+    async fn reserve(&self, order_id: Uuid, lines: &[StockLine]) -> Result<(), InventoryError>;
+    /// Give back whatever `order_id` holds; nothing happens when it holds
+    /// nothing.
+    async fn release(&self, order_id: Uuid) -> Result<(), InventoryError>;
+    /// Deduct what `order_id` holds from the stock on hand; nothing happens
+    /// when it holds nothing.
+    async fn commit(&self, order_id: Uuid) -> Result<(), InventoryError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::money::Money;
+
+    #[test]
+    fn items_are_merged_per_sku_and_levels_parse() {
+        let item = |name: &str, qty| OrderItem {
+            name: name.into(),
+            qty,
+            unit_price: Money::usd(100),
+            weight_grams: 0,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
+        };
+        let lines = StockLine::for_items(&[item("A", 1), item("B", 2), item("A", 3)]);
+        assert_eq!(
+            lines,
+            StockLine::parse_list("A=4, B=2").unwrap(),
+            "merged in first-seen order"
+        );
+        assert!(StockLine::parse_list("A").is_err());
+        assert!(StockLine::parse_list("A=-1").is_err());
+    }
+}
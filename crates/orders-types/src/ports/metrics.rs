@@ -0,0 +1,14 @@
+/// Extra series for the `GET /metrics` scrape, so adapters can report on
+/// themselves without the HTTP layer knowing them.
+pub trait MetricsSource: Send + Sync + 'static {
+    /// Prometheus text exposition, `# HELP` and `# TYPE` lines included.
+    fn prometheus(&self) -> String;
+
+    /// OpenMetrics exposition, for scrapes that ask for it, without the
+    /// closing `# EOF`. Gauges read the same in both formats, so this
+    /// defaults to [`prometheus`](Self::prometheus); sources with counters
+    /// or exemplars override it.
+    fn openmetrics(&self) -> String {
+        self.prometheus()
+    }
+}
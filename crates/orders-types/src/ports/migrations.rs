@@ -0,0 +1,24 @@
+use async_trait::async_trait;
+use serde::Serialize;
+
+/// A schema migration this build ships that the store hasn't applied.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PendingMigration {
+    pub version: i64,
+    pub description: String,
+}
+
+/// Where the storage schema stands.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MigrationStatus {
+    /// Highest applied migration; `None` for stores without a schema.
+    pub current_version: Option<i64>,
+    pub pending: Vec<PendingMigration>,
+}
+
+/// Reports the schema state for the operational endpoints, so the HTTP
+/// layer needn't know the repository adapter.
+#[async_trait]
+pub trait MigrationSource: Send + Sync + 'static {
+    async fn migration_status(&self) -> anyhow::Result<MigrationStatus>;
+}
@@ -1 +1,21 @@
+pub mod api_key_repository;
+pub mod audit_repository;
+pub mod clock;
+pub mod dead_letter;
+pub mod discount_repository;
+pub mod email_validation;
+pub mod field_encryption;
+pub mod id;
+pub mod inventory;
+pub mod metrics;
+pub mod migrations;
+pub mod notifier;
+pub mod order_read_repository;
 pub mod order_repository;
+pub mod outbox;
+pub mod payment_gateway;
+pub mod pricing;
+pub mod refund;
+pub mod unit_of_work;
+pub mod validation;
+pub mod webhook;
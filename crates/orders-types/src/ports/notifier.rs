@@ -0,0 +1,55 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::order::Order;
+
+/// Lifecycle moments the customer is told about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationKind {
+    Created,
+    Shipped,
+    Cancelled,
+}
+
+impl NotificationKind {
+    pub const ALL: [NotificationKind; 3] = [
+        NotificationKind::Created,
+        NotificationKind::Shipped,
+        NotificationKind::Cancelled,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationKind::Created => "created",
+            NotificationKind::Shipped => "shipped",
+            NotificationKind::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// One message to an order's customer, with the order as it was then.
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub kind: NotificationKind,
+    pub order: Order,
+}
+
+/// Tells customers about their orders, e.g. by email.
+#[async_trait]
+pub trait Notifier: Send + Sync + 'static {
+    /// Deliver `notification` to `notification.order.email`. An `Err` is
+    /// retried by the caller.
+    async fn notify(&self, notification: &Notification) -> Result<(), String>;
+}
+
+/// Sends nothing; for tests and deployments without notifications.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopNotifier;
+
+#[async_trait]
+impl Notifier for NoopNotifier {
+    async fn notify(&self, _notification: &Notification) -> Result<(), String> {
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::domain::events::OrderEvent;
+use crate::domain::filter::OrderFilter;
+use crate::domain::order::Order;
+use crate::domain::tenant::TenantId;
+use crate::ports::order_repository::RepoError;
+
+/// The query side of orders: a denormalized copy of every order, kept by a
+/// projector from the order events instead of written by the service. It
+/// lags the [`OrderRepository`](crate::ports::order_repository::OrderRepository)
+/// by however far the projector is behind, so reads that decide a write
+/// must not use it.
+#[async_trait]
+pub trait OrderReadRepository: Send + Sync + 'static {
+    async fn get_view(&self, tenant: &TenantId, id: Uuid) -> Result<Option<Order>, RepoError>;
+    /// Orders matching `filter`, sorted and paged as it asks.
+    async fn list_views(
+        &self,
+        tenant: &TenantId,
+        filter: &OrderFilter,
+    ) -> Result<Vec<Order>, RepoError>;
+    /// Number of orders matching `filter`'s predicate; paging is ignored.
+    async fn count_views(
+        &self,
+        tenant: &TenantId,
+        filter: &OrderFilter,
+    ) -> Result<usize, RepoError>;
+}
+
+/// The write side of the read model, driven by the projector.
+#[async_trait]
+pub trait OrderProjection: Send + Sync + 'static {
+    /// Fold `event` into the read model. Events may arrive more than once;
+    /// one older than the stored view must leave it unchanged.
+    async fn project(&self, event: &OrderEvent) -> Result<(), RepoError>;
+}
@@ -1,23 +1,213 @@
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
+use crate::domain::filter::OrderFilter;
+use crate::domain::fulfillment::Fulfillment;
+use crate::domain::history::OrderHistoryEntry;
+use crate::domain::integrity::{IntegrityReport, StatusMapping};
 use crate::domain::order::{Order, OrderStatus};
+use crate::domain::order_number::OrderNumber;
+use crate::domain::stats::{OrderStats, StatsRange};
+use crate::domain::tenant::TenantId;
+use crate::ports::unit_of_work::{UnitOfWork, WriteThrough};
 
+/// What a failed [`RepoError`] came from, kept as its
+/// [`source`](std::error::Error::source).
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Why a repository call failed, by what the caller can do about it.
 #[derive(thiserror::Error, Debug)]
 pub enum RepoError {
+    /// The write collides with stored data: the row changed since it was
+    /// read, or a unique key is already taken. The caller should reload and
+    /// retry, or pick another key.
+    #[error("conflict: {0}")]
+    Conflict(String),
+    /// A row the call relies on does not exist.
+    #[error("not found: {0}")]
+    NotFound(String),
+    /// A failure that may clear up on its own, such as a locked database or
+    /// a dropped connection; the same call can be retried.
+    #[error("transient db error: {0}")]
+    Transient(#[source] BoxError),
+    /// A value could not be encoded for storage or decoded from it, such as
+    /// malformed JSON or an unknown status in a row.
+    #[error("serialization error: {0}")]
+    Serialization(#[source] BoxError),
+    /// Any other failure of the storage backend.
     #[error("db error: {0}")]
-    DbError(String),
+    Backend(#[source] BoxError),
+}
+
+impl RepoError {
+    pub fn transient<E: Into<BoxError>>(e: E) -> Self {
+        Self::Transient(e.into())
+    }
+
+    pub fn serialization<E: Into<BoxError>>(e: E) -> Self {
+        Self::Serialization(e.into())
+    }
+
+    pub fn backend<E: Into<BoxError>>(e: E) -> Self {
+        Self::Backend(e.into())
+    }
+
+    /// Whether retrying the call that failed may succeed.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::Transient(_))
+    }
 }
 
+/// Order storage. Every read and write is scoped to a tenant: an order that
+/// belongs to another tenant behaves exactly like a missing one.
 #[async_trait]
 pub trait OrderRepository: Send + Sync + 'static {
+    /// Start a [`UnitOfWork`] for writes that must land together, such as an
+    /// order and its status history. Adapters with transactions should
+    /// override this; the default writes straight through.
+    async fn begin(&self) -> Result<Box<dyn UnitOfWork + '_>, RepoError> {
+        Ok(Box::new(WriteThrough(self)))
+    }
+    /// Stores `order` under `order.tenant_id` and returns it with its
+    /// [`OrderNumber`]: the next one in sequence, unless the order already
+    /// has one (e.g. when restored from a backup), which is kept.
     async fn create(&self, order: Order) -> Result<Order, RepoError>;
-    async fn get(&self, id: Uuid) -> Result<Option<Order>, RepoError>;
-    async fn list(&self) -> Result<Vec<Order>, RepoError>;
+    /// Store a batch of orders. Adapters with transactions should override
+    /// this to insert all-or-nothing in one round trip.
+    async fn create_many(&self, orders: Vec<Order>) -> Result<(), RepoError> {
+        for order in orders {
+            self.create(order).await?;
+        }
+        Ok(())
+    }
+    async fn get(&self, tenant: &TenantId, id: Uuid) -> Result<Option<Order>, RepoError>;
+    /// The order numbered `number`. Adapters should override this to look
+    /// it up by index instead of listing the tenant's orders.
+    async fn get_by_number(
+        &self,
+        tenant: &TenantId,
+        number: &OrderNumber,
+    ) -> Result<Option<Order>, RepoError> {
+        Ok(self
+            .list(tenant)
+            .await?
+            .into_iter()
+            .find(|o| o.order_number.as_ref() == Some(number)))
+    }
+    async fn list(&self, tenant: &TenantId) -> Result<Vec<Order>, RepoError>;
+    /// Orders matching `filter`. Adapters may override to push filtering down.
+    async fn list_filtered(
+        &self,
+        tenant: &TenantId,
+        filter: &OrderFilter,
+    ) -> Result<Vec<Order>, RepoError> {
+        Ok(filter.apply(self.list(tenant).await?))
+    }
+    /// Whether `id` exists for `tenant`. Adapters should override this to
+    /// avoid loading the order.
+    async fn exists(&self, tenant: &TenantId, id: Uuid) -> Result<bool, RepoError> {
+        Ok(self.get(tenant, id).await?.is_some())
+    }
+    /// Number of orders matching `filter`'s predicate; paging is ignored.
+    async fn count(&self, tenant: &TenantId, filter: &OrderFilter) -> Result<usize, RepoError> {
+        Ok(self
+            .list(tenant)
+            .await?
+            .iter()
+            .filter(|o| filter.matches(o))
+            .count())
+    }
+    /// Counts and revenue over the tenant's orders created in `range`.
+    /// Adapters should override this to aggregate in the store instead of
+    /// loading every order.
+    async fn aggregate(
+        &self,
+        tenant: &TenantId,
+        range: &StatsRange,
+    ) -> Result<OrderStats, RepoError> {
+        Ok(OrderStats::compute(&self.list(tenant).await?, *range))
+    }
+    /// Pending orders of every tenant created before `before`, oldest
+    /// first and at most `limit`, for housekeeping that sweeps all tenants.
+    async fn stale_pending(
+        &self,
+        before: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<Order>, RepoError>;
+    /// Up to `limit` orders of every tenant with an id above `after` (from
+    /// the start when `None`), in id order, for walking the whole store a
+    /// page at a time, e.g. for a backup.
+    async fn scan(&self, after: Option<Uuid>, limit: usize) -> Result<Vec<Order>, RepoError>;
     async fn update_status(
         &self,
+        tenant: &TenantId,
         id: Uuid,
         status: OrderStatus,
     ) -> Result<Option<Order>, RepoError>;
-    async fn delete(&self, id: Uuid) -> Result<bool, RepoError>;
+    /// Persist every mutable field of an existing order of `order.tenant_id`.
+    async fn update(&self, order: Order) -> Result<Option<Order>, RepoError>;
+    /// Store the items and totals of `order`, but only while the stored order
+    /// is still pending and unchanged since `read_at` (its `updated_at` when
+    /// the caller loaded it). Returns `Ok(None)` when the order doesn't exist
+    /// and [`RepoError::Conflict`] when it was modified in between. Adapters
+    /// should override this to make the check and the write one step.
+    async fn update_items(
+        &self,
+        order: &Order,
+        read_at: DateTime<Utc>,
+    ) -> Result<Option<Order>, RepoError> {
+        match self.get(&order.tenant_id, order.id).await? {
+            None => Ok(None),
+            Some(stored) if stored.updated_at != read_at || !stored.items_editable() => Err(
+                RepoError::Conflict(format!("order {} was modified", order.id)),
+            ),
+            Some(_) => self.update(order.clone()).await,
+        }
+    }
+    /// Removes the order together with its status history and fulfillments.
+    async fn delete(&self, tenant: &TenantId, id: Uuid) -> Result<bool, RepoError>;
+    /// Append one status change to the history of order `id`.
+    async fn record_transition(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        entry: OrderHistoryEntry,
+    ) -> Result<(), RepoError>;
+    /// The status history of order `id`, oldest first; empty when it has
+    /// none.
+    async fn status_history(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+    ) -> Result<Vec<OrderHistoryEntry>, RepoError>;
+    /// Store one shipment of order `id`.
+    async fn record_fulfillment(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        fulfillment: Fulfillment,
+    ) -> Result<(), RepoError>;
+    /// The shipments of order `id`, oldest first; empty when it has none.
+    async fn fulfillments(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+    ) -> Result<Vec<Fulfillment>, RepoError>;
+    /// Scan stored rows of every tenant for values the domain cannot
+    /// represent. With `fix`, unknown statuses covered by `mapping` are
+    /// rewritten. Adapters that only ever hold typed orders have nothing to
+    /// check.
+    async fn check_integrity(
+        &self,
+        _mapping: &StatusMapping,
+        _fix: bool,
+    ) -> Result<IntegrityReport, RepoError> {
+        Ok(IntegrityReport::default())
+    }
+    /// Cheapest round trip proving the store answers (e.g. `SELECT 1`), for
+    /// readiness probes. Stores without a connection are always ready.
+    async fn ping(&self) -> Result<(), RepoError> {
+        Ok(())
+    }
 }
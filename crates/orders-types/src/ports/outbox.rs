@@ -0,0 +1,26 @@
+use async_trait::async_trait;
+
+use crate::domain::outbox::OutboxRecord;
+use crate::ports::order_repository::RepoError;
+
+/// The read side of the transactional outbox, for relays that forward its
+/// events elsewhere. Each consumer keeps its own checkpoint, so consumers
+/// progress independently.
+#[async_trait]
+pub trait OutboxStore: Send + Sync + 'static {
+    /// Up to `limit` records with a `seq` above `after`, oldest first.
+    async fn outbox_after(&self, after: u64, limit: usize) -> Result<Vec<OutboxRecord>, RepoError>;
+    /// The last `seq` `consumer` has handled; 0 before its first checkpoint.
+    async fn checkpoint(&self, consumer: &str) -> Result<u64, RepoError>;
+    async fn save_checkpoint(&self, consumer: &str, seq: u64) -> Result<(), RepoError>;
+    /// Delete the records every consumer has checkpointed past, returning
+    /// how many went. A consumer that has never saved a checkpoint doesn't
+    /// hold records back.
+    async fn prune_outbox(&self) -> Result<u64, RepoError>;
+    /// Put back records from a backup, keeping their `seq` and
+    /// `recorded_at`, all or nothing. A `seq` that is already taken is a
+    /// [`RepoError::Conflict`]. Stores that can't take records back refuse.
+    async fn restore_outbox(&self, _records: &[OutboxRecord]) -> Result<(), RepoError> {
+        Err(RepoError::backend("this outbox cannot restore records"))
+    }
+}
@@ -0,0 +1,30 @@
+use async_trait::async_trait;
+
+use crate::domain::money::Money;
+use crate::domain::order::Order;
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum PaymentError {
+    /// The provider refused the charge; retrying won't help.
+    #[error("payment declined: {0}")]
+    Declined(String),
+    /// The provider couldn't be reached or failed; retrying later may work.
+    #[error("payment provider unavailable: {0}")]
+    Unavailable(String),
+}
+
+/// Charges customers through a payment provider. An order's payment is
+/// authorized and captured when it is confirmed, and refunded when a
+/// confirmed order is cancelled. An authorization whose capture fails is
+/// voided, and a capture whose order can't be stored is refunded.
+#[async_trait]
+pub trait PaymentGateway: Send + Sync + 'static {
+    /// Reserve `amount` for `order`, returning the provider's payment id.
+    async fn authorize(&self, order: &Order, amount: Money) -> Result<String, PaymentError>;
+    /// Collect `amount` of an authorized payment.
+    async fn capture(&self, payment_id: &str, amount: Money) -> Result<(), PaymentError>;
+    /// Release an authorization that was never captured.
+    async fn void(&self, payment_id: &str) -> Result<(), PaymentError>;
+    /// Return `amount` of a captured payment to the customer.
+    async fn refund(&self, payment_id: &str, amount: Money) -> Result<(), PaymentError>;
+}
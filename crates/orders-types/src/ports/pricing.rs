@@ -0,0 +1,136 @@
+use crate::domain::order::OrderItem;
+
+/// Catalog price, discount and tax rate for one order line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineQuote {
+    pub unit_price_cents: i64,
+    pub discount_cents: i64,
+    pub tax_rate_bps: u32,
+}
+
+/// Current catalog pricing rules, consulted when an order is created, when
+/// its pricing is frozen at confirmation and again on an explicit re-price.
+pub trait PricingRules: Send + Sync + 'static {
+    fn quote(&self, item: &OrderItem) -> LineQuote;
+
+    /// Shipping charged on the whole order; free unless overridden.
+    fn shipping_cents(&self, _items: &[OrderItem]) -> i64 {
+        0
+    }
+}
+
+/// Default rules: trust the submitted unit price and line discount, no tax.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ItemPriceRules;
+
+impl PricingRules for ItemPriceRules {
+    fn quote(&self, item: &OrderItem) -> LineQuote {
+        LineQuote {
+            unit_price_cents: item.unit_price.amount_minor(),
+            discount_cents: item.discount_cents,
+            tax_rate_bps: 0,
+        }
+    }
+}
+
+/// How shipping is charged per order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ShippingRule {
+    #[default]
+    Free,
+    Flat {
+        cents: i64,
+    },
+    /// `base_cents` plus `per_kg_cents` for every started kilogram of the
+    /// items' total `weight_grams`.
+    ByWeight {
+        base_cents: i64,
+        per_kg_cents: i64,
+    },
+}
+
+impl ShippingRule {
+    /// Saturates at `i64::MAX`, which no order total can then fit.
+    pub fn cents_for(&self, items: &[OrderItem]) -> i64 {
+        match *self {
+            ShippingRule::Free => 0,
+            ShippingRule::Flat { cents } => cents,
+            ShippingRule::ByWeight {
+                base_cents,
+                per_kg_cents,
+            } => {
+                let grams = items.iter().fold(0u64, |sum, it| {
+                    sum.saturating_add(u64::from(it.weight_grams) * u64::from(it.qty))
+                });
+                let kgs = i64::try_from(grams.div_ceil(1000)).unwrap_or(i64::MAX);
+                base_cents.saturating_add(kgs.saturating_mul(per_kg_cents))
+            }
+        }
+    }
+}
+
+/// Submitted unit prices with one configured tax rate on every line and a
+/// shipping rule on the order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PricingPolicy {
+    /// Tax rate in basis points (1/100th of a percent).
+    pub tax_rate_bps: u32,
+    pub shipping: ShippingRule,
+}
+
+impl PricingRules for PricingPolicy {
+    fn quote(&self, item: &OrderItem) -> LineQuote {
+        LineQuote {
+            tax_rate_bps: self.tax_rate_bps,
+            ..ItemPriceRules.quote(item)
+        }
+    }
+
+    fn shipping_cents(&self, items: &[OrderItem]) -> i64 {
+        self.shipping.cents_for(items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::money::Money;
+
+    fn item(qty: u32, weight_grams: u32) -> OrderItem {
+        OrderItem {
+            name: "A".into(),
+            qty,
+            unit_price: Money::usd(1000),
+            weight_grams,
+            sku: None,
+            description: None,
+            metadata: Default::default(),
+            discount_cents: 0,
+        }
+    }
+
+    #[test]
+    fn shipping_rules_charge_per_order_or_per_started_kilogram() {
+        let items = [item(2, 600), item(1, 0)];
+        assert_eq!(ShippingRule::Free.cents_for(&items), 0);
+        assert_eq!(ShippingRule::Flat { cents: 499 }.cents_for(&items), 499);
+        let by_weight = ShippingRule::ByWeight {
+            base_cents: 300,
+            per_kg_cents: 150,
+        };
+        assert_eq!(by_weight.cents_for(&items), 300 + 2 * 150);
+        assert_eq!(by_weight.cents_for(&[item(1, 0)]), 300);
+    }
+
+    #[test]
+    fn policy_applies_its_tax_rate_to_every_line() {
+        let policy = PricingPolicy {
+            tax_rate_bps: 825,
+            shipping: ShippingRule::Flat { cents: 500 },
+        };
+        let quote = policy.quote(&item(3, 0));
+        assert_eq!(quote.unit_price_cents, 1000);
+        assert_eq!(quote.tax_rate_bps, 825);
+        assert_eq!(policy.shipping_cents(&[item(3, 0)]), 500);
+    }
+}
@@ -0,0 +1,12 @@
+use async_trait::async_trait;
+
+use crate::domain::order::Order;
+
+/// Gives the customer their money back when a paid order is cancelled, so a
+/// payment provider adapter can plug in.
+#[async_trait]
+pub trait RefundGateway: Send + Sync + 'static {
+    /// Refund `order` in full. Called before the cancellation is stored; an
+    /// `Err` leaves the order as it was.
+    async fn refund(&self, order: &Order, reason: &str) -> Result<(), String>;
+}
@@ -0,0 +1,144 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::domain::events::OrderEvent;
+use crate::domain::fulfillment::Fulfillment;
+use crate::domain::history::OrderHistoryEntry;
+use crate::domain::order::{Order, OrderStatus};
+use crate::domain::tenant::TenantId;
+use crate::ports::order_repository::{OrderRepository, RepoError};
+
+/// Order writes that take effect together, begun with
+/// [`OrderRepository::begin`]. Each write behaves like the repository method
+/// of the same name, including any outbox event the adapter records, but
+/// nothing is visible to other callers until [`commit`](Self::commit).
+/// Dropping a unit without committing rolls it back.
+#[async_trait]
+pub trait UnitOfWork: Send {
+    async fn create(&mut self, order: Order) -> Result<Order, RepoError>;
+    async fn update(&mut self, order: Order) -> Result<Option<Order>, RepoError>;
+    /// Like [`update`](Self::update), for a change computed from `read`: it
+    /// only lands while the stored order still has `read`'s status and
+    /// `updated_at`, and fails with [`RepoError::Conflict`] once another
+    /// write got there first. `kind` is the event an outbox records.
+    async fn update_from(
+        &mut self,
+        order: Order,
+        read: &Order,
+        kind: ChangeKind,
+    ) -> Result<Option<Order>, RepoError>;
+    async fn update_status(
+        &mut self,
+        tenant: &TenantId,
+        id: Uuid,
+        status: OrderStatus,
+    ) -> Result<Option<Order>, RepoError>;
+    async fn record_transition(
+        &mut self,
+        tenant: &TenantId,
+        id: Uuid,
+        entry: OrderHistoryEntry,
+    ) -> Result<(), RepoError>;
+    async fn record_fulfillment(
+        &mut self,
+        tenant: &TenantId,
+        id: Uuid,
+        fulfillment: Fulfillment,
+    ) -> Result<(), RepoError>;
+    async fn commit(self: Box<Self>) -> Result<(), RepoError>;
+    async fn rollback(self: Box<Self>) -> Result<(), RepoError>;
+}
+
+/// What a stored change was, for adapters that record an event with each
+/// write.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// Recorded as [`OrderEvent::Updated`].
+    Updated,
+    /// An admin set the status outside the transition rules; recorded as
+    /// [`OrderEvent::StatusOverridden`].
+    StatusOverridden { from: OrderStatus, reason: String },
+}
+
+impl ChangeKind {
+    /// The event recording this change to `order`.
+    pub fn event(self, order: Order) -> OrderEvent {
+        match self {
+            ChangeKind::Updated => OrderEvent::Updated { order },
+            ChangeKind::StatusOverridden { from, reason } => OrderEvent::StatusOverridden {
+                order,
+                from,
+                reason,
+            },
+        }
+    }
+}
+
+/// The unit of work of adapters without transactions: each write goes
+/// straight to the repository, so commit and rollback have nothing left to
+/// do. Good enough for stores whose writes can't fail halfway, such as
+/// memory.
+pub struct WriteThrough<'a, R: ?Sized>(pub &'a R);
+
+#[async_trait]
+impl<R: OrderRepository + ?Sized> UnitOfWork for WriteThrough<'_, R> {
+    async fn create(&mut self, order: Order) -> Result<Order, RepoError> {
+        self.0.create(order).await
+    }
+
+    async fn update(&mut self, order: Order) -> Result<Option<Order>, RepoError> {
+        self.0.update(order).await
+    }
+
+    /// Checks, then writes: another caller can still slip in between.
+    async fn update_from(
+        &mut self,
+        order: Order,
+        read: &Order,
+        _kind: ChangeKind,
+    ) -> Result<Option<Order>, RepoError> {
+        match self.0.get(&order.tenant_id, order.id).await? {
+            None => Ok(None),
+            Some(stored) if !stored.same_version(read) => Err(RepoError::Conflict(format!(
+                "order {} was modified",
+                order.id
+            ))),
+            Some(_) => self.0.update(order).await,
+        }
+    }
+
+    async fn update_status(
+        &mut self,
+        tenant: &TenantId,
+        id: Uuid,
+        status: OrderStatus,
+    ) -> Result<Option<Order>, RepoError> {
+        self.0.update_status(tenant, id, status).await
+    }
+
+    async fn record_transition(
+        &mut self,
+        tenant: &TenantId,
+        id: Uuid,
+        entry: OrderHistoryEntry,
+    ) -> Result<(), RepoError> {
+        self.0.record_transition(tenant, id, entry).await
+    }
+
+    async fn record_fulfillment(
+        &mut self,
+        tenant: &TenantId,
+        id: Uuid,
+        fulfillment: Fulfillment,
+    ) -> Result<(), RepoError> {
+        self.0.record_fulfillment(tenant, id, fulfillment).await
+    }
+
+    async fn commit(self: Box<Self>) -> Result<(), RepoError> {
+        Ok(())
+    }
+
+    async fn rollback(self: Box<Self>) -> Result<(), RepoError> {
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::order::Order;
+
+/// An external validator's answer for a candidate order; also its wire form,
+/// e.g. `{"accept":false,"reason":"embargoed country"}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Verdict {
+    pub accept: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Business-specific acceptance rules consulted before an order is stored.
+#[async_trait]
+pub trait OrderValidator: Send + Sync + 'static {
+    /// `Err` means no verdict could be obtained (unreachable, bad status,
+    /// unparsable answer, ...); the caller's [`FailurePolicy`] decides.
+    async fn validate(&self, order: &Order) -> Result<Verdict, String>;
+    /// Whether the validator is reachable right now, for readiness probes.
+    async fn ping(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// What to do with an order when the validator gives no verdict in time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FailurePolicy {
+    /// Accept the order as if the validator had.
+    FailOpen,
+    /// Refuse the order until the validator answers again.
+    #[default]
+    FailClosed,
+}
+
+impl FailurePolicy {
+    /// `fail-open` or `fail-closed`.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "fail-open" => Some(FailurePolicy::FailOpen),
+            "fail-closed" => Some(FailurePolicy::FailClosed),
+            _ => None,
+        }
+    }
+}
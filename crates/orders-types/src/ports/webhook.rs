@@ -0,0 +1,22 @@
+use async_trait::async_trait;
+
+/// What a receiver answered to a delivery.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebhookResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+/// Outbound transport for webhook deliveries.
+#[async_trait]
+pub trait WebhookTransport: Send + Sync + 'static {
+    /// POST `body`, a CloudEvent in structured JSON mode, to `url` with the
+    /// extra `headers`. `Err` means no
+    /// response was received (connect failure, timeout, ...).
+    async fn post(
+        &self,
+        url: &str,
+        headers: &[(&'static str, String)],
+        body: String,
+    ) -> Result<WebhookResponse, String>;
+}
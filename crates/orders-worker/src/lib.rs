@@ -0,0 +1,35 @@
+//! Forwards the transactional outbox that `orders-app` writes (with
+//! `OUTBOX_ENABLED=true`) to [sinks](sink::Sink), one [`Relay`] per sink.
+
+pub mod relay;
+pub mod sink;
+
+pub use relay::{Relay, RelayOptions};
+pub use sink::Sink;
+
+#[cfg(test)]
+pub(crate) mod testing {
+    use chrono::Utc;
+    use orders_types::domain::cloudevent::{OrderCloudEvent, DEFAULT_SOURCE};
+    use orders_types::domain::events::OrderEvent;
+    use orders_types::domain::outbox::OutboxRecord;
+    use orders_types::domain::tenant::TenantId;
+    use uuid::Uuid;
+
+    /// A deletion of `order_id` at outbox position `seq`.
+    pub(crate) fn record(seq: u64, order_id: Uuid) -> OutboxRecord {
+        OutboxRecord {
+            seq,
+            recorded_at: Utc::now(),
+            event: OrderEvent::Deleted {
+                id: order_id,
+                tenant_id: TenantId::default(),
+            },
+        }
+    }
+
+    /// [`record`] as the relay hands it to sinks.
+    pub(crate) fn event(seq: u64, order_id: Uuid) -> OrderCloudEvent {
+        OrderCloudEvent::from_outbox(&record(seq, order_id), DEFAULT_SOURCE)
+    }
+}
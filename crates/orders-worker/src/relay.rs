@@ -0,0 +1,602 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::stream::{self, StreamExt};
+use orders_types::domain::cloudevent::{OrderCloudEvent, DEFAULT_SOURCE};
+use orders_types::domain::outbox::OutboxRecord;
+use orders_types::ports::dead_letter::DeadLetterStore;
+use orders_types::ports::outbox::OutboxStore;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use crate::sink::Sink;
+
+/// How a [`Relay`] polls and retries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RelayOptions {
+    /// Records read per poll.
+    pub batch_size: usize,
+    /// Wait between polls once the outbox is drained, and after a failed
+    /// read.
+    pub poll_interval: Duration,
+    /// Orders delivered at once; the records of one order always go one
+    /// after another.
+    pub concurrency: usize,
+    /// Wait before the first retry of a failed delivery; doubled for each
+    /// one after, up to `max_backoff`.
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// After each checkpoint, delete the records every consumer has handled.
+    pub prune: bool,
+}
+
+impl Default for RelayOptions {
+    fn default() -> Self {
+        Self {
+            batch_size: 100,
+            poll_interval: Duration::from_millis(500),
+            concurrency: 8,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(30),
+            prune: false,
+        }
+    }
+}
+
+/// Forwards outbox records to one sink, checkpointing under the sink's
+/// name. A failed delivery is retried, holding back the later records of
+/// the same order but not those of other orders: until it succeeds, or,
+/// with [`Relay::with_dead_letters`], until it has used up its attempts.
+pub struct Relay<S> {
+    store: Arc<S>,
+    sink: Arc<dyn Sink>,
+    options: RelayOptions,
+    source: String,
+    dead_letters: Option<(Arc<dyn DeadLetterStore>, u32)>,
+}
+
+/// How [`Relay::send`] ended.
+enum Sent {
+    Delivered { attempts: u32 },
+    GaveUp { attempts: u32, error: String },
+    Interrupted,
+}
+
+impl<S: OutboxStore> Relay<S> {
+    pub fn new(store: Arc<S>, sink: impl Sink) -> Self {
+        Self {
+            store,
+            sink: Arc::new(sink),
+            options: RelayOptions::default(),
+            source: DEFAULT_SOURCE.to_string(),
+            dead_letters: None,
+        }
+    }
+
+    pub fn with_options(mut self, options: RelayOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// The CloudEvents `source` of the events relayed; [`DEFAULT_SOURCE`]
+    /// unless set.
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = source.into();
+        self
+    }
+
+    /// Give up on a record after `max_attempts` deliveries and park it in
+    /// `store` instead, so it stops holding back its order. Each poll also
+    /// delivers the letters of this sink an operator asked to replay.
+    pub fn with_dead_letters(mut self, store: Arc<dyn DeadLetterStore>, max_attempts: u32) -> Self {
+        self.dead_letters = Some((store, max_attempts.max(1)));
+        self
+    }
+
+    /// Relay until `shutdown` is cancelled. Deliveries already sent are
+    /// finished and checkpointed before it returns; it only fails if the
+    /// starting checkpoint can't be read.
+    pub async fn run(&self, shutdown: CancellationToken) -> anyhow::Result<()> {
+        let name = self.sink.name();
+        let mut after = self.store.checkpoint(name).await?;
+        tracing::info!(sink = name, checkpoint = after, "relay started");
+        while !shutdown.is_cancelled() {
+            let replayed = match self.replay_dead_letters(&shutdown).await {
+                Ok(replayed) => replayed,
+                Err(e) => {
+                    tracing::warn!(sink = name, error = %e, "dead letter poll failed");
+                    0
+                }
+            };
+            let idle = match self.relay_batch(after, &shutdown).await {
+                Ok(next) => {
+                    let idle = next == after && replayed == 0;
+                    after = next;
+                    idle
+                }
+                Err(e) => {
+                    tracing::warn!(sink = name, error = %e, "outbox poll failed");
+                    true
+                }
+            };
+            if idle {
+                tokio::select! {
+                    _ = tokio::time::sleep(self.options.poll_interval) => {}
+                    _ = shutdown.cancelled() => {}
+                }
+            }
+        }
+        tracing::info!(sink = name, checkpoint = after, "relay stopped");
+        Ok(())
+    }
+
+    /// Deliver the next batch after `after` and checkpoint it, returning the
+    /// new checkpoint. Only a run of records delivered from the start of the
+    /// batch is checkpointed; the rest are delivered again next time.
+    pub async fn relay_batch(
+        &self,
+        after: u64,
+        shutdown: &CancellationToken,
+    ) -> anyhow::Result<u64> {
+        let records = self
+            .store
+            .outbox_after(after, self.options.batch_size)
+            .await?;
+        if records.is_empty() {
+            return Ok(after);
+        }
+
+        let seqs: Vec<u64> = records.iter().map(|r| r.seq).collect();
+        let mut lanes: Vec<Vec<OutboxRecord>> = Vec::new();
+        let mut lane_of: HashMap<Uuid, usize> = HashMap::new();
+        for record in records {
+            let lane = *lane_of.entry(record.event.order_id()).or_insert_with(|| {
+                lanes.push(Vec::new());
+                lanes.len() - 1
+            });
+            lanes[lane].push(record);
+        }
+        let delivered: HashSet<u64> = stream::iter(lanes)
+            .map(|lane| self.deliver_lane(lane, shutdown))
+            .buffer_unordered(self.options.concurrency.max(1))
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .flatten()
+            .collect();
+
+        let next = seqs
+            .into_iter()
+            .take_while(|seq| delivered.contains(seq))
+            .last()
+            .unwrap_or(after);
+        if next > after {
+            self.store.save_checkpoint(self.sink.name(), next).await?;
+            if self.options.prune {
+                let pruned = self.store.prune_outbox().await?;
+                tracing::debug!(pruned, "pruned outbox");
+            }
+        }
+        Ok(next)
+    }
+
+    /// Deliver this sink's dead letters an operator asked to replay, parking
+    /// again those that still fail. Returns how many were tried. A replay
+    /// goes out after whatever its order has done since, so receivers
+    /// should order by the event's `sequence`.
+    pub async fn replay_dead_letters(&self, shutdown: &CancellationToken) -> anyhow::Result<usize> {
+        let Some((letters, _)) = &self.dead_letters else {
+            return Ok(0);
+        };
+        let due = letters
+            .replays_requested(self.sink.name(), self.options.batch_size)
+            .await?;
+        let mut tried = 0;
+        for letter in due {
+            let event = OrderCloudEvent::from_outbox(&letter.record, &self.source);
+            match self.send(&event, letter.record.seq, shutdown).await {
+                Sent::Delivered { attempts } => {
+                    letters.finish_replay(letter.id, attempts, None).await?;
+                    tracing::info!(
+                        sink = self.sink.name(),
+                        letter = letter.id,
+                        "dead letter replayed"
+                    );
+                }
+                Sent::GaveUp { attempts, error } => {
+                    letters
+                        .finish_replay(letter.id, attempts, Some(&error))
+                        .await?;
+                }
+                Sent::Interrupted => break,
+            }
+            tried += 1;
+        }
+        Ok(tried)
+    }
+
+    /// Deliver one order's records in order, stopping at the first that
+    /// doesn't go through before shutdown. Returns the `seq`s delivered.
+    async fn deliver_lane(
+        &self,
+        lane: Vec<OutboxRecord>,
+        shutdown: &CancellationToken,
+    ) -> Vec<u64> {
+        let mut delivered = Vec::with_capacity(lane.len());
+        for record in lane {
+            if !self.deliver(&record, shutdown).await {
+                break;
+            }
+            delivered.push(record.seq);
+        }
+        delivered
+    }
+
+    /// Send `record`, or park it once it has used up its attempts; `false`
+    /// when it is neither, because shutdown stopped the retries or the dead
+    /// letter couldn't be stored.
+    async fn deliver(&self, record: &OutboxRecord, shutdown: &CancellationToken) -> bool {
+        let event = OrderCloudEvent::from_outbox(record, &self.source);
+        match self.send(&event, record.seq, shutdown).await {
+            Sent::Delivered { .. } => true,
+            Sent::Interrupted => false,
+            Sent::GaveUp { attempts, error } => {
+                let Some((letters, _)) = &self.dead_letters else {
+                    return false;
+                };
+                match letters
+                    .dead_letter(self.sink.name(), record, attempts, &error)
+                    .await
+                {
+                    Ok(id) => {
+                        tracing::error!(
+                            sink = self.sink.name(),
+                            seq = record.seq,
+                            attempts,
+                            letter = id,
+                            error = %error,
+                            "delivery gave up; dead-lettered"
+                        );
+                        true
+                    }
+                    Err(e) => {
+                        tracing::error!(
+                            sink = self.sink.name(),
+                            seq = record.seq,
+                            error = %e,
+                            "cannot store dead letter"
+                        );
+                        false
+                    }
+                }
+            }
+        }
+    }
+
+    /// Send `event`, retrying with backoff until it goes through, shutdown
+    /// stops the retries, or the dead letter attempt limit is reached.
+    async fn send(&self, event: &OrderCloudEvent, seq: u64, shutdown: &CancellationToken) -> Sent {
+        let max_attempts = self.dead_letters.as_ref().map(|(_, max)| *max);
+        let mut backoff = self.options.initial_backoff;
+        let mut attempt = 0u32;
+        loop {
+            attempt += 1;
+            if shutdown.is_cancelled() {
+                return Sent::Interrupted;
+            }
+            match self.sink.send(event).await {
+                Ok(()) => return Sent::Delivered { attempts: attempt },
+                Err(e) if max_attempts.is_some_and(|max| attempt >= max) => {
+                    return Sent::GaveUp {
+                        attempts: attempt,
+                        error: e.to_string(),
+                    }
+                }
+                Err(e) => tracing::warn!(
+                    sink = self.sink.name(),
+                    seq,
+                    attempt,
+                    error = %e,
+                    "delivery failed; retrying"
+                ),
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(backoff) => {}
+                _ = shutdown.cancelled() => return Sent::Interrupted,
+            }
+            backoff = (backoff * 2).min(self.options.max_backoff);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::record;
+    use async_trait::async_trait;
+    use chrono::Utc;
+    use orders_types::domain::dead_letter::{DeadLetter, DeadLetterDepth, DeadLetterState};
+    use orders_types::ports::order_repository::RepoError;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct FakeStore {
+        records: Vec<OutboxRecord>,
+        checkpoints: Mutex<HashMap<String, u64>>,
+    }
+
+    #[async_trait]
+    impl OutboxStore for FakeStore {
+        async fn outbox_after(
+            &self,
+            after: u64,
+            limit: usize,
+        ) -> Result<Vec<OutboxRecord>, RepoError> {
+            Ok(self
+                .records
+                .iter()
+                .filter(|r| r.seq > after)
+                .take(limit)
+                .cloned()
+                .collect())
+        }
+
+        async fn checkpoint(&self, consumer: &str) -> Result<u64, RepoError> {
+            Ok(self
+                .checkpoints
+                .lock()
+                .unwrap()
+                .get(consumer)
+                .copied()
+                .unwrap_or(0))
+        }
+
+        async fn save_checkpoint(&self, consumer: &str, seq: u64) -> Result<(), RepoError> {
+            self.checkpoints
+                .lock()
+                .unwrap()
+                .insert(consumer.to_string(), seq);
+            Ok(())
+        }
+
+        async fn prune_outbox(&self) -> Result<u64, RepoError> {
+            Ok(0)
+        }
+    }
+
+    #[derive(Default)]
+    struct FakeLetters(Mutex<Vec<DeadLetter>>);
+
+    impl FakeLetters {
+        fn set_state(&self, id: u64, state: DeadLetterState) {
+            self.0.lock().unwrap()[id as usize - 1].state = state;
+        }
+    }
+
+    #[async_trait]
+    impl DeadLetterStore for FakeLetters {
+        async fn dead_letter(
+            &self,
+            consumer: &str,
+            record: &OutboxRecord,
+            attempts: u32,
+            error: &str,
+        ) -> Result<u64, RepoError> {
+            let mut letters = self.0.lock().unwrap();
+            let id = letters.len() as u64 + 1;
+            letters.push(DeadLetter {
+                id,
+                consumer: consumer.to_string(),
+                record: record.clone(),
+                attempts,
+                error: error.to_string(),
+                failed_at: Utc::now(),
+                state: DeadLetterState::Dead,
+                replay_requested_at: None,
+                replayed_at: None,
+            });
+            Ok(id)
+        }
+
+        async fn get_dead_letter(&self, id: u64) -> Result<Option<DeadLetter>, RepoError> {
+            Ok(self.0.lock().unwrap().get(id as usize - 1).cloned())
+        }
+
+        async fn list_dead_letters(
+            &self,
+            _state: Option<DeadLetterState>,
+            _limit: usize,
+            _offset: usize,
+        ) -> Result<Vec<DeadLetter>, RepoError> {
+            Ok(self.0.lock().unwrap().clone())
+        }
+
+        async fn request_replay(&self, id: u64) -> Result<Option<DeadLetter>, RepoError> {
+            self.set_state(id, DeadLetterState::ReplayRequested);
+            self.get_dead_letter(id).await
+        }
+
+        async fn replays_requested(
+            &self,
+            consumer: &str,
+            _limit: usize,
+        ) -> Result<Vec<DeadLetter>, RepoError> {
+            Ok(self
+                .0
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|l| l.consumer == consumer && l.state == DeadLetterState::ReplayRequested)
+                .cloned()
+                .collect())
+        }
+
+        async fn finish_replay(
+            &self,
+            id: u64,
+            attempts: u32,
+            error: Option<&str>,
+        ) -> Result<(), RepoError> {
+            let mut letters = self.0.lock().unwrap();
+            let letter = &mut letters[id as usize - 1];
+            letter.attempts += attempts;
+            letter.state = match error {
+                None => DeadLetterState::Replayed,
+                Some(_) => DeadLetterState::Dead,
+            };
+            Ok(())
+        }
+
+        async fn dead_letter_depth(&self) -> Result<Vec<DeadLetterDepth>, RepoError> {
+            Ok(Vec::new())
+        }
+    }
+
+    /// Records what it was sent; fails `poison` for good.
+    struct Recorder {
+        sent: Arc<Mutex<Vec<u64>>>,
+        poison: Option<u64>,
+    }
+
+    #[async_trait]
+    impl Sink for Recorder {
+        fn name(&self) -> &str {
+            "recorder"
+        }
+
+        async fn send(&self, event: &OrderCloudEvent) -> anyhow::Result<()> {
+            let seq = event.id.parse().unwrap();
+            if Some(seq) == self.poison {
+                anyhow::bail!("poisoned");
+            }
+            self.sent.lock().unwrap().push(seq);
+            Ok(())
+        }
+    }
+
+    fn relay(
+        records: Vec<OutboxRecord>,
+        poison: Option<u64>,
+    ) -> (Relay<FakeStore>, Arc<Mutex<Vec<u64>>>, CancellationToken) {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let shutdown = CancellationToken::new();
+        let store = FakeStore {
+            records,
+            ..Default::default()
+        };
+        let relay = Relay::new(
+            Arc::new(store),
+            Recorder {
+                sent: sent.clone(),
+                poison,
+            },
+        );
+        (relay, sent, shutdown)
+    }
+
+    #[tokio::test]
+    async fn relays_each_order_in_sequence_and_checkpoints() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let records = vec![record(1, a), record(2, b), record(3, a), record(4, b)];
+        let (relay, sent, shutdown) = relay(records, None);
+
+        assert_eq!(relay.relay_batch(0, &shutdown).await.unwrap(), 4);
+        assert_eq!(relay.store.checkpoint("recorder").await.unwrap(), 4);
+        assert_eq!(relay.relay_batch(4, &shutdown).await.unwrap(), 4);
+
+        let sent = sent.lock().unwrap().clone();
+        assert_eq!(sent.len(), 4);
+        let position = |seq| sent.iter().position(|s| *s == seq).unwrap();
+        assert!(position(1) < position(3));
+        assert!(position(2) < position(4));
+    }
+
+    #[tokio::test]
+    async fn a_failing_record_holds_back_only_its_own_order() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let records = vec![record(1, a), record(2, a), record(3, b), record(4, a)];
+        let (relay, sent, shutdown) = relay(records, Some(2));
+
+        // 1 goes out right before the first attempt at 2, so once 1 and 3
+        // are out, 2 is being retried; shutdown interrupts that, and 4 waits
+        // behind it.
+        let stop = async {
+            while sent.lock().unwrap().len() < 2 {
+                tokio::task::yield_now().await;
+            }
+            shutdown.cancel();
+        };
+        let (next, ()) = tokio::join!(relay.relay_batch(0, &shutdown), stop);
+        assert_eq!(next.unwrap(), 1);
+        assert_eq!(relay.store.checkpoint("recorder").await.unwrap(), 1);
+        let mut sent = sent.lock().unwrap().clone();
+        sent.sort();
+        assert_eq!(sent, [1, 3]);
+    }
+
+    #[tokio::test]
+    async fn run_resumes_from_the_checkpoint_and_stops_on_shutdown() {
+        let order = Uuid::new_v4();
+        let records = (1..=5).map(|seq| record(seq, order)).collect();
+        let (relay, sent, shutdown) = relay(records, None);
+        relay.store.save_checkpoint("recorder", 2).await.unwrap();
+
+        let running = {
+            let shutdown = shutdown.clone();
+            async move { relay.run(shutdown).await.map(|()| relay) }
+        };
+        let stop = async {
+            while sent.lock().unwrap().len() < 3 {
+                tokio::task::yield_now().await;
+            }
+            shutdown.cancel();
+        };
+        let (relay, ()) = tokio::join!(running, stop);
+
+        assert_eq!(*sent.lock().unwrap(), [3, 4, 5]);
+        assert_eq!(
+            relay.unwrap().store.checkpoint("recorder").await.unwrap(),
+            5
+        );
+    }
+
+    #[tokio::test]
+    async fn a_record_out_of_attempts_is_dead_lettered_and_replayed_on_request() {
+        let order = Uuid::new_v4();
+        let records: Vec<_> = (1..=3).map(|seq| record(seq, order)).collect();
+        let letters = Arc::new(FakeLetters::default());
+        let options = RelayOptions {
+            initial_backoff: Duration::from_millis(1),
+            ..Default::default()
+        };
+        let (failing, sent, shutdown) = relay(records.clone(), Some(2));
+        let failing = failing
+            .with_options(options)
+            .with_dead_letters(letters.clone(), 3);
+
+        // 2 no longer holds back 3.
+        assert_eq!(failing.relay_batch(0, &shutdown).await.unwrap(), 3);
+        assert_eq!(*sent.lock().unwrap(), [1, 3]);
+        let parked = letters.get_dead_letter(1).await.unwrap().unwrap();
+        assert_eq!((parked.record.seq, parked.attempts), (2, 3));
+        assert_eq!(parked.error, "poisoned");
+        // Nothing is replayed until asked for.
+        assert_eq!(failing.replay_dead_letters(&shutdown).await.unwrap(), 0);
+
+        // Still failing: parked again with the extra attempts counted.
+        letters.request_replay(1).await.unwrap();
+        assert_eq!(failing.replay_dead_letters(&shutdown).await.unwrap(), 1);
+        let parked = letters.get_dead_letter(1).await.unwrap().unwrap();
+        assert_eq!((parked.state, parked.attempts), (DeadLetterState::Dead, 6));
+
+        // Once the receiver is back, the replay goes through.
+        let (healed, sent, shutdown) = relay(records, None);
+        let healed = healed.with_dead_letters(letters.clone(), 3);
+        letters.request_replay(1).await.unwrap();
+        assert_eq!(healed.replay_dead_letters(&shutdown).await.unwrap(), 1);
+        assert_eq!(*sent.lock().unwrap(), [2]);
+        let replayed = letters.get_dead_letter(1).await.unwrap().unwrap();
+        assert_eq!(replayed.state, DeadLetterState::Replayed);
+    }
+}
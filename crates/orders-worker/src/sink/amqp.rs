@@ -0,0 +1,172 @@
+use anyhow::Context;
+use async_trait::async_trait;
+use lapin::options::{BasicPublishOptions, ConfirmSelectOptions, ExchangeDeclareOptions};
+use lapin::types::FieldTable;
+use lapin::{
+    BasicProperties, Channel, Confirmation, Connection, ConnectionProperties, ExchangeKind,
+};
+use orders_types::domain::cloudevent::{OrderCloudEvent, CONTENT_TYPE};
+use tokio::sync::Mutex;
+
+use super::Sink;
+
+/// Publishes each event, as a structured-mode CloudEvent, to a RabbitMQ (or any AMQP 0-9-1) exchange and
+/// waits for the broker's publisher confirm, so a record only counts as
+/// delivered once the broker has taken responsibility for it. The message id
+/// is the event id.
+///
+/// Messages are persistent and published `mandatory`: one no queue is bound
+/// for comes back and fails the delivery rather than being dropped. A lost
+/// connection is reopened on the next attempt.
+pub struct AmqpSink {
+    name: String,
+    uri: String,
+    exchange: String,
+    routing_key: String,
+    /// `None` until the first delivery and after a connection failure.
+    channel: Mutex<Option<(Connection, Channel)>>,
+}
+
+impl AmqpSink {
+    /// Publish through the broker at `uri` (`amqp://` or `amqps://`) to the
+    /// durable topic exchange `orders`, declared on connect, with routing
+    /// keys like `order.created`.
+    pub fn new(uri: &str) -> Self {
+        Self {
+            name: "amqp".to_string(),
+            uri: uri.to_string(),
+            exchange: "orders".to_string(),
+            routing_key: "order.{type}".to_string(),
+            channel: Mutex::new(None),
+        }
+    }
+
+    /// Checkpoint name; needed to relay to more than one exchange.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Topic exchange to publish to; declared durable if it doesn't exist.
+    pub fn with_exchange(mut self, exchange: impl Into<String>) -> Self {
+        self.exchange = exchange.into();
+        self
+    }
+
+    /// Routing key per message, with `{type}` replaced by the event type
+    /// (`created`, `updated` or `deleted`) and `{tenant}` by its tenant,
+    /// e.g. `{tenant}.order.{type}`.
+    pub fn with_routing_key(mut self, template: impl Into<String>) -> Self {
+        self.routing_key = template.into();
+        self
+    }
+
+    fn routing_key(&self, event: &OrderCloudEvent) -> String {
+        self.routing_key
+            .replace("{type}", event.data.kind())
+            .replace("{tenant}", event.data.tenant_id().as_str())
+    }
+
+    async fn connect(&self) -> anyhow::Result<(Connection, Channel)> {
+        let connection = Connection::connect(&self.uri, ConnectionProperties::default())
+            .await
+            .context("connecting to the amqp broker")?;
+        let channel = connection.create_channel().await?;
+        channel
+            .exchange_declare(
+                self.exchange.as_str().into(),
+                ExchangeKind::Topic,
+                ExchangeDeclareOptions {
+                    durable: true,
+                    ..Default::default()
+                },
+                FieldTable::default(),
+            )
+            .await
+            .with_context(|| format!("declaring exchange `{}`", self.exchange))?;
+        channel
+            .confirm_select(ConfirmSelectOptions::default())
+            .await
+            .context("enabling publisher confirms")?;
+        tracing::info!(exchange = %self.exchange, "connected to amqp broker");
+        Ok((connection, channel))
+    }
+
+    async fn publish(&self, channel: &Channel, event: &OrderCloudEvent) -> anyhow::Result<()> {
+        let properties = BasicProperties::default()
+            .with_content_type(CONTENT_TYPE.into())
+            // Persistent: survives a broker restart once confirmed.
+            .with_delivery_mode(2)
+            .with_message_id(event.id.as_str().into())
+            .with_timestamp(event.time.timestamp() as u64);
+        let confirm = channel
+            .basic_publish(
+                self.exchange.as_str().into(),
+                self.routing_key(event).as_str().into(),
+                BasicPublishOptions {
+                    mandatory: true,
+                    ..Default::default()
+                },
+                &serde_json::to_vec(event)?,
+                properties,
+            )
+            .await?
+            .await?;
+        match confirm {
+            Confirmation::Ack(None) => Ok(()),
+            Confirmation::Ack(Some(returned)) => anyhow::bail!(
+                "no queue is bound for routing key `{}` ({})",
+                returned.delivery.routing_key,
+                returned.reply_text
+            ),
+            Confirmation::Nack(_) => anyhow::bail!("the broker rejected the message"),
+            Confirmation::NotRequested => anyhow::bail!("publisher confirms are off"),
+        }
+    }
+}
+
+#[async_trait]
+impl Sink for AmqpSink {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn send(&self, event: &OrderCloudEvent) -> anyhow::Result<()> {
+        // One publish at a time: confirms are awaited per message, and the
+        // relay's per-order ordering holds on a single channel.
+        let mut slot = self.channel.lock().await;
+        if slot.is_none() {
+            *slot = Some(self.connect().await?);
+        }
+        let (_, channel) = slot.as_ref().expect("connected above");
+        let res = self.publish(channel, event).await;
+        if res.is_err() && !channel.status().connected() {
+            // Reconnect on the next attempt.
+            *slot = None;
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::event;
+    use orders_types::domain::events::OrderEvent;
+    use orders_types::domain::tenant::TenantId;
+
+    #[test]
+    fn routing_keys_fill_in_type_and_tenant() {
+        let mut event = event(1, uuid::Uuid::new_v4());
+        event.data = OrderEvent::Deleted {
+            id: uuid::Uuid::new_v4(),
+            tenant_id: TenantId::parse("acme").unwrap(),
+        };
+        assert_eq!(
+            AmqpSink::new("amqp://x").routing_key(&event),
+            "order.deleted"
+        );
+        let sink = AmqpSink::new("amqp://x").with_routing_key("{tenant}.orders.{type}");
+        assert_eq!(sink.routing_key(&event), "acme.orders.deleted");
+    }
+}
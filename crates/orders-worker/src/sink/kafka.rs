@@ -0,0 +1,90 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use chrono::Utc;
+use orders_types::domain::cloudevent::{OrderCloudEvent, CONTENT_TYPE};
+use rskafka::client::partition::{Compression, PartitionClient, UnknownTopicHandling};
+use rskafka::client::ClientBuilder;
+use rskafka::record::Record;
+
+use super::Sink;
+
+/// Produces each event to a Kafka topic as a structured-mode CloudEvent,
+/// keyed by order id. All events of one order go to the same partition, so
+/// consumers see them in order.
+pub struct KafkaSink {
+    name: String,
+    /// One per partition of the topic, by partition index.
+    partitions: Vec<PartitionClient>,
+}
+
+impl KafkaSink {
+    /// Connect to `brokers` (`host:port`) and look up the partitions of
+    /// `topic`, which must already exist.
+    pub async fn connect(brokers: Vec<String>, topic: &str) -> anyhow::Result<Self> {
+        let client = ClientBuilder::new(brokers)
+            .client_id("orders-worker")
+            .build()
+            .await
+            .context("connecting to kafka")?;
+        let partitions = client
+            .list_topics()
+            .await
+            .context("listing kafka topics")?
+            .into_iter()
+            .find(|t| t.name == topic)
+            .with_context(|| format!("kafka topic `{topic}` does not exist"))?
+            .partitions;
+        let mut clients = Vec::with_capacity(partitions.len());
+        for partition in partitions {
+            clients.push(
+                client
+                    .partition_client(topic, partition, UnknownTopicHandling::Error)
+                    .await
+                    .with_context(|| format!("opening {topic}/{partition}"))?,
+            );
+        }
+        if clients.is_empty() {
+            anyhow::bail!("kafka topic `{topic}` has no partitions");
+        }
+        Ok(Self {
+            name: "kafka".to_string(),
+            partitions: clients,
+        })
+    }
+
+    /// Checkpoint name; needed to relay to more than one topic.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+}
+
+#[async_trait]
+impl Sink for KafkaSink {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn send(&self, event: &OrderCloudEvent) -> anyhow::Result<()> {
+        let order_id = event.data.order_id();
+        // The same partition for an order on every run and every worker.
+        let partition =
+            &self.partitions[(order_id.as_u128() % self.partitions.len() as u128) as usize];
+        let message = Record {
+            key: Some(order_id.to_string().into_bytes()),
+            value: Some(serde_json::to_vec(event)?),
+            headers: BTreeMap::from([(
+                "content-type".to_string(),
+                CONTENT_TYPE.as_bytes().to_vec(),
+            )]),
+            timestamp: Utc::now(),
+        };
+        partition
+            .produce(vec![message], Compression::NoCompression)
+            .await
+            .with_context(|| format!("producing to {}", partition.topic()))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,25 @@
+//! Where the relay sends outbox records, as CloudEvents. Delivery is at
+//! least once: an event may arrive again after a failure or a restart, with
+//! the same `id`, so receivers should drop ids they have already seen.
+
+use async_trait::async_trait;
+use orders_types::domain::cloudevent::OrderCloudEvent;
+
+#[cfg(feature = "amqp")]
+pub mod amqp;
+#[cfg(feature = "kafka")]
+pub mod kafka;
+pub mod ndjson;
+pub mod projection;
+pub mod webhook;
+
+/// A destination for outbox events.
+#[async_trait]
+pub trait Sink: Send + Sync + 'static {
+    /// Names the sink's checkpoint, so it must stay the same across
+    /// restarts.
+    fn name(&self) -> &str;
+    /// Deliver `event`. An error is retried with backoff; the events of the
+    /// same order after it wait until it succeeds.
+    async fn send(&self, event: &OrderCloudEvent) -> anyhow::Result<()>;
+}
@@ -0,0 +1,69 @@
+use std::io::{self, Write};
+use std::sync::Mutex;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use orders_types::domain::cloudevent::OrderCloudEvent;
+
+use super::Sink;
+
+/// Writes each event as one line of JSON, flushed as it goes, e.g. to pipe
+/// into `jq` or a log shipper.
+pub struct NdjsonSink<W> {
+    name: String,
+    out: Mutex<W>,
+}
+
+impl NdjsonSink<io::Stdout> {
+    pub fn stdout() -> Self {
+        Self::new("stdout", io::stdout())
+    }
+}
+
+impl<W: Write + Send + 'static> NdjsonSink<W> {
+    pub fn new(name: impl Into<String>, out: W) -> Self {
+        Self {
+            name: name.into(),
+            out: Mutex::new(out),
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out.into_inner().expect("ndjson sink poisoned")
+    }
+}
+
+#[async_trait]
+impl<W: Write + Send + 'static> Sink for NdjsonSink<W> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn send(&self, event: &OrderCloudEvent) -> anyhow::Result<()> {
+        let mut line = serde_json::to_vec(event)?;
+        line.push(b'\n');
+        let mut out = self.out.lock().expect("ndjson sink poisoned");
+        out.write_all(&line).context("writing ndjson")?;
+        out.flush().context("flushing ndjson")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::event;
+
+    #[tokio::test]
+    async fn writes_one_line_per_event() {
+        let sink = NdjsonSink::new("test", Vec::new());
+        sink.send(&event(1, uuid::Uuid::new_v4())).await.unwrap();
+        sink.send(&event(2, uuid::Uuid::new_v4())).await.unwrap();
+
+        let out = String::from_utf8(sink.into_inner()).unwrap();
+        let ids: Vec<String> = out
+            .lines()
+            .map(|l| serde_json::from_str::<OrderCloudEvent>(l).unwrap().id)
+            .collect();
+        assert_eq!(ids, ["1", "2"]);
+    }
+}
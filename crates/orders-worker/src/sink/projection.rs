@@ -0,0 +1,72 @@
+use async_trait::async_trait;
+use orders_types::domain::cloudevent::OrderCloudEvent;
+use orders_types::ports::order_read_repository::OrderProjection;
+
+use super::Sink;
+
+/// Folds each event into the orders read model, the projector of the CQRS
+/// split. The projection ignores stale redeliveries, so at-least-once
+/// delivery leaves every view as the write store has it.
+pub struct ProjectionSink<P> {
+    name: String,
+    projection: P,
+}
+
+impl<P: OrderProjection> ProjectionSink<P> {
+    pub fn new(projection: P) -> Self {
+        Self {
+            name: "projection".to_string(),
+            projection,
+        }
+    }
+
+    /// Checkpoint under `name`, e.g. to feed a second read model.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+}
+
+#[async_trait]
+impl<P: OrderProjection> Sink for ProjectionSink<P> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn send(&self, event: &OrderCloudEvent) -> anyhow::Result<()> {
+        self.projection.project(&event.data).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::testing::event;
+    use orders_types::domain::events::OrderEvent;
+    use orders_types::ports::order_repository::RepoError;
+    use uuid::Uuid;
+
+    #[derive(Clone, Default)]
+    struct Projected(Arc<Mutex<Vec<Uuid>>>);
+
+    #[async_trait]
+    impl OrderProjection for Projected {
+        async fn project(&self, event: &OrderEvent) -> Result<(), RepoError> {
+            self.0.lock().unwrap().push(event.order_id());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn projects_the_event_data() {
+        let projected = Projected::default();
+        let sink = ProjectionSink::new(projected.clone());
+        assert_eq!(sink.name(), "projection");
+        let id = Uuid::new_v4();
+        sink.send(&event(1, id)).await.unwrap();
+        assert_eq!(*projected.0.lock().unwrap(), [id]);
+    }
+}
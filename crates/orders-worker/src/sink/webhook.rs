@@ -0,0 +1,140 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use orders_types::domain::cloudevent::{OrderCloudEvent, CONTENT_TYPE};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+
+use super::Sink;
+
+/// POSTs each event to one URL as a structured-mode CloudEvent; anything but
+/// a `2xx` is a failure and is retried.
+pub struct WebhookSink {
+    name: String,
+    url: String,
+    client: reqwest::Client,
+}
+
+impl WebhookSink {
+    pub fn new(url: &str) -> anyhow::Result<Self> {
+        Self::builder(url).build()
+    }
+
+    pub fn builder(url: &str) -> WebhookSinkBuilder {
+        WebhookSinkBuilder {
+            name: "webhook".to_string(),
+            url: url.to_string(),
+            timeout: Duration::from_secs(10),
+            headers: HeaderMap::new(),
+        }
+    }
+}
+
+pub struct WebhookSinkBuilder {
+    name: String,
+    url: String,
+    timeout: Duration,
+    headers: HeaderMap,
+}
+
+impl WebhookSinkBuilder {
+    /// Checkpoint name; needed to relay to more than one webhook.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Send `name: value` with every delivery, e.g. the receiver's
+    /// credentials.
+    pub fn with_header(mut self, name: &str, value: &str) -> anyhow::Result<Self> {
+        self.headers.insert(
+            HeaderName::from_bytes(name.as_bytes())?,
+            HeaderValue::from_str(value)?,
+        );
+        Ok(self)
+    }
+
+    pub fn build(self) -> anyhow::Result<WebhookSink> {
+        if !(self.url.starts_with("http://") || self.url.starts_with("https://")) {
+            anyhow::bail!("webhook url must be http(s), got `{}`", self.url);
+        }
+        let client = reqwest::Client::builder()
+            .timeout(self.timeout)
+            .default_headers(self.headers)
+            .build()?;
+        Ok(WebhookSink {
+            name: self.name,
+            url: self.url,
+            client,
+        })
+    }
+}
+
+#[async_trait]
+impl Sink for WebhookSink {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn send(&self, event: &OrderCloudEvent) -> anyhow::Result<()> {
+        let res = self
+            .client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, CONTENT_TYPE)
+            .body(serde_json::to_vec(event)?)
+            .send()
+            .await
+            .with_context(|| format!("posting to {}", self.url))?;
+        let status = res.status();
+        if !status.is_success() {
+            anyhow::bail!("{} answered {status}", self.url);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::event;
+    use httpmock::prelude::*;
+
+    #[tokio::test]
+    async fn posts_events_and_fails_on_error_statuses() {
+        let server = MockServer::start();
+        let ok = server.mock(|when, then| {
+            when.method(POST)
+                .path("/hooks")
+                .header("content-type", CONTENT_TYPE)
+                .header("authorization", "Bearer s3cret")
+                .json_body_partial(r#"{"id": "1", "type": "orders.order.deleted"}"#);
+            then.status(204);
+        });
+        let down = server.mock(|when, then| {
+            when.method(POST)
+                .path("/hooks")
+                .json_body_partial(r#"{"id": "2"}"#);
+            then.status(503);
+        });
+        let sink = WebhookSink::builder(&server.url("/hooks"))
+            .with_header("authorization", "Bearer s3cret")
+            .unwrap()
+            .build()
+            .unwrap();
+
+        sink.send(&event(1, uuid::Uuid::new_v4())).await.unwrap();
+        let err = sink
+            .send(&event(2, uuid::Uuid::new_v4()))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("503"), "{err}");
+        ok.assert();
+        down.assert();
+        assert!(WebhookSink::new("ftp://example.com").is_err());
+    }
+}
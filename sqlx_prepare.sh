@@ -0,0 +1,39 @@
+#!/usr/bin/env bash
+
+# Regenerate the sqlx query cache (crates/orders-repo/.sqlx) that lets the
+# checked queries of the sqlite adapter build offline. The queries are
+# described by a scratch database built from the migrations, so run this
+# after changing a query or adding a migration. Needs the sqlite3 CLI.
+# Usage: ./sqlx_prepare.sh          # rewrite the cache
+#        ./sqlx_prepare.sh --check  # fail if the cache is out of date
+
+set -euo pipefail
+
+cd "$(dirname "$0")"
+
+crate=crates/orders-repo
+scratch=$(mktemp -d)
+trap 'rm -rf "$scratch"' EXIT
+
+for migration in "$crate"/migrations/*.sql; do
+    sqlite3 "$scratch/schema.db" < "$migration"
+done
+mkdir "$scratch/cache"
+
+# The macros only run when the crate is rebuilt.
+touch "$crate/src/sqlite.rs"
+SQLX_OFFLINE=false \
+DATABASE_URL="sqlite://$scratch/schema.db" \
+SQLX_OFFLINE_DIR="$scratch/cache" \
+    cargo check --quiet -p orders-repo --features sqlite --lib
+
+if [[ "${1:-}" == "--check" ]]; then
+    if ! diff -r "$crate/.sqlx" "$scratch/cache" > /dev/null; then
+        echo "$crate/.sqlx is out of date; run ./sqlx_prepare.sh" >&2
+        exit 1
+    fi
+else
+    rm -rf "$crate/.sqlx"
+    cp -r "$scratch/cache" "$crate/.sqlx"
+    echo "wrote $(ls "$crate/.sqlx" | wc -l) queries to $crate/.sqlx"
+fi
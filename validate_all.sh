@@ -71,18 +71,23 @@ run_warn() {
 # 1) Code checks
 run_required "cargo check (all targets)" cargo check --all-targets
 run_warn "cargo clippy (all targets, all features)" cargo clippy --all-targets --all-features -- -D warnings
+run_required "sqlx query cache up to date" ./sqlx_prepare.sh --check
 
 # 2) Tests (feature matrix)
 run_required "orders-types tests" cargo test -p orders-types
+run_required "orders-types property tests" cargo test -p orders-types --features proptest
 run_required "orders-repo tests (memory)" cargo test -p orders-repo
 run_required "orders-repo tests (sqlite)" cargo test -p orders-repo --features sqlite
+run_required "orders-repo tests (sqlite + compression)" cargo test -p orders-repo --features sqlite,compression
 run_required "orders-hex tests" cargo test -p orders-hex
 run_required "orders-app tests (sqlite default)" cargo test -p orders-app
 run_required "orders-app tests (memory feature)" cargo test -p orders-app --no-default-features --features memory
+run_required "orders-worker tests" cargo test -p orders-worker
 
 # 3) Build verification
 run_required "release build (sqlite default)" cargo build --release
 run_required "release build (memory feature)" cargo build --release --no-default-features --features memory
+run_required "orders-client wasm32 build" cargo check -p orders-client --target wasm32-unknown-unknown
 
 end_time=$(date +%s)
 duration=$((end_time - start_time))
@@ -106,8 +111,8 @@ if [ ${#warns[@]} -gt 0 ]; then
 fi
 
 echo "Steps run:"
-echo "  • cargo check, clippy (warn only)"
+echo "  • cargo check, clippy (warn only), sqlx query cache"
 echo "  • tests: orders-types, orders-repo (memory/sqlite), orders-hex, orders-app (sqlite/memory)"
-echo "  • release builds: default sqlite, memory feature"
+echo "  • release builds: default sqlite, memory feature; orders-client for wasm32"
 
 print_success "Orders workspace is healthy."